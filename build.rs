@@ -0,0 +1,13 @@
+//! Generates the gRPC service (REQ-API-020) from `proto/`.
+//!
+//! The proto files are parsed with `protox` rather than `protoc` so the
+//! build, including the static musl release, needs no system toolchain.
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-changed=proto");
+    let fds = protox::compile(["phoenix/v1/conversations.proto"], ["proto"])?;
+    tonic_build::configure()
+        .build_client(false)
+        .compile_fds(fds)?;
+    Ok(())
+}
@@ -2,17 +2,45 @@
 //!
 //! REQ-API-001 through REQ-API-010
 
+mod artifact_handlers;
 mod assets;
+mod attachment_handlers;
 pub mod auth;
+mod backup_handlers;
+mod bash_policy_handlers;
+mod browser_session_handlers;
+mod browser_view_handlers;
 mod chains;
+mod duplicate_handlers;
+mod file_edit_handlers;
+mod follow_up_handlers;
 mod git_handlers;
+mod grpc;
 mod handlers;
+mod headless;
 mod lifecycle_handlers;
+mod patch_review_handlers;
+mod provider_handlers;
+mod push_handlers;
+mod rate_limit;
+mod retention;
+mod roots_handlers;
+mod settings_handlers;
+mod shell_handlers;
+mod skill_handlers;
 mod sse;
+mod template_handlers;
+mod timeline_handlers;
 mod types;
 pub(crate) mod wire;
 
+pub use grpc::{spawn_grpc_server, GrpcConfig};
 pub use handlers::create_router;
+pub use headless::{
+    run_headless, send_follow_up, start_task, wait_until_settled, RunArgs, RunReport, RunStatus,
+};
+pub use rate_limit::{RateLimitConfig, RateLimitLayer};
+pub use retention::{spawn_retention_task, RetentionConfig};
 #[allow(unused_imports)] // Public API re-exports
 pub use types::*;
 
@@ -63,6 +91,8 @@ impl AppState {
             credential_helper.clone(),
         ));
         runtime.start_sub_agent_handler().await;
+        runtime.start_lock_heartbeat();
+        runtime.start_follow_up_watchdog();
         let terminals = runtime.terminals.clone();
         // Chain Q&A is constructed last so it can share the same `Database`
         // and `ModelRegistry` handles. Its internal `ChainRuntimeRegistry`
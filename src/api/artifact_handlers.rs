@@ -0,0 +1,83 @@
+//! Files a conversation's tools produced (REQ-BT-034).
+//!
+//! `GET /api/conversations/:id/artifacts` lists them, newest first. Today
+//! that is what the browser downloaded into `downloads/` under the
+//! conversation's working directory. A download still in progress is saved
+//! under its CDP guid and only renamed when it completes, so guid-named
+//! files are left out.
+
+use axum::extract::{Path, State};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use std::path::{Path as FsPath, PathBuf};
+
+use super::handlers::AppError;
+use super::types::{ArtifactEntry, ArtifactsResponse};
+use super::AppState;
+use crate::tools::browser::session::DOWNLOADS_DIR;
+
+pub(super) async fn list_artifacts(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ArtifactsResponse>, AppError> {
+    let conversation = state.db.get_conversation(&id).await?;
+    let root = PathBuf::from(&conversation.cwd);
+    let artifacts = tokio::task::spawn_blocking(move || downloads(&root))
+        .await
+        .map_err(|e| AppError::Internal(format!("Listing artifacts failed: {e}")))?;
+    Ok(Json(ArtifactsResponse { artifacts }))
+}
+
+/// Completed browser downloads under `root`.
+fn downloads(root: &FsPath) -> Vec<ArtifactEntry> {
+    let Ok(entries) = std::fs::read_dir(root.join(DOWNLOADS_DIR)) else {
+        return Vec::new();
+    };
+    let mut artifacts: Vec<ArtifactEntry> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(std::fs::Metadata::is_file)?;
+            let name = entry.file_name().to_str()?.to_string();
+            if uuid::Uuid::parse_str(&name).is_ok() {
+                return None;
+            }
+            Some(ArtifactEntry {
+                path: format!("{DOWNLOADS_DIR}/{name}"),
+                name,
+                kind: "download",
+                size: metadata.len(),
+                modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+            })
+        })
+        .collect();
+    artifacts.sort_by(|a, b| {
+        b.modified
+            .cmp(&a.modified)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    artifacts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_finished_downloads_only() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(downloads(root.path()).is_empty());
+
+        let dir = root.path().join(DOWNLOADS_DIR);
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("report.csv"), "a,b\n1,2\n").unwrap();
+        std::fs::write(dir.join(uuid::Uuid::new_v4().to_string()), "partial").unwrap();
+
+        let artifacts = downloads(root.path());
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].name, "report.csv");
+        assert_eq!(artifacts[0].path, "downloads/report.csv");
+        assert_eq!(artifacts[0].kind, "download");
+        assert_eq!(artifacts[0].size, 8);
+        assert!(artifacts[0].modified.is_some());
+    }
+}
@@ -1,121 +1,226 @@
 //! Embedded static assets for production builds
 //!
 //! In development, falls back to serving from filesystem.
+//!
+//! Every asset carries a strong `ETag` (SHA-256 of its bytes) and honours
+//! `If-None-Match` with `304 Not Modified`. Vite's content-hashed bundles
+//! under `/assets/` never change at a given URL, so they are cached for a
+//! year as `immutable`; everything else (`index.html`, the service worker,
+//! the favicon) must revalidate so a deploy is picked up on the next load.
 
 use axum::{
     body::Body,
-    http::{header, Request, Response, StatusCode},
+    http::{header, HeaderMap, Request, Response, StatusCode},
     response::IntoResponse,
 };
 use rust_embed::Embed;
+use sha2::{Digest, Sha256};
+use std::fmt::Write;
 use std::path::PathBuf;
 
 #[derive(Embed)]
 #[folder = "ui/dist"]
 struct Assets;
 
-/// Serve embedded static files, with filesystem fallback for development
-pub async fn serve_static(req: Request<Body>) -> impl IntoResponse {
-    let path = req.uri().path().trim_start_matches('/');
+/// For content-hashed files: the URL changes whenever the bytes do.
+const CACHE_IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// For files served at a stable URL: cache, but revalidate every use.
+const CACHE_REVALIDATE: &str = "no-cache";
 
-    // Try embedded assets first
+/// Bytes and strong validator for one asset.
+struct Asset {
+    data: Vec<u8>,
+    etag: String,
+}
+
+fn etag_from_hash(hash: &[u8]) -> String {
+    // 128 bits of the digest is plenty to tell builds apart.
+    let mut hex = String::with_capacity(32);
+    for b in hash.iter().take(16) {
+        let _ = write!(hex, "{b:02x}");
+    }
+    format!("\"{hex}\"")
+}
+
+/// Load `path` from the embedded bundle, falling back to `ui/dist` on disk
+/// in development.
+fn load(path: &str) -> Option<Asset> {
     if let Some(content) = Assets::get(path) {
-        let mime = mime_guess::from_path(path).first_or_octet_stream();
-        return Response::builder()
-            .status(StatusCode::OK)
-            .header(header::CONTENT_TYPE, mime.as_ref())
-            .body(Body::from(content.data.to_vec()))
-            .unwrap();
+        return Some(Asset {
+            etag: etag_from_hash(&content.metadata.sha256_hash()),
+            data: content.data.into_owned(),
+        });
     }
+    let data = std::fs::read(PathBuf::from("ui/dist").join(path)).ok()?;
+    Some(Asset {
+        etag: etag_from_hash(&Sha256::digest(&data)),
+        data,
+    })
+}
 
-    // Fallback to filesystem in development
-    let fs_path = PathBuf::from("ui/dist").join(path);
-    if fs_path.exists() {
-        if let Ok(content) = std::fs::read(&fs_path) {
-            let mime = mime_guess::from_path(path).first_or_octet_stream();
-            return Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, mime.as_ref())
-                .body(Body::from(content))
-                .unwrap();
-        }
+/// Whether a file name carries Vite's content hash (`index-B1a2c3D4.js`).
+fn is_content_hashed(path: &str) -> bool {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    let Some((stem, _ext)) = file.rsplit_once('.') else {
+        return false;
+    };
+    let Some((_name, hash)) = stem.rsplit_once('-') else {
+        return false;
+    };
+    hash.len() >= 8
+        && hash.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && hash
+            .chars()
+            .any(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+}
+
+/// `If-None-Match` evaluation (RFC 9110 §13.1.2): weak comparison against a
+/// comma-separated list, or `*`.
+fn not_modified(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+fn asset_response(
+    headers: &HeaderMap,
+    asset: Asset,
+    content_type: &str,
+    cache_control: &str,
+) -> Response<Body> {
+    let builder = Response::builder()
+        .header(header::ETAG, &asset.etag)
+        .header(header::CACHE_CONTROL, cache_control);
+    if not_modified(headers, &asset.etag) {
+        return builder
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+            .unwrap();
     }
+    builder
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Body::from(asset.data))
+        .unwrap()
+}
 
+fn not_found(message: &'static str) -> Response<Body> {
     Response::builder()
         .status(StatusCode::NOT_FOUND)
-        .body(Body::from("Not found"))
+        .body(Body::from(message))
         .unwrap()
 }
 
+/// Serve embedded static files, with filesystem fallback for development
+pub async fn serve_static(req: Request<Body>) -> impl IntoResponse {
+    let path = req.uri().path().trim_start_matches('/');
+    let Some(asset) = load(path) else {
+        return not_found("Not found");
+    };
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+    let cache_control = if is_content_hashed(path) {
+        CACHE_IMMUTABLE
+    } else {
+        CACHE_REVALIDATE
+    };
+    asset_response(req.headers(), asset, mime.as_ref(), cache_control)
+}
+
 /// Serve the favicon (phoenix.svg)
-pub async fn serve_favicon() -> impl IntoResponse {
-    // Try embedded asset first
-    if let Some(content) = Assets::get("phoenix.svg") {
-        return Response::builder()
-            .status(StatusCode::OK)
-            .header(header::CONTENT_TYPE, "image/svg+xml")
-            .header(header::CACHE_CONTROL, "public, max-age=31536000")
-            .body(Body::from(content.data.to_vec()))
-            .unwrap();
+pub async fn serve_favicon(headers: HeaderMap) -> impl IntoResponse {
+    match load("phoenix.svg") {
+        Some(asset) => asset_response(&headers, asset, "image/svg+xml", CACHE_REVALIDATE),
+        None => not_found("Favicon not found"),
     }
+}
 
-    // Fallback to filesystem in development
-    let fs_path = PathBuf::from("ui/dist/phoenix.svg");
-    if fs_path.exists() {
-        if let Ok(content) = std::fs::read(&fs_path) {
-            return Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, "image/svg+xml")
-                .header(header::CACHE_CONTROL, "public, max-age=31536000")
-                .body(Body::from(content))
-                .unwrap();
-        }
+/// Serve the service worker file. Browsers already bypass the HTTP cache for
+/// update checks, but `no-cache` keeps intermediaries honest too.
+pub async fn serve_service_worker(headers: HeaderMap) -> impl IntoResponse {
+    match load("service-worker.js") {
+        Some(asset) => asset_response(&headers, asset, "application/javascript", CACHE_REVALIDATE),
+        None => not_found("Service worker not found"),
     }
+}
 
-    Response::builder()
-        .status(StatusCode::NOT_FOUND)
-        .body(Body::from("Favicon not found"))
-        .unwrap()
+/// Serve index.html (embedded or from filesystem). Always revalidated: it is
+/// the one file that points at the current build's hashed bundles.
+pub fn index_response(headers: &HeaderMap) -> Option<Response<Body>> {
+    let asset = load("index.html")?;
+    Some(asset_response(
+        headers,
+        asset,
+        "text/html; charset=utf-8",
+        CACHE_REVALIDATE,
+    ))
 }
 
-/// Serve the service worker file
-pub async fn serve_service_worker() -> impl IntoResponse {
-    // Try embedded asset first
-    if let Some(content) = Assets::get("service-worker.js") {
-        return Response::builder()
-            .status(StatusCode::OK)
-            .header(header::CONTENT_TYPE, "application/javascript")
-            .header(header::CACHE_CONTROL, "no-cache")
-            .body(Body::from(content.data.to_vec()))
-            .unwrap();
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
 
-    // Fallback to filesystem in development
-    let fs_path = PathBuf::from("ui/dist/service-worker.js");
-    if fs_path.exists() {
-        if let Ok(content) = std::fs::read(&fs_path) {
-            return Response::builder()
-                .status(StatusCode::OK)
-                .header(header::CONTENT_TYPE, "application/javascript")
-                .header(header::CACHE_CONTROL, "no-cache")
-                .body(Body::from(content))
-                .unwrap();
+    fn asset() -> Asset {
+        Asset {
+            data: b"console.log(1)".to_vec(),
+            etag: etag_from_hash(&Sha256::digest(b"console.log(1)")),
         }
     }
 
-    Response::builder()
-        .status(StatusCode::NOT_FOUND)
-        .body(Body::from("Service worker not found"))
-        .unwrap()
-}
+    #[test]
+    fn content_hashed_names_are_detected() {
+        assert!(is_content_hashed("assets/index-B1a2c3D4.js"));
+        assert!(is_content_hashed("assets/vendor-react-Dq3x_9kZ.css"));
+        assert!(!is_content_hashed("phoenix.svg"));
+        assert!(!is_content_hashed("service-worker.js"));
+        assert!(!is_content_hashed("assets/code-review.js"));
+        assert!(!is_content_hashed("assets/noext"));
+    }
 
-/// Get the index.html content (embedded or from filesystem)
-pub fn get_index_html() -> Option<String> {
-    // Try embedded first
-    if let Some(content) = Assets::get("index.html") {
-        return String::from_utf8(content.data.to_vec()).ok();
+    #[test]
+    fn etag_is_quoted_hex() {
+        let tag = asset().etag;
+        assert_eq!(tag.len(), 34);
+        assert!(tag.starts_with('"') && tag.ends_with('"'));
     }
 
-    // Fallback to filesystem
-    std::fs::read_to_string("ui/dist/index.html").ok()
+    #[test]
+    fn matching_if_none_match_returns_304_without_body() {
+        let etag = asset().etag;
+        let candidates = [
+            etag.clone(),
+            format!("W/{etag}"),
+            format!("\"other\", {etag}"),
+            "*".to_string(),
+        ];
+        for value in candidates {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::IF_NONE_MATCH,
+                HeaderValue::from_str(&value).unwrap(),
+            );
+            let resp = asset_response(&headers, asset(), "text/javascript", CACHE_IMMUTABLE);
+            assert_eq!(resp.status(), StatusCode::NOT_MODIFIED, "{value}");
+            assert_eq!(resp.headers()[header::ETAG], etag.as_str());
+            assert!(resp.headers().get(header::CONTENT_TYPE).is_none());
+        }
+    }
+
+    #[test]
+    fn stale_or_missing_validator_returns_full_body() {
+        let mut headers = HeaderMap::new();
+        let resp = asset_response(&headers, asset(), "text/javascript", CACHE_IMMUTABLE);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[header::CACHE_CONTROL], CACHE_IMMUTABLE);
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"stale\""));
+        let resp = asset_response(&headers, asset(), "text/javascript", CACHE_REVALIDATE);
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers()[header::CACHE_CONTROL], CACHE_REVALIDATE);
+    }
 }
@@ -0,0 +1,173 @@
+//! User-uploaded attachments (REQ-API-021).
+//!
+//! `POST /api/conversations/:id/attachments` takes a multipart form with one
+//! or more file parts and writes each file into the conversation's working
+//! directory under `.phoenix/attachments/<id>/<name>`. Living in the
+//! workspace means the agent's file tools read them like any other file, and
+//! a text attachment can be cited inline with the usual `@path` reference
+//! (REQ-IR-001). A `.gitignore` in the attachments directory keeps uploads
+//! out of the user's commits.
+
+use axum::extract::{Multipart, Path, State};
+use axum::Json;
+use std::path::{Path as FsPath, PathBuf};
+
+use super::handlers::AppError;
+use super::types::{AttachmentInfo, AttachmentsResponse};
+use super::AppState;
+
+/// Largest accepted request body; applied to the route as its body limit.
+pub(super) const MAX_UPLOAD_BYTES: usize = 25 * 1024 * 1024;
+/// Workspace-relative directory attachments are stored under.
+const ATTACHMENTS_DIR: &str = ".phoenix/attachments";
+const MAX_NAME_CHARS: usize = 100;
+
+/// Reduce a client-supplied file name to a safe single path component.
+fn sanitize_name(raw: &str) -> String {
+    let base = raw.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_NAME_CHARS)
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.');
+    if cleaned.is_empty() {
+        "attachment".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// Whether `data` can be inlined as an `@` reference (REQ-IR-001 only
+/// expands UTF-8 text).
+fn is_text(data: &[u8]) -> bool {
+    !data.contains(&0) && std::str::from_utf8(data).is_ok()
+}
+
+/// Create the attachments directory with its `.gitignore` on first use.
+fn ensure_attachments_dir(root: &FsPath) -> std::io::Result<PathBuf> {
+    let dir = root.join(ATTACHMENTS_DIR);
+    std::fs::create_dir_all(&dir)?;
+    let ignore = dir.join(".gitignore");
+    if !ignore.exists() {
+        std::fs::write(ignore, "*\n")?;
+    }
+    Ok(dir)
+}
+
+/// Store uploaded files in the conversation workspace (REQ-API-021)
+pub(super) async fn upload_attachments(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Json<AttachmentsResponse>, AppError> {
+    let conversation = state.db.get_conversation(&id).await?;
+    if conversation.state.is_terminal() {
+        return Err(AppError::BadRequest(
+            "Conversation is read-only; attachments cannot be added".to_string(),
+        ));
+    }
+    let root = PathBuf::from(&conversation.cwd);
+    if !root.is_dir() {
+        return Err(AppError::BadRequest(format!(
+            "Working directory no longer exists: {}",
+            conversation.cwd
+        )));
+    }
+    let dir = ensure_attachments_dir(&root).map_err(|e| AppError::Internal(e.to_string()))?;
+
+    let mut attachments = Vec::new();
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Invalid multipart body: {e}")))?
+    {
+        // Non-file form fields are ignored.
+        let Some(file_name) = field.file_name().map(sanitize_name) else {
+            continue;
+        };
+        let media_type = field.content_type().map_or_else(
+            || {
+                mime_guess::from_path(&file_name)
+                    .first_or_octet_stream()
+                    .to_string()
+            },
+            str::to_string,
+        );
+        let data = field
+            .bytes()
+            .await
+            .map_err(|e| AppError::BadRequest(format!("Failed to read {file_name}: {e}")))?;
+
+        let mut attachment_id = uuid::Uuid::new_v4().simple().to_string();
+        attachment_id.truncate(12);
+        let relative = format!("{ATTACHMENTS_DIR}/{attachment_id}/{file_name}");
+        let path = dir.join(&attachment_id).join(&file_name);
+        let write = async {
+            tokio::fs::create_dir_all(dir.join(&attachment_id)).await?;
+            tokio::fs::write(&path, &data).await
+        };
+        write
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to store {file_name}: {e}")))?;
+
+        tracing::info!(conv_id = %id, attachment_id, size = data.len(), "Stored attachment");
+        attachments.push(AttachmentInfo {
+            reference: if is_text(&data) {
+                format!("@{relative}")
+            } else {
+                relative.clone()
+            },
+            attachment_id,
+            name: file_name,
+            path: path.display().to_string(),
+            relative_path: relative,
+            size: data.len() as u64,
+            media_type,
+        });
+    }
+
+    if attachments.is_empty() {
+        return Err(AppError::BadRequest("No files in upload".to_string()));
+    }
+    Ok(Json(AttachmentsResponse { attachments }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_reduced_to_one_safe_component() {
+        assert_eq!(sanitize_name("report.pdf"), "report.pdf");
+        assert_eq!(sanitize_name("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_name("C:\\Users\\me\\notes v2.txt"), "notes_v2.txt");
+        assert_eq!(sanitize_name(".env"), "env");
+        assert_eq!(sanitize_name(".."), "attachment");
+        assert_eq!(sanitize_name(""), "attachment");
+        assert_eq!(sanitize_name(&"a".repeat(300)).len(), MAX_NAME_CHARS);
+    }
+
+    #[test]
+    fn text_detection_rejects_binary() {
+        assert!(is_text(b"hello\nworld"));
+        assert!(is_text("héllo".as_bytes()));
+        assert!(!is_text(b"\x89PNG\r\n\x1a\n\0\0"));
+        assert!(!is_text(&[0xff, 0xfe, 0x41]));
+    }
+
+    #[test]
+    fn attachments_dir_is_git_ignored() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = ensure_attachments_dir(root.path()).unwrap();
+        assert_eq!(dir, root.path().join(".phoenix/attachments"));
+        let ignore = std::fs::read_to_string(dir.join(".gitignore")).unwrap();
+        assert_eq!(ignore, "*\n");
+    }
+}
@@ -16,7 +16,7 @@ use serde::{Deserialize, Serialize};
 use super::AppState;
 
 /// Constant-time string comparison to prevent timing attacks on password checks.
-fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+pub(super) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
     if a.len() != b.len() {
         return false;
     }
@@ -28,7 +28,7 @@ fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
 }
 
 /// Extract the `phoenix-auth` cookie value from a Cookie header.
-fn extract_cookie_value(cookie_header: &str) -> Option<&str> {
+pub(super) fn extract_cookie_value(cookie_header: &str) -> Option<&str> {
     for cookie in cookie_header.split(';') {
         let cookie = cookie.trim();
         if let Some(value) = cookie.strip_prefix("phoenix-auth=") {
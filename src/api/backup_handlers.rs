@@ -0,0 +1,68 @@
+//! Database backup HTTP handlers (REQ-API-015).
+//!
+//! Thin wrappers over `crate::db::backup`. The copy runs on a blocking
+//! thread through its own `SQLite` connection, so the server keeps serving
+//! while a snapshot is taken. Restoring is a startup option
+//! (`--restore-backup <name>`), never an endpoint: swapping the file out
+//! from under a live pool is not safe.
+
+use super::handlers::AppError;
+use super::types::{BackupListResponse, BackupResponse};
+use super::AppState;
+use crate::db::backup::{self, BackupConfig, BackupError};
+
+use axum::extract::State;
+use axum::Json;
+use std::path::PathBuf;
+
+impl From<BackupError> for AppError {
+    fn from(e: BackupError) -> Self {
+        match e {
+            BackupError::InvalidName(_) => AppError::BadRequest(e.to_string()),
+            BackupError::NotFound(_) => AppError::NotFound(e.to_string()),
+            BackupError::Sqlite(_) | BackupError::Io(_) | BackupError::Corrupt(_) => {
+                AppError::Internal(e.to_string())
+            }
+        }
+    }
+}
+
+fn db_path(state: &AppState) -> Result<PathBuf, AppError> {
+    state
+        .db
+        .path()
+        .map(PathBuf::from)
+        .ok_or_else(|| AppError::BadRequest("In-memory database cannot be backed up".into()))
+}
+
+/// `POST /api/admin/backup` — snapshot the database and rotate old backups.
+pub(crate) async fn create_backup(
+    State(state): State<AppState>,
+) -> Result<Json<BackupResponse>, AppError> {
+    let db_path = db_path(&state)?;
+    let config = BackupConfig::from_env(&db_path);
+    let outcome = tokio::task::spawn_blocking(move || backup::create_backup(&db_path, &config))
+        .await
+        .map_err(|e| AppError::Internal(format!("Backup task failed: {e}")))??;
+    tracing::info!(
+        name = %outcome.backup.name,
+        size_bytes = outcome.backup.size_bytes,
+        rotated = outcome.removed.len(),
+        "Database backup written"
+    );
+    Ok(Json(BackupResponse {
+        backup: outcome.backup,
+        removed: outcome.removed,
+    }))
+}
+
+/// `GET /api/admin/backups` — backups available to restore, newest first.
+pub(crate) async fn list_backups(
+    State(state): State<AppState>,
+) -> Result<Json<BackupListResponse>, AppError> {
+    let config = BackupConfig::from_env(&db_path(&state)?);
+    let backups = tokio::task::spawn_blocking(move || backup::list_backups(&config.dir))
+        .await
+        .map_err(|e| AppError::Internal(format!("Backup listing failed: {e}")))??;
+    Ok(Json(BackupListResponse { backups }))
+}
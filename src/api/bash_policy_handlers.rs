@@ -0,0 +1,52 @@
+//! A conversation's own bash allow/deny lists (REQ-BASH-018). They are
+//! checked together with the server's lists from the settings; a deny in
+//! either wins.
+
+use super::handlers::AppError;
+use super::AppState;
+use crate::db::CommandPolicy;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+/// The conversation's lists; empty unless some were set.
+pub(super) async fn get_conversation_bash_policy(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<CommandPolicy>, AppError> {
+    state.db.get_conversation(&id).await?;
+    Ok(Json(state.db.get_conversation_bash_policy(&id).await?))
+}
+
+/// Replace the conversation's lists. Requires the conversation to be idle,
+/// like its shell: the policy is read when the runtime is created.
+pub(super) async fn set_conversation_bash_policy(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<CommandPolicy>,
+) -> Result<Json<CommandPolicy>, AppError> {
+    let conv = state.db.get_conversation(&id).await?;
+    if !conv.state.is_idle() {
+        return Err(AppError::BadRequest(
+            "Conversation must be idle to change its bash policy".to_string(),
+        ));
+    }
+    crate::tools::bash::policy::validate(&req).map_err(AppError::BadRequest)?;
+    state
+        .db
+        .set_conversation_bash_policy(&id, &req, chrono::Utc::now())
+        .await?;
+
+    // Evict the active runtime so it gets recreated with the new policy
+    state.runtime.evict_runtime(&id).await;
+
+    tracing::info!(
+        conv_id = %id,
+        allow = req.allow.len(),
+        deny = req.deny.len(),
+        "Conversation bash policy set"
+    );
+    Ok(Json(req))
+}
@@ -0,0 +1,459 @@
+//! Browser session export (REQ-BT-025).
+//!
+//! `GET /api/conversations/:id/browser-session.zip` bundles the browser tool
+//! calls a conversation made, in the order the agent made them, with the
+//! screenshots they produced. Everything comes from the stored messages: the
+//! tool inputs from the agent's `tool_use` blocks, and the outputs, timings,
+//! and screenshot images from the matching tool results.
+//!
+//! The bundle holds `session.json` with one entry per call, and
+//! `screenshots/NNN.png` for each call that returned an image, numbered by
+//! step. Inputs are kept verbatim so a QA flow the agent walked through can
+//! be reviewed later.
+//!
+//! `POST /api/conversations/:id/browser-session/playwright` goes one step
+//! further and writes the same calls as a Playwright test into the
+//! conversation's working directory (REQ-BT-026).
+
+mod playwright;
+
+use axum::extract::{Path, State};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{Cursor, Write};
+use std::path::{Component, Path as FsPath, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::handlers::AppError;
+use super::types::{ConflictErrorResponse, PlaywrightExportRequest, PlaywrightExportResponse};
+use super::AppState;
+use crate::db::{Message, MessageContent};
+use crate::llm::ContentBlock;
+
+/// Tool names with this prefix drive the shared browser session.
+const BROWSER_TOOL_PREFIX: &str = "browser_";
+/// Workspace-relative directory generated Playwright tests go to by default.
+const PLAYWRIGHT_DIR: &str = "tests/phoenix";
+
+/// One browser tool call as written to `session.json`.
+#[derive(Debug, Serialize)]
+struct BrowserStep {
+    /// 1-based position in the session
+    step: usize,
+    tool: String,
+    input: Value,
+    /// `None` if the call never finished, e.g. the turn was cancelled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+    is_error: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_ms: Option<u64>,
+    /// Path of the screenshot inside the bundle
+    #[serde(skip_serializing_if = "Option::is_none")]
+    screenshot: Option<String>,
+    at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+struct SessionManifest<'a> {
+    conversation_id: &'a str,
+    exported_at: DateTime<Utc>,
+    steps: Vec<&'a BrowserStep>,
+}
+
+/// A step with the decoded PNG behind its `screenshot` path.
+struct RecordedStep {
+    step: BrowserStep,
+    png: Option<Vec<u8>>,
+}
+
+/// The conversation's browser tool calls as a zip bundle.
+pub(super) async fn export_browser_session(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Response, AppError> {
+    state.db.get_conversation(&id).await?;
+    let messages = state.db.get_messages(&id).await?;
+    let steps = collect_steps(&messages);
+    let bundle = write_bundle(&id, &steps, Utc::now())
+        .map_err(|e| AppError::Internal(format!("Failed to build browser session: {e}")))?;
+
+    let disposition = format!("attachment; filename=\"phoenix-browser-session-{id}.zip\"");
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        bundle,
+    )
+        .into_response())
+}
+
+/// Write the conversation's browser tool calls as a Playwright test in its
+/// working directory (REQ-BT-026).
+pub(super) async fn export_playwright_test(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<PlaywrightExportRequest>,
+) -> Result<Json<PlaywrightExportResponse>, AppError> {
+    let conversation = state.db.get_conversation(&id).await?;
+    let root = PathBuf::from(&conversation.cwd);
+    if !root.is_dir() {
+        return Err(AppError::BadRequest(format!(
+            "Working directory no longer exists: {}",
+            conversation.cwd
+        )));
+    }
+    let name = conversation.slug.as_deref().unwrap_or(&conversation.id);
+    let relative = req
+        .path
+        .unwrap_or_else(|| format!("{PLAYWRIGHT_DIR}/{name}.spec.ts"));
+    if !is_workspace_relative(FsPath::new(&relative)) {
+        return Err(AppError::BadRequest(format!(
+            "Test path must be relative to the working directory: {relative}"
+        )));
+    }
+    let path = root.join(&relative);
+    if !resolves_inside(&root, &path) {
+        return Err(AppError::BadRequest(format!(
+            "Test path leads outside the working directory: {relative}"
+        )));
+    }
+    if path.exists() && !req.overwrite {
+        return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+            format!("{relative} already exists"),
+            "file_exists",
+        ))));
+    }
+
+    let messages = state.db.get_messages(&id).await?;
+    let steps: Vec<BrowserStep> = collect_steps(&messages)
+        .into_iter()
+        .map(|recorded| recorded.step)
+        .collect();
+    if steps.is_empty() {
+        return Err(AppError::BadRequest(
+            "Conversation has no browser tool calls".to_string(),
+        ));
+    }
+    let title = conversation.title.as_deref().unwrap_or(name);
+    let script = playwright::generate(title, &id, &steps);
+
+    let write = async {
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(&path, &script.source).await
+    };
+    write
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to write {relative}: {e}")))?;
+
+    tracing::info!(conv_id = %id, path = %relative, steps = steps.len(), "Wrote Playwright test");
+    Ok(Json(PlaywrightExportResponse {
+        path: path.display().to_string(),
+        relative_path: relative,
+        translated: script.translated,
+        skipped: script.skipped,
+    }))
+}
+
+/// Whether `path` stays inside the directory it is joined to.
+fn is_workspace_relative(path: &FsPath) -> bool {
+    path.components().next().is_some()
+        && path.components().all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// Whether `path` stays inside `root` once symlinks are followed. The longest
+/// part of it that exists, the file itself included, must resolve under the
+/// canonical root; whatever is missing is created below that. A dangling
+/// symlink cannot be resolved and fails the check.
+fn resolves_inside(root: &FsPath, path: &FsPath) -> bool {
+    let Ok(root) = std::fs::canonicalize(root) else {
+        return false;
+    };
+    path.ancestors()
+        .find(|p| p.symlink_metadata().is_ok())
+        .and_then(|existing| std::fs::canonicalize(existing).ok())
+        .is_some_and(|resolved| resolved.starts_with(&root))
+}
+
+/// Browser tool calls in `messages`, oldest first, joined to their results.
+fn collect_steps(messages: &[Message]) -> Vec<RecordedStep> {
+    let mut steps: Vec<RecordedStep> = Vec::new();
+    let mut by_tool_use: HashMap<&str, usize> = HashMap::new();
+
+    for message in messages {
+        match &message.content {
+            MessageContent::Agent(blocks) => {
+                for block in blocks {
+                    let ContentBlock::ToolUse { id, name, input } = block else {
+                        continue;
+                    };
+                    if !name.starts_with(BROWSER_TOOL_PREFIX) {
+                        continue;
+                    }
+                    by_tool_use.insert(id.as_str(), steps.len());
+                    steps.push(RecordedStep {
+                        step: BrowserStep {
+                            step: steps.len() + 1,
+                            tool: name.clone(),
+                            input: input.clone(),
+                            output: None,
+                            is_error: false,
+                            duration_ms: None,
+                            screenshot: None,
+                            at: message.created_at,
+                        },
+                        png: None,
+                    });
+                }
+            }
+            MessageContent::Tool(result) => {
+                let Some(&index) = by_tool_use.get(result.tool_use_id.as_str()) else {
+                    continue;
+                };
+                let recorded = &mut steps[index];
+                recorded.step.output = Some(result.content.clone());
+                recorded.step.is_error = result.is_error;
+                let display = message.display_data.as_ref();
+                recorded.step.duration_ms = display
+                    .and_then(|d| d.get("duration_ms"))
+                    .and_then(Value::as_u64);
+                recorded.png = display.and_then(screenshot_png);
+                if recorded.png.is_some() {
+                    recorded.step.screenshot =
+                        Some(format!("screenshots/{:03}.png", recorded.step.step));
+                }
+            }
+            _ => {}
+        }
+    }
+    steps
+}
+
+/// The PNG a `browser_take_screenshot` result carries in its display data.
+fn screenshot_png(display: &Value) -> Option<Vec<u8>> {
+    if display.get("type").and_then(Value::as_str) != Some("image")
+        || display.get("media_type").and_then(Value::as_str) != Some("image/png")
+    {
+        return None;
+    }
+    let data = display.get("data").and_then(Value::as_str)?;
+    base64::engine::general_purpose::STANDARD.decode(data).ok()
+}
+
+/// Zip `steps` into a bundle. Screenshots are stored as-is since PNG is
+/// already compressed.
+fn write_bundle(
+    conversation_id: &str,
+    steps: &[RecordedStep],
+    exported_at: DateTime<Utc>,
+) -> zip::result::ZipResult<Vec<u8>> {
+    let manifest = SessionManifest {
+        conversation_id,
+        exported_at,
+        steps: steps.iter().map(|s| &s.step).collect(),
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(std::io::Error::from)?;
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("session.json", SimpleFileOptions::default())?;
+    zip.write_all(&manifest)?;
+
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for recorded in steps {
+        if let (Some(path), Some(png)) = (&recorded.step.screenshot, &recorded.png) {
+            zip.start_file(path.as_str(), stored)?;
+            zip.write_all(png)?;
+        }
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Read;
+
+    fn at(second: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + second, 0).unwrap()
+    }
+
+    fn message(seq: i64, content: MessageContent, display_data: Option<Value>) -> Message {
+        Message {
+            message_id: format!("msg-{seq}"),
+            conversation_id: "conv-1".to_string(),
+            sequence_id: seq,
+            message_type: content.message_type(),
+            content,
+            display_data,
+            usage_data: None,
+            model: None,
+            created_at: at(seq),
+        }
+    }
+
+    fn tool_use(id: &str, name: &str, input: Value) -> ContentBlock {
+        ContentBlock::ToolUse {
+            id: id.to_string(),
+            name: name.to_string(),
+            input,
+        }
+    }
+
+    fn browser_run() -> Vec<Message> {
+        let png = base64::engine::general_purpose::STANDARD.encode(b"\x89PNG fake");
+        vec![
+            message(
+                1,
+                MessageContent::Agent(vec![
+                    tool_use(
+                        "t1",
+                        "browser_navigate",
+                        json!({"url": "http://localhost:3000"}),
+                    ),
+                    tool_use("t2", "bash", json!({"cmd": "ls"})),
+                ]),
+                None,
+            ),
+            message(2, MessageContent::tool("t1", "Navigated", false), None),
+            message(3, MessageContent::tool("t2", "README.md", false), None),
+            message(
+                4,
+                MessageContent::Agent(vec![tool_use("t3", "browser_take_screenshot", json!({}))]),
+                None,
+            ),
+            message(
+                5,
+                MessageContent::tool("t3", "Screenshot taken", false),
+                Some(json!({
+                    "type": "image",
+                    "media_type": "image/png",
+                    "data": png,
+                    "duration_ms": 120,
+                })),
+            ),
+            message(
+                6,
+                MessageContent::Agent(vec![tool_use(
+                    "t4",
+                    "browser_click",
+                    json!({"selector": "#go"}),
+                )]),
+                None,
+            ),
+        ]
+    }
+
+    #[test]
+    fn steps_follow_browser_calls_in_order() {
+        let steps = collect_steps(&browser_run());
+        let tools: Vec<&str> = steps.iter().map(|s| s.step.tool.as_str()).collect();
+        assert_eq!(
+            tools,
+            [
+                "browser_navigate",
+                "browser_take_screenshot",
+                "browser_click"
+            ]
+        );
+
+        assert_eq!(steps[0].step.output.as_deref(), Some("Navigated"));
+        assert!(steps[0].png.is_none());
+        assert_eq!(steps[1].step.step, 2);
+        assert_eq!(steps[1].step.duration_ms, Some(120));
+        assert_eq!(
+            steps[1].step.screenshot.as_deref(),
+            Some("screenshots/002.png")
+        );
+        assert_eq!(steps[1].png.as_deref(), Some(&b"\x89PNG fake"[..]));
+        // The click never got a result
+        assert!(steps[2].step.output.is_none());
+        assert_eq!(steps[2].step.at, at(6));
+        assert_eq!(steps[2].step.input, json!({"selector": "#go"}));
+    }
+
+    #[test]
+    fn bundle_holds_manifest_and_screenshots() {
+        let steps = collect_steps(&browser_run());
+        let bundle = write_bundle("conv-1", &steps, at(10)).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(bundle)).unwrap();
+        assert_eq!(archive.len(), 2);
+
+        let mut manifest = String::new();
+        archive
+            .by_name("session.json")
+            .unwrap()
+            .read_to_string(&mut manifest)
+            .unwrap();
+        let manifest: Value = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(manifest["conversation_id"], "conv-1");
+        assert_eq!(manifest["steps"].as_array().unwrap().len(), 3);
+        assert_eq!(manifest["steps"][1]["screenshot"], "screenshots/002.png");
+        assert!(manifest["steps"][0].get("screenshot").is_none());
+
+        let mut png = Vec::new();
+        archive
+            .by_name("screenshots/002.png")
+            .unwrap()
+            .read_to_end(&mut png)
+            .unwrap();
+        assert_eq!(png, b"\x89PNG fake");
+    }
+
+    #[test]
+    fn conversation_without_browser_calls_has_empty_bundle() {
+        let messages = vec![message(1, MessageContent::user("hello"), None)];
+        let steps = collect_steps(&messages);
+        assert!(steps.is_empty());
+        let bundle = write_bundle("conv-1", &steps, at(0)).unwrap();
+        let archive = zip::ZipArchive::new(Cursor::new(bundle)).unwrap();
+        assert_eq!(archive.len(), 1);
+    }
+
+    #[test]
+    fn test_paths_stay_in_the_workspace() {
+        assert!(is_workspace_relative(FsPath::new("tests/login.spec.ts")));
+        assert!(!is_workspace_relative(FsPath::new("../login.spec.ts")));
+        assert!(!is_workspace_relative(FsPath::new("tests/../../x.ts")));
+        assert!(!is_workspace_relative(FsPath::new("/tmp/x.spec.ts")));
+        assert!(!is_workspace_relative(FsPath::new("./x.spec.ts")));
+        assert!(!is_workspace_relative(FsPath::new("")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_cannot_lead_out_of_the_workspace() {
+        let workspace = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let root = workspace.path();
+        std::os::unix::fs::symlink(outside.path(), root.join("link")).unwrap();
+        std::os::unix::fs::symlink(outside.path().join("x.ts"), root.join("dangling.ts")).unwrap();
+        std::fs::write(outside.path().join("y.ts"), "").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("y.ts"), root.join("y.ts")).unwrap();
+
+        assert!(resolves_inside(root, &root.join("tests/new/x.spec.ts")));
+        assert!(!resolves_inside(root, &root.join("link/x.spec.ts")));
+        assert!(!resolves_inside(root, &root.join("link/deeper/x.spec.ts")));
+        assert!(!resolves_inside(root, &root.join("dangling.ts")));
+        assert!(!resolves_inside(root, &root.join("y.ts")));
+    }
+
+    #[test]
+    fn only_png_images_become_screenshots() {
+        let jpeg = json!({"type": "image", "media_type": "image/jpeg", "data": ""});
+        assert!(screenshot_png(&jpeg).is_none());
+        assert!(screenshot_png(&json!({"duration_ms": 5})).is_none());
+        let garbled = json!({"type": "image", "media_type": "image/png", "data": "!"});
+        assert!(screenshot_png(&garbled).is_none());
+    }
+}
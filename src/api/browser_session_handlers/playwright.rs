@@ -0,0 +1,265 @@
+//! Playwright test generation from recorded browser steps (REQ-BT-026).
+//!
+//! Each successful browser tool call becomes one Playwright statement.
+//! Calls with no Playwright counterpart (console logs, React helpers) and
+//! calls that failed or never finished are kept as comments, so the script
+//! still reads as the session the agent ran and the gaps are visible.
+
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use super::BrowserStep;
+
+/// A generated test file and how much of the session it covers.
+pub(super) struct Script {
+    pub source: String,
+    /// Steps turned into statements
+    pub translated: usize,
+    /// Steps left as comments
+    pub skipped: usize,
+}
+
+/// Render `steps` as a single Playwright test named `title`.
+pub(super) fn generate(title: &str, conversation_id: &str, steps: &[BrowserStep]) -> Script {
+    let mut body = String::new();
+    let mut translated = 0;
+    let mut skipped = 0;
+    for step in steps {
+        let outcome = match (&step.output, step.is_error) {
+            (Some(_), false) => statement(&step.tool, &step.input),
+            (Some(_), true) => Err("failed"),
+            (None, _) => Err("did not finish"),
+        };
+        match outcome {
+            Ok(line) => {
+                translated += 1;
+                let _ = writeln!(body, "  {line}");
+            }
+            Err(reason) => {
+                skipped += 1;
+                let _ = writeln!(body, "  // step {}: {} {reason}", step.step, step.tool);
+            }
+        }
+    }
+
+    let source = format!(
+        "// Generated by Phoenix from the browser steps of conversation {conversation_id}.\n\
+         // Review before relying on it as a regression test.\n\
+         import {{ test }} from '@playwright/test';\n\
+         \n\
+         test({}, async ({{ page }}) => {{\n\
+         {body}}});\n",
+        js_string(title),
+    );
+    Script {
+        source,
+        translated,
+        skipped,
+    }
+}
+
+/// The Playwright statement for one call, or why there is none.
+fn statement(tool: &str, input: &Value) -> Result<String, &'static str> {
+    let str_field = |name: &str| input.get(name).and_then(Value::as_str);
+    // A call aimed at an iframe (REQ-BT-028) finds it the way the tool did:
+    // by name or by part of its URL.
+    let target = match str_field("frame") {
+        Some(frame) => {
+            let frame = js_string(frame);
+            format!("page.frames().find((f) => f.name() === {frame} || f.url().includes({frame}))!")
+        }
+        None => "page".to_string(),
+    };
+    let selector = || {
+        str_field("selector")
+            .map(|s| format!("{target}.locator({})", js_string(s)))
+            .ok_or("has no selector")
+    };
+
+    let line = match tool {
+        "browser_navigate" => {
+            let url = str_field("url").ok_or("has no url")?;
+            format!("await page.goto({});", js_string(url))
+        }
+        "browser_click" => format!("await {}.click();", selector()?),
+        "browser_type" => {
+            let text = js_string(str_field("text").ok_or("has no text")?);
+            if input.get("clear").and_then(Value::as_bool) == Some(true) {
+                format!("await {}.fill({text});", selector()?)
+            } else {
+                format!("await {}.pressSequentially({text});", selector()?)
+            }
+        }
+        "browser_wait_for_selector" => {
+            let state = if input.get("visible").and_then(Value::as_bool) == Some(true) {
+                "visible"
+            } else {
+                "attached"
+            };
+            format!("await {}.waitFor({{ state: '{state}' }});", selector()?)
+        }
+        "browser_key_press" => {
+            let key = str_field("key").ok_or("has no key")?;
+            let mut chord: Vec<&str> = input
+                .get("modifiers")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .filter_map(modifier)
+                .collect();
+            chord.push(key);
+            format!(
+                "await page.keyboard.press({});",
+                js_string(&chord.join("+"))
+            )
+        }
+        "browser_resize" => {
+            let width = input.get("width").and_then(Value::as_u64);
+            let height = input.get("height").and_then(Value::as_u64);
+            let (Some(width), Some(height)) = (width, height) else {
+                return Err("has no size");
+            };
+            format!("await page.setViewportSize({{ width: {width}, height: {height} }});")
+        }
+        "browser_eval" => {
+            let expression = str_field("expression").ok_or("has no expression")?;
+            format!("await {target}.evaluate({});", js_string(expression))
+        }
+        "browser_take_screenshot" => match str_field("selector") {
+            Some(_) => format!("await {}.screenshot();", selector()?),
+            None => "await page.screenshot();".to_string(),
+        },
+        _ => return Err("has no Playwright equivalent"),
+    };
+    Ok(line)
+}
+
+/// Playwright's name for a `browser_key_press` modifier.
+fn modifier(name: &str) -> Option<&'static str> {
+    match name.to_lowercase().as_str() {
+        "alt" => Some("Alt"),
+        "ctrl" | "control" => Some("Control"),
+        "meta" | "cmd" | "command" => Some("Meta"),
+        "shift" => Some("Shift"),
+        _ => None,
+    }
+}
+
+/// A JavaScript string literal for `s`. JSON string syntax is valid JS.
+fn js_string(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use serde_json::json;
+
+    fn step(n: usize, tool: &str, input: Value, output: Option<&str>) -> BrowserStep {
+        BrowserStep {
+            step: n,
+            tool: tool.to_string(),
+            input,
+            output: output.map(str::to_string),
+            is_error: false,
+            duration_ms: None,
+            screenshot: None,
+            at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn calls_become_playwright_statements() {
+        let cases = [
+            ("browser_navigate", json!({"url": "http://localhost:3000"})),
+            ("browser_click", json!({"selector": "#save", "wait": true})),
+            (
+                "browser_type",
+                json!({"selector": "input[name=\"q\"]", "text": "hi"}),
+            ),
+            (
+                "browser_type",
+                json!({"selector": "#q", "text": "x", "clear": true}),
+            ),
+            (
+                "browser_wait_for_selector",
+                json!({"selector": ".done", "visible": true}),
+            ),
+            (
+                "browser_key_press",
+                json!({"key": "k", "modifiers": ["ctrl", "shift"]}),
+            ),
+            ("browser_resize", json!({"width": 375, "height": 812})),
+            ("browser_eval", json!({"expression": "document.title"})),
+            ("browser_take_screenshot", json!({})),
+        ];
+        let lines: Vec<String> = cases
+            .iter()
+            .map(|(tool, input)| statement(tool, input).unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                r#"await page.goto("http://localhost:3000");"#,
+                r##"await page.locator("#save").click();"##,
+                r#"await page.locator("input[name=\"q\"]").pressSequentially("hi");"#,
+                r##"await page.locator("#q").fill("x");"##,
+                r#"await page.locator(".done").waitFor({ state: 'visible' });"#,
+                r#"await page.keyboard.press("Control+Shift+k");"#,
+                "await page.setViewportSize({ width: 375, height: 812 });",
+                r#"await page.evaluate("document.title");"#,
+                "await page.screenshot();",
+            ]
+        );
+    }
+
+    #[test]
+    fn frame_calls_find_the_frame_first() {
+        let input = json!({"selector": "#pay", "frame": "checkout"});
+        assert_eq!(
+            statement("browser_click", &input).unwrap(),
+            "await page.frames().find((f) => f.name() === \"checkout\" || \
+             f.url().includes(\"checkout\"))!.locator(\"#pay\").click();"
+        );
+    }
+
+    #[test]
+    fn untranslatable_steps_stay_as_comments() {
+        let mut failed = step(
+            3,
+            "browser_click",
+            json!({"selector": "#gone"}),
+            Some("Not found"),
+        );
+        failed.is_error = true;
+        let steps = [
+            step(
+                1,
+                "browser_navigate",
+                json!({"url": "http://localhost"}),
+                Some("ok"),
+            ),
+            step(2, "browser_recent_console_logs", json!({}), Some("[]")),
+            failed,
+            step(4, "browser_click", json!({"selector": "#go"}), None),
+        ];
+        let script = generate("Check \"login\"", "conv-1", &steps);
+        assert_eq!(script.translated, 1);
+        assert_eq!(script.skipped, 3);
+        let expected = r#"// Generated by Phoenix from the browser steps of conversation conv-1.
+// Review before relying on it as a regression test.
+import { test } from '@playwright/test';
+
+test("Check \"login\"", async ({ page }) => {
+  await page.goto("http://localhost");
+  // step 2: browser_recent_console_logs has no Playwright equivalent
+  // step 3: browser_click failed
+  // step 4: browser_click did not finish
+});
+"#;
+        assert_eq!(script.source, expected);
+    }
+}
@@ -0,0 +1,91 @@
+//! Live view of a conversation's browser (REQ-BT-033).
+//!
+//! `GET /api/conversations/:id/browser/live` upgrades to a WebSocket that
+//! streams the page the agent is driving and accepts input back, so a
+//! developer can watch and take over when the agent gets stuck.
+//!
+//! Text frame protocol (JSON):
+//!   server → client: `{"type": "frame", "data": <base64 JPEG>, "width", "height", ...}`
+//!   client → server: `{"type": "mouse" | "key" | "text", ...}`, see `ViewerInput`
+//!
+//! Only one viewer is attached at a time, and the browser must already be
+//! running: watching never launches one.
+
+use axum::extract::ws::{Message, WebSocket};
+use axum::extract::{Path, State, WebSocketUpgrade};
+use axum::response::IntoResponse;
+use futures::{SinkExt, StreamExt};
+
+use super::handlers::AppError;
+use super::types::ConflictErrorResponse;
+use super::AppState;
+use crate::tools::browser::screencast::{frame_message, Viewer, ViewerInput};
+
+pub(super) async fn browser_live_view(
+    ws: WebSocketUpgrade,
+    Path(id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(session) = state.runtime.browser_sessions().existing_session(&id).await else {
+        return Err(AppError::NotFound(
+            "This conversation has no browser running".to_string(),
+        ));
+    };
+    let Some(viewer) = Viewer::claim(&session).await else {
+        return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+            "Someone is already watching this browser",
+            "browser_view_taken",
+        ))));
+    };
+    Ok(ws.on_upgrade(move |socket| relay(socket, viewer, id)))
+}
+
+/// Forward frames to the socket and input to the page until either side
+/// goes away.
+async fn relay(socket: WebSocket, viewer: Viewer, conv_id: String) {
+    let (mut sender, mut receiver) = socket.split();
+    let mut frames = match viewer.start().await {
+        Ok(frames) => frames,
+        Err(e) => {
+            tracing::warn!(%conv_id, error = %e, "Live view: screencast failed");
+            let _ = sender.send(Message::Close(None)).await;
+            return;
+        }
+    };
+    tracing::info!(%conv_id, "Live view attached");
+
+    loop {
+        tokio::select! {
+            frame = frames.next() => {
+                // The stream ends when the browser session is killed
+                let Some(frame) = frame else { break };
+                if let Err(e) = viewer.ack(&frame).await {
+                    tracing::debug!(%conv_id, error = %e, "Live view: ack failed");
+                }
+                let text = frame_message(&frame).to_string();
+                if sender.send(Message::Text(text)).await.is_err() {
+                    break;
+                }
+            }
+            message = receiver.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let input = match serde_json::from_str::<ViewerInput>(&text) {
+                        Ok(input) => input,
+                        Err(e) => {
+                            tracing::debug!(%conv_id, error = %e, "Live view: bad input");
+                            continue;
+                        }
+                    };
+                    if let Err(e) = viewer.input(input).await {
+                        tracing::warn!(%conv_id, error = %e, "Live view: input failed");
+                    }
+                }
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    viewer.stop().await;
+    tracing::info!(%conv_id, "Live view detached");
+}
@@ -203,7 +203,7 @@ pub async fn set_chain_name(
         .await
         .map_err(|e| match e {
             DbError::ConversationNotFound(_) => AppError::NotFound(format!("chain {root_id}")),
-            other => AppError::Internal(other.to_string()),
+            other => other.into(),
         })?;
 
     let view = build_chain_view(&state, &root_id).await?;
@@ -484,7 +484,7 @@ fn resolve_display_name(root: &Conversation) -> String {
 fn db_to_app(e: DbError) -> AppError {
     match e {
         DbError::ConversationNotFound(id) => AppError::NotFound(id),
-        other => AppError::Internal(other.to_string()),
+        other => other.into(),
     }
 }
 
@@ -494,8 +494,8 @@ fn map_chain_qa_error(e: ChainQaError) -> AppError {
         ChainQaError::Db(DbError::ConversationNotFound(id)) => {
             AppError::NotFound(format!("conversation {id} not found"))
         }
-        ChainQaError::Db(other) => AppError::Internal(other.to_string()),
-        ChainQaError::Llm(msg) => AppError::Internal(format!("LLM error: {msg}")),
+        ChainQaError::Db(other) => other.into(),
+        ChainQaError::Llm(e) => e.into(),
         ChainQaError::NoModelAvailable => AppError::Internal(
             "no mid-tier LLM model is available — chain Q&A is disabled".to_string(),
         ),
@@ -0,0 +1,79 @@
+//! Conversation duplication (REQ-API-025): "same setup, new task". Unlike a
+//! continuation (REQ-BED-030), the copy carries no history or summary and
+//! the source stays usable.
+
+use super::handlers::AppError;
+use super::types::ConversationResponse;
+use super::AppState;
+use crate::db::{Conversation, DbError};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+/// Create an empty conversation with the source's cwd, model and settings.
+pub(super) async fn duplicate_conversation(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ConversationResponse>, AppError> {
+    let source = state.db.get_conversation(&id).await?;
+    check_duplicable(&source).map_err(AppError::BadRequest)?;
+
+    let new_id = uuid::Uuid::new_v4().to_string();
+    let copy = state
+        .db
+        .duplicate_conversation(&id, &new_id)
+        .await
+        .map_err(|e| match e {
+            DbError::ConversationNotFound(msg) => AppError::NotFound(msg),
+            other => AppError::Internal(other.to_string()),
+        })?;
+    tracing::info!(source_id = %id, conv_id = %copy.id, "Conversation duplicated");
+
+    Ok(Json(ConversationResponse {
+        conversation: serde_json::to_value(copy).unwrap_or(serde_json::Value::Null),
+    }))
+}
+
+/// Sub-agents belong to their parent, and a worktree belongs to exactly one
+/// conversation, so neither can be copied.
+fn check_duplicable(source: &Conversation) -> Result<(), String> {
+    if source.parent_conversation_id.is_some() {
+        return Err("Sub-agent conversations cannot be duplicated".to_string());
+    }
+    if source.conv_mode.worktree_path().is_some() {
+        return Err(format!(
+            "{} conversations own their worktree and cannot be duplicated; \
+             start a new conversation in the repository instead",
+            source.conv_mode.label()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{ConvMode, Database, NonEmptyString};
+
+    #[tokio::test]
+    async fn only_worktree_free_top_level_conversations_are_duplicable() {
+        let db = Database::open_in_memory().await.unwrap();
+        let mut conv = db
+            .create_conversation("c1", "c1", "/tmp", true, None, None)
+            .await
+            .unwrap();
+        assert!(check_duplicable(&conv).is_ok());
+
+        conv.conv_mode = ConvMode::Explore {
+            worktree_path: Some(NonEmptyString::new("/repo/.phoenix/worktrees/c1").unwrap()),
+        };
+        let err = check_duplicable(&conv).unwrap_err();
+        assert!(err.contains("worktree"), "{err}");
+
+        conv.conv_mode = ConvMode::Direct;
+        conv.parent_conversation_id = Some("parent".to_string());
+        assert!(check_duplicable(&conv).is_err());
+    }
+}
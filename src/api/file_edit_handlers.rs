@@ -0,0 +1,177 @@
+//! Files the user saves through the files API while a conversation is open
+//! (REQ-FE-011). The write is confined to the conversation's cwd and its
+//! writable roots, and leaves a short note in the conversation so the agent
+//! re-reads the file instead of patching what it saw before.
+
+use super::handlers::AppError;
+use super::types::{WriteFileRequest, WriteFileResponse};
+use super::AppState;
+use crate::db::ConversationRoot;
+use std::path::{Path as FsPath, PathBuf};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+/// Largest file the API writes, matching what it reads.
+const MAX_WRITE_BYTES: usize = 10 * 1024 * 1024;
+
+/// Request body limit: the content plus room for its JSON escaping.
+pub(super) const MAX_WRITE_BODY_BYTES: usize = 2 * MAX_WRITE_BYTES;
+
+/// Write a file on the user's behalf and tell the agent about it.
+pub(super) async fn write_conversation_file(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<WriteFileRequest>,
+) -> Result<Json<WriteFileResponse>, AppError> {
+    let conv = state.db.get_conversation(&id).await?;
+    if req.content.len() > MAX_WRITE_BYTES {
+        return Err(AppError::BadRequest(
+            "File too large (max 10MB)".to_string(),
+        ));
+    }
+    let roots = state.db.list_conversation_roots(&id).await?;
+    let path = resolve_writable(&conv.cwd, &roots, &req.path).map_err(AppError::BadRequest)?;
+    // The browser never lists these, so it does not write them either (REQ-BED-050)
+    if crate::phoenixignore::PhoenixIgnore::path_is_ignored(&path) {
+        return Err(AppError::SandboxViolation(format!(
+            "{} is excluded by {}",
+            path.display(),
+            crate::phoenixignore::FILE_NAME
+        )));
+    }
+    let created = !path.exists();
+    // The note below covers this save; the cwd watcher need not (REQ-BED-059)
+    state.runtime.record_user_write(&path);
+    tokio::fs::write(&path, &req.content)
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Cannot write file: {e}")))?;
+
+    let cwd = std::fs::canonicalize(&conv.cwd).unwrap_or_else(|_| PathBuf::from(&conv.cwd));
+    let shown = path
+        .strip_prefix(&cwd)
+        .unwrap_or(&path)
+        .display()
+        .to_string();
+    tracing::info!(conv_id = %id, path = %shown, created, "User wrote a file");
+
+    // The file is written either way; a note that cannot be recorded only
+    // costs the agent a stale view.
+    let note = modified_note(&shown, created);
+    let noted = match state.runtime.note_for_agent(&id, note).await {
+        Ok(noted) => noted,
+        Err(e) => {
+            tracing::warn!(conv_id = %id, error = %e, "Failed to note user file edit");
+            false
+        }
+    };
+    Ok(Json(WriteFileResponse {
+        path: path.display().to_string(),
+        noted,
+    }))
+}
+
+/// What the agent is told about a file the user wrote.
+fn modified_note(path: &str, created: bool) -> String {
+    let verb = if created { "created" } else { "modified" };
+    format!("I {verb} `{path}` myself. Re-read it before changing it.")
+}
+
+/// Resolve `path` (absolute, or relative to `cwd`) and check that it lies
+/// in the cwd or a writable extra root (REQ-BED-049). The parent directory
+/// must exist; symlinks are followed before the check.
+fn resolve_writable(cwd: &str, roots: &[ConversationRoot], path: &str) -> Result<PathBuf, String> {
+    let requested = FsPath::new(path);
+    let joined = if requested.is_absolute() {
+        requested.to_path_buf()
+    } else {
+        FsPath::new(cwd).join(requested)
+    };
+    let (Some(parent), Some(name)) = (joined.parent(), joined.file_name()) else {
+        return Err(format!("Not a file path: {path}"));
+    };
+    let parent = std::fs::canonicalize(parent)
+        .map_err(|e| format!("Cannot resolve {}: {e}", parent.display()))?;
+    let mut target = parent.join(name);
+    if target.is_dir() {
+        return Err(format!("Path is a directory: {path}"));
+    }
+    if target.exists() {
+        target =
+            std::fs::canonicalize(&target).map_err(|e| format!("Cannot resolve {path}: {e}"))?;
+    }
+
+    let cwd = std::fs::canonicalize(cwd).unwrap_or_else(|_| cwd.into());
+    let allowed = target.starts_with(&cwd)
+        || roots
+            .iter()
+            .any(|root| root.writable && target.starts_with(&root.path));
+    if allowed {
+        Ok(target)
+    } else {
+        Err(format!(
+            "{path} is outside the conversation's writable directories"
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn root(path: &FsPath, writable: bool) -> ConversationRoot {
+        ConversationRoot {
+            path: std::fs::canonicalize(path)
+                .unwrap()
+                .to_string_lossy()
+                .to_string(),
+            writable,
+        }
+    }
+
+    #[test]
+    fn writes_stay_in_the_cwd_and_writable_roots() {
+        let cwd = TempDir::new().unwrap();
+        let shared = TempDir::new().unwrap();
+        let docs = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let cwd_str = cwd.path().to_str().unwrap();
+        let roots = [root(shared.path(), true), root(docs.path(), false)];
+
+        let resolved = resolve_writable(cwd_str, &roots, "notes.md").unwrap();
+        let expected = std::fs::canonicalize(cwd.path()).unwrap().join("notes.md");
+        assert_eq!(resolved, expected);
+        let in_root = shared.path().join("lib.rs");
+        assert!(resolve_writable(cwd_str, &roots, in_root.to_str().unwrap()).is_ok());
+
+        for rejected in [
+            docs.path().join("README.md"),
+            outside.path().join("x.rs"),
+            cwd.path().join("missing/x.rs"),
+        ] {
+            let rejected = rejected.to_str().unwrap();
+            assert!(
+                resolve_writable(cwd_str, &roots, rejected).is_err(),
+                "{rejected}"
+            );
+        }
+        assert!(resolve_writable(cwd_str, &roots, "../escape.rs").is_err());
+        assert!(resolve_writable(cwd_str, &roots, ".").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_the_cwd_are_refused() {
+        let cwd = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        let target = outside.path().join("secret.txt");
+        std::fs::write(&target, "x").unwrap();
+        std::os::unix::fs::symlink(&target, cwd.path().join("link.txt")).unwrap();
+
+        let cwd_str = cwd.path().to_str().unwrap();
+        assert!(resolve_writable(cwd_str, &[], "link.txt").is_err());
+    }
+}
@@ -0,0 +1,43 @@
+//! A conversation's reminder for unanswered questions (REQ-BED-056). The
+//! watchdog reads it on every sweep, so it can change at any time.
+
+use super::handlers::AppError;
+use super::AppState;
+use crate::db::FollowUpSettings;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+/// The conversation's reminder, or `null` when none is set.
+pub(super) async fn get_conversation_follow_up(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Option<FollowUpSettings>>, AppError> {
+    state.db.get_conversation(&id).await?;
+    Ok(Json(state.db.get_conversation_follow_up(&id).await?))
+}
+
+/// Set the reminder, or clear it with `null`.
+pub(super) async fn set_conversation_follow_up(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<Option<FollowUpSettings>>,
+) -> Result<Json<Option<FollowUpSettings>>, AppError> {
+    state.db.get_conversation(&id).await?;
+    if let Some(settings) = &req {
+        crate::runtime::follow_up::validate(settings).map_err(AppError::BadRequest)?;
+    }
+    state
+        .db
+        .set_conversation_follow_up(&id, req.as_ref(), chrono::Utc::now())
+        .await?;
+
+    tracing::info!(
+        conv_id = %id,
+        after_hours = req.as_ref().map(|s| s.after_hours),
+        "Conversation follow-up set"
+    );
+    Ok(Json(req))
+}
@@ -344,12 +344,7 @@ pub(crate) async fn get_conversation_diff(
 ) -> Result<Json<ConversationDiffResponse>, AppError> {
     const MAX_DIFF_BYTES: usize = 256 * 1024;
 
-    let conv = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conv = state.runtime.db().get_conversation(&id).await?;
 
     let (worktree_path, base_branch) = match &conv.conv_mode {
         ConvMode::Work {
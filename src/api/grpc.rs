@@ -0,0 +1,294 @@
+//! gRPC API (REQ-API-020)
+//!
+//! A tonic service mirroring the conversation endpoints (create, chat,
+//! cancel, and the event stream) for programmatic consumers and
+//! service-to-service use. Each RPC calls the same handler as its HTTP
+//! route against the same [`AppState`], so validation, idempotency, and
+//! runtime dispatch cannot drift between the two surfaces.
+//!
+//! Off unless `PHOENIX_GRPC_PORT` is set. Served as plaintext HTTP/2 on its
+//! own port; when `PHOENIX_PASSWORD` is set every call must carry
+//! `authorization: Bearer <password>` metadata (REQ-AUTH-001).
+
+use axum::extract::{Path, State};
+use axum::Json;
+use futures::Stream;
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use tonic::{Request, Response, Status};
+
+use super::auth::constant_time_eq;
+use super::handlers::{
+    cancel_conversation, create_conversation, send_chat, subscribe_conversation, AppError,
+};
+use super::sse::{StreamFilter, Subscriber};
+use super::types::{ChatRequest, CreateConversationRequest, ImageAttachment};
+use super::AppState;
+use crate::llm::LlmErrorKind;
+
+#[allow(clippy::all, clippy::pedantic)]
+mod pb {
+    tonic::include_proto!("phoenix.v1");
+}
+
+use pb::conversations_server::{Conversations, ConversationsServer};
+
+/// Where to serve the gRPC API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrpcConfig {
+    pub addr: SocketAddr,
+}
+
+impl GrpcConfig {
+    /// Read `PHOENIX_GRPC_PORT`. Returns `None` when it is unset or invalid.
+    pub fn from_env() -> Option<Self> {
+        Self::from_port(&std::env::var("PHOENIX_GRPC_PORT").ok()?)
+    }
+
+    fn from_port(raw: &str) -> Option<Self> {
+        let Ok(port) = raw.trim().parse::<u16>() else {
+            tracing::warn!(value = %raw, "Ignoring invalid PHOENIX_GRPC_PORT");
+            return None;
+        };
+        Some(Self {
+            addr: SocketAddr::from(([0, 0, 0, 0], port)),
+        })
+    }
+}
+
+/// Serve the gRPC API in the background for the life of the process.
+#[allow(clippy::result_large_err)] // tonic interceptors return `Status`
+pub fn spawn_grpc_server(state: AppState, config: GrpcConfig) {
+    let password = state.password.clone();
+    let service = ConversationService { state };
+    let service = ConversationsServer::with_interceptor(service, move |req| {
+        check_auth(req, password.as_deref())
+    });
+    tokio::spawn(async move {
+        tracing::info!(addr = %config.addr, "gRPC API listening");
+        let server = tonic::transport::Server::builder()
+            .add_service(service)
+            .serve(config.addr);
+        if let Err(e) = server.await {
+            tracing::error!(error = %e, "gRPC server failed");
+        }
+    });
+}
+
+/// Same credential as the HTTP Bearer header; there are no cookies here.
+#[allow(clippy::result_large_err)] // tonic interceptors return `Status`
+fn check_auth(req: Request<()>, password: Option<&str>) -> Result<Request<()>, Status> {
+    let Some(password) = password else {
+        return Ok(req);
+    };
+    let token = req
+        .metadata()
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    match token {
+        Some(token) if constant_time_eq(token.as_bytes(), password.as_bytes()) => Ok(req),
+        _ => Err(Status::unauthenticated("Authentication required")),
+    }
+}
+
+/// HTTP status to gRPC code. Structured conflict and expansion errors keep
+/// their `error_type` as a message prefix so callers can still branch on it.
+impl From<AppError> for Status {
+    fn from(err: AppError) -> Self {
+        match err {
+            AppError::BadRequest(msg) => Status::invalid_argument(msg),
+            AppError::NotFound(msg) => Status::not_found(msg),
+            AppError::Internal(msg) => Status::internal(msg),
+            AppError::Database(e) => Status::unavailable(e.to_string()),
+            AppError::Llm(e) => match e.kind {
+                LlmErrorKind::RateLimit => Status::resource_exhausted(e.to_string()),
+                kind if kind.is_retryable() => Status::unavailable(e.to_string()),
+                _ => Status::failed_precondition(e.to_string()),
+            },
+            AppError::SandboxViolation(msg) => Status::permission_denied(msg),
+            AppError::Conflict(detail) => {
+                Status::failed_precondition(format!("{}: {}", detail.error_type, detail.error))
+            }
+            AppError::UnprocessableEntity(detail) => {
+                Status::invalid_argument(format!("{}: {}", detail.error_type, detail.error))
+            }
+        }
+    }
+}
+
+fn attachments(images: Vec<pb::ImageAttachment>) -> Vec<ImageAttachment> {
+    images
+        .into_iter()
+        .map(|img| ImageAttachment {
+            data: img.data,
+            media_type: img.media_type,
+        })
+        .collect()
+}
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<pb::ConversationEvent, Status>> + Send>>;
+
+struct ConversationService {
+    state: AppState,
+}
+
+#[tonic::async_trait]
+impl Conversations for ConversationService {
+    async fn create_conversation(
+        &self,
+        request: Request<pb::CreateConversationRequest>,
+    ) -> Result<Response<pb::CreateConversationResponse>, Status> {
+        let req = request.into_inner();
+        let Json(created) = create_conversation(
+            State(self.state.clone()),
+            Json(CreateConversationRequest {
+                cwd: req.cwd,
+                model: req.model,
+                text: req.text,
+                message_id: req.message_id,
+                images: attachments(req.images),
+                mode: req.mode,
+                base_branch: req.base_branch,
+                seed_parent_id: None,
+                seed_label: None,
+                template: req.template,
+                disabled_tools: req.disabled_tools,
+            }),
+        )
+        .await?;
+        let conversation = created.conversation;
+        let field = |name: &str| {
+            conversation
+                .get(name)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        Ok(Response::new(pb::CreateConversationResponse {
+            conversation_id: field("id"),
+            slug: field("slug"),
+            conversation_json: conversation.to_string(),
+        }))
+    }
+
+    async fn chat(
+        &self,
+        request: Request<pb::ChatRequest>,
+    ) -> Result<Response<pb::ChatResponse>, Status> {
+        let req = request.into_inner();
+        let Json(sent) = send_chat(
+            State(self.state.clone()),
+            Path(req.conversation_id),
+            Json(ChatRequest {
+                text: req.text,
+                message_id: req.message_id,
+                images: attachments(req.images),
+                user_agent: None,
+                client_id: None,
+                council: Vec::new(),
+            }),
+        )
+        .await?;
+        Ok(Response::new(pb::ChatResponse {
+            queued: sent.queued,
+        }))
+    }
+
+    async fn cancel(
+        &self,
+        request: Request<pb::CancelRequest>,
+    ) -> Result<Response<pb::CancelResponse>, Status> {
+        let req = request.into_inner();
+        let Json(cancelled) =
+            cancel_conversation(State(self.state.clone()), Path(req.conversation_id)).await?;
+        Ok(Response::new(pb::CancelResponse {
+            no_op: cancelled.no_op,
+        }))
+    }
+
+    type StreamEventsStream = EventStream;
+
+    /// Same events, ids, and payloads as the SSE stream. gRPC consumers are
+    /// not browser tabs, so they do not register for presence.
+    async fn stream_events(
+        &self,
+        request: Request<pb::StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        let req = request.into_inner();
+        let groups = (!req.events.is_empty()).then(|| req.events.join(","));
+        let filter =
+            StreamFilter::parse(groups.as_deref(), req.thin).map_err(Status::invalid_argument)?;
+        let opened =
+            subscribe_conversation(&self.state, &req.conversation_id, req.last_event_id).await?;
+        let subscriber = Subscriber::new(
+            req.conversation_id,
+            opened.events,
+            opened.rx,
+            filter,
+            Some(opened.resync),
+            None,
+        );
+        let stream = futures::stream::unfold(subscriber, |mut sub| async move {
+            let frame = sub.next_frame().await?;
+            let event = pb::ConversationEvent {
+                sequence_id: frame.sequence_id,
+                r#type: frame.event_type.to_string(),
+                data_json: frame.data,
+            };
+            Some((Ok(event), sub))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::types::ConflictErrorResponse;
+    use super::*;
+
+    fn with_auth(value: &str) -> Request<()> {
+        let mut req = Request::new(());
+        req.metadata_mut()
+            .insert("authorization", value.parse().unwrap());
+        req
+    }
+
+    #[test]
+    fn auth_is_bypassed_without_password() {
+        assert!(check_auth(Request::new(()), None).is_ok());
+    }
+
+    #[test]
+    fn auth_requires_matching_bearer_token() {
+        assert!(check_auth(with_auth("Bearer secret"), Some("secret")).is_ok());
+
+        for req in [
+            Request::new(()),
+            with_auth("Bearer wrong"),
+            with_auth("secret"),
+        ] {
+            let status = check_auth(req, Some("secret")).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        }
+    }
+
+    #[test]
+    fn app_errors_map_to_grpc_codes() {
+        let status = Status::from(AppError::NotFound("gone".to_string()));
+        assert_eq!(status.code(), tonic::Code::NotFound);
+
+        let conflict = ConflictErrorResponse::new("busy", "agent_busy");
+        let status = Status::from(AppError::Conflict(Box::new(conflict)));
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+        assert_eq!(status.message(), "agent_busy: busy");
+    }
+
+    #[test]
+    fn port_is_parsed_from_env_value() {
+        let config = GrpcConfig::from_port(" 50051 ").unwrap();
+        assert_eq!(config.addr.port(), 50051);
+        assert!(GrpcConfig::from_port("grpc").is_none());
+    }
+}
@@ -2,48 +2,93 @@
 //!
 //! REQ-API-001 through REQ-API-010
 
-use super::assets::{get_index_html, serve_favicon, serve_service_worker, serve_static};
+use super::artifact_handlers::list_artifacts;
+use super::assets::{index_response, serve_favicon, serve_service_worker, serve_static};
+use super::attachment_handlers::{upload_attachments, MAX_UPLOAD_BYTES};
+use super::backup_handlers::{create_backup, list_backups};
+use super::bash_policy_handlers::{get_conversation_bash_policy, set_conversation_bash_policy};
+use super::browser_session_handlers::{export_browser_session, export_playwright_test};
+use super::browser_view_handlers::browser_live_view;
 use super::chains::{
     archive_chain_handler, delete_chain_handler, get_chain, set_chain_name, stream_chain,
     submit_chain_question, unarchive_chain_handler,
 };
+use super::duplicate_handlers::duplicate_conversation;
+use super::file_edit_handlers::{write_conversation_file, MAX_WRITE_BODY_BYTES};
+use super::follow_up_handlers::{get_conversation_follow_up, set_conversation_follow_up};
 use super::git_handlers::{get_conversation_diff, list_git_branches};
 use super::lifecycle_handlers::{
     abandon_task, approve_task, mark_merged, reject_task, task_feedback,
 };
-use super::sse::sse_stream;
+use super::patch_review_handlers::{
+    apply_pending_patch, list_pending_patches, reject_pending_patch, set_patch_review,
+};
+use super::provider_handlers::{delete_provider_key, set_provider_key};
+use super::push_handlers::{
+    get_conversation_push, get_push_key, set_conversation_push, subscribe_push, unsubscribe_push,
+};
+use super::retention::admin_cleanup;
+use super::roots_handlers::{get_conversation_roots, set_conversation_roots};
+use super::settings_handlers::{get_server_settings, set_server_settings};
+use super::shell_handlers::{get_conversation_shell, run_command, set_conversation_shell};
+use super::skill_handlers::{
+    create_library_skill, delete_library_skill, get_library_skill, list_library_skills,
+    update_library_skill,
+};
+use super::sse::{compress_stream, sse_stream, Resync, StreamFilter, PAYLOAD_METRICS};
+use super::template_handlers::{
+    create_template, delete_template, get_template, list_templates, update_template,
+};
+use super::timeline_handlers::get_timeline;
 use super::types::{
-    CancelResponse, ChatRequest, ChatResponse, ConflictErrorResponse, ContinueConversationResponse,
+    AuditLogResponse, CancelResponse, ChatRequest, ChatResponse, CommandEntry, CommandsResponse,
+    ComposerRequest, ComposerResponse, ConflictErrorResponse, ContinueConversationResponse,
     ConversationListResponse, ConversationResponse, ConversationWithMessagesResponse,
-    CreateConversationRequest, CredentialStatusApi, DirectoryEntry, ErrorResponse,
-    ExpansionErrorResponse, FileEntry, FileSearchEntry, FileSearchQuery, FileSearchResponse,
-    GatewayStatusApi, ListDirectoryResponse, ListFilesResponse, MkdirResponse, ModelsResponse,
-    ReadFileResponse, RenameRequest, SkillEntry, SkillsResponse, SuccessResponse,
-    SystemPromptResponse, TaskEntry, TasksResponse, UpgradeModelRequest, ValidateCwdResponse,
+    CreateConversationRequest, CredentialStatusApi, DirectoryEntry, ErrorCode, ErrorResponse,
+    EventsQuery, EventsResponse, ExpansionErrorResponse, FeedbackExportQuery, FileEntry,
+    FileSearchEntry, FileSearchQuery, FileSearchResponse, GatewayStatusApi, ListDirectoryResponse,
+    ListFilesResponse, LlmLogQuery, LlmLogResponse, MessageFeedbackRequest,
+    MessageFeedbackResponse, MkdirResponse, ModelsResponse, PinnedMessagesResponse,
+    ReadFileResponse, RenameRequest, SaveDraftRequest, SetHistoryWindowRequest, SetThinkingRequest,
+    SetToolsRequest, SetVerifyRequest, SkillEntry, SkillsResponse, SteerRequest, StreamMetrics,
+    SuccessResponse, SystemPromptResponse, TaskEntry, TasksResponse, ToolEntry, ToolUsageResponse,
+    ToolsResponse, TouchedFilesResponse, TransitionsQuery, TransitionsResponse,
+    UpgradeModelRequest, UsageCost, UsageGroup, UsageSummaryQuery, UsageSummaryResponse,
+    ValidateCwdResponse,
 };
+use super::wire::EnrichedMessage;
 use super::AppState;
-use crate::db::{ConvMode, ConversationUsage, ImageData, Message, MessageContent, MessageType};
+use crate::db::{
+    AuditQuery, ConvMode, ConversationListQuery, ConversationListStats, ConversationSort,
+    ConversationUsage, DbError, ImageData, Message, MessageContent, MessageType, SortOrder,
+    UsageBreakdownRow, UsageGroupBy, UsageTotals, VerifySettings,
+};
 use crate::git_ops::{
     check_branch_conflict, create_worktree, effective_base_ref, materialize_branch, run_git,
     BranchConflict, GitOpError,
 };
-use crate::llm::{ContentBlock, GatewayStatus};
+use crate::llm::{
+    ContentBlock, GatewayStatus, LlmError, LlmErrorKind, MAX_THINKING_BUDGET, MIN_THINKING_BUDGET,
+};
+use crate::runtime::follow_up;
+use crate::runtime::verify::{DEFAULT_VERIFY_ATTEMPTS, MAX_VERIFY_ATTEMPTS};
 use crate::runtime::SseEvent;
-use crate::state_machine::{check_user_message_acceptable, ConvState, Event, TransitionError};
+use crate::state_machine::replay::{replay, replay_events, EventStep, ReplayReport, ReplayStep};
+use crate::state_machine::{
+    check_tool_cancellable, check_user_message_acceptable, check_user_steer_acceptable, ConvState,
+    Event, TransitionError,
+};
 use crate::terminal::terminal_ws_handler;
 
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
+    extract::{DefaultBodyLimit, Path, Query, State},
+    http::{HeaderMap, StatusCode},
     middleware,
     response::{Html, IntoResponse, Redirect, Response},
-    routing::{get, post},
+    routing::{get, post, put},
     Json, Router,
 };
-use chrono::Datelike;
-use chrono::{Local, Timelike};
-use rand::seq::SliceRandom;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
 use std::path::PathBuf;
@@ -65,7 +110,7 @@ pub fn create_router(state: AppState) -> Router {
         .route("/assets/*path", get(serve_static))
         // Preview: serves files from absolute paths so relative references work
         .route("/preview/*filepath", get(serve_preview_file))
-        // Conversation listing (REQ-API-001)
+        // Conversation listing (REQ-API-001), sorted and filtered (REQ-API-027)
         .route("/api/conversations", get(list_conversations))
         .route(
             "/api/conversations/archived",
@@ -78,11 +123,36 @@ pub fn create_router(state: AppState) -> Router {
         .route("/api/conversations/:id/slug", get(get_conversation_slug))
         // SSE streaming (REQ-API-005)
         .route("/api/conversations/:id/stream", get(stream_conversation))
+        .route(
+            "/api/conversations/:id/messages/:message_id",
+            get(get_message),
+        )
+        // Pinned messages stay in context verbatim (REQ-BED-045)
+        .route(
+            "/api/conversations/:id/messages/:message_id/pin",
+            post(pin_message).delete(unpin_message),
+        )
         // Terminal WebSocket (REQ-TERM-001 through REQ-TERM-014)
         .route("/api/conversations/:id/terminal", get(terminal_ws_handler))
         // User actions (REQ-API-004)
         .route("/api/conversations/:id/chat", post(send_chat))
+        // File uploads into the workspace (REQ-API-021)
+        .route(
+            "/api/conversations/:id/attachments",
+            post(upload_attachments).layer(DefaultBodyLimit::max(MAX_UPLOAD_BYTES)),
+        )
+        // Mid-run guidance without cancelling (REQ-BED-034)
+        .route("/api/conversations/:id/steer", post(steer_conversation))
+        // Multi-tab composer coordination (REQ-API-013)
+        .route("/api/conversations/:id/composer", post(update_composer))
         .route("/api/conversations/:id/cancel", post(cancel_conversation))
+        // Drop one queued tool, keep the rest of the round (REQ-BED-055)
+        .route(
+            "/api/conversations/:id/cancel-tool/:tool_use_id",
+            post(cancel_queued_tool),
+        )
+        // Re-send the last user turn after an error (REQ-BED-035)
+        .route("/api/conversations/:id/retry", post(retry_conversation))
         .route(
             "/api/conversations/:id/trigger-continuation",
             post(trigger_continuation),
@@ -92,12 +162,24 @@ pub fn create_router(state: AppState) -> Router {
             "/api/conversations/:id/continue",
             post(continue_conversation),
         )
+        // Same setup, empty history (REQ-API-025)
+        .route(
+            "/api/conversations/:id/duplicate",
+            post(duplicate_conversation),
+        )
         // Task approval (REQ-BED-028)
         .route("/api/conversations/:id/approve-task", post(approve_task))
         .route("/api/conversations/:id/reject-task", post(reject_task))
         .route("/api/conversations/:id/task-feedback", post(task_feedback))
         // User question response (REQ-AUQ-003)
         .route("/api/conversations/:id/respond", post(respond_to_question))
+        // ask_user answer (REQ-AUQ-009)
+        .route("/api/conversations/:id/answer", post(answer_user_input))
+        // Council answer choice (REQ-BED-058)
+        .route(
+            "/api/conversations/:id/council/choose",
+            post(choose_council_answer),
+        )
         // Task abandon (REQ-PROJ-010)
         .route("/api/conversations/:id/abandon-task", post(abandon_task))
         // Mark as merged (REQ-PROJ-026)
@@ -110,11 +192,81 @@ pub fn create_router(state: AppState) -> Router {
         )
         .route("/api/conversations/:id/delete", post(delete_conversation))
         .route("/api/conversations/:id/rename", post(rename_conversation))
+        // Title regeneration from history (REQ-API-012)
+        .route(
+            "/api/conversations/:id/regenerate-title",
+            post(regenerate_title),
+        )
         // Token usage (Phase 4)
         .route(
             "/api/conversations/:id/usage",
             get(get_conversation_usage_handler),
         )
+        // Input tokens spent on each tool's outputs (REQ-API-031)
+        .route("/api/conversations/:id/usage/by-tool", get(get_tool_usage))
+        // State-machine transition log and replay (REQ-API-017)
+        .route("/api/conversations/:id/transitions", get(get_transitions))
+        .route(
+            "/api/conversations/:id/transitions/replay",
+            get(replay_transitions),
+        )
+        // Event log and replay (REQ-BED-054)
+        .route("/api/conversations/:id/events", get(get_events))
+        .route(
+            "/api/conversations/:id/events/replay",
+            get(replay_logged_events),
+        )
+        // Browser tool calls and screenshots as a zip (REQ-BT-025)
+        .route(
+            "/api/conversations/:id/browser-session.zip",
+            get(export_browser_session),
+        )
+        // ...or as a Playwright test in the working directory (REQ-BT-026)
+        .route(
+            "/api/conversations/:id/browser-session/playwright",
+            post(export_playwright_test),
+        )
+        // Watch and take over the running browser over WebSocket (REQ-BT-033)
+        .route(
+            "/api/conversations/:id/browser/live",
+            get(browser_live_view),
+        )
+        // Files the tools produced, such as browser downloads (REQ-BT-034)
+        .route("/api/conversations/:id/artifacts", get(list_artifacts))
+        // Where the time went, per state (REQ-API-023)
+        .route("/api/conversations/:id/timeline", get(get_timeline))
+        // Files the agent has read or edited (REQ-BED-042)
+        .route("/api/conversations/:id/files", get(get_touched_files))
+        // Raw LLM traffic for debugging (REQ-LLM-017)
+        .route("/api/conversations/:id/llm-log", get(get_llm_log))
+        // Usage summary across conversations (REQ-LLM-010)
+        .route("/api/usage/summary", get(get_usage_summary))
+        // Tool execution audit log (REQ-API-016)
+        .route("/api/audit", get(get_audit_log))
+        // Ratings on agent messages (REQ-API-022)
+        .route(
+            "/api/messages/:id/feedback",
+            post(set_message_feedback).delete(delete_message_feedback),
+        )
+        .route("/api/feedback/export", get(export_feedback))
+        // On-demand retention pass (REQ-API-014)
+        .route("/api/admin/cleanup", post(admin_cleanup))
+        // Server settings, applied without a restart (REQ-API-028)
+        .route(
+            "/api/admin/config",
+            get(get_server_settings).put(set_server_settings),
+        )
+        // LLM provider keys, applied without a restart (REQ-LLM-019)
+        .route("/api/admin/providers", post(set_provider_key))
+        .route(
+            "/api/admin/providers/:provider",
+            axum::routing::delete(delete_provider_key),
+        )
+        // SSE payload totals (REQ-API-030)
+        .route("/api/admin/stream-metrics", get(stream_metrics))
+        // Online database backups (REQ-API-015)
+        .route("/api/admin/backup", post(create_backup))
+        .route("/api/admin/backups", get(list_backups))
         // System prompt inspection
         .route(
             "/api/conversations/:id/system-prompt",
@@ -150,15 +302,46 @@ pub fn create_router(state: AppState) -> Router {
             "/api/conversations/:id/files/search",
             get(search_conversation_files),
         )
+        // Files the user saves, noted for the agent (REQ-FE-011)
+        .route(
+            "/api/conversations/:id/files/write",
+            post(write_conversation_file).layer(DefaultBodyLimit::max(MAX_WRITE_BODY_BYTES)),
+        )
         // Skill discovery for autocomplete (REQ-IR-005)
         .route(
             "/api/conversations/:id/skills",
             get(list_conversation_skills),
         )
+        // Slash-command prompt templates for autocomplete (REQ-IR-009)
+        .route("/api/commands", get(list_commands))
+        // User skill library CRUD (REQ-SK-009)
+        .route(
+            "/api/skills",
+            get(list_library_skills).post(create_library_skill),
+        )
+        .route(
+            "/api/skills/:name",
+            get(get_library_skill)
+                .put(update_library_skill)
+                .delete(delete_library_skill),
+        )
+        // Conversation templates (REQ-API-018)
+        .route("/api/templates", get(list_templates).post(create_template))
+        .route(
+            "/api/templates/:name",
+            get(get_template)
+                .put(update_template)
+                .delete(delete_template),
+        )
         // Task listing
         .route("/api/conversations/:id/tasks", get(list_conversation_tasks))
         // Projects (REQ-PROJ-014)
         .route("/api/projects", get(list_projects))
+        // Post-edit verification (REQ-BED-037)
+        .route(
+            "/api/projects/:id/verify",
+            get(get_project_verify).put(set_project_verify),
+        )
         // Model info (REQ-API-009)
         .route("/api/models", get(list_models))
         // Interactive credential helper (REQ-CREDHELPER-003)
@@ -171,6 +354,67 @@ pub fn create_router(state: AppState) -> Router {
             "/api/conversations/:id/upgrade-model",
             post(upgrade_conversation_model),
         )
+        // Extended thinking budget (REQ-LLM-014)
+        .route(
+            "/api/conversations/:id/thinking",
+            put(set_conversation_thinking),
+        )
+        // History window strategy (REQ-BED-046)
+        .route(
+            "/api/conversations/:id/history-window",
+            put(set_conversation_history_window),
+        )
+        // Server-side composer drafts (REQ-BED-047)
+        .route("/api/conversations/:id/draft", put(save_conversation_draft))
+        // Web Push notifications (REQ-API-024)
+        .route("/api/push/key", get(get_push_key))
+        .route(
+            "/api/push/subscriptions",
+            post(subscribe_push).delete(unsubscribe_push),
+        )
+        .route(
+            "/api/conversations/:id/push",
+            get(get_conversation_push).put(set_conversation_push),
+        )
+        // Extra working directories (REQ-BED-049)
+        .route(
+            "/api/conversations/:id/roots",
+            get(get_conversation_roots).put(set_conversation_roots),
+        )
+        // Shell and profile for the bash tool (REQ-BASH-017)
+        .route(
+            "/api/conversations/:id/shell",
+            get(get_conversation_shell).put(set_conversation_shell),
+        )
+        // Commands the user runs themselves through the bash tool (REQ-BASH-020)
+        .route("/api/conversations/:id/run-command", post(run_command))
+        // Command allow/deny lists for the bash tool (REQ-BASH-018)
+        .route(
+            "/api/conversations/:id/bash-policy",
+            get(get_conversation_bash_policy).put(set_conversation_bash_policy),
+        )
+        // Reminders for unanswered questions (REQ-BED-056)
+        .route(
+            "/api/conversations/:id/follow-up",
+            get(get_conversation_follow_up).put(set_conversation_follow_up),
+        )
+        // Per-conversation tool selection (REQ-BED-039)
+        .route("/api/tools", get(list_tools))
+        .route("/api/conversations/:id/tools", put(set_conversation_tools))
+        // Patch review before apply (REQ-PATCH-010)
+        .route("/api/conversations/:id/patch-review", put(set_patch_review))
+        .route(
+            "/api/conversations/:id/pending-patches",
+            get(list_pending_patches),
+        )
+        .route(
+            "/api/conversations/:id/pending-patches/:tool_use_id/apply",
+            post(apply_pending_patch),
+        )
+        .route(
+            "/api/conversations/:id/pending-patches/:tool_use_id/reject",
+            post(reject_pending_patch),
+        )
         // Per-conversation worktree diff (Work/Branch-mode "View diff" action)
         .route("/api/conversations/:id/diff", get(get_conversation_diff))
         // Git utilities
@@ -368,9 +612,9 @@ async fn conversation_to_json_with_seed(state: &AppState, conv: &crate::db::Conv
 // ============================================================
 
 /// Serve the SPA index.html for all client-side routes
-async fn serve_spa() -> impl IntoResponse {
-    match get_index_html() {
-        Some(content) => Html(content).into_response(),
+async fn serve_spa(headers: HeaderMap) -> impl IntoResponse {
+    match index_response(&headers) {
+        Some(response) => response,
         None => (
             StatusCode::NOT_FOUND,
             Html("<h1>404 - UI not found. Build with: cd ui && npm run build</h1>".to_string()),
@@ -383,17 +627,22 @@ async fn serve_spa() -> impl IntoResponse {
 // Conversation Listing (REQ-API-001)
 // ============================================================
 
+/// Active conversations, sorted and filtered by `query` (REQ-API-027).
 async fn list_conversations(
     State(state): State<AppState>,
+    Query(query): Query<ConversationListQuery>,
 ) -> Result<Json<ConversationListResponse>, AppError> {
-    let conversations = state
-        .runtime
-        .db()
-        .list_conversations()
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let conversations = state.runtime.db().query_conversations(&query).await?;
+    let list_stats = state.db.conversation_list_stats(false).await?;
 
-    let json_convs: Vec<Value> = conversations.iter().map(conversation_to_json).collect();
+    let now = chrono::Utc::now();
+    let mut json_convs: Vec<Value> = conversations
+        .iter()
+        .map(|conv| conversation_list_json(conv, list_stats.get(&conv.id), now))
+        .collect();
+    if query.sort == ConversationSort::Cost {
+        sort_by_cost(&mut json_convs, query.order);
+    }
 
     Ok(Json(ConversationListResponse {
         conversations: json_convs,
@@ -403,42 +652,137 @@ async fn list_conversations(
 async fn list_archived_conversations(
     State(state): State<AppState>,
 ) -> Result<Json<ConversationListResponse>, AppError> {
-    let conversations = state
-        .runtime
-        .db()
-        .list_archived_conversations()
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let conversations = state.runtime.db().list_archived_conversations().await?;
+    let list_stats = state.db.conversation_list_stats(true).await?;
 
-    let json_convs: Vec<Value> = conversations.iter().map(conversation_to_json).collect();
+    let now = chrono::Utc::now();
+    let json_convs: Vec<Value> = conversations
+        .iter()
+        .map(|conv| conversation_list_json(conv, list_stats.get(&conv.id), now))
+        .collect();
 
     Ok(Json(ConversationListResponse {
         conversations: json_convs,
     }))
 }
 
+/// [`conversation_to_json`] plus what a list row shows without fetching
+/// more (REQ-API-026): a preview of the latest message, how long the
+/// conversation has been in its current state, and what it has cost so far,
+/// sub-agents included. Models without known pricing add nothing to the cost.
+/// `needs_input` flags a question still unanswered after its follow-up
+/// reminder went out (REQ-BED-056).
+fn conversation_list_json(
+    conv: &crate::db::Conversation,
+    stats: Option<&ConversationListStats>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Value {
+    let mut val = conversation_to_json(conv);
+    if let Value::Object(ref mut map) = val {
+        let preview = stats.and_then(|s| s.last_message_preview.clone());
+        let cost_usd: f64 = stats
+            .map(|s| &s.usage_by_model)
+            .into_iter()
+            .flatten()
+            .filter_map(|(model, totals)| usage_cost_usd(model, totals))
+            .sum();
+        let state_ms = (now - conv.state_updated_at).num_milliseconds();
+        let reminded_at = stats.and_then(|s| s.reminded_at);
+        let needs_input = follow_up::needs_input(&conv.state, conv.state_updated_at, reminded_at);
+        map.insert("last_message_preview".to_string(), Value::from(preview));
+        map.insert("cost_usd".to_string(), Value::from(cost_usd));
+        map.insert(
+            "state_duration_ms".to_string(),
+            Value::from(u64::try_from(state_ms).unwrap_or(0)),
+        );
+        map.insert("needs_input".to_string(), Value::from(needs_input));
+    }
+    val
+}
+
+/// Order list rows by their `cost_usd`. The sort is stable, so rows of equal
+/// cost keep the database's most-recently-updated-first order.
+fn sort_by_cost(rows: &mut [Value], order: SortOrder) {
+    let cost = |row: &Value| row["cost_usd"].as_f64().unwrap_or(0.0);
+    rows.sort_by(|a, b| match order {
+        SortOrder::Asc => cost(a).total_cmp(&cost(b)),
+        SortOrder::Desc => cost(b).total_cmp(&cost(a)),
+    });
+}
+
 // ============================================================
 // Projects (REQ-PROJ-014)
 // ============================================================
 
 async fn list_projects(State(state): State<AppState>) -> Result<Json<Value>, AppError> {
-    let projects = state
-        .db
-        .list_projects()
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let projects = state.db.list_projects().await?;
 
     Ok(Json(
         serde_json::to_value(projects).unwrap_or(Value::Array(vec![])),
     ))
 }
 
+/// A project's post-edit verify settings (REQ-BED-037); `verify` is null
+/// when none are configured.
+async fn get_project_verify(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, AppError> {
+    state
+        .db
+        .get_project(&id)
+        .await
+        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let settings = state.db.get_verify_settings(&id).await?;
+    Ok(Json(serde_json::json!({ "verify": settings })))
+}
+
+/// Set or clear (blank or null `command`) a project's verify command
+/// (REQ-BED-037). Runtimes read the settings when a turn ends, so the change
+/// applies to running conversations without a restart.
+async fn set_project_verify(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<SetVerifyRequest>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    let max_attempts = req.max_attempts.unwrap_or(DEFAULT_VERIFY_ATTEMPTS);
+    if !(1..=MAX_VERIFY_ATTEMPTS).contains(&max_attempts) {
+        return Err(AppError::BadRequest(format!(
+            "max_attempts must be between 1 and {MAX_VERIFY_ATTEMPTS}"
+        )));
+    }
+    let settings = req
+        .command
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .map(|command| VerifySettings {
+            command,
+            max_attempts,
+        });
+
+    state
+        .db
+        .get_project(&id)
+        .await
+        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    state.db.set_verify_settings(&id, settings.as_ref()).await?;
+
+    tracing::info!(
+        project_id = %id,
+        command = ?settings.as_ref().map(|s| &s.command),
+        max_attempts,
+        "Project verify settings updated"
+    );
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
 // ============================================================
 // Conversation Creation (REQ-API-002)
 // ============================================================
 
 #[allow(clippy::too_many_lines)]
-async fn create_conversation(
+pub(super) async fn create_conversation(
     State(state): State<AppState>,
     Json(req): Json<CreateConversationRequest>,
 ) -> Result<Json<ConversationResponse>, AppError> {
@@ -471,6 +815,18 @@ async fn create_conversation(
         }
     }
 
+    // Conversation template (REQ-API-018). Resolved before any worktree or
+    // title work so an unknown name fails cheaply.
+    let template =
+        match req.template.as_deref() {
+            Some(name) => Some(state.db.get_conversation_template(name).await?.ok_or_else(
+                || AppError::BadRequest(format!("Template '{name}' does not exist")),
+            )?),
+            None => None,
+        };
+
+    let disabled_tools = checked_tool_names(&state, req.disabled_tools.clone()).await?;
+
     // Idempotency check: if message_id already exists, find and return that conversation
     if state
         .db
@@ -524,12 +880,12 @@ async fn create_conversation(
             }
             _ => {
                 tracing::info!("Title generation failed, using random slug");
-                generate_slug()
+                crate::title_generator::random_slug()
             }
         }
     } else {
         tracing::info!("No cheap model available for title generation, using random slug");
-        generate_slug()
+        crate::title_generator::random_slug()
     };
 
     // Detect project from git repo root (REQ-PROJ-001)
@@ -707,31 +1063,38 @@ async fn create_conversation(
     // actually being used (instead of leaving NULL and forcing every
     // consumer to reach for a default).
     //
-    // - Explicit `req.model` always wins.
+    // - Explicit `req.model` always wins, then the template's model
+    //   (REQ-API-018) if the registry still has it.
     // - Explore mode with no explicit model: drop to the cheap model for
     //   the registry's default-provider family (task 08670). Explore is
     //   read-only planning — Haiku-tier is fast enough for the iterative
     //   "think out loud, refine" loop and avoids charging Sonnet rates
     //   for plan iteration. Mirrors the sub-agent path at
     //   `runtime/executor.rs:914`.
-    // - All other modes default to the registry default.
+    // - All other modes use the default model: the operator's setting
+    //   (REQ-API-028) if registered, else the registry default.
     //   Task 08609: a NULL `model` field surfaces as a literal "null" in
     //   tooltips, so we always persist a concrete id.
-    let registry_default = state.llm_registry.default_model_id();
+    let default_model = state.runtime.default_model_id().await;
     let cheap_for_explore = state
         .llm_registry
-        .cheap_model_id_for_provider(registry_default);
-    let resolved_model = req.model.as_deref().map_or_else(
+        .cheap_model_id_for_provider(&default_model);
+    let template_model = template
+        .as_ref()
+        .and_then(|t| t.model.as_deref())
+        .filter(|m| state.llm_registry.get(m).is_some());
+    let resolved_model = req.model.as_deref().or(template_model).map_or_else(
         || {
             if matches!(conv_mode, crate::db::ConvMode::Explore { .. }) {
                 cheap_for_explore
             } else {
-                registry_default.to_string()
+                default_model
             }
         },
         String::from,
     );
-    let conversation = state
+    ensure_model_accepts_images(&state, Some(&resolved_model), req.images.len())?;
+    let mut conversation = state
         .runtime
         .db()
         .create_conversation_with_project(
@@ -747,8 +1110,18 @@ async fn create_conversation(
             req.seed_parent_id.as_deref(),
             req.seed_label.as_deref(),
         )
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+        .await?;
+    if let Some(template) = template {
+        state
+            .db
+            .set_conversation_template(&id, &template.name)
+            .await?;
+        conversation.template = Some(template.name);
+    }
+    if !disabled_tools.is_empty() {
+        state.db.set_disabled_tools(&id, &disabled_tools).await?;
+        conversation.disabled_tools = disabled_tools;
+    }
 
     // REQ-SEED-001: seeded conversations may be created with an empty
     // `text` — the UI will hydrate the input area from localStorage and the
@@ -757,14 +1130,19 @@ async fn create_conversation(
     if !(is_seeded && req.text.trim().is_empty()) {
         // Expand `@file` inline references before sending (REQ-IR-001, REQ-IR-007)
         let working_dir_for_expand = std::path::PathBuf::from(&effective_cwd);
-        let expanded_initial = crate::message_expander::expand(&req.text, &working_dir_for_expand)
-            .map_err(|e| {
-                AppError::UnprocessableEntity(ExpansionErrorResponse {
-                    error: e.to_string(),
-                    error_type: e.error_type().to_string(),
-                    reference: e.reference(),
-                })
-            })?;
+        let templates = load_prompt_templates(&state).await;
+        let expanded_initial = crate::message_expander::expand_with_templates(
+            &req.text,
+            &working_dir_for_expand,
+            &templates,
+        )
+        .map_err(|e| {
+            AppError::UnprocessableEntity(ExpansionErrorResponse {
+                error: e.to_string(),
+                error_type: e.error_type().to_string(),
+                reference: e.reference(),
+            })
+        })?;
 
         // Convert images
         let images: Vec<ImageData> = req
@@ -955,12 +1333,7 @@ async fn get_conversation(
     Path(id): Path<String>,
     Query(query): Query<GetConversationQuery>,
 ) -> Result<Json<ConversationWithMessagesResponse>, AppError> {
-    let conversation = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conversation = state.runtime.db().get_conversation(&id).await?;
 
     let messages = if let Some(after) = query.after_sequence {
         state.runtime.db().get_messages_after(&id, after).await
@@ -995,12 +1368,7 @@ async fn get_conversation_slug(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    let conversation = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conversation = state.runtime.db().get_conversation(&id).await?;
 
     Ok(Json(serde_json::json!({ "slug": conversation.slug })))
 }
@@ -1009,12 +1377,7 @@ async fn get_system_prompt(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<SystemPromptResponse>, AppError> {
-    let conversation = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conversation = state.runtime.db().get_conversation(&id).await?;
 
     let cwd = std::path::PathBuf::from(&conversation.cwd);
     let system_prompt = crate::system_prompt::build_system_prompt(&cwd, false, None);
@@ -1200,7 +1563,19 @@ fn extract_tool_preview(tool_name: &str, input: &serde_json::Value) -> Option<St
             .get("query")
             .and_then(|v| v.as_str())
             .map(|s| truncate_preview(s, 60)),
-        "read_image" => input
+        "archive" => input
+            .get("archive")
+            .and_then(|v| v.as_str())
+            .map(|archive| {
+                let operation = input.get("operation").and_then(|v| v.as_str());
+                let preview = format!("{} {archive}", operation.unwrap_or("list"));
+                truncate_preview(&preview, 60)
+            }),
+        "kill_process" => input
+            .get("pid")
+            .and_then(serde_json::Value::as_i64)
+            .map(|pid| format!("pid {pid}")),
+        "read_image" | "inspect_binary" => input
             .get("path")
             .and_then(|v| v.as_str())
             .map(|s| truncate_preview(s, 60)),
@@ -1247,24 +1622,213 @@ fn truncate_preview(s: &str, max_len: usize) -> String {
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct StreamQuery {
+    /// Per-tab presence id (REQ-API-013). Streams opened without one are not
+    /// registered for presence.
+    client_id: Option<String>,
+    /// Resume point for clients that reopen the stream themselves and so
+    /// cannot set `Last-Event-ID` (REQ-API-005). The header wins if both are
+    /// present.
+    last_event_id: Option<i64>,
+    /// Comma-separated event groups to receive (REQ-API-019). Omitted means
+    /// all events.
+    events: Option<String>,
+    /// Drop message bodies and display data, leaving ids and sequence
+    /// numbers (REQ-API-019).
+    #[serde(default)]
+    thin: bool,
+}
+
+/// `Last-Event-ID` as sent by a browser's own `EventSource` reconnect.
+fn last_event_id_header(headers: &HeaderMap) -> Option<i64> {
+    headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+}
+
+/// Repo root, base branch, and task branch of a Work/Branch conversation —
+/// what the commits-behind/ahead badge compares (REQ-PROJ-011).
+async fn work_git_info(
+    state: &AppState,
+    conversation: &crate::db::Conversation,
+) -> Option<(PathBuf, String, String)> {
+    match &conversation.conv_mode {
+        ConvMode::Work {
+            branch_name,
+            base_branch,
+            ..
+        }
+        | ConvMode::Branch {
+            branch_name,
+            base_branch,
+            ..
+        } if !base_branch.as_str().starts_with("__LEGACY")
+            && !branch_name.as_str().starts_with("__LEGACY") =>
+        {
+            // Resolve repo root from project
+            let project_id = conversation.project_id.as_ref()?;
+            let project = state.db.get_project(project_id).await.ok()?;
+            Some((
+                PathBuf::from(project.canonical_path),
+                base_branch.to_string(),
+                branch_name.to_string(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// `(commits_behind, commits_ahead)` for [`work_git_info`]'s branches.
+async fn git_delta((repo_root, base, task): &(PathBuf, String, String)) -> (u32, u32) {
+    let root1 = repo_root.clone();
+    let base1 = base.clone();
+    let task1 = task.clone();
+    let root2 = repo_root.clone();
+    let base2 = base.clone();
+    let task2 = task.clone();
+    let (behind, ahead) = tokio::join!(
+        tokio::task::spawn_blocking(move || commits_behind(&root1, &base1, &task1)),
+        tokio::task::spawn_blocking(move || commits_ahead(&root2, &base2, &task2)),
+    );
+    (behind.unwrap_or(0), ahead.unwrap_or(0))
+}
+
+/// Derive `project_name` from the project's canonical path (repo root dirname).
+async fn project_name(state: &AppState, conversation: &crate::db::Conversation) -> Option<String> {
+    let project_id = conversation.project_id.as_ref()?;
+    let project = state.db.get_project(project_id).await.ok()?;
+    std::path::Path::new(&project.canonical_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+}
+
+/// Catches up a stream whose broadcast receiver lagged, without closing it.
+struct ConversationResync {
+    state: AppState,
+    conversation_id: String,
+    broadcast_tx: crate::runtime::SseBroadcaster,
+}
+
+#[async_trait::async_trait]
+impl Resync for ConversationResync {
+    async fn resync(
+        &self,
+        last_seen: i64,
+    ) -> Option<(Vec<SseEvent>, tokio::sync::broadcast::Receiver<SseEvent>)> {
+        // Cheap path: the missed events are still in the replay buffer.
+        if let Some(resumed) = self.broadcast_tx.resume(last_seen) {
+            return Some(resumed);
+        }
+        self.snapshot(last_seen).await
+    }
+}
+
+impl ConversationResync {
+    /// A fresh `init` carrying only the messages after `last_seen` (all of
+    /// them for `0`); the client's `init` handler merges them by id.
+    /// Subscribing first means nothing broadcast while the snapshot loads
+    /// is lost.
+    async fn snapshot(
+        &self,
+        last_seen: i64,
+    ) -> Option<(Vec<SseEvent>, tokio::sync::broadcast::Receiver<SseEvent>)> {
+        let rx = self.broadcast_tx.subscribe();
+        let db = self.state.runtime.db();
+        let conversation = db.get_conversation(&self.conversation_id).await.ok()?;
+        let messages = db.get_messages(&self.conversation_id).await.ok()?;
+        let context_window_size = messages
+            .iter()
+            .filter_map(|m| m.usage_data.as_ref())
+            .next_back()
+            .map_or(0, crate::db::UsageData::context_window_used);
+        let breadcrumbs = extract_breadcrumbs(&messages);
+        let git_info = work_git_info(&self.state, &conversation).await;
+        let (commits_behind, commits_ahead) = match &git_info {
+            Some(info) => git_delta(info).await,
+            None => (0, 0),
+        };
+        let seq = self.broadcast_tx.current_seq();
+        let init = SseEvent::Init {
+            sequence_id: seq,
+            conversation: Box::new(enrich_conversation_with_seed(&self.state, &conversation).await),
+            messages: messages
+                .into_iter()
+                .filter(|m| m.sequence_id > last_seen)
+                .collect(),
+            agent_working: conversation.is_agent_working(),
+            display_state: conversation.state.display_state().as_str().to_string(),
+            last_sequence_id: seq,
+            context_window_size,
+            breadcrumbs,
+            commits_behind,
+            commits_ahead,
+            project_name: project_name(&self.state, &conversation).await,
+            draft: db.get_draft(&self.conversation_id).await.ok().flatten(),
+        };
+        Some((vec![init], rx))
+    }
+}
+
+/// A conversation stream opened by [`subscribe_conversation`].
+pub(super) struct ConversationSubscription {
+    /// Delivered before anything from `rx`.
+    pub events: Vec<SseEvent>,
+    pub rx: tokio::sync::broadcast::Receiver<SseEvent>,
+    pub resync: std::sync::Arc<dyn Resync>,
+}
+
+/// Open a conversation stream for a non-SSE consumer (the gRPC API,
+/// REQ-API-020).
+///
+/// With `resume_from` the first events are exactly those missed since that
+/// id, when the replay buffer still holds them; otherwise a full `init`.
+pub(super) async fn subscribe_conversation(
+    state: &AppState,
+    id: &str,
+    resume_from: Option<i64>,
+) -> Result<ConversationSubscription, AppError> {
+    state.db.get_conversation(id).await?;
+    let handle = state
+        .runtime
+        .get_or_create(id)
+        .await
+        .map_err(AppError::Internal)?;
+    let last_sequence_id = state.db.get_last_sequence_id(id).await.unwrap_or(0);
+    handle.broadcast_tx.observe_seq(last_sequence_id);
+
+    let resync = ConversationResync {
+        state: state.clone(),
+        conversation_id: id.to_string(),
+        broadcast_tx: handle.broadcast_tx.clone(),
+    };
+    let opened = match resume_from.and_then(|seen| resync.broadcast_tx.resume(seen)) {
+        Some(resumed) => Some(resumed),
+        None => resync.snapshot(0).await,
+    };
+    let (events, rx) =
+        opened.ok_or_else(|| AppError::Internal(format!("Failed to load conversation {id}")))?;
+    Ok(ConversationSubscription {
+        events,
+        rx,
+        resync: std::sync::Arc::new(resync),
+    })
+}
+
 #[allow(clippy::too_many_lines)]
 async fn stream_conversation(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    Query(query): Query<StreamQuery>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, AppError> {
-    let conversation = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let filter =
+        StreamFilter::parse(query.events.as_deref(), query.thin).map_err(AppError::BadRequest)?;
 
-    let messages = state
-        .runtime
-        .db()
-        .get_messages(&id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let conversation = state.runtime.db().get_conversation(&id).await?;
+
+    let messages = state.runtime.db().get_messages(&id).await?;
 
     let last_sequence_id = state
         .runtime
@@ -1288,80 +1852,54 @@ async fn stream_conversation(
         .get_or_create(&id)
         .await
         .map_err(AppError::Internal)?;
-    let broadcast_rx = handle.broadcast_tx.subscribe();
+
+    // Ensure the broadcaster's counter has at least absorbed the highest
+    // persisted message id before judging a resume point against it.
+    handle.broadcast_tx.observe_seq(last_sequence_id);
+
+    // A client resuming from `Last-Event-ID` gets a message-less `init`
+    // pinned at its resume point, then exactly the events it missed. When
+    // the gap is older than the replay buffer it falls back to a full init.
+    let resume_from = last_event_id_header(&headers).or(query.last_event_id);
+    let resumed = resume_from.and_then(|seen| {
+        let (missed, rx) = handle.broadcast_tx.resume(seen)?;
+        Some((seen, missed, rx))
+    });
+    let (resumed_at, replayed, broadcast_rx) = match resumed {
+        Some((seen, missed, rx)) => (Some(seen), missed, rx),
+        None => (None, Vec::new(), handle.broadcast_tx.subscribe()),
+    };
 
     // Compute initial commits_behind for Work conversations.
     // Extract the git info we need for both the init value and the polling task.
-    let work_git_info = match &conversation.conv_mode {
-        ConvMode::Work {
-            branch_name,
-            base_branch,
-            ..
-        }
-        | ConvMode::Branch {
-            branch_name,
-            base_branch,
-            ..
-        } if !base_branch.as_str().starts_with("__LEGACY")
-            && !branch_name.as_str().starts_with("__LEGACY") =>
-        {
-            // Resolve repo root from project
-            let repo_root = if let Some(ref project_id) = conversation.project_id {
-                state
-                    .db
-                    .get_project(project_id)
-                    .await
-                    .ok()
-                    .map(|p| PathBuf::from(p.canonical_path))
-            } else {
-                None
-            };
-            repo_root.map(|root| (root, base_branch.to_string(), branch_name.to_string()))
-        }
-        _ => None,
+    let work_git_info = work_git_info(&state, &conversation).await;
+    let (initial_commits_behind, initial_commits_ahead) = match &work_git_info {
+        Some(info) => git_delta(info).await,
+        None => (0, 0),
     };
-
-    let (initial_commits_behind, initial_commits_ahead) =
-        if let Some((ref repo_root, ref base, ref task)) = work_git_info {
-            let root1 = repo_root.clone();
-            let base1 = base.clone();
-            let task1 = task.clone();
-            let root2 = repo_root.clone();
-            let base2 = base.clone();
-            let task2 = task.clone();
-            let (behind, ahead) = tokio::join!(
-                tokio::task::spawn_blocking(move || commits_behind(&root1, &base1, &task1)),
-                tokio::task::spawn_blocking(move || commits_ahead(&root2, &base2, &task2)),
-            );
-            (behind.unwrap_or(0), ahead.unwrap_or(0))
-        } else {
-            (0, 0)
-        };
-
-    // Derive project_name from the project's canonical_path (repo root dirname).
-    let project_name = if let Some(ref project_id) = conversation.project_id {
-        state.db.get_project(project_id).await.ok().and_then(|p| {
-            std::path::Path::new(&p.canonical_path)
-                .file_name()
-                .map(|n| n.to_string_lossy().into_owned())
-        })
-    } else {
+    let project_name = project_name(&state, &conversation).await;
+    let draft = state.db.get_draft(&id).await.unwrap_or_else(|e| {
+        tracing::warn!(conv_id = %id, error = %e, "Failed to load draft");
         None
-    };
+    });
 
-    // Ensure the broadcaster's counter has at least absorbed the highest
-    // persisted message id, then take the current tip as the Init's own
-    // sequence_id. Init's `sequence_id` and `last_sequence_id` are the same
-    // number by construction: the snapshot IS the highest fact the client has
-    // seen so far, and it sets the floor for subsequent `applyIfNewer` checks.
-    handle.broadcast_tx.observe_seq(last_sequence_id);
-    let init_seq = handle.broadcast_tx.current_seq();
+    // Take the current tip as the Init's own sequence_id. Init's
+    // `sequence_id` and `last_sequence_id` are the same number by
+    // construction: the snapshot IS the highest fact the client has seen so
+    // far, and it sets the floor for subsequent `applyIfNewer` checks. On
+    // resume that fact is the client's own `Last-Event-ID`, so the replayed
+    // events all land above the floor.
+    let init_seq = resumed_at.unwrap_or_else(|| handle.broadcast_tx.current_seq());
 
     // Create init event with typed data -- serialization deferred to SSE layer
     let init_event = SseEvent::Init {
         sequence_id: init_seq,
         conversation: Box::new(enrich_conversation_with_seed(&state, &conversation).await),
-        messages,
+        messages: if resumed_at.is_some() {
+            Vec::new()
+        } else {
+            messages
+        },
         agent_working: conversation.is_agent_working(),
         display_state: conversation.state.display_state().as_str().to_string(),
         last_sequence_id: init_seq,
@@ -1370,6 +1908,7 @@ async fn stream_conversation(
         commits_behind: initial_commits_behind,
         commits_ahead: initial_commits_ahead,
         project_name,
+        draft,
     };
 
     // Spawn periodic git delta polling for Work conversations (REQ-PROJ-011)
@@ -1421,6 +1960,8 @@ async fn stream_conversation(
                             commits_behind: Some(new_behind),
                             commits_ahead: Some(new_ahead),
                             task_title: None,
+                            slug: None,
+                            title: None,
                         },
                     });
                     // No receivers left -- client disconnected, exit polling loop
@@ -1433,14 +1974,192 @@ async fn stream_conversation(
         });
     }
 
-    Ok(sse_stream(id, init_event, broadcast_rx))
+    // Join after init_seq is allocated so our own `client_joined` orders
+    // after the snapshot and survives the client's `applyIfNewer` guard.
+    let registry = state.runtime.presence();
+    let presence = query
+        .client_id
+        .filter(|c| !c.is_empty())
+        .map(|client_id| registry.join(&id, &client_id, handle.broadcast_tx.clone()));
+
+    let resync: std::sync::Arc<dyn Resync> = std::sync::Arc::new(ConversationResync {
+        state: state.clone(),
+        conversation_id: id.clone(),
+        broadcast_tx: handle.broadcast_tx.clone(),
+    });
+    let stream = sse_stream(
+        id,
+        init_event,
+        replayed,
+        broadcast_rx,
+        presence,
+        filter,
+        Some(resync),
+    );
+    Ok(compress_stream(&headers, stream))
+}
+
+/// Fetch one message in full, for thin-mode streams that carry only ids
+/// (REQ-API-019).
+async fn get_message(
+    State(state): State<AppState>,
+    Path((id, message_id)): Path<(String, String)>,
+) -> Result<Json<EnrichedMessage>, AppError> {
+    let message = state
+        .db
+        .get_message_by_id(&message_id)
+        .await
+        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    if message.conversation_id != id {
+        return Err(AppError::NotFound(format!(
+            "Message not found: {message_id}"
+        )));
+    }
+    Ok(Json(EnrichedMessage::from(message)))
+}
+
+/// Pin a message so rebuilt LLM requests and continuations keep it
+/// verbatim (REQ-BED-045). Returns the conversation's pinned message ids.
+async fn pin_message(
+    State(state): State<AppState>,
+    Path((id, message_id)): Path<(String, String)>,
+) -> Result<Json<PinnedMessagesResponse>, AppError> {
+    use crate::db::MessageContent;
+
+    let message = state
+        .db
+        .get_message_by_id(&message_id)
+        .await
+        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    if message.conversation_id != id {
+        return Err(AppError::NotFound(format!(
+            "Message not found: {message_id}"
+        )));
+    }
+    // Only what the model actually sees can be kept in its context
+    if matches!(
+        message.content,
+        MessageContent::System(_) | MessageContent::Error(_) | MessageContent::Continuation(_)
+    ) {
+        return Err(AppError::BadRequest(format!(
+            "{} messages are not sent to the model and cannot be pinned",
+            message.message_type
+        )));
+    }
+    state
+        .db
+        .pin_message(&id, &message_id, chrono::Utc::now())
+        .await?;
+    pinned_messages_response(&state, &id).await
+}
+
+/// Unpin a message (REQ-BED-045). Unpinning a message that is not pinned
+/// is a 404.
+async fn unpin_message(
+    State(state): State<AppState>,
+    Path((id, message_id)): Path<(String, String)>,
+) -> Result<Json<PinnedMessagesResponse>, AppError> {
+    let removed = state.db.unpin_message(&id, &message_id).await?;
+    if !removed {
+        return Err(AppError::NotFound(format!(
+            "Message not pinned: {message_id}"
+        )));
+    }
+    pinned_messages_response(&state, &id).await
+}
+
+async fn pinned_messages_response(
+    state: &AppState,
+    conversation_id: &str,
+) -> Result<Json<PinnedMessagesResponse>, AppError> {
+    let pinned = state.db.list_pinned_messages(conversation_id).await?;
+    Ok(Json(PinnedMessagesResponse {
+        message_ids: pinned.into_iter().map(|m| m.message_id).collect(),
+    }))
 }
 
 // ============================================================
 // User Actions (REQ-API-004)
 // ============================================================
 
-async fn send_chat(
+/// Claim, renew, or release the composer for one tab (REQ-API-013).
+///
+/// The UI calls this while the user types so other tabs can show an
+/// "another window is typing" indicator and hold their send button. Claiming
+/// while another tab holds an unexpired lease returns 409 `composer_locked`.
+async fn update_composer(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<ComposerRequest>,
+) -> Result<Json<ComposerResponse>, AppError> {
+    if req.client_id.is_empty() {
+        return Err(AppError::BadRequest("client_id is required".to_string()));
+    }
+    let presence = state.runtime.presence();
+    let snapshot = if req.typing {
+        let before = presence.snapshot(&id).composer_holder;
+        let Ok(snapshot) = presence.claim_composer(&id, &req.client_id) else {
+            return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+                "Another window is composing a message in this conversation",
+                "composer_locked",
+            ))));
+        };
+        if before != snapshot.composer_holder {
+            broadcast_composer_change(&state, &id, snapshot.clone()).await;
+        }
+        snapshot
+    } else {
+        match presence.release_composer(&id, &req.client_id) {
+            Some(snapshot) => {
+                broadcast_composer_change(&state, &id, snapshot.clone()).await;
+                snapshot
+            }
+            None => presence.snapshot(&id),
+        }
+    };
+    Ok(Json(ComposerResponse {
+        clients: snapshot.clients,
+        composer_holder: snapshot.composer_holder,
+    }))
+}
+
+/// Push a `composer_changed` event to every stream on the conversation.
+/// No-op when no runtime is live — nobody is listening.
+async fn broadcast_composer_change(
+    state: &AppState,
+    conversation_id: &str,
+    snapshot: crate::runtime::presence::PresenceSnapshot,
+) {
+    if let Some(handle) = state.runtime.try_get_handle(conversation_id).await {
+        let _ = handle
+            .broadcast_tx
+            .send_seq(|seq| SseEvent::ComposerChanged {
+                sequence_id: seq,
+                composer_holder: snapshot.composer_holder,
+            });
+    }
+}
+
+/// Reject new images for a model that cannot view them (REQ-BED-041).
+/// Images already in history are replaced with placeholders instead
+/// (REQ-BED-040), so switching models never breaks an existing conversation.
+fn ensure_model_accepts_images(
+    state: &AppState,
+    model: Option<&str>,
+    image_count: usize,
+) -> Result<(), AppError> {
+    let model = model.map_or_else(|| state.llm_registry.default_model_id(), str::to_string);
+    if image_count == 0 || state.llm_registry.supports_vision(&model) {
+        return Ok(());
+    }
+    Err(AppError::BadRequest(format!(
+        "Model {model} cannot view images; remove the attached images or switch to a \
+         vision-capable model"
+    )))
+}
+
+#[allow(clippy::too_many_lines)]
+pub(super) async fn send_chat(
     State(state): State<AppState>,
     Path(id): Path<String>,
     Json(req): Json<ChatRequest>,
@@ -1461,12 +2180,7 @@ async fn send_chat(
     }
 
     // Expand `@file` inline references before sending to the LLM (REQ-IR-001, REQ-IR-007)
-    let conversation = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conversation = state.runtime.db().get_conversation(&id).await?;
 
     // Fail-fast when the state would reject UserMessage. Without this, the
     // chat POST returns 200, the runtime drops the queued event with only a
@@ -1481,8 +2195,13 @@ async fn send_chat(
             TransitionError::ConversationTerminal => "conversation_terminal",
             TransitionError::AwaitingTaskApproval => "awaiting_task_approval",
             TransitionError::AwaitingUserResponse => "awaiting_user_response",
+            TransitionError::AwaitingPatchReview => "awaiting_patch_review",
+            TransitionError::AwaitingCouncilChoice => "awaiting_council_choice",
+            TransitionError::UnknownCouncilCandidate => "unknown_council_candidate",
             TransitionError::AgentBusy => "agent_busy",
             TransitionError::CancellationInProgress => "cancellation_in_progress",
+            TransitionError::AgentNotRunning => "agent_not_running",
+            TransitionError::ToolNotQueued => "tool_not_queued",
             TransitionError::InvalidTransition { .. } => "invalid_state_for_message",
         };
         tracing::info!(
@@ -1497,14 +2216,50 @@ async fn send_chat(
         ))));
     }
 
+    // Multi-tab coordination (REQ-API-013): refuse while another tab holds
+    // the composer, and drop the same text arriving from a second tab. The
+    // `message_id` check above only dedupes retries from a single tab.
+    if let Some(client_id) = req.client_id.as_deref() {
+        let presence = state.runtime.presence();
+        match presence.check_submission(&id, client_id, &req.text) {
+            Ok(Some(snapshot)) => broadcast_composer_change(&state, &id, snapshot).await,
+            Ok(None) => {}
+            Err(rejection) => {
+                tracing::info!(
+                    conv_id = %id,
+                    client_id,
+                    error_type = rejection.error_type(),
+                    "Chat rejected by multi-client coordination"
+                );
+                return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+                    rejection.to_string(),
+                    rejection.error_type(),
+                ))));
+            }
+        }
+    }
+
+    ensure_model_accepts_images(&state, conversation.model.as_deref(), req.images.len())?;
+    // A council answers only if every member can (REQ-BED-058)
+    if !req.council.is_empty() {
+        crate::runtime::council::validate(&state.llm_registry, &req.council)
+            .map_err(AppError::BadRequest)?;
+        for model in &req.council {
+            ensure_model_accepts_images(&state, Some(model), req.images.len())?;
+        }
+    }
+
     let working_dir = std::path::PathBuf::from(&conversation.cwd);
-    let expanded = crate::message_expander::expand(&req.text, &working_dir).map_err(|e| {
-        AppError::UnprocessableEntity(ExpansionErrorResponse {
-            error: e.to_string(),
-            error_type: e.error_type().to_string(),
-            reference: e.reference(),
-        })
-    })?;
+    let templates = load_prompt_templates(&state).await;
+    let expanded =
+        crate::message_expander::expand_with_templates(&req.text, &working_dir, &templates)
+            .map_err(|e| {
+                AppError::UnprocessableEntity(ExpansionErrorResponse {
+                    error: e.to_string(),
+                    error_type: e.error_type().to_string(),
+                    reference: e.reference(),
+                })
+            })?;
 
     // Convert images
     let images: Vec<ImageData> = req
@@ -1522,13 +2277,25 @@ async fn send_chat(
     // Send event to runtime with message_id and user_agent.
     // `text` carries the `display_text` (stored in DB, shown in history — REQ-IR-006).
     // `llm_text` is the expanded form delivered to the model when present (REQ-IR-001).
-    let event = Event::UserMessage {
-        text: expanded.display_text,
-        llm_text: chat_llm_text,
-        images,
-        message_id: req.message_id,
-        user_agent: req.user_agent,
-        skill_invocation: expanded.skill_invocation,
+    let event = if req.council.is_empty() {
+        Event::UserMessage {
+            text: expanded.display_text,
+            llm_text: chat_llm_text,
+            images,
+            message_id: req.message_id,
+            user_agent: req.user_agent,
+            skill_invocation: expanded.skill_invocation,
+        }
+    } else {
+        Event::UserCouncilMessage {
+            text: expanded.display_text,
+            llm_text: chat_llm_text,
+            images,
+            message_id: req.message_id,
+            user_agent: req.user_agent,
+            skill_invocation: expanded.skill_invocation,
+            models: req.council,
+        }
     };
 
     state
@@ -1537,10 +2304,82 @@ async fn send_chat(
         .await
         .map_err(AppError::BadRequest)?;
 
+    // The draft became this message (REQ-BED-047)
+    if let Err(e) = state.db.clear_draft(&id).await {
+        tracing::warn!(conv_id = %id, error = %e, "Failed to clear draft after send");
+    }
+
+    Ok(Json(ChatResponse { queued: true }))
+}
+
+/// `POST /api/conversations/:id/steer` — pass guidance to a running agent
+/// without cancelling it (REQ-BED-034). The note reaches the model with the
+/// next LLM request; 409 when nothing is running to steer.
+async fn steer_conversation(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<SteerRequest>,
+) -> Result<Json<ChatResponse>, AppError> {
+    if req.text.trim().is_empty() {
+        return Err(AppError::BadRequest("Steering text is empty".to_string()));
+    }
+    let conversation = state.db.get_conversation(&id).await?;
+    if let Err(err) = check_user_steer_acceptable(&conversation.state) {
+        let error_type = match err {
+            TransitionError::CancellationInProgress => "cancellation_in_progress",
+            _ => "agent_not_running",
+        };
+        return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+            err.to_string(),
+            error_type,
+        ))));
+    }
+
+    let event = Event::UserSteer {
+        text: req.text,
+        message_id: req.message_id,
+    };
+    state
+        .runtime
+        .send_event(&id, event)
+        .await
+        .map_err(AppError::BadRequest)?;
     Ok(Json(ChatResponse { queued: true }))
 }
 
-async fn cancel_conversation(
+/// `POST /api/conversations/:id/retry` — re-dispatch the last user turn of
+/// a conversation sitting in `Error` (REQ-BED-035). 409 in any other state.
+async fn retry_conversation(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    let conversation = state.db.get_conversation(&id).await?;
+    if !matches!(conversation.state, ConvState::Error { .. }) {
+        return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+            "Conversation is not in an error state; nothing to retry",
+            "not_in_error",
+        ))));
+    }
+
+    state
+        .runtime
+        .send_event(&id, Event::UserRetry)
+        .await
+        .map_err(AppError::BadRequest)?;
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// Prompt templates for `/command` expansion (REQ-IR-009). A read failure
+/// degrades to "no templates" so chat keeps working; the message is then
+/// sent as typed.
+async fn load_prompt_templates(state: &AppState) -> Vec<crate::db::PromptTemplate> {
+    state.db.list_prompt_templates().await.unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Failed to load prompt templates; skipping expansion");
+        Vec::new()
+    })
+}
+
+pub(super) async fn cancel_conversation(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<CancelResponse>, AppError> {
@@ -1551,12 +2390,7 @@ async fn cancel_conversation(
     // Doing nothing is the right answer — there's nothing to cancel —
     // and the response's `no_op: true` lets callers distinguish this
     // from the "we stopped something in flight" case.
-    let conversation = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conversation = state.runtime.db().get_conversation(&id).await?;
 
     if matches!(conversation.state, ConvState::Idle) || conversation.state.is_terminal() {
         tracing::debug!(
@@ -1582,6 +2416,33 @@ async fn cancel_conversation(
     }))
 }
 
+/// `POST /api/conversations/:id/cancel-tool/:tool_use_id` — drop a tool
+/// that is queued behind the running one (REQ-BED-055). The tool gets a
+/// skipped result and the round goes on; 409 when it is not queued.
+async fn cancel_queued_tool(
+    State(state): State<AppState>,
+    Path((id, tool_use_id)): Path<(String, String)>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    let conversation = state.runtime.db().get_conversation(&id).await?;
+    if let Err(err) = check_tool_cancellable(&conversation.state, &tool_use_id) {
+        let error_type = match err {
+            TransitionError::CancellationInProgress => "cancellation_in_progress",
+            _ => "tool_not_queued",
+        };
+        return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+            err.to_string(),
+            error_type,
+        ))));
+    }
+
+    state
+        .runtime
+        .send_event(&id, Event::CancelSpecificTool { tool_use_id })
+        .await
+        .map_err(AppError::BadRequest)?;
+    Ok(Json(SuccessResponse { success: true }))
+}
+
 /// Upgrade a conversation's model (e.g., from 200k to 1M context).
 /// Requires the conversation to be idle -- cannot upgrade mid-turn.
 async fn upgrade_conversation_model(
@@ -1599,14 +2460,9 @@ async fn upgrade_conversation_model(
     }
 
     // Validate conversation exists and is idle
-    let conv = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conv = state.runtime.db().get_conversation(&id).await?;
 
-    if !matches!(conv.state, ConvState::Idle) {
+    if !conv.state.is_idle() {
         return Err(AppError::BadRequest(
             "Conversation must be idle to upgrade model".to_string(),
         ));
@@ -1617,8 +2473,7 @@ async fn upgrade_conversation_model(
         .runtime
         .db()
         .update_conversation_model(&id, &req.model)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+        .await?;
 
     // Evict the active runtime so it gets recreated with the new model
     state.runtime.evict_runtime(&id).await;
@@ -1633,6 +2488,206 @@ async fn upgrade_conversation_model(
     Ok(Json(SuccessResponse { success: true }))
 }
 
+/// Set or clear a conversation's extended-thinking budget (REQ-LLM-014).
+/// Requires the conversation to be idle, like a model upgrade: the budget is
+/// read when the runtime is created.
+async fn set_conversation_thinking(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<SetThinkingRequest>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    if let Some(budget) = req.budget_tokens {
+        if !(MIN_THINKING_BUDGET..=MAX_THINKING_BUDGET).contains(&budget) {
+            return Err(AppError::BadRequest(format!(
+                "budget_tokens must be between {MIN_THINKING_BUDGET} and {MAX_THINKING_BUDGET}"
+            )));
+        }
+    }
+
+    let conv = state.runtime.db().get_conversation(&id).await?;
+
+    if !conv.state.is_idle() {
+        return Err(AppError::BadRequest(
+            "Conversation must be idle to change thinking".to_string(),
+        ));
+    }
+
+    state
+        .runtime
+        .db()
+        .set_thinking_budget(&id, req.budget_tokens)
+        .await?;
+
+    // Evict the active runtime so it gets recreated with the new budget
+    state.runtime.evict_runtime(&id).await;
+
+    tracing::info!(
+        conv_id = %id,
+        budget_tokens = ?req.budget_tokens,
+        "Conversation thinking budget set"
+    );
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// Set how much history the conversation's LLM requests carry
+/// (REQ-BED-046). Requires the conversation to be idle: the window is read
+/// when the runtime is created.
+async fn set_conversation_history_window(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<SetHistoryWindowRequest>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    req.window.validate().map_err(AppError::BadRequest)?;
+
+    let conv = state.runtime.db().get_conversation(&id).await?;
+
+    if !conv.state.is_idle() {
+        return Err(AppError::BadRequest(
+            "Conversation must be idle to change the history window".to_string(),
+        ));
+    }
+
+    state
+        .runtime
+        .db()
+        .set_history_window(&id, req.window)
+        .await?;
+
+    // Evict the active runtime so it gets recreated with the new window
+    state.runtime.evict_runtime(&id).await;
+
+    tracing::info!(conv_id = %id, window = ?req.window, "Conversation history window set");
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// Store the conversation's unsent draft so another device or a reload can
+/// pick it up from the next `init` (REQ-BED-047). Empty text with no images
+/// clears it.
+async fn save_conversation_draft(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<SaveDraftRequest>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    state.db.get_conversation(&id).await?;
+
+    let result = if req.text.trim().is_empty() && req.images.is_empty() {
+        state.db.clear_draft(&id).await
+    } else {
+        let images: Vec<ImageData> = req
+            .images
+            .into_iter()
+            .map(|img| ImageData {
+                data: img.data,
+                media_type: img.media_type,
+            })
+            .collect();
+        state
+            .db
+            .save_draft(&id, &req.text, &images, chrono::Utc::now())
+            .await
+    };
+    result.map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// Built-in tools plus live MCP tools: the choices for per-conversation
+/// tool selection (REQ-BED-039).
+async fn available_tools(state: &AppState) -> Vec<ToolEntry> {
+    let mut tools: Vec<ToolEntry> = crate::tools::ToolRegistry::catalog()
+        .definitions()
+        .into_iter()
+        .map(|d| ToolEntry {
+            source: if crate::tools::plugin::is_plugin(&d.name) {
+                "plugin"
+            } else {
+                "builtin"
+            },
+            name: d.name,
+            description: d.description,
+        })
+        .collect();
+    for (server_name, def) in state.mcp_manager.tool_definitions().await {
+        tools.push(ToolEntry {
+            name: format!("{server_name}__{}", def.name),
+            description: def.description,
+            source: "mcp",
+        });
+    }
+    tools
+}
+
+/// List the tools a conversation can switch off (REQ-BED-039)
+async fn list_tools(State(state): State<AppState>) -> Json<ToolsResponse> {
+    Json(ToolsResponse {
+        tools: available_tools(&state).await,
+    })
+}
+
+/// Deduplicate `names` and reject any that match no available tool, so a
+/// typo does not silently leave a tool enabled.
+pub(super) async fn checked_tool_names(
+    state: &AppState,
+    mut names: Vec<String>,
+) -> Result<Vec<String>, AppError> {
+    names.sort();
+    names.dedup();
+    if names.is_empty() {
+        return Ok(names);
+    }
+    let tools = available_tools(state).await;
+    let unknown: Vec<&str> = names
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !tools.iter().any(|t| t.name == *name))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Unknown tools: {}",
+            unknown.join(", ")
+        )));
+    }
+    Ok(names)
+}
+
+/// Replace the tools a conversation's agent may not use (REQ-BED-039).
+/// Requires the conversation to be idle, like the thinking budget: the
+/// selection is read when the runtime is created.
+async fn set_conversation_tools(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<SetToolsRequest>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    let disabled = checked_tool_names(&state, req.disabled).await?;
+
+    let conv = state.runtime.db().get_conversation(&id).await?;
+    if conv.parent_conversation_id.is_some() {
+        return Err(AppError::BadRequest(
+            "Sub-agents use their parent's tool selection".to_string(),
+        ));
+    }
+    if !conv.state.is_idle() {
+        return Err(AppError::BadRequest(
+            "Conversation must be idle to change tools".to_string(),
+        ));
+    }
+
+    state
+        .runtime
+        .db()
+        .set_disabled_tools(&id, &disabled)
+        .await?;
+
+    // Evict the active runtime so it gets recreated with the new selection
+    state.runtime.evict_runtime(&id).await;
+
+    tracing::info!(conv_id = %id, disabled = ?disabled, "Conversation tools set");
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
 /// Manually trigger context continuation (REQ-BED-023)
 async fn trigger_continuation(
     State(state): State<AppState>,
@@ -1757,12 +2812,7 @@ async fn respond_to_question(
     Json(req): Json<RespondToQuestionPayload>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     // 1. Validate conversation exists and is in AwaitingUserResponse state
-    let conv = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conv = state.runtime.db().get_conversation(&id).await?;
 
     if !matches!(conv.state, ConvState::AwaitingUserResponse { .. }) {
         return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
@@ -1787,6 +2837,94 @@ async fn respond_to_question(
     Ok(Json(SuccessResponse { success: true }))
 }
 
+#[derive(Deserialize)]
+struct AnswerUserInputPayload {
+    answer: String,
+}
+
+/// Answer a pending `ask_user` call; the answer becomes its result and the
+/// turn resumes (REQ-AUQ-009).
+async fn answer_user_input(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<AnswerUserInputPayload>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    let answer = req.answer.trim();
+    if answer.is_empty() {
+        return Err(AppError::BadRequest("Answer must not be empty".to_string()));
+    }
+    let conv = state.runtime.db().get_conversation(&id).await?;
+    if !matches!(
+        conv.state,
+        ConvState::AwaitingUserInput { ask: Some(_), .. }
+    ) {
+        return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+            "Conversation is not awaiting an answer",
+            "wrong_state",
+        ))));
+    }
+
+    state
+        .runtime
+        .send_event(
+            &id,
+            Event::UserInputAnswer {
+                answer: answer.to_string(),
+            },
+        )
+        .await
+        .map_err(AppError::BadRequest)?;
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+#[derive(Deserialize)]
+struct CouncilChoicePayload {
+    candidate_id: String,
+}
+
+/// Continue a council prompt from one of its answers (REQ-BED-058). The
+/// answer is persisted as the agent's reply and its tool calls run.
+async fn choose_council_answer(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<CouncilChoicePayload>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    let db = state.runtime.db();
+    let conv = db.get_conversation(&id).await?;
+    let ConvState::AwaitingCouncilChoice { candidates } = &conv.state else {
+        return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+            "Conversation is not awaiting a council choice",
+            "wrong_state",
+        ))));
+    };
+    if !candidates
+        .iter()
+        .any(|c| c.id == req.candidate_id && c.error.is_none())
+    {
+        return Err(AppError::BadRequest(format!(
+            "No council answer '{}' to choose",
+            req.candidate_id
+        )));
+    }
+
+    state
+        .runtime
+        .send_event(
+            &id,
+            Event::CouncilChoice {
+                candidate_id: req.candidate_id.clone(),
+            },
+        )
+        .await
+        .map_err(AppError::BadRequest)?;
+    if let Err(e) = db.mark_council_choice(&id, &req.candidate_id).await {
+        tracing::warn!(conv_id = %id, error = %e, "Failed to record council choice");
+    }
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
 // ============================================================
 // Lifecycle (REQ-API-006)
 // ============================================================
@@ -1901,12 +3039,7 @@ pub(super) async fn run_hard_delete_cascade(state: &AppState, id: &str) -> Resul
     // Step 1: reject-if-busy. Read the conversation's persisted state
     // (the DB is updated before any side effect per persist-before-broadcast,
     // so DB state is the authoritative answer to "is this conversation busy?").
-    let conv = state
-        .runtime
-        .db()
-        .get_conversation(id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conv = state.runtime.db().get_conversation(id).await?;
 
     if conv.state.is_busy() {
         return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
@@ -2181,12 +3314,32 @@ async fn rename_conversation(
             _ => AppError::NotFound(e.to_string()),
         })?;
 
-    let conversation = state
+    let conversation = state.runtime.db().get_conversation(&id).await?;
+
+    Ok(Json(ConversationResponse {
+        conversation: serde_json::to_value(conversation).unwrap_or(Value::Null),
+    }))
+}
+
+/// Regenerate the title from the conversation so far (REQ-API-012).
+async fn regenerate_title(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ConversationResponse>, AppError> {
+    use crate::db::DbError;
+    use crate::title_generator::RetitleError;
+
+    state
         .runtime
-        .db()
-        .get_conversation(&id)
+        .regenerate_title(&id)
         .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+        .map_err(|e| match e {
+            RetitleError::Db(DbError::ConversationNotFound(msg)) => AppError::NotFound(msg),
+            RetitleError::NoModel => AppError::BadRequest(e.to_string()),
+            RetitleError::NoTitle | RetitleError::Db(_) => AppError::Internal(e.to_string()),
+        })?;
+
+    let conversation = state.runtime.db().get_conversation(&id).await?;
 
     Ok(Json(ConversationResponse {
         conversation: serde_json::to_value(conversation).unwrap_or(Value::Null),
@@ -2201,19 +3354,9 @@ async fn get_by_slug(
     State(state): State<AppState>,
     Path(slug): Path<String>,
 ) -> Result<Json<ConversationWithMessagesResponse>, AppError> {
-    let conversation = state
-        .runtime
-        .db()
-        .get_conversation_by_slug(&slug)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conversation = state.runtime.db().get_conversation_by_slug(&slug).await?;
 
-    let messages = state
-        .runtime
-        .db()
-        .get_messages(&conversation.id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let messages = state.runtime.db().get_messages(&conversation.id).await?;
 
     let json_msgs: Vec<Value> = messages.iter().map(enrich_message_for_api).collect();
 
@@ -2454,11 +3597,18 @@ async fn list_files(Query(query): Query<PathQuery>) -> Result<Json<ListFilesResp
         builder.build().ok()
     };
 
+    // Entries matched by `.phoenixignore` are not listed at all (REQ-BED-050)
+    let phoenixignore = crate::phoenixignore::PhoenixIgnore::load(&path);
+
     let entries = fs::read_dir(&path)
         .map_err(|e| AppError::BadRequest(format!("Cannot read directory: {e}")))?;
 
     let mut items: Vec<FileEntry> = entries
         .filter_map(Result::ok)
+        .filter(|entry| {
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            !phoenixignore.is_ignored(&entry.path(), is_dir)
+        })
         .map(|entry| {
             let name = entry.file_name().to_string_lossy().to_string();
             let entry_path = entry.path();
@@ -2513,8 +3663,44 @@ async fn list_files(Query(query): Query<PathQuery>) -> Result<Json<ListFilesResp
     Ok(Json(ListFilesResponse { items }))
 }
 
-/// Read file contents with text encoding validation (REQ-PF-005)
-async fn read_file(Query(query): Query<PathQuery>) -> Result<Json<ReadFileResponse>, AppError> {
+/// Largest file returned whole; bigger ones are read a chunk at a time.
+const MAX_WHOLE_READ_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Largest file read at all.
+const MAX_CHUNKED_READ_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Target size of the chunks a large file is read in (REQ-FE-012).
+const READ_CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Chunk outline of a file read in chunks, with the byte range of each
+/// chunk, so paging through a large file reads and parses it once.
+struct ChunkOutline {
+    len: u64,
+    modified: Option<std::time::SystemTime>,
+    chunks: Vec<crate::file_chunks::FileChunk>,
+    ranges: Vec<std::ops::Range<usize>>,
+}
+
+/// Cached outlines. Key: file path. An entry is used while the file's size
+/// and modification time still match.
+type ChunkOutlineMap = std::collections::HashMap<PathBuf, ChunkOutline>;
+static CHUNK_OUTLINES: std::sync::LazyLock<std::sync::Mutex<ChunkOutlineMap>> =
+    std::sync::LazyLock::new(|| std::sync::Mutex::new(ChunkOutlineMap::new()));
+
+/// Most outlines cached; the cache starts over when full.
+const MAX_CHUNK_OUTLINES: usize = 16;
+
+#[derive(Debug, Deserialize)]
+struct ReadFileQuery {
+    path: String,
+    /// 1-based chunk to return instead of the whole file (REQ-FE-012).
+    chunk: Option<usize>,
+}
+
+/// Read file contents with text encoding validation (REQ-FE-011). A file
+/// over 10MB, or any file read with `?chunk=`, comes back with its chunks
+/// (REQ-FE-012).
+async fn read_file(Query(query): Query<ReadFileQuery>) -> Result<Json<ReadFileResponse>, AppError> {
     let path = PathBuf::from(&query.path);
 
     if !path.exists() {
@@ -2523,15 +3709,49 @@ async fn read_file(Query(query): Query<PathQuery>) -> Result<Json<ReadFileRespon
     if path.is_dir() {
         return Err(AppError::BadRequest("Path is a directory".to_string()));
     }
+    // The browser never lists these, so it does not read them either (REQ-BED-050)
+    if crate::phoenixignore::PhoenixIgnore::path_is_ignored(&path) {
+        return Err(AppError::SandboxViolation(format!(
+            "{} is excluded by {}",
+            path.display(),
+            crate::phoenixignore::FILE_NAME
+        )));
+    }
 
-    // Check file size (limit to 10MB for safety)
     let metadata = fs::metadata(&path)
         .map_err(|e| AppError::BadRequest(format!("Cannot read file metadata: {e}")))?;
-    if metadata.len() > 10 * 1024 * 1024 {
+    if metadata.len() > MAX_CHUNKED_READ_BYTES {
         return Err(AppError::BadRequest(
-            "File too large (max 10MB)".to_string(),
+            "File too large (max 256MB)".to_string(),
         ));
     }
+    let modified = metadata.modified().ok();
+
+    // A file already outlined only needs the bytes of the chunk asked for
+    let cached = {
+        let cache = CHUNK_OUTLINES
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        cache
+            .get(&path)
+            .filter(|outline| outline.len == metadata.len() && outline.modified == modified)
+            .map(|outline| (outline.chunks.clone(), outline.ranges.clone()))
+    };
+    if let Some((chunks, ranges)) = cached {
+        let content = match query.chunk {
+            None => String::new(),
+            Some(index) => {
+                let range = chunk_index(index, &ranges)?;
+                read_range(&path, range)?
+            }
+        };
+        return Ok(Json(ReadFileResponse {
+            content,
+            encoding: "utf-8".to_string(),
+            chunks: Some(chunks),
+            chunk: query.chunk,
+        }));
+    }
 
     // Read file content
     let content =
@@ -2543,17 +3763,82 @@ async fn read_file(Query(query): Query<PathQuery>) -> Result<Json<ReadFileRespon
             "File appears to be binary or has invalid encoding".to_string(),
         ));
     }
-
-    // Convert to string (we know it's valid UTF-8 from is_valid_text check)
-    let text = String::from_utf8(content)
-        .map_err(|_| AppError::BadRequest("Invalid UTF-8 encoding".to_string()))?;
-
+
+    // Convert to string (we know it's valid UTF-8 from is_valid_text check)
+    let text = String::from_utf8(content)
+        .map_err(|_| AppError::BadRequest("Invalid UTF-8 encoding".to_string()))?;
+
+    if query.chunk.is_none() && metadata.len() <= MAX_WHOLE_READ_BYTES {
+        return Ok(Json(ReadFileResponse {
+            content: text,
+            encoding: "utf-8".to_string(),
+            chunks: None,
+            chunk: None,
+        }));
+    }
+
+    let chunks = crate::file_chunks::chunks(&path, &text, READ_CHUNK_BYTES);
+    let ranges: Vec<_> = chunks
+        .iter()
+        .map(|chunk| crate::file_chunks::chunk_range(&text, chunk))
+        .collect();
+    let content = match query.chunk {
+        None => String::new(),
+        Some(index) => {
+            let range = chunk_index(index, &ranges)?;
+            text.get(range).unwrap_or_default().to_string()
+        }
+    };
+    {
+        let mut cache = CHUNK_OUTLINES
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if cache.len() >= MAX_CHUNK_OUTLINES {
+            cache.clear();
+        }
+        cache.insert(
+            path,
+            ChunkOutline {
+                len: metadata.len(),
+                modified,
+                chunks: chunks.clone(),
+                ranges,
+            },
+        );
+    }
     Ok(Json(ReadFileResponse {
-        content: text,
+        content,
         encoding: "utf-8".to_string(),
+        chunks: Some(chunks),
+        chunk: query.chunk,
     }))
 }
 
+/// The byte range of 1-based chunk `index`.
+fn chunk_index(
+    index: usize,
+    ranges: &[std::ops::Range<usize>],
+) -> Result<std::ops::Range<usize>, AppError> {
+    index
+        .checked_sub(1)
+        .and_then(|i| ranges.get(i))
+        .cloned()
+        .ok_or_else(|| AppError::BadRequest(format!("No chunk {index} of {}", ranges.len())))
+}
+
+/// Read `range` of the file at `path` as text.
+fn read_range(path: &std::path::Path, range: std::ops::Range<usize>) -> Result<String, AppError> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file =
+        fs::File::open(path).map_err(|e| AppError::BadRequest(format!("Cannot read file: {e}")))?;
+    let mut bytes = vec![0; range.len()];
+    file.seek(SeekFrom::Start(range.start as u64))
+        .and_then(|_| file.read_exact(&mut bytes))
+        .map_err(|e| AppError::BadRequest(format!("Cannot read file: {e}")))?;
+    String::from_utf8(bytes).map_err(|_| AppError::BadRequest("Invalid UTF-8 encoding".to_string()))
+}
+
 /// Serve a file from an absolute path with native Content-Type.
 /// Used by "Open in browser" for HTML preview -- the path-based URL means
 /// relative references (CSS, JS, images) resolve correctly against the
@@ -2619,18 +3904,27 @@ async fn search_conversation_files(
     Path(id): Path<String>,
     Query(query): Query<FileSearchQuery>,
 ) -> Result<Json<FileSearchResponse>, AppError> {
-    let conversation = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
-
-    let root = std::path::PathBuf::from(&conversation.cwd);
+    let conversation = state.runtime.db().get_conversation(&id).await?;
+
+    // `?root=` searches one of the conversation's extra roots (REQ-BED-049)
+    let root = match &query.root {
+        None => std::path::PathBuf::from(&conversation.cwd),
+        Some(requested) => {
+            let roots = state.runtime.db().list_conversation_roots(&id).await?;
+            let requested = requested.trim_end_matches('/');
+            if !roots.iter().any(|r| r.path == requested) {
+                return Err(AppError::BadRequest(format!(
+                    "Not a root of this conversation: {requested}"
+                )));
+            }
+            std::path::PathBuf::from(requested)
+        }
+    };
     if !root.exists() {
-        return Err(AppError::NotFound(
-            "Conversation working directory does not exist".to_string(),
-        ));
+        return Err(AppError::NotFound(format!(
+            "Directory does not exist: {}",
+            root.display()
+        )));
     }
 
     let limit = query.limit.unwrap_or(50);
@@ -2643,6 +3937,7 @@ async fn search_conversation_files(
         .git_global(true)
         .git_exclude(true)
         .ignore(true)
+        .add_custom_ignore_filename(crate::phoenixignore::FILE_NAME)
         .filter_entry(|e| e.file_name() != ".git") // .git/ is not gitignored, exclude explicitly
         .build();
 
@@ -2762,29 +4057,28 @@ async fn list_conversation_skills(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<SkillsResponse>, AppError> {
-    let conversation = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conversation = state.runtime.db().get_conversation(&id).await?;
 
     let cwd = std::path::PathBuf::from(&conversation.cwd);
     let skills = crate::system_prompt::discover_skills(&cwd);
 
-    let skill_entries: Vec<SkillEntry> = skills
-        .into_iter()
-        .map(|s| SkillEntry {
-            name: s.name,
-            description: s.description,
-            argument_hint: s.argument_hint,
-            source: s.source,
-            path: s.path.to_string_lossy().to_string(),
-        })
-        .collect();
-
     Ok(Json(SkillsResponse {
-        skills: skill_entries,
+        skills: skills.into_iter().map(SkillEntry::from).collect(),
+    }))
+}
+
+/// List server-side slash-command templates for autocomplete (REQ-IR-009).
+async fn list_commands(State(state): State<AppState>) -> Result<Json<CommandsResponse>, AppError> {
+    let templates = state.db.list_prompt_templates().await?;
+    Ok(Json(CommandsResponse {
+        commands: templates
+            .into_iter()
+            .map(|t| CommandEntry {
+                name: t.command,
+                description: t.description,
+                argument_hint: t.argument_hint,
+            })
+            .collect(),
     }))
 }
 
@@ -2797,12 +4091,7 @@ async fn list_conversation_tasks(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<TasksResponse>, AppError> {
-    let conversation = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conversation = state.runtime.db().get_conversation(&id).await?;
 
     let cwd = std::path::PathBuf::from(&conversation.cwd);
     let tasks_dir = cwd.join("tasks");
@@ -2846,12 +4135,320 @@ async fn get_conversation_usage_handler(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<ConversationUsage>, AppError> {
-    let usage = state
+    let usage = state.db.get_conversation_usage(&id).await?;
+    Ok(Json(usage))
+}
+
+/// Estimated input tokens each tool's outputs have cost the conversation's
+/// requests, largest first (REQ-API-031). `prompt_tokens` is what those
+/// requests actually cost in all, for scale.
+async fn get_tool_usage(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ToolUsageResponse>, AppError> {
+    state.db.get_conversation(&id).await?;
+    let tools = state.db.list_tool_token_usage(&id).await?;
+    let own = state.db.get_conversation_usage(&id).await?.own;
+    Ok(Json(ToolUsageResponse {
+        tools,
+        prompt_tokens: own.input_tokens + own.cache_creation_tokens + own.cache_read_tokens,
+    }))
+}
+
+/// Token usage and estimated spend grouped by model, day, or conversation (REQ-LLM-010).
+async fn get_usage_summary(
+    State(state): State<AppState>,
+    Query(query): Query<UsageSummaryQuery>,
+) -> Result<Json<UsageSummaryResponse>, AppError> {
+    let rows = state.db.usage_breakdown(query.group_by).await?;
+    Ok(Json(summarize_usage(query.group_by, rows)))
+}
+
+/// Tool executions from the audit log, most recent first (REQ-API-016).
+async fn get_audit_log(
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<AuditLogResponse>, AppError> {
+    let entries = state.db.query_audit_log(&query).await?;
+    Ok(Json(AuditLogResponse { entries }))
+}
+
+/// Longest comment accepted with a message rating, in chars.
+const MAX_FEEDBACK_COMMENT_CHARS: usize = 4_000;
+
+/// Rate an agent message up or down, with an optional comment
+/// (REQ-API-022). Rating again replaces the earlier rating.
+async fn set_message_feedback(
+    State(state): State<AppState>,
+    Path(message_id): Path<String>,
+    Json(req): Json<MessageFeedbackRequest>,
+) -> Result<Json<MessageFeedbackResponse>, AppError> {
+    let message = state
         .db
-        .get_conversation_usage(&id)
+        .get_message_by_id(&message_id)
         .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
-    Ok(Json(usage))
+        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    if message.message_type != MessageType::Agent {
+        return Err(AppError::BadRequest(format!(
+            "Only agent messages can be rated, not {} messages",
+            message.message_type
+        )));
+    }
+    let comment = req
+        .comment
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+    if comment.is_some_and(|c| c.chars().count() > MAX_FEEDBACK_COMMENT_CHARS) {
+        return Err(AppError::BadRequest(format!(
+            "Comment is longer than {MAX_FEEDBACK_COMMENT_CHARS} characters"
+        )));
+    }
+
+    state
+        .db
+        .set_message_feedback(
+            &message_id,
+            &message.conversation_id,
+            req.rating,
+            comment,
+            chrono::Utc::now(),
+        )
+        .await?;
+    let feedback = state
+        .db
+        .get_message_feedback(&message_id)
+        .await?
+        .ok_or_else(|| AppError::Internal(format!("Feedback for {message_id} vanished")))?;
+
+    tracing::info!(
+        conv_id = %message.conversation_id,
+        message_id = %message_id,
+        rating = feedback.rating.as_str(),
+        "Message rated"
+    );
+    Ok(Json(MessageFeedbackResponse { feedback }))
+}
+
+/// Remove the rating of a message (REQ-API-022). 404 when it has none.
+async fn delete_message_feedback(
+    State(state): State<AppState>,
+    Path(message_id): Path<String>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    let removed = state.db.delete_message_feedback(&message_id).await?;
+    if !removed {
+        return Err(AppError::NotFound(format!(
+            "Message not rated: {message_id}"
+        )));
+    }
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// Every rated agent turn as JSON Lines, oldest rating first, for building
+/// evaluation datasets (REQ-API-022).
+async fn export_feedback(
+    State(state): State<AppState>,
+    Query(query): Query<FeedbackExportQuery>,
+) -> Result<axum::response::Response, AppError> {
+    use axum::http::header;
+    use axum::response::IntoResponse;
+
+    let records = state.db.export_feedback(query.rating, query.since).await?;
+    let mut body = String::new();
+    for record in &records {
+        let line = serde_json::to_string(record).map_err(|e| AppError::Internal(e.to_string()))?;
+        body.push_str(&line);
+        body.push('\n');
+    }
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/x-ndjson"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"phoenix-feedback.jsonl\"",
+            ),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Recorded state-machine transitions for a conversation, oldest first
+/// (REQ-API-017).
+async fn get_transitions(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<TransitionsQuery>,
+) -> Result<Json<TransitionsResponse>, AppError> {
+    state.db.get_conversation(&id).await?;
+    let transitions = state
+        .db
+        .list_transitions(&id, query.after_id, query.limit)
+        .await?;
+    Ok(Json(TransitionsResponse { transitions }))
+}
+
+/// Files the conversation's tools have read or edited, most recently
+/// touched first (REQ-BED-042).
+async fn get_touched_files(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<TouchedFilesResponse>, AppError> {
+    state.db.get_conversation(&id).await?;
+    let files = state.db.list_touched_files(&id).await?;
+    Ok(Json(TouchedFilesResponse { files }))
+}
+
+/// Logged LLM requests and responses for a conversation, oldest first
+/// (REQ-LLM-017).
+async fn get_llm_log(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<LlmLogQuery>,
+) -> Result<Json<LlmLogResponse>, AppError> {
+    state.db.get_conversation(&id).await?;
+    let Some(log) = state.runtime.llm_log() else {
+        return Ok(Json(LlmLogResponse {
+            enabled: false,
+            entries: Vec::new(),
+        }));
+    };
+    let entries = log.entries_for(&id, query.limit.unwrap_or(50)).await;
+    Ok(Json(LlmLogResponse {
+        enabled: true,
+        entries,
+    }))
+}
+
+/// Re-run a conversation's recorded transitions through the current state
+/// machine and report where it first behaves differently (REQ-API-017).
+async fn replay_transitions(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ReplayReport>, AppError> {
+    let conversation = state.db.get_conversation(&id).await?;
+
+    let mut steps = Vec::new();
+    let mut after_id = None;
+    loop {
+        let page = state
+            .db
+            .list_transitions(&id, after_id, Some(u32::MAX))
+            .await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after_id = Some(last.id);
+        for record in &page {
+            let step = ReplayStep::try_from(record).map_err(|e| {
+                AppError::Internal(format!("Transition {} is unreadable: {e}", record.id))
+            })?;
+            steps.push(step);
+        }
+    }
+
+    let context = state.runtime.conversation_context(&conversation).await;
+    Ok(Json(replay(&context, &steps)))
+}
+
+/// Logged events for a conversation, oldest first (REQ-BED-054). Empty
+/// unless the server runs with `PHOENIX_EVENT_LOG`.
+async fn get_events(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<EventsQuery>,
+) -> Result<Json<EventsResponse>, AppError> {
+    state.db.get_conversation(&id).await?;
+    let events = state
+        .db
+        .list_events(&id, query.after_id, query.limit)
+        .await?;
+    Ok(Json(EventsResponse { events }))
+}
+
+/// Re-drive a conversation's logged events through the current state
+/// machine and report the first that fares differently (REQ-BED-054).
+async fn replay_logged_events(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ReplayReport>, AppError> {
+    let conversation = state.db.get_conversation(&id).await?;
+
+    let mut steps = Vec::new();
+    let mut after_id = None;
+    loop {
+        let page = state.db.list_events(&id, after_id, Some(u32::MAX)).await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after_id = Some(last.id);
+        for record in &page {
+            let step = EventStep::try_from(record).map_err(|e| {
+                AppError::Internal(format!("Event {} is unreadable: {e}", record.id))
+            })?;
+            steps.push(step);
+        }
+    }
+
+    let context = state.runtime.conversation_context(&conversation).await;
+    Ok(Json(replay_events(&context, &steps)))
+}
+
+/// What `totals` cost on `model`, or `None` if its pricing is unknown.
+fn usage_cost_usd(model: &str, t: &UsageTotals) -> Option<f64> {
+    crate::llm::model_pricing(model).map(|p| {
+        p.cost_usd(
+            t.input_tokens,
+            t.output_tokens,
+            t.cache_creation_tokens,
+            t.cache_read_tokens,
+        )
+    })
+}
+
+/// Price each `(group, model)` row and fold rows into groups. Days are
+/// listed most recent first; other groupings by descending cost.
+fn summarize_usage(group_by: UsageGroupBy, rows: Vec<UsageBreakdownRow>) -> UsageSummaryResponse {
+    fn add(usage: &mut UsageCost, row: &UsageBreakdownRow, cost: Option<f64>) {
+        usage.totals.add(&row.totals);
+        usage.cache_hit_rate = crate::llm::Usage::from(&usage.totals).cache_hit_rate();
+        match cost {
+            Some(cost) => usage.cost_usd += cost,
+            None => usage.unpriced_turns += row.totals.turns,
+        }
+    }
+
+    let mut groups: Vec<UsageGroup> = Vec::new();
+    let mut total = UsageCost::default();
+    for row in rows {
+        let cost = usage_cost_usd(&row.model, &row.totals);
+        add(&mut total, &row, cost);
+        match groups.last_mut() {
+            Some(group) if group.key == row.key => add(&mut group.usage, &row, cost),
+            _ => {
+                let mut usage = UsageCost::default();
+                add(&mut usage, &row, cost);
+                groups.push(UsageGroup {
+                    key: row.key,
+                    label: row.label,
+                    usage,
+                });
+            }
+        }
+    }
+
+    match group_by {
+        UsageGroupBy::Day => groups.reverse(),
+        UsageGroupBy::Model | UsageGroupBy::Conversation => {
+            groups.sort_by(|a, b| b.usage.cost_usd.total_cmp(&a.usage.cost_usd));
+        }
+    }
+
+    UsageSummaryResponse {
+        group_by,
+        groups,
+        total,
+    }
 }
 
 // ============================================================
@@ -2862,14 +4459,14 @@ async fn list_models(State(state): State<AppState>) -> Json<ModelsResponse> {
     // Get model metadata from registry
     let models = state.llm_registry.available_model_info();
 
-    let gateway_status = match state.llm_registry.gateway_status {
+    let gateway_status = match state.llm_registry.gateway_status() {
         GatewayStatus::NotConfigured => GatewayStatusApi::NotConfigured,
         GatewayStatus::Healthy => GatewayStatusApi::Healthy,
         GatewayStatus::Unreachable => GatewayStatusApi::Unreachable,
     };
 
     let llm_configured = state.llm_registry.has_models()
-        || state.llm_registry.gateway_status != GatewayStatus::NotConfigured;
+        || state.llm_registry.gateway_status() != GatewayStatus::NotConfigured;
 
     let credential_status = if let Some(ref hs) = state.credential_helper {
         use crate::llm::CredentialStatus;
@@ -2887,7 +4484,7 @@ async fn list_models(State(state): State<AppState>) -> Json<ModelsResponse> {
 
     Json(ModelsResponse {
         models,
-        default: state.llm_registry.default_model_id().to_string(),
+        default: state.runtime.default_model_id().await,
         gateway_status,
         llm_configured,
         credential_status,
@@ -2981,6 +4578,11 @@ async fn mcp_status(State(state): State<AppState>) -> impl IntoResponse {
     Json(state.mcp_manager.status().await)
 }
 
+/// SSE payload and compression totals since startup (REQ-API-030).
+async fn stream_metrics() -> Json<StreamMetrics> {
+    Json(PAYLOAD_METRICS.snapshot())
+}
+
 /// Reload MCP server configurations: disconnect removed servers,
 /// connect newly added ones, leave existing ones untouched.
 async fn reload_mcp(State(state): State<AppState>) -> impl IntoResponse {
@@ -3053,79 +4655,6 @@ fn slugify_label(label: &str) -> String {
     out.trim_end_matches('-').to_string()
 }
 
-fn generate_slug() -> String {
-    let now = Local::now();
-
-    // Day of week
-    let day = match now.weekday() {
-        chrono::Weekday::Mon => "monday",
-        chrono::Weekday::Tue => "tuesday",
-        chrono::Weekday::Wed => "wednesday",
-        chrono::Weekday::Thu => "thursday",
-        chrono::Weekday::Fri => "friday",
-        chrono::Weekday::Sat => "saturday",
-        chrono::Weekday::Sun => "sunday",
-    };
-
-    // Time of day
-    let time = match now.hour() {
-        6..=11 => "morning",
-        12..=16 => "afternoon",
-        17..=20 => "evening",
-        _ => "night",
-    };
-
-    // Random words
-    let words = &[
-        "autumn",
-        "river",
-        "mountain",
-        "forest",
-        "meadow",
-        "ocean",
-        "desert",
-        "valley",
-        "sunrise",
-        "sunset",
-        "thunder",
-        "lightning",
-        "rainbow",
-        "crystal",
-        "shadow",
-        "light",
-        "ancient",
-        "swift",
-        "quiet",
-        "brave",
-        "golden",
-        "silver",
-        "azure",
-        "emerald",
-        "phoenix",
-        "dragon",
-        "falcon",
-        "wolf",
-        "raven",
-        "tiger",
-        "eagle",
-        "fox",
-        "dream",
-        "spark",
-        "flame",
-        "frost",
-        "storm",
-        "breeze",
-        "tide",
-        "star",
-    ];
-
-    let mut rng = rand::thread_rng();
-    let adjective = words.choose(&mut rng).unwrap_or(&"blue");
-    let noun = words.choose(&mut rng).unwrap_or(&"sky");
-
-    format!("{day}-{time}-{adjective}-{noun}")
-}
-
 // ============================================================
 // Share Mode (REQ-AUTH-004 through REQ-AUTH-008)
 // ============================================================
@@ -3137,18 +4666,9 @@ async fn create_or_redirect_share(
     State(state): State<AppState>,
     Path(slug): Path<String>,
 ) -> Result<Redirect, AppError> {
-    let conversation = state
-        .runtime
-        .db()
-        .get_conversation_by_slug(&slug)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conversation = state.runtime.db().get_conversation_by_slug(&slug).await?;
 
-    let token = state
-        .db
-        .create_share_token(&conversation.id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let token = state.db.create_share_token(&conversation.id).await?;
 
     Ok(Redirect::to(&format!("/s/{token}")))
 }
@@ -3159,19 +4679,19 @@ async fn create_or_redirect_share(
 async fn serve_share_page(
     State(state): State<AppState>,
     Path(token): Path<String>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, AppError> {
     // Validate token exists
     state
         .db
         .get_share_token_by_token(&token)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?
+        .await?
         .ok_or_else(|| {
             AppError::NotFound("Share link not found or has been revoked".to_string())
         })?;
 
-    match get_index_html() {
-        Some(content) => Ok(Html(content).into_response()),
+    match index_response(&headers) {
+        Some(response) => Ok(response),
         None => Ok((
             StatusCode::NOT_FOUND,
             Html("<h1>404 - UI not found. Build with: cd ui && npm run build</h1>".to_string()),
@@ -3190,23 +4710,16 @@ async fn get_shared_conversation(
     let (conversation_id, _) = state
         .db
         .get_share_token_by_token(&token)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?
+        .await?
         .ok_or_else(|| AppError::NotFound("Invalid share token".to_string()))?;
 
     let conversation = state
         .runtime
         .db()
         .get_conversation(&conversation_id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+        .await?;
 
-    let messages = state
-        .runtime
-        .db()
-        .get_messages(&conversation_id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let messages = state.runtime.db().get_messages(&conversation_id).await?;
 
     let json_msgs: Vec<Value> = messages.iter().map(enrich_message_for_api).collect();
 
@@ -3231,27 +4744,21 @@ async fn get_shared_conversation(
 async fn shared_sse_stream(
     State(state): State<AppState>,
     Path(token): Path<String>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, AppError> {
     let (conversation_id, _) = state
         .db
         .get_share_token_by_token(&token)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?
+        .await?
         .ok_or_else(|| AppError::NotFound("Invalid share token".to_string()))?;
 
     let conversation = state
         .runtime
         .db()
         .get_conversation(&conversation_id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+        .await?;
 
-    let messages = state
-        .runtime
-        .db()
-        .get_messages(&conversation_id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let messages = state.runtime.db().get_messages(&conversation_id).await?;
 
     let last_sequence_id = state
         .runtime
@@ -3300,20 +4807,42 @@ async fn shared_sse_stream(
         commits_behind: 0,
         commits_ahead: 0,
         project_name,
+        // Drafts belong to the owner, not to share viewers
+        draft: None,
     };
 
-    Ok(sse_stream(conversation_id, init_event, broadcast_rx))
+    let stream = sse_stream(
+        conversation_id,
+        init_event,
+        Vec::new(),
+        broadcast_rx,
+        None,
+        StreamFilter::default(),
+        None,
+    );
+    Ok(compress_stream(&headers, stream))
 }
 
 // ============================================================
 // Error Handling
 // ============================================================
 
+/// Every error a handler returns. Each maps to an HTTP status and an
+/// [`ErrorCode`] (REQ-API-029) so clients can branch on the kind of failure
+/// without parsing messages.
 #[derive(Debug)]
 pub(super) enum AppError {
     BadRequest(String),
     NotFound(String),
     Internal(String),
+    /// The database failed. Missing rows convert to `NotFound` instead; see
+    /// the `From<DbError>` impl.
+    Database(DbError),
+    /// An LLM call made for the request failed
+    Llm(LlmError),
+    /// The request named a path it may not read, e.g. one matched by
+    /// `.phoenixignore` (REQ-BED-050)
+    SandboxViolation(String),
     /// 409 — conflict (dirty worktree, merge conflicts, etc.). Boxed because
     /// `ConflictErrorResponse` is the largest variant and grew with
     /// `continuation_id` (REQ-BED-031) — boxing keeps `AppError` compact so
@@ -3323,41 +4852,181 @@ pub(super) enum AppError {
     UnprocessableEntity(ExpansionErrorResponse),
 }
 
+impl From<DbError> for AppError {
+    fn from(e: DbError) -> Self {
+        match e {
+            DbError::ConversationNotFound(_) | DbError::MessageNotFound(_) => {
+                AppError::NotFound(e.to_string())
+            }
+            other => AppError::Database(other),
+        }
+    }
+}
+
+impl From<LlmError> for AppError {
+    fn from(e: LlmError) -> Self {
+        AppError::Llm(e)
+    }
+}
+
+impl AppError {
+    /// The HTTP status and machine-readable code for this error
+    fn status_and_code(&self) -> (StatusCode, ErrorCode) {
+        match self {
+            AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, ErrorCode::BadRequest),
+            AppError::NotFound(_) => (StatusCode::NOT_FOUND, ErrorCode::NotFound),
+            AppError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Internal),
+            AppError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::Database),
+            AppError::Llm(e) => match e.kind {
+                LlmErrorKind::Auth => (StatusCode::BAD_GATEWAY, ErrorCode::LlmAuth),
+                LlmErrorKind::RateLimit => {
+                    (StatusCode::TOO_MANY_REQUESTS, ErrorCode::LlmRateLimited)
+                }
+                LlmErrorKind::Network | LlmErrorKind::ServerError => {
+                    (StatusCode::BAD_GATEWAY, ErrorCode::LlmUnavailable)
+                }
+                LlmErrorKind::InvalidRequest
+                | LlmErrorKind::ContentFilter
+                | LlmErrorKind::ContextWindowExceeded => {
+                    (StatusCode::BAD_GATEWAY, ErrorCode::LlmRejected)
+                }
+            },
+            AppError::SandboxViolation(_) => (StatusCode::FORBIDDEN, ErrorCode::SandboxViolation),
+            AppError::Conflict(detail) => (StatusCode::CONFLICT, conflict_code(&detail.error_type)),
+            AppError::UnprocessableEntity(_) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ErrorCode::InvalidReference,
+            ),
+        }
+    }
+}
+
+/// 409s that clear up on their own once the conversation's current turn or
+/// another tab's draft is done are `conversation_busy`; the rest need the
+/// client to do something first.
+fn conflict_code(error_type: &str) -> ErrorCode {
+    match error_type {
+        "agent_busy" | "cancellation_in_progress" | "cancel_first" | "composer_locked" => {
+            ErrorCode::ConversationBusy
+        }
+        _ => ErrorCode::Conflict,
+    }
+}
+
+/// A typed error payload with `code` and `retryable` alongside its own
+/// fields.
+#[derive(Serialize)]
+struct Coded<T> {
+    #[serde(flatten)]
+    detail: T,
+    code: ErrorCode,
+    retryable: bool,
+}
+
+impl<T> Coded<T> {
+    fn new(detail: T, code: ErrorCode) -> Json<Self> {
+        Json(Self {
+            detail,
+            code,
+            retryable: code.retryable(),
+        })
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let (status, code) = self.status_and_code();
         match self {
             AppError::BadRequest(ref msg) => {
                 tracing::debug!(error = %msg, "400 Bad Request");
-                (
-                    StatusCode::BAD_REQUEST,
-                    Json(ErrorResponse::new(msg.clone())),
-                )
-                    .into_response()
+                (status, Json(ErrorResponse::new(code, msg.clone()))).into_response()
             }
             AppError::NotFound(ref msg) => {
                 tracing::debug!(error = %msg, "404 Not Found");
-                (StatusCode::NOT_FOUND, Json(ErrorResponse::new(msg.clone()))).into_response()
+                (status, Json(ErrorResponse::new(code, msg.clone()))).into_response()
             }
             AppError::Internal(ref msg) => {
                 tracing::error!(error = %msg, "500 Internal Server Error");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse::new(msg.clone())),
-                )
-                    .into_response()
+                (status, Json(ErrorResponse::new(code, msg.clone()))).into_response()
+            }
+            AppError::Database(ref e) => {
+                tracing::error!(error = %e, "500 Database Error");
+                (status, Json(ErrorResponse::new(code, e.to_string()))).into_response()
+            }
+            AppError::Llm(ref e) => {
+                tracing::warn!(kind = ?e.kind, error = %e, "LLM call failed");
+                (status, Json(ErrorResponse::new(code, e.to_string()))).into_response()
+            }
+            AppError::SandboxViolation(ref msg) => {
+                tracing::warn!(error = %msg, "403 Sandbox Violation");
+                (status, Json(ErrorResponse::new(code, msg.clone()))).into_response()
             }
             AppError::Conflict(detail) => {
                 tracing::warn!(error_type = %detail.error_type, error = %detail.error, "409 Conflict");
-                (StatusCode::CONFLICT, Json(*detail)).into_response()
+                (status, Coded::new(*detail, code)).into_response()
             }
-            AppError::UnprocessableEntity(ref detail) => {
+            AppError::UnprocessableEntity(detail) => {
                 tracing::warn!(error = %detail.error, "422 Unprocessable Entity");
-                (StatusCode::UNPROCESSABLE_ENTITY, Json(detail.clone())).into_response()
+                (status, Coded::new(detail, code)).into_response()
             }
         }
     }
 }
 
+#[cfg(test)]
+mod app_error_tests {
+    use super::*;
+
+    /// Status, `code`, and `retryable` of the response for `error`, plus
+    /// the whole body.
+    async fn respond(error: AppError) -> (StatusCode, String, bool, Value) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: Value = serde_json::from_slice(&bytes).unwrap();
+        let code = json["code"].as_str().unwrap().to_string();
+        let retryable = json["retryable"].as_bool().unwrap();
+        (status, code, retryable, json)
+    }
+
+    #[tokio::test]
+    async fn errors_carry_codes_and_retry_hints() {
+        let missing = DbError::ConversationNotFound("c1".to_string());
+        let (status, code, retryable, _) = respond(missing.into()).await;
+        assert_eq!(
+            (status, code.as_str(), retryable),
+            (StatusCode::NOT_FOUND, "not_found", false)
+        );
+
+        let broken = DbError::Serialization("bad".to_string());
+        let (status, code, _, _) = respond(broken.into()).await;
+        assert_eq!(
+            (status, code.as_str()),
+            (StatusCode::INTERNAL_SERVER_ERROR, "database")
+        );
+
+        let limited = LlmError::new(LlmErrorKind::RateLimit, "slow down");
+        let (status, code, retryable, _) = respond(limited.into()).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!((code.as_str(), retryable), ("llm_rate_limited", true));
+        let rejected = LlmError::new(LlmErrorKind::Auth, "bad key");
+        let (_, code, retryable, _) = respond(rejected.into()).await;
+        assert_eq!((code.as_str(), retryable), ("llm_auth", false));
+
+        // Typed conflicts keep their fields and gain a code
+        let busy = ConflictErrorResponse::new("Agent is busy", "agent_busy");
+        let (status, code, retryable, json) = respond(AppError::Conflict(Box::new(busy))).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert_eq!(json["error_type"], "agent_busy");
+        assert_eq!((code.as_str(), retryable), ("conversation_busy", true));
+        let exists = ConflictErrorResponse::new("Template exists", "template_exists");
+        let (_, code, retryable, _) = respond(AppError::Conflict(Box::new(exists))).await;
+        assert_eq!((code.as_str(), retryable), ("conflict", false));
+    }
+}
+
 // ============================================================
 // Hard-delete cascade tests (REQ-BED-032)
 // ============================================================
@@ -3741,3 +5410,120 @@ mod hard_delete_cascade_tests {
         assert!(state.db.get_conversation("cb-b").await.is_ok());
     }
 }
+
+#[cfg(test)]
+mod usage_summary_tests {
+    use super::*;
+
+    fn row(key: &str, model: &str, input_tokens: i64) -> UsageBreakdownRow {
+        UsageBreakdownRow {
+            key: key.to_string(),
+            label: None,
+            model: model.to_string(),
+            totals: UsageTotals {
+                input_tokens,
+                turns: 1,
+                ..UsageTotals::default()
+            },
+        }
+    }
+
+    #[test]
+    fn folds_models_into_groups_and_sorts_by_cost() {
+        let rows = vec![
+            row("c1", "claude-haiku-4-5", 1_000_000),
+            row("c1", "mock", 500),
+            row("c2", "claude-opus-4-7", 1_000_000),
+        ];
+        let summary = summarize_usage(UsageGroupBy::Conversation, rows);
+
+        assert_eq!(summary.groups.len(), 2);
+        assert_eq!(summary.groups[0].key, "c2");
+        let c1 = &summary.groups[1].usage;
+        assert_eq!(c1.totals.turns, 2);
+        assert_eq!(c1.totals.input_tokens, 1_000_500);
+        assert_eq!(c1.unpriced_turns, 1);
+        assert!((c1.cost_usd - 1.0).abs() < 1e-9);
+        assert!((summary.total.cost_usd - 6.0).abs() < 1e-9);
+        assert_eq!(summary.total.totals.turns, 3);
+    }
+
+    #[test]
+    fn days_are_most_recent_first() {
+        let rows = vec![
+            row("2026-10-01", "claude-haiku-4-5", 10),
+            row("2026-10-02", "claude-haiku-4-5", 10),
+        ];
+        let summary = summarize_usage(UsageGroupBy::Day, rows);
+        assert_eq!(summary.groups[0].key, "2026-10-02");
+    }
+
+    #[test]
+    fn cache_hit_rate_covers_all_prompt_tokens() {
+        let mut cached = row("c1", "claude-haiku-4-5", 100);
+        cached.totals.cache_creation_tokens = 100;
+        cached.totals.cache_read_tokens = 800;
+        let summary = summarize_usage(
+            UsageGroupBy::Model,
+            vec![cached, row("c1", "claude-haiku-4-5", 1_000)],
+        );
+        let rate = summary.total.cache_hit_rate.unwrap();
+        assert!((rate - 0.4).abs() < 1e-9, "{rate}");
+    }
+
+    #[tokio::test]
+    async fn list_rows_carry_preview_cost_and_state_duration() {
+        let db = crate::db::Database::open_in_memory().await.unwrap();
+        let conv = db
+            .create_conversation("c1", "list-row", "/tmp", true, None, None)
+            .await
+            .unwrap();
+        let priced = row("c1", "claude-haiku-4-5", 1_000_000).totals;
+        let stats = ConversationListStats {
+            last_message_preview: Some("Fix the login page".to_string()),
+            usage_by_model: vec![
+                ("claude-haiku-4-5".to_string(), priced),
+                ("mock".to_string(), row("c1", "mock", 500).totals),
+            ],
+            reminded_at: Some(conv.state_updated_at + chrono::Duration::seconds(60)),
+        };
+        let now = conv.state_updated_at + chrono::Duration::seconds(90);
+
+        let json = conversation_list_json(&conv, Some(&stats), now);
+        assert_eq!(json["last_message_preview"], "Fix the login page");
+        assert!((json["cost_usd"].as_f64().unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(json["state_duration_ms"], 90_000);
+        assert_eq!(json["display_state"], "idle");
+        assert_eq!(json["needs_input"], false);
+
+        let bare = conversation_list_json(&conv, None, now);
+        assert!(bare["last_message_preview"].is_null());
+        assert_eq!(bare["cost_usd"].as_f64(), Some(0.0));
+
+        let mut asking = conv.clone();
+        asking.state = ConvState::AwaitingUserResponse {
+            questions: vec![],
+            tool_use_id: "t1".to_string(),
+        };
+        assert_eq!(
+            conversation_list_json(&asking, Some(&stats), now)["needs_input"],
+            true
+        );
+        assert_eq!(
+            conversation_list_json(&asking, None, now)["needs_input"],
+            false
+        );
+    }
+
+    #[test]
+    fn cost_sort_is_stable() {
+        let row = |id: &str, cost: f64| serde_json::json!({ "id": id, "cost_usd": cost });
+        let mut rows = vec![row("a", 0.5), row("b", 2.0), row("c", 0.5)];
+        sort_by_cost(&mut rows, SortOrder::Desc);
+        let ids: Vec<&str> = rows.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["b", "a", "c"]);
+        sort_by_cost(&mut rows, SortOrder::Asc);
+        let ids: Vec<&str> = rows.iter().map(|r| r["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["a", "c", "b"]);
+    }
+}
@@ -0,0 +1,441 @@
+//! Headless one-shot runs (REQ-CLI-011): `phoenix-ide run`
+//!
+//! `phoenix-ide run --prompt "fix the flaky test" [--cwd DIR] [--max-turns N]`
+//! starts the server's state and runtime in-process, without binding a port,
+//! creates one conversation through the same handler as
+//! `POST /api/conversations/new`, and waits for the agent to settle. The
+//! final agent response goes to stdout, a JSON report to `--report` (default
+//! `<data dir>/runs/<conversation id>.json`), and the exit code says how the
+//! run ended, so CI can gate on it:
+//!
+//! | Code | Status |
+//! |------|--------|
+//! | 0 | `completed` — the agent finished its turn |
+//! | 1 | `error` / `failed` — the conversation ended in `Error`, `Failed`, or a terminal state |
+//! | 2 | bad arguments, or the run could not start |
+//! | 3 | `turn_limit` / `needs_input` — paused for a person (REQ-BED-038, approval, questions) |
+//!
+//! `--max-turns` caps model requests for the turn through the per-turn
+//! budget (`PHOENIX_TURN_MAX_LLM_REQUESTS`), so hitting it pauses the
+//! conversation exactly as in the UI.
+//!
+//! The evaluation harness (`crate::eval`, REQ-CLI-012) drives its fixtures
+//! through the same [`start_task`], [`send_follow_up`], and
+//! [`wait_until_settled`] steps.
+
+use axum::extract::{Path as AxumPath, State};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::handlers::{create_conversation, send_chat, AppError};
+use super::types::{ChatRequest, CreateConversationRequest};
+use super::AppState;
+use crate::db::{Conversation, Message, MessageContent, UsageData};
+use crate::llm::ContentBlock;
+use crate::state_machine::ConvState;
+
+/// Fallback re-check interval in case a broadcast is missed.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const USAGE: &str = "\
+Usage: phoenix-ide run --prompt TEXT [options]
+
+Options:
+  --prompt TEXT     Task for the agent (required)
+  --cwd DIR         Working directory (default: current directory)
+  --max-turns N     Stop after N model requests
+  --model ID        Model to use
+  --mode MODE       Conversation mode (\"direct\" or \"managed\")
+  --report PATH     Where to write the JSON report";
+
+/// Parsed `phoenix-ide run` arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunArgs {
+    pub prompt: String,
+    pub cwd: PathBuf,
+    pub max_turns: Option<u32>,
+    pub model: Option<String>,
+    pub mode: Option<String>,
+    pub report: Option<PathBuf>,
+}
+
+impl RunArgs {
+    /// Parse the process arguments (without the program name). Returns
+    /// `Ok(None)` when the first argument is not `run`, i.e. this is a
+    /// normal server start.
+    pub fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let Some((first, rest)) = args.split_first() else {
+            return Ok(None);
+        };
+        if first != "run" {
+            return Ok(None);
+        }
+        let mut prompt = None;
+        let mut run = Self {
+            prompt: String::new(),
+            cwd: PathBuf::from("."),
+            max_turns: None,
+            model: None,
+            mode: None,
+            report: None,
+        };
+        let mut iter = rest.iter();
+        while let Some(arg) = iter.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            if matches!(flag, "-h" | "--help") {
+                return Err(USAGE.to_string());
+            }
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| iter.next().cloned())
+                    .ok_or_else(|| format!("{flag} needs a value\n\n{USAGE}"))
+            };
+            match flag {
+                "--prompt" | "-p" => prompt = Some(value()?),
+                "--cwd" | "-d" => run.cwd = PathBuf::from(value()?),
+                "--max-turns" => {
+                    let raw = value()?;
+                    let n = raw
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("invalid --max-turns: {raw}"))?;
+                    run.max_turns = Some(n);
+                }
+                "--model" | "-m" => run.model = Some(value()?),
+                "--mode" => run.mode = Some(value()?),
+                "--report" => run.report = Some(PathBuf::from(value()?)),
+                _ => return Err(format!("unknown argument: {arg}\n\n{USAGE}")),
+            }
+        }
+        run.prompt = prompt
+            .filter(|p| !p.trim().is_empty())
+            .ok_or_else(|| format!("--prompt is required\n\n{USAGE}"))?;
+        Ok(Some(run))
+    }
+}
+
+/// How a run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Completed,
+    Error,
+    Failed,
+    TurnLimit,
+    NeedsInput,
+}
+
+impl RunStatus {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            RunStatus::Completed => 0,
+            RunStatus::Error | RunStatus::Failed => 1,
+            RunStatus::TurnLimit | RunStatus::NeedsInput => 3,
+        }
+    }
+
+    /// `None` while the agent still has work in flight. A fresh conversation
+    /// is `Idle` until the runtime picks up the first message, so `Idle`
+    /// only counts once an agent message exists.
+    fn settled(state: &ConvState, agent_replied: bool) -> Option<Self> {
+        match state {
+            ConvState::Idle => agent_replied.then_some(RunStatus::Completed),
+            ConvState::Completed { .. } => Some(RunStatus::Completed),
+            ConvState::Error { .. } => Some(RunStatus::Error),
+            ConvState::Failed { .. } | ConvState::ContextExhausted { .. } | ConvState::Terminal => {
+                Some(RunStatus::Failed)
+            }
+            ConvState::AwaitingUserGuidance { .. } => Some(RunStatus::TurnLimit),
+            ConvState::AwaitingTaskApproval { .. }
+            | ConvState::AwaitingUserResponse { .. }
+            | ConvState::AwaitingUserInput { .. }
+            | ConvState::AwaitingCouncilChoice { .. }
+            | ConvState::AwaitingPatchReview { .. } => Some(RunStatus::NeedsInput),
+            _ => None,
+        }
+    }
+}
+
+/// The JSON report written at the end of a run.
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub status: RunStatus,
+    pub conversation_id: String,
+    pub slug: Option<String>,
+    pub cwd: String,
+    /// Text of the last agent message
+    pub final_response: String,
+    /// Error or pause reason, when the run did not complete
+    pub error: Option<String>,
+    /// Model requests made (one per agent message)
+    pub turns: u32,
+    pub tool_calls: u32,
+    pub usage: UsageData,
+    pub duration_ms: u64,
+}
+
+impl RunReport {
+    fn new(
+        status: RunStatus,
+        conversation: &Conversation,
+        messages: &[Message],
+        started: Instant,
+    ) -> Self {
+        let mut report = Self {
+            status,
+            conversation_id: conversation.id.clone(),
+            slug: conversation.slug.clone(),
+            cwd: conversation.cwd.clone(),
+            final_response: String::new(),
+            error: None,
+            turns: 0,
+            tool_calls: 0,
+            usage: UsageData::default(),
+            duration_ms: u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX),
+        };
+        for message in messages {
+            if let Some(usage) = &message.usage_data {
+                report.usage.input_tokens += usage.input_tokens;
+                report.usage.output_tokens += usage.output_tokens;
+                report.usage.cache_creation_tokens += usage.cache_creation_tokens;
+                report.usage.cache_read_tokens += usage.cache_read_tokens;
+            }
+            match &message.content {
+                MessageContent::Agent(blocks) => {
+                    report.turns += 1;
+                    let text: Vec<&str> = blocks
+                        .iter()
+                        .filter_map(|b| match b {
+                            ContentBlock::Text { text } => Some(text.as_str()),
+                            ContentBlock::ToolUse { .. } => {
+                                report.tool_calls += 1;
+                                None
+                            }
+                            _ => None,
+                        })
+                        .collect();
+                    if !text.is_empty() {
+                        report.final_response = text.join("\n");
+                    }
+                }
+                MessageContent::Error(err) => report.error = Some(err.message.clone()),
+                _ => {}
+            }
+        }
+        report.error = match &conversation.state {
+            ConvState::Error { message, .. } => Some(message.clone()),
+            ConvState::Failed { error, .. } => Some(error.clone()),
+            ConvState::AwaitingUserGuidance { reason } => Some(reason.clone()),
+            ConvState::Idle | ConvState::Completed { .. } => None,
+            _ => report.error,
+        };
+        report
+    }
+}
+
+/// Run one task to completion and return the process exit code.
+pub async fn run_headless(state: &AppState, args: &RunArgs, data_dir: &Path) -> i32 {
+    match run(state, args, data_dir).await {
+        Ok(status) => status.exit_code(),
+        Err(e) => {
+            eprintln!("phoenix-ide run: {e}");
+            2
+        }
+    }
+}
+
+async fn run(state: &AppState, args: &RunArgs, data_dir: &Path) -> Result<RunStatus, String> {
+    let started = Instant::now();
+    let id = start_task(state, args).await?;
+    let (report, _) = wait_until_settled(state, &id, 0, started).await?;
+
+    let path = args
+        .report
+        .clone()
+        .unwrap_or_else(|| data_dir.join("runs").join(format!("{id}.json")));
+    write_report(&path, &report).map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+    println!("{}", report.final_response);
+    eprintln!(
+        "phoenix-ide run: {} after {} turn(s); report at {}",
+        serde_json::to_value(report.status).unwrap_or_default(),
+        report.turns,
+        path.display()
+    );
+    Ok(report.status)
+}
+
+/// Create the conversation for `args` with its prompt as the first message.
+/// Returns the conversation id.
+pub async fn start_task(state: &AppState, args: &RunArgs) -> Result<String, String> {
+    let cwd = std::fs::canonicalize(&args.cwd)
+        .map_err(|e| format!("cannot use {}: {e}", args.cwd.display()))?;
+    let Json(created) = create_conversation(
+        State(state.clone()),
+        Json(CreateConversationRequest {
+            cwd: cwd.display().to_string(),
+            model: args.model.clone(),
+            text: args.prompt.clone(),
+            message_id: uuid::Uuid::new_v4().to_string(),
+            images: Vec::new(),
+            mode: args.mode.clone(),
+            base_branch: None,
+            seed_parent_id: None,
+            seed_label: None,
+            template: None,
+            disabled_tools: Vec::new(),
+        }),
+    )
+    .await
+    .map_err(app_error_message)?;
+    created.conversation["id"]
+        .as_str()
+        .map(ToString::to_string)
+        .ok_or_else(|| "created conversation has no id".to_string())
+}
+
+/// Send another user message to a settled conversation, through the same
+/// handler as `POST /api/conversations/:id/chat`.
+pub async fn send_follow_up(state: &AppState, id: &str, text: &str) -> Result<(), String> {
+    send_chat(
+        State(state.clone()),
+        AxumPath(id.to_string()),
+        Json(ChatRequest {
+            text: text.to_string(),
+            message_id: uuid::Uuid::new_v4().to_string(),
+            images: Vec::new(),
+            user_agent: None,
+            client_id: None,
+            council: Vec::new(),
+        }),
+    )
+    .await
+    .map(|_| ())
+    .map_err(app_error_message)
+}
+
+/// Wait until the agent has answered a message newer than `after_sequence`
+/// and the conversation has settled. Returns the report over the whole
+/// conversation and its messages.
+pub async fn wait_until_settled(
+    state: &AppState,
+    id: &str,
+    after_sequence: i64,
+    started: Instant,
+) -> Result<(RunReport, Vec<Message>), String> {
+    let handle = state.runtime.get_or_create(id).await?;
+    let mut rx = handle.broadcast_tx.subscribe();
+    loop {
+        let conversation = state
+            .db
+            .get_conversation(id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let messages = state.db.get_messages(id).await.map_err(|e| e.to_string())?;
+        let agent_replied = messages.iter().any(|m| {
+            m.sequence_id > after_sequence && matches!(m.content, MessageContent::Agent(_))
+        });
+        if let Some(status) = RunStatus::settled(&conversation.state, agent_replied) {
+            let report = RunReport::new(status, &conversation, &messages, started);
+            return Ok((report, messages));
+        }
+        // Any event may be the state change we are waiting for; the timeout
+        // covers a closed or lagged channel.
+        if let Ok(Err(tokio::sync::broadcast::error::RecvError::Closed)) =
+            tokio::time::timeout(POLL_INTERVAL, rx.recv()).await
+        {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+fn write_report(path: &Path, report: &RunReport) -> std::io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_vec_pretty(report).map_err(std::io::Error::other)?;
+    std::fs::write(path, json)
+}
+
+fn app_error_message(err: AppError) -> String {
+    match err {
+        AppError::BadRequest(msg)
+        | AppError::NotFound(msg)
+        | AppError::Internal(msg)
+        | AppError::SandboxViolation(msg) => msg,
+        AppError::Database(e) => e.to_string(),
+        AppError::Llm(e) => e.to_string(),
+        AppError::Conflict(detail) => detail.error,
+        AppError::UnprocessableEntity(detail) => detail.error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::ErrorKind;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn parses_run_arguments() {
+        let parsed = RunArgs::parse(&args(&[
+            "run",
+            "--cwd",
+            "/repo",
+            "--prompt=fix the flaky test",
+            "--max-turns",
+            "20",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(parsed.prompt, "fix the flaky test");
+        assert_eq!(parsed.cwd, PathBuf::from("/repo"));
+        assert_eq!(parsed.max_turns, Some(20));
+        assert_eq!(parsed.report, None);
+
+        assert_eq!(RunArgs::parse(&args(&["--restore-backup", "x"])), Ok(None));
+        assert!(RunArgs::parse(&args(&["run", "--cwd", "."])).is_err());
+        assert!(RunArgs::parse(&args(&["run", "--prompt", "x", "--max-turns", "0"])).is_err());
+        assert!(RunArgs::parse(&args(&["run", "--prompt", "x", "--verbose"])).is_err());
+    }
+
+    #[test]
+    fn settled_states_map_to_exit_codes() {
+        assert_eq!(RunStatus::settled(&ConvState::Idle, false), None);
+        assert_eq!(
+            RunStatus::settled(&ConvState::LlmRequesting { attempt: 1 }, true),
+            None
+        );
+
+        let cases = [
+            (ConvState::Idle, 0),
+            (
+                ConvState::Error {
+                    message: "boom".to_string(),
+                    error_kind: ErrorKind::Network,
+                },
+                1,
+            ),
+            (ConvState::Terminal, 1),
+            (
+                ConvState::AwaitingUserGuidance {
+                    reason: "made 5 model requests this turn".to_string(),
+                },
+                3,
+            ),
+        ];
+        for (state, code) in cases {
+            let status = RunStatus::settled(&state, true).unwrap();
+            assert_eq!(status.exit_code(), code, "{state:?}");
+        }
+    }
+}
@@ -59,12 +59,7 @@ pub(crate) async fn approve_task(
     Path(id): Path<String>,
 ) -> Result<Json<TaskApprovalResponse>, AppError> {
     // 1. Validate conversation exists and is in AwaitingTaskApproval state
-    let conv = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conv = state.runtime.db().get_conversation(&id).await?;
 
     if !matches!(conv.state, ConvState::AwaitingTaskApproval { .. }) {
         return Err(AppError::BadRequest(
@@ -102,12 +97,7 @@ pub(crate) async fn reject_task(
     Path(id): Path<String>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     // Validate conversation exists and is in AwaitingTaskApproval state
-    let conv = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conv = state.runtime.db().get_conversation(&id).await?;
 
     if !matches!(conv.state, ConvState::AwaitingTaskApproval { .. }) {
         return Err(AppError::BadRequest(
@@ -135,12 +125,7 @@ pub(crate) async fn task_feedback(
     Json(req): Json<TaskFeedbackRequest>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     // Validate conversation exists and is in AwaitingTaskApproval state
-    let conv = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conv = state.runtime.db().get_conversation(&id).await?;
 
     if !matches!(conv.state, ConvState::AwaitingTaskApproval { .. }) {
         return Err(AppError::BadRequest(
@@ -177,12 +162,7 @@ pub(crate) async fn abandon_task(
     Path(id): Path<String>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     // 1. Validate conversation exists, is Work or Branch mode, Idle state, project-scoped
-    let conv = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conv = state.runtime.db().get_conversation(&id).await?;
 
     // REQ-BED-031: reject if the conversation has already been continued.
     // The live conversation is the continuation; terminal actions belong there.
@@ -193,10 +173,7 @@ pub(crate) async fn abandon_task(
     // "user is done; tear it down" path — the gate above already ensured
     // no continuation exists, so the worktree/branch are still ours to
     // destroy.
-    if !matches!(
-        conv.state,
-        ConvState::Idle | ConvState::ContextExhausted { .. },
-    ) {
+    if !conv.state.is_idle() && !matches!(conv.state, ConvState::ContextExhausted { .. }) {
         return Err(AppError::BadRequest(
             "Conversation must be idle or context-exhausted to abandon a task".to_string(),
         ));
@@ -241,11 +218,7 @@ pub(crate) async fn abandon_task(
         .as_deref()
         .ok_or_else(|| AppError::BadRequest("Conversation is not project-scoped".to_string()))?;
 
-    let project = state
-        .db
-        .get_project(project_id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let project = state.db.get_project(project_id).await?;
     let repo_root = PathBuf::from(&project.canonical_path);
 
     // 2a. Capture diff snapshot from worktree BEFORE deleting it (blocking).
@@ -471,12 +444,7 @@ pub(crate) async fn mark_merged(
     Path(id): Path<String>,
 ) -> Result<Json<SuccessResponse>, AppError> {
     // 1. Validate conversation exists, is Work or Branch mode, Idle state
-    let conv = state
-        .runtime
-        .db()
-        .get_conversation(&id)
-        .await
-        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    let conv = state.runtime.db().get_conversation(&id).await?;
 
     // REQ-BED-031: reject if the conversation has already been continued.
     // The live conversation is the continuation; terminal actions belong there.
@@ -486,10 +454,7 @@ pub(crate) async fn mark_merged(
     // ContextExhausted. A context-exhausted parent whose work has already
     // been merged (e.g. user committed and merged externally) needs a way
     // to dispose of the worktree without forcing a continuation first.
-    if !matches!(
-        conv.state,
-        ConvState::Idle | ConvState::ContextExhausted { .. },
-    ) {
+    if !conv.state.is_idle() && !matches!(conv.state, ConvState::ContextExhausted { .. }) {
         return Err(AppError::BadRequest(
             "Conversation must be idle or context-exhausted to mark as merged".to_string(),
         ));
@@ -518,11 +483,7 @@ pub(crate) async fn mark_merged(
         .as_deref()
         .ok_or_else(|| AppError::BadRequest("Conversation is not project-scoped".to_string()))?;
 
-    let project = state
-        .db
-        .get_project(project_id)
-        .await
-        .map_err(|e| AppError::Internal(e.to_string()))?;
+    let project = state.db.get_project(project_id).await?;
     let repo_root = PathBuf::from(&project.canonical_path);
     let repo_root_str = repo_root.display().to_string();
 
@@ -616,7 +577,7 @@ mod tests {
     //! not currently exist in the repo (Phase 2 tested `Database::
     //! continue_conversation` at the DB layer for the same reason).
     use super::*;
-    use crate::db::{ConvMode, Conversation, NonEmptyString};
+    use crate::db::{ConvMode, Conversation, HistoryWindow, NonEmptyString};
     use crate::state_machine::state::ConvState;
     use chrono::{TimeZone, Utc};
 
@@ -649,6 +610,11 @@ mod tests {
             seed_label: None,
             continued_in_conv_id,
             chain_name: None,
+            thinking_budget: None,
+            template: None,
+            disabled_tools: Vec::new(),
+            patch_review: false,
+            history_window: HistoryWindow::Full,
         }
     }
 
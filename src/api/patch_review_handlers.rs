@@ -0,0 +1,120 @@
+//! Patch review HTTP handlers (REQ-PATCH-010): toggle review mode, list the
+//! staged patch, and apply or reject it.
+
+use super::handlers::AppError;
+use super::types::{
+    ConflictErrorResponse, PendingPatch, PendingPatchesResponse, SetPatchReviewRequest,
+    SuccessResponse,
+};
+use super::AppState;
+use crate::state_machine::{ConvState, Event};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+/// Turn patch review on or off. Requires the conversation to be idle, like
+/// the tool selection: the setting is read when the runtime is created.
+pub(crate) async fn set_patch_review(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<SetPatchReviewRequest>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    let conv = state.runtime.db().get_conversation(&id).await?;
+    if conv.parent_conversation_id.is_some() {
+        return Err(AppError::BadRequest(
+            "Sub-agents write patches directly".to_string(),
+        ));
+    }
+    if !conv.state.is_idle() {
+        return Err(AppError::BadRequest(
+            "Conversation must be idle to change patch review".to_string(),
+        ));
+    }
+
+    state
+        .runtime
+        .db()
+        .set_patch_review(&id, req.enabled)
+        .await?;
+
+    // Evict the active runtime so it gets recreated with the new setting
+    state.runtime.evict_runtime(&id).await;
+
+    tracing::info!(conv_id = %id, enabled = req.enabled, "Patch review set");
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// Patches waiting for the user. At most one: the tool round pauses on it.
+pub(crate) async fn list_pending_patches(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<PendingPatchesResponse>, AppError> {
+    let conv = state.runtime.db().get_conversation(&id).await?;
+
+    let patches = match conv.state {
+        ConvState::AwaitingPatchReview {
+            patch,
+            current_tool,
+            ..
+        } => vec![PendingPatch {
+            tool_use_id: current_tool.id,
+            path: patch.path,
+            diff: patch.diff,
+        }],
+        _ => Vec::new(),
+    };
+
+    Ok(Json(PendingPatchesResponse { patches }))
+}
+
+pub(crate) async fn apply_pending_patch(
+    State(state): State<AppState>,
+    Path((id, tool_use_id)): Path<(String, String)>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    respond_to_patch(&state, &id, tool_use_id, true).await
+}
+
+pub(crate) async fn reject_pending_patch(
+    State(state): State<AppState>,
+    Path((id, tool_use_id)): Path<(String, String)>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    respond_to_patch(&state, &id, tool_use_id, false).await
+}
+
+async fn respond_to_patch(
+    state: &AppState,
+    id: &str,
+    tool_use_id: String,
+    approved: bool,
+) -> Result<Json<SuccessResponse>, AppError> {
+    let conv = state.runtime.db().get_conversation(id).await?;
+
+    let ConvState::AwaitingPatchReview { current_tool, .. } = &conv.state else {
+        return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+            "Conversation is not awaiting patch review",
+            "wrong_state",
+        ))));
+    };
+    if current_tool.id != tool_use_id {
+        return Err(AppError::NotFound(format!(
+            "No pending patch for tool call {tool_use_id}"
+        )));
+    }
+
+    state
+        .runtime
+        .send_event(
+            id,
+            Event::PatchReviewResponse {
+                tool_use_id,
+                approved,
+            },
+        )
+        .await
+        .map_err(AppError::BadRequest)?;
+
+    Ok(Json(SuccessResponse { success: true }))
+}
@@ -0,0 +1,111 @@
+//! LLM provider keys (REQ-LLM-019): set or drop a provider's API key on a
+//! running server. A key is checked against the provider before it is
+//! stored (sealed, REQ-BED-051), and the model registry reloads so the
+//! provider's models are available at once. Conversations pick up the new
+//! models on their next request; a request already in flight finishes with
+//! the key it started with.
+
+use super::handlers::AppError;
+use super::types::{ProviderKeyRequest, ProviderKeyResponse};
+use super::AppState;
+use crate::llm::{check_api_key, Provider};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+/// Check, store, and apply a provider key.
+pub(super) async fn set_provider_key(
+    State(state): State<AppState>,
+    Json(req): Json<ProviderKeyRequest>,
+) -> Result<Json<ProviderKeyResponse>, AppError> {
+    let provider = parse_provider(&req.provider).map_err(AppError::BadRequest)?;
+    let api_key = req.api_key.trim();
+    if api_key.is_empty() {
+        return Err(AppError::BadRequest(
+            "api_key must not be empty".to_string(),
+        ));
+    }
+    let config = state.llm_registry.config();
+    if config.gateway.is_some() || config.credential_helper.is_some() {
+        return Err(AppError::BadRequest(
+            "Credentials come from the gateway or credential helper; provider keys are not used"
+                .to_string(),
+        ));
+    }
+    let base_url = match provider {
+        Provider::Anthropic => config.anthropic_base_url.as_deref(),
+        _ => config.openai_base_url.as_deref(),
+    };
+    check_api_key(provider, api_key, base_url, &config.custom_headers)
+        .await
+        .map_err(|e| AppError::BadRequest(format!("Key check failed: {e}")))?;
+
+    state
+        .db
+        .set_provider_key(provider.header_value(), api_key, chrono::Utc::now())
+        .await?;
+    reload_registry(&state).await?;
+    tracing::info!(provider = provider.header_value(), "Provider key set");
+    Ok(Json(ProviderKeyResponse {
+        provider: provider.header_value().to_string(),
+        models: state.llm_registry.available_models(),
+    }))
+}
+
+/// Drop a stored provider key. The provider falls back to its environment
+/// key, if any.
+pub(super) async fn delete_provider_key(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+) -> Result<Json<ProviderKeyResponse>, AppError> {
+    let provider = parse_provider(&provider).map_err(AppError::BadRequest)?;
+    let removed = state
+        .db
+        .delete_provider_key(provider.header_value())
+        .await?;
+    if !removed {
+        return Err(AppError::NotFound(format!(
+            "No stored key for {}",
+            provider.header_value()
+        )));
+    }
+    reload_registry(&state).await?;
+    tracing::info!(provider = provider.header_value(), "Provider key removed");
+    Ok(Json(ProviderKeyResponse {
+        provider: provider.header_value().to_string(),
+        models: state.llm_registry.available_models(),
+    }))
+}
+
+/// Rebuild the registry with every stored key.
+async fn reload_registry(state: &AppState) -> Result<(), AppError> {
+    let keys = state.db.list_provider_keys().await?;
+    state.llm_registry.reload_with_keys(&keys).await;
+    Ok(())
+}
+
+/// The providers that take an API key.
+fn parse_provider(name: &str) -> Result<Provider, String> {
+    match name.trim().to_ascii_lowercase().as_str() {
+        "anthropic" => Ok(Provider::Anthropic),
+        "openai" => Ok(Provider::OpenAI),
+        other => Err(format!(
+            "Unknown provider: {other} (expected anthropic or openai)"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_keyed_providers_parse() {
+        assert_eq!(parse_provider("anthropic"), Ok(Provider::Anthropic));
+        assert_eq!(parse_provider(" OpenAI "), Ok(Provider::OpenAI));
+        let err = parse_provider("mock").unwrap_err();
+        assert!(err.contains("expected anthropic or openai"), "{err}");
+    }
+}
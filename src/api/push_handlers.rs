@@ -0,0 +1,152 @@
+//! Web Push HTTP handlers (REQ-API-024): hand out the VAPID key, store and
+//! drop browser subscriptions, and opt conversations in or out.
+
+use super::handlers::AppError;
+use super::types::{
+    ConversationPushSetting, PushKeyResponse, PushSubscribeRequest, PushUnsubscribeRequest,
+    SuccessResponse,
+};
+use super::AppState;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+/// Decoded length of a subscription's `p256dh` key: an uncompressed P-256
+/// point.
+const P256DH_BYTES: usize = 65;
+
+/// Decoded length of a subscription's `auth` secret.
+const AUTH_BYTES: usize = 16;
+
+/// The VAPID public key browsers pass to `pushManager.subscribe`.
+pub(super) async fn get_push_key(
+    State(state): State<AppState>,
+) -> Result<Json<PushKeyResponse>, AppError> {
+    let public_key = state
+        .runtime
+        .push()
+        .public_key()
+        .await
+        .map_err(AppError::Internal)?;
+    Ok(Json(PushKeyResponse { public_key }))
+}
+
+/// Store a browser's subscription. Subscribing again replaces its keys.
+pub(super) async fn subscribe_push(
+    State(state): State<AppState>,
+    Json(req): Json<PushSubscribeRequest>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    validate_subscription(&req).map_err(AppError::BadRequest)?;
+    state
+        .db
+        .upsert_push_subscription(
+            &req.endpoint,
+            &req.keys.p256dh,
+            &req.keys.auth,
+            chrono::Utc::now(),
+        )
+        .await?;
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// Drop a subscription, e.g. when the user turns notifications off in this
+/// browser.
+pub(super) async fn unsubscribe_push(
+    State(state): State<AppState>,
+    Json(req): Json<PushUnsubscribeRequest>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    let removed = state.db.delete_push_subscription(&req.endpoint).await?;
+    if !removed {
+        return Err(AppError::NotFound("Unknown push subscription".to_string()));
+    }
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+/// Whether the conversation notifies push subscribers.
+pub(super) async fn get_conversation_push(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ConversationPushSetting>, AppError> {
+    state.db.get_conversation(&id).await?;
+    let enabled = state.db.is_push_enabled(&id).await?;
+    Ok(Json(ConversationPushSetting { enabled }))
+}
+
+/// Opt the conversation in to or out of push notifications. Read on every
+/// turn ending, so unlike most settings it applies to a running turn too.
+pub(super) async fn set_conversation_push(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<ConversationPushSetting>,
+) -> Result<Json<ConversationPushSetting>, AppError> {
+    let conv = state.db.get_conversation(&id).await?;
+    if conv.parent_conversation_id.is_some() {
+        return Err(AppError::BadRequest(
+            "Sub-agents report to their parent, not to push subscribers".to_string(),
+        ));
+    }
+    state
+        .db
+        .set_push_enabled(&id, req.enabled, chrono::Utc::now())
+        .await?;
+    tracing::info!(conv_id = %id, enabled = req.enabled, "Push notifications set");
+    Ok(Json(ConversationPushSetting {
+        enabled: req.enabled,
+    }))
+}
+
+/// Push services are public HTTPS endpoints, and the keys have fixed sizes.
+/// Checking up front keeps junk out of the table and the server from
+/// posting to arbitrary URLs.
+fn validate_subscription(req: &PushSubscribeRequest) -> Result<(), String> {
+    let endpoint =
+        reqwest::Url::parse(&req.endpoint).map_err(|e| format!("Invalid endpoint: {e}"))?;
+    if endpoint.scheme() != "https" {
+        return Err("Push endpoint must be an https URL".to_string());
+    }
+    let decoded_len = |key: &str| {
+        URL_SAFE_NO_PAD
+            .decode(key.trim_end_matches('='))
+            .map(|bytes| bytes.len())
+            .ok()
+    };
+    if decoded_len(&req.keys.p256dh) != Some(P256DH_BYTES) {
+        return Err("keys.p256dh must be a base64url P-256 public key".to_string());
+    }
+    if decoded_len(&req.keys.auth) != Some(AUTH_BYTES) {
+        return Err("keys.auth must be a base64url 16-byte secret".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::types::PushSubscriptionKeys;
+
+    fn request(endpoint: &str, p256dh_bytes: usize, auth_bytes: usize) -> PushSubscribeRequest {
+        PushSubscribeRequest {
+            endpoint: endpoint.to_string(),
+            keys: PushSubscriptionKeys {
+                p256dh: URL_SAFE_NO_PAD.encode(vec![4; p256dh_bytes]),
+                auth: URL_SAFE_NO_PAD.encode(vec![1; auth_bytes]),
+            },
+        }
+    }
+
+    #[test]
+    fn subscriptions_are_validated() {
+        let endpoint = "https://fcm.googleapis.com/fcm/send/abc";
+        assert!(validate_subscription(&request(endpoint, 65, 16)).is_ok());
+
+        let err = validate_subscription(&request("http://10.0.0.1/x", 65, 16)).unwrap_err();
+        assert!(err.contains("https"), "{err}");
+        assert!(validate_subscription(&request("not a url", 65, 16)).is_err());
+        assert!(validate_subscription(&request(endpoint, 33, 16)).is_err());
+        assert!(validate_subscription(&request(endpoint, 65, 8)).is_err());
+    }
+}
@@ -0,0 +1,499 @@
+//! Request rate limiting (REQ-AUTH-009)
+//!
+//! Optional per-IP and per-token limits on request rate and concurrent SSE
+//! streams, for instances reachable beyond localhost. Configured through
+//! environment variables; when none is set the layer is not installed:
+//!
+//! - `PHOENIX_RATE_LIMIT_IP_RPM` / `PHOENIX_RATE_LIMIT_TOKEN_RPM` — requests per minute
+//! - `PHOENIX_RATE_LIMIT_IP_STREAMS` / `PHOENIX_RATE_LIMIT_TOKEN_STREAMS` — open SSE streams
+//!
+//! Only `/api/` requests count; the SPA shell and static assets do not. A
+//! "token" is whatever credential the request carries: the password cookie
+//! or Bearer token, or the share token in a share URL. Over-limit requests
+//! get `429 Too Many Requests` with a `Retry-After` header.
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{header, Request, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use futures::future::BoxFuture;
+use futures::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::{Layer, Service};
+
+use super::types::{ErrorCode, ErrorResponse};
+
+/// Retry-After for a rejected stream. Streams free up when a client
+/// disconnects, not on a clock, so this is only a polite back-off.
+const STREAM_RETRY_AFTER: Duration = Duration::from_secs(5);
+/// Idle buckets are pruned once the table grows past this many keys.
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Limits read from the environment. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    pub ip_requests_per_minute: Option<u32>,
+    pub token_requests_per_minute: Option<u32>,
+    pub ip_max_streams: Option<u32>,
+    pub token_max_streams: Option<u32>,
+}
+
+impl RateLimitConfig {
+    /// Read limits from `PHOENIX_RATE_LIMIT_*`. Returns `None` when no limit
+    /// is configured. Zero or unparseable values are treated as unset.
+    pub fn from_env() -> Option<Self> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let limit = |name: &str| {
+            let raw = lookup(name)?;
+            match raw.trim().parse::<u32>() {
+                Ok(0) => None,
+                Ok(n) => Some(n),
+                Err(_) => {
+                    tracing::warn!(var = name, value = %raw, "Ignoring invalid rate limit");
+                    None
+                }
+            }
+        };
+        let config = Self {
+            ip_requests_per_minute: limit("PHOENIX_RATE_LIMIT_IP_RPM"),
+            token_requests_per_minute: limit("PHOENIX_RATE_LIMIT_TOKEN_RPM"),
+            ip_max_streams: limit("PHOENIX_RATE_LIMIT_IP_STREAMS"),
+            token_max_streams: limit("PHOENIX_RATE_LIMIT_TOKEN_STREAMS"),
+        };
+        (config != Self::default()).then_some(config)
+    }
+}
+
+/// Who a limit applies to. Tokens are hashed so credentials are never held
+/// in the limiter's tables.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ClientKey {
+    Ip(IpAddr),
+    Token([u8; 32]),
+}
+
+impl ClientKey {
+    fn token(token: &str) -> Self {
+        Self::Token(Sha256::digest(token.as_bytes()).into())
+    }
+}
+
+/// One key plus the limits that apply to it.
+#[derive(Debug, Clone)]
+struct Scope {
+    key: ClientKey,
+    requests_per_minute: Option<u32>,
+    max_streams: Option<u32>,
+}
+
+/// Token bucket holding up to a minute's worth of requests.
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(rpm: u32, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(rpm),
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, rpm: u32, now: Instant) {
+        let per_sec = f64::from(rpm) / 60.0;
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_sec).min(f64::from(rpm));
+        self.updated = now;
+    }
+
+    /// Time until one whole token is available.
+    fn wait(&self, rpm: u32) -> Duration {
+        let per_sec = f64::from(rpm) / 60.0;
+        Duration::from_secs_f64(((1.0 - self.tokens) / per_sec).max(0.0))
+    }
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    buckets: HashMap<ClientKey, Bucket>,
+    streams: HashMap<ClientKey, u32>,
+}
+
+#[derive(Debug)]
+struct RateLimiter {
+    config: RateLimitConfig,
+    state: Mutex<LimiterState>,
+}
+
+impl RateLimiter {
+    fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::new(LimiterState::default()),
+        }
+    }
+
+    fn scopes(&self, req: &Request<Body>) -> Vec<Scope> {
+        let config = &self.config;
+        let mut scopes = Vec::with_capacity(2);
+        if config.ip_requests_per_minute.is_some() || config.ip_max_streams.is_some() {
+            if let Some(ConnectInfo(addr)) = req.extensions().get::<ConnectInfo<SocketAddr>>() {
+                scopes.push(Scope {
+                    key: ClientKey::Ip(addr.ip()),
+                    requests_per_minute: config.ip_requests_per_minute,
+                    max_streams: config.ip_max_streams,
+                });
+            }
+        }
+        if config.token_requests_per_minute.is_some() || config.token_max_streams.is_some() {
+            if let Some(token) = request_token(req) {
+                scopes.push(Scope {
+                    key: ClientKey::token(token),
+                    requests_per_minute: config.token_requests_per_minute,
+                    max_streams: config.token_max_streams,
+                });
+            }
+        }
+        scopes
+    }
+
+    /// Admit a request against every scope, or return how long to wait.
+    ///
+    /// All scopes are checked before any is charged, so a request rejected
+    /// by one limit does not consume another's budget.
+    fn admit(&self, scopes: &[Scope], is_stream: bool, now: Instant) -> Result<(), Duration> {
+        let mut guard = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let state = &mut *guard;
+        if state.buckets.len() > PRUNE_THRESHOLD {
+            // A bucket idle for a minute has refilled completely, so
+            // dropping it is indistinguishable from keeping it.
+            state
+                .buckets
+                .retain(|_, b| now.saturating_duration_since(b.updated) < Duration::from_secs(60));
+        }
+
+        let mut retry_after = None;
+        for scope in scopes {
+            if let Some(rpm) = scope.requests_per_minute {
+                let bucket = state
+                    .buckets
+                    .entry(scope.key.clone())
+                    .or_insert_with(|| Bucket::full(rpm, now));
+                bucket.refill(rpm, now);
+                if bucket.tokens < 1.0 {
+                    retry_after = retry_after.max(Some(bucket.wait(rpm)));
+                }
+            }
+            if let (true, Some(max)) = (is_stream, scope.max_streams) {
+                if state.streams.get(&scope.key).copied().unwrap_or(0) >= max {
+                    retry_after = retry_after.max(Some(STREAM_RETRY_AFTER));
+                }
+            }
+        }
+        if let Some(wait) = retry_after {
+            return Err(wait);
+        }
+
+        for scope in scopes {
+            if let Some(bucket) = state.buckets.get_mut(&scope.key) {
+                bucket.tokens -= 1.0;
+            }
+            if is_stream && scope.max_streams.is_some() {
+                *state.streams.entry(scope.key.clone()).or_default() += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn release_streams(&self, keys: &[ClientKey]) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        for key in keys {
+            if let Some(count) = state.streams.get_mut(key) {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    state.streams.remove(key);
+                }
+            }
+        }
+    }
+}
+
+/// Stream slots held by one open SSE response; released on drop.
+struct StreamPermit {
+    limiter: Arc<RateLimiter>,
+    keys: Vec<ClientKey>,
+}
+
+impl Drop for StreamPermit {
+    fn drop(&mut self) {
+        self.limiter.release_streams(&self.keys);
+    }
+}
+
+/// Response body stream that keeps its [`StreamPermit`] until the client
+/// disconnects and the body is dropped.
+struct PermitStream<S> {
+    inner: S,
+    _permit: StreamPermit,
+}
+
+impl<S: Stream + Unpin> Stream for PermitStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+/// The credential a request carries, if any: share token from the path,
+/// Bearer token, or auth cookie.
+fn request_token(req: &Request<Body>) -> Option<&str> {
+    if let Some(rest) = req.uri().path().strip_prefix("/api/share/") {
+        return rest.split('/').next().filter(|t| !t.is_empty());
+    }
+    let headers = req.headers();
+    if let Some(token) = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+    {
+        return Some(token);
+    }
+    headers
+        .get(header::COOKIE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(super::auth::extract_cookie_value)
+}
+
+fn is_event_stream(req: &Request<Body>) -> bool {
+    let path = req.uri().path();
+    path.ends_with("/stream")
+        || path.ends_with("/events")
+        || req
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.contains("text/event-stream"))
+}
+
+fn too_many_requests(wait: Duration) -> Response {
+    // Round up: a client retrying after the advertised delay must succeed.
+    let secs = (wait.as_secs() + u64::from(wait.subsec_nanos() > 0)).max(1);
+    let mut body = ErrorResponse::new(ErrorCode::RateLimited, "Rate limit exceeded");
+    body.retry_after_ms = Some(secs * 1000);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, secs.to_string())],
+        Json(body),
+    )
+        .into_response()
+}
+
+/// Tower layer enforcing [`RateLimitConfig`]. Needs `ConnectInfo<SocketAddr>`
+/// in request extensions for per-IP limits; requests without it are only
+/// limited per token.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimitLayer {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            limiter: Arc::new(RateLimiter::new(config)),
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            limiter: Arc::clone(&self.limiter),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response>,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if !req.uri().path().starts_with("/api/") {
+            return Box::pin(self.inner.call(req));
+        }
+
+        let scopes = self.limiter.scopes(&req);
+        let is_stream = is_event_stream(&req);
+        if let Err(wait) = self.limiter.admit(&scopes, is_stream, Instant::now()) {
+            tracing::debug!(path = %req.uri().path(), retry_after = ?wait, "429 Too Many Requests");
+            return Box::pin(async move { Ok(too_many_requests(wait)) });
+        }
+
+        let permit = is_stream.then(|| StreamPermit {
+            limiter: Arc::clone(&self.limiter),
+            keys: scopes
+                .into_iter()
+                .filter(|s| s.max_streams.is_some())
+                .map(|s| s.key)
+                .collect(),
+        });
+        let response = self.inner.call(req);
+        Box::pin(async move {
+            let response = response.await?;
+            Ok(match permit {
+                Some(permit) => response.map(|body| {
+                    Body::from_stream(PermitStream {
+                        inner: body.into_data_stream(),
+                        _permit: permit,
+                    })
+                }),
+                None => response,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip_scope(rpm: Option<u32>, max_streams: Option<u32>) -> Scope {
+        Scope {
+            key: ClientKey::Ip(IpAddr::from([10, 0, 0, 1])),
+            requests_per_minute: rpm,
+            max_streams,
+        }
+    }
+
+    #[test]
+    fn config_from_lookup() {
+        assert_eq!(RateLimitConfig::from_lookup(|_| None), None);
+        let config = RateLimitConfig::from_lookup(|name| match name {
+            "PHOENIX_RATE_LIMIT_IP_RPM" => Some("120".to_string()),
+            "PHOENIX_RATE_LIMIT_TOKEN_STREAMS" => Some("4".to_string()),
+            "PHOENIX_RATE_LIMIT_TOKEN_RPM" => Some("0".to_string()),
+            _ => Some("lots".to_string()),
+        })
+        .unwrap();
+        assert_eq!(config.ip_requests_per_minute, Some(120));
+        assert_eq!(config.token_max_streams, Some(4));
+        assert_eq!(config.token_requests_per_minute, None);
+        assert_eq!(config.ip_max_streams, None);
+    }
+
+    #[test]
+    fn requests_beyond_budget_are_rejected_until_refill() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        let scopes = [ip_scope(Some(2), None)];
+        let start = Instant::now();
+        assert!(limiter.admit(&scopes, false, start).is_ok());
+        assert!(limiter.admit(&scopes, false, start).is_ok());
+        // Two per minute refills one token every 30 seconds.
+        let wait = limiter.admit(&scopes, false, start).unwrap_err();
+        assert!((wait.as_secs_f64() - 30.0).abs() < 1e-6, "{wait:?}");
+
+        let later = start + Duration::from_secs(31);
+        assert!(limiter.admit(&scopes, false, later).is_ok());
+        assert!(limiter.admit(&scopes, false, later).is_err());
+    }
+
+    #[test]
+    fn rejection_by_one_scope_does_not_charge_another() {
+        let limiter = RateLimiter::new(RateLimitConfig::default());
+        let token = Scope {
+            key: ClientKey::token("secret"),
+            requests_per_minute: Some(1),
+            max_streams: None,
+        };
+        let now = Instant::now();
+        assert!(limiter
+            .admit(std::slice::from_ref(&token), false, now)
+            .is_ok());
+
+        let ip = ip_scope(Some(1), None);
+        assert!(limiter.admit(&[ip.clone(), token], false, now).is_err());
+        assert!(limiter.admit(&[ip], false, now).is_ok());
+    }
+
+    #[test]
+    fn streams_are_limited_until_released() {
+        let limiter = Arc::new(RateLimiter::new(RateLimitConfig::default()));
+        let scope = ip_scope(None, Some(1));
+        let now = Instant::now();
+        assert!(limiter
+            .admit(std::slice::from_ref(&scope), true, now)
+            .is_ok());
+        // Plain requests are not subject to the stream limit.
+        assert!(limiter
+            .admit(std::slice::from_ref(&scope), false, now)
+            .is_ok());
+        assert_eq!(
+            limiter.admit(std::slice::from_ref(&scope), true, now),
+            Err(STREAM_RETRY_AFTER)
+        );
+
+        drop(StreamPermit {
+            limiter: Arc::clone(&limiter),
+            keys: vec![scope.key.clone()],
+        });
+        assert!(limiter.admit(&[scope], true, now).is_ok());
+    }
+
+    #[test]
+    fn token_comes_from_share_path_bearer_or_cookie() {
+        let req = |uri: &str, name: header::HeaderName, value: &str| {
+            Request::builder()
+                .uri(uri)
+                .header(name, value)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let share = req("/api/share/abc/events", header::ACCEPT, "*/*");
+        assert_eq!(request_token(&share), Some("abc"));
+        let bearer = req("/api/conversations", header::AUTHORIZATION, "Bearer pw");
+        assert_eq!(request_token(&bearer), Some("pw"));
+        let cookie = req("/api/conversations", header::COOKIE, "a=b; phoenix-auth=pw");
+        assert_eq!(request_token(&cookie), Some("pw"));
+        let none = req("/api/conversations", header::ACCEPT, "*/*");
+        assert_eq!(request_token(&none), None);
+    }
+
+    #[test]
+    fn retry_after_rounds_up() {
+        let response = too_many_requests(Duration::from_millis(1500));
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+    }
+}
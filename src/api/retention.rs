@@ -0,0 +1,378 @@
+//! Conversation retention and cleanup (REQ-API-014)
+//!
+//! Optional policies that keep `phoenix.db` from growing without bound.
+//! Configured through environment variables, which the operator's settings
+//! (REQ-API-028) override; a background pass with no policy does nothing:
+//!
+//! - `PHOENIX_RETENTION_ARCHIVED_DAYS` — hard-delete archived conversations
+//!   not touched for this many days
+//! - `PHOENIX_RETENTION_TOOL_OUTPUT_DAYS` — truncate tool-result output older
+//!   than this many days, keeping a head excerpt and the display summary
+//! - `PHOENIX_RETENTION_INTERVAL_HOURS` — how often the background pass runs
+//!   (default 24)
+//!
+//! Deletes go through the same hard-delete cascade as the UI's delete
+//! button, so bash handles, tmux servers, and worktrees are cleaned up too.
+//! `POST /api/admin/cleanup` runs a pass on demand.
+
+use axum::extract::State;
+use axum::Json;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use super::handlers::{run_hard_delete_cascade, AppError};
+use super::types::ConflictErrorResponse;
+use super::AppState;
+use crate::db::ServerSettings;
+
+/// Characters of tool output kept when a result is pruned.
+const TOOL_OUTPUT_KEEP_CHARS: usize = 500;
+/// Delay before the first background pass, so startup work settles first.
+const FIRST_RUN_DELAY: Duration = Duration::from_secs(300);
+const DEFAULT_INTERVAL_HOURS: u64 = 24;
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(DEFAULT_INTERVAL_HOURS * 3600);
+
+/// Set while a cleanup pass runs; a second caller gets 409 instead of
+/// racing the first through the same conversations.
+static CLEANUP_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Retention policies. `None` disables that policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionConfig {
+    pub archived_days: Option<u32>,
+    pub tool_output_days: Option<u32>,
+    pub interval: Duration,
+}
+
+impl RetentionConfig {
+    /// Read policies from `PHOENIX_RETENTION_*`. Returns `None` when neither
+    /// policy is configured. Zero or unparseable values are treated as unset.
+    pub fn from_env() -> Option<Self> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let days = |name: &str| {
+            let raw = lookup(name)?;
+            match raw.trim().parse::<u32>() {
+                Ok(0) => None,
+                Ok(n) => Some(n),
+                Err(_) => {
+                    tracing::warn!(var = name, value = %raw, "Ignoring invalid retention value");
+                    None
+                }
+            }
+        };
+        let archived_days = days("PHOENIX_RETENTION_ARCHIVED_DAYS");
+        let tool_output_days = days("PHOENIX_RETENTION_TOOL_OUTPUT_DAYS");
+        if archived_days.is_none() && tool_output_days.is_none() {
+            return None;
+        }
+        let hours =
+            days("PHOENIX_RETENTION_INTERVAL_HOURS").map_or(DEFAULT_INTERVAL_HOURS, u64::from);
+        Some(Self {
+            archived_days,
+            tool_output_days,
+            interval: Duration::from_secs(hours * 3600),
+        })
+    }
+
+    /// `env` with the operator's retention settings (REQ-API-028) applied:
+    /// a set value replaces the environment's and `0` turns that policy
+    /// off. `None` when no policy is left.
+    pub fn with_settings(env: Option<Self>, settings: &ServerSettings) -> Option<Self> {
+        let pick = |setting: Option<u32>, env_days: Option<u32>| match setting {
+            Some(0) => None,
+            Some(days) => Some(days),
+            None => env_days,
+        };
+        let archived_days = pick(
+            settings.retention_archived_days,
+            env.and_then(|c| c.archived_days),
+        );
+        let tool_output_days = pick(
+            settings.retention_tool_output_days,
+            env.and_then(|c| c.tool_output_days),
+        );
+        if archived_days.is_none() && tool_output_days.is_none() {
+            return None;
+        }
+        Some(Self {
+            archived_days,
+            tool_output_days,
+            interval: env.map_or(DEFAULT_INTERVAL, |c| c.interval),
+        })
+    }
+
+    /// Policies in effect right now: the environment's, overridden by the
+    /// operator's settings as currently stored.
+    pub async fn current(state: &AppState) -> Option<Self> {
+        Self::with_settings(Self::from_env(), &state.runtime.server_settings().await)
+    }
+}
+
+/// Optional per-request overrides for `POST /api/admin/cleanup`.
+#[derive(Debug, Default, Deserialize)]
+pub struct CleanupRequest {
+    #[serde(default)]
+    pub archived_days: Option<u32>,
+    #[serde(default)]
+    pub tool_output_days: Option<u32>,
+    /// Skip the `VACUUM` at the end of the pass.
+    #[serde(default)]
+    pub skip_vacuum: bool,
+}
+
+/// What one cleanup pass did.
+#[derive(Debug, Default, Serialize)]
+pub struct CleanupReport {
+    /// Ids of conversations that were hard-deleted.
+    pub deleted_conversations: Vec<String>,
+    /// Expired conversations left in place: busy, part of a chain with a
+    /// member that has not expired, or failed to delete.
+    pub skipped_conversations: Vec<String>,
+    pub pruned_tool_messages: u64,
+    pub vacuumed: bool,
+}
+
+/// `POST /api/admin/cleanup` — run a retention pass now.
+///
+/// Body fields override the configured policies for this run only. With no
+/// policy from any source the request is rejected rather than silently
+/// doing nothing.
+pub async fn admin_cleanup(
+    State(state): State<AppState>,
+    body: Option<Json<CleanupRequest>>,
+) -> Result<Json<CleanupReport>, AppError> {
+    let req = body.map(|Json(b)| b).unwrap_or_default();
+    let configured = RetentionConfig::current(&state).await;
+    let archived_days = req
+        .archived_days
+        .or_else(|| configured.and_then(|c| c.archived_days));
+    let tool_output_days = req
+        .tool_output_days
+        .or_else(|| configured.and_then(|c| c.tool_output_days));
+    if archived_days.is_none() && tool_output_days.is_none() {
+        return Err(AppError::BadRequest(
+            "No retention policy configured; set archived_days or tool_output_days".to_string(),
+        ));
+    }
+    let config = RetentionConfig {
+        archived_days,
+        tool_output_days,
+        interval: configured.map_or(Duration::ZERO, |c| c.interval),
+    };
+
+    let report = run_cleanup(&state, &config, !req.skip_vacuum).await?;
+    Ok(Json(report))
+}
+
+/// Start the periodic retention pass, every `interval` (the environment's,
+/// or daily). The first run is delayed by [`FIRST_RUN_DELAY`]. Each pass
+/// reads the policies anew, so settings changed since startup apply; a pass
+/// with no policy, or that finds another already running, is skipped.
+pub fn spawn_retention_task(state: AppState, interval: Option<Duration>) {
+    tokio::spawn(async move {
+        let start = tokio::time::Instant::now() + FIRST_RUN_DELAY;
+        let mut ticker = tokio::time::interval_at(start, interval.unwrap_or(DEFAULT_INTERVAL));
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let Some(config) = RetentionConfig::current(&state).await else {
+                continue;
+            };
+            match run_cleanup(&state, &config, true).await {
+                Ok(report) => tracing::info!(
+                    deleted = report.deleted_conversations.len(),
+                    skipped = report.skipped_conversations.len(),
+                    pruned = report.pruned_tool_messages,
+                    vacuumed = report.vacuumed,
+                    "Retention pass complete"
+                ),
+                Err(e) => tracing::warn!(error = ?e, "Retention pass failed"),
+            }
+        }
+    });
+}
+
+/// One retention pass: delete expired archives, prune old tool output, then
+/// `VACUUM` if anything changed and `vacuum` is set.
+pub(super) async fn run_cleanup(
+    state: &AppState,
+    config: &RetentionConfig,
+    vacuum: bool,
+) -> Result<CleanupReport, AppError> {
+    if CLEANUP_RUNNING.swap(true, Ordering::AcqRel) {
+        return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+            "A cleanup pass is already running",
+            "cleanup_in_progress",
+        ))));
+    }
+    let _running = RunningGuard;
+    cleanup_pass(state, config, vacuum).await
+}
+
+/// Clears [`CLEANUP_RUNNING`] even if the pass is dropped mid-flight
+/// (e.g. the admin request's client disconnects).
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        CLEANUP_RUNNING.store(false, Ordering::Release);
+    }
+}
+
+async fn cleanup_pass(
+    state: &AppState,
+    config: &RetentionConfig,
+    vacuum: bool,
+) -> Result<CleanupReport, AppError> {
+    let db = &state.db;
+    let mut report = CleanupReport::default();
+
+    if let Some(days) = config.archived_days {
+        let cutoff = Utc::now() - chrono::Duration::days(i64::from(days));
+        let expired = db
+            .list_expired_archived_conversations(cutoff)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        delete_expired(state, &expired, &mut report).await;
+    }
+
+    if let Some(days) = config.tool_output_days {
+        let cutoff = Utc::now() - chrono::Duration::days(i64::from(days));
+        report.pruned_tool_messages = db
+            .prune_tool_outputs(cutoff, TOOL_OUTPUT_KEEP_CHARS)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+    }
+
+    let changed = !report.deleted_conversations.is_empty() || report.pruned_tool_messages > 0;
+    if vacuum && changed {
+        match db.vacuum().await {
+            Ok(()) => report.vacuumed = true,
+            Err(e) => tracing::warn!(error = %e, "VACUUM after retention pass failed"),
+        }
+    }
+    Ok(report)
+}
+
+/// Hard-delete each expired conversation. Chains are atomic (REQ-CHN-*):
+/// a chain is deleted root-first only when every member has expired, and
+/// is skipped otherwise; non-root members are handled via their root.
+async fn delete_expired(state: &AppState, expired: &[String], report: &mut CleanupReport) {
+    let db = &state.db;
+    let expired_set: HashSet<&str> = expired.iter().map(String::as_str).collect();
+    let mut handled: HashSet<String> = HashSet::new();
+
+    for id in expired {
+        if handled.contains(id) {
+            continue;
+        }
+        let members = match db.chain_root_if_member(id).await {
+            Ok(None) => vec![id.clone()],
+            Ok(Some(root)) => match db.chain_members_forward(&root).await {
+                Ok(members) => members,
+                Err(e) => {
+                    tracing::warn!(conv_id = %id, error = %e, "Retention: chain lookup failed");
+                    report.skipped_conversations.push(id.clone());
+                    continue;
+                }
+            },
+            Err(e) => {
+                tracing::warn!(conv_id = %id, error = %e, "Retention: chain lookup failed");
+                report.skipped_conversations.push(id.clone());
+                continue;
+            }
+        };
+        handled.extend(members.iter().cloned());
+
+        if !members.iter().all(|m| expired_set.contains(m.as_str())) {
+            report.skipped_conversations.extend(
+                members
+                    .into_iter()
+                    .filter(|m| expired_set.contains(m.as_str())),
+            );
+            continue;
+        }
+        // Root-first, same order as the chain delete endpoint.
+        for member in members {
+            match run_hard_delete_cascade(state, &member).await {
+                Ok(()) => report.deleted_conversations.push(member),
+                Err(e) => {
+                    tracing::warn!(conv_id = %member, error = ?e, "Retention: delete skipped");
+                    report.skipped_conversations.push(member);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| (*v).to_string())
+        }
+    }
+
+    #[test]
+    fn config_absent_without_policies() {
+        assert_eq!(RetentionConfig::from_lookup(lookup(&[])), None);
+        let only_interval = [("PHOENIX_RETENTION_INTERVAL_HOURS", "6")];
+        assert_eq!(RetentionConfig::from_lookup(lookup(&only_interval)), None);
+    }
+
+    #[test]
+    fn config_reads_policies_and_interval() {
+        let vars = [
+            ("PHOENIX_RETENTION_ARCHIVED_DAYS", "30"),
+            ("PHOENIX_RETENTION_TOOL_OUTPUT_DAYS", "bogus"),
+            ("PHOENIX_RETENTION_INTERVAL_HOURS", "6"),
+        ];
+        let config = RetentionConfig::from_lookup(lookup(&vars)).unwrap();
+        assert_eq!(config.archived_days, Some(30));
+        assert_eq!(config.tool_output_days, None);
+        assert_eq!(config.interval, Duration::from_secs(6 * 3600));
+    }
+
+    #[test]
+    fn settings_override_the_environment() {
+        let env = RetentionConfig::from_lookup(lookup(&[
+            ("PHOENIX_RETENTION_ARCHIVED_DAYS", "30"),
+            ("PHOENIX_RETENTION_INTERVAL_HOURS", "6"),
+        ]));
+        let settings = ServerSettings {
+            retention_tool_output_days: Some(90),
+            ..ServerSettings::default()
+        };
+        let config = RetentionConfig::with_settings(env, &settings).unwrap();
+        assert_eq!(config.archived_days, Some(30));
+        assert_eq!(config.tool_output_days, Some(90));
+        assert_eq!(config.interval, Duration::from_secs(6 * 3600));
+
+        // Zero turns an environment policy off
+        let off = ServerSettings {
+            retention_archived_days: Some(0),
+            ..ServerSettings::default()
+        };
+        assert_eq!(RetentionConfig::with_settings(env, &off), None);
+        let only_settings = RetentionConfig::with_settings(None, &settings).unwrap();
+        assert_eq!(only_settings.interval, DEFAULT_INTERVAL);
+    }
+
+    #[test]
+    fn config_defaults_interval_to_daily() {
+        let vars = [("PHOENIX_RETENTION_TOOL_OUTPUT_DAYS", "90")];
+        let config = RetentionConfig::from_lookup(lookup(&vars)).unwrap();
+        assert_eq!(config.tool_output_days, Some(90));
+        assert_eq!(config.interval, Duration::from_secs(24 * 3600));
+    }
+}
@@ -0,0 +1,124 @@
+//! Extra conversation roots (REQ-BED-049): directories besides the cwd that
+//! a conversation may use, such as a shared library checkout. They are
+//! preopened for plugins, listed in the system prompt, and searchable
+//! through the files API with `?root=`.
+
+use super::handlers::AppError;
+use super::types::ConversationRoots;
+use super::AppState;
+use crate::db::ConversationRoot;
+use std::path::Path as FsPath;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+
+/// Most extra roots a conversation may have.
+const MAX_ROOTS: usize = 16;
+
+/// The conversation's extra roots, in path order.
+pub(super) async fn get_conversation_roots(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ConversationRoots>, AppError> {
+    state.db.get_conversation(&id).await?;
+    let roots = state.db.list_conversation_roots(&id).await?;
+    Ok(Json(ConversationRoots { roots }))
+}
+
+/// Replace the conversation's extra roots. Requires the conversation to be
+/// idle, like patch review: the roots are read when the runtime is created.
+pub(super) async fn set_conversation_roots(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<ConversationRoots>,
+) -> Result<Json<ConversationRoots>, AppError> {
+    let conv = state.db.get_conversation(&id).await?;
+    if !conv.state.is_idle() {
+        return Err(AppError::BadRequest(
+            "Conversation must be idle to change its roots".to_string(),
+        ));
+    }
+    let roots = normalize_roots(&conv.cwd, req.roots).map_err(AppError::BadRequest)?;
+    state
+        .db
+        .set_conversation_roots(&id, &roots, chrono::Utc::now())
+        .await?;
+
+    // Evict the active runtime so it gets recreated with the new roots
+    state.runtime.evict_runtime(&id).await;
+
+    tracing::info!(conv_id = %id, count = roots.len(), "Conversation roots set");
+    Ok(Json(ConversationRoots { roots }))
+}
+
+/// Check that every root is an existing directory given by absolute path,
+/// and resolve it so symlinked spellings of the same directory collapse.
+/// The cwd itself and repeats are rejected rather than silently dropped.
+fn normalize_roots(
+    cwd: &str,
+    roots: Vec<ConversationRoot>,
+) -> Result<Vec<ConversationRoot>, String> {
+    if roots.len() > MAX_ROOTS {
+        return Err(format!("At most {MAX_ROOTS} extra roots are allowed"));
+    }
+    let cwd = std::fs::canonicalize(cwd).unwrap_or_else(|_| cwd.into());
+    let mut normalized: Vec<ConversationRoot> = Vec::with_capacity(roots.len());
+    for root in roots {
+        let path = FsPath::new(&root.path);
+        if !path.is_absolute() {
+            return Err(format!("Root must be an absolute path: {}", root.path));
+        }
+        if !path.is_dir() {
+            return Err(format!("Root is not a directory: {}", root.path));
+        }
+        let resolved = std::fs::canonicalize(path)
+            .map_err(|e| format!("Cannot resolve root {}: {e}", root.path))?;
+        if resolved == cwd {
+            return Err(format!("Root is the working directory: {}", root.path));
+        }
+        let resolved = resolved.to_string_lossy().to_string();
+        if normalized.iter().any(|r| r.path == resolved) {
+            return Err(format!("Root listed twice: {}", root.path));
+        }
+        normalized.push(ConversationRoot {
+            path: resolved,
+            writable: root.writable,
+        });
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn root(path: &FsPath, writable: bool) -> ConversationRoot {
+        ConversationRoot {
+            path: path.to_string_lossy().to_string(),
+            writable,
+        }
+    }
+
+    #[test]
+    fn roots_must_be_distinct_existing_directories() {
+        let cwd = TempDir::new().unwrap();
+        let lib = TempDir::new().unwrap();
+        let cwd_str = cwd.path().to_str().unwrap();
+
+        let ok = normalize_roots(cwd_str, vec![root(lib.path(), false)]).unwrap();
+        let expected = std::fs::canonicalize(lib.path()).unwrap();
+        assert_eq!(ok, vec![root(&expected, false)]);
+
+        let err = normalize_roots(cwd_str, vec![root(FsPath::new("lib"), false)]).unwrap_err();
+        assert!(err.contains("absolute"), "{err}");
+        let missing = lib.path().join("missing");
+        assert!(normalize_roots(cwd_str, vec![root(&missing, true)]).is_err());
+        let err = normalize_roots(cwd_str, vec![root(cwd.path(), true)]).unwrap_err();
+        assert!(err.contains("working directory"), "{err}");
+        let twice = vec![root(lib.path(), false), root(lib.path(), true)];
+        assert!(normalize_roots(cwd_str, twice).is_err());
+    }
+}
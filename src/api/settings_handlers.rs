@@ -0,0 +1,50 @@
+//! Server settings (REQ-API-028): operator overrides for the default model,
+//! retention, tools switched off everywhere, per-turn limits and the bash
+//! command policy every conversation shares (REQ-BASH-018). They are
+//! stored in the database and read where they apply, so a change takes
+//! effect without a restart: new conversations and runtimes started after
+//! it, and the next retention pass. Runtimes already running keep what they
+//! started with.
+
+use super::handlers::{checked_tool_names, AppError};
+use super::AppState;
+use crate::db::ServerSettings;
+
+use axum::{extract::State, Json};
+
+/// The stored settings. Unset fields follow the environment.
+pub(super) async fn get_server_settings(
+    State(state): State<AppState>,
+) -> Result<Json<ServerSettings>, AppError> {
+    let settings = state.db.get_server_settings().await?;
+    Ok(Json(settings))
+}
+
+/// Replace the settings. Fields left out are unset and fall back to the
+/// environment again.
+pub(super) async fn set_server_settings(
+    State(state): State<AppState>,
+    Json(mut settings): Json<ServerSettings>,
+) -> Result<Json<ServerSettings>, AppError> {
+    settings.default_model = settings
+        .default_model
+        .map(|model| model.trim().to_string())
+        .filter(|model| !model.is_empty());
+    if let Some(model) = &settings.default_model {
+        if state.llm_registry.get(model).is_none() {
+            return Err(AppError::BadRequest(format!("Unknown model: {model}")));
+        }
+    }
+    settings.disabled_tools = checked_tool_names(&state, settings.disabled_tools).await?;
+    settings.bash_policy = settings.bash_policy.filter(|policy| !policy.is_empty());
+    if let Some(policy) = &settings.bash_policy {
+        crate::tools::bash::policy::validate(policy).map_err(AppError::BadRequest)?;
+    }
+
+    state
+        .db
+        .set_server_settings(&settings, chrono::Utc::now())
+        .await?;
+    tracing::info!(?settings, "Server settings updated");
+    Ok(Json(settings))
+}
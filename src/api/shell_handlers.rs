@@ -0,0 +1,172 @@
+//! The shell a conversation's bash tool runs commands in (REQ-BASH-017):
+//! `bash`, `zsh`, `fish` or a shell by absolute path, and whether it loads
+//! the user's login profile. Also commands the user runs in it themselves
+//! (REQ-BASH-020).
+
+use super::handlers::AppError;
+use super::types::{ConflictErrorResponse, RunCommandRequest, RunCommandResponse};
+use super::AppState;
+use crate::db::{AuditEntry, AuditOutcome, MessageContent, ShellSettings, ToolContent};
+use crate::tools::bash::policy::BashPolicy;
+use crate::tools::{BashTool, Tool, ToolContext};
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use std::path::PathBuf;
+use tokio_util::sync::CancellationToken;
+
+/// The conversation's shell; plain `bash` unless one was set.
+pub(super) async fn get_conversation_shell(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<ShellSettings>, AppError> {
+    state.db.get_conversation(&id).await?;
+    Ok(Json(state.db.get_conversation_shell(&id).await?))
+}
+
+/// Set the conversation's shell. Requires the conversation to be idle, like
+/// its roots: the shell is read when the runtime is created. Handles already
+/// running keep the shell they started in.
+pub(super) async fn set_conversation_shell(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<ShellSettings>,
+) -> Result<Json<ShellSettings>, AppError> {
+    let conv = state.db.get_conversation(&id).await?;
+    if !conv.state.is_idle() {
+        return Err(AppError::BadRequest(
+            "Conversation must be idle to change its shell".to_string(),
+        ));
+    }
+    crate::tools::bash::shell::validate(&req).map_err(AppError::BadRequest)?;
+    state
+        .db
+        .set_conversation_shell(&id, &req, chrono::Utc::now())
+        .await?;
+
+    // Evict the active runtime so it gets recreated with the new shell
+    state.runtime.evict_runtime(&id).await;
+
+    tracing::info!(
+        conv_id = %id,
+        program = %req.program,
+        load_profile = req.load_profile,
+        "Conversation shell set"
+    );
+    Ok(Json(req))
+}
+
+/// Run a command the user typed through the bash tool, in the
+/// conversation's cwd with its shell and policy, and record the output as a
+/// tool message attributed to the user, audited like the agent's calls.
+/// Requires the conversation to be idle so the output cannot land between
+/// an agent's tool call and its result, both before the command runs and
+/// when its output is stored: if a turn started in between, the output is
+/// dropped with a 409. The command still ran, so it is still audited.
+pub(super) async fn run_command(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<RunCommandRequest>,
+) -> Result<Json<RunCommandResponse>, AppError> {
+    let command = req.command.trim().to_string();
+    if command.is_empty() {
+        return Err(AppError::BadRequest("Command is empty".to_string()));
+    }
+    let conv = state.db.get_conversation(&id).await?;
+    if !conv.state.is_idle() {
+        return Err(AppError::BadRequest(
+            "Conversation must be idle to run a command".to_string(),
+        ));
+    }
+
+    let runtime = &state.runtime;
+    let settings = runtime.server_settings().await;
+    let bash_policy = BashPolicy {
+        global: settings.bash_policy.clone().unwrap_or_default(),
+        conversation: state.db.get_conversation_bash_policy(&id).await?,
+    };
+    let ctx = ToolContext::new(
+        CancellationToken::new(),
+        id.clone(),
+        PathBuf::from(&conv.cwd),
+        runtime.browser_sessions().clone(),
+        runtime.bash_handles().clone(),
+        runtime.llm_registry().clone(),
+        runtime.terminals.clone(),
+        runtime.tmux_registry().clone(),
+        None,
+    )
+    .with_extra_roots(state.db.list_conversation_roots(&id).await?)
+    .with_shell(state.db.get_conversation_shell(&id).await?)
+    .with_bash_policy(bash_policy);
+
+    // The user typing the command is the confirmation REQ-BASH-019 asks for
+    let input = serde_json::json!({ "cmd": command, "confirm_discard": true });
+    let (input_hash, input_preview) = AuditEntry::fingerprint_input(&input);
+    let started_at = chrono::Utc::now();
+    let start = std::time::Instant::now();
+    let mut output = BashTool.run(input, ctx).await;
+    tracing::info!(
+        conv_id = %id,
+        success = output.success,
+        "User ran a command"
+    );
+
+    let content = ToolContent::user_command(command, output.output.clone(), !output.success);
+    let policy_hit = output.policy_hit.take();
+    let outcome = match &policy_hit {
+        Some(hit) if hit.denied => AuditOutcome::Denied,
+        _ if output.success => AuditOutcome::Success,
+        _ => AuditOutcome::Error,
+    };
+    let audit = AuditEntry {
+        conversation_id: id.clone(),
+        tool_use_id: content.tool_use_id.clone(),
+        tool_name: "bash".to_string(),
+        input_hash,
+        input_preview,
+        cwd: conv.cwd.clone(),
+        os_user: std::env::var("USER").ok(),
+        started_at,
+        duration_ms: i64::try_from(start.elapsed().as_millis()).unwrap_or(i64::MAX),
+        outcome,
+        policy: policy_hit.map(|hit| hit.audit_label()),
+    };
+    if let Err(e) = state.db.insert_audit_entry(&audit).await {
+        tracing::warn!(error = %e, "failed to write audit_log row");
+    }
+
+    let message_id = uuid::Uuid::new_v4().to_string();
+    let handle = runtime
+        .get_or_create(&id)
+        .await
+        .map_err(AppError::Internal)?;
+    let seq = handle.broadcast_tx.next_seq();
+    // The command can run for minutes; a turn may have started meanwhile
+    let message = state
+        .db
+        .add_message_with_seq_if_unchanged(
+            &message_id,
+            &id,
+            seq,
+            &MessageContent::Tool(content),
+            output.display_data.as_ref(),
+            conv.message_count,
+        )
+        .await?
+        .ok_or_else(|| {
+            AppError::Conflict(Box::new(ConflictErrorResponse::new(
+                "The conversation moved on while the command ran; its output was not recorded",
+                "conversation_not_idle",
+            )))
+        })?;
+    let _ = handle.broadcast_tx.send_message(message);
+
+    Ok(Json(RunCommandResponse {
+        message_id,
+        success: output.success,
+        output: output.output,
+    }))
+}
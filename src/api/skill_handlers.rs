@@ -0,0 +1,93 @@
+//! User skill library HTTP handlers (REQ-SK-009).
+//!
+//! Library skills are flat markdown files under `$HOME/.phoenix-ide/skills/`;
+//! the filesystem is the source of truth, so these handlers are thin wrappers
+//! over the CRUD helpers in `crate::skills`.
+
+use super::handlers::AppError;
+use super::types::{
+    ConflictErrorResponse, LibrarySkillRequest, LibrarySkillResponse, SkillEntry, SkillsResponse,
+    SuccessResponse,
+};
+use crate::skills::{self, LibrarySkill, SkillLibraryError};
+
+use axum::{extract::Path, http::StatusCode, Json};
+use std::path::PathBuf;
+
+impl From<SkillLibraryError> for AppError {
+    fn from(e: SkillLibraryError) -> Self {
+        match e {
+            SkillLibraryError::InvalidName(_) => AppError::BadRequest(e.to_string()),
+            SkillLibraryError::NotFound(_) => AppError::NotFound(e.to_string()),
+            SkillLibraryError::AlreadyExists(_) => AppError::Conflict(Box::new(
+                ConflictErrorResponse::new(e.to_string(), "skill_exists"),
+            )),
+            SkillLibraryError::Io(_) => AppError::Internal(e.to_string()),
+        }
+    }
+}
+
+fn library_dir() -> Result<PathBuf, AppError> {
+    skills::library_dir()
+        .ok_or_else(|| AppError::Internal("HOME is not set; skill library unavailable".into()))
+}
+
+fn to_library_skill(name: String, req: LibrarySkillRequest) -> LibrarySkill {
+    LibrarySkill {
+        name,
+        description: req.description,
+        argument_hint: req.argument_hint,
+        triggers: req.triggers,
+        body: req.body,
+    }
+}
+
+pub(crate) async fn list_library_skills() -> Result<Json<SkillsResponse>, AppError> {
+    let dir = library_dir()?;
+    let skills = crate::system_prompt::list_library_skills(&dir);
+    Ok(Json(SkillsResponse {
+        skills: skills.into_iter().map(SkillEntry::from).collect(),
+    }))
+}
+
+pub(crate) async fn get_library_skill(
+    Path(name): Path<String>,
+) -> Result<Json<LibrarySkillResponse>, AppError> {
+    let skill = skills::read_library_skill(&library_dir()?, &name)?;
+    Ok(Json(skill.into()))
+}
+
+pub(crate) async fn create_library_skill(
+    Json(req): Json<LibrarySkillRequest>,
+) -> Result<(StatusCode, Json<LibrarySkillResponse>), AppError> {
+    let Some(name) = req.name.clone() else {
+        return Err(AppError::BadRequest("name is required".into()));
+    };
+    let dir = library_dir()?;
+    let skill = to_library_skill(name, req);
+    skills::create_library_skill(&dir, &skill)?;
+    let saved = skills::read_library_skill(&dir, &skill.name)?;
+    Ok((StatusCode::CREATED, Json(saved.into())))
+}
+
+pub(crate) async fn update_library_skill(
+    Path(name): Path<String>,
+    Json(req): Json<LibrarySkillRequest>,
+) -> Result<Json<LibrarySkillResponse>, AppError> {
+    if req.name.as_deref().is_some_and(|n| n != name) {
+        return Err(AppError::BadRequest(
+            "renaming a skill is not supported; delete and re-create it".into(),
+        ));
+    }
+    let dir = library_dir()?;
+    skills::update_library_skill(&dir, &to_library_skill(name.clone(), req))?;
+    let saved = skills::read_library_skill(&dir, &name)?;
+    Ok(Json(saved.into()))
+}
+
+pub(crate) async fn delete_library_skill(
+    Path(name): Path<String>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    skills::delete_library_skill(&library_dir()?, &name)?;
+    Ok(Json(SuccessResponse { success: true }))
+}
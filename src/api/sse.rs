@@ -7,31 +7,260 @@
 //! then through `serde_json::to_string`. See `super::wire` for the rationale
 //! and for the ts-rs-driven TS codegen that downstream clients consume.
 
+use super::types::StreamMetrics;
 use super::wire::SseWireEvent;
+use crate::runtime::presence::PresenceGuard;
 use crate::runtime::SseEvent;
+use async_compression::tokio::write::{BrotliEncoder, GzipEncoder};
+use async_trait::async_trait;
+use axum::body::{Body, Bytes};
+use axum::http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, VARY};
 use axum::http::{HeaderMap, HeaderValue};
 use axum::response::sse::{Event, KeepAlive, Sse};
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
+use futures::StreamExt;
+use serde_json::Value;
+use std::collections::VecDeque;
 use std::convert::Infallible;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
-use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
-use tokio_stream::wrappers::BroadcastStream;
-use tokio_stream::StreamExt;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::broadcast::{self, error::RecvError};
 
-/// Stream `init_event` followed by broadcast events to an SSE client.
+/// Subscribable event groups (`?events=`) and the SSE event types in each.
+const EVENT_GROUPS: &[(&str, &[&str])] = &[
+    ("message", &["message", "message_updated"]),
+    (
+        "state",
+        &["state_change", "agent_done", "conversation_became_terminal"],
+    ),
+    ("token", &["token"]),
+    (
+        "conversation",
+        &[
+            "conversation_update",
+            "context_warning",
+            "conversation_continued",
+            "files_changed",
+        ],
+    ),
+    ("error", &["error", "error_remediation"]),
+    (
+        "presence",
+        &["client_joined", "client_left", "composer_changed"],
+    ),
+];
+
+/// Sent whatever the filter says: the snapshot, and the event that tells the
+/// client its conversation is gone.
+const ALWAYS_SENT: &[&str] = &["init", "conversation_hard_deleted"];
+
+/// Message fields dropped in thin mode. Clients fetch them on demand from
+/// `GET /api/conversations/:id/messages/:message_id`.
+const THIN_MESSAGE_FIELDS: &[&str] = &["content", "display_data", "usage_data"];
+
+/// Per-connection event selection and payload trimming (REQ-API-019).
+#[derive(Debug, Clone, Default)]
+pub struct StreamFilter {
+    /// Selected groups from [`EVENT_GROUPS`]; `None` sends every event.
+    groups: Option<Vec<&'static str>>,
+    thin: bool,
+}
+
+impl StreamFilter {
+    /// Parse `?events=state,message&thin=true`. Unknown group names are an
+    /// error rather than silently matching nothing.
+    pub fn parse(events: Option<&str>, thin: bool) -> Result<Self, String> {
+        let Some(list) = events else {
+            return Ok(Self { groups: None, thin });
+        };
+        let mut groups = Vec::new();
+        for name in list.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let Some((group, _)) = EVENT_GROUPS.iter().find(|(g, _)| *g == name) else {
+                let known: Vec<&str> = EVENT_GROUPS.iter().map(|(g, _)| *g).collect();
+                return Err(format!(
+                    "Unknown event group '{name}' (expected one of: {})",
+                    known.join(", ")
+                ));
+            };
+            groups.push(*group);
+        }
+        Ok(Self {
+            groups: Some(groups),
+            thin,
+        })
+    }
+
+    fn allows(&self, event_type: &str) -> bool {
+        let Some(groups) = &self.groups else {
+            return true;
+        };
+        ALWAYS_SENT.contains(&event_type)
+            || EVENT_GROUPS
+                .iter()
+                .any(|(g, types)| groups.contains(g) && types.contains(&event_type))
+    }
+}
+
+/// Largest `data` one event carries (REQ-API-030). Messages in a bigger
+/// event lose their bodies, largest first, until it fits, and are marked
+/// `"truncated": true`; clients fetch them in full from
+/// `GET /api/conversations/:id/messages/:message_id`, as in thin mode.
+const MAX_EVENT_BYTES: usize = 256 * 1024;
+
+fn strip_message(message: &mut Value) {
+    if let Some(obj) = message.as_object_mut() {
+        for field in THIN_MESSAGE_FIELDS {
+            obj.remove(*field);
+        }
+    }
+}
+
+/// The messages a serialized event carries: the `message` of a `message`
+/// event, every entry of an `init`, or the `message_updated` event itself.
+fn messages_in<'a>(event_type: &str, value: &'a mut Value) -> Vec<&'a mut Value> {
+    match event_type {
+        "message" => value.get_mut("message").into_iter().collect(),
+        "init" => value
+            .get_mut("messages")
+            .and_then(Value::as_array_mut)
+            .map(|messages| messages.iter_mut().collect())
+            .unwrap_or_default(),
+        "message_updated" => vec![value],
+        _ => Vec::new(),
+    }
+}
+
+/// Strip [`THIN_MESSAGE_FIELDS`] from every message in a serialized event,
+/// leaving ids, types, and sequence numbers.
+fn thin_payload(event_type: &str, value: &mut Value) {
+    messages_in(event_type, value)
+        .into_iter()
+        .for_each(strip_message);
+}
+
+/// Bring an event of `size` bytes under [`MAX_EVENT_BYTES`] by stripping
+/// message bodies, largest first. Returns how many messages were trimmed;
+/// zero means the event carries no body to drop and goes out as it is.
+fn fit_payload(event_type: &str, value: &mut Value, mut size: usize) -> usize {
+    let body_bytes = |message: &Value| -> usize {
+        THIN_MESSAGE_FIELDS
+            .iter()
+            .filter_map(|field| message.get(*field))
+            .map(|body| body.to_string().len())
+            .sum()
+    };
+    let mut sized: Vec<(usize, &mut Value)> = messages_in(event_type, value)
+        .into_iter()
+        .map(|message| (body_bytes(message), message))
+        .collect();
+    sized.sort_by_key(|(bytes, _)| std::cmp::Reverse(*bytes));
+    let mut trimmed = 0;
+    for (bytes, message) in sized {
+        if size <= MAX_EVENT_BYTES || bytes == 0 {
+            break;
+        }
+        strip_message(message);
+        if let Some(obj) = message.as_object_mut() {
+            obj.insert("truncated".to_string(), Value::Bool(true));
+        }
+        size = size.saturating_sub(bytes);
+        trimmed += 1;
+    }
+    trimmed
+}
+
+/// Process-wide SSE payload counters (REQ-API-030), served by
+/// `GET /api/admin/stream-metrics`.
+pub static PAYLOAD_METRICS: PayloadMetrics = PayloadMetrics::new();
+
+/// Totals since startup; see [`StreamMetrics`] for what each counts.
+pub struct PayloadMetrics {
+    events: AtomicU64,
+    payload_bytes: AtomicU64,
+    largest_event_bytes: AtomicU64,
+    truncated_events: AtomicU64,
+    truncated_messages: AtomicU64,
+    compressed_streams: AtomicU64,
+    compressed_bytes_in: AtomicU64,
+    compressed_bytes_out: AtomicU64,
+}
+
+impl PayloadMetrics {
+    const fn new() -> Self {
+        Self {
+            events: AtomicU64::new(0),
+            payload_bytes: AtomicU64::new(0),
+            largest_event_bytes: AtomicU64::new(0),
+            truncated_events: AtomicU64::new(0),
+            truncated_messages: AtomicU64::new(0),
+            compressed_streams: AtomicU64::new(0),
+            compressed_bytes_in: AtomicU64::new(0),
+            compressed_bytes_out: AtomicU64::new(0),
+        }
+    }
+
+    fn record_event(&self, bytes: usize, trimmed: usize) {
+        let bytes = bytes as u64;
+        self.events.fetch_add(1, Ordering::Relaxed);
+        self.payload_bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.largest_event_bytes.fetch_max(bytes, Ordering::Relaxed);
+        if trimmed > 0 {
+            self.truncated_events.fetch_add(1, Ordering::Relaxed);
+            self.truncated_messages
+                .fetch_add(trimmed as u64, Ordering::Relaxed);
+        }
+    }
+
+    fn record_compressed(&self, bytes_in: usize, bytes_out: usize) {
+        self.compressed_bytes_in
+            .fetch_add(bytes_in as u64, Ordering::Relaxed);
+        self.compressed_bytes_out
+            .fetch_add(bytes_out as u64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> StreamMetrics {
+        StreamMetrics {
+            events: self.events.load(Ordering::Relaxed),
+            payload_bytes: self.payload_bytes.load(Ordering::Relaxed),
+            largest_event_bytes: self.largest_event_bytes.load(Ordering::Relaxed),
+            max_event_bytes: MAX_EVENT_BYTES as u64,
+            truncated_events: self.truncated_events.load(Ordering::Relaxed),
+            truncated_messages: self.truncated_messages.load(Ordering::Relaxed),
+            compressed_streams: self.compressed_streams.load(Ordering::Relaxed),
+            compressed_bytes_in: self.compressed_bytes_in.load(Ordering::Relaxed),
+            compressed_bytes_out: self.compressed_bytes_out.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Stream `init_event`, then `replayed`, then broadcast events to an SSE
+/// client, keeping only what `filter` selects.
 ///
-/// On `BroadcastStreamRecvError::Lagged` — the client fell far enough behind
-/// that the `broadcast::channel` overwrote unread entries — this stream ends.
-/// The client's `EventSource` observes the close, its `ConnectionMachine`
-/// reconnects, and the next `init` event pulls in everything that was in
-/// the gap (the server persisted it all to `SQLite` regardless). Silently
-/// dropping Lagged — which this function used to do — left the client's
-/// state strictly behind truth with no way to notice the gap.
+/// Every event's SSE `id:` is its `sequence_id`, so a browser reconnecting
+/// on its own sends it back as `Last-Event-ID`. `replayed` holds the events
+/// such a client missed (see [`crate::runtime::SseBroadcaster::resume`]);
+/// it is empty for a fresh connection.
+///
+/// On `RecvError::Lagged` — the client fell far enough behind that the
+/// `broadcast::channel` overwrote unread entries — `resync` catches the
+/// client up in place: from the replay buffer when it still covers the gap,
+/// otherwise with a fresh `init` carrying the messages after the last event
+/// this stream sent. Only when there is no `resync` (or it fails) does the
+/// stream end; the client's `ConnectionMachine` then reconnects and the next
+/// `init` pulls in the gap. Silently dropping Lagged — which this function
+/// once did — left the client's state strictly behind truth with no way to
+/// notice the gap.
 ///
 /// `conv_id` is threaded through only for the Lagged log line; the stream
 /// itself does not consume it. Capacity of the underlying channel lives
 /// at `crate::runtime::SSE_BROADCAST_CAPACITY`.
 ///
+/// `presence` is moved into the stream so the client stays registered
+/// exactly as long as the response body lives; dropping the stream (client
+/// disconnect, Lagged close) broadcasts `client_left` (REQ-API-013).
+///
 /// Sets `X-Accel-Buffering: no` so any HTTP-aware intermediary on the path
 /// (nginx, ingress controllers, etc.) flushes events immediately rather than
 /// batching them. Without this hint such a proxy may hold `state_change`
@@ -40,34 +269,22 @@ use tokio_stream::StreamExt;
 pub fn sse_stream(
     conv_id: String,
     init_event: SseEvent,
-    broadcast_rx: tokio::sync::broadcast::Receiver<SseEvent>,
+    replayed: Vec<SseEvent>,
+    broadcast_rx: broadcast::Receiver<SseEvent>,
+    presence: Option<PresenceGuard>,
+    filter: StreamFilter,
+    resync: Option<Arc<dyn Resync>>,
 ) -> impl IntoResponse {
-    let init =
-        futures::stream::once(
-            async move { Ok::<Event, Infallible>(sse_event_to_axum(init_event)) },
-        );
-
-    let broadcasts = BroadcastStream::new(broadcast_rx)
-        .take_while(move |result| {
-            if let Err(BroadcastStreamRecvError::Lagged(n)) = result {
-                tracing::warn!(
-                    conv_id = %conv_id,
-                    lagged_by = n,
-                    "SSE broadcast lagged; closing stream so client reconnects and resyncs"
-                );
-                false
-            } else {
-                true
-            }
-        })
-        .filter_map(|result| match result {
-            Ok(event) => Some(Ok(sse_event_to_axum(event))),
-            Err(_) => None, // Lagged already closed the stream above
-        });
+    let queued = std::iter::once(init_event).chain(replayed).collect();
+    let subscriber = Subscriber::new(conv_id, queued, broadcast_rx, filter, resync, presence);
+    let stream = futures::stream::unfold(subscriber, |mut sub| async move {
+        let frame = sub.next_frame().await?;
+        Some((Ok::<Event, Infallible>(frame.into()), sub))
+    });
 
-    let combined = init.chain(broadcasts);
-
-    let sse = Sse::new(combined).keep_alive(
+    // Comment-only keep-alives stop proxies and load balancers from reaping
+    // the connection while the agent is idle.
+    let sse = Sse::new(stream).keep_alive(
         KeepAlive::new()
             .interval(Duration::from_secs(15))
             .text("ping"),
@@ -78,20 +295,287 @@ pub fn sse_stream(
     (headers, sse)
 }
 
-fn sse_event_to_axum(event: SseEvent) -> Event {
+/// Compress an SSE response when the request's `Accept-Encoding` allows
+/// brotli or gzip (REQ-API-030). The stream-wide `CompressionLayer` leaves
+/// `text/event-stream` alone because a buffering encoder would hold events
+/// back; this one flushes after every chunk the SSE body yields, so each
+/// event (and keep-alive) reaches the client as soon as it is written,
+/// while the shared dictionary still shrinks repeated message JSON.
+pub fn compress_stream(request_headers: &HeaderMap, response: impl IntoResponse) -> Response {
+    let response = response.into_response();
+    let Some(encoding) = StreamEncoding::negotiate(request_headers) else {
+        return response;
+    };
+    PAYLOAD_METRICS
+        .compressed_streams
+        .fetch_add(1, Ordering::Relaxed);
+    let (mut parts, body) = response.into_parts();
+    parts.headers.insert(
+        CONTENT_ENCODING,
+        HeaderValue::from_static(encoding.as_str()),
+    );
+    parts
+        .headers
+        .append(VARY, HeaderValue::from_static("accept-encoding"));
+    parts.headers.remove(CONTENT_LENGTH);
+
+    let chunks = body.into_data_stream();
+    let state = (chunks, Some(encoding.encoder()));
+    let stream = futures::stream::unfold(state, |(mut chunks, encoder)| async move {
+        let mut encoder = encoder?;
+        let out = match chunks.next().await {
+            Some(Ok(chunk)) => encoder.compress(&chunk).await,
+            Some(Err(e)) => Err(std::io::Error::other(e)),
+            // Body done: write the trailer and end on the next poll.
+            None => return Some((encoder.finish().await, (chunks, None))),
+        };
+        Some((out, (chunks, Some(encoder))))
+    });
+    Response::from_parts(parts, Body::from_stream(stream))
+}
+
+/// Content codings [`compress_stream`] offers, in order of preference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum StreamEncoding {
+    Gzip,
+    Brotli,
+}
+
+impl StreamEncoding {
+    /// The best coding `Accept-Encoding` allows, ignoring `q` beyond
+    /// `q=0` (not acceptable).
+    fn negotiate(headers: &HeaderMap) -> Option<Self> {
+        headers
+            .get_all(ACCEPT_ENCODING)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|item| {
+                let mut params = item.split(';');
+                let name = params.next().unwrap_or_default().trim();
+                let refused = params
+                    .filter_map(|p| p.trim().strip_prefix("q="))
+                    .any(|q| q.trim().parse::<f32>().is_ok_and(|q| q <= 0.0));
+                if refused {
+                    return None;
+                }
+                match name.to_ascii_lowercase().as_str() {
+                    "br" => Some(Self::Brotli),
+                    "gzip" => Some(Self::Gzip),
+                    _ => None,
+                }
+            })
+            .max()
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Brotli => "br",
+        }
+    }
+
+    fn encoder(self) -> StreamEncoder {
+        match self {
+            Self::Gzip => StreamEncoder::Gzip(GzipEncoder::new(Vec::new())),
+            Self::Brotli => StreamEncoder::Brotli(Box::new(BrotliEncoder::new(Vec::new()))),
+        }
+    }
+}
+
+/// One stream's compressor. Each call returns the bytes that chunk
+/// produced, flushed so the client can decode them at once. Brotli's
+/// window is boxed so gzip streams do not carry its size around.
+enum StreamEncoder {
+    Gzip(GzipEncoder<Vec<u8>>),
+    Brotli(Box<BrotliEncoder<Vec<u8>>>),
+}
+
+impl StreamEncoder {
+    async fn compress(&mut self, chunk: &[u8]) -> std::io::Result<Bytes> {
+        let out = match self {
+            Self::Gzip(e) => {
+                e.write_all(chunk).await?;
+                e.flush().await?;
+                std::mem::take(e.get_mut())
+            }
+            Self::Brotli(e) => {
+                e.write_all(chunk).await?;
+                e.flush().await?;
+                std::mem::take(e.get_mut())
+            }
+        };
+        PAYLOAD_METRICS.record_compressed(chunk.len(), out.len());
+        Ok(out.into())
+    }
+
+    async fn finish(self) -> std::io::Result<Bytes> {
+        let out = match self {
+            Self::Gzip(mut e) => {
+                e.shutdown().await?;
+                e.into_inner()
+            }
+            Self::Brotli(mut e) => {
+                e.shutdown().await?;
+                (*e).into_inner()
+            }
+        };
+        PAYLOAD_METRICS.record_compressed(0, out.len());
+        Ok(out.into())
+    }
+}
+
+/// Catches up a subscriber whose broadcast receiver lagged.
+#[async_trait]
+pub trait Resync: Send + Sync {
+    /// Events that bring a client which last saw `last_seen` up to date, and
+    /// a receiver for everything after them. `None` ends the stream.
+    async fn resync(
+        &self,
+        last_seen: i64,
+    ) -> Option<(Vec<SseEvent>, broadcast::Receiver<SseEvent>)>;
+}
+
+/// One event ready for the wire: its SSE `id`, `event` type, and JSON
+/// `data`. The gRPC stream (REQ-API-020) carries the same three fields.
+#[derive(Debug)]
+pub struct Frame {
+    pub sequence_id: i64,
+    pub event_type: &'static str,
+    pub data: String,
+}
+
+impl From<Frame> for Event {
+    fn from(frame: Frame) -> Self {
+        Event::default()
+            .event(frame.event_type)
+            .id(frame.sequence_id.to_string())
+            .data(frame.data)
+    }
+}
+
+/// Live half of a conversation stream: the broadcast receiver plus whatever
+/// is queued ahead of it (the snapshot, replayed events, or a resync).
+pub struct Subscriber {
+    conv_id: String,
+    rx: broadcast::Receiver<SseEvent>,
+    pending: VecDeque<SseEvent>,
+    /// Highest `sequence_id` this stream has passed on (sent or filtered).
+    last_seen: i64,
+    filter: StreamFilter,
+    resync: Option<Arc<dyn Resync>>,
+    /// Held only for its Drop; see [`sse_stream`].
+    _presence: Option<PresenceGuard>,
+}
+
+impl Subscriber {
+    /// Deliver `queued` in order, then whatever arrives on `rx`.
+    pub fn new(
+        conv_id: String,
+        queued: Vec<SseEvent>,
+        rx: broadcast::Receiver<SseEvent>,
+        filter: StreamFilter,
+        resync: Option<Arc<dyn Resync>>,
+        presence: Option<PresenceGuard>,
+    ) -> Self {
+        Self {
+            conv_id,
+            rx,
+            pending: queued.into(),
+            last_seen: 0,
+            filter,
+            resync,
+            _presence: presence,
+        }
+    }
+
+    /// Next event the filter lets through, or `None` when the stream ends.
+    pub async fn next_frame(&mut self) -> Option<Frame> {
+        loop {
+            let event = match self.pending.pop_front() {
+                Some(event) => event,
+                None => match self.rx.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Closed) => return None,
+                    Err(RecvError::Lagged(n)) => {
+                        tracing::warn!(
+                            conv_id = %self.conv_id,
+                            lagged_by = n,
+                            last_seen = self.last_seen,
+                            "SSE broadcast lagged; resyncing client"
+                        );
+                        let (missed, rx) = self.resync.as_ref()?.resync(self.last_seen).await?;
+                        self.rx = rx;
+                        self.pending.extend(missed);
+                        continue;
+                    }
+                },
+            };
+            self.last_seen = self.last_seen.max(event.sequence_id());
+            if let Some(frame) = encode(event, &self.filter) {
+                return Some(frame);
+            }
+        }
+    }
+}
+
+/// Serialize `event` for the wire, or `None` when `filter` excludes it.
+fn encode(event: SseEvent, filter: &StreamFilter) -> Option<Frame> {
+    let sequence_id = event.sequence_id();
     let wire: SseWireEvent = event.into();
     let event_type = wire.event_type();
+    if !filter.allows(event_type) {
+        return None;
+    }
     // SseWireEvent derives Serialize over types that themselves derive
-    // Serialize (or carry `serde_json::Value`). `to_string` cannot fail
+    // Serialize (or carry `serde_json::Value`). Serialization cannot fail
     // at this layer; if it did, we'd want to know loudly.
-    let data = serde_json::to_string(&wire).expect("SseWireEvent is always serializable");
-    Event::default().event(event_type).data(data)
+    let mut data = if filter.thin {
+        let mut value = serde_json::to_value(&wire).expect("SseWireEvent is always serializable");
+        thin_payload(event_type, &mut value);
+        value.to_string()
+    } else {
+        serde_json::to_string(&wire).expect("SseWireEvent is always serializable")
+    };
+    let mut trimmed = 0;
+    if data.len() > MAX_EVENT_BYTES {
+        let mut value = serde_json::to_value(&wire).expect("SseWireEvent is always serializable");
+        if filter.thin {
+            thin_payload(event_type, &mut value);
+        }
+        trimmed = fit_payload(event_type, &mut value, data.len());
+        if trimmed == 0 {
+            tracing::warn!(
+                event_type,
+                bytes = data.len(),
+                "SSE event over the payload budget has no message body to drop"
+            );
+        } else {
+            data = value.to_string();
+            tracing::debug!(
+                event_type,
+                sequence_id,
+                trimmed,
+                "Trimmed oversized SSE event"
+            );
+        }
+    }
+    PAYLOAD_METRICS.record_event(data.len(), trimmed);
+    Some(Frame {
+        sequence_id,
+        event_type,
+        data,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::db::{ConvMode, Conversation, Message, MessageContent, MessageType, UsageData};
+    use crate::db::{
+        ConvMode, Conversation, ErrorKind, HistoryWindow, Message, MessageContent, MessageType,
+        UsageData,
+    };
+    use crate::runtime::remediation::classify;
     use crate::runtime::user_facing_error::UserFacingError;
     use crate::runtime::{ConversationMetadataUpdate, EnrichedConversation, SseBreadcrumb};
     use crate::state_machine::state::ConvState;
@@ -120,6 +604,7 @@ mod tests {
                 commits_behind,
                 commits_ahead,
                 project_name,
+                draft,
             } => {
                 let enriched_msgs: Vec<Value> =
                     messages.iter().map(enrich_message_for_api).collect();
@@ -136,6 +621,7 @@ mod tests {
                     "commits_behind": commits_behind,
                     "commits_ahead": commits_ahead,
                     "project_name": project_name,
+                    "draft": draft,
                 })
             }
             SseEvent::Message { message } => {
@@ -208,6 +694,36 @@ mod tests {
                 "message": error.flat_message(),
                 "error": error,
             }),
+            SseEvent::ErrorRemediation {
+                sequence_id,
+                remediation,
+            } => json!({
+                "type": "error_remediation",
+                "sequence_id": sequence_id,
+                "remediation": remediation,
+            }),
+            SseEvent::ContextWarning {
+                sequence_id,
+                used,
+                limit,
+                percent,
+            } => json!({
+                "type": "context_warning",
+                "sequence_id": sequence_id,
+                "used": used,
+                "limit": limit,
+                "percent": percent,
+            }),
+            SseEvent::ConversationContinued {
+                sequence_id,
+                conversation_id,
+                slug,
+            } => json!({
+                "type": "conversation_continued",
+                "sequence_id": sequence_id,
+                "conversation_id": conversation_id,
+                "slug": slug,
+            }),
             SseEvent::ConversationHardDeleted {
                 sequence_id,
                 conversation_id,
@@ -216,6 +732,43 @@ mod tests {
                 "sequence_id": sequence_id,
                 "conversation_id": conversation_id,
             }),
+            SseEvent::ClientJoined {
+                sequence_id,
+                client_id,
+                clients,
+                composer_holder,
+            } => json!({
+                "type": "client_joined",
+                "sequence_id": sequence_id,
+                "client_id": client_id,
+                "clients": clients,
+                "composer_holder": composer_holder,
+            }),
+            SseEvent::ClientLeft {
+                sequence_id,
+                client_id,
+                clients,
+                composer_holder,
+            } => json!({
+                "type": "client_left",
+                "sequence_id": sequence_id,
+                "client_id": client_id,
+                "clients": clients,
+                "composer_holder": composer_holder,
+            }),
+            SseEvent::ComposerChanged {
+                sequence_id,
+                composer_holder,
+            } => json!({
+                "type": "composer_changed",
+                "sequence_id": sequence_id,
+                "composer_holder": composer_holder,
+            }),
+            SseEvent::FilesChanged { sequence_id, paths } => json!({
+                "type": "files_changed",
+                "sequence_id": sequence_id,
+                "paths": paths,
+            }),
         }
     }
 
@@ -272,6 +825,11 @@ mod tests {
             seed_label: None,
             continued_in_conv_id: None,
             chain_name: None,
+            thinking_budget: None,
+            template: None,
+            disabled_tools: Vec::new(),
+            patch_review: false,
+            history_window: HistoryWindow::Full,
         }
     }
 
@@ -298,6 +856,7 @@ mod tests {
             content: MessageContent::user("hello"),
             display_data: None,
             usage_data: None,
+            model: None,
             created_at: ts(),
         }
     }
@@ -328,7 +887,9 @@ mod tests {
                 output_tokens: 5,
                 cache_creation_tokens: 0,
                 cache_read_tokens: 0,
+                model: None,
             }),
+            model: None,
             created_at: ts(),
         }
     }
@@ -361,6 +922,11 @@ mod tests {
             commits_behind: 0,
             commits_ahead: 3,
             project_name: Some("phoenix".to_string()),
+            draft: Some(crate::db::ConversationDraft {
+                text: "half-written".to_string(),
+                images: vec![],
+                updated_at: ts(),
+            }),
         };
         assert_parity(&event);
     }
@@ -515,6 +1081,8 @@ mod tests {
                 commits_behind: Some(0),
                 commits_ahead: Some(2),
                 task_title: None,
+                slug: None,
+                title: None,
             },
         };
         assert_parity(&event);
@@ -538,6 +1106,36 @@ mod tests {
         assert_parity(&event);
     }
 
+    #[test]
+    fn parity_error_remediation() {
+        let event = SseEvent::ErrorRemediation {
+            sequence_id: 25,
+            remediation: classify("prompt is too long", &ErrorKind::InvalidRequest),
+        };
+        assert_parity(&event);
+    }
+
+    #[test]
+    fn parity_context_warning() {
+        let event = SseEvent::ContextWarning {
+            sequence_id: 26,
+            used: 144_000,
+            limit: 200_000,
+            percent: 72,
+        };
+        assert_parity(&event);
+    }
+
+    #[test]
+    fn parity_conversation_continued() {
+        let event = SseEvent::ConversationContinued {
+            sequence_id: 27,
+            conversation_id: "conv-2".to_string(),
+            slug: Some("fix-auth-continued".to_string()),
+        };
+        assert_parity(&event);
+    }
+
     #[test]
     fn parity_conversation_hard_deleted() {
         let event = SseEvent::ConversationHardDeleted {
@@ -547,6 +1145,46 @@ mod tests {
         assert_parity(&event);
     }
 
+    #[test]
+    fn parity_client_joined() {
+        let event = SseEvent::ClientJoined {
+            sequence_id: 22,
+            client_id: "tab-b".to_string(),
+            clients: vec!["tab-a".to_string(), "tab-b".to_string()],
+            composer_holder: Some("tab-a".to_string()),
+        };
+        assert_parity(&event);
+    }
+
+    #[test]
+    fn parity_client_left() {
+        let event = SseEvent::ClientLeft {
+            sequence_id: 23,
+            client_id: "tab-a".to_string(),
+            clients: vec!["tab-b".to_string()],
+            composer_holder: None,
+        };
+        assert_parity(&event);
+    }
+
+    #[test]
+    fn parity_composer_changed() {
+        let event = SseEvent::ComposerChanged {
+            sequence_id: 24,
+            composer_holder: Some("tab-b".to_string()),
+        };
+        assert_parity(&event);
+    }
+
+    #[test]
+    fn parity_files_changed() {
+        let event = SseEvent::FilesChanged {
+            sequence_id: 25,
+            paths: vec!["src/main.rs".to_string()],
+        };
+        assert_parity(&event);
+    }
+
     // ------------------------------------------------------------------
     // Backwards-compat sanity: the axum Event is still constructed with
     // the correct `event:` label for every variant.
@@ -563,12 +1201,226 @@ mod tests {
             content: None,
             duration_ms: None,
         };
-        let axum_event = sse_event_to_axum(event);
+        let axum_event = Event::from(encode(event, &StreamFilter::default()).unwrap());
         let dbg = format!("{axum_event:?}");
         assert!(
             dbg.contains("message_updated"),
             "expected event label: {dbg}"
         );
         assert!(dbg.contains("msg-abc"), "expected id in payload: {dbg}");
+        assert!(
+            dbg.contains("id: 42"),
+            "expected sequence_id as event id: {dbg}"
+        );
+    }
+
+    #[test]
+    fn stream_filter_selects_groups() {
+        let filter = StreamFilter::parse(Some("state, message"), false).unwrap();
+        assert!(filter.allows("state_change"));
+        assert!(filter.allows("message_updated"));
+        assert!(!filter.allows("token"));
+        assert!(!filter.allows("client_joined"));
+        // Never filtered out.
+        assert!(filter.allows("init"));
+        assert!(filter.allows("conversation_hard_deleted"));
+
+        let all = StreamFilter::parse(None, false).unwrap();
+        assert!(all.allows("token"));
+
+        let err = StreamFilter::parse(Some("state,bogus"), false).unwrap_err();
+        assert!(err.contains("bogus"), "{err}");
+    }
+
+    #[test]
+    fn filtered_out_event_is_not_serialized() {
+        let filter = StreamFilter::parse(Some("message"), false).unwrap();
+        let token = SseEvent::Token {
+            sequence_id: 3,
+            text: "hi".to_string(),
+            request_id: "req-1".to_string(),
+        };
+        assert!(encode(token, &filter).is_none());
+    }
+
+    #[test]
+    fn thin_mode_keeps_ids_and_drops_bodies() {
+        let mut message = typed_sse_event_to_value(&SseEvent::Message {
+            message: fixture_agent_message_with_bash(),
+        });
+        thin_payload("message", &mut message);
+        let inner = &message["message"];
+        assert!(inner.get("content").is_none());
+        assert!(inner.get("display_data").is_none());
+        assert!(inner.get("usage_data").is_none());
+        assert!(inner["message_id"].is_string());
+        assert!(inner["sequence_id"].is_number());
+
+        let mut init = typed_sse_event_to_value(&SseEvent::Init {
+            sequence_id: 9,
+            conversation: Box::new(fixture_enriched_conversation()),
+            messages: vec![fixture_user_message(), fixture_agent_message_with_bash()],
+            agent_working: false,
+            display_state: "idle".to_string(),
+            last_sequence_id: 9,
+            context_window_size: 0,
+            breadcrumbs: vec![],
+            commits_behind: 0,
+            commits_ahead: 0,
+            project_name: None,
+            draft: None,
+        });
+        thin_payload("init", &mut init);
+        for m in init["messages"].as_array().unwrap() {
+            assert!(m.get("content").is_none());
+            assert!(m["message_id"].is_string());
+        }
+
+        let mut updated = typed_sse_event_to_value(&SseEvent::MessageUpdated {
+            sequence_id: 10,
+            message_id: "msg-abc".to_string(),
+            display_data: Some(json!({ "type": "subagent_summary" })),
+            content: None,
+            duration_ms: Some(12),
+        });
+        thin_payload("message_updated", &mut updated);
+        assert!(updated.get("display_data").is_none());
+        assert_eq!(updated["duration_ms"], 12);
+    }
+
+    #[test]
+    fn oversized_events_drop_the_largest_bodies() {
+        let mut big = fixture_user_message();
+        big.message_id = "msg-big".to_string();
+        big.content = MessageContent::user("x".repeat(MAX_EVENT_BYTES));
+        let init = SseEvent::Init {
+            sequence_id: 9,
+            conversation: Box::new(fixture_enriched_conversation()),
+            messages: vec![fixture_user_message(), big.clone()],
+            agent_working: false,
+            display_state: "idle".to_string(),
+            last_sequence_id: 9,
+            context_window_size: 0,
+            breadcrumbs: vec![],
+            commits_behind: 0,
+            commits_ahead: 0,
+            project_name: None,
+            draft: None,
+        };
+        let frame = encode(init, &StreamFilter::default()).unwrap();
+        assert!(frame.data.len() <= MAX_EVENT_BYTES);
+        let value: Value = serde_json::from_str(&frame.data).unwrap();
+        let messages = value["messages"].as_array().unwrap();
+        // Only the message that broke the budget loses its body
+        assert!(messages[0].get("content").is_some());
+        assert!(messages[0].get("truncated").is_none());
+        assert!(messages[1].get("content").is_none());
+        assert_eq!(messages[1]["truncated"], true);
+        assert_eq!(messages[1]["message_id"], "msg-big");
+
+        let frame = encode(SseEvent::Message { message: big }, &StreamFilter::default()).unwrap();
+        let value: Value = serde_json::from_str(&frame.data).unwrap();
+        assert_eq!(value["message"]["truncated"], true);
+        assert!(value["message"]["sequence_id"].is_number());
+
+        // Events within the budget are untouched
+        let frame = encode(
+            SseEvent::Message {
+                message: fixture_user_message(),
+            },
+            &StreamFilter::default(),
+        )
+        .unwrap();
+        assert!(!frame.data.contains("truncated"));
+    }
+
+    #[test]
+    fn stream_encoding_follows_accept_encoding() {
+        let negotiate = |accept: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(ACCEPT_ENCODING, HeaderValue::from_str(accept).unwrap());
+            StreamEncoding::negotiate(&headers)
+        };
+        assert_eq!(negotiate("gzip, deflate, br"), Some(StreamEncoding::Brotli));
+        assert_eq!(negotiate("gzip;q=0.5, br;q=0"), Some(StreamEncoding::Gzip));
+        assert_eq!(negotiate("GZIP"), Some(StreamEncoding::Gzip));
+        assert_eq!(negotiate("identity, deflate"), None);
+        assert_eq!(StreamEncoding::negotiate(&HeaderMap::new()), None);
+    }
+
+    #[tokio::test]
+    async fn compressed_events_decode_as_they_arrive() {
+        use async_compression::tokio::write::GzipDecoder;
+
+        let mut encoder = StreamEncoding::Gzip.encoder();
+        let mut decoder = GzipDecoder::new(Vec::new());
+        for event in ["event: init\ndata: {}\n\n", "event: token\ndata: {}\n\n"] {
+            let compressed = encoder.compress(event.as_bytes()).await.unwrap();
+            decoder.write_all(&compressed).await.unwrap();
+            decoder.flush().await.unwrap();
+            // Everything sent so far decodes without waiting for more
+            assert_eq!(std::mem::take(decoder.get_mut()), event.as_bytes());
+        }
+        let trailer = encoder.finish().await.unwrap();
+        decoder.write_all(&trailer).await.unwrap();
+        decoder.shutdown().await.unwrap();
+    }
+
+    struct BufferResync(crate::runtime::SseBroadcaster);
+
+    #[async_trait]
+    impl Resync for BufferResync {
+        async fn resync(
+            &self,
+            last_seen: i64,
+        ) -> Option<(Vec<SseEvent>, broadcast::Receiver<SseEvent>)> {
+            self.0.resume(last_seen)
+        }
+    }
+
+    fn subscriber(
+        b: &crate::runtime::SseBroadcaster,
+        resync: Option<Arc<dyn Resync>>,
+    ) -> Subscriber {
+        let filter = StreamFilter::default();
+        Subscriber::new(
+            "conv-1".to_string(),
+            vec![],
+            b.subscribe(),
+            filter,
+            resync,
+            None,
+        )
+    }
+
+    fn send_tokens(b: &crate::runtime::SseBroadcaster, count: usize) {
+        for i in 0..count {
+            let _ = b.send_seq(|seq| SseEvent::Token {
+                sequence_id: seq,
+                text: format!("t{i}"),
+                request_id: "req-1".to_string(),
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn lagged_subscriber_is_resynced_in_place() {
+        let b = crate::runtime::SseBroadcaster::new(2, 0);
+        let mut sub = subscriber(&b, Some(Arc::new(BufferResync(b.clone()))));
+        send_tokens(&b, 5);
+        // The channel only held two; the rest come from the replay buffer,
+        // in order and without gaps.
+        for expected in 1..=5 {
+            let frame = sub.next_frame().await.expect("stream stays open");
+            assert_eq!(frame.sequence_id, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn lagged_subscriber_without_resync_ends_stream() {
+        let b = crate::runtime::SseBroadcaster::new(2, 0);
+        let mut sub = subscriber(&b, None);
+        send_tokens(&b, 5);
+        assert!(sub.next_frame().await.is_none());
     }
 }
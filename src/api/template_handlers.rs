@@ -0,0 +1,131 @@
+//! Conversation template HTTP handlers (REQ-API-018).
+//!
+//! Templates are presets picked when a conversation is created: a system
+//! prompt addendum, a tool selection, and a default model. They live in the
+//! `conversation_templates` table; these handlers are thin CRUD wrappers
+//! that validate names and models before writing.
+
+use super::handlers::AppError;
+use super::types::{
+    ConflictErrorResponse, ConversationTemplateRequest, SuccessResponse, TemplatesResponse,
+};
+use super::AppState;
+use crate::db::ConversationTemplate;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+/// Template names are URL path segments and appear in create requests, so
+/// they are kept to lowercase kebab-case.
+fn validate_name(name: &str) -> Result<(), AppError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::BadRequest(format!(
+            "Invalid template name '{name}': use lowercase letters, digits, and '-'"
+        )))
+    }
+}
+
+fn to_template(
+    state: &AppState,
+    name: String,
+    req: ConversationTemplateRequest,
+) -> Result<ConversationTemplate, AppError> {
+    validate_name(&name)?;
+    if req.system_prompt.trim().is_empty() {
+        return Err(AppError::BadRequest("system_prompt cannot be empty".into()));
+    }
+    if let Some(model) = req.model.as_deref() {
+        if state.llm_registry.get(model).is_none() {
+            return Err(AppError::BadRequest(format!(
+                "Model '{model}' is not available"
+            )));
+        }
+    }
+    Ok(ConversationTemplate {
+        name,
+        description: req.description,
+        system_prompt: req.system_prompt,
+        tools: req.tools,
+        model: req.model,
+    })
+}
+
+fn not_found(name: &str) -> AppError {
+    AppError::NotFound(format!("Template '{name}' not found"))
+}
+
+pub(crate) async fn list_templates(
+    State(state): State<AppState>,
+) -> Result<Json<TemplatesResponse>, AppError> {
+    let templates = state.db.list_conversation_templates().await?;
+    Ok(Json(TemplatesResponse { templates }))
+}
+
+pub(crate) async fn get_template(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ConversationTemplate>, AppError> {
+    state
+        .db
+        .get_conversation_template(&name)
+        .await?
+        .map(Json)
+        .ok_or_else(|| not_found(&name))
+}
+
+pub(crate) async fn create_template(
+    State(state): State<AppState>,
+    Json(req): Json<ConversationTemplateRequest>,
+) -> Result<(StatusCode, Json<ConversationTemplate>), AppError> {
+    let Some(name) = req.name.clone() else {
+        return Err(AppError::BadRequest("name is required".into()));
+    };
+    let template = to_template(&state, name, req)?;
+    let created = state.db.create_conversation_template(&template).await?;
+    if !created {
+        return Err(AppError::Conflict(Box::new(ConflictErrorResponse::new(
+            format!("Template '{}' already exists", template.name),
+            "template_exists",
+        ))));
+    }
+    Ok((StatusCode::CREATED, Json(template)))
+}
+
+pub(crate) async fn update_template(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(req): Json<ConversationTemplateRequest>,
+) -> Result<Json<ConversationTemplate>, AppError> {
+    if req.name.as_deref().is_some_and(|n| n != name) {
+        return Err(AppError::BadRequest(
+            "renaming a template is not supported; delete and re-create it".into(),
+        ));
+    }
+    let template = to_template(&state, name, req)?;
+    let updated = state.db.update_conversation_template(&template).await?;
+    if !updated {
+        return Err(not_found(&template.name));
+    }
+    Ok(Json(template))
+}
+
+pub(crate) async fn delete_template(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<SuccessResponse>, AppError> {
+    let deleted = state.db.delete_conversation_template(&name).await?;
+    if !deleted {
+        return Err(not_found(&name));
+    }
+    Ok(Json(SuccessResponse { success: true }))
+}
@@ -0,0 +1,123 @@
+//! Conversation state timeline (REQ-API-023).
+//!
+//! `GET /api/conversations/:id/timeline` turns the transition log
+//! (REQ-API-017) into stretches of time: each recorded transition starts a
+//! stretch in its new state that lasts until the next one. For a long agent
+//! run this shows how much of the wall clock went to waiting on the model
+//! versus running tools, retrying, or waiting on the user. The last stretch
+//! is still open and runs up to the request.
+
+use axum::extract::{Path, State};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use super::handlers::AppError;
+use super::types::{StateDuration, TimelineEntry, TimelineResponse};
+use super::AppState;
+use crate::db::StateChange;
+
+/// Stretches of time per state for a conversation, oldest first.
+pub(super) async fn get_timeline(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<TimelineResponse>, AppError> {
+    state.db.get_conversation(&id).await?;
+    let changes = state.db.list_state_changes(&id).await?;
+    Ok(Json(build_timeline(&changes, Utc::now())))
+}
+
+fn millis_between(start: DateTime<Utc>, end: DateTime<Utc>) -> u64 {
+    u64::try_from((end - start).num_milliseconds()).unwrap_or(0)
+}
+
+/// Entries for `changes`, the last one open until `now`, and totals over
+/// the finished ones. An open stretch is left out of the totals so a
+/// conversation idle since yesterday does not drown out the run itself.
+fn build_timeline(changes: &[StateChange], now: DateTime<Utc>) -> TimelineResponse {
+    let entries: Vec<TimelineEntry> = changes
+        .iter()
+        .enumerate()
+        .map(|(i, change)| {
+            let next = changes.get(i + 1);
+            TimelineEntry {
+                state: change.state.clone(),
+                event_type: change.event_type.clone(),
+                started_at: change.at,
+                duration_ms: millis_between(change.at, next.map_or(now, |n| n.at)),
+                ongoing: next.is_none(),
+            }
+        })
+        .collect();
+
+    let mut by_state: HashMap<&str, (u64, u32)> = HashMap::new();
+    for entry in entries.iter().filter(|e| !e.ongoing) {
+        let total = by_state.entry(entry.state.as_str()).or_default();
+        total.0 += entry.duration_ms;
+        total.1 += 1;
+    }
+    let mut totals: Vec<StateDuration> = by_state
+        .into_iter()
+        .map(|(state, (duration_ms, entries))| StateDuration {
+            state: state.to_string(),
+            duration_ms,
+            entries,
+        })
+        .collect();
+    totals.sort_by(|a, b| {
+        b.duration_ms
+            .cmp(&a.duration_ms)
+            .then_with(|| a.state.cmp(&b.state))
+    });
+
+    TimelineResponse { entries, totals }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(state: &str, event_type: &str, second: i64) -> StateChange {
+        StateChange {
+            state: state.to_string(),
+            event_type: event_type.to_string(),
+            at: DateTime::from_timestamp(1_700_000_000 + second, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn stretches_run_until_the_next_change() {
+        let changes = [
+            change("llm_requesting", "UserMessage", 0),
+            change("tool_executing", "LlmResponse", 30),
+            change("llm_requesting", "ToolComplete", 90),
+            change("idle", "LlmResponse", 100),
+        ];
+        let now = DateTime::from_timestamp(1_700_000_500, 0).unwrap();
+        let timeline = build_timeline(&changes, now);
+
+        let durations: Vec<u64> = timeline.entries.iter().map(|e| e.duration_ms).collect();
+        assert_eq!(durations, [30_000, 60_000, 10_000, 400_000]);
+        assert_eq!(timeline.entries[1].event_type, "LlmResponse");
+        assert!(timeline.entries[3].ongoing);
+        assert!(!timeline.entries[2].ongoing);
+
+        // The open idle stretch is not counted
+        let totals: Vec<(&str, u64, u32)> = timeline
+            .totals
+            .iter()
+            .map(|t| (t.state.as_str(), t.duration_ms, t.entries))
+            .collect();
+        assert_eq!(
+            totals,
+            [("tool_executing", 60_000, 1), ("llm_requesting", 40_000, 2)]
+        );
+    }
+
+    #[test]
+    fn empty_log_has_an_empty_timeline() {
+        let timeline = build_timeline(&[], Utc::now());
+        assert!(timeline.entries.is_empty());
+        assert!(timeline.totals.is_empty());
+    }
+}
@@ -29,6 +29,13 @@ pub struct CreateConversationRequest {
     /// the seeded conversation's breadcrumb.
     #[serde(default)]
     pub seed_label: Option<String>,
+    /// Conversation template name (REQ-API-018), e.g. "bug-fix". Supplies
+    /// the model when `model` is omitted.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Tools the agent may not use (REQ-BED-039), e.g. `["bash"]`
+    #[serde(default)]
+    pub disabled_tools: Vec<String>,
 }
 
 /// Request to upgrade a conversation's model
@@ -38,6 +45,57 @@ pub struct UpgradeModelRequest {
     pub model: String,
 }
 
+/// Request to set a conversation's extended-thinking budget (REQ-LLM-014)
+#[derive(Debug, Deserialize)]
+pub struct SetThinkingRequest {
+    /// Budget in tokens; `null` turns thinking off
+    pub budget_tokens: Option<u32>,
+}
+
+/// Request to set how much history a conversation's LLM requests carry
+/// (REQ-BED-046)
+#[derive(Debug, Deserialize)]
+pub struct SetHistoryWindowRequest {
+    pub window: crate::db::HistoryWindow,
+}
+
+/// Request to store a conversation's unsent draft (REQ-BED-047). Empty text
+/// and no images clears it.
+#[derive(Debug, Deserialize)]
+pub struct SaveDraftRequest {
+    pub text: String,
+    #[serde(default)]
+    pub images: Vec<ImageAttachment>,
+}
+
+/// Request to replace a conversation's disabled tools (REQ-BED-039)
+#[derive(Debug, Deserialize)]
+pub struct SetToolsRequest {
+    /// Tool names the agent may not use; empty enables everything
+    pub disabled: Vec<String>,
+}
+
+/// Request to run a shell command the user typed (REQ-BASH-020)
+#[derive(Debug, Deserialize)]
+pub struct RunCommandRequest {
+    pub command: String,
+}
+
+/// Request to turn patch review on or off (REQ-PATCH-010)
+#[derive(Debug, Deserialize)]
+pub struct SetPatchReviewRequest {
+    pub enabled: bool,
+}
+
+/// Request to set a project's post-edit verify command (REQ-BED-037)
+#[derive(Debug, Deserialize)]
+pub struct SetVerifyRequest {
+    /// Shell command; `null` or blank removes verification
+    pub command: Option<String>,
+    /// Failed runs fed back before giving up; defaults to 3
+    pub max_attempts: Option<u32>,
+}
+
 /// Request to send a chat message
 #[derive(Debug, Deserialize)]
 pub struct ChatRequest {
@@ -50,6 +108,61 @@ pub struct ChatRequest {
     /// Browser user agent for display (e.g., show iPhone icon)
     #[serde(default)]
     pub user_agent: Option<String>,
+    /// Per-tab presence id (REQ-API-013). When set, the submission is gated
+    /// on the composer lease and deduplicated against other tabs.
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// Models to answer this message side by side (REQ-BED-058). Empty
+    /// for an ordinary message to the conversation's model.
+    #[serde(default)]
+    pub council: Vec<String>,
+}
+
+/// Request to steer a running agent (REQ-BED-034)
+#[derive(Debug, Deserialize)]
+pub struct SteerRequest {
+    pub text: String,
+    /// Client-generated UUID, used as the id of the persisted note
+    pub message_id: String,
+}
+
+/// Request to claim or release a conversation's composer (REQ-API-013)
+#[derive(Debug, Deserialize)]
+pub struct ComposerRequest {
+    pub client_id: String,
+    /// `true` claims (or renews) the lease; `false` releases it.
+    pub typing: bool,
+}
+
+/// Outcome of a command the user ran (REQ-BASH-020). The tool message is
+/// also broadcast on the conversation's stream.
+#[derive(Debug, Serialize)]
+pub struct RunCommandResponse {
+    pub message_id: String,
+    pub success: bool,
+    /// The bash tool's JSON response
+    pub output: String,
+}
+
+/// Presence snapshot returned by the composer endpoint (REQ-API-013)
+#[derive(Debug, Serialize)]
+pub struct ComposerResponse {
+    pub clients: Vec<String>,
+    pub composer_holder: Option<String>,
+}
+
+/// Response for `POST /api/admin/backup` (REQ-API-015)
+#[derive(Debug, Serialize)]
+pub struct BackupResponse {
+    pub backup: crate::db::backup::BackupInfo,
+    /// Older backups deleted by rotation.
+    pub removed: Vec<String>,
+}
+
+/// Response for `GET /api/admin/backups` (REQ-API-015)
+#[derive(Debug, Serialize)]
+pub struct BackupListResponse {
+    pub backups: Vec<crate::db::backup::BackupInfo>,
 }
 
 /// Image attachment in a chat message
@@ -193,6 +306,29 @@ pub struct ListFilesResponse {
 pub struct ReadFileResponse {
     pub content: String,
     pub encoding: String,
+    /// Set for a file too large to return whole, or when a chunk was asked
+    /// for (REQ-FE-012). `content` then holds only `chunk`, or nothing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<Vec<crate::file_chunks::FileChunk>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk: Option<usize>,
+}
+
+/// Request to write a file in a conversation's directories (REQ-FE-011).
+/// A relative `path` is taken from the conversation's cwd.
+#[derive(Debug, Deserialize)]
+pub struct WriteFileRequest {
+    pub path: String,
+    pub content: String,
+}
+
+/// Response for a file written through the files API (REQ-FE-011)
+#[derive(Debug, Serialize)]
+pub struct WriteFileResponse {
+    /// The resolved absolute path
+    pub path: String,
+    /// Whether the agent was told about the change
+    pub noted: bool,
 }
 
 /// Error response for file operations
@@ -211,6 +347,10 @@ pub struct ModelInfo {
     pub description: String,
     pub context_window: usize,
     pub recommended: bool,
+    /// Capability flags and limits (REQ-BED-041)
+    pub supports_vision: bool,
+    pub supports_tools: bool,
+    pub max_output_tokens: u32,
 }
 
 /// Gateway reachability status surfaced to the frontend
@@ -283,6 +423,9 @@ pub struct FileSearchQuery {
     pub q: String,
     /// Maximum number of results (default 50)
     pub limit: Option<usize>,
+    /// Search this extra root of the conversation instead of its cwd
+    /// (REQ-BED-049)
+    pub root: Option<String>,
 }
 
 /// A single skill entry returned by the skills API (REQ-IR-005)
@@ -296,6 +439,22 @@ pub struct SkillEntry {
     pub source: String,
     /// Absolute path to the SKILL.md file
     pub path: String,
+    /// Trigger keywords for automatic inclusion (REQ-SK-008)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<String>,
+}
+
+impl From<crate::system_prompt::SkillMetadata> for SkillEntry {
+    fn from(s: crate::system_prompt::SkillMetadata) -> Self {
+        Self {
+            name: s.name,
+            description: s.description,
+            argument_hint: s.argument_hint,
+            source: s.source,
+            path: s.path.to_string_lossy().to_string(),
+            triggers: s.triggers,
+        }
+    }
 }
 
 /// Response for the skills list endpoint (REQ-IR-005)
@@ -304,6 +463,421 @@ pub struct SkillsResponse {
     pub skills: Vec<SkillEntry>,
 }
 
+/// A server-side slash command returned by `GET /api/commands` (REQ-IR-009)
+#[derive(Debug, Serialize)]
+pub struct CommandEntry {
+    /// Command name without the leading `/`
+    pub name: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub argument_hint: Option<String>,
+}
+
+/// Response for the slash-command list endpoint (REQ-IR-009)
+#[derive(Debug, Serialize)]
+pub struct CommandsResponse {
+    pub commands: Vec<CommandEntry>,
+}
+
+/// Query for the usage summary endpoint (REQ-LLM-010)
+#[derive(Debug, Deserialize)]
+pub struct UsageSummaryQuery {
+    #[serde(default)]
+    pub group_by: crate::db::UsageGroupBy,
+}
+
+/// Token counts plus estimated spend for one slice of `turn_usage`
+#[derive(Debug, Default, Serialize)]
+pub struct UsageCost {
+    #[serde(flatten)]
+    pub totals: crate::db::UsageTotals,
+    /// Estimated spend in USD at list prices
+    pub cost_usd: f64,
+    /// Turns on models without known pricing; `cost_usd` excludes them
+    pub unpriced_turns: i64,
+    /// Fraction of prompt tokens served from the prompt cache
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_hit_rate: Option<f64>,
+}
+
+/// One group in the usage summary
+#[derive(Debug, Serialize)]
+pub struct UsageGroup {
+    /// Model ID, UTC date (`YYYY-MM-DD`), or root conversation ID
+    pub key: String,
+    /// Conversation title when grouping by conversation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub usage: UsageCost,
+}
+
+/// Response for the usage summary endpoint (REQ-LLM-010)
+#[derive(Debug, Serialize)]
+pub struct UsageSummaryResponse {
+    pub group_by: crate::db::UsageGroupBy,
+    pub groups: Vec<UsageGroup>,
+    pub total: UsageCost,
+}
+
+/// Response for `GET /api/audit` (REQ-API-016)
+#[derive(Debug, Serialize)]
+pub struct AuditLogResponse {
+    pub entries: Vec<crate::db::AuditEntry>,
+}
+
+/// Request to rate an agent message (REQ-API-022)
+#[derive(Debug, Deserialize)]
+pub struct MessageFeedbackRequest {
+    pub rating: crate::db::FeedbackRating,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// Response for `POST /api/messages/:id/feedback` (REQ-API-022)
+#[derive(Debug, Serialize)]
+pub struct MessageFeedbackResponse {
+    pub feedback: crate::db::MessageFeedback,
+}
+
+/// Filters for `GET /api/feedback/export` (REQ-API-022). All optional.
+#[derive(Debug, Default, Deserialize)]
+pub struct FeedbackExportQuery {
+    pub rating: Option<crate::db::FeedbackRating>,
+    /// Inclusive lower bound on when the message was first rated.
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Query for `GET /api/conversations/:id/transitions` (REQ-API-017)
+#[derive(Debug, Default, Deserialize)]
+pub struct TransitionsQuery {
+    /// Return only transitions recorded after this row id (for paging).
+    pub after_id: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+/// Response for `GET /api/conversations/:id/transitions` (REQ-API-017)
+#[derive(Debug, Serialize)]
+pub struct TransitionsResponse {
+    pub transitions: Vec<crate::db::TransitionRecord>,
+}
+
+/// Query for `GET /api/conversations/:id/events` (REQ-BED-054)
+#[derive(Debug, Default, Deserialize)]
+pub struct EventsQuery {
+    /// Return only events logged after this row id (for paging).
+    pub after_id: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+/// Response for `GET /api/conversations/:id/events` (REQ-BED-054)
+#[derive(Debug, Serialize)]
+pub struct EventsResponse {
+    pub events: Vec<crate::db::EventRecord>,
+}
+
+/// One stretch of time a conversation spent in a state (REQ-API-023)
+#[derive(Debug, Serialize)]
+pub struct TimelineEntry {
+    /// State type, e.g. `llm_requesting` or `tool_executing`
+    pub state: String,
+    /// Event that moved the conversation into the state
+    pub event_type: String,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub duration_ms: u64,
+    /// Still in this state; `duration_ms` runs up to the request
+    pub ongoing: bool,
+}
+
+/// Time spent in one state across the timeline
+#[derive(Debug, Serialize)]
+pub struct StateDuration {
+    pub state: String,
+    pub duration_ms: u64,
+    /// How many separate stretches make up the total
+    pub entries: u32,
+}
+
+/// Response for `GET /api/conversations/:id/timeline` (REQ-API-023)
+#[derive(Debug, Serialize)]
+pub struct TimelineResponse {
+    pub entries: Vec<TimelineEntry>,
+    /// Per-state totals over the finished entries, longest first
+    pub totals: Vec<StateDuration>,
+}
+
+/// Response for `GET /api/push/key` (REQ-API-024)
+#[derive(Debug, Serialize)]
+pub struct PushKeyResponse {
+    /// VAPID public key, base64url: the `applicationServerKey` to subscribe with
+    pub public_key: String,
+}
+
+/// A browser push subscription as `PushSubscription.toJSON()` gives it
+/// (REQ-API-024)
+#[derive(Debug, Deserialize)]
+pub struct PushSubscribeRequest {
+    pub endpoint: String,
+    pub keys: PushSubscriptionKeys,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PushSubscriptionKeys {
+    pub p256dh: String,
+    pub auth: String,
+}
+
+/// Request to drop a push subscription (REQ-API-024)
+#[derive(Debug, Deserialize)]
+pub struct PushUnsubscribeRequest {
+    pub endpoint: String,
+}
+
+/// Whether a conversation sends push notifications (REQ-API-024); both the
+/// request and the response of `/api/conversations/:id/push`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConversationPushSetting {
+    pub enabled: bool,
+}
+
+/// Request for `POST /api/admin/providers` (REQ-LLM-019)
+#[derive(Debug, Deserialize)]
+pub struct ProviderKeyRequest {
+    /// `anthropic` or `openai`
+    pub provider: String,
+    pub api_key: String,
+}
+
+/// Response for `/api/admin/providers` (REQ-LLM-019): the models available
+/// once the registry has reloaded
+#[derive(Debug, Serialize)]
+pub struct ProviderKeyResponse {
+    pub provider: String,
+    pub models: Vec<String>,
+}
+
+/// Response for `/api/admin/stream-metrics` (REQ-API-030): SSE payload
+/// totals since startup, across every conversation stream
+#[derive(Debug, Serialize)]
+pub struct StreamMetrics {
+    /// Events sent, and the bytes of their JSON `data`
+    pub events: u64,
+    pub payload_bytes: u64,
+    pub largest_event_bytes: u64,
+    /// The per-event budget above which message bodies are dropped
+    pub max_event_bytes: u64,
+    /// Events that went over the budget, and the messages trimmed from them
+    pub truncated_events: u64,
+    pub truncated_messages: u64,
+    /// Streams sent compressed, and the bytes before and after compression
+    pub compressed_streams: u64,
+    pub compressed_bytes_in: u64,
+    pub compressed_bytes_out: u64,
+}
+
+/// A conversation's extra roots (REQ-BED-049); both the request and the
+/// response of `/api/conversations/:id/roots`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConversationRoots {
+    pub roots: Vec<crate::db::ConversationRoot>,
+}
+
+/// Response for `POST`/`DELETE /api/conversations/:id/messages/:message_id/pin`
+/// (REQ-BED-045): every pinned message in the conversation, in order
+#[derive(Debug, Serialize)]
+pub struct PinnedMessagesResponse {
+    pub message_ids: Vec<String>,
+}
+
+/// Response for `GET /api/conversations/:id/files` (REQ-BED-042)
+#[derive(Debug, Serialize)]
+pub struct TouchedFilesResponse {
+    pub files: Vec<crate::db::TouchedFile>,
+}
+
+/// Response for `GET /api/conversations/:id/usage/by-tool` (REQ-API-031)
+#[derive(Debug, Serialize)]
+pub struct ToolUsageResponse {
+    pub tools: Vec<crate::db::ToolTokenUsage>,
+    /// Prompt tokens the conversation's requests were billed for, cached or
+    /// not; the tools' estimates are a share of this.
+    pub prompt_tokens: i64,
+}
+
+/// Query for `GET /api/conversations/:id/llm-log` (REQ-LLM-017)
+#[derive(Debug, Default, Deserialize)]
+pub struct LlmLogQuery {
+    /// Most recent entries to return (default 50).
+    pub limit: Option<usize>,
+}
+
+/// Response for `GET /api/conversations/:id/llm-log` (REQ-LLM-017)
+#[derive(Debug, Serialize)]
+pub struct LlmLogResponse {
+    /// False when `PHOENIX_LLM_LOG` is off; `entries` is then empty.
+    pub enabled: bool,
+    /// Logged request/response pairs, oldest first.
+    pub entries: Vec<serde_json::Value>,
+}
+
+/// Request body for creating or replacing a library skill (REQ-SK-009).
+/// On `PUT /api/skills/:name` the path segment is authoritative and
+/// `name` may be omitted.
+#[derive(Debug, Deserialize)]
+pub struct LibrarySkillRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub description: String,
+    #[serde(default)]
+    pub argument_hint: Option<String>,
+    #[serde(default)]
+    pub triggers: Vec<String>,
+    pub body: String,
+}
+
+/// A library skill with its markdown body (REQ-SK-009)
+#[derive(Debug, Serialize)]
+pub struct LibrarySkillResponse {
+    pub name: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub argument_hint: Option<String>,
+    pub triggers: Vec<String>,
+    /// Markdown body without frontmatter
+    pub body: String,
+}
+
+impl From<crate::skills::LibrarySkill> for LibrarySkillResponse {
+    fn from(s: crate::skills::LibrarySkill) -> Self {
+        Self {
+            name: s.name,
+            description: s.description,
+            argument_hint: s.argument_hint,
+            triggers: s.triggers,
+            body: s.body,
+        }
+    }
+}
+
+/// Create or replace a conversation template (REQ-API-018). `name` is
+/// required on create and must match the path on update.
+#[derive(Debug, Deserialize)]
+pub struct ConversationTemplateRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    pub system_prompt: String,
+    /// Tool names; omit or `null` for the mode's full tool set
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// Response for `GET /api/templates`
+#[derive(Debug, Serialize)]
+pub struct TemplatesResponse {
+    pub templates: Vec<crate::db::ConversationTemplate>,
+}
+
+/// A tool that can be switched off per conversation (REQ-BED-039)
+#[derive(Debug, Serialize)]
+pub struct ToolEntry {
+    pub name: String,
+    pub description: String,
+    /// "builtin", "plugin" for WASM plugins, or "mcp" for MCP server tools
+    pub source: &'static str,
+}
+
+/// Response for `GET /api/tools`
+#[derive(Debug, Serialize)]
+pub struct ToolsResponse {
+    pub tools: Vec<ToolEntry>,
+}
+
+/// A staged patch awaiting the user's decision (REQ-PATCH-010)
+#[derive(Debug, Serialize)]
+pub struct PendingPatch {
+    pub tool_use_id: String,
+    pub path: String,
+    /// Unified diff of the edit
+    pub diff: String,
+}
+
+/// Response for `GET /api/conversations/:id/pending-patches`
+#[derive(Debug, Serialize)]
+pub struct PendingPatchesResponse {
+    pub patches: Vec<PendingPatch>,
+}
+
+/// A file stored by `POST /api/conversations/:id/attachments` (REQ-API-021)
+#[derive(Debug, Serialize)]
+pub struct AttachmentInfo {
+    pub attachment_id: String,
+    /// Sanitized file name as stored
+    pub name: String,
+    /// Absolute path on the server
+    pub path: String,
+    /// Path relative to the conversation's working directory
+    pub relative_path: String,
+    /// Text to put in a message to cite the file: an `@path` reference
+    /// (inlined for the model, REQ-IR-001) for text files, the bare relative
+    /// path for binary ones
+    pub reference: String,
+    pub size: u64,
+    pub media_type: String,
+}
+
+/// Response for `POST /api/conversations/:id/attachments`
+#[derive(Debug, Serialize)]
+pub struct AttachmentsResponse {
+    pub attachments: Vec<AttachmentInfo>,
+}
+
+/// Request for `POST /api/conversations/:id/browser-session/playwright`
+/// (REQ-BT-026)
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PlaywrightExportRequest {
+    /// Where to write the test, relative to the working directory.
+    /// Defaults to `tests/phoenix/<slug>.spec.ts`.
+    pub path: Option<String>,
+    /// Replace an existing file at `path`
+    pub overwrite: bool,
+}
+
+/// Response for `POST /api/conversations/:id/browser-session/playwright`
+#[derive(Debug, Serialize)]
+pub struct PlaywrightExportResponse {
+    /// Absolute path on the server
+    pub path: String,
+    /// Path relative to the conversation's working directory
+    pub relative_path: String,
+    /// Browser calls turned into Playwright statements
+    pub translated: usize,
+    /// Browser calls kept as comments: failed, unfinished, or untranslatable
+    pub skipped: usize,
+}
+
+/// A file a conversation's tools produced (REQ-BT-034)
+#[derive(Debug, Serialize)]
+pub struct ArtifactEntry {
+    pub name: String,
+    /// Path relative to the conversation's working directory
+    pub path: String,
+    /// What produced it: `download` for files the browser downloaded
+    pub kind: &'static str,
+    pub size: u64,
+    pub modified: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Response for `GET /api/conversations/:id/artifacts`, newest first
+#[derive(Debug, Serialize)]
+pub struct ArtifactsResponse {
+    pub artifacts: Vec<ArtifactEntry>,
+}
+
 /// A task file entry returned by the tasks list endpoint.
 #[derive(Debug, Serialize)]
 pub struct TaskEntry {
@@ -473,16 +1047,68 @@ pub struct ConversationDiffResponse {
     pub uncommitted_saturated: bool,
 }
 
+/// Machine-readable kind of an error response (REQ-API-029). Every error
+/// body carries one as `code`, next to a `retryable` hint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    BadRequest,
+    NotFound,
+    /// A 409 the client has to resolve first; `error_type` says which
+    Conflict,
+    /// An `@` reference or skill in the message could not be expanded
+    InvalidReference,
+    /// The server is rate limiting this client
+    RateLimited,
+    /// The database failed, often only for a moment (a locked database)
+    Database,
+    /// The LLM provider rejected the server's credentials
+    LlmAuth,
+    /// The LLM provider is rate limiting the server
+    LlmRateLimited,
+    /// The LLM provider could not be reached or failed on its side
+    LlmUnavailable,
+    /// The LLM provider refused the request itself
+    LlmRejected,
+    /// The request reached for a path it may not touch
+    SandboxViolation,
+    /// The conversation is mid-turn or being edited in another tab
+    ConversationBusy,
+    Internal,
+}
+
+impl ErrorCode {
+    /// Whether the same request may succeed later, unchanged
+    pub fn retryable(self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited
+                | Self::Database
+                | Self::LlmRateLimited
+                | Self::LlmUnavailable
+                | Self::ConversationBusy
+        )
+    }
+}
+
 /// Error response
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
     pub error: String,
+    pub code: ErrorCode,
+    pub retryable: bool,
+    /// How long to wait before retrying, when the server knows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
 }
 
 impl ErrorResponse {
-    pub fn new(message: impl Into<String>) -> Self {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
         Self {
             error: message.into(),
+            code,
+            retryable: code.retryable(),
+            retry_after_ms: None,
         }
     }
 }
@@ -53,10 +53,10 @@ use serde_json::Value;
 use ts_rs::TS;
 
 use crate::chain_runtime::ChainSseEvent;
-use crate::db::{Message, MessageType, UsageData};
+use crate::db::{ConversationDraft, Message, MessageType, UsageData};
 use crate::runtime::{
-    user_facing_error::UserFacingError, ConversationMetadataUpdate, EnrichedConversation,
-    SseBreadcrumb, SseEvent,
+    remediation::Remediation, user_facing_error::UserFacingError, ConversationMetadataUpdate,
+    EnrichedConversation, SseBreadcrumb, SseEvent,
 };
 
 /// A message enriched for API output: bash `tool_use` blocks have their
@@ -82,6 +82,10 @@ pub struct EnrichedMessage {
     #[ts(type = "unknown | null")]
     pub display_data: Option<Value>,
     pub usage_data: Option<UsageData>,
+    /// Model that wrote an agent message (REQ-LLM-020)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub model: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -96,6 +100,7 @@ impl From<&Message> for EnrichedMessage {
             content,
             display_data: msg.display_data.clone(),
             usage_data: msg.usage_data.clone(),
+            model: msg.model.clone(),
             created_at: msg.created_at,
         }
     }
@@ -215,6 +220,10 @@ pub enum SseWireEvent {
         commits_behind: u32,
         commits_ahead: u32,
         project_name: Option<String>,
+        /// Unsent composer text and images (REQ-BED-047); `null` when there
+        /// is none. Validated by `DraftSchema` on the UI side.
+        #[ts(type = "unknown | null")]
+        draft: Option<ConversationDraft>,
     },
     /// A newly-persisted message joins the conversation. The envelope
     /// `sequence_id` equals `message.sequence_id` by construction.
@@ -278,6 +287,27 @@ pub enum SseWireEvent {
         #[ts(type = "unknown")]
         error: UserFacingError,
     },
+    /// REQ-BED-035: structured recovery suggestion for a conversation that
+    /// just entered `Error`.
+    ErrorRemediation {
+        sequence_id: i64,
+        remediation: Remediation,
+    },
+    /// REQ-BED-043: context usage reached a warning threshold. `percent` is
+    /// `used / limit` rounded down.
+    ContextWarning {
+        sequence_id: i64,
+        used: u64,
+        limit: u64,
+        percent: u64,
+    },
+    /// REQ-BED-044: the conversation ran out of context and was continued
+    /// automatically. Clients navigate to the continuation.
+    ConversationContinued {
+        sequence_id: i64,
+        conversation_id: String,
+        slug: Option<String>,
+    },
     /// REQ-BED-032 step 6: a conversation has just been hard-deleted (its
     /// row is gone from `SQLite`, all per-conversation resources cleaned
     /// up). UI consumers refresh sidebar / navigation in response. Emitted
@@ -286,6 +316,33 @@ pub enum SseWireEvent {
         sequence_id: i64,
         conversation_id: String,
     },
+    /// REQ-API-013: a client opened a stream. `clients` and
+    /// `composer_holder` are the full presence snapshot after the join.
+    ClientJoined {
+        sequence_id: i64,
+        client_id: String,
+        clients: Vec<String>,
+        composer_holder: Option<String>,
+    },
+    /// REQ-API-013: a client's stream closed. Snapshot is post-leave.
+    ClientLeft {
+        sequence_id: i64,
+        client_id: String,
+        clients: Vec<String>,
+        composer_holder: Option<String>,
+    },
+    /// REQ-API-013: the composer lease changed. `null` means nobody is
+    /// typing and any client may submit.
+    ComposerChanged {
+        sequence_id: i64,
+        composer_holder: Option<String>,
+    },
+    /// REQ-BED-059: files in the cwd changed outside the conversation.
+    /// `paths` are relative to the cwd.
+    FilesChanged {
+        sequence_id: i64,
+        paths: Vec<String>,
+    },
 }
 
 impl SseWireEvent {
@@ -302,12 +359,20 @@ impl SseWireEvent {
             SseWireEvent::ConversationBecameTerminal { .. } => "conversation_became_terminal",
             SseWireEvent::ConversationUpdate { .. } => "conversation_update",
             SseWireEvent::Error { .. } => "error",
+            SseWireEvent::ErrorRemediation { .. } => "error_remediation",
+            SseWireEvent::ContextWarning { .. } => "context_warning",
+            SseWireEvent::ConversationContinued { .. } => "conversation_continued",
             SseWireEvent::ConversationHardDeleted { .. } => "conversation_hard_deleted",
+            SseWireEvent::ClientJoined { .. } => "client_joined",
+            SseWireEvent::ClientLeft { .. } => "client_left",
+            SseWireEvent::ComposerChanged { .. } => "composer_changed",
+            SseWireEvent::FilesChanged { .. } => "files_changed",
         }
     }
 }
 
 impl From<SseEvent> for SseWireEvent {
+    #[allow(clippy::too_many_lines)] // one arm per event
     fn from(event: SseEvent) -> Self {
         match event {
             SseEvent::Init {
@@ -322,6 +387,7 @@ impl From<SseEvent> for SseWireEvent {
                 commits_behind,
                 commits_ahead,
                 project_name,
+                draft,
             } => SseWireEvent::Init {
                 sequence_id,
                 conversation,
@@ -334,6 +400,7 @@ impl From<SseEvent> for SseWireEvent {
                 commits_behind,
                 commits_ahead,
                 project_name,
+                draft,
             },
             SseEvent::Message { message } => {
                 // The envelope `sequence_id` equals `message.sequence_id` —
@@ -400,6 +467,33 @@ impl From<SseEvent> for SseWireEvent {
                     error,
                 }
             }
+            SseEvent::ErrorRemediation {
+                sequence_id,
+                remediation,
+            } => SseWireEvent::ErrorRemediation {
+                sequence_id,
+                remediation,
+            },
+            SseEvent::ContextWarning {
+                sequence_id,
+                used,
+                limit,
+                percent,
+            } => SseWireEvent::ContextWarning {
+                sequence_id,
+                used,
+                limit,
+                percent,
+            },
+            SseEvent::ConversationContinued {
+                sequence_id,
+                conversation_id,
+                slug,
+            } => SseWireEvent::ConversationContinued {
+                sequence_id,
+                conversation_id,
+                slug,
+            },
             SseEvent::ConversationHardDeleted {
                 sequence_id,
                 conversation_id,
@@ -407,6 +501,38 @@ impl From<SseEvent> for SseWireEvent {
                 sequence_id,
                 conversation_id,
             },
+            SseEvent::ClientJoined {
+                sequence_id,
+                client_id,
+                clients,
+                composer_holder,
+            } => SseWireEvent::ClientJoined {
+                sequence_id,
+                client_id,
+                clients,
+                composer_holder,
+            },
+            SseEvent::ClientLeft {
+                sequence_id,
+                client_id,
+                clients,
+                composer_holder,
+            } => SseWireEvent::ClientLeft {
+                sequence_id,
+                client_id,
+                clients,
+                composer_holder,
+            },
+            SseEvent::ComposerChanged {
+                sequence_id,
+                composer_holder,
+            } => SseWireEvent::ComposerChanged {
+                sequence_id,
+                composer_holder,
+            },
+            SseEvent::FilesChanged { sequence_id, paths } => {
+                SseWireEvent::FilesChanged { sequence_id, paths }
+            }
         }
     }
 }
@@ -792,6 +918,23 @@ pub enum BashErrorResponse {
         error_message: String,
         reason: String,
     },
+    /// Refused by the bash command policy (REQ-BASH-018). `scope` and
+    /// `rule` are absent when the command matched no allow rule.
+    CommandPolicyDenied {
+        error_message: String,
+        command: String,
+        scope: Option<String>,
+        rule: Option<String>,
+    },
+    /// A git command that would discard uncommitted changes the
+    /// conversation did not make (REQ-BASH-019). `files` are relative to
+    /// the repository root.
+    UncommittedChanges {
+        error_message: String,
+        command: String,
+        files: Vec<String>,
+        hint: String,
+    },
     SpawnFailed {
         error_message: String,
     },
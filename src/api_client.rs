@@ -0,0 +1,116 @@
+//! Pieces shared by Phoenix's own API clients: the `phoenix` terminal
+//! client (REQ-CLI-009) and the `--tui` mode (REQ-CLI-010).
+//!
+//! The binary pulls this file in with `#[path]`, like `tls_certs.rs`, so it
+//! stays free of server-only dependencies.
+
+use serde_json::Value;
+
+/// Characters per line of tool input or output before it is cut.
+pub const MAX_LINE_CHARS: usize = 160;
+
+/// One event read from a conversation stream.
+#[derive(Debug, PartialEq)]
+pub struct SseFrame {
+    pub event: String,
+    pub data: Value,
+}
+
+/// Incremental `text/event-stream` parser. Only `event:` and `data:`
+/// matter to these clients; ids and keep-alive comments are skipped.
+#[derive(Default)]
+pub struct SseParser {
+    buf: Vec<u8>,
+    event: String,
+    data: String,
+}
+
+impl SseParser {
+    pub fn push(&mut self, chunk: &[u8]) {
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Next complete frame in the buffer, if any.
+    pub fn next_frame(&mut self) -> Option<SseFrame> {
+        while let Some(end) = self.buf.iter().position(|&b| b == b'\n') {
+            let raw: Vec<u8> = self.buf.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&raw);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                if self.data.is_empty() {
+                    self.event.clear();
+                    continue;
+                }
+                let data = std::mem::take(&mut self.data);
+                return Some(SseFrame {
+                    event: std::mem::take(&mut self.event),
+                    data: serde_json::from_str(&data).unwrap_or(Value::Null),
+                });
+            }
+            if let Some(event) = line.strip_prefix("event:") {
+                self.event = event.trim_start().to_string();
+            } else if let Some(data) = line.strip_prefix("data:") {
+                if !self.data.is_empty() {
+                    self.data.push('\n');
+                }
+                self.data.push_str(data.strip_prefix(' ').unwrap_or(data));
+            }
+        }
+        None
+    }
+}
+
+/// Cut `line` to [`MAX_LINE_CHARS`], marking the cut with an ellipsis.
+pub fn truncate_line(line: &str) -> String {
+    if line.chars().count() <= MAX_LINE_CHARS {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(MAX_LINE_CHARS - 1).collect();
+    format!("{cut}…")
+}
+
+/// One-line summary of a tool call's input: the command for shell-like
+/// tools, the path for file tools, otherwise compact JSON.
+pub fn tool_summary(input: &Value) -> String {
+    let summary = ["command", "path", "pattern", "url"]
+        .iter()
+        .find_map(|key| input.get(key).and_then(Value::as_str))
+        .map_or_else(|| input.to_string(), str::to_string);
+    truncate_line(summary.lines().next().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sse_frames_split_across_chunks() {
+        let mut parser = SseParser::default();
+        parser.push(b": ping\n\nevent: token\nid: 4\ndata: {\"te");
+        assert_eq!(parser.next_frame(), None);
+        parser.push(b"xt\":\"hi\"}\r\n\r\nevent: agent_done\ndata: {}\n\n");
+        assert_eq!(
+            parser.next_frame(),
+            Some(SseFrame {
+                event: "token".to_string(),
+                data: json!({ "text": "hi" }),
+            })
+        );
+        assert_eq!(parser.next_frame().unwrap().event, "agent_done");
+        assert_eq!(parser.next_frame(), None);
+    }
+
+    #[test]
+    fn tool_summary_prefers_command_and_truncates() {
+        assert_eq!(
+            tool_summary(&json!({ "command": "ls -la\necho" })),
+            "ls -la"
+        );
+        let long = "x".repeat(500);
+        assert_eq!(
+            tool_summary(&json!({ "path": long })).chars().count(),
+            MAX_LINE_CHARS
+        );
+    }
+}
@@ -0,0 +1,599 @@
+//! `phoenix` — terminal client for a running Phoenix server (REQ-CLI-009).
+//!
+//! Starts a conversation in the current directory (or continues one),
+//! streams the agent's output with ANSI styling, and takes follow-ups at a
+//! prompt. Ctrl-C cancels the running turn; a second Ctrl-C quits.
+//!
+//! Uses the same HTTP API as the web UI: `POST /api/conversations/new`,
+//! `POST .../chat`, `POST .../cancel`, and the conversation SSE stream.
+
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
+use serde_json::{json, Value};
+use tokio::io::AsyncBufReadExt;
+
+#[path = "../api_client.rs"]
+mod api_client;
+
+use api_client::{tool_summary, truncate_line, SseFrame, SseParser};
+
+const DEFAULT_API_URL: &str = "http://localhost:8000";
+/// Lines of each tool result shown under its call.
+const TOOL_RESULT_LINES: usize = 8;
+
+const USAGE: &str = "\
+usage: phoenix [OPTIONS] [MESSAGE]
+       phoenix cancel CONVERSATION
+
+Start a conversation in the current directory and stream the agent's reply.
+Without MESSAGE, or with --interactive, keep prompting for follow-ups.
+Ctrl-C cancels the running turn; a second Ctrl-C quits.
+
+options:
+  -c, --conversation ID|SLUG  continue an existing conversation
+  -d, --directory DIR         working directory for a new conversation
+  -m, --model MODEL           model for a new conversation
+      --mode MODE             direct (default), managed, or auto
+  -i, --interactive           prompt for follow-ups after MESSAGE
+      --api-url URL           server URL (PHOENIX_API_URL, default http://localhost:8000)
+      --password PASSWORD     server password (PHOENIX_PASSWORD)
+  -h, --help                  show this help";
+
+type CliResult<T> = Result<T, String>;
+
+// ============================================================
+// Arguments
+// ============================================================
+
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    conversation: Option<String>,
+    directory: Option<PathBuf>,
+    model: Option<String>,
+    mode: Option<String>,
+    interactive: bool,
+    api_url: Option<String>,
+    password: Option<String>,
+    /// `phoenix cancel CONVERSATION`
+    cancel: bool,
+    help: bool,
+    message: Option<String>,
+}
+
+fn value(args: &mut impl Iterator<Item = String>, flag: &str) -> CliResult<String> {
+    args.next()
+        .ok_or_else(|| format!("{flag} requires a value"))
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> CliResult<Args> {
+    let mut parsed = Args::default();
+    let mut words = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-c" | "--conversation" => parsed.conversation = Some(value(&mut args, &arg)?),
+            "-d" | "--directory" => parsed.directory = Some(value(&mut args, &arg)?.into()),
+            "-m" | "--model" => parsed.model = Some(value(&mut args, &arg)?),
+            "--mode" => parsed.mode = Some(value(&mut args, &arg)?),
+            "--api-url" => parsed.api_url = Some(value(&mut args, &arg)?),
+            "--password" => parsed.password = Some(value(&mut args, &arg)?),
+            "-i" | "--interactive" => parsed.interactive = true,
+            "-h" | "--help" => parsed.help = true,
+            "--" => words.extend(args.by_ref()),
+            flag if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(format!("unknown option {flag}"));
+            }
+            _ => words.push(arg),
+        }
+    }
+    if words.first().is_some_and(|w| w == "cancel") {
+        let [_, conversation] = <[String; 2]>::try_from(words)
+            .map_err(|_| "usage: phoenix cancel CONVERSATION".to_string())?;
+        parsed.cancel = true;
+        parsed.conversation = Some(conversation);
+    } else if !words.is_empty() {
+        parsed.message = Some(words.join(" "));
+    }
+    Ok(parsed)
+}
+
+// ============================================================
+// HTTP client
+// ============================================================
+
+struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    password: Option<String>,
+}
+
+impl Client {
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let req = self
+            .http
+            .request(method, format!("{}{path}", self.base_url));
+        match &self.password {
+            Some(password) => req.bearer_auth(password),
+            None => req,
+        }
+    }
+
+    async fn send(&self, req: reqwest::RequestBuilder) -> CliResult<reqwest::Response> {
+        let resp = req.send().await.map_err(|e| {
+            if e.is_connect() {
+                format!("cannot connect to {}; is Phoenix running?", self.base_url)
+            } else {
+                e.to_string()
+            }
+        })?;
+        let status = resp.status();
+        if status.is_success() {
+            return Ok(resp);
+        }
+        // Every API error body is `{ "error": "..." }`.
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        let message = body
+            .get("error")
+            .and_then(Value::as_str)
+            .or_else(|| status.canonical_reason())
+            .unwrap_or("request failed");
+        Err(format!("{message} ({})", status.as_u16()))
+    }
+
+    async fn get(&self, path: &str) -> CliResult<Value> {
+        let resp = self.send(self.request(reqwest::Method::GET, path)).await?;
+        resp.json().await.map_err(|e| e.to_string())
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> CliResult<Value> {
+        let req = self.request(reqwest::Method::POST, path).json(body);
+        self.send(req)
+            .await?
+            .json()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Conversation record for an id or slug.
+    async fn resolve(&self, id_or_slug: &str) -> CliResult<Value> {
+        let by_slug = format!("/api/conversations/by-slug/{id_or_slug}");
+        let found = match self.get(&by_slug).await {
+            Ok(found) => found,
+            Err(_) => {
+                self.get(&format!("/api/conversations/{id_or_slug}"))
+                    .await?
+            }
+        };
+        Ok(found["conversation"].clone())
+    }
+
+    /// Open the conversation's event stream and read its `init` snapshot.
+    async fn attach(&self, conv_id: &str) -> CliResult<(EventStream, Value)> {
+        let path = format!("/api/conversations/{conv_id}/stream");
+        let resp = self.send(self.request(reqwest::Method::GET, &path)).await?;
+        let mut events = EventStream {
+            resp,
+            parser: SseParser::default(),
+        };
+        match events.next().await? {
+            Some(frame) if frame.event == "init" => Ok((events, frame.data)),
+            _ => Err("stream closed before init".to_string()),
+        }
+    }
+
+    async fn chat(&self, conv_id: &str, text: &str) -> CliResult<()> {
+        let body = json!({ "text": text, "message_id": uuid::Uuid::new_v4().to_string() });
+        self.post(&format!("/api/conversations/{conv_id}/chat"), &body)
+            .await
+            .map(drop)
+    }
+
+    async fn cancel(&self, conv_id: &str) -> CliResult<bool> {
+        let resp = self
+            .post(&format!("/api/conversations/{conv_id}/cancel"), &json!({}))
+            .await?;
+        Ok(resp["no_op"].as_bool().unwrap_or(false))
+    }
+}
+
+// ============================================================
+// SSE
+// ============================================================
+
+struct EventStream {
+    resp: reqwest::Response,
+    parser: SseParser,
+}
+
+impl EventStream {
+    /// Next event, or `None` when the server closes the stream.
+    async fn next(&mut self) -> CliResult<Option<SseFrame>> {
+        loop {
+            if let Some(frame) = self.parser.next_frame() {
+                return Ok(Some(frame));
+            }
+            match self.resp.chunk().await.map_err(|e| e.to_string())? {
+                Some(chunk) => self.parser.push(&chunk),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+// ============================================================
+// Rendering
+// ============================================================
+
+const BOLD: &str = "1";
+const DIM: &str = "2";
+const RED: &str = "31";
+const CYAN: &str = "36";
+
+struct Renderer {
+    color: bool,
+    /// Tokens for the current agent message were already printed, so its
+    /// text blocks must not be printed again.
+    streamed: bool,
+    at_line_start: bool,
+}
+
+impl Renderer {
+    fn new() -> Self {
+        Self {
+            color: std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            streamed: false,
+            at_line_start: true,
+        }
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let mut out = std::io::stdout().lock();
+        let _ = out.write_all(text.as_bytes());
+        let _ = out.flush();
+        self.at_line_start = text.ends_with('\n');
+    }
+
+    fn line(&mut self, text: &str) {
+        if !self.at_line_start {
+            self.write("\n");
+        }
+        self.write(&format!("{text}\n"));
+    }
+
+    fn token(&mut self, text: &str) {
+        self.streamed = true;
+        self.write(text);
+    }
+
+    fn message(&mut self, message: &Value) {
+        let content = &message["content"];
+        match message["message_type"].as_str() {
+            Some("agent") => {
+                for block in content.as_array().into_iter().flatten() {
+                    match block["type"].as_str() {
+                        Some("text") if !self.streamed => {
+                            self.line(block["text"].as_str().unwrap_or_default());
+                        }
+                        Some("tool_use") => {
+                            let name = block["name"].as_str().unwrap_or("tool");
+                            let call = format!("⏺ {name}({})", tool_summary(&block["input"]));
+                            self.line(&self.paint(CYAN, &call));
+                        }
+                        _ => {}
+                    }
+                }
+                self.streamed = false;
+            }
+            Some("tool") => {
+                let output = content["content"].as_str().unwrap_or_default();
+                let code = if content["is_error"].as_bool() == Some(true) {
+                    RED
+                } else {
+                    DIM
+                };
+                let lines: Vec<&str> = output.lines().collect();
+                for (i, line) in lines.iter().take(TOOL_RESULT_LINES).enumerate() {
+                    let gutter = if i == 0 { "  ⎿ " } else { "    " };
+                    self.line(&self.paint(code, &format!("{gutter}{}", truncate_line(line))));
+                }
+                if lines.len() > TOOL_RESULT_LINES {
+                    let more = format!("    … {} more lines", lines.len() - TOOL_RESULT_LINES);
+                    self.line(&self.paint(DIM, &more));
+                }
+            }
+            Some("error") => {
+                let text = content["message"].as_str().unwrap_or("error");
+                self.line(&self.paint(RED, &format!("✗ {text}")));
+            }
+            Some("system" | "continuation") => {
+                let text = content["text"].as_str().unwrap_or_default();
+                self.line(&self.paint(DIM, text));
+            }
+            _ => {}
+        }
+    }
+
+    fn note(&mut self, text: &str) {
+        self.line(&self.paint(DIM, text));
+    }
+}
+
+// ============================================================
+// Turns
+// ============================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TurnEnd {
+    Idle,
+    Cancelled,
+    Failed,
+}
+
+/// Render events until the agent finishes, fails, or stops for the user.
+/// The first Ctrl-C cancels the turn; the second exits.
+async fn follow_turn(
+    client: &Client,
+    conv_id: &str,
+    events: &mut EventStream,
+    out: &mut Renderer,
+) -> CliResult<TurnEnd> {
+    let mut cancelled = false;
+    loop {
+        let frame = tokio::select! {
+            frame = events.next() => frame?,
+            _ = tokio::signal::ctrl_c() => {
+                if cancelled {
+                    out.line("");
+                    std::process::exit(130);
+                }
+                cancelled = true;
+                out.note("Cancelling… (Ctrl-C again to quit)");
+                if client.cancel(conv_id).await? {
+                    return Ok(TurnEnd::Cancelled);
+                }
+                continue;
+            }
+        };
+        let Some(SseFrame { event, data }) = frame else {
+            return Err("server closed the stream".to_string());
+        };
+        match event.as_str() {
+            "token" => out.token(data["text"].as_str().unwrap_or_default()),
+            "message" => {
+                let message = &data["message"];
+                if message["message_type"] != "user" {
+                    out.message(message);
+                }
+            }
+            "agent_done" if cancelled => return Ok(TurnEnd::Cancelled),
+            "agent_done" => return Ok(TurnEnd::Idle),
+            "state_change" => match data["display_state"].as_str() {
+                Some("idle") if cancelled => return Ok(TurnEnd::Cancelled),
+                Some("error") => return Ok(TurnEnd::Failed),
+                Some("terminal") => {
+                    out.note("This conversation has ended.");
+                    return Ok(TurnEnd::Failed);
+                }
+                Some("awaiting_approval") => {
+                    out.note("The agent proposed a task; approve or reject it in the web UI.");
+                    return Ok(TurnEnd::Idle);
+                }
+                _ => {}
+            },
+            "error" => {
+                let text = data["message"].as_str().unwrap_or("error");
+                let text = out.paint(RED, &format!("✗ {text}"));
+                out.line(&text);
+            }
+            "conversation_hard_deleted" => return Err("conversation was deleted".to_string()),
+            _ => {}
+        }
+    }
+}
+
+/// Create a conversation and attach to it. The agent may have answered
+/// before the stream opened, so the snapshot's replies are shown first.
+async fn start(
+    client: &Client,
+    args: &Args,
+    text: &str,
+    out: &mut Renderer,
+) -> CliResult<(String, EventStream, bool)> {
+    let cwd = match &args.directory {
+        Some(dir) => dir.clone(),
+        None => std::env::current_dir().map_err(|e| e.to_string())?,
+    };
+    let cwd = cwd
+        .canonicalize()
+        .map_err(|e| format!("{}: {e}", cwd.display()))?;
+    let body = json!({
+        "cwd": cwd.to_string_lossy(),
+        "text": text,
+        "message_id": uuid::Uuid::new_v4().to_string(),
+        "model": args.model,
+        "mode": args.mode,
+    });
+    let created = client.post("/api/conversations/new", &body).await?;
+    let conversation = &created["conversation"];
+    let conv_id = conversation["id"].as_str().unwrap_or_default().to_string();
+    let slug = conversation["slug"].as_str().unwrap_or(&conv_id);
+    out.note(&format!("Created {slug} in {}", cwd.display()));
+
+    let (events, init) = client.attach(&conv_id).await?;
+    let mut answered = false;
+    for message in init["messages"].as_array().into_iter().flatten() {
+        if message["message_type"] != "user" {
+            answered = true;
+            out.message(message);
+        }
+    }
+    let finished = answered && init["agent_working"] == false;
+    Ok((conv_id, events, finished))
+}
+
+/// Read one follow-up from the prompt; `None` on EOF or Ctrl-C.
+async fn prompt(
+    lines: &mut tokio::io::Lines<tokio::io::BufReader<tokio::io::Stdin>>,
+    out: &mut Renderer,
+) -> Option<String> {
+    loop {
+        let marker = out.paint(BOLD, "› ");
+        out.line("");
+        out.write(&marker);
+        let line = tokio::select! {
+            line = lines.next_line() => line.ok().flatten()?,
+            _ = tokio::signal::ctrl_c() => return None,
+        };
+        out.at_line_start = true;
+        let line = line.trim();
+        if !line.is_empty() {
+            return Some(line.to_string());
+        }
+    }
+}
+
+async fn run(args: &Args) -> CliResult<i32> {
+    let client = Client {
+        http: reqwest::Client::new(),
+        base_url: args
+            .api_url
+            .clone()
+            .or_else(|| std::env::var("PHOENIX_API_URL").ok())
+            .unwrap_or_else(|| DEFAULT_API_URL.to_string())
+            .trim_end_matches('/')
+            .to_string(),
+        password: args
+            .password
+            .clone()
+            .or_else(|| std::env::var("PHOENIX_PASSWORD").ok())
+            .filter(|p| !p.is_empty()),
+    };
+    let mut out = Renderer::new();
+
+    if args.cancel {
+        let target = args.conversation.as_deref().unwrap_or_default();
+        let conversation = client.resolve(target).await?;
+        let no_op = client
+            .cancel(conversation["id"].as_str().unwrap_or(target))
+            .await?;
+        out.note(if no_op {
+            "Nothing to cancel."
+        } else {
+            "Cancelled."
+        });
+        return Ok(0);
+    }
+
+    let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+    let interactive = args.interactive || args.message.is_none();
+
+    let (conv_id, mut events) = if let Some(target) = &args.conversation {
+        let conversation = client.resolve(target).await?;
+        let conv_id = conversation["id"].as_str().unwrap_or(target).to_string();
+        let slug = conversation["slug"].as_str().unwrap_or(&conv_id);
+        out.note(&format!("Continuing {slug}"));
+        let (events, _) = client.attach(&conv_id).await?;
+        (conv_id, events)
+    } else {
+        let text = match &args.message {
+            Some(text) => text.clone(),
+            None => match prompt(&mut lines, &mut out).await {
+                Some(text) => text,
+                None => return Ok(0),
+            },
+        };
+        let (conv_id, mut events, finished) = start(&client, args, &text, &mut out).await?;
+        let end = if finished {
+            TurnEnd::Idle
+        } else {
+            follow_turn(&client, &conv_id, &mut events, &mut out).await?
+        };
+        if !interactive {
+            out.line("");
+            return Ok(i32::from(end != TurnEnd::Idle));
+        }
+        (conv_id, events)
+    };
+
+    let mut next = if args.conversation.is_some() {
+        args.message.clone()
+    } else {
+        None
+    };
+    loop {
+        let text = match next.take() {
+            Some(text) => text,
+            None => match prompt(&mut lines, &mut out).await {
+                Some(text) => text,
+                None => return Ok(0),
+            },
+        };
+        client.chat(&conv_id, &text).await?;
+        let end = follow_turn(&client, &conv_id, &mut events, &mut out).await?;
+        if !interactive {
+            out.line("");
+            return Ok(i32::from(end != TurnEnd::Idle));
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) if args.help => {
+            println!("{USAGE}");
+            return;
+        }
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("phoenix: {e}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    match run(&args).await {
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            eprintln!("phoenix: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(words: &[&str]) -> CliResult<Args> {
+        parse_args(words.iter().map(ToString::to_string))
+    }
+
+    #[test]
+    fn message_words_are_joined() {
+        let parsed = args(&["-m", "sonnet", "fix", "the", "build"]).unwrap();
+        assert_eq!(parsed.model.as_deref(), Some("sonnet"));
+        assert_eq!(parsed.message.as_deref(), Some("fix the build"));
+        assert!(!parsed.cancel);
+    }
+
+    #[test]
+    fn cancel_takes_exactly_one_conversation() {
+        let parsed = args(&["cancel", "blue-river"]).unwrap();
+        assert!(parsed.cancel);
+        assert_eq!(parsed.conversation.as_deref(), Some("blue-river"));
+        assert!(args(&["cancel"]).is_err());
+        assert!(args(&["--bogus"]).is_err());
+        assert!(args(&["-c"]).is_err());
+    }
+}
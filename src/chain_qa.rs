@@ -173,17 +173,11 @@ pub enum ChainQaError {
     #[error("database error: {0}")]
     Db(#[from] DbError),
     #[error("LLM error: {0}")]
-    Llm(String),
+    Llm(#[from] LlmError),
     #[error("no mid-tier LLM model available — registry has no models")]
     NoModelAvailable,
 }
 
-impl From<LlmError> for ChainQaError {
-    fn from(e: LlmError) -> Self {
-        Self::Llm(e.message)
-    }
-}
-
 /// Identifier returned to the caller of [`ChainQa::submit_question`] —
 /// doubles as the SSE-stream demux key in Phase 3.
 pub type ChainQaId = String;
@@ -559,6 +553,7 @@ fn build_answer_request(bundled: &BundledContext, question: &str) -> LlmRequest
         }],
         tools: vec![],
         max_tokens: Some(ANSWER_MAX_TOKENS),
+        thinking_budget: None,
         // Shared by every chain answer call across all chains, so the
         // ANSWER_SYSTEM_PROMPT prefix caches once.
         cache_key: PromptCacheKey::stable("chain-qa-answer"),
@@ -719,6 +714,7 @@ async fn summarize_leaf_in_process(
         }],
         tools: vec![],
         max_tokens: Some(LEAF_SUMMARY_MAX_TOKENS),
+        thinking_budget: None,
         // Shared by every leaf-summary call so the boilerplate prompt caches.
         cache_key: PromptCacheKey::stable("chain-qa-leaf-summary"),
     };
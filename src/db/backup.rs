@@ -0,0 +1,311 @@
+//! Online database backups (REQ-API-015).
+//!
+//! Snapshots are taken with `SQLite`'s online backup API through a separate
+//! rusqlite connection, so the server keeps serving while pages are copied
+//! and the result is a consistent point-in-time copy even in WAL mode.
+//! Backups are plain `SQLite` files named `phoenix-<UTC timestamp>.db`; the
+//! timestamp sorts lexically, which rotation relies on.
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const BACKUP_PREFIX: &str = "phoenix-";
+const BACKUP_SUFFIX: &str = ".db";
+const DEFAULT_KEEP: usize = 10;
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("SQLite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid backup name: {0}")]
+    InvalidName(String),
+    #[error("Backup not found: {0}")]
+    NotFound(String),
+    #[error("Backup failed integrity check: {0}")]
+    Corrupt(String),
+}
+
+/// Where backups go and how many are kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupConfig {
+    pub dir: PathBuf,
+    pub keep: usize,
+}
+
+impl BackupConfig {
+    /// `PHOENIX_BACKUP_DIR` (default: `backups/` next to the database) and
+    /// `PHOENIX_BACKUP_KEEP` (default 10; zero or invalid falls back).
+    pub fn from_env(db_path: &Path) -> Self {
+        Self::from_lookup(db_path, |name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(db_path: &Path, lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let dir = lookup("PHOENIX_BACKUP_DIR").map_or_else(
+            || {
+                db_path
+                    .parent()
+                    .unwrap_or_else(|| Path::new("."))
+                    .join("backups")
+            },
+            PathBuf::from,
+        );
+        let keep = match lookup("PHOENIX_BACKUP_KEEP") {
+            None => DEFAULT_KEEP,
+            Some(raw) => match raw.trim().parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => {
+                    tracing::warn!(value = %raw, "Ignoring invalid PHOENIX_BACKUP_KEEP");
+                    DEFAULT_KEEP
+                }
+            },
+        };
+        Self { dir, keep }
+    }
+}
+
+/// One backup file on disk.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub name: String,
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Result of [`create_backup`]: the new snapshot and any rotated out.
+#[derive(Debug)]
+pub struct BackupOutcome {
+    pub backup: BackupInfo,
+    pub removed: Vec<String>,
+}
+
+/// Snapshot `db_path` into `config.dir`, then delete the oldest backups
+/// beyond `config.keep`. Blocking; call from `spawn_blocking`.
+///
+/// The copy is written under a temporary name and renamed into place, so a
+/// crash mid-backup never leaves a truncated file that looks restorable.
+pub fn create_backup(db_path: &Path, config: &BackupConfig) -> Result<BackupOutcome, BackupError> {
+    std::fs::create_dir_all(&config.dir)?;
+    let created_at = Utc::now();
+    let name = format!(
+        "{BACKUP_PREFIX}{}{BACKUP_SUFFIX}",
+        created_at.format("%Y%m%dT%H%M%S%3fZ")
+    );
+    let final_path = config.dir.join(&name);
+    let tmp_path = config.dir.join(format!("{name}.partial"));
+
+    let source = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+    if let Err(e) = source.backup(DatabaseName::Main, &tmp_path, None) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    std::fs::rename(&tmp_path, &final_path)?;
+
+    let size_bytes = std::fs::metadata(&final_path)?.len();
+    let removed = rotate(&config.dir, config.keep)?;
+    Ok(BackupOutcome {
+        backup: BackupInfo {
+            name,
+            size_bytes,
+            created_at,
+        },
+        removed,
+    })
+}
+
+/// Backups in `dir`, newest first. A missing directory is an empty list.
+pub fn list_backups(dir: &Path) -> Result<Vec<BackupInfo>, BackupError> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut backups = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if !is_backup_name(&name) {
+            continue;
+        }
+        let meta = entry.metadata()?;
+        let created_at = meta
+            .modified()
+            .map_or_else(|_| Utc::now(), DateTime::<Utc>::from);
+        backups.push(BackupInfo {
+            name,
+            size_bytes: meta.len(),
+            created_at,
+        });
+    }
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(backups)
+}
+
+/// Replace the database at `db_path` with the named backup. Must run before
+/// the database is opened.
+///
+/// The backup is integrity-checked first. An existing database (and its
+/// `-wal`/`-shm` sidecars) is moved aside to `<db>.pre-restore-<timestamp>`
+/// rather than deleted; the moved-aside path is returned.
+pub fn restore_backup(
+    db_path: &Path,
+    dir: &Path,
+    name: &str,
+) -> Result<Option<PathBuf>, BackupError> {
+    if !is_backup_name(name) {
+        return Err(BackupError::InvalidName(name.to_string()));
+    }
+    let backup_path = dir.join(name);
+    if !backup_path.is_file() {
+        return Err(BackupError::NotFound(name.to_string()));
+    }
+    check_integrity(&backup_path)?;
+
+    let moved_aside = if db_path.exists() {
+        let stamp = Utc::now().format("%Y%m%dT%H%M%SZ");
+        let aside = sibling(db_path, &format!(".pre-restore-{stamp}"));
+        std::fs::rename(db_path, &aside)?;
+        for sidecar in ["-wal", "-shm"] {
+            let from = sibling(db_path, sidecar);
+            if from.exists() {
+                std::fs::rename(&from, sibling(&aside, sidecar))?;
+            }
+        }
+        Some(aside)
+    } else {
+        None
+    };
+
+    let tmp_path = sibling(db_path, ".restoring");
+    std::fs::copy(&backup_path, &tmp_path)?;
+    std::fs::rename(&tmp_path, db_path)?;
+    Ok(moved_aside)
+}
+
+fn check_integrity(path: &Path) -> Result<(), BackupError> {
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let result: String = conn.query_row("PRAGMA integrity_check", [], |row| row.get(0))?;
+    if result == "ok" {
+        Ok(())
+    } else {
+        Err(BackupError::Corrupt(result))
+    }
+}
+
+/// Delete the oldest backups so at most `keep` remain. Returns removed names.
+fn rotate(dir: &Path, keep: usize) -> Result<Vec<String>, BackupError> {
+    let mut removed = Vec::new();
+    for stale in list_backups(dir)?.into_iter().skip(keep) {
+        match std::fs::remove_file(dir.join(&stale.name)) {
+            Ok(()) => removed.push(stale.name),
+            // A concurrent backup's rotation got there first.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(removed)
+}
+
+/// Names produced by [`create_backup`]; also rejects anything path-like so
+/// a restore request cannot reach outside the backup directory.
+fn is_backup_name(name: &str) -> bool {
+    name.starts_with(BACKUP_PREFIX)
+        && name.ends_with(BACKUP_SUFFIX)
+        && !name.contains(['/', '\\'])
+        && !name.contains("..")
+}
+
+/// `path` with `suffix` appended to its file name.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut os = path.as_os_str().to_owned();
+    os.push(suffix);
+    PathBuf::from(os)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed_db(path: &Path, value: &str) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch("PRAGMA journal_mode = WAL; CREATE TABLE IF NOT EXISTS t (v TEXT);")
+            .unwrap();
+        conn.execute("DELETE FROM t", []).unwrap();
+        conn.execute("INSERT INTO t (v) VALUES (?1)", [value])
+            .unwrap();
+    }
+
+    fn read_value(path: &Path) -> String {
+        let conn = Connection::open(path).unwrap();
+        conn.query_row("SELECT v FROM t", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn backup_rotates_and_restores() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db_path = tmp.path().join("phoenix.db");
+        let config = BackupConfig {
+            dir: tmp.path().join("backups"),
+            keep: 2,
+        };
+
+        seed_db(&db_path, "first");
+        let first = create_backup(&db_path, &config).unwrap();
+        assert!(first.removed.is_empty());
+        assert_eq!(read_value(&config.dir.join(&first.backup.name)), "first");
+
+        for _ in 0..2 {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            create_backup(&db_path, &config).unwrap();
+        }
+        let names: Vec<_> = list_backups(&config.dir)
+            .unwrap()
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(!names.contains(&first.backup.name), "oldest rotated out");
+
+        seed_db(&db_path, "second");
+        let aside = restore_backup(&db_path, &config.dir, &names[0])
+            .unwrap()
+            .expect("existing db moved aside");
+        assert_eq!(read_value(&db_path), "first");
+        assert_eq!(read_value(&aside), "second");
+    }
+
+    #[test]
+    fn restore_rejects_unknown_and_path_like_names() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db_path = tmp.path().join("phoenix.db");
+        let dir = tmp.path().join("backups");
+
+        let err = restore_backup(&db_path, &dir, "../phoenix.db").unwrap_err();
+        assert!(matches!(err, BackupError::InvalidName(_)));
+        let err = restore_backup(&db_path, &dir, "phoenix-missing.db").unwrap_err();
+        assert!(matches!(err, BackupError::NotFound(_)));
+    }
+
+    #[test]
+    fn config_defaults_next_to_database() {
+        let db_path = Path::new("/data/phoenix.db");
+        let config = BackupConfig::from_lookup(db_path, |_| None);
+        assert_eq!(config.dir, PathBuf::from("/data/backups"));
+        assert_eq!(config.keep, DEFAULT_KEEP);
+
+        let config = BackupConfig::from_lookup(db_path, |name| match name {
+            "PHOENIX_BACKUP_DIR" => Some("/srv/snapshots".to_string()),
+            "PHOENIX_BACKUP_KEEP" => Some("0".to_string()),
+            _ => None,
+        });
+        assert_eq!(config.dir, PathBuf::from("/srv/snapshots"));
+        assert_eq!(config.keep, DEFAULT_KEEP);
+    }
+}
@@ -1,16 +1,53 @@
 //! Sequential database migrations.
 //!
-//! Each migration runs exactly once, tracked by the `_migrations` table.
-//! Migrations run at startup before any conversation is loaded.
+//! Each migration runs exactly once, tracked by the `_migrations` table, and
+//! is applied in its own transaction together with its tracking row.
+//! Migrations run at startup before any conversation is loaded, after an
+//! integrity check of the recorded history ([`verify_migrations`]).
+//!
+//! New schema changes go here, not into the idempotent `CREATE TABLE IF NOT
+//! EXISTS` / ignored-`ALTER` block in `Database::run_migrations`; that block
+//! is frozen as the baseline every numbered migration builds on. Never edit
+//! an applied migration's SQL — the checksum check will refuse to start —
+//! add a new one instead.
 
+use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
+use std::collections::HashSet;
 
-use super::DbResult;
+use super::{DbError, DbResult};
 
 struct Migration {
     version: u32,
     name: &'static str,
     sql: &'static str,
+    down: Down,
+}
+
+/// How a migration is reversed by [`rollback_migrations`].
+enum Down {
+    /// SQL that undoes the migration.
+    Sql(&'static str),
+    /// Data-only fix-up whose result older releases read fine; rolling
+    /// back just forgets that it ran.
+    Noop,
+    /// Cannot be undone (lossy data rewrite, or a column `SQLite` cannot
+    /// drop). Rolling back past it is refused.
+    Irreversible,
+}
+
+impl Migration {
+    /// Stable fingerprint of the up script, recorded when the migration is
+    /// applied and compared on every startup.
+    fn checksum(&self) -> String {
+        let digest = Sha256::digest(self.sql.as_bytes());
+        let prefix = u64::from_be_bytes(
+            digest[..8]
+                .try_into()
+                .expect("SHA-256 digest is 32 bytes; first 8 always fits a u64"),
+        );
+        format!("{prefix:016x}")
+    }
 }
 
 const MIGRATIONS: &[Migration] = &[
@@ -18,36 +55,233 @@ const MIGRATIONS: &[Migration] = &[
         version: 1,
         name: "rewrite_standalone_to_direct",
         sql: MIGRATION_001,
+        down: Down::Irreversible,
     },
     Migration {
         version: 2,
         name: "backfill_empty_convmode_fields",
         sql: MIGRATION_002,
+        down: Down::Irreversible,
     },
     Migration {
         version: 3,
         name: "add_continued_in_conv_id_column",
         sql: MIGRATION_003,
+        // SQLite refuses to drop a column that carries a foreign key.
+        down: Down::Irreversible,
     },
     Migration {
         version: 4,
         name: "create_turn_usage_table",
         sql: MIGRATION_004,
+        down: Down::Sql("DROP TABLE IF EXISTS turn_usage;"),
     },
     Migration {
         version: 5,
         name: "add_chain_name_and_chain_qa",
         sql: MIGRATION_005,
+        down: Down::Sql(
+            "DROP TABLE IF EXISTS chain_qa;\nALTER TABLE conversations DROP COLUMN chain_name;",
+        ),
     },
     Migration {
         version: 6,
         name: "archive_partially_archived_chains",
         sql: MIGRATION_006,
+        down: Down::Noop,
     },
     Migration {
         version: 7,
         name: "backfill_explore_worktree_path",
         sql: MIGRATION_007,
+        down: Down::Noop,
+    },
+    Migration {
+        version: 8,
+        name: "create_prompt_templates_table",
+        sql: MIGRATION_008,
+        down: Down::Sql("DROP TABLE IF EXISTS prompt_templates;"),
+    },
+    Migration {
+        version: 9,
+        name: "create_audit_log_table",
+        sql: MIGRATION_009,
+        down: Down::Sql("DROP TABLE IF EXISTS audit_log;"),
+    },
+    Migration {
+        version: 10,
+        name: "create_transitions_table",
+        sql: MIGRATION_010,
+        down: Down::Sql("DROP TABLE IF EXISTS transitions;"),
+    },
+    Migration {
+        version: 11,
+        name: "add_thinking_budget_column",
+        sql: MIGRATION_011,
+        down: Down::Sql("ALTER TABLE conversations DROP COLUMN thinking_budget;"),
+    },
+    Migration {
+        version: 12,
+        name: "create_project_verify_table",
+        sql: MIGRATION_012,
+        down: Down::Sql("DROP TABLE IF EXISTS project_verify;"),
+    },
+    Migration {
+        version: 13,
+        name: "create_conversation_templates_table",
+        sql: MIGRATION_013,
+        down: Down::Sql(
+            "ALTER TABLE conversations DROP COLUMN template; \
+             DROP TABLE IF EXISTS conversation_templates;",
+        ),
+    },
+    Migration {
+        version: 14,
+        name: "add_disabled_tools_column",
+        sql: MIGRATION_014,
+        down: Down::Sql("ALTER TABLE conversations DROP COLUMN disabled_tools;"),
+    },
+    Migration {
+        version: 15,
+        name: "create_message_sequences",
+        sql: MIGRATION_015,
+        down: Down::Sql(
+            "DROP TRIGGER IF EXISTS messages_advance_sequence; \
+             DROP INDEX IF EXISTS idx_messages_conversation_sequence; \
+             DROP TABLE IF EXISTS message_sequences;",
+        ),
+    },
+    Migration {
+        version: 16,
+        name: "add_patch_review_column",
+        sql: MIGRATION_016,
+        down: Down::Sql("ALTER TABLE conversations DROP COLUMN patch_review;"),
+    },
+    Migration {
+        version: 17,
+        name: "create_touched_files",
+        sql: MIGRATION_017,
+        down: Down::Sql("DROP TABLE IF EXISTS touched_files;"),
+    },
+    Migration {
+        version: 18,
+        name: "create_pinned_messages",
+        sql: MIGRATION_018,
+        down: Down::Sql("DROP TABLE IF EXISTS pinned_messages;"),
+    },
+    Migration {
+        version: 19,
+        name: "add_history_window_column",
+        sql: MIGRATION_019,
+        down: Down::Sql("ALTER TABLE conversations DROP COLUMN history_window;"),
+    },
+    Migration {
+        version: 20,
+        name: "create_conversation_drafts",
+        sql: MIGRATION_020,
+        down: Down::Sql("DROP TABLE IF EXISTS conversation_drafts;"),
+    },
+    Migration {
+        version: 21,
+        name: "create_message_feedback",
+        sql: MIGRATION_021,
+        down: Down::Sql("DROP TABLE IF EXISTS message_feedback;"),
+    },
+    Migration {
+        version: 22,
+        name: "create_web_push",
+        sql: MIGRATION_022,
+        down: Down::Sql(
+            "DROP TABLE IF EXISTS push_subscriptions; \
+             DROP TABLE IF EXISTS push_conversations; \
+             DROP TABLE IF EXISTS vapid_keys;",
+        ),
+    },
+    Migration {
+        version: 23,
+        name: "create_conversation_roots",
+        sql: MIGRATION_023,
+        down: Down::Sql("DROP TABLE IF EXISTS conversation_roots;"),
+    },
+    Migration {
+        version: 24,
+        name: "index_conversation_list",
+        sql: MIGRATION_024,
+        down: Down::Sql(
+            "DROP INDEX IF EXISTS idx_conversations_created; \
+             DROP INDEX IF EXISTS idx_conversations_model; \
+             DROP INDEX IF EXISTS idx_conversations_cwd; \
+             DROP INDEX IF EXISTS idx_conversations_state_type;",
+        ),
+    },
+    Migration {
+        version: 25,
+        name: "create_settings",
+        sql: MIGRATION_025,
+        down: Down::Sql("DROP TABLE IF EXISTS settings;"),
+    },
+    Migration {
+        version: 26,
+        name: "create_provider_keys",
+        sql: MIGRATION_026,
+        down: Down::Sql("DROP TABLE IF EXISTS provider_keys;"),
+    },
+    Migration {
+        version: 27,
+        name: "add_runtime_lock_columns",
+        sql: MIGRATION_027,
+        down: Down::Sql(
+            "ALTER TABLE conversations DROP COLUMN runtime_owner; \
+             ALTER TABLE conversations DROP COLUMN runtime_heartbeat_at;",
+        ),
+    },
+    Migration {
+        version: 28,
+        name: "create_events",
+        sql: MIGRATION_028,
+        down: Down::Sql("DROP TABLE IF EXISTS events;"),
+    },
+    Migration {
+        version: 29,
+        name: "create_conversation_shells",
+        sql: MIGRATION_029,
+        down: Down::Sql("DROP TABLE IF EXISTS conversation_shells;"),
+    },
+    Migration {
+        version: 30,
+        name: "create_conversation_bash_policies",
+        sql: MIGRATION_030,
+        down: Down::Sql("DROP TABLE IF EXISTS conversation_bash_policies;"),
+    },
+    Migration {
+        version: 31,
+        name: "add_audit_log_policy_column",
+        sql: MIGRATION_031,
+        down: Down::Sql("ALTER TABLE audit_log DROP COLUMN policy;"),
+    },
+    Migration {
+        version: 32,
+        name: "add_conversation_follow_ups",
+        sql: MIGRATION_032,
+        down: Down::Sql("DROP TABLE IF EXISTS conversation_follow_ups;"),
+    },
+    Migration {
+        version: 33,
+        name: "create_council_candidates",
+        sql: MIGRATION_033,
+        down: Down::Sql("DROP TABLE IF EXISTS council_candidates;"),
+    },
+    Migration {
+        version: 34,
+        name: "add_messages_model_column",
+        sql: MIGRATION_034,
+        down: Down::Sql("ALTER TABLE messages DROP COLUMN model;"),
+    },
+    Migration {
+        version: 35,
+        name: "create_tool_token_usage",
+        sql: MIGRATION_035,
+        down: Down::Sql("DROP TABLE IF EXISTS tool_token_usage;"),
     },
 ];
 
@@ -227,32 +461,495 @@ WHERE json_extract(conv_mode, '$.mode') = 'Explore'
   AND json_extract(conv_mode, '$.worktree_path') IS NULL;
 ";
 
-/// Run all pending migrations against the database.
+/// Create `prompt_templates` for server-side slash commands (REQ-IR-009) and
+/// seed the built-in `/review`, `/tests`, and `/explain` commands.
 ///
-/// Returns the number of migrations applied.
-pub async fn run_pending_migrations(pool: &SqlitePool) -> DbResult<u32> {
-    // Ensure the tracking table exists
+/// `command` is stored without the leading `/`. Seeds use `INSERT OR IGNORE`
+/// so a user-edited row with the same name is never overwritten.
+const MIGRATION_008: &str = r"
+CREATE TABLE IF NOT EXISTS prompt_templates (
+    command TEXT PRIMARY KEY,
+    description TEXT NOT NULL,
+    argument_hint TEXT,
+    template TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+INSERT OR IGNORE INTO prompt_templates (command, description, argument_hint, template) VALUES
+('review', 'Review code for bugs, risks, and style', '[path or focus]',
+ 'Review code in $CWD. Report correctness bugs first, then risky edge cases, then readability issues. Cite file:line for each finding and suggest a concrete fix. Do not modify any files.
+
+Scope (when empty, review the uncommitted changes from git diff): $ARGUMENTS'),
+('tests', 'Write or extend tests', '[path or behavior]',
+ 'Write tests in $CWD, following the test layout and helpers the project already uses. Cover the main behavior, edge cases, and error paths, then run the tests and fix failures in the new tests.
+
+What to test (when empty, the most recently changed code): $ARGUMENTS'),
+('explain', 'Explain how code works', '[path or symbol]',
+ 'Explain code in $CWD. Start with a short summary, then walk through the control flow and key data structures, citing file:line. Call out anything surprising or fragile. Do not modify any files.
+
+What to explain (when empty, the overall project structure): $ARGUMENTS');
+";
+
+/// Create `audit_log`: one row per tool execution (REQ-API-016).
+///
+/// `conversation_id` deliberately has no foreign key — the audit record must
+/// outlive a hard-deleted conversation. `started_at` is fixed-width RFC 3339
+/// UTC so range filters can compare strings.
+const MIGRATION_009: &str = r"
+CREATE TABLE IF NOT EXISTS audit_log (
+    id INTEGER PRIMARY KEY,
+    conversation_id TEXT NOT NULL,
+    tool_use_id TEXT NOT NULL,
+    tool_name TEXT NOT NULL,
+    input_hash TEXT NOT NULL,
+    input_preview TEXT NOT NULL,
+    cwd TEXT NOT NULL,
+    os_user TEXT,
+    started_at TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    outcome TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_started ON audit_log(started_at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_conversation ON audit_log(conversation_id, started_at);
+";
+
+/// Create `transitions`: every state-machine step the executor applied
+/// (REQ-API-017). `event`, `old_state`, `new_state` and `effects` are the
+/// serde JSON of the corresponding Rust values; rows are ordered by `id`.
+const MIGRATION_010: &str = r"
+CREATE TABLE IF NOT EXISTS transitions (
+    id INTEGER PRIMARY KEY,
+    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    event TEXT NOT NULL,
+    old_state TEXT NOT NULL,
+    new_state TEXT NOT NULL,
+    effects TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_transitions_conversation ON transitions(conversation_id, id);
+";
+
+/// Add the per-conversation extended-thinking budget (REQ-LLM-014). NULL
+/// (the default for existing rows) means thinking is off.
+const MIGRATION_011: &str = r"
+ALTER TABLE conversations ADD COLUMN thinking_budget INTEGER;
+";
+
+/// Per-project post-edit verification (REQ-BED-037). A project without a
+/// row has no verify phase.
+const MIGRATION_012: &str = r"
+CREATE TABLE IF NOT EXISTS project_verify (
+    project_id TEXT PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+    command TEXT NOT NULL,
+    max_attempts INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
+";
+
+/// Create `conversation_templates` (REQ-API-018), seed the built-in
+/// `bug-fix`, `code-review`, `refactor`, and `research` presets, and record
+/// the template a conversation was created from.
+///
+/// `tools` is a JSON array of tool names, NULL for the mode's full set;
+/// `model` NULL means the registry default. Seeds use `INSERT OR IGNORE` so
+/// a user-edited row with the same name is never overwritten.
+const MIGRATION_013: &str = r#"
+CREATE TABLE IF NOT EXISTS conversation_templates (
+    name TEXT PRIMARY KEY,
+    description TEXT NOT NULL,
+    system_prompt TEXT NOT NULL,
+    tools TEXT,
+    model TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+ALTER TABLE conversations ADD COLUMN template TEXT;
+
+INSERT OR IGNORE INTO conversation_templates (name, description, system_prompt, tools) VALUES
+('bug-fix', 'Reproduce, fix, and regression-test a bug',
+ 'This conversation is a bug fix. Reproduce the problem before changing anything, find the root cause rather than patching the symptom, keep the fix minimal, and add a test that fails without it. Finish by running the relevant tests.',
+ NULL),
+('code-review', 'Review changes without modifying files',
+ 'This conversation is a code review. Do not modify files. Read the changes (git diff when no scope is given) and report correctness bugs first, then risky edge cases, then readability issues. Cite file:line for each finding and suggest a concrete fix.',
+ '["think","read_file","search","keyword_search","read_image","bash","ask_user_question"]'),
+('refactor', 'Restructure code without changing behavior',
+ 'This conversation is a refactor. Preserve behavior exactly: no feature changes and no drive-by fixes. Make the change in small steps, run the tests after each one, and call out anything whose behavior you could not verify.',
+ NULL),
+('research', 'Investigate and answer questions without editing',
+ 'This conversation is research. Do not modify files. Gather evidence from the code, documentation, and the web, cite where each finding comes from, and end with a concise answer and any open questions.',
+ '["think","read_file","search","keyword_search","read_image","browser_navigate","browser_eval","browser_take_screenshot","browser_recent_console_logs","browser_wait_for_selector","browser_click","browser_type","browser_key_press","spawn_agents","ask_user_question"]');
+"#;
+
+/// Per-conversation tool selection: a JSON array of tool names the agent
+/// may not use. NULL (the default for existing rows) disables nothing.
+const MIGRATION_014: &str = r"
+ALTER TABLE conversations ADD COLUMN disabled_tools TEXT;
+";
+
+/// Allocate message `sequence_id`s in the database instead of with a
+/// separate `MAX(sequence_id) + 1` read that two writers can race on.
+///
+/// `message_sequences` holds each conversation's high-water mark;
+/// `Database::add_message` bumps it in a single upsert. Messages inserted
+/// with a caller-chosen id (`add_message_with_seq`, crash recovery) advance
+/// it through the trigger. The unique index makes a collision an error
+/// rather than two messages silently sharing a position. Any duplicates
+/// already on disk are moved after the conversation's last message, in
+/// insertion order, so the index can be built.
+const MIGRATION_015: &str = r"
+UPDATE messages
+SET sequence_id = rowid + (
+    SELECT MAX(m.sequence_id) FROM messages m WHERE m.conversation_id = messages.conversation_id
+)
+WHERE EXISTS (
+    SELECT 1 FROM messages d
+    WHERE d.conversation_id = messages.conversation_id
+      AND d.sequence_id = messages.sequence_id
+      AND d.rowid < messages.rowid
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_messages_conversation_sequence
+    ON messages(conversation_id, sequence_id);
+
+CREATE TABLE IF NOT EXISTS message_sequences (
+    conversation_id TEXT PRIMARY KEY REFERENCES conversations(id) ON DELETE CASCADE,
+    last_sequence_id INTEGER NOT NULL
+);
+
+INSERT OR IGNORE INTO message_sequences (conversation_id, last_sequence_id)
+SELECT conversation_id, MAX(sequence_id) FROM messages GROUP BY conversation_id;
+
+CREATE TRIGGER IF NOT EXISTS messages_advance_sequence
+AFTER INSERT ON messages
+BEGIN
+    INSERT OR IGNORE INTO message_sequences (conversation_id, last_sequence_id)
+    VALUES (NEW.conversation_id, NEW.sequence_id);
+    UPDATE message_sequences SET last_sequence_id = NEW.sequence_id
+    WHERE conversation_id = NEW.conversation_id AND last_sequence_id < NEW.sequence_id;
+END;
+";
+
+/// Per-conversation patch review (REQ-PATCH-010): when set, file edits are
+/// staged for the user to apply or reject instead of written directly.
+const MIGRATION_016: &str = r"
+ALTER TABLE conversations ADD COLUMN patch_review INTEGER NOT NULL DEFAULT 0;
+";
+
+/// Create `touched_files`: per conversation, which files the agent's tools
+/// have read or edited and how often (REQ-BED-042). One row per path.
+const MIGRATION_017: &str = r"
+CREATE TABLE IF NOT EXISTS touched_files (
+    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    read_count INTEGER NOT NULL DEFAULT 0,
+    edit_count INTEGER NOT NULL DEFAULT 0,
+    first_touched_at TEXT NOT NULL,
+    last_touched_at TEXT NOT NULL,
+    PRIMARY KEY (conversation_id, path)
+);
+";
+
+/// Messages the user pinned to stay in context verbatim (REQ-BED-045).
+/// No foreign key to `messages`: a continuation pins its seed before the
+/// runtime has persisted it.
+const MIGRATION_018: &str = r"
+CREATE TABLE IF NOT EXISTS pinned_messages (
+    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    message_id TEXT NOT NULL,
+    pinned_at TEXT NOT NULL,
+    PRIMARY KEY (conversation_id, message_id)
+);
+";
+
+/// Per-conversation history window strategy as JSON (REQ-BED-046). NULL is
+/// the full history.
+const MIGRATION_019: &str = r"
+ALTER TABLE conversations ADD COLUMN history_window TEXT;
+";
+
+/// Unsent draft per conversation (REQ-BED-047). `images` is a JSON array of
+/// `ImageData`.
+const MIGRATION_020: &str = r"
+CREATE TABLE IF NOT EXISTS conversation_drafts (
+    conversation_id TEXT PRIMARY KEY REFERENCES conversations(id) ON DELETE CASCADE,
+    text TEXT NOT NULL,
+    images TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+";
+
+/// Thumbs up/down with an optional comment per agent message (REQ-API-022).
+const MIGRATION_021: &str = r"
+CREATE TABLE IF NOT EXISTS message_feedback (
+    message_id TEXT PRIMARY KEY,
+    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    rating TEXT NOT NULL CHECK (rating IN ('up', 'down')),
+    comment TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_message_feedback_created ON message_feedback(created_at);
+";
+
+/// Web Push (REQ-API-024): browser subscriptions, the conversations that
+/// notify them, and the server's VAPID key pair (a single PKCS#8 PEM row).
+const MIGRATION_022: &str = r"
+CREATE TABLE IF NOT EXISTS push_subscriptions (
+    endpoint TEXT PRIMARY KEY,
+    p256dh TEXT NOT NULL,
+    auth TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS push_conversations (
+    conversation_id TEXT PRIMARY KEY REFERENCES conversations(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS vapid_keys (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    private_key_pem TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+";
+
+/// Extra working directories a conversation may use besides its cwd
+/// (REQ-BED-049). `writable` is 0 for read-only roots.
+const MIGRATION_023: &str = r"
+CREATE TABLE IF NOT EXISTS conversation_roots (
+    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    writable INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (conversation_id, path)
+);
+";
+
+/// Indexes behind the conversation list's sorting and filters
+/// (REQ-API-027). The state index is on the same expression the filter
+/// uses, so `SQLite` can match it.
+const MIGRATION_024: &str = r"
+CREATE INDEX IF NOT EXISTS idx_conversations_created ON conversations(created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_conversations_model ON conversations(model);
+CREATE INDEX IF NOT EXISTS idx_conversations_cwd ON conversations(cwd);
+CREATE INDEX IF NOT EXISTS idx_conversations_state_type
+    ON conversations(json_extract(state, '$.type'));
+";
+
+/// Operator settings for a running server (REQ-API-028), one JSON value
+/// per `ServerSettings` field. Unset fields have no row.
+const MIGRATION_025: &str = r"
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+";
+
+/// LLM provider API keys set at runtime (REQ-LLM-019), sealed at rest
+/// (REQ-BED-051). A stored key replaces the provider's environment key.
+const MIGRATION_026: &str = r"
+CREATE TABLE IF NOT EXISTS provider_keys (
+    provider TEXT PRIMARY KEY,
+    api_key TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+";
+
+/// Advisory runtime lock per conversation (REQ-BED-052): the process
+/// running its runtime and when that process last renewed the claim.
+const MIGRATION_027: &str = r"
+ALTER TABLE conversations ADD COLUMN runtime_owner TEXT;
+ALTER TABLE conversations ADD COLUMN runtime_heartbeat_at TEXT;
+";
+
+/// Create `events`: every event the runtime handled, applied or not
+/// (REQ-BED-054). `state` is the serde JSON of the state it arrived in;
+/// rows are ordered by `id`.
+const MIGRATION_028: &str = r"
+CREATE TABLE IF NOT EXISTS events (
+    id INTEGER PRIMARY KEY,
+    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    event TEXT NOT NULL,
+    state TEXT NOT NULL,
+    disposition TEXT NOT NULL,
+    error TEXT,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_events_conversation ON events(conversation_id, id);
+";
+
+/// The shell a conversation's bash tool uses (REQ-BASH-017). No row means
+/// the default: plain `bash`, no profile.
+const MIGRATION_029: &str = r"
+CREATE TABLE IF NOT EXISTS conversation_shells (
+    conversation_id TEXT PRIMARY KEY REFERENCES conversations(id) ON DELETE CASCADE,
+    program TEXT NOT NULL,
+    load_profile INTEGER NOT NULL,
+    updated_at TEXT NOT NULL
+);
+";
+
+/// A conversation's own bash allow/deny lists (REQ-BASH-018), as the serde
+/// JSON of `CommandPolicy`. No row means no conversation rules.
+const MIGRATION_030: &str = r"
+CREATE TABLE IF NOT EXISTS conversation_bash_policies (
+    conversation_id TEXT PRIMARY KEY REFERENCES conversations(id) ON DELETE CASCADE,
+    policy TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+";
+
+/// The bash policy verdict behind an audited call (REQ-BASH-018); NULL when
+/// no rule applied.
+const MIGRATION_031: &str = r"
+ALTER TABLE audit_log ADD COLUMN policy TEXT;
+";
+
+/// Reminders for unanswered questions (REQ-BED-056). `reminded_at` is when
+/// the last one went out, so each wait is reminded about once.
+const MIGRATION_032: &str = r"
+CREATE TABLE IF NOT EXISTS conversation_follow_ups (
+    conversation_id TEXT PRIMARY KEY REFERENCES conversations(id) ON DELETE CASCADE,
+    after_hours INTEGER NOT NULL,
+    webhook_url TEXT,
+    reminded_at TEXT,
+    updated_at TEXT NOT NULL
+);
+";
+
+/// Every answer to a council prompt (REQ-BED-058), kept whether or not it
+/// was chosen. `content` is the JSON of the answer's content blocks;
+/// `error` is set, and `content` empty, for a model that failed.
+const MIGRATION_033: &str = r"
+CREATE TABLE IF NOT EXISTS council_candidates (
+    id TEXT PRIMARY KEY,
+    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    model TEXT NOT NULL,
+    content TEXT NOT NULL,
+    error TEXT,
+    chosen INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_council_candidates_conversation
+    ON council_candidates(conversation_id);
+";
+
+/// The model that wrote each agent message (REQ-LLM-020). Existing replies
+/// take it from their usage, which has named the answering model since
+/// fallbacks (REQ-LLM-013); older ones stay NULL.
+const MIGRATION_034: &str = r"
+ALTER TABLE messages ADD COLUMN model TEXT;
+UPDATE messages SET model = json_extract(usage_data, '$.model')
+WHERE message_type = 'agent' AND usage_data IS NOT NULL;
+";
+
+/// Estimated input tokens each tool's outputs have added to a
+/// conversation's requests (REQ-API-031). `requests` counts the requests
+/// that carried at least one of its outputs.
+const MIGRATION_035: &str = r"
+CREATE TABLE IF NOT EXISTS tool_token_usage (
+    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    tool_name TEXT NOT NULL,
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    requests INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (conversation_id, tool_name)
+);
+";
+
+/// Create `_migrations` if needed. Tables created before checksums were
+/// tracked lack the column; the ALTER fails harmlessly once it exists.
+async fn ensure_tracking_table(pool: &SqlitePool) -> DbResult<()> {
     sqlx::raw_sql(
         "CREATE TABLE IF NOT EXISTS _migrations (\
             version INTEGER PRIMARY KEY, \
             name TEXT NOT NULL, \
-            applied_at TEXT NOT NULL DEFAULT (datetime('now'))\
+            applied_at TEXT NOT NULL DEFAULT (datetime('now')), \
+            checksum TEXT\
         )",
     )
     .execute(pool)
     .await?;
+    let _ = sqlx::raw_sql("ALTER TABLE _migrations ADD COLUMN checksum TEXT")
+        .execute(pool)
+        .await;
+    Ok(())
+}
+
+fn find_migration(version: u32) -> Option<&'static Migration> {
+    MIGRATIONS.iter().find(|m| m.version == version)
+}
+
+/// Startup integrity check of the recorded migration history.
+///
+/// Fails when the database records a migration this build does not know
+/// (it was migrated by a newer release — roll back with that release
+/// first), or when an applied migration's name or SQL has changed since it
+/// ran. Rows recorded before checksums existed are stamped on first check.
+pub async fn verify_migrations(pool: &SqlitePool) -> DbResult<()> {
+    ensure_tracking_table(pool).await?;
+    let applied: Vec<(u32, String, Option<String>)> =
+        sqlx::query_as("SELECT version, name, checksum FROM _migrations ORDER BY version")
+            .fetch_all(pool)
+            .await?;
 
-    // Find the highest version already applied
-    let current_version: u32 =
-        sqlx::query_scalar::<_, Option<u32>>("SELECT MAX(version) FROM _migrations")
-            .fetch_one(pool)
+    for (version, name, checksum) in applied {
+        let Some(known) = find_migration(version) else {
+            return Err(DbError::Migration(format!(
+                "database has migration {version} ({name}) unknown to this build; \
+                 it was migrated by a newer release"
+            )));
+        };
+        if known.name != name {
+            return Err(DbError::Migration(format!(
+                "migration {version} is recorded as {name} but this build calls it {}",
+                known.name
+            )));
+        }
+        let expected = known.checksum();
+        match checksum {
+            Some(recorded) if recorded == expected => {}
+            Some(recorded) => {
+                return Err(DbError::Migration(format!(
+                    "migration {version} ({name}) changed after it was applied \
+                     (checksum {recorded}, now {expected})"
+                )));
+            }
+            None => {
+                sqlx::query("UPDATE _migrations SET checksum = ? WHERE version = ?")
+                    .bind(&expected)
+                    .bind(version)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run all pending migrations against the database.
+///
+/// Verifies the recorded history first, then applies every known migration
+/// not yet recorded, in version order. Returns the number applied.
+pub async fn run_pending_migrations(pool: &SqlitePool) -> DbResult<u32> {
+    verify_migrations(pool).await?;
+
+    let applied_versions: HashSet<u32> =
+        sqlx::query_scalar::<_, u32>("SELECT version FROM _migrations")
+            .fetch_all(pool)
             .await?
-            .unwrap_or(0);
+            .into_iter()
+            .collect();
 
     let mut applied = 0u32;
 
     for migration in MIGRATIONS {
-        if migration.version <= current_version {
+        if applied_versions.contains(&migration.version) {
             continue;
         }
 
@@ -262,13 +959,15 @@ pub async fn run_pending_migrations(pool: &SqlitePool) -> DbResult<u32> {
             "Applying database migration"
         );
 
-        sqlx::raw_sql(migration.sql).execute(pool).await?;
-
-        sqlx::query("INSERT INTO _migrations (version, name) VALUES (?, ?)")
+        let mut tx = pool.begin().await?;
+        sqlx::raw_sql(migration.sql).execute(&mut *tx).await?;
+        sqlx::query("INSERT INTO _migrations (version, name, checksum) VALUES (?, ?, ?)")
             .bind(migration.version)
             .bind(migration.name)
-            .execute(pool)
+            .bind(migration.checksum())
+            .execute(&mut *tx)
             .await?;
+        tx.commit().await?;
 
         applied += 1;
     }
@@ -280,6 +979,56 @@ pub async fn run_pending_migrations(pool: &SqlitePool) -> DbResult<u32> {
     Ok(applied)
 }
 
+/// Undo applied migrations newer than `target`, newest first, each in its
+/// own transaction. Refuses up front, without touching anything, if any
+/// migration in range is irreversible. Returns the number rolled back.
+pub async fn rollback_migrations(pool: &SqlitePool, target: u32) -> DbResult<u32> {
+    verify_migrations(pool).await?;
+
+    let versions: Vec<u32> = sqlx::query_scalar(
+        "SELECT version FROM _migrations WHERE version > ? ORDER BY version DESC",
+    )
+    .bind(target)
+    .fetch_all(pool)
+    .await?;
+
+    let mut plan = Vec::with_capacity(versions.len());
+    for version in versions {
+        // verify_migrations guarantees every recorded version is known.
+        let Some(migration) = find_migration(version) else {
+            return Err(DbError::Migration(format!("unknown migration {version}")));
+        };
+        if matches!(migration.down, Down::Irreversible) {
+            return Err(DbError::Migration(format!(
+                "migration {version} ({}) is irreversible; cannot roll back to {target}",
+                migration.name
+            )));
+        }
+        plan.push(migration);
+    }
+
+    let mut rolled_back = 0u32;
+    for migration in plan {
+        tracing::info!(
+            version = migration.version,
+            name = migration.name,
+            "Rolling back database migration"
+        );
+        let mut tx = pool.begin().await?;
+        if let Down::Sql(sql) = migration.down {
+            sqlx::raw_sql(sql).execute(&mut *tx).await?;
+        }
+        sqlx::query("DELETE FROM _migrations WHERE version = ?")
+            .bind(migration.version)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        rolled_back += 1;
+    }
+
+    Ok(rolled_back)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -299,8 +1048,8 @@ mod tests {
             .unwrap()
     }
 
-    /// Create the conversations table with `conv_mode` and state columns
-    /// (minimal schema needed for migration tests).
+    /// Create the conversations table with `conv_mode` and state columns,
+    /// plus a bare messages table (minimal schema needed for migration tests).
     async fn setup_conversations_table(pool: &SqlitePool) {
         sqlx::raw_sql(
             "CREATE TABLE conversations (\
@@ -308,12 +1057,20 @@ mod tests {
                 conv_mode TEXT NOT NULL DEFAULT '{\"mode\":\"Explore\"}', \
                 state TEXT NOT NULL DEFAULT '{\"type\":\"idle\"}', \
                 cwd TEXT NOT NULL DEFAULT '/tmp', \
+                model TEXT, \
                 parent_conversation_id TEXT, \
                 user_initiated BOOLEAN NOT NULL DEFAULT 1, \
                 archived BOOLEAN NOT NULL DEFAULT 0, \
                 state_updated_at TEXT NOT NULL DEFAULT '2025-01-01', \
                 created_at TEXT NOT NULL DEFAULT '2025-01-01', \
                 updated_at TEXT NOT NULL DEFAULT '2025-01-01'\
+            ); \
+            CREATE TABLE messages (\
+                message_id TEXT PRIMARY KEY, \
+                conversation_id TEXT NOT NULL, \
+                sequence_id INTEGER NOT NULL, \
+                message_type TEXT NOT NULL DEFAULT 'user', \
+                usage_data TEXT\
             )",
         )
         .execute(pool)
@@ -327,7 +1084,7 @@ mod tests {
         setup_conversations_table(&pool).await;
 
         let first = run_pending_migrations(&pool).await.unwrap();
-        assert_eq!(first, 7);
+        assert_eq!(first, 35);
 
         let second = run_pending_migrations(&pool).await.unwrap();
         assert_eq!(second, 0);
@@ -833,4 +1590,262 @@ mod tests {
             "/repo/.phoenix/worktrees/top-explore"
         );
     }
+
+    #[tokio::test]
+    async fn migration_008_seeds_builtin_prompt_templates() {
+        let pool = test_pool().await;
+        setup_conversations_table(&pool).await;
+        run_pending_migrations(&pool).await.unwrap();
+
+        let commands: Vec<String> =
+            sqlx::query_scalar("SELECT command FROM prompt_templates ORDER BY command")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(commands, vec!["explain", "review", "tests"]);
+    }
+
+    /// Migration 011 (REQ-LLM-014): adds a nullable `thinking_budget`;
+    /// existing rows keep thinking off.
+    #[tokio::test]
+    async fn migration_011_adds_thinking_budget_column() {
+        let pool = test_pool().await;
+        setup_conversations_table(&pool).await;
+        sqlx::query("INSERT INTO conversations (id) VALUES ('c-pre')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        run_pending_migrations(&pool).await.unwrap();
+
+        let budget: Option<i64> =
+            sqlx::query_scalar("SELECT thinking_budget FROM conversations WHERE id = 'c-pre'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(budget, None);
+    }
+
+    /// Migration 012 (REQ-BED-037): verify settings live in their own table.
+    #[tokio::test]
+    async fn migration_012_creates_project_verify_table() {
+        let pool = test_pool().await;
+        setup_conversations_table(&pool).await;
+        run_pending_migrations(&pool).await.unwrap();
+        assert!(table_exists(&pool, "project_verify").await);
+
+        rollback_migrations(&pool, 11).await.unwrap();
+        assert!(!table_exists(&pool, "project_verify").await);
+    }
+
+    /// Migration 013 (REQ-API-018): built-in templates are seeded and the
+    /// conversation's template column is added.
+    #[tokio::test]
+    async fn migration_013_seeds_conversation_templates() {
+        let pool = test_pool().await;
+        setup_conversations_table(&pool).await;
+        run_pending_migrations(&pool).await.unwrap();
+
+        let names: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM conversation_templates ORDER BY name")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(names, ["bug-fix", "code-review", "refactor", "research"]);
+        let template: Option<String> =
+            sqlx::query_scalar("SELECT template FROM conversations LIMIT 1")
+                .fetch_optional(&pool)
+                .await
+                .unwrap()
+                .flatten();
+        assert_eq!(template, None);
+
+        rollback_migrations(&pool, 12).await.unwrap();
+        assert!(!table_exists(&pool, "conversation_templates").await);
+    }
+
+    /// Migration 015: existing duplicates are moved to the end, the counter
+    /// starts at each conversation's high-water mark, and explicitly
+    /// numbered inserts advance it.
+    #[tokio::test]
+    async fn migration_015_creates_message_sequences() {
+        let pool = test_pool().await;
+        setup_conversations_table(&pool).await;
+        sqlx::raw_sql(
+            "INSERT INTO conversations (id) VALUES ('c-1'); \
+             INSERT INTO messages (message_id, conversation_id, sequence_id) VALUES \
+             ('m-1', 'c-1', 1), ('m-2', 'c-1', 2), ('m-dup', 'c-1', 2);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        run_pending_migrations(&pool).await.unwrap();
+
+        let seqs: Vec<(String, i64)> =
+            sqlx::query_as("SELECT message_id, sequence_id FROM messages ORDER BY sequence_id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(seqs[..2], [("m-1".to_string(), 1), ("m-2".to_string(), 2)]);
+        assert_eq!(seqs[2].0, "m-dup");
+        assert!(seqs[2].1 > 2);
+
+        let insert = "INSERT INTO messages (message_id, conversation_id, sequence_id) VALUES";
+        let dup = sqlx::query(&format!("{insert} ('m-x', 'c-1', 1)"))
+            .execute(&pool)
+            .await;
+        assert!(
+            dup.is_err(),
+            "unique index must reject a reused sequence_id"
+        );
+
+        sqlx::query(&format!("{insert} ('m-9', 'c-1', 900)"))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let last: i64 = sqlx::query_scalar(
+            "SELECT last_sequence_id FROM message_sequences WHERE conversation_id = 'c-1'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(last, 900);
+    }
+
+    /// Migration 034 (REQ-LLM-020): agent messages get their model from the
+    /// usage stored with them; other messages stay unattributed.
+    #[tokio::test]
+    async fn migration_034_backfills_message_models() {
+        let pool = test_pool().await;
+        setup_conversations_table(&pool).await;
+        sqlx::raw_sql(
+            "INSERT INTO conversations (id) VALUES ('c-1'); \
+             INSERT INTO messages \
+             (message_id, conversation_id, sequence_id, message_type, usage_data) VALUES \
+             ('m-1', 'c-1', 1, 'user', NULL), \
+             ('m-2', 'c-1', 2, 'agent', '{\"input_tokens\":1,\"model\":\"gpt-5.5\"}'), \
+             ('m-3', 'c-1', 3, 'agent', '{\"input_tokens\":1}');",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        run_pending_migrations(&pool).await.unwrap();
+
+        let models: Vec<Option<String>> =
+            sqlx::query_scalar("SELECT model FROM messages ORDER BY sequence_id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(models, [None, Some("gpt-5.5".to_string()), None]);
+    }
+
+    async fn table_exists(pool: &SqlitePool, name: &str) -> bool {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+        )
+        .bind(name)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+        count > 0
+    }
+
+    #[tokio::test]
+    async fn rollback_reverses_migrations_and_they_reapply() {
+        let pool = test_pool().await;
+        setup_conversations_table(&pool).await;
+        run_pending_migrations(&pool).await.unwrap();
+
+        let above_3 = u32::try_from(MIGRATIONS.len()).unwrap() - 3;
+        let rolled_back = rollback_migrations(&pool, 3).await.unwrap();
+        assert_eq!(rolled_back, above_3);
+        assert!(!table_exists(&pool, "prompt_templates").await);
+        assert!(!table_exists(&pool, "chain_qa").await);
+        assert!(!table_exists(&pool, "turn_usage").await);
+        let chain_name_cols: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pragma_table_info('conversations') WHERE name = 'chain_name'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(chain_name_cols, 0);
+
+        let reapplied = run_pending_migrations(&pool).await.unwrap();
+        assert_eq!(reapplied, above_3);
+        assert!(table_exists(&pool, "prompt_templates").await);
+    }
+
+    #[tokio::test]
+    async fn rollback_refuses_irreversible_without_changes() {
+        let pool = test_pool().await;
+        setup_conversations_table(&pool).await;
+        run_pending_migrations(&pool).await.unwrap();
+
+        let err = rollback_migrations(&pool, 0).await.unwrap_err();
+        assert!(err.to_string().contains("irreversible"), "{err}");
+        // Refused up front: nothing above the irreversible one was undone.
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM _migrations")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, i64::try_from(MIGRATIONS.len()).unwrap());
+        assert!(table_exists(&pool, "prompt_templates").await);
+    }
+
+    #[tokio::test]
+    async fn integrity_check_rejects_modified_and_unknown_migrations() {
+        let pool = test_pool().await;
+        setup_conversations_table(&pool).await;
+        run_pending_migrations(&pool).await.unwrap();
+        verify_migrations(&pool).await.unwrap();
+
+        sqlx::query("UPDATE _migrations SET checksum = 'deadbeef' WHERE version = 4")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let err = run_pending_migrations(&pool).await.unwrap_err();
+        assert!(
+            err.to_string().contains("changed after it was applied"),
+            "{err}"
+        );
+
+        sqlx::query("UPDATE _migrations SET checksum = NULL WHERE version = 4")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO _migrations (version, name) VALUES (999, 'from_the_future')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let err = verify_migrations(&pool).await.unwrap_err();
+        assert!(err.to_string().contains("newer release"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn legacy_history_without_checksums_is_stamped() {
+        let pool = test_pool().await;
+        setup_conversations_table(&pool).await;
+        // Tracking table as created by releases before checksums existed.
+        sqlx::raw_sql(
+            "CREATE TABLE _migrations (\
+                version INTEGER PRIMARY KEY, \
+                name TEXT NOT NULL, \
+                applied_at TEXT NOT NULL DEFAULT (datetime('now'))\
+            ); \
+            INSERT INTO _migrations (version, name) VALUES (1, 'rewrite_standalone_to_direct');",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let applied = run_pending_migrations(&pool).await.unwrap();
+        assert_eq!(applied, u32::try_from(MIGRATIONS.len()).unwrap() - 1);
+
+        let missing: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM _migrations WHERE checksum IS NULL")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(missing, 0);
+    }
 }
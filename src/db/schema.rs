@@ -1,6 +1,7 @@
 //! Database schema and types
 
 use crate::llm::ContentBlock;
+use crate::state_machine::budget::TurnLimits;
 pub use crate::state_machine::state::ConvState;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -427,6 +428,68 @@ pub struct Conversation {
     /// DB rows that predate this column.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub chain_name: Option<String>,
+    /// Extended-thinking budget in tokens (REQ-LLM-014). NULL means
+    /// thinking is off. `#[serde(default)]` handles old DB rows that
+    /// predate this column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<u32>,
+    /// Name of the conversation template this conversation was created
+    /// from (REQ-API-018), if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Tools the agent may not use in this conversation. Stored as a JSON
+    /// array; NULL in the DB reads as empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_tools: Vec<String>,
+    /// Stage file edits for the user to apply or reject (REQ-PATCH-010)
+    #[serde(default)]
+    pub patch_review: bool,
+    /// How much history each LLM request carries (REQ-BED-046). NULL in
+    /// the DB reads as the full history.
+    #[serde(default)]
+    pub history_window: HistoryWindow,
+}
+
+/// Which part of the conversation is sent with each LLM request
+/// (REQ-BED-046). Anything but `Full` trades faithfulness for cost, and
+/// defeats prompt caching of old turns once they fall out of the window.
+/// A turn starts at each user message; pinned messages (REQ-BED-045) are
+/// always carried.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum HistoryWindow {
+    /// Every message.
+    #[default]
+    Full,
+    /// Only the most recent `turns` turns.
+    LastTurns { turns: u32 },
+    /// The most recent `turns` turns, preceded by a condensed transcript of
+    /// the user and assistant text of the ones before.
+    SummaryRecent { turns: u32 },
+    /// As many recent turns as fit in `tokens` (at least one).
+    TokenBudget { tokens: u32 },
+}
+
+impl HistoryWindow {
+    /// Smallest accepted `tokens` for [`HistoryWindow::TokenBudget`].
+    pub const MIN_TOKEN_BUDGET: u32 = 1_000;
+
+    /// Reject windows that would send nothing useful.
+    pub fn validate(self) -> Result<(), String> {
+        match self {
+            Self::Full => Ok(()),
+            Self::LastTurns { turns } | Self::SummaryRecent { turns } if turns == 0 => {
+                Err("turns must be at least 1".to_string())
+            }
+            Self::TokenBudget { tokens } if tokens < Self::MIN_TOKEN_BUDGET => Err(format!(
+                "tokens must be at least {}",
+                Self::MIN_TOKEN_BUDGET
+            )),
+            Self::LastTurns { .. } | Self::SummaryRecent { .. } | Self::TokenBudget { .. } => {
+                Ok(())
+            }
+        }
+    }
 }
 
 /// Derive a human-readable title from a kebab-case slug.
@@ -738,6 +801,10 @@ pub struct ToolContent {
     /// `#[serde(default)]` ensures old DB rows (no `images` key) deserialize to empty vec.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub images: Vec<ToolContentImage>,
+    /// The command, when the user ran it themselves rather than the agent
+    /// (REQ-BASH-020). No `tool_use` block answers to such a result.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_command: Option<String>,
 }
 
 impl ToolContent {
@@ -747,6 +814,19 @@ impl ToolContent {
             content: content.into(),
             is_error,
             images: vec![],
+            user_command: None,
+        }
+    }
+
+    /// Output of `command`, which the user ran in the conversation (REQ-BASH-020).
+    pub fn user_command(command: impl Into<String>, content: String, is_error: bool) -> Self {
+        Self {
+            user_command: Some(command.into()),
+            ..Self::new(
+                format!("user-cmd-{}", uuid::Uuid::new_v4()),
+                content,
+                is_error,
+            )
         }
     }
 }
@@ -932,9 +1012,22 @@ pub struct Message {
     pub content: MessageContent,
     pub display_data: Option<Value>,
     pub usage_data: Option<UsageData>,
+    /// Model that wrote an agent message (REQ-LLM-020); `None` for other
+    /// messages and for replies stored before models were recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
+/// The model to record for a new message: an agent reply's usage names the
+/// model that answered (REQ-LLM-020).
+pub fn message_model(message_type: MessageType, usage: Option<&UsageData>) -> Option<String> {
+    if message_type != MessageType::Agent {
+        return None;
+    }
+    usage?.model.clone()
+}
+
 /// Message type
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, ts_rs::TS)]
 #[serde(rename_all = "snake_case")]
@@ -1049,11 +1142,45 @@ pub struct ChainQaRow {
     pub completed_at: Option<DateTime<Utc>>,
 }
 
+/// Server-side slash-command prompt template (REQ-IR-009).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    /// Command name without the leading `/` (e.g., "review")
+    pub command: String,
+    pub description: String,
+    pub argument_hint: Option<String>,
+    /// Template body; `$ARGUMENTS`, `$N`, and `$CWD` are substituted on expansion
+    pub template: String,
+}
+
+/// Preset applied to a conversation created from it (REQ-API-018).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversationTemplate {
+    /// Template name as passed in `template` on create (e.g., "bug-fix")
+    pub name: String,
+    pub description: String,
+    /// Appended to the conversation's system prompt
+    pub system_prompt: String,
+    /// Tool names the conversation may use; `None` keeps the mode's full set
+    pub tools: Option<Vec<String>>,
+    /// Model used when the create request names none
+    pub model: Option<String>,
+}
+
+/// A project's post-edit verification settings (REQ-BED-037).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifySettings {
+    /// Shell command run in the conversation's working directory
+    pub command: String,
+    /// Failed verify runs fed back to the agent before it gives up
+    pub max_attempts: u32,
+}
+
 /// Type alias for backward compatibility — `Usage` is the canonical type.
 pub type UsageData = crate::llm::Usage;
 
 /// Aggregated token counts and turn count for a query scope.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct UsageTotals {
     pub input_tokens: i64,
     pub output_tokens: i64,
@@ -1062,6 +1189,67 @@ pub struct UsageTotals {
     pub turns: i64,
 }
 
+impl UsageTotals {
+    pub fn add(&mut self, other: &UsageTotals) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.turns += other.turns;
+    }
+}
+
+/// The totals as one usage, so derived figures such as the cache hit rate
+/// are computed by `Usage` alone.
+impl From<&UsageTotals> for crate::llm::Usage {
+    fn from(totals: &UsageTotals) -> Self {
+        let count = |tokens: i64| u64::try_from(tokens).unwrap_or(0);
+        Self {
+            input_tokens: count(totals.input_tokens),
+            output_tokens: count(totals.output_tokens),
+            cache_creation_tokens: count(totals.cache_creation_tokens),
+            cache_read_tokens: count(totals.cache_read_tokens),
+            model: None,
+        }
+    }
+}
+
+/// Dimension for the usage summary (REQ-LLM-010).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGroupBy {
+    #[default]
+    Model,
+    /// UTC calendar day of the turn.
+    Day,
+    /// Root conversation; sub-agent turns roll up into their parent.
+    Conversation,
+}
+
+/// One `(group, model)` bucket of `turn_usage`. Rows keep the model even
+/// when grouping by day or conversation so cost can be priced per model.
+#[derive(Debug, Clone)]
+pub struct UsageBreakdownRow {
+    pub key: String,
+    /// Conversation title when grouping by conversation.
+    pub label: Option<String>,
+    pub model: String,
+    pub totals: UsageTotals,
+}
+
+/// What the conversation list shows about a conversation beyond its row
+/// (REQ-API-026). Read for the whole list in one query.
+#[derive(Debug, Clone, Default)]
+pub struct ConversationListStats {
+    /// Start of the text of the latest user or agent message.
+    pub last_message_preview: Option<String>,
+    /// Token usage of the conversation and its sub-agents, per model, so
+    /// each model can be priced.
+    pub usage_by_model: Vec<(String, UsageTotals)>,
+    /// When a follow-up reminder last went out (REQ-BED-056).
+    pub reminded_at: Option<DateTime<Utc>>,
+}
+
 /// Token usage for a conversation, broken out by scope.
 ///
 /// `own` covers only the conversation itself; `total` includes all sub-agents
@@ -1072,6 +1260,517 @@ pub struct ConversationUsage {
     pub total: UsageTotals,
 }
 
+/// How an audited tool execution ended (REQ-API-016).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Error,
+    Cancelled,
+    UnknownTool,
+    /// Refused by the bash command policy before it ran (REQ-BASH-018)
+    Denied,
+}
+
+impl AuditOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Success => "success",
+            Self::Error => "error",
+            Self::Cancelled => "cancelled",
+            Self::UnknownTool => "unknown_tool",
+            Self::Denied => "denied",
+        }
+    }
+}
+
+impl std::str::FromStr for AuditOutcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "success" => Ok(Self::Success),
+            "error" => Ok(Self::Error),
+            "cancelled" => Ok(Self::Cancelled),
+            "unknown_tool" => Ok(Self::UnknownTool),
+            "denied" => Ok(Self::Denied),
+            _ => Err(format!("unknown audit outcome: {s}")),
+        }
+    }
+}
+
+/// Characters of tool input kept in [`AuditEntry::input_preview`].
+const AUDIT_PREVIEW_CHARS: usize = 300;
+
+/// One `audit_log` row: a single tool execution (REQ-API-016).
+///
+/// Rows are not tied to the conversation by foreign key, so the record of
+/// what ran survives the conversation being deleted.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub conversation_id: String,
+    pub tool_use_id: String,
+    pub tool_name: String,
+    /// SHA-256 (hex) of the tool input as compact JSON.
+    pub input_hash: String,
+    /// Start of the compact JSON input, for answering "what ran" without
+    /// storing whole file bodies.
+    pub input_preview: String,
+    pub cwd: String,
+    /// OS account the tool ran as.
+    pub os_user: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: i64,
+    pub outcome: AuditOutcome,
+    /// The bash command policy's verdict, when a rule applied
+    /// (REQ-BASH-018), e.g. `deny conversation prefix "git push"`.
+    pub policy: Option<String>,
+}
+
+impl AuditEntry {
+    /// `(input_hash, input_preview)` for a tool input.
+    pub fn fingerprint_input(input: &Value) -> (String, String) {
+        use sha2::{Digest, Sha256};
+        use std::fmt::Write;
+
+        let json = input.to_string();
+        let hash = Sha256::digest(json.as_bytes()).iter().fold(
+            String::with_capacity(64),
+            |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            },
+        );
+        let preview = json.chars().take(AUDIT_PREVIEW_CHARS).collect();
+        (hash, preview)
+    }
+}
+
+/// Filters for `GET /api/audit` (REQ-API-016). All optional.
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditQuery {
+    pub conversation_id: Option<String>,
+    pub tool: Option<String>,
+    /// Inclusive lower bound on `started_at`.
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `started_at`.
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<u32>,
+}
+
+/// Sort key for `GET /api/conversations` (REQ-API-027).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConversationSort {
+    Created,
+    #[default]
+    Updated,
+    /// Priced per model outside SQL, so the database returns these by
+    /// `Updated` and the API layer reorders them.
+    Cost,
+    MessageCount,
+}
+
+/// Direction of a [`ConversationSort`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Sorting and filters for `GET /api/conversations` (REQ-API-027). All
+/// optional; the default is most recently updated first.
+#[derive(Debug, Default, Deserialize)]
+pub struct ConversationListQuery {
+    #[serde(default)]
+    pub sort: ConversationSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    /// State type, e.g. `idle` or `error`.
+    pub state: Option<String>,
+    /// Plain string prefix of the working directory.
+    pub cwd_prefix: Option<String>,
+    pub model: Option<String>,
+    /// Inclusive lower bound on `created_at`.
+    pub since: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `created_at`.
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Operator overrides for a running server (REQ-API-028), kept in the
+/// `settings` table and read on each use, so changes apply without a
+/// restart. Unset fields fall back to the environment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerSettings {
+    /// Model for new conversations that don't name one. Ignored while the
+    /// registry does not have it.
+    #[serde(default)]
+    pub default_model: Option<String>,
+    /// Overrides `PHOENIX_RETENTION_ARCHIVED_DAYS` (REQ-API-014); `0` turns
+    /// the policy off.
+    #[serde(default)]
+    pub retention_archived_days: Option<u32>,
+    /// Overrides `PHOENIX_RETENTION_TOOL_OUTPUT_DAYS`; `0` turns it off.
+    #[serde(default)]
+    pub retention_tool_output_days: Option<u32>,
+    /// Tools switched off in every conversation, on top of each
+    /// conversation's own selection (REQ-BED-039).
+    #[serde(default)]
+    pub disabled_tools: Vec<String>,
+    /// Per-turn limit overrides (REQ-BED-038), like the matching
+    /// `PHOENIX_TURN_MAX_*` variables; `0` disables a limit.
+    #[serde(default)]
+    pub turn_max_tool_calls: Option<u32>,
+    #[serde(default)]
+    pub turn_max_llm_requests: Option<u32>,
+    #[serde(default)]
+    pub turn_max_minutes: Option<u32>,
+    #[serde(default)]
+    pub turn_max_repeats: Option<u32>,
+    /// Bash commands allowed or refused in every conversation, on top of
+    /// each conversation's own policy (REQ-BASH-018).
+    #[serde(default)]
+    pub bash_policy: Option<CommandPolicy>,
+}
+
+impl ServerSettings {
+    /// `base` with the turn limits set here replacing its own.
+    pub fn turn_limits(&self, base: TurnLimits) -> TurnLimits {
+        TurnLimits {
+            max_tool_calls: self.turn_max_tool_calls.unwrap_or(base.max_tool_calls),
+            max_llm_requests: self.turn_max_llm_requests.unwrap_or(base.max_llm_requests),
+            max_duration: self.turn_max_minutes.map_or(base.max_duration, |mins| {
+                (mins > 0).then_some(std::time::Duration::from_mins(u64::from(mins)))
+            }),
+            max_repeats: self.turn_max_repeats.unwrap_or(base.max_repeats),
+        }
+    }
+}
+
+/// One `transitions` row: a state-machine step the executor applied
+/// (REQ-API-017).
+///
+/// The JSON columns hold the serde form of the Rust values at the time of
+/// the step. They are kept as `Value` so old rows stay readable after the
+/// state machine's types evolve.
+#[derive(Debug, Clone, Serialize)]
+pub struct TransitionRecord {
+    /// Row id, ascending in application order. `0` before insertion.
+    pub id: i64,
+    pub conversation_id: String,
+    /// `Event::variant_name` of `event`, for filtering without parsing JSON.
+    pub event_type: String,
+    pub event: Value,
+    pub old_state: Value,
+    pub new_state: Value,
+    pub effects: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TransitionRecord {
+    /// Snapshot a transition that is about to be applied.
+    pub fn capture(
+        conversation_id: &str,
+        event: &crate::state_machine::Event,
+        old_state: &ConvState,
+        new_state: &ConvState,
+        effects: &[crate::state_machine::Effect],
+    ) -> Self {
+        Self {
+            id: 0,
+            conversation_id: conversation_id.to_string(),
+            event_type: event.variant_name().to_string(),
+            event: serde_json::to_value(event).unwrap_or_default(),
+            old_state: serde_json::to_value(old_state).unwrap_or_default(),
+            new_state: serde_json::to_value(new_state).unwrap_or_default(),
+            effects: serde_json::to_value(effects).unwrap_or_default(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// What became of an event the runtime handled (REQ-BED-054).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventDisposition {
+    /// `transition()` accepted it.
+    Applied,
+    /// `transition()` rejected it; the state did not change.
+    Rejected,
+    /// Held until the state can take it (sub-agent results).
+    Buffered,
+    /// Handled before reaching the state machine (a verify failure past
+    /// its attempt budget).
+    Dropped,
+}
+
+impl EventDisposition {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Applied => "applied",
+            Self::Rejected => "rejected",
+            Self::Buffered => "buffered",
+            Self::Dropped => "dropped",
+        }
+    }
+}
+
+impl std::str::FromStr for EventDisposition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "applied" => Ok(Self::Applied),
+            "rejected" => Ok(Self::Rejected),
+            "buffered" => Ok(Self::Buffered),
+            "dropped" => Ok(Self::Dropped),
+            other => Err(format!("unknown event disposition: {other}")),
+        }
+    }
+}
+
+/// One `events` row: an event the runtime handled, in the state it found
+/// (REQ-BED-054). Unlike [`TransitionRecord`], rejected and buffered events
+/// are kept too, so the log reproduces everything the runtime was asked to
+/// do.
+#[derive(Debug, Clone, Serialize)]
+pub struct EventRecord {
+    /// Row id, ascending in handling order. `0` before insertion.
+    pub id: i64,
+    pub conversation_id: String,
+    /// `Event::variant_name` of `event`.
+    pub event_type: String,
+    pub event: Value,
+    /// The conversation state when the event arrived.
+    pub state: Value,
+    pub disposition: EventDisposition,
+    /// Why `transition()` rejected the event.
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EventRecord {
+    /// Snapshot an event as it is handled.
+    pub fn capture(
+        conversation_id: &str,
+        event: &crate::state_machine::Event,
+        state: &ConvState,
+        disposition: EventDisposition,
+        error: Option<String>,
+    ) -> Self {
+        Self {
+            id: 0,
+            conversation_id: conversation_id.to_string(),
+            event_type: event.variant_name().to_string(),
+            event: serde_json::to_value(event).unwrap_or_default(),
+            state: serde_json::to_value(state).unwrap_or_default(),
+            disposition,
+            error,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// The state a conversation entered at one recorded transition, without the
+/// payloads, for the state timeline (REQ-API-023).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateChange {
+    /// `type` tag of the new state, e.g. `llm_requesting`.
+    pub state: String,
+    /// `Event::variant_name` of the event that caused the change.
+    pub event_type: String,
+    pub at: DateTime<Utc>,
+}
+
+/// How a tool call touched a file, for the touched-files index (REQ-BED-042).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TouchKind {
+    Read,
+    Edit,
+}
+
+/// One `touched_files` row: a file the agent's tools read or edited in a
+/// conversation (REQ-BED-042).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TouchedFile {
+    /// Absolute path as the tool resolved it.
+    pub path: String,
+    pub read_count: i64,
+    pub edit_count: i64,
+    pub first_touched_at: DateTime<Utc>,
+    pub last_touched_at: DateTime<Utc>,
+}
+
+/// One `tool_token_usage` row: how many input tokens a tool's outputs have
+/// added to a conversation's requests, by estimate (REQ-API-031).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolTokenUsage {
+    pub tool_name: String,
+    /// Summed over every request, so an output counts once per request
+    /// that carried it.
+    pub input_tokens: i64,
+    /// Requests that carried at least one of the tool's outputs.
+    pub requests: i64,
+}
+
+/// An unsent message kept server-side so it survives reloads and device
+/// switches (REQ-BED-047).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConversationDraft {
+    pub text: String,
+    pub images: Vec<ImageData>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A user's verdict on an agent message (REQ-API-022).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackRating {
+    Up,
+    Down,
+}
+
+impl FeedbackRating {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+        }
+    }
+}
+
+impl std::str::FromStr for FeedbackRating {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(Self::Up),
+            "down" => Ok(Self::Down),
+            _ => Err(format!("unknown feedback rating: {s}")),
+        }
+    }
+}
+
+/// One `message_feedback` row: the rating a user gave an agent message
+/// (REQ-API-022). Rating again replaces it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MessageFeedback {
+    pub message_id: String,
+    pub conversation_id: String,
+    pub rating: FeedbackRating,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A rated agent turn as exported for evaluation datasets (REQ-API-022):
+/// the feedback, the user message that prompted the turn, and the agent's
+/// reply text.
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedbackExportRecord {
+    #[serde(flatten)]
+    pub feedback: MessageFeedback,
+    /// Text of the closest earlier user message; `None` when there is none.
+    pub prompt: Option<String>,
+    /// Text blocks of the rated message.
+    pub response: String,
+    pub model: Option<String>,
+}
+
+/// A browser's Web Push subscription (REQ-API-024). `p256dh` and `auth` are
+/// the base64url keys the browser hands out for payload encryption.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PushSubscription {
+    pub endpoint: String,
+    pub p256dh: String,
+    pub auth: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A directory a conversation may use besides its cwd (REQ-BED-049), e.g. a
+/// shared library checkout. Read-only unless `writable`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConversationRoot {
+    pub path: String,
+    #[serde(default)]
+    pub writable: bool,
+}
+
+/// The shell a conversation's bash tool runs commands in (REQ-BASH-017).
+/// `program` is `bash`, `zsh`, `fish`, or the absolute path of a shell.
+/// With `load_profile` it starts as a login shell, so the user's profile
+/// sets up aliases and version-manager shims as in their own terminal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShellSettings {
+    #[serde(default = "default_shell_program")]
+    pub program: String,
+    #[serde(default)]
+    pub load_profile: bool,
+}
+
+fn default_shell_program() -> String {
+    "bash".to_string()
+}
+
+impl Default for ShellSettings {
+    fn default() -> Self {
+        Self {
+            program: default_shell_program(),
+            load_profile: false,
+        }
+    }
+}
+
+/// Allow and deny lists for bash commands (REQ-BASH-018), one for the
+/// server and one per conversation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandPolicy {
+    #[serde(default)]
+    pub allow: Vec<PolicyRule>,
+    #[serde(default)]
+    pub deny: Vec<PolicyRule>,
+}
+
+impl CommandPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+}
+
+/// One entry of a [`CommandPolicy`], as `{"kind": "prefix", "pattern": "git push"}`.
+/// A prefix matches whole leading words; a regex matches anywhere in the
+/// command unless anchored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "pattern", rename_all = "snake_case")]
+pub enum PolicyRule {
+    Prefix(String),
+    Regex(String),
+}
+
+/// Reminder for a question the user has left unanswered (REQ-BED-056).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FollowUpSettings {
+    /// Hours the question may wait before the reminder goes out.
+    pub after_hours: u32,
+    /// Also `POST` the reminder here, besides Web Push.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+}
+
+/// A conversation with a follow-up set, as the watchdog sees it.
+#[derive(Debug, Clone)]
+pub struct FollowUpWatch {
+    pub conversation_id: String,
+    pub settings: FollowUpSettings,
+    pub state: ConvState,
+    pub state_updated_at: DateTime<Utc>,
+    /// When the last reminder went out, if one did.
+    pub reminded_at: Option<DateTime<Utc>>,
+}
+
 #[cfg(test)]
 mod conv_mode_tests {
     use super::*;
@@ -1273,6 +1972,11 @@ mod conversation_serde_tests {
             seed_label: None,
             continued_in_conv_id,
             chain_name: None,
+            thinking_budget: None,
+            template: None,
+            disabled_tools: Vec::new(),
+            patch_review: false,
+            history_window: HistoryWindow::Full,
         }
     }
 
@@ -1323,3 +2027,63 @@ mod conversation_serde_tests {
         assert_eq!(parsed.continued_in_conv_id, None);
     }
 }
+
+#[cfg(test)]
+mod history_window_tests {
+    use super::*;
+
+    #[test]
+    fn history_window_wire_format() {
+        let window: HistoryWindow =
+            serde_json::from_str(r#"{"strategy":"last_turns","turns":5}"#).unwrap();
+        assert_eq!(window, HistoryWindow::LastTurns { turns: 5 });
+        assert_eq!(
+            serde_json::to_string(&HistoryWindow::Full).unwrap(),
+            r#"{"strategy":"full"}"#
+        );
+    }
+
+    #[test]
+    fn history_window_validation() {
+        assert!(HistoryWindow::Full.validate().is_ok());
+        assert!(HistoryWindow::SummaryRecent { turns: 3 }.validate().is_ok());
+        assert!(HistoryWindow::LastTurns { turns: 0 }.validate().is_err());
+        assert!(HistoryWindow::TokenBudget { tokens: 500 }
+            .validate()
+            .is_err());
+        assert!(HistoryWindow::TokenBudget { tokens: 50_000 }
+            .validate()
+            .is_ok());
+    }
+}
+
+#[cfg(test)]
+mod server_settings_tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn turn_limit_settings_replace_the_base() {
+        let settings = ServerSettings {
+            turn_max_tool_calls: Some(50),
+            turn_max_minutes: Some(0),
+            ..ServerSettings::default()
+        };
+        let limits = settings.turn_limits(TurnLimits::DEFAULT);
+        assert_eq!(limits.max_tool_calls, 50);
+        assert_eq!(limits.max_duration, None);
+        assert_eq!(
+            limits.max_llm_requests,
+            TurnLimits::DEFAULT.max_llm_requests
+        );
+
+        let limits = ServerSettings::default().turn_limits(TurnLimits::DEFAULT);
+        assert_eq!(limits, TurnLimits::DEFAULT);
+        let minutes = ServerSettings {
+            turn_max_minutes: Some(5),
+            ..ServerSettings::default()
+        };
+        let limits = minutes.turn_limits(TurnLimits::DEFAULT);
+        assert_eq!(limits.max_duration, Some(Duration::from_mins(5)));
+    }
+}
@@ -0,0 +1,813 @@
+//! Evaluation harness (REQ-CLI-012): `phoenix-ide eval`
+//!
+//! `phoenix-ide eval --fixtures DIR [--model ID] [--baseline REPORT]` replays
+//! recorded tasks against the current system prompt, tools, and model, and
+//! scores how each one turned out. The model can be the mock (`--model
+//! mock`), recorded cassettes (`PHOENIX_LLM_MODE=replay`, REQ-LLM-011), or a
+//! live provider. Each fixture is a JSON file in `DIR`:
+//!
+//! ```json
+//! {
+//!   "name": "fix-pagination",
+//!   "workspace": "repos/pagination",
+//!   "setup": ["git init -q", "git add -A", "git commit -qm base"],
+//!   "prompt": "The pagination test fails. Fix it.",
+//!   "follow_ups": ["Add a regression test too."],
+//!   "expect": { "status": "completed", "test_command": "cargo test", "max_turns": 8 }
+//! }
+//! ```
+//!
+//! `workspace` (relative to the fixture file) is copied to
+//! `<data dir>/evals/<run id>/<name>` so every run starts from the same tree
+//! and the result stays around for inspection. `setup` runs there before the
+//! prompt is sent. The prompt and each follow-up go through the same steps as
+//! `phoenix-ide run` (REQ-CLI-011). A fixture passes when every check does:
+//!
+//! - `status`: how the run ended (default `completed`)
+//! - `patches_apply`: no `patch` call failed, unless `allow_patch_errors`
+//! - `max_turns`: at most this many model requests, when given
+//! - `tests`: `test_command` exits 0 in the workspace, when given
+//!
+//! The JSON report goes to `--report` (default `<data dir>/evals/<run
+//! id>/report.json`) and a summary to stdout. With `--baseline`, fixtures
+//! that passed in that earlier report and fail now are listed as
+//! regressions. Exit code 0 means everything passed (with a baseline:
+//! nothing regressed), 1 that something did not, 2 bad arguments.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use crate::api::{
+    send_follow_up, start_task, wait_until_settled, AppState, RunArgs, RunReport, RunStatus,
+};
+use crate::db::{Message, MessageContent};
+use crate::llm::ContentBlock;
+
+/// Limit on each setup and test command.
+const COMMAND_TIMEOUT: Duration = Duration::from_mins(10);
+
+/// Tail of a failing command's output kept in the report, in chars.
+const OUTPUT_TAIL_CHARS: usize = 2_000;
+
+const USAGE: &str = "\
+Usage: phoenix-ide eval --fixtures DIR [options]
+
+Options:
+  --fixtures DIR    Directory of fixture JSON files (required)
+  --only TEXT       Run only fixtures whose name contains TEXT
+  --model ID        Model for every fixture (\"mock\" needs no API key)
+  --max-turns N     Stop each turn after N model requests
+  --baseline PATH   Earlier report to compare against
+  --report PATH     Where to write the JSON report";
+
+/// Parsed `phoenix-ide eval` arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalArgs {
+    pub fixtures: PathBuf,
+    pub only: Option<String>,
+    pub model: Option<String>,
+    pub max_turns: Option<u32>,
+    pub baseline: Option<PathBuf>,
+    pub report: Option<PathBuf>,
+}
+
+impl EvalArgs {
+    /// Parse the process arguments (without the program name). Returns
+    /// `Ok(None)` when the first argument is not `eval`.
+    pub fn parse(args: &[String]) -> Result<Option<Self>, String> {
+        let Some((first, rest)) = args.split_first() else {
+            return Ok(None);
+        };
+        if first != "eval" {
+            return Ok(None);
+        }
+        let mut fixtures = None;
+        let mut eval = Self {
+            fixtures: PathBuf::new(),
+            only: None,
+            model: None,
+            max_turns: None,
+            baseline: None,
+            report: None,
+        };
+        let mut iter = rest.iter();
+        while let Some(arg) = iter.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            if matches!(flag, "-h" | "--help") {
+                return Err(USAGE.to_string());
+            }
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| iter.next().cloned())
+                    .ok_or_else(|| format!("{flag} needs a value\n\n{USAGE}"))
+            };
+            match flag {
+                "--fixtures" => fixtures = Some(PathBuf::from(value()?)),
+                "--only" => eval.only = Some(value()?),
+                "--model" | "-m" => eval.model = Some(value()?),
+                "--max-turns" => {
+                    let raw = value()?;
+                    let n = raw
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| format!("invalid --max-turns: {raw}"))?;
+                    eval.max_turns = Some(n);
+                }
+                "--baseline" => eval.baseline = Some(PathBuf::from(value()?)),
+                "--report" => eval.report = Some(PathBuf::from(value()?)),
+                _ => return Err(format!("unknown argument: {arg}\n\n{USAGE}")),
+            }
+        }
+        eval.fixtures = fixtures.ok_or_else(|| format!("--fixtures is required\n\n{USAGE}"))?;
+        Ok(Some(eval))
+    }
+}
+
+/// One recorded task, as stored in a fixture file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fixture {
+    /// Defaults to the file name without `.json`.
+    #[serde(default)]
+    pub name: String,
+    /// Directory copied in as the working directory, relative to the
+    /// fixture file. An empty directory when absent.
+    #[serde(default)]
+    pub workspace: Option<PathBuf>,
+    /// Shell commands run in the workspace before the prompt.
+    #[serde(default)]
+    pub setup: Vec<String>,
+    pub prompt: String,
+    /// Later user messages, each sent once the agent has settled.
+    #[serde(default)]
+    pub follow_ups: Vec<String>,
+    /// Model for this fixture when `--model` is not given.
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+/// What a fixture's run has to achieve.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectations {
+    /// How the run must end; `completed` when absent.
+    #[serde(default)]
+    pub status: Option<RunStatus>,
+    /// Shell command that must exit 0 in the workspace afterwards.
+    #[serde(default)]
+    pub test_command: Option<String>,
+    /// Most model requests the whole run may take.
+    #[serde(default)]
+    pub max_turns: Option<u32>,
+    /// Don't fail the fixture when a `patch` call failed.
+    #[serde(default)]
+    pub allow_patch_errors: bool,
+}
+
+/// Outcome of one check on a fixture's run.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String,
+}
+
+/// Everything the report says about one fixture.
+#[derive(Debug, Serialize)]
+pub struct FixtureResult {
+    pub name: String,
+    pub passed: bool,
+    /// Set when the fixture could not be run to the end (bad setup, no
+    /// conversation); its checks are then empty.
+    pub error: Option<String>,
+    pub status: Option<RunStatus>,
+    pub conversation_id: Option<String>,
+    pub workspace: String,
+    pub turns: u32,
+    pub tool_calls: u32,
+    pub duration_ms: u64,
+    pub checks: Vec<CheckResult>,
+}
+
+/// The JSON report of one `phoenix-ide eval`.
+#[derive(Debug, Serialize)]
+pub struct EvalReport {
+    pub run_id: String,
+    pub model: Option<String>,
+    pub passed: usize,
+    pub failed: usize,
+    /// Fixtures that passed in the baseline and fail now.
+    pub regressions: Vec<String>,
+    /// Fixtures that failed in the baseline and pass now.
+    pub fixed: Vec<String>,
+    pub fixtures: Vec<FixtureResult>,
+}
+
+/// The part of an earlier report a baseline comparison reads.
+#[derive(Debug, Deserialize)]
+struct Baseline {
+    fixtures: Vec<BaselineFixture>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BaselineFixture {
+    name: String,
+    passed: bool,
+}
+
+/// Run every fixture and return the process exit code.
+pub async fn run_eval(state: &AppState, args: &EvalArgs, data_dir: &Path) -> i32 {
+    match eval(state, args, data_dir).await {
+        Ok(report) => {
+            let ok = if args.baseline.is_some() {
+                report.regressions.is_empty()
+            } else {
+                report.failed == 0
+            };
+            i32::from(!ok)
+        }
+        Err(e) => {
+            eprintln!("phoenix-ide eval: {e}");
+            2
+        }
+    }
+}
+
+async fn eval(state: &AppState, args: &EvalArgs, data_dir: &Path) -> Result<EvalReport, String> {
+    let fixtures = load_fixtures(&args.fixtures)?
+        .into_iter()
+        .filter(|(_, f)| args.only.as_ref().is_none_or(|only| f.name.contains(only)))
+        .collect::<Vec<_>>();
+    if fixtures.is_empty() {
+        return Err(format!("no fixtures in {}", args.fixtures.display()));
+    }
+    let baseline = args
+        .baseline
+        .as_deref()
+        .map(|path| {
+            let raw = std::fs::read_to_string(path)
+                .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+            serde_json::from_str::<Baseline>(&raw)
+                .map_err(|e| format!("{} is not an eval report: {e}", path.display()))
+        })
+        .transpose()?;
+
+    let run_id = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let run_dir = data_dir.join("evals").join(&run_id);
+    let mut results = Vec::new();
+    for (dir, fixture) in &fixtures {
+        eprintln!("phoenix-ide eval: running {}", fixture.name);
+        let workspace = run_dir.join(&fixture.name);
+        let model = args.model.clone().or_else(|| fixture.model.clone());
+        let result = run_fixture(state, fixture, dir, &workspace, model).await;
+        println!(
+            "{} {} ({} turn(s), {} ms){}",
+            if result.passed { "PASS" } else { "FAIL" },
+            result.name,
+            result.turns,
+            result.duration_ms,
+            failure_summary(&result)
+        );
+        results.push(result);
+    }
+
+    let (regressions, fixed) = baseline
+        .as_ref()
+        .map(|b| compare(b, &results))
+        .unwrap_or_default();
+    let passed = results.iter().filter(|r| r.passed).count();
+    let report = EvalReport {
+        run_id,
+        model: args.model.clone(),
+        passed,
+        failed: results.len() - passed,
+        regressions,
+        fixed,
+        fixtures: results,
+    };
+
+    let path = args
+        .report
+        .clone()
+        .unwrap_or_else(|| run_dir.join("report.json"));
+    write_report(&path, &report).map_err(|e| format!("cannot write {}: {e}", path.display()))?;
+    println!("{} passed, {} failed", report.passed, report.failed);
+    for name in &report.regressions {
+        println!("REGRESSION {name}");
+    }
+    for name in &report.fixed {
+        println!("FIXED {name}");
+    }
+    eprintln!("phoenix-ide eval: report at {}", path.display());
+    Ok(report)
+}
+
+/// Fixtures in `dir` sorted by file name, each with the directory its
+/// relative paths resolve against.
+fn load_fixtures(dir: &Path) -> Result<Vec<(PathBuf, Fixture)>, String> {
+    let entries =
+        std::fs::read_dir(dir).map_err(|e| format!("cannot read {}: {e}", dir.display()))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let raw = std::fs::read_to_string(&path)
+                .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+            let mut fixture: Fixture = serde_json::from_str(&raw)
+                .map_err(|e| format!("invalid fixture {}: {e}", path.display()))?;
+            if fixture.name.trim().is_empty() {
+                fixture.name = path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+            }
+            let base = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            Ok((base, fixture))
+        })
+        .collect()
+}
+
+async fn run_fixture(
+    state: &AppState,
+    fixture: &Fixture,
+    fixture_dir: &Path,
+    workspace: &Path,
+    model: Option<String>,
+) -> FixtureResult {
+    let started = Instant::now();
+    let mut result = FixtureResult {
+        name: fixture.name.clone(),
+        passed: false,
+        error: None,
+        status: None,
+        conversation_id: None,
+        workspace: workspace.display().to_string(),
+        turns: 0,
+        tool_calls: 0,
+        duration_ms: 0,
+        checks: Vec::new(),
+    };
+    match drive(state, fixture, fixture_dir, workspace, model, started).await {
+        Ok((report, messages)) => {
+            let tests = match &fixture.expect.test_command {
+                Some(command) => Some(run_command(command, workspace).await),
+                None => None,
+            };
+            result.checks = score(&fixture.expect, &report, &messages, tests);
+            result.passed = result.checks.iter().all(|c| c.passed);
+            result.status = Some(report.status);
+            result.conversation_id = Some(report.conversation_id);
+            result.turns = report.turns;
+            result.tool_calls = report.tool_calls;
+        }
+        Err(e) => result.error = Some(e),
+    }
+    result.duration_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+    result
+}
+
+/// Prepare the workspace, then send the prompt and follow-ups, waiting for
+/// the agent to settle after each.
+async fn drive(
+    state: &AppState,
+    fixture: &Fixture,
+    fixture_dir: &Path,
+    workspace: &Path,
+    model: Option<String>,
+    started: Instant,
+) -> Result<(RunReport, Vec<Message>), String> {
+    if workspace.exists() {
+        return Err(format!("{} already exists", workspace.display()));
+    }
+    match &fixture.workspace {
+        Some(source) => copy_dir(&fixture_dir.join(source), workspace)
+            .map_err(|e| format!("cannot copy workspace {}: {e}", source.display()))?,
+        None => std::fs::create_dir_all(workspace)
+            .map_err(|e| format!("cannot create {}: {e}", workspace.display()))?,
+    }
+    for command in &fixture.setup {
+        let (ok, output) = run_command(command, workspace).await;
+        if !ok {
+            return Err(format!("setup `{command}` failed:\n{output}"));
+        }
+    }
+
+    let args = RunArgs {
+        prompt: fixture.prompt.clone(),
+        cwd: workspace.to_path_buf(),
+        max_turns: None,
+        model,
+        mode: None,
+        report: None,
+    };
+    let id = start_task(state, &args).await?;
+    let mut settled = wait_until_settled(state, &id, 0, started).await?;
+    for text in &fixture.follow_ups {
+        if settled.0.status != RunStatus::Completed {
+            break;
+        }
+        let after = settled.1.last().map_or(0, |m| m.sequence_id);
+        send_follow_up(state, &id, text).await?;
+        settled = wait_until_settled(state, &id, after, started).await?;
+    }
+    Ok(settled)
+}
+
+/// Check a finished run against the fixture's expectations. `tests` is the
+/// outcome of `test_command`, when there is one.
+fn score(
+    expect: &Expectations,
+    report: &RunReport,
+    messages: &[Message],
+    tests: Option<(bool, String)>,
+) -> Vec<CheckResult> {
+    let wanted = expect.status.unwrap_or(RunStatus::Completed);
+    let mut checks = vec![CheckResult {
+        name: "status",
+        passed: report.status == wanted,
+        detail: format!(
+            "expected {}, got {}{}",
+            status_name(wanted),
+            status_name(report.status),
+            report
+                .error
+                .as_deref()
+                .map_or_else(String::new, |e| format!(": {e}"))
+        ),
+    }];
+
+    let patch_errors = patch_errors(messages);
+    checks.push(CheckResult {
+        name: "patches_apply",
+        passed: expect.allow_patch_errors || patch_errors.is_empty(),
+        detail: if patch_errors.is_empty() {
+            "every patch applied".to_string()
+        } else {
+            format!(
+                "{} patch call(s) failed: {}",
+                patch_errors.len(),
+                patch_errors.join("; ")
+            )
+        },
+    });
+
+    if let Some(max) = expect.max_turns {
+        checks.push(CheckResult {
+            name: "max_turns",
+            passed: report.turns <= max,
+            detail: format!("{} of at most {max} model requests", report.turns),
+        });
+    }
+
+    if let Some((passed, output)) = tests {
+        checks.push(CheckResult {
+            name: "tests",
+            passed,
+            detail: output,
+        });
+    }
+    checks
+}
+
+/// First line of each failed `patch` tool result.
+fn patch_errors(messages: &[Message]) -> Vec<String> {
+    let mut names: HashMap<&str, &str> = HashMap::new();
+    let mut errors = Vec::new();
+    for message in messages {
+        match &message.content {
+            MessageContent::Agent(blocks) => {
+                for block in blocks {
+                    if let ContentBlock::ToolUse { id, name, .. } = block {
+                        names.insert(id.as_str(), name.as_str());
+                    }
+                }
+            }
+            MessageContent::Tool(tool)
+                if tool.is_error && names.get(tool.tool_use_id.as_str()) == Some(&"patch") =>
+            {
+                errors.push(tool.content.lines().next().unwrap_or_default().to_string());
+            }
+            _ => {}
+        }
+    }
+    errors
+}
+
+/// Names that passed in `baseline` and fail now, and the reverse.
+fn compare(baseline: &Baseline, results: &[FixtureResult]) -> (Vec<String>, Vec<String>) {
+    let before: HashMap<&str, bool> = baseline
+        .fixtures
+        .iter()
+        .map(|f| (f.name.as_str(), f.passed))
+        .collect();
+    let mut regressions = Vec::new();
+    let mut fixed = Vec::new();
+    for result in results {
+        match before.get(result.name.as_str()) {
+            Some(true) if !result.passed => regressions.push(result.name.clone()),
+            Some(false) if result.passed => fixed.push(result.name.clone()),
+            _ => {}
+        }
+    }
+    (regressions, fixed)
+}
+
+fn status_name(status: RunStatus) -> String {
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|v| v.as_str().map(ToString::to_string))
+        .unwrap_or_default()
+}
+
+fn failure_summary(result: &FixtureResult) -> String {
+    if let Some(error) = &result.error {
+        return format!(": {}", error.lines().next().unwrap_or_default());
+    }
+    let failed: Vec<&str> = result
+        .checks
+        .iter()
+        .filter(|c| !c.passed)
+        .map(|c| c.name)
+        .collect();
+    if failed.is_empty() {
+        String::new()
+    } else {
+        format!(": failed {}", failed.join(", "))
+    }
+}
+
+/// Run `command` with `sh -c` in `dir`. Returns whether it exited 0, and
+/// the tail of its combined output.
+async fn run_command(command: &str, dir: &Path) -> (bool, String) {
+    let run = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    match tokio::time::timeout(COMMAND_TIMEOUT, run).await {
+        Ok(Ok(output)) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+            (output.status.success(), tail(&text))
+        }
+        Ok(Err(e)) => (false, format!("cannot run `{command}`: {e}")),
+        Err(_) => (false, format!("`{command}` timed out")),
+    }
+}
+
+fn tail(text: &str) -> String {
+    let text = text.trim_end();
+    let skip = text.chars().count().saturating_sub(OUTPUT_TAIL_CHARS);
+    text.chars().skip(skip).collect()
+}
+
+/// Copy `from` into a new directory `to`, keeping symlinks as symlinks.
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_symlink() {
+            std::os::unix::fs::symlink(std::fs::read_link(entry.path())?, &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_report(path: &Path, report: &EvalReport) -> std::io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_vec_pretty(report).map_err(std::io::Error::other)?;
+    std::fs::write(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{MessageType, ToolContent, UsageData};
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(ToString::to_string).collect()
+    }
+
+    fn message(seq: i64, content: MessageContent) -> Message {
+        Message {
+            message_id: format!("m{seq}"),
+            conversation_id: "c1".to_string(),
+            sequence_id: seq,
+            message_type: content.message_type(),
+            content,
+            display_data: None,
+            usage_data: None,
+            model: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    fn report(status: RunStatus, turns: u32) -> RunReport {
+        RunReport {
+            status,
+            conversation_id: "c1".to_string(),
+            slug: None,
+            cwd: "/work".to_string(),
+            final_response: String::new(),
+            error: None,
+            turns,
+            tool_calls: 0,
+            usage: UsageData::default(),
+            duration_ms: 0,
+        }
+    }
+
+    fn result(name: &str, passed: bool) -> FixtureResult {
+        FixtureResult {
+            name: name.to_string(),
+            passed,
+            error: None,
+            status: None,
+            conversation_id: None,
+            workspace: String::new(),
+            turns: 0,
+            tool_calls: 0,
+            duration_ms: 0,
+            checks: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parses_eval_arguments() {
+        let parsed = EvalArgs::parse(&args(&[
+            "eval",
+            "--fixtures",
+            "evals",
+            "--model=mock",
+            "--baseline",
+            "last.json",
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(parsed.fixtures, PathBuf::from("evals"));
+        assert_eq!(parsed.model.as_deref(), Some("mock"));
+        assert_eq!(parsed.baseline, Some(PathBuf::from("last.json")));
+
+        assert_eq!(EvalArgs::parse(&args(&["run", "--prompt", "x"])), Ok(None));
+        assert!(EvalArgs::parse(&args(&["eval"])).is_err());
+        assert!(EvalArgs::parse(&args(&["eval", "--fixtures", "d", "--max-turns", "0"])).is_err());
+    }
+
+    #[test]
+    fn loads_fixtures_in_name_order_with_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("b.json"), r#"{"prompt": "second"}"#).unwrap();
+        std::fs::write(
+            dir.path().join("a.json"),
+            r#"{"name": "first", "prompt": "x", "expect": {"status": "turn_limit"}}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a fixture").unwrap();
+
+        let fixtures = load_fixtures(dir.path()).unwrap();
+        let names: Vec<&str> = fixtures.iter().map(|(_, f)| f.name.as_str()).collect();
+        assert_eq!(names, ["first", "b"]);
+        assert_eq!(fixtures[0].1.expect.status, Some(RunStatus::TurnLimit));
+        assert_eq!(fixtures[0].0, dir.path());
+
+        std::fs::write(
+            dir.path().join("c.json"),
+            r#"{"prompt": "x", "tests": "make"}"#,
+        )
+        .unwrap();
+        assert!(
+            load_fixtures(dir.path()).is_err(),
+            "unknown fields are rejected"
+        );
+    }
+
+    #[test]
+    fn scores_status_turns_and_tests() {
+        let expect = Expectations {
+            max_turns: Some(3),
+            test_command: Some("make test".to_string()),
+            ..Expectations::default()
+        };
+        let checks = score(
+            &expect,
+            &report(RunStatus::Completed, 4),
+            &[],
+            Some((true, "ok".to_string())),
+        );
+        let failed: Vec<&str> = checks
+            .iter()
+            .filter(|c| !c.passed)
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(failed, ["max_turns"]);
+
+        let checks = score(
+            &Expectations::default(),
+            &report(RunStatus::Error, 1),
+            &[],
+            None,
+        );
+        assert!(!checks[0].passed);
+        assert_eq!(checks[0].detail, "expected completed, got error");
+        assert_eq!(checks.len(), 2);
+    }
+
+    #[test]
+    fn failed_patches_fail_the_fixture() {
+        let messages = vec![
+            message(
+                1,
+                MessageContent::agent(vec![
+                    ContentBlock::tool_use("t1", "patch", serde_json::json!({})),
+                    ContentBlock::tool_use("t2", "bash", serde_json::json!({})),
+                ]),
+            ),
+            message(
+                2,
+                MessageContent::Tool(ToolContent::new("t1", "old text not found\nmore", true)),
+            ),
+            message(
+                3,
+                MessageContent::Tool(ToolContent::new("t2", "exit 1", true)),
+            ),
+        ];
+        assert_eq!(messages[1].message_type, MessageType::Tool);
+        assert_eq!(patch_errors(&messages), ["old text not found"]);
+
+        let done = report(RunStatus::Completed, 1);
+        let checks = score(&Expectations::default(), &done, &messages, None);
+        assert!(!checks[1].passed);
+        let lenient = Expectations {
+            allow_patch_errors: true,
+            ..Expectations::default()
+        };
+        assert!(score(&lenient, &done, &messages, None)[1].passed);
+    }
+
+    #[test]
+    fn baseline_comparison_finds_regressions_and_fixes() {
+        let baseline: Baseline = serde_json::from_str(
+            r#"{"fixtures": [
+                {"name": "a", "passed": true, "turns": 2},
+                {"name": "b", "passed": false},
+                {"name": "c", "passed": true}
+            ]}"#,
+        )
+        .unwrap();
+        let results = [
+            result("a", false),
+            result("b", true),
+            result("c", true),
+            result("d", false),
+        ];
+        let (regressions, fixed) = compare(&baseline, &results);
+        assert_eq!(regressions, ["a"]);
+        assert_eq!(fixed, ["b"]);
+    }
+
+    #[test]
+    fn copies_workspace_tree() {
+        let src = tempfile::tempdir().unwrap();
+        std::fs::create_dir(src.path().join("src")).unwrap();
+        std::fs::write(src.path().join("src/lib.rs"), "fn main() {}").unwrap();
+        std::os::unix::fs::symlink("src/lib.rs", src.path().join("link.rs")).unwrap();
+
+        let dst = tempfile::tempdir().unwrap();
+        let target = dst.path().join("ws");
+        copy_dir(src.path(), &target).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(target.join("src/lib.rs")).unwrap(),
+            "fn main() {}"
+        );
+        assert!(target
+            .join("link.rs")
+            .symlink_metadata()
+            .unwrap()
+            .is_symlink());
+    }
+}
@@ -0,0 +1,240 @@
+//! Large files read a chunk at a time (REQ-FE-012).
+//!
+//! A file too big to hand over whole is described by its chunks: line
+//! ranges of roughly even size, each listing the symbols that start in it.
+//! When tree-sitter knows the language (the grammars of REQ-PATCH-011), a
+//! chunk starts where an item starts, descending into items too big for one
+//! chunk, such as an `impl` or a class. Other text breaks after blank lines.
+//! Either way a chunk without a good break falls back to a line boundary.
+
+use crate::tools::patch::syntax::language_for;
+use serde::Serialize;
+use std::ops::Range;
+use std::path::Path;
+use tree_sitter::{Node, Parser};
+
+/// Largest file parsed for symbols. Bigger ones break after blank lines.
+const MAX_PARSE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Most symbols listed for one chunk.
+const MAX_SYMBOLS: usize = 8;
+
+/// A chunk of a file. `index` and lines are 1-based; `end_line` is included.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileChunk {
+    pub index: usize,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub symbols: Vec<String>,
+}
+
+/// Split `text` into chunks of about `target_bytes` each. `path` picks the
+/// grammar by extension.
+pub fn chunks(path: &Path, text: &str, target_bytes: usize) -> Vec<FileChunk> {
+    let mut offsets = vec![0];
+    offsets.extend(text.split_inclusive('\n').scan(0, |end, line| {
+        *end += line.len();
+        Some(*end)
+    }));
+    let line_count = offsets.len() - 1;
+
+    // Lines a chunk may start on, and the symbols that start on each line
+    let mut breaks = vec![false; line_count];
+    let mut symbols = Vec::new();
+    match items(path, text, target_bytes) {
+        Some(items) => {
+            for (row, name) in items {
+                // An error node can start at the very end of the file
+                if let Some(starts_item) = breaks.get_mut(row) {
+                    *starts_item = true;
+                }
+                symbols.extend(name.map(|name| (row, name)));
+            }
+        }
+        None => {
+            for (row, starts_item) in breaks.iter_mut().enumerate().skip(1) {
+                *starts_item = text
+                    .get(offsets[row - 1]..offsets[row])
+                    .is_some_and(|line| line.trim().is_empty());
+            }
+        }
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut last_break = None;
+    for end in 1..=line_count {
+        if offsets[end] - offsets[start] > target_bytes && end - 1 > start {
+            // Break at the last item start, unless it leaves a sliver
+            let cut = last_break
+                .filter(|&row| offsets[row] - offsets[start] >= target_bytes / 4)
+                .unwrap_or(end - 1);
+            ranges.push((start, cut));
+            start = cut;
+            last_break = None;
+        }
+        if end < line_count && breaks[end] {
+            last_break = Some(end);
+        }
+    }
+    if start < line_count {
+        ranges.push((start, line_count));
+    }
+
+    ranges
+        .into_iter()
+        .enumerate()
+        .map(|(i, (start, end))| {
+            let mut names: Vec<String> = symbols
+                .iter()
+                .filter(|(row, _)| (start..end).contains(row))
+                .map(|(_, name)| name.clone())
+                .collect();
+            if names.len() > MAX_SYMBOLS {
+                let more = names.len() - MAX_SYMBOLS;
+                names.truncate(MAX_SYMBOLS);
+                names.push(format!("{more} more"));
+            }
+            FileChunk {
+                index: i + 1,
+                start_line: start + 1,
+                end_line: end,
+                symbols: names,
+            }
+        })
+        .collect()
+}
+
+/// The byte range of `chunk` in `text`.
+pub fn chunk_range(text: &str, chunk: &FileChunk) -> Range<usize> {
+    let mut lines = text.split_inclusive('\n');
+    let skipped: usize = lines
+        .by_ref()
+        .take(chunk.start_line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let taken: usize = lines
+        .take((chunk.end_line + 1).saturating_sub(chunk.start_line))
+        .map(str::len)
+        .sum();
+    skipped..skipped + taken
+}
+
+/// Start rows of the items in `text`, with a name for those that are
+/// definitions. `None` when the language is unknown or the file too big to
+/// parse.
+fn items(path: &Path, text: &str, target_bytes: usize) -> Option<Vec<(usize, Option<String>)>> {
+    if text.len() > MAX_PARSE_BYTES {
+        return None;
+    }
+    let language = language_for(path)?;
+    let mut parser = Parser::new();
+    parser.set_language(&language).ok()?;
+    let tree = parser.parse(text, None)?;
+    let mut items = Vec::new();
+    collect_items(tree.root_node(), text.as_bytes(), target_bytes, &mut items);
+    Some(items)
+}
+
+/// Add `node`'s children to `out`, and the children of any child too big to
+/// fit a chunk.
+fn collect_items(
+    node: Node<'_>,
+    source: &[u8],
+    target_bytes: usize,
+    out: &mut Vec<(usize, Option<String>)>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        out.push((child.start_position().row, symbol_name(child, source)));
+        if child.byte_range().len() > target_bytes {
+            collect_items(child, source, target_bytes, out);
+        }
+    }
+}
+
+/// `function parse`, `struct Config`, `impl Parser`, `class Reader`: what
+/// kind of definition a node is and its name.
+fn symbol_name(node: Node<'_>, source: &[u8]) -> Option<String> {
+    let name = node.child_by_field_name("name").or_else(|| {
+        node.child_by_field_name("type")
+            .filter(|_| node.kind() == "impl_item")
+    })?;
+    let name = name.utf8_text(source).ok()?;
+    let kind = ["_item", "_definition", "_declaration", "_specifier"]
+        .iter()
+        .fold(node.kind(), |kind, suffix| kind.trim_end_matches(suffix));
+    Some(format!("{} {name}", kind.replace('_', " ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    fn chunk_text<'a>(text: &'a str, chunk: &FileChunk) -> &'a str {
+        text.get(chunk_range(text, chunk)).unwrap_or("")
+    }
+
+    fn rust_source(functions: usize) -> String {
+        (0..functions).fold(String::new(), |mut s, i| {
+            let _ = write!(s, "fn f{i}() {{\n    let x = {i};\n    x + 1;\n}}\n\n");
+            s
+        })
+    }
+
+    #[test]
+    fn rust_chunks_start_at_functions() {
+        let source = rust_source(40);
+        let chunks = chunks(Path::new("lib.rs"), &source, 400);
+
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks[0].start_line, 1);
+        assert_eq!(chunks.last().unwrap().end_line, source.lines().count());
+        for pair in chunks.windows(2) {
+            assert_eq!(pair[1].start_line, pair[0].end_line + 1);
+            let first_line = source.lines().nth(pair[1].start_line - 1).unwrap();
+            assert!(first_line.starts_with("fn f"), "{first_line}");
+        }
+        assert_eq!(chunks[0].symbols[0], "function f0");
+        assert!(chunks.iter().all(|c| chunk_text(&source, c).len() <= 400));
+    }
+
+    #[test]
+    fn big_items_are_split_inside() {
+        let body = rust_source(30).replace("fn ", "    fn ");
+        let source = format!("struct Parser;\n\nimpl Parser {{\n{body}}}\n");
+        let chunks = chunks(Path::new("parser.rs"), &source, 300);
+
+        assert!(chunks.len() > 2);
+        let names: Vec<&str> = chunks
+            .iter()
+            .flat_map(|c| c.symbols.iter().map(String::as_str))
+            .collect();
+        assert!(names.contains(&"struct Parser"));
+        assert!(names.contains(&"impl Parser"));
+        assert!(names.contains(&"function f29"));
+    }
+
+    #[test]
+    fn plain_text_breaks_after_blank_lines() {
+        let source = "para one\nstill one\n\npara two\nstill two\n\npara three\n";
+        let chunks = chunks(Path::new("notes.txt"), source, 25);
+
+        let starts: Vec<usize> = chunks.iter().map(|c| c.start_line).collect();
+        assert_eq!(starts, [1, 4, 7]);
+        assert!(chunks.iter().all(|c| c.symbols.is_empty()));
+        assert_eq!(chunk_text(source, &chunks[1]), "para two\nstill two\n\n");
+    }
+
+    #[test]
+    fn long_lines_without_breaks_still_split() {
+        let source = "x".repeat(50) + "\n" + &"y".repeat(50) + "\n" + &"z".repeat(50);
+        let chunks = chunks(Path::new("data.bin"), &source, 60);
+
+        let ranges: Vec<(usize, usize)> =
+            chunks.iter().map(|c| (c.start_line, c.end_line)).collect();
+        assert_eq!(ranges, [(1, 1), (2, 2), (3, 3)]);
+        assert_eq!(chunk_text(&source, &chunks[2]), "z".repeat(50));
+    }
+}
@@ -55,31 +55,44 @@
 //! occur, aiding diagnosis.
 
 mod anthropic;
+mod batch;
+mod cassette;
 pub mod codex_credential;
 pub mod credential_helper;
 mod discovery;
 mod error;
+pub mod images;
 mod mock;
 mod models;
 mod openai;
+pub mod preflight;
 #[cfg(test)]
 mod proptests;
 mod registry;
+mod scripted;
 mod service;
 pub(crate) mod sse;
+mod structured;
+mod traffic_log;
+pub mod transport;
 mod types;
 
+pub use batch::BatchGroup;
+pub use cassette::{CassetteMode, LlmCassette};
 pub use codex_credential::{CodexCredential, CODEX_BACKEND_URL};
 pub use credential_helper::{CredentialHelper, CredentialStatus};
-pub use discovery::{discover_models, probe_gateway, DiscoveryConfig};
+pub use discovery::{check_api_key, discover_models, probe_gateway, DiscoveryConfig};
 pub use error::{LlmError, LlmErrorKind};
-pub use models::{all_models, ModelSpec, Provider};
+pub use models::{all_models, model_pricing, ModelSpec, Provider};
 #[allow(unused_imports)]
 // CredentialSource + ResolvedAuth + AuthStyle: public API for downstream consumers
 pub use registry::{
     AuthStyle, CredentialSource, GatewayStatus, LlmAuth, LlmConfig, ModelRegistry, ResolvedAuth,
 };
+pub use scripted::ScriptedLlmClient;
 pub use service::LlmServiceImpl;
+pub use structured::complete_json;
+pub use traffic_log::LlmTrafficLog;
 pub use types::*;
 
 use async_trait::async_trait;
@@ -112,6 +125,21 @@ pub trait LlmService: Send + Sync {
         self.complete(request).await
     }
 
+    /// Submit several independent requests through the provider's batch API
+    /// (REQ-SA-009), returning one result per request in input order. Batch
+    /// requests trade latency for cost; the default reports them unsupported
+    /// so callers fall back to interactive requests.
+    async fn complete_batch(
+        &self,
+        requests: &[LlmRequest],
+    ) -> Result<Vec<Result<LlmResponse, LlmError>>, LlmError> {
+        let _ = requests;
+        Err(LlmError::invalid_request(format!(
+            "Model {} does not support batch requests",
+            self.model_id()
+        )))
+    }
+
     /// Get the model ID
     fn model_id(&self) -> &str;
 }
@@ -193,6 +221,31 @@ impl LlmService for LoggingService {
         result
     }
 
+    async fn complete_batch(
+        &self,
+        requests: &[LlmRequest],
+    ) -> Result<Vec<Result<LlmResponse, LlmError>>, LlmError> {
+        let start = std::time::Instant::now();
+        let result = self.inner.complete_batch(requests).await;
+        let duration_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+        match &result {
+            Ok(results) => tracing::info!(
+                model = %self.model_id,
+                duration_ms,
+                requests = requests.len(),
+                succeeded = results.iter().filter(|r| r.is_ok()).count(),
+                "LLM batch completed"
+            ),
+            Err(e) => tracing::warn!(
+                model = %self.model_id,
+                duration_ms,
+                error = %e.message,
+                "LLM batch failed"
+            ),
+        }
+        result
+    }
+
     fn model_id(&self) -> &str {
         &self.model_id
     }
@@ -1,17 +1,18 @@
 //! Anthropic Claude provider implementation
 
 use super::models::ModelSpec;
+use super::transport::client_builder;
 use super::types::{
     ContentBlock, ImageSource, LlmMessage, LlmRequest, LlmResponse, MessageRole, Usage,
     LLM_SOURCE_HEADER,
 };
 use super::LlmError;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::time::Duration;
 
 /// Accumulates state across Anthropic SSE stream events to assemble the final response.
+#[allow(clippy::struct_excessive_bools)] // per-block parser flags
 struct StreamAccumulator {
     input_tokens: u64,
     output_tokens: u64,
@@ -26,6 +27,10 @@ struct StreamAccumulator {
     current_tool_id: String,
     current_tool_name: String,
     current_tool_json: String,
+    /// True while a `thinking` block is open; its text is not streamed to the UI.
+    current_is_thinking: bool,
+    current_thinking: String,
+    current_signature: String,
     /// True when the current tool block is a `server_tool_use` (not a regular `tool_use`).
     current_is_server_tool: bool,
     /// Raw JSON for server-handled blocks that arrive complete in `content_block_start`.
@@ -49,6 +54,9 @@ impl StreamAccumulator {
             current_tool_id: String::new(),
             current_tool_name: String::new(),
             current_tool_json: String::new(),
+            current_is_thinking: false,
+            current_thinking: String::new(),
+            current_signature: String::new(),
             current_is_server_tool: false,
             current_server_block: None,
             done: false,
@@ -119,6 +127,13 @@ impl StreamAccumulator {
                 self.current_is_text = true;
                 self.current_text.clear();
             }
+            "thinking" => {
+                self.current_index = Some(idx);
+                self.current_is_text = false;
+                self.current_is_thinking = true;
+                self.current_thinking.clear();
+                self.current_signature.clear();
+            }
             "tool_use" => {
                 self.current_index = Some(idx);
                 self.current_is_text = false;
@@ -160,7 +175,8 @@ impl StreamAccumulator {
             | "code_execution_tool_result"
             | "bash_code_execution_tool_result"
             | "text_editor_code_execution_tool_result"
-            | "mcp_tool_result" => {
+            | "mcp_tool_result"
+            | "redacted_thinking" => {
                 // Server result blocks arrive complete -- capture the whole block.
                 if let Some(block) = v.get("content_block") {
                     self.current_index = Some(idx);
@@ -210,6 +226,22 @@ impl StreamAccumulator {
                     self.current_tool_json.push_str(partial);
                 }
             }
+            "thinking_delta" => {
+                if let Some(text) = v
+                    .pointer("/delta/thinking")
+                    .and_then(serde_json::Value::as_str)
+                {
+                    self.current_thinking.push_str(text);
+                }
+            }
+            "signature_delta" => {
+                if let Some(sig) = v
+                    .pointer("/delta/signature")
+                    .and_then(serde_json::Value::as_str)
+                {
+                    self.current_signature.push_str(sig);
+                }
+            }
             _ => {}
         }
     }
@@ -236,7 +268,16 @@ impl StreamAccumulator {
             }
             return;
         }
-        if self.current_is_text {
+        if self.current_is_thinking {
+            self.content_blocks.push((
+                idx,
+                AnthropicContentBlock::Thinking {
+                    thinking: std::mem::take(&mut self.current_thinking),
+                    signature: std::mem::take(&mut self.current_signature),
+                },
+            ));
+            self.current_is_thinking = false;
+        } else if self.current_is_text {
             if !self.current_text.is_empty() {
                 self.content_blocks.push((
                     idx,
@@ -330,7 +371,7 @@ pub async fn complete_streaming(
     use futures::StreamExt;
 
     let base_url = resolve_anthropic_url(gateway, base_url_override);
-    let client = Client::builder()
+    let client = client_builder()
         .timeout(Duration::from_mins(10))
         .build()
         .map_err(|e| LlmError::network(format!("Failed to create HTTP client: {e}")))?;
@@ -430,7 +471,7 @@ pub async fn complete(
 ) -> Result<LlmResponse, LlmError> {
     let base_url = resolve_anthropic_url(gateway, base_url_override);
 
-    let client = Client::builder()
+    let client = client_builder()
         .timeout(Duration::from_mins(5))
         .build()
         .map_err(|e| LlmError::network(format!("Failed to create HTTP client: {e}")))?;
@@ -489,6 +530,181 @@ pub async fn complete(
     normalize_response(anthropic_response)
 }
 
+/// How often a submitted message batch is polled (REQ-SA-009).
+const BATCH_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Batch metadata returned on create and on each poll.
+#[derive(Debug, Deserialize)]
+struct MessageBatch {
+    id: String,
+    processing_status: String,
+    results_url: Option<String>,
+}
+
+/// One line of a batch's JSONL results file.
+#[derive(Debug, Deserialize)]
+struct BatchResultLine {
+    custom_id: String,
+    result: BatchResult,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BatchResult {
+    Succeeded { message: AnthropicResponse },
+    Errored { error: serde_json::Value },
+    Canceled,
+    Expired,
+}
+
+/// Run `requests` through the Message Batches API and wait for their
+/// results, returned in request order (REQ-SA-009). Batches are billed at
+/// half price but can take minutes; a batch still running after `deadline`
+/// is cancelled and reported as a network error so callers fall back to
+/// interactive requests.
+pub async fn complete_batch(
+    spec: &ModelSpec,
+    auth: &super::ResolvedAuth,
+    gateway: Option<&str>,
+    base_url_override: Option<&str>,
+    custom_headers: &[(String, String)],
+    requests: &[LlmRequest],
+    deadline: Duration,
+) -> Result<Vec<Result<LlmResponse, LlmError>>, LlmError> {
+    let batches_url = format!(
+        "{}/batches",
+        resolve_anthropic_url(gateway, base_url_override)
+    );
+    let client = client_builder()
+        .timeout(Duration::from_mins(2))
+        .build()
+        .map_err(|e| LlmError::network(format!("Failed to create HTTP client: {e}")))?;
+    let has_deferred = spec.supports_tool_search
+        && requests
+            .iter()
+            .any(|r| r.tools.iter().any(|t| t.defer_loading));
+    let with_headers = |mut builder: reqwest::RequestBuilder| {
+        builder = match auth.style {
+            super::AuthStyle::ApiKey => builder.header("x-api-key", &auth.credential),
+            super::AuthStyle::PlainBearer => {
+                builder.header("Authorization", format!("Bearer {}", auth.credential))
+            }
+        };
+        if has_deferred {
+            builder = builder.header("anthropic-beta", "advanced-tool-use-2025-11-20");
+        }
+        if spec.context_window >= 1_000_000 {
+            builder = builder.header("anthropic-beta", "context-1m-2025-08-07");
+        }
+        builder = builder
+            .header("anthropic-version", "2023-06-01")
+            .header("source", LLM_SOURCE_HEADER);
+        for (k, v) in custom_headers {
+            builder = builder.header(k.as_str(), v.as_str());
+        }
+        builder
+    };
+
+    let body = serde_json::json!({
+        "requests": requests
+            .iter()
+            .enumerate()
+            .map(|(i, request)| serde_json::json!({
+                "custom_id": format!("req-{i}"),
+                "params": translate_request(spec, request),
+            }))
+            .collect::<Vec<_>>(),
+    });
+    let created = send_batch_call(with_headers(client.post(&batches_url)).json(&body)).await?;
+    let mut batch: MessageBatch = parse_batch_json(&created)?;
+    tracing::info!(batch_id = %batch.id, requests = requests.len(), "Submitted message batch");
+
+    let started = std::time::Instant::now();
+    while batch.processing_status != "ended" {
+        if started.elapsed() >= deadline {
+            let cancel_url = format!("{batches_url}/{}/cancel", batch.id);
+            let _ = with_headers(client.post(cancel_url)).send().await;
+            return Err(LlmError::network(format!(
+                "Message batch {} did not finish within {}s",
+                batch.id,
+                deadline.as_secs()
+            )));
+        }
+        tokio::time::sleep(BATCH_POLL_INTERVAL).await;
+        let polled = send_batch_call(with_headers(
+            client.get(format!("{batches_url}/{}", batch.id)),
+        ))
+        .await?;
+        batch = parse_batch_json(&polled)?;
+    }
+
+    let results_url = batch.results_url.ok_or_else(|| {
+        LlmError::invalid_response(format!("Message batch {} ended without results", batch.id))
+    })?;
+    let results = send_batch_call(with_headers(client.get(results_url))).await?;
+    collect_batch_results(&results, requests.len())
+}
+
+async fn send_batch_call(builder: reqwest::RequestBuilder) -> Result<String, LlmError> {
+    let response = builder
+        .send()
+        .await
+        .map_err(|e| LlmError::network(format!("Batch request failed: {e}")))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| LlmError::network(format!("Failed to read batch response: {e}")))?;
+    if !status.is_success() {
+        return Err(LlmError::from_http_status(status.as_u16(), &body));
+    }
+    Ok(body)
+}
+
+fn parse_batch_json(body: &str) -> Result<MessageBatch, LlmError> {
+    serde_json::from_str(body).map_err(|e| {
+        LlmError::invalid_response(format!("Failed to parse batch: {e} - body: {body}"))
+    })
+}
+
+/// Match a JSONL results file back to request order. Requests missing from
+/// the file, or that errored, expired, or were cancelled, get an error each.
+fn collect_batch_results(
+    jsonl: &str,
+    count: usize,
+) -> Result<Vec<Result<LlmResponse, LlmError>>, LlmError> {
+    let mut results: Vec<Result<LlmResponse, LlmError>> = (0..count)
+        .map(|_| {
+            Err(LlmError::invalid_response(
+                "Request missing from batch results",
+            ))
+        })
+        .collect();
+    for line in jsonl.lines().filter(|l| !l.trim().is_empty()) {
+        let line: BatchResultLine = serde_json::from_str(line).map_err(|e| {
+            LlmError::invalid_response(format!("Failed to parse batch result: {e}"))
+        })?;
+        let Some(slot) = line
+            .custom_id
+            .strip_prefix("req-")
+            .and_then(|i| i.parse::<usize>().ok())
+            .and_then(|i| results.get_mut(i))
+        else {
+            continue;
+        };
+        *slot = match line.result {
+            BatchResult::Succeeded { message } => normalize_response(message),
+            BatchResult::Errored { error } => Err(LlmError::invalid_request(format!(
+                "Batch request failed: {error}"
+            ))),
+            BatchResult::Canceled | BatchResult::Expired => {
+                Err(LlmError::network("Batch request was cancelled or expired"))
+            }
+        };
+    }
+    Ok(results)
+}
+
 fn translate_request(spec: &super::ModelSpec, request: &LlmRequest) -> AnthropicRequest {
     let system: Vec<AnthropicSystemBlock> = request
         .system
@@ -506,7 +722,15 @@ fn translate_request(spec: &super::ModelSpec, request: &LlmRequest) -> Anthropic
         })
         .collect();
 
-    let messages: Vec<AnthropicMessage> = request.messages.iter().map(translate_message).collect();
+    let mut messages: Vec<AnthropicMessage> =
+        request.messages.iter().map(translate_message).collect();
+    let thinking = match request.thinking_budget {
+        Some(budget) if thinking_allowed(&mut messages) => Some(budget),
+        _ => {
+            strip_thinking(&mut messages);
+            None
+        }
+    };
 
     let has_deferred = spec.supports_tool_search && request.tools.iter().any(|t| t.defer_loading);
 
@@ -544,33 +768,105 @@ fn translate_request(spec: &super::ModelSpec, request: &LlmRequest) -> Anthropic
         }));
     }
 
-    // Set explicit cache breakpoint on last content block of last user message.
-    // Combined with system-prompt and last-tool breakpoints, this gives Anthropic
-    // three deterministic cache anchor points per request.
-    let mut messages = messages;
-    if let Some(last_user) = messages.iter_mut().rev().find(|m| m.role == "user") {
-        if let Some(
-            AnthropicContentBlock::Text { cache_control, .. }
-            | AnthropicContentBlock::Image { cache_control, .. },
-        ) = last_user.content.last_mut()
-        {
-            *cache_control = Some(CacheControl {
-                r#type: "ephemeral".to_string(),
-            });
+    // Spend the remaining breakpoints on history: the latest user message
+    // (written this turn, read next turn) and the one before it (written last
+    // turn, read now). The older anchor keeps the history prefix hitting even
+    // when a turn appends more blocks than Anthropic's 20-block lookback.
+    let cached_system = request.system.iter().filter(|s| s.cache).count();
+    let used = cached_system + usize::from(tool_count > 0 && !has_deferred);
+    let mut remaining = MAX_CACHE_BREAKPOINTS
+        .saturating_sub(used)
+        .min(HISTORY_CACHE_BREAKPOINTS);
+    for message in messages.iter_mut().rev().filter(|m| m.role == "user") {
+        if remaining == 0 {
+            break;
+        }
+        if mark_cache_breakpoint(message) {
+            remaining -= 1;
         }
     }
 
+    let max_tokens = request.max_tokens.unwrap_or(16_384);
     AnthropicRequest {
         model: spec.api_name.clone(),
-        max_tokens: request.max_tokens.unwrap_or(16_384),
+        // The budget counts against max_tokens, so make room for an answer,
+        // but never ask for more than the model can produce (REQ-BED-041).
+        max_tokens: thinking
+            .map_or(max_tokens, |budget| {
+                max_tokens.max(budget + THINKING_ANSWER_HEADROOM)
+            })
+            .min(spec.max_output_tokens),
         system,
         messages,
         tools: if tools.is_empty() { None } else { Some(tools) },
+        thinking: thinking.map(|budget_tokens| AnthropicThinking {
+            r#type: "enabled".to_string(),
+            budget_tokens,
+        }),
         stream: None,
         tags: None,
     }
 }
 
+/// Whether thinking can be enabled for a request carrying `messages`,
+/// after dropping thinking blocks blanked by redaction at rest, whose
+/// signatures no longer verify (REQ-LLM-014).
+///
+/// While a tool loop is in flight `Anthropic` requires the latest assistant
+/// message to open with its thinking. When that block is missing (thinking
+/// was just switched on, or it was blanked) the call runs without thinking.
+fn thinking_allowed(messages: &mut Vec<AnthropicMessage>) -> bool {
+    for message in messages.iter_mut() {
+        message.content.retain(|b| match b {
+            AnthropicContentBlock::Thinking { thinking, .. } => !thinking.is_empty(),
+            _ => true,
+        });
+    }
+    messages.retain(|m| !m.content.is_empty());
+    let Some(last) = messages.iter().rev().find(|m| m.role == "assistant") else {
+        return true;
+    };
+    let in_tool_loop = last
+        .content
+        .iter()
+        .any(|b| matches!(b, AnthropicContentBlock::ToolUse { .. }));
+    !in_tool_loop || last.content.first().is_some_and(is_thinking)
+}
+
+/// Remove thinking from history for a request sent without it. Messages
+/// left with no content are dropped.
+fn strip_thinking(messages: &mut Vec<AnthropicMessage>) {
+    for message in messages.iter_mut() {
+        message.content.retain(|b| !is_thinking(b));
+    }
+    messages.retain(|m| !m.content.is_empty());
+}
+
+fn is_thinking(block: &AnthropicContentBlock) -> bool {
+    matches!(
+        block,
+        AnthropicContentBlock::Thinking { .. } | AnthropicContentBlock::RedactedThinking { .. }
+    )
+}
+
+/// Put a cache breakpoint on the last block of `message`, if that block type
+/// accepts one. Returns whether a breakpoint was placed.
+fn mark_cache_breakpoint(message: &mut AnthropicMessage) -> bool {
+    match message.content.last_mut() {
+        Some(
+            AnthropicContentBlock::Text { cache_control, .. }
+            | AnthropicContentBlock::Image { cache_control, .. }
+            | AnthropicContentBlock::ToolResult { cache_control, .. },
+        ) => {
+            *cache_control = Some(CacheControl {
+                r#type: "ephemeral".to_string(),
+            });
+            true
+        }
+        _ => false,
+    }
+}
+
 #[allow(clippy::too_many_lines)] // single-pass per-variant mapping; splitting would add indirection without clarity
 pub(crate) fn translate_message(msg: &LlmMessage) -> AnthropicMessage {
     let role = match msg.role {
@@ -629,8 +925,19 @@ pub(crate) fn translate_message(msg: &LlmMessage) -> AnthropicMessage {
                     tool_use_id: tool_use_id.clone(),
                     content: wire_content,
                     is_error: *is_error,
+                    cache_control: None,
                 }
             }
+            ContentBlock::Thinking {
+                thinking,
+                signature,
+            } => AnthropicContentBlock::Thinking {
+                thinking: thinking.clone(),
+                signature: signature.clone(),
+            },
+            ContentBlock::RedactedThinking { data } => {
+                AnthropicContentBlock::RedactedThinking { data: data.clone() }
+            }
             // Server-handled blocks: round-trip back to their Anthropic wire types.
             ContentBlock::ServerToolUse { id, name, input } => {
                 AnthropicContentBlock::ServerToolUse {
@@ -742,6 +1049,19 @@ fn normalize_response_with_diagnostics(
                     "Unexpected tool_result block in Anthropic response",
                 ));
             }
+            // Thinking is kept so it can be sent back during a tool loop.
+            AnthropicContentBlock::Thinking {
+                thinking,
+                signature,
+            } => {
+                content.push(ContentBlock::Thinking {
+                    thinking,
+                    signature,
+                });
+            }
+            AnthropicContentBlock::RedactedThinking { data } => {
+                content.push(ContentBlock::RedactedThinking { data });
+            }
             // Server-handled blocks: preserved in history for multi-turn correctness.
             AnthropicContentBlock::ServerToolUse { id, name, input } => {
                 tracing::debug!(name = %name, "Server tool use in response");
@@ -902,6 +1222,7 @@ fn normalize_response_with_diagnostics(
             output_tokens: resp.usage.output_tokens,
             cache_creation_tokens: resp.usage.cache_creation_input_tokens.unwrap_or(0),
             cache_read_tokens: resp.usage.cache_read_input_tokens.unwrap_or(0),
+            model: None,
         },
     })
 }
@@ -917,6 +1238,8 @@ struct AnthropicRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<AnthropicToolEntry>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<AnthropicThinking>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     stream: Option<bool>,
     /// Free-form metadata forwarded to the gateway/proxy in front of the
     /// model. Phoenix does not interpret these — they're a pass-through
@@ -928,6 +1251,12 @@ struct AnthropicRequest {
     tags: Option<BTreeMap<String, String>>,
 }
 
+#[derive(Debug, Serialize)]
+struct AnthropicThinking {
+    r#type: String,
+    budget_tokens: u32,
+}
+
 #[derive(Debug, Serialize)]
 struct AnthropicSystemBlock {
     r#type: String,
@@ -971,6 +1300,15 @@ pub(crate) enum AnthropicContentBlock {
         content: serde_json::Value,
         #[serde(default)]
         is_error: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cache_control: Option<CacheControl>,
+    },
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    RedactedThinking {
+        data: String,
     },
     /// Server-side tool invocation (tool search, web search, code execution).
     /// Handled by Anthropic -- Phoenix preserves these for multi-turn history.
@@ -1028,6 +1366,15 @@ pub(crate) struct AnthropicImageSource {
     pub(crate) data: String,
 }
 
+/// Anthropic rejects requests with more `cache_control` blocks than this.
+const MAX_CACHE_BREAKPOINTS: usize = 4;
+
+/// Breakpoints placed in message history, newest user messages first.
+const HISTORY_CACHE_BREAKPOINTS: usize = 2;
+
+/// Output tokens kept for the answer on top of the thinking budget.
+const THINKING_ANSWER_HEADROOM: u32 = 4_096;
+
 const TOOL_SEARCH_VARIANT: &str = "tool_search_tool_regex_20251119";
 const TOOL_SEARCH_NAME: &str = "tool_search_tool_regex";
 
@@ -1087,6 +1434,9 @@ mod tests {
             context_window: 200_000,
             recommended: false,
             supports_tool_search,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 64_000,
         }
     }
 
@@ -1109,6 +1459,7 @@ mod tests {
                 },
             ],
             max_tokens: None,
+            thinking_budget: None,
             cache_key: PromptCacheKey::ephemeral(),
         }
     }
@@ -1178,6 +1529,195 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cache_breakpoints_cover_prefix_and_recent_history() {
+        use crate::llm::types::{ContentBlock, LlmMessage, MessageRole, SystemContent};
+
+        let tool_round = |id: &str| {
+            [
+                LlmMessage {
+                    role: MessageRole::Assistant,
+                    content: vec![ContentBlock::tool_use(id, "bash", serde_json::json!({}))],
+                },
+                LlmMessage {
+                    role: MessageRole::User,
+                    content: vec![ContentBlock::ToolResult {
+                        tool_use_id: id.to_string(),
+                        content: "ok".to_string(),
+                        images: vec![],
+                        is_error: false,
+                    }],
+                },
+            ]
+        };
+        let mut request = test_request_with_tools();
+        request.tools.truncate(1);
+        request.system = vec![SystemContent::cached("You are a test.")];
+        request.messages = vec![LlmMessage {
+            role: MessageRole::User,
+            content: vec![ContentBlock::text("start")],
+        }];
+        request.messages.extend(tool_round("t1"));
+        request.messages.extend(tool_round("t2"));
+
+        let json = serde_json::to_value(translate_request(&test_spec(false), &request)).unwrap();
+        let marked = |v: &serde_json::Value| v.get("cache_control").is_some();
+
+        assert!(marked(&json["system"][0]));
+        assert!(marked(&json["tools"][0]));
+        let messages = json["messages"].as_array().unwrap();
+        let history: Vec<bool> = messages.iter().map(|m| marked(&m["content"][0])).collect();
+        // Only the two newest user messages (both tool results) are anchors.
+        assert_eq!(history, [false, false, true, false, true]);
+    }
+
+    #[test]
+    fn test_thinking_budget_enables_thinking_and_raises_max_tokens() {
+        use crate::llm::types::{ContentBlock, LlmMessage, MessageRole};
+
+        let mut request = test_request_with_tools();
+        request.max_tokens = Some(2_000);
+        request.thinking_budget = Some(8_000);
+        request.messages = vec![LlmMessage {
+            role: MessageRole::User,
+            content: vec![ContentBlock::text("think hard")],
+        }];
+
+        let json = serde_json::to_value(translate_request(&test_spec(false), &request)).unwrap();
+        assert_eq!(json["thinking"]["type"], "enabled");
+        assert_eq!(json["thinking"]["budget_tokens"], 8_000);
+        assert_eq!(json["max_tokens"], 8_000 + THINKING_ANSWER_HEADROOM);
+
+        request.thinking_budget = None;
+        let json = serde_json::to_value(translate_request(&test_spec(false), &request)).unwrap();
+        assert!(json.get("thinking").is_none());
+        assert_eq!(json["max_tokens"], 2_000);
+    }
+
+    #[test]
+    fn test_max_tokens_clamped_to_model_maximum() {
+        let mut request = test_request_with_tools();
+        request.max_tokens = Some(500_000);
+        let json = serde_json::to_value(translate_request(&test_spec(false), &request)).unwrap();
+        assert_eq!(json["max_tokens"], 64_000);
+    }
+
+    #[test]
+    fn test_thinking_disabled_mid_tool_loop_without_leading_thinking() {
+        use crate::llm::types::{ContentBlock, LlmMessage, MessageRole};
+
+        let thinking = |text: &str| ContentBlock::Thinking {
+            thinking: text.to_string(),
+            signature: "sig".to_string(),
+        };
+        let tool_use = ContentBlock::tool_use("t1", "bash", serde_json::json!({}));
+        let tool_loop = |first: ContentBlock| {
+            vec![
+                LlmMessage {
+                    role: MessageRole::User,
+                    content: vec![ContentBlock::text("go")],
+                },
+                LlmMessage {
+                    role: MessageRole::Assistant,
+                    content: vec![first, tool_use.clone()],
+                },
+                LlmMessage {
+                    role: MessageRole::User,
+                    content: vec![ContentBlock::ToolResult {
+                        tool_use_id: "t1".to_string(),
+                        content: "ok".to_string(),
+                        images: vec![],
+                        is_error: false,
+                    }],
+                },
+            ]
+        };
+        let mut request = test_request_with_tools();
+        request.thinking_budget = Some(4_000);
+
+        // Intact thinking: sent back and thinking stays on.
+        request.messages = tool_loop(thinking("plan"));
+        let json = serde_json::to_value(translate_request(&test_spec(false), &request)).unwrap();
+        assert!(json.get("thinking").is_some());
+        assert_eq!(json["messages"][1]["content"][0]["type"], "thinking");
+
+        // Blanked at rest: the block is dropped and this call runs without thinking.
+        request.messages = tool_loop(thinking(""));
+        let json = serde_json::to_value(translate_request(&test_spec(false), &request)).unwrap();
+        assert!(json.get("thinking").is_none());
+        assert_eq!(json["messages"][1]["content"][0]["type"], "tool_use");
+
+        // Thinking switched off: history is stripped of thinking blocks.
+        request.messages = tool_loop(thinking("plan"));
+        request.thinking_budget = None;
+        let json = serde_json::to_value(translate_request(&test_spec(false), &request)).unwrap();
+        assert_eq!(json["messages"][1]["content"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_streamed_thinking_is_accumulated_but_not_forwarded() {
+        let (tx, mut rx) = tokio::sync::broadcast::channel(16);
+        let mut acc = StreamAccumulator::new();
+        let events = [
+            (
+                "content_block_start",
+                r#"{"index":0,"content_block":{"type":"thinking"}}"#,
+            ),
+            (
+                "content_block_delta",
+                r#"{"index":0,"delta":{"type":"thinking_delta","thinking":"Let me "}}"#,
+            ),
+            (
+                "content_block_delta",
+                r#"{"index":0,"delta":{"type":"thinking_delta","thinking":"check."}}"#,
+            ),
+            (
+                "content_block_delta",
+                r#"{"index":0,"delta":{"type":"signature_delta","signature":"EqQB"}}"#,
+            ),
+            ("content_block_stop", r#"{"index":0}"#),
+            (
+                "content_block_start",
+                r#"{"index":1,"content_block":{"type":"redacted_thinking","data":"enc"}}"#,
+            ),
+            ("content_block_stop", r#"{"index":1}"#),
+            (
+                "content_block_start",
+                r#"{"index":2,"content_block":{"type":"text"}}"#,
+            ),
+            (
+                "content_block_delta",
+                r#"{"index":2,"delta":{"type":"text_delta","text":"Done."}}"#,
+            ),
+            ("content_block_stop", r#"{"index":2}"#),
+            (
+                "message_delta",
+                r#"{"delta":{"stop_reason":"end_turn"},"usage":{}}"#,
+            ),
+        ];
+        for (event_type, data) in events {
+            acc.process_event(event_type, data, &tx).unwrap();
+        }
+
+        let response = acc.into_response_with_diagnostics("").unwrap();
+        assert_eq!(
+            response.content,
+            vec![
+                ContentBlock::Thinking {
+                    thinking: "Let me check.".to_string(),
+                    signature: "EqQB".to_string(),
+                },
+                ContentBlock::RedactedThinking {
+                    data: "enc".to_string(),
+                },
+                ContentBlock::text("Done."),
+            ]
+        );
+        // Only the answer text reaches the UI stream.
+        assert!(matches!(rx.try_recv(), Ok(crate::llm::TokenChunk::Text(t)) if t == "Done."));
+        assert!(rx.try_recv().is_err());
+    }
+
     #[test]
     fn test_resolve_anthropic_url_override_takes_priority() {
         let url = resolve_anthropic_url(
@@ -1231,6 +1771,28 @@ mod tests {
         assert_eq!(json["tags"]["foo"], "bar");
     }
 
+    #[test]
+    fn test_batch_results_are_matched_to_request_order() {
+        let jsonl = concat!(
+            r#"{"custom_id":"req-1","result":{"type":"succeeded","message":{"content":[{"type":"text","text":"second"}],"stop_reason":"end_turn","usage":{"input_tokens":3,"output_tokens":1}}}}"#,
+            "\n",
+            r#"{"custom_id":"req-0","result":{"type":"errored","error":{"type":"invalid_request_error"}}}"#,
+            "\n",
+            r#"{"custom_id":"req-2","result":{"type":"expired"}}"#,
+            "\n",
+        );
+        let results = collect_batch_results(jsonl, 4).unwrap();
+        assert_eq!(results.len(), 4);
+        assert!(results[0]
+            .as_ref()
+            .is_err_and(|e| e.message.contains("invalid_request_error")));
+        assert_eq!(results[1].as_ref().unwrap().text(), "second");
+        assert!(results[2].is_err());
+        assert!(results[3]
+            .as_ref()
+            .is_err_and(|e| e.message.contains("missing")));
+    }
+
     #[test]
     fn test_normalize_response_with_server_tool_use() {
         let resp = AnthropicResponse {
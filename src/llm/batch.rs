@@ -0,0 +1,231 @@
+//! First-turn request batching for sub-agent swarms (REQ-SA-009)
+//!
+//! Sub-agents spawned together by one `spawn_agents` call start from
+//! independent prompts, so their first requests can go through the
+//! provider's batch API at a discount. A [`BatchGroup`] collects those
+//! requests and submits them together once every sub-agent has arrived, or
+//! when the collection window closes. Any failure is reported to each
+//! waiter, which then makes its request interactively instead.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use super::{LlmError, LlmRequest, LlmResponse, LlmService};
+
+/// How long the first request waits for the rest of its group.
+const COLLECTION_WINDOW: Duration = Duration::from_secs(5);
+
+type Reply = oneshot::Sender<Result<LlmResponse, LlmError>>;
+
+struct Entry {
+    service: Arc<dyn LlmService>,
+    request: LlmRequest,
+    reply: Reply,
+}
+
+#[derive(Default)]
+struct Pending {
+    entries: Vec<Entry>,
+    /// Incremented on every flush so a stale window timer cannot flush a
+    /// later round early.
+    round: u64,
+}
+
+/// Requests from one group of sub-agents, submitted as batches.
+pub struct BatchGroup {
+    expected: usize,
+    window: Duration,
+    pending: Mutex<Pending>,
+}
+
+impl BatchGroup {
+    /// A group expecting `expected` first-turn requests.
+    pub fn new(expected: usize) -> Self {
+        Self::with_window(expected, COLLECTION_WINDOW)
+    }
+
+    fn with_window(expected: usize, window: Duration) -> Self {
+        Self {
+            expected,
+            window,
+            pending: Mutex::new(Pending::default()),
+        }
+    }
+
+    /// Queue `request` for `service`'s model and wait for its batched result.
+    pub async fn submit(
+        self: &Arc<Self>,
+        service: Arc<dyn LlmService>,
+        request: LlmRequest,
+    ) -> Result<LlmResponse, LlmError> {
+        let (reply, result) = oneshot::channel();
+        let ready = {
+            let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+            pending.entries.push(Entry {
+                service,
+                request,
+                reply,
+            });
+            if pending.entries.len() >= self.expected {
+                Some(Self::take(&mut pending))
+            } else {
+                if pending.entries.len() == 1 {
+                    self.start_window(pending.round);
+                }
+                None
+            }
+        };
+        if let Some(entries) = ready {
+            tokio::spawn(flush(entries));
+        }
+        result
+            .await
+            .unwrap_or_else(|_| Err(LlmError::network("Batch was dropped before completing")))
+    }
+
+    fn take(pending: &mut Pending) -> Vec<Entry> {
+        pending.round += 1;
+        std::mem::take(&mut pending.entries)
+    }
+
+    fn start_window(self: &Arc<Self>, round: u64) {
+        let group = Arc::clone(self);
+        tokio::spawn(async move {
+            tokio::time::sleep(group.window).await;
+            let entries = {
+                let mut pending = group.pending.lock().unwrap_or_else(PoisonError::into_inner);
+                if pending.round != round {
+                    return;
+                }
+                Self::take(&mut pending)
+            };
+            flush(entries).await;
+        });
+    }
+}
+
+/// Submit one batch per model and hand every waiter its result.
+async fn flush(entries: Vec<Entry>) {
+    let mut by_model: BTreeMap<String, Vec<Entry>> = BTreeMap::new();
+    for entry in entries {
+        by_model
+            .entry(entry.service.model_id().to_string())
+            .or_default()
+            .push(entry);
+    }
+
+    for (model_id, entries) in by_model {
+        // A batch of one saves little and only adds latency.
+        if entries.len() < 2 {
+            for entry in entries {
+                let error = LlmError::invalid_request("Not enough requests to batch");
+                let _ = entry.reply.send(Err(error));
+            }
+            continue;
+        }
+
+        let service = Arc::clone(&entries[0].service);
+        let requests: Vec<LlmRequest> = entries.iter().map(|e| e.request.clone()).collect();
+        match service.complete_batch(&requests).await {
+            Ok(results) => {
+                for (entry, result) in entries.into_iter().zip(results) {
+                    let _ = entry.reply.send(result);
+                }
+            }
+            Err(e) => {
+                tracing::warn!(model = %model_id, error = %e.message, "Batch submission failed");
+                for entry in entries {
+                    let _ = entry
+                        .reply
+                        .send(Err(LlmError::new(e.kind, e.message.clone())));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{ContentBlock, PromptCacheKey, SystemContent, Usage};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Answers each batched request with its own system prompt text.
+    struct Echo {
+        batches: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl LlmService for Echo {
+        async fn complete(&self, _request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+            Err(LlmError::invalid_request("interactive call"))
+        }
+
+        async fn complete_batch(
+            &self,
+            requests: &[LlmRequest],
+        ) -> Result<Vec<Result<LlmResponse, LlmError>>, LlmError> {
+            self.batches.fetch_add(1, Ordering::SeqCst);
+            Ok(requests
+                .iter()
+                .map(|r| {
+                    Ok(LlmResponse {
+                        content: vec![ContentBlock::text(r.system[0].text.clone())],
+                        end_turn: true,
+                        usage: Usage::default(),
+                    })
+                })
+                .collect())
+        }
+
+        #[allow(clippy::unnecessary_literal_bound)] // trait signature requires &str
+        fn model_id(&self) -> &str {
+            "echo"
+        }
+    }
+
+    fn request(text: &str) -> LlmRequest {
+        LlmRequest {
+            system: vec![SystemContent::new(text)],
+            messages: vec![],
+            tools: vec![],
+            max_tokens: Some(1024),
+            thinking_budget: None,
+            cache_key: PromptCacheKey::stable("batch"),
+        }
+    }
+
+    #[tokio::test]
+    async fn full_group_is_sent_as_one_batch() {
+        let service = Arc::new(Echo {
+            batches: AtomicUsize::new(0),
+        });
+        let group = Arc::new(BatchGroup::new(3));
+        let submit = |text: &str| {
+            let group = Arc::clone(&group);
+            let service: Arc<dyn LlmService> = service.clone();
+            let request = request(text);
+            async move { group.submit(service, request).await }
+        };
+        let (a, b, c) = tokio::join!(submit("a"), submit("b"), submit("c"));
+        assert_eq!(a.unwrap().text(), "a");
+        assert_eq!(b.unwrap().text(), "b");
+        assert_eq!(c.unwrap().text(), "c");
+        assert_eq!(service.batches.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn lone_request_falls_back_after_window() {
+        let service = Arc::new(Echo {
+            batches: AtomicUsize::new(0),
+        });
+        let group = Arc::new(BatchGroup::with_window(3, Duration::from_millis(10)));
+        let result = group.submit(service.clone(), request("a")).await;
+        assert!(result.is_err());
+        assert_eq!(service.batches.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,318 @@
+//! Record and replay LLM traffic (REQ-LLM-011)
+//!
+//! With `PHOENIX_LLM_MODE=record`, every successful completion is written to
+//! `PHOENIX_LLM_CASSETTE_DIR` (default `~/.phoenix-ide/llm-cassettes`) as one
+//! JSON file named by a hash of the model id and request. With
+//! `PHOENIX_LLM_MODE=replay`, completions are answered from those files and
+//! no provider is contacted; a request that was never recorded fails as an
+//! invalid request. Errors are not recorded.
+//!
+//! The hash covers the system prompt, messages, tool definitions, and
+//! `max_tokens` — everything that shapes the answer — but not the prompt
+//! cache key, which is per-conversation and changes between runs. Replay is
+//! therefore exact only when the replayed run reproduces the same prompt,
+//! including the working directory embedded in the system prompt.
+
+use super::{ContentBlock, LlmError, LlmRequest, LlmResponse, MessageRole, Usage};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// Whether completions are captured or served from disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+/// Directory of recorded request/response pairs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LlmCassette {
+    mode: CassetteMode,
+    dir: PathBuf,
+}
+
+/// One recorded completion on disk.
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    model: String,
+    /// The hashed request, kept for humans diffing cassettes.
+    request: Value,
+    response: RecordedResponse,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedResponse {
+    content: Vec<ContentBlock>,
+    end_turn: bool,
+    usage: Usage,
+}
+
+impl LlmCassette {
+    pub fn new(mode: CassetteMode, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            mode,
+            dir: dir.into(),
+        }
+    }
+
+    /// Read `PHOENIX_LLM_MODE` and `PHOENIX_LLM_CASSETTE_DIR`. Returns `None`
+    /// for live traffic (unset, `live`, or an unrecognised mode).
+    pub fn from_env() -> Option<Self> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let raw = lookup("PHOENIX_LLM_MODE")?;
+        let mode = match raw.trim() {
+            "record" => CassetteMode::Record,
+            "replay" => CassetteMode::Replay,
+            "" | "live" => return None,
+            _ => {
+                tracing::warn!(value = %raw, "Ignoring invalid PHOENIX_LLM_MODE");
+                return None;
+            }
+        };
+        let dir = lookup("PHOENIX_LLM_CASSETTE_DIR").map_or_else(
+            || {
+                let home = lookup("HOME").unwrap_or_else(|| "/tmp".to_string());
+                Path::new(&home).join(".phoenix-ide").join("llm-cassettes")
+            },
+            PathBuf::from,
+        );
+        Some(Self::new(mode, dir))
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Answer `request` from disk.
+    pub async fn replay(
+        &self,
+        model_id: &str,
+        request: &LlmRequest,
+    ) -> Result<LlmResponse, LlmError> {
+        let (key, _) = request_key(model_id, request);
+        let path = self.entry_path(&key);
+        let raw = match tokio::fs::read(&path).await {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(LlmError::invalid_request(format!(
+                    "No recorded response for request {key} in {} (PHOENIX_LLM_MODE=replay)",
+                    self.dir.display()
+                )));
+            }
+            Err(e) => {
+                return Err(LlmError::invalid_request(format!(
+                    "Failed to read {}: {e}",
+                    path.display()
+                )));
+            }
+        };
+        let entry: Entry = serde_json::from_slice(&raw).map_err(|e| {
+            LlmError::invalid_request(format!("Corrupt cassette {}: {e}", path.display()))
+        })?;
+        Ok(LlmResponse {
+            content: entry.response.content,
+            end_turn: entry.response.end_turn,
+            usage: entry.response.usage,
+        })
+    }
+
+    /// Write `response` for `request`, replacing any earlier recording of the
+    /// same request. Failures are logged; the live response is still used.
+    pub async fn record(&self, model_id: &str, request: &LlmRequest, response: &LlmResponse) {
+        let (key, request) = request_key(model_id, request);
+        let entry = Entry {
+            model: model_id.to_string(),
+            request,
+            response: RecordedResponse {
+                content: response.content.clone(),
+                end_turn: response.end_turn,
+                usage: response.usage.clone(),
+            },
+        };
+        if let Err(e) = self.write_entry(&key, &entry).await {
+            tracing::warn!(key = %key, error = %e, "Failed to record LLM response");
+        }
+    }
+
+    async fn write_entry(&self, key: &str, entry: &Entry) -> std::io::Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let body = serde_json::to_vec_pretty(entry)?;
+        let path = self.entry_path(key);
+        let tmp = self.dir.join(format!("{key}.json.partial"));
+        tokio::fs::write(&tmp, body).await?;
+        tokio::fs::rename(&tmp, &path).await
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+/// SHA-256 hex of the canonical request JSON, plus that JSON. Object keys
+/// serialize sorted, so the hash is stable across runs.
+fn request_key(model_id: &str, request: &LlmRequest) -> (String, Value) {
+    use sha2::{Digest, Sha256};
+    use std::fmt::Write;
+
+    let mut canonical = json!({
+        "model": model_id,
+        "system": request
+            .system
+            .iter()
+            .map(|s| json!({ "text": s.text, "cache": s.cache }))
+            .collect::<Vec<_>>(),
+        "messages": request
+            .messages
+            .iter()
+            .map(|m| {
+                let role = match m.role {
+                    MessageRole::User => "user",
+                    MessageRole::Assistant => "assistant",
+                };
+                json!({ "role": role, "content": m.content })
+            })
+            .collect::<Vec<_>>(),
+        "tools": request
+            .tools
+            .iter()
+            .map(|t| {
+                json!({
+                    "name": t.name,
+                    "description": t.description,
+                    "input_schema": t.input_schema,
+                    "defer_loading": t.defer_loading,
+                })
+            })
+            .collect::<Vec<_>>(),
+        "max_tokens": request.max_tokens,
+    });
+    // Only present when set, so cassettes recorded before thinking existed
+    // keep their keys.
+    if let Some(budget) = request.thinking_budget {
+        canonical["thinking_budget"] = json!(budget);
+    }
+    let key = Sha256::digest(canonical.to_string().as_bytes())
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        });
+    (key, canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{LlmMessage, PromptCacheKey, SystemContent};
+
+    fn request(text: &str, cache_key: &str) -> LlmRequest {
+        LlmRequest {
+            system: vec![SystemContent::new("You are a test.")],
+            messages: vec![LlmMessage {
+                role: MessageRole::User,
+                content: vec![ContentBlock::text(text)],
+            }],
+            tools: vec![],
+            max_tokens: Some(1024),
+            thinking_budget: None,
+            cache_key: PromptCacheKey::stable(cache_key),
+        }
+    }
+
+    #[tokio::test]
+    async fn recorded_response_replays_regardless_of_cache_key() {
+        let tmp = tempfile::tempdir().unwrap();
+        let recorder = LlmCassette::new(CassetteMode::Record, tmp.path());
+        let response = LlmResponse {
+            content: vec![ContentBlock::text("hi there")],
+            end_turn: true,
+            usage: Usage {
+                input_tokens: 10,
+                output_tokens: 3,
+                ..Usage::default()
+            },
+        };
+        recorder
+            .record("model-a", &request("hello", "conv-1"), &response)
+            .await;
+
+        let player = LlmCassette::new(CassetteMode::Replay, tmp.path());
+        let replayed = player
+            .replay("model-a", &request("hello", "conv-2"))
+            .await
+            .unwrap();
+        assert_eq!(replayed.content, response.content);
+        assert!(replayed.end_turn);
+        assert_eq!(replayed.usage, response.usage);
+    }
+
+    #[tokio::test]
+    async fn unrecorded_request_fails_without_network() {
+        let tmp = tempfile::tempdir().unwrap();
+        let recorder = LlmCassette::new(CassetteMode::Record, tmp.path());
+        let response = LlmResponse {
+            content: vec![ContentBlock::text("hi")],
+            end_turn: true,
+            usage: Usage::default(),
+        };
+        recorder
+            .record("model-a", &request("hello", "c"), &response)
+            .await;
+
+        let player = LlmCassette::new(CassetteMode::Replay, tmp.path());
+        let err = player
+            .replay("model-a", &request("different", "c"))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("No recorded response"));
+        let err = player
+            .replay("model-b", &request("hello", "c"))
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("No recorded response"));
+    }
+
+    #[test]
+    fn mode_read_from_env() {
+        let lookup = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| (*v).to_string())
+            }
+        };
+        assert_eq!(LlmCassette::from_lookup(lookup(&[])), None);
+        assert_eq!(
+            LlmCassette::from_lookup(lookup(&[("PHOENIX_LLM_MODE", "shadow")])),
+            None
+        );
+
+        let cassette = LlmCassette::from_lookup(lookup(&[
+            ("PHOENIX_LLM_MODE", "replay"),
+            ("HOME", "/home/dev"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            cassette,
+            LlmCassette::new(CassetteMode::Replay, "/home/dev/.phoenix-ide/llm-cassettes")
+        );
+
+        let cassette = LlmCassette::from_lookup(lookup(&[
+            ("PHOENIX_LLM_MODE", "record"),
+            ("PHOENIX_LLM_CASSETTE_DIR", "/srv/cassettes"),
+        ]))
+        .unwrap();
+        assert_eq!(
+            cassette,
+            LlmCassette::new(CassetteMode::Record, "/srv/cassettes")
+        );
+    }
+}
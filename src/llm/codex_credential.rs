@@ -212,7 +212,7 @@ async fn refresh_tokens(refresh_token: &str) -> Result<RefreshResponse, CodexAut
         grant_type: "refresh_token",
         refresh_token,
     };
-    let client = reqwest::Client::builder()
+    let client = super::transport::client_builder()
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .map_err(|e| CodexAuthError::RefreshFailed(format!("client build failed: {e}")))?;
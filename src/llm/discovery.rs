@@ -3,6 +3,7 @@
 //! Queries gateway endpoints to discover available models at runtime,
 //! validating which hardcoded models are available.
 
+use super::Provider;
 use serde::Deserialize;
 use std::collections::HashSet;
 
@@ -39,7 +40,9 @@ pub async fn probe_gateway(
     custom_headers: &[(String, String)],
 ) -> bool {
     let url = format!("{}/_proxy/status", gateway_url.trim_end_matches('/'));
-    let client = reqwest::Client::new();
+    let client = super::transport::client_builder()
+        .build()
+        .unwrap_or_default();
     let mut request = client.get(&url).timeout(std::time::Duration::from_secs(3));
 
     if let Some(token) = auth_token {
@@ -101,6 +104,56 @@ pub async fn discover_models(config: &DiscoveryConfig) -> HashSet<String> {
     models
 }
 
+/// Check an API key against the provider's models endpoint, which needs a
+/// valid key but spends no tokens (REQ-LLM-019). `base_url` is the
+/// provider's configured `*_BASE_URL`; the models URL is derived from it.
+pub async fn check_api_key(
+    provider: Provider,
+    api_key: &str,
+    base_url: Option<&str>,
+    custom_headers: &[(String, String)],
+) -> Result<(), String> {
+    let url = match (provider, base_url) {
+        (_, Some(base)) => super::registry::derive_models_url(base)
+            .ok_or_else(|| format!("cannot derive a models URL from {base}"))?,
+        (Provider::Anthropic, None) => "https://api.anthropic.com/v1/models".to_string(),
+        (Provider::OpenAI, None) => "https://api.openai.com/v1/models".to_string(),
+        (Provider::Mock, None) => return Err("the mock provider takes no key".to_string()),
+    };
+    let client = super::transport::client_builder()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut request = client
+        .get(&url)
+        .header("provider", provider.header_value())
+        .timeout(std::time::Duration::from_secs(10));
+    request = match provider {
+        Provider::Anthropic => request
+            .header("x-api-key", api_key)
+            .header("anthropic-version", "2023-06-01"),
+        _ => request.header("Authorization", format!("Bearer {api_key}")),
+    };
+    for (key, value) in custom_headers {
+        request = request.header(key.as_str(), value.as_str());
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("cannot reach {url}: {e}"))?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(())
+    } else if matches!(status.as_u16(), 401 | 403) {
+        Err(format!(
+            "{} rejected the key ({status})",
+            provider.display_name()
+        ))
+    } else {
+        Err(format!("{url} returned {status}"))
+    }
+}
+
 /// Discover model IDs from a single provider endpoint.
 async fn discover_provider(
     url: &str,
@@ -109,7 +162,7 @@ async fn discover_provider(
     custom_headers: &[(String, String)],
     extra_headers: &[(&str, &str)],
 ) -> Result<HashSet<String>, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
+    let client = super::transport::client_builder().build()?;
     let mut request = client
         .get(url)
         .header("provider", provider_name)
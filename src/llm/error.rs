@@ -48,7 +48,6 @@ impl LlmError {
         Self::new(LlmErrorKind::ContentFilter, message)
     }
 
-    #[allow(dead_code)] // Will be used when providers detect context window errors
     pub fn context_window_exceeded(message: impl Into<String>) -> Self {
         Self::new(LlmErrorKind::ContextWindowExceeded, message)
     }
@@ -100,6 +99,9 @@ impl LlmError {
         match status {
             401 | 403 => Self::auth(format!("Authentication failed: {body}")),
             429 => Self::rate_limit(format!("Rate limited: {body}")),
+            400 if mentions_context_overflow(body) => {
+                Self::context_window_exceeded(format!("Bad request ({status}): {body}"))
+            }
             400..=499 => Self::invalid_request(format!("Bad request ({status}): {body}")),
             500..=599 => Self::server_error(format!("Server error ({status}): {body}")),
             // Unexpected status (1xx, 3xx, etc.) — treat as retryable server error
@@ -107,3 +109,28 @@ impl LlmError {
         }
     }
 }
+
+/// Whether a 400 body is the provider saying the prompt exceeds the context
+/// window (`Anthropic`: "prompt is too long"; `OpenAI`:
+/// `context_length_exceeded`).
+pub(crate) fn mentions_context_overflow(body: &str) -> bool {
+    let body = body.to_lowercase();
+    body.contains("prompt is too long")
+        || body.contains("context_length_exceeded")
+        || body.contains("maximum context length")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn context_overflow_400_is_classified() {
+        let body = r#"{"error":{"message":"prompt is too long: 210000 tokens > 200000"}}"#;
+        let err = LlmError::from_http_status(400, body);
+        assert_eq!(err.kind, LlmErrorKind::ContextWindowExceeded);
+
+        let err = LlmError::from_http_status(400, r#"{"error":{"message":"bad tool schema"}}"#);
+        assert_eq!(err.kind, LlmErrorKind::InvalidRequest);
+    }
+}
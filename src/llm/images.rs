@@ -0,0 +1,253 @@
+//! Image preparation for LLM requests (REQ-BED-040)
+//!
+//! User messages carry images as base64 with a client-declared media type
+//! (REQ-BED-013), and tools can return screenshots. Before a request is
+//! sent, [`prepare_images`] makes every image something the provider will
+//! accept:
+//!
+//! - the media type is taken from the image's magic bytes when they are
+//!   recognised, since browsers and clipboards often mislabel pastes;
+//! - images over the per-image size limit, and the oldest images beyond the
+//!   per-request count limit, are replaced by a short text placeholder;
+//! - for models without vision every image becomes a placeholder, so the
+//!   conversation still works with the model told what it cannot see.
+//!
+//! Stored messages are never modified; this runs on the assembled request.
+
+use base64::Engine;
+
+use super::{ContentBlock, ImageSource, LlmMessage};
+
+/// Per-request image limits. Defaults follow Anthropic's API: 5 MB per
+/// image, and a count well under its per-request maximum so long
+/// screenshot-heavy sessions keep only their most recent images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageLimits {
+    pub max_images: usize,
+    pub max_bytes: usize,
+}
+
+impl ImageLimits {
+    pub const DEFAULT: Self = Self {
+        max_images: 20,
+        max_bytes: 5 * 1024 * 1024,
+    };
+}
+
+/// Media type from the image's leading bytes, for the formats providers
+/// accept.
+pub fn sniff_media_type(data_base64: &str) -> Option<&'static str> {
+    // 16 base64 characters decode to the 12 bytes the checks need.
+    let prefix = data_base64.get(..16)?;
+    let head = base64::engine::general_purpose::STANDARD
+        .decode(prefix)
+        .ok()?;
+    match head.as_slice() {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// Decoded size of a base64 payload, without decoding it.
+fn decoded_len(data_base64: &str) -> usize {
+    let padding = data_base64.bytes().rev().take_while(|b| *b == b'=').count();
+    (data_base64.len() / 4 * 3).saturating_sub(padding)
+}
+
+/// Why an image was left out of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Omitted {
+    NoVision,
+    TooLarge,
+    OverLimit,
+}
+
+impl Omitted {
+    fn placeholder(self) -> &'static str {
+        match self {
+            Omitted::NoVision => "[Image omitted: this model cannot view images.]",
+            Omitted::TooLarge => "[Image omitted: larger than the per-image size limit.]",
+            Omitted::OverLimit => {
+                "[Image omitted: older image dropped to stay within the per-request image limit.]"
+            }
+        }
+    }
+}
+
+/// What [`prepare_images`] changed, for logging.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImageReport {
+    pub kept: usize,
+    pub omitted: usize,
+    pub retyped: usize,
+}
+
+/// Apply sniffing, limits, and the vision fallback to every image in
+/// `messages`, newest first so the most recent images are the ones kept.
+pub fn prepare_images(
+    messages: &mut [LlmMessage],
+    supports_vision: bool,
+    limits: ImageLimits,
+) -> ImageReport {
+    let mut report = ImageReport::default();
+    let mut decide = |source: &mut ImageSource| -> Option<Omitted> {
+        let ImageSource::Base64 { media_type, data } = source;
+        if !supports_vision {
+            return Some(Omitted::NoVision);
+        }
+        if decoded_len(data) > limits.max_bytes {
+            return Some(Omitted::TooLarge);
+        }
+        if report.kept >= limits.max_images {
+            return Some(Omitted::OverLimit);
+        }
+        if let Some(sniffed) = sniff_media_type(data) {
+            if media_type != sniffed {
+                *media_type = sniffed.to_string();
+                report.retyped += 1;
+            }
+        }
+        report.kept += 1;
+        None
+    };
+
+    let mut omitted = 0;
+    for message in messages.iter_mut().rev() {
+        let mut blocks = Vec::with_capacity(message.content.len());
+        for mut block in std::mem::take(&mut message.content).into_iter().rev() {
+            match &mut block {
+                ContentBlock::Image { source } => {
+                    if let Some(reason) = decide(source) {
+                        omitted += 1;
+                        block = ContentBlock::text(reason.placeholder());
+                    }
+                }
+                ContentBlock::ToolResult {
+                    content, images, ..
+                } => {
+                    let mut kept = Vec::with_capacity(images.len());
+                    for mut source in std::mem::take(images).into_iter().rev() {
+                        match decide(&mut source) {
+                            Some(reason) => {
+                                omitted += 1;
+                                content.push_str("\n\n");
+                                content.push_str(reason.placeholder());
+                            }
+                            None => kept.push(source),
+                        }
+                    }
+                    kept.reverse();
+                    *images = kept;
+                }
+                _ => {}
+            }
+            blocks.push(block);
+        }
+        blocks.reverse();
+        message.content = blocks;
+    }
+    report.omitted = omitted;
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::MessageRole;
+
+    const PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk";
+    const JPEG: &str = "/9j/4AAQSkZJRgABAQAAAQABAAD/2wBDAAgGBgcGBQgHBwcJCQgKDBQNDAsL";
+
+    fn image(media_type: &str, data: &str) -> ContentBlock {
+        ContentBlock::Image {
+            source: ImageSource::Base64 {
+                media_type: media_type.to_string(),
+                data: data.to_string(),
+            },
+        }
+    }
+
+    fn user(content: Vec<ContentBlock>) -> LlmMessage {
+        LlmMessage {
+            role: MessageRole::User,
+            content,
+        }
+    }
+
+    fn media_type(block: &ContentBlock) -> Option<&str> {
+        match block {
+            ContentBlock::Image {
+                source: ImageSource::Base64 { media_type, .. },
+            } => Some(media_type),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn sniffs_common_formats() {
+        assert_eq!(sniff_media_type(PNG), Some("image/png"));
+        assert_eq!(sniff_media_type(JPEG), Some("image/jpeg"));
+        assert_eq!(sniff_media_type("R0lGODlhAQABAIAAAP"), Some("image/gif"));
+        assert_eq!(sniff_media_type("UklGRiQAAABXRUJQVlA4"), Some("image/webp"));
+        assert_eq!(sniff_media_type("aGVsbG8gd29ybGQhISE="), None);
+        assert_eq!(sniff_media_type("abc"), None);
+    }
+
+    #[test]
+    fn mislabelled_media_type_is_corrected() {
+        let mut messages = vec![user(vec![
+            ContentBlock::text("look"),
+            image("image/png", JPEG),
+        ])];
+        let report = prepare_images(&mut messages, true, ImageLimits::DEFAULT);
+        let expected = ImageReport {
+            kept: 1,
+            omitted: 0,
+            retyped: 1,
+        };
+        assert_eq!(report, expected);
+        assert_eq!(media_type(&messages[0].content[1]), Some("image/jpeg"));
+    }
+
+    #[test]
+    fn models_without_vision_get_placeholders() {
+        let mut messages = vec![user(vec![
+            ContentBlock::text("look"),
+            image("image/png", PNG),
+        ])];
+        let report = prepare_images(&mut messages, false, ImageLimits::DEFAULT);
+        assert_eq!(report.omitted, 1);
+        assert!(matches!(
+            &messages[0].content[1],
+            ContentBlock::Text { text } if text.contains("cannot view images")
+        ));
+    }
+
+    #[test]
+    fn oldest_and_oversized_images_are_dropped() {
+        let limits = ImageLimits {
+            max_images: 2,
+            max_bytes: 60,
+        };
+        let big = "A".repeat(100);
+        let mut messages = vec![
+            user(vec![image("image/png", PNG)]),
+            user(vec![image("image/png", PNG), image("image/png", &big)]),
+            user(vec![image("image/png", PNG)]),
+        ];
+        let report = prepare_images(&mut messages, true, limits);
+        assert_eq!(report.kept, 2);
+        assert_eq!(report.omitted, 2);
+        assert!(media_type(&messages[2].content[0]).is_some());
+        let placeholder = |block: &ContentBlock| match block {
+            ContentBlock::Text { text } => text.clone(),
+            _ => String::new(),
+        };
+        assert!(placeholder(&messages[1].content[1]).contains("size limit"));
+        assert!(media_type(&messages[1].content[0]).is_some());
+        assert!(placeholder(&messages[0].content[0]).contains("older image"));
+    }
+}
@@ -259,7 +259,7 @@ fn build_response(scenario: &Scenario) -> (Vec<ContentBlock>, String) {
 }
 
 /// Stream text word-by-word with small delays to simulate real LLM output.
-async fn stream_text(text: &str, chunk_tx: &broadcast::Sender<TokenChunk>) {
+pub(super) async fn stream_text(text: &str, chunk_tx: &broadcast::Sender<TokenChunk>) {
     // Split into small chunks (roughly word-sized) for realistic streaming
     let mut chars = text.chars().peekable();
     let mut buf = String::new();
@@ -296,6 +296,7 @@ impl LlmService for MockLlmService {
                 output_tokens: 80,
                 cache_creation_tokens: 0,
                 cache_read_tokens: 0,
+                model: None,
             },
         })
     }
@@ -324,6 +325,7 @@ impl LlmService for MockLlmService {
                 output_tokens: 80,
                 cache_creation_tokens: 0,
                 cache_read_tokens: 0,
+                model: None,
             },
         })
     }
@@ -42,6 +42,7 @@ pub enum ApiFormat {
 
 /// Model specification with metadata
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)] // independent capability flags
 pub struct ModelSpec {
     /// User-facing model ID (e.g., "claude-4.5-opus")
     pub id: String,
@@ -59,6 +60,12 @@ pub struct ModelSpec {
     pub recommended: bool,
     /// Whether this model supports Anthropic's tool search feature
     pub supports_tool_search: bool,
+    /// Whether this model accepts image input (REQ-BED-040)
+    pub supports_vision: bool,
+    /// Whether this model accepts tool definitions (REQ-BED-041)
+    pub supports_tools: bool,
+    /// Largest `max_tokens` the provider accepts for this model (REQ-BED-041)
+    pub max_output_tokens: u32,
 }
 
 /// Get all available model specifications
@@ -76,6 +83,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 200_000,
             recommended: true,
             supports_tool_search: true,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 128_000,
         },
         ModelSpec {
             id: "claude-opus-4-7-1m".into(),
@@ -86,6 +96,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 1_000_000,
             recommended: false,
             supports_tool_search: true,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 128_000,
         },
         ModelSpec {
             id: "claude-opus-4-6".into(),
@@ -96,6 +109,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 200_000,
             recommended: false,
             supports_tool_search: true,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 128_000,
         },
         ModelSpec {
             id: "claude-sonnet-4-6".into(),
@@ -106,6 +122,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 200_000,
             recommended: true,
             supports_tool_search: true,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 64_000,
         },
         ModelSpec {
             id: "claude-haiku-4-5".into(),
@@ -116,6 +135,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 200_000,
             recommended: true,
             supports_tool_search: false,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 64_000,
         },
         ModelSpec {
             id: "claude-opus-4-6-1m".into(),
@@ -126,6 +148,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 1_000_000,
             recommended: false,
             supports_tool_search: true,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 128_000,
         },
         ModelSpec {
             id: "claude-sonnet-4-6-1m".into(),
@@ -136,6 +161,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 1_000_000,
             recommended: false,
             supports_tool_search: true,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 64_000,
         },
         ModelSpec {
             id: "claude-opus-4-5".into(),
@@ -146,6 +174,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 200_000,
             recommended: false,
             supports_tool_search: true,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 64_000,
         },
         // OpenAI models
         // GPT-5 models
@@ -158,6 +189,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 1_000_000,
             recommended: true,
             supports_tool_search: false,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 128_000,
         },
         ModelSpec {
             id: "gpt-5.4".into(),
@@ -168,6 +202,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 400_000,
             recommended: false,
             supports_tool_search: false,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 128_000,
         },
         ModelSpec {
             id: "gpt-5.4-mini".into(),
@@ -178,6 +215,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 400_000,
             recommended: true,
             supports_tool_search: false,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 128_000,
         },
         // GPT-5 Codex models (responses API)
         ModelSpec {
@@ -189,6 +229,9 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 200_000,
             recommended: true,
             supports_tool_search: false,
+            supports_vision: true,
+            supports_tools: true,
+            max_output_tokens: 128_000,
         },
         // Mock model for frontend development without API keys
         ModelSpec {
@@ -200,6 +243,110 @@ pub fn all_models() -> Vec<ModelSpec> {
             context_window: 200_000,
             recommended: false,
             supports_tool_search: false,
+            supports_vision: false,
+            supports_tools: true,
+            max_output_tokens: 16_384,
+        },
+        // Scripted responses for demos (REQ-LLM-018); registered only when
+        // PHOENIX_LLM_SCRIPT names a script
+        ModelSpec {
+            id: "scripted".into(),
+            api_name: "scripted".into(),
+            provider: Provider::Mock,
+            api_format: ApiFormat::Anthropic, // unused, as for mock
+            description: "Scripted (replays PHOENIX_LLM_SCRIPT)".into(),
+            context_window: 200_000,
+            recommended: false,
+            supports_tool_search: false,
+            supports_vision: false,
+            supports_tools: true,
+            max_output_tokens: 16_384,
         },
     ]
 }
+
+/// List prices in USD per million tokens, used to estimate spend from
+/// recorded token counts (REQ-LLM-010).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl ModelPricing {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    /// Estimated cost in USD for the given token counts.
+    #[allow(clippy::cast_precision_loss)] // token counts are far below 2^52
+    pub fn cost_usd(
+        &self,
+        input_tokens: i64,
+        output_tokens: i64,
+        cache_creation_tokens: i64,
+        cache_read_tokens: i64,
+    ) -> f64 {
+        (input_tokens as f64 * self.input
+            + output_tokens as f64 * self.output
+            + cache_creation_tokens as f64 * self.cache_write
+            + cache_read_tokens as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+/// Pricing for a model ID as recorded in `turn_usage`.
+///
+/// Long-context (`-1m`) variants share their base model's entry. Returns
+/// `None` for models without published pricing (mock, discovered models).
+pub fn model_pricing(model_id: &str) -> Option<ModelPricing> {
+    let base = model_id.strip_suffix("-1m").unwrap_or(model_id);
+    let pricing = match base {
+        "claude-opus-4-7" | "claude-opus-4-6" | "claude-opus-4-5" => {
+            ModelPricing::new(5.0, 25.0, 6.25, 0.50)
+        }
+        "claude-sonnet-4-6" => ModelPricing::new(3.0, 15.0, 3.75, 0.30),
+        "claude-haiku-4-5" => ModelPricing::new(1.0, 5.0, 1.25, 0.10),
+        "gpt-5.5" => ModelPricing::new(5.0, 30.0, 0.0, 0.50),
+        "gpt-5.4" => ModelPricing::new(2.50, 15.0, 0.0, 0.25),
+        "gpt-5.4-mini" => ModelPricing::new(0.75, 4.50, 0.0, 0.075),
+        "gpt-5.3-codex" => ModelPricing::new(1.75, 14.0, 0.0, 0.175),
+        _ => return None,
+    };
+    Some(pricing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_billable_model_has_pricing() {
+        for spec in all_models() {
+            let priced = model_pricing(&spec.id).is_some();
+            assert_eq!(priced, spec.provider != Provider::Mock, "{}", spec.id);
+        }
+    }
+
+    #[test]
+    fn long_context_variant_uses_base_pricing() {
+        assert_eq!(
+            model_pricing("claude-sonnet-4-6-1m"),
+            model_pricing("claude-sonnet-4-6")
+        );
+    }
+
+    #[test]
+    fn cost_is_per_million_tokens() {
+        let pricing = model_pricing("claude-haiku-4-5").unwrap();
+        let cost = pricing.cost_usd(1_000_000, 100_000, 0, 2_000_000);
+        assert!((cost - 1.7).abs() < 1e-9, "{cost}");
+    }
+}
@@ -1,9 +1,9 @@
 //! `OpenAI` and `OpenAI`-compatible provider implementation
 
 use super::models::ModelSpec;
+use super::transport::client_builder;
 use super::types::{ContentBlock, LlmRequest, LlmResponse, MessageRole, Usage, LLM_SOURCE_HEADER};
 use super::LlmError;
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::time::Duration;
@@ -64,7 +64,7 @@ pub async fn complete(
         responses_request.tags = Some(request_tags.clone());
     }
 
-    let client = Client::builder()
+    let client = client_builder()
         .timeout(Duration::from_mins(5))
         .build()
         .map_err(|e| LlmError::network(format!("Failed to create HTTP client: {e}")))?;
@@ -99,6 +99,9 @@ pub async fn complete(
             return Err(match status.as_u16() {
                 401 | 403 => LlmError::auth(format!("Authentication failed: {message}")),
                 429 => LlmError::rate_limit(format!("Rate limit exceeded: {message}")),
+                400 if super::error::mentions_context_overflow(&message) => {
+                    LlmError::context_window_exceeded(format!("Bad request ({status}): {message}"))
+                }
                 400..=499 => {
                     LlmError::invalid_request(format!("Bad request ({status}): {message}"))
                 }
@@ -262,7 +265,7 @@ pub async fn complete_streaming(
         responses_request.tags = Some(request_tags.clone());
     }
 
-    let client = Client::builder()
+    let client = client_builder()
         .timeout(Duration::from_mins(10))
         .build()
         .map_err(|e| LlmError::network(format!("Failed to create HTTP client: {e}")))?;
@@ -375,6 +378,8 @@ fn translate_to_responses_request(
                 ContentBlock::Image { source } => image_blocks.push(source),
                 ContentBlock::ToolUse { .. } => tool_calls.push(block),
                 ContentBlock::ToolResult { .. } => tool_results.push(block),
+                // Thinking signatures only verify against Anthropic; skip.
+                ContentBlock::Thinking { .. } | ContentBlock::RedactedThinking { .. } => {}
                 // Anthropic-specific server blocks -- no OpenAI equivalent; skip.
                 ContentBlock::ServerToolUse { .. }
                 | ContentBlock::ToolSearchToolResult { .. }
@@ -577,6 +582,7 @@ fn normalize_responses_api_response(resp: ResponsesApiResponse) -> LlmResponse {
             output_tokens: u64::from(resp.usage.output_tokens),
             cache_creation_tokens: 0,
             cache_read_tokens: 0,
+            model: None,
         },
     }
 }
@@ -754,6 +760,7 @@ mod tests {
             messages: vec![],
             tools: vec![],
             max_tokens: None,
+            thinking_budget: None,
             cache_key: PromptCacheKey::stable("test"),
         }
     }
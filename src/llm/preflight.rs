@@ -0,0 +1,387 @@
+//! Preflight context-window guard (REQ-BED-036)
+//!
+//! Providers reject a request whose prompt does not fit the model's window,
+//! and by then the round trip and the user's wait are already spent. The
+//! executor runs [`fit_request`] on every assembled request first: it
+//! estimates the prompt size and, when it will not fit, blanks the oldest
+//! tool outputs until it does. A request that still overflows is reported
+//! so the caller can compact the conversation instead of sending it.
+//! Outputs of pinned tool calls (REQ-BED-045) are never blanked.
+//!
+//! Estimates are byte-ratio heuristics per provider rather than a real
+//! tokenizer. They lean high for `Anthropic` (the tokenizer is not public)
+//! so the guard errs toward trimming a little early, never toward a 400.
+
+use super::{ContentBlock, LlmMessage, LlmRequest, MessageRole, Provider};
+use std::collections::HashSet;
+
+/// Flat charge per image. `Anthropic` bills roughly `width * height / 750`
+/// tokens, capped near 1,600 after its own downscaling; dimensions are not
+/// decoded here, so every image is charged the cap.
+const IMAGE_TOKENS: usize = 1_600;
+
+/// Framing per message (role markers, block separators).
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Replacement text for a tool output dropped to make room.
+const TRUNCATED_OUTPUT: &str =
+    "[Tool output removed to fit the context window. Re-run the tool if it is still needed.]";
+
+/// What [`fit_request`] did to a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preflight {
+    /// Fits as assembled.
+    Fits { estimate: usize },
+    /// Fits after blanking `outputs` of the oldest tool results.
+    Truncated {
+        outputs: usize,
+        before: usize,
+        after: usize,
+    },
+    /// Does not fit even with every older tool output blanked. The request
+    /// is left trimmed but should not be sent.
+    Overflow { estimate: usize, budget: usize },
+}
+
+/// Estimated prompt tokens for `request`, including system prompt and tool
+/// definitions. Deferred tools cost nothing until discovered.
+pub fn estimate_tokens(request: &LlmRequest, provider: Option<Provider>) -> usize {
+    let system: usize = request.system.iter().map(|s| s.text.len()).sum();
+    let tools: usize = request
+        .tools
+        .iter()
+        .filter(|t| !t.defer_loading)
+        .map(|t| t.name.len() + t.description.len() + t.input_schema.to_string().len())
+        .sum();
+    let messages: usize = request
+        .messages
+        .iter()
+        .map(|m| message_tokens(m, provider))
+        .sum();
+    bytes_to_tokens(system + tools, provider) + messages
+}
+
+/// Trim `request` so its prompt plus `max_tokens` fits `context_window`.
+///
+/// Tool outputs are blanked oldest first, skipping those whose
+/// `tool_use_id` is in `pinned`. The final message is never touched: it
+/// holds the results the model is about to act on.
+pub fn fit_request(
+    request: &mut LlmRequest,
+    context_window: usize,
+    provider: Option<Provider>,
+    pinned: &HashSet<String>,
+) -> Preflight {
+    let reserved = request.max_tokens.map_or(0, |t| t as usize);
+    let budget = context_window.saturating_sub(reserved);
+    let before = estimate_tokens(request, provider);
+    if before <= budget {
+        return Preflight::Fits { estimate: before };
+    }
+
+    let mut estimate = before;
+    let mut outputs = 0;
+    let older = request.messages.len().saturating_sub(1);
+    'messages: for message in &mut request.messages[..older] {
+        for block in &mut message.content {
+            if estimate <= budget {
+                break 'messages;
+            }
+            let ContentBlock::ToolResult { tool_use_id, .. } = &*block else {
+                continue;
+            };
+            if pinned.contains(tool_use_id) {
+                continue;
+            }
+            let cost = block_tokens(block, provider);
+            if let ContentBlock::ToolResult {
+                content, images, ..
+            } = block
+            {
+                if content == TRUNCATED_OUTPUT && images.is_empty() {
+                    continue;
+                }
+                *content = TRUNCATED_OUTPUT.to_string();
+                images.clear();
+                estimate = estimate - cost + block_tokens(block, provider);
+                outputs += 1;
+            }
+        }
+    }
+
+    if estimate <= budget {
+        Preflight::Truncated {
+            outputs,
+            before,
+            after: estimate,
+        }
+    } else {
+        Preflight::Overflow { estimate, budget }
+    }
+}
+
+/// Drop the oldest messages until the prompt fits, keeping the history
+/// starting on a user turn. For tool-less summary requests, where losing
+/// the earliest turns beats not summarizing at all.
+pub fn drop_oldest_messages(
+    request: &mut LlmRequest,
+    context_window: usize,
+    provider: Option<Provider>,
+) -> usize {
+    let reserved = request.max_tokens.map_or(0, |t| t as usize);
+    let budget = context_window.saturating_sub(reserved);
+    let mut estimate = estimate_tokens(request, provider);
+    let mut dropped = 0;
+    while estimate > budget && request.messages.len() > 1 {
+        let removed = request.messages.remove(0);
+        estimate -= message_tokens(&removed, provider);
+        dropped += 1;
+        while request.messages.len() > 1 && request.messages[0].role != MessageRole::User {
+            let removed = request.messages.remove(0);
+            estimate -= message_tokens(&removed, provider);
+            dropped += 1;
+        }
+    }
+    dropped
+}
+
+/// Estimated tokens the tool outputs in `messages` add to a request, per
+/// tool name, sorted by name (REQ-API-031). An output whose call is not in
+/// `messages` counts under `"unknown"`.
+pub fn tool_output_tokens(
+    messages: &[LlmMessage],
+    provider: Option<Provider>,
+) -> Vec<(String, usize)> {
+    let names: std::collections::HashMap<&str, &str> = messages
+        .iter()
+        .flat_map(|m| &m.content)
+        .filter_map(|b| match b {
+            ContentBlock::ToolUse { id, name, .. } => Some((id.as_str(), name.as_str())),
+            _ => None,
+        })
+        .collect();
+    let mut totals = std::collections::BTreeMap::<&str, usize>::new();
+    for block in messages.iter().flat_map(|m| &m.content) {
+        if let ContentBlock::ToolResult { tool_use_id, .. } = block {
+            let name = names
+                .get(tool_use_id.as_str())
+                .copied()
+                .unwrap_or("unknown");
+            *totals.entry(name).or_default() += block_tokens(block, provider);
+        }
+    }
+    totals
+        .into_iter()
+        .map(|(name, tokens)| (name.to_string(), tokens))
+        .collect()
+}
+
+/// Estimated tokens for one message, including its framing.
+pub fn message_tokens(message: &LlmMessage, provider: Option<Provider>) -> usize {
+    MESSAGE_OVERHEAD_TOKENS
+        + message
+            .content
+            .iter()
+            .map(|b| block_tokens(b, provider))
+            .sum::<usize>()
+}
+
+fn block_tokens(block: &ContentBlock, provider: Option<Provider>) -> usize {
+    match block {
+        ContentBlock::Text { text } => bytes_to_tokens(text.len(), provider),
+        ContentBlock::Image { .. } => IMAGE_TOKENS,
+        ContentBlock::ToolResult {
+            content, images, ..
+        } => bytes_to_tokens(content.len(), provider) + images.len() * IMAGE_TOKENS,
+        other => bytes_to_tokens(
+            serde_json::to_string(other).map_or(0, |json| json.len()),
+            provider,
+        ),
+    }
+}
+
+/// Bytes per token: ~4 for `OpenAI`'s `o200k` encoding on English and
+/// code, ~3.5 for `Anthropic`. Unknown providers get the denser ratio.
+fn bytes_to_tokens(bytes: usize, provider: Option<Provider>) -> usize {
+    match provider {
+        Some(Provider::OpenAI) => bytes.div_ceil(4),
+        Some(Provider::Anthropic | Provider::Mock) | None => (bytes * 2).div_ceil(7),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{PromptCacheKey, SystemContent};
+
+    fn tool_round(id: &str, output: &str) -> [LlmMessage; 2] {
+        [
+            LlmMessage {
+                role: MessageRole::Assistant,
+                content: vec![ContentBlock::tool_use(id, "bash", serde_json::json!({}))],
+            },
+            LlmMessage {
+                role: MessageRole::User,
+                content: vec![ContentBlock::ToolResult {
+                    tool_use_id: id.to_string(),
+                    content: output.to_string(),
+                    images: vec![],
+                    is_error: false,
+                }],
+            },
+        ]
+    }
+
+    fn request(messages: Vec<LlmMessage>) -> LlmRequest {
+        LlmRequest {
+            system: vec![SystemContent::new("You are a test.")],
+            messages,
+            tools: vec![],
+            max_tokens: Some(1_000),
+            thinking_budget: None,
+            cache_key: PromptCacheKey::stable("c"),
+        }
+    }
+
+    #[test]
+    fn openai_estimates_fewer_tokens_than_anthropic() {
+        let req = request(vec![LlmMessage {
+            role: MessageRole::User,
+            content: vec![ContentBlock::text("x".repeat(7_000))],
+        }]);
+        let openai = estimate_tokens(&req, Some(Provider::OpenAI));
+        let anthropic = estimate_tokens(&req, Some(Provider::Anthropic));
+        assert!(openai < anthropic, "{openai} vs {anthropic}");
+        assert!((2_000..2_100).contains(&anthropic), "{anthropic}");
+    }
+
+    #[test]
+    fn small_request_fits_untouched() {
+        let mut req = request(tool_round("t1", "ok").to_vec());
+        let outcome = fit_request(
+            &mut req,
+            200_000,
+            Some(Provider::Anthropic),
+            &HashSet::new(),
+        );
+        assert!(matches!(outcome, Preflight::Fits { .. }));
+    }
+
+    #[test]
+    fn oldest_tool_outputs_are_blanked_first() {
+        let big = "y".repeat(35_000); // ~10k tokens
+        let mut messages = vec![LlmMessage {
+            role: MessageRole::User,
+            content: vec![ContentBlock::text("start")],
+        }];
+        messages.extend(tool_round("t1", &big));
+        messages.extend(tool_round("t2", &big));
+        messages.extend(tool_round("t3", &big));
+        let mut req = request(messages);
+
+        let outcome = fit_request(&mut req, 24_000, Some(Provider::Anthropic), &HashSet::new());
+        let Preflight::Truncated { outputs, after, .. } = outcome else {
+            panic!("expected truncation, got {outcome:?}");
+        };
+        assert_eq!(outputs, 1);
+        assert!(after <= 23_000);
+
+        let outputs: Vec<&str> = req
+            .messages
+            .iter()
+            .flat_map(|m| &m.content)
+            .filter_map(|b| match b {
+                ContentBlock::ToolResult { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(outputs[0], TRUNCATED_OUTPUT);
+        assert_eq!(outputs[1], big);
+        assert_eq!(outputs[2], big, "final message is never trimmed");
+    }
+
+    #[test]
+    fn pinned_tool_outputs_are_kept() {
+        let big = "y".repeat(35_000); // ~10k tokens
+        let mut messages = vec![LlmMessage {
+            role: MessageRole::User,
+            content: vec![ContentBlock::text("start")],
+        }];
+        messages.extend(tool_round("t1", &big));
+        messages.extend(tool_round("t2", &big));
+        messages.extend(tool_round("t3", &big));
+        let mut req = request(messages);
+
+        let pinned = HashSet::from(["t1".to_string()]);
+        let outcome = fit_request(&mut req, 24_000, Some(Provider::Anthropic), &pinned);
+        assert!(matches!(outcome, Preflight::Truncated { outputs: 1, .. }));
+
+        let outputs: Vec<&str> = req
+            .messages
+            .iter()
+            .flat_map(|m| &m.content)
+            .filter_map(|b| match b {
+                ContentBlock::ToolResult { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(outputs[0], big, "pinned output is never trimmed");
+        assert_eq!(outputs[1], TRUNCATED_OUTPUT);
+    }
+
+    #[test]
+    fn overflow_when_trimming_is_not_enough() {
+        let mut req = request(vec![LlmMessage {
+            role: MessageRole::User,
+            content: vec![ContentBlock::text("z".repeat(70_000))],
+        }]);
+        let outcome = fit_request(&mut req, 10_000, Some(Provider::Anthropic), &HashSet::new());
+        assert!(matches!(outcome, Preflight::Overflow { budget: 9_000, .. }));
+    }
+
+    #[test]
+    fn dropping_messages_keeps_a_user_turn_first() {
+        // ~2,000 tokens each.
+        let turn = |role| LlmMessage {
+            role,
+            content: vec![ContentBlock::text("a".repeat(7_000))],
+        };
+        let mut req = request(vec![
+            turn(MessageRole::User),
+            turn(MessageRole::Assistant),
+            turn(MessageRole::User),
+            turn(MessageRole::Assistant),
+            LlmMessage {
+                role: MessageRole::User,
+                content: vec![ContentBlock::text("summarize")],
+            },
+        ]);
+
+        let dropped = drop_oldest_messages(&mut req, 6_000, Some(Provider::Anthropic));
+        assert_eq!(dropped, 2);
+        assert_eq!(req.messages.len(), 3);
+        assert_eq!(req.messages[0].role, MessageRole::User);
+        assert!(estimate_tokens(&req, Some(Provider::Anthropic)) <= 5_000);
+    }
+
+    #[test]
+    fn tool_output_tokens_are_summed_per_tool() {
+        let mut messages = tool_round("t1", &"a".repeat(3_500)).to_vec();
+        messages.extend(tool_round("t2", &"b".repeat(7_000)));
+        messages.push(LlmMessage {
+            role: MessageRole::User,
+            content: vec![ContentBlock::ToolResult {
+                tool_use_id: "gone".to_string(),
+                content: "c".repeat(35),
+                images: vec![],
+                is_error: false,
+            }],
+        });
+
+        let totals = tool_output_tokens(&messages, Some(Provider::Anthropic));
+        assert_eq!(
+            totals,
+            [("bash".to_string(), 3_000), ("unknown".to_string(), 10)]
+        );
+    }
+}
@@ -221,6 +221,8 @@ proptest! {
                 | (ContentBlock::Image { .. }, AnthropicContentBlock::Image { .. })
                 | (ContentBlock::ToolUse { .. }, AnthropicContentBlock::ToolUse { .. })
                 | (ContentBlock::ToolResult { .. }, AnthropicContentBlock::ToolResult { .. })
+                | (ContentBlock::Thinking { .. }, AnthropicContentBlock::Thinking { .. })
+                | (ContentBlock::RedactedThinking { .. }, AnthropicContentBlock::RedactedThinking { .. })
                 | (ContentBlock::ServerToolUse { .. }, AnthropicContentBlock::ServerToolUse { .. })
                 | (ContentBlock::ToolSearchToolResult { .. }, AnthropicContentBlock::ToolSearchToolResult { .. })
                 | (ContentBlock::WebSearchToolResult { .. }, AnthropicContentBlock::WebSearchToolResult { .. })
@@ -321,6 +323,7 @@ fn make_llm_request(messages: Vec<LlmMessage>) -> LlmRequest {
         messages,
         tools: vec![],
         max_tokens: None,
+        thinking_budget: None,
         cache_key: super::types::PromptCacheKey::stable("proptest"),
     }
 }
@@ -553,6 +556,18 @@ fn arb_mcp_tool_use() -> impl Strategy<Value = ContentBlock> {
         })
 }
 
+fn arb_thinking_block() -> impl Strategy<Value = ContentBlock> {
+    prop_oneof![
+        ("[a-zA-Z0-9 .]{0,80}", "[A-Za-z0-9+/]{8,40}").prop_map(|(thinking, signature)| {
+            ContentBlock::Thinking {
+                thinking,
+                signature,
+            }
+        }),
+        "[A-Za-z0-9+/]{8,40}".prop_map(|data| ContentBlock::RedactedThinking { data }),
+    ]
+}
+
 /// Any `ContentBlock` variant
 fn arb_content_block() -> impl Strategy<Value = ContentBlock> {
     prop_oneof![
@@ -564,6 +579,7 @@ fn arb_content_block() -> impl Strategy<Value = ContentBlock> {
         1 => arb_tool_search_tool_result(),
         2 => arb_opaque_server_result(),
         1 => arb_mcp_tool_use(),
+        1 => arb_thinking_block(),
     ]
 }
 
@@ -607,6 +623,7 @@ proptest! {
                 output_tokens: 0,
                 cache_creation_tokens: 0,
                 cache_read_tokens: 0,
+                model: None,
             },
         };
         for (id, _name, _input) in response.tool_uses() {
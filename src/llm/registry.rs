@@ -2,9 +2,10 @@
 
 #![allow(dead_code)] // new_empty() used in tests
 
+use super::models::ApiFormat;
 use super::{
     all_models, codex_credential, discover_models, probe_gateway, CodexCredential, DiscoveryConfig,
-    LlmService, LlmServiceImpl, LoggingService, Provider,
+    LlmService, LlmServiceImpl, LoggingService, Provider, ScriptedLlmClient,
 };
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -157,6 +158,10 @@ pub struct LlmConfig {
     pub gateway: Option<String>,
     /// Default model ID
     pub default_model: Option<String>,
+    /// Models to try, in order, when a conversation's model keeps failing
+    /// with network or server errors (REQ-LLM-013). Parsed from
+    /// `LLM_FALLBACK_MODELS` as comma-separated model IDs.
+    pub fallback_models: Vec<String>,
     /// Interactive credential helper. Implements `CredentialSource` for LLM auth
     /// and streams interactive output (OIDC flows) to the UI panel.
     pub credential_helper: Option<Arc<crate::llm::CredentialHelper>>,
@@ -174,6 +179,14 @@ pub struct LlmConfig {
     /// Phoenix doesn't interpret these — they're a pass-through channel for
     /// whatever proxy sits in front of the model.
     pub request_tags: std::collections::BTreeMap<String, String>,
+    /// Per-model endpoint overrides (REQ-LLM-016): a model listed here sends
+    /// its requests to the given URL instead of the gateway or the
+    /// provider-wide `*_BASE_URL`. Parsed from `LLM_MODEL_ENDPOINTS` as
+    /// comma-separated `model_id=url` pairs.
+    pub model_endpoints: std::collections::BTreeMap<String, String>,
+    /// Proxy and TLS settings for LLM traffic (REQ-LLM-016). Installed
+    /// process-wide at startup via [`super::transport::install`].
+    pub transport: super::transport::TransportConfig,
     /// How credential helper output should be sent in HTTP headers.
     /// Parsed from `LLM_AUTH_HEADER` env var at startup.
     pub auth_style: AuthStyle,
@@ -185,6 +198,9 @@ pub struct LlmConfig {
     /// `OAuth` tokens borrowed from the local `Codex` CLI's `~/.codex/auth.json`.
     /// `Anthropic` and `Mock` providers are unaffected.
     pub codex_credential: Option<Arc<CodexCredential>>,
+    /// YAML script answering for the `scripted` model (REQ-LLM-018). Parsed
+    /// from `PHOENIX_LLM_SCRIPT`; the model is only registered when set.
+    pub llm_script: Option<std::path::PathBuf>,
 }
 
 impl std::fmt::Debug for LlmConfig {
@@ -200,14 +216,18 @@ impl std::fmt::Debug for LlmConfig {
             )
             .field("gateway", &self.gateway)
             .field("default_model", &self.default_model)
+            .field("fallback_models", &self.fallback_models)
             .field("credential_helper", &self.credential_helper.is_some())
             .field("anthropic_base_url", &self.anthropic_base_url)
             .field("openai_base_url", &self.openai_base_url)
             .field("custom_headers", &self.custom_headers)
             .field("request_tags", &self.request_tags)
+            .field("model_endpoints", &self.model_endpoints)
+            .field("transport", &self.transport)
             .field("auth_style", &self.auth_style)
             .field("use_codex_auth", &self.use_codex_auth)
             .field("codex_credential", &self.codex_credential.is_some())
+            .field("llm_script", &self.llm_script)
             .finish()
     }
 }
@@ -219,14 +239,18 @@ impl Clone for LlmConfig {
             openai_api_key: self.openai_api_key.clone(),
             gateway: self.gateway.clone(),
             default_model: self.default_model.clone(),
+            fallback_models: self.fallback_models.clone(),
             credential_helper: self.credential_helper.as_ref().map(Arc::clone),
             anthropic_base_url: self.anthropic_base_url.clone(),
             openai_base_url: self.openai_base_url.clone(),
             custom_headers: self.custom_headers.clone(),
             request_tags: self.request_tags.clone(),
+            model_endpoints: self.model_endpoints.clone(),
+            transport: self.transport.clone(),
             auth_style: self.auth_style,
             use_codex_auth: self.use_codex_auth,
             codex_credential: self.codex_credential.as_ref().map(Arc::clone),
+            llm_script: self.llm_script.clone(),
         }
     }
 }
@@ -238,14 +262,18 @@ impl Default for LlmConfig {
             openai_api_key: None,
             gateway: None,
             default_model: None,
+            fallback_models: Vec::new(),
             credential_helper: None,
             anthropic_base_url: None,
             openai_base_url: None,
             custom_headers: Vec::new(),
             request_tags: std::collections::BTreeMap::new(),
+            model_endpoints: std::collections::BTreeMap::new(),
+            transport: super::transport::TransportConfig::default(),
             auth_style: AuthStyle::ApiKey,
             use_codex_auth: false,
             codex_credential: None,
+            llm_script: None,
         }
     }
 }
@@ -292,6 +320,12 @@ impl LlmConfig {
             .map(parse_request_tags)
             .unwrap_or_default();
 
+        let model_endpoints = std::env::var("LLM_MODEL_ENDPOINTS")
+            .ok()
+            .as_deref()
+            .map(parse_request_tags)
+            .unwrap_or_default();
+
         let use_codex_auth = std::env::var("OPENAI_USE_CODEX_AUTH")
             .ok()
             .is_some_and(|v| matches!(v.as_str(), "1" | "true" | "yes" | "on"));
@@ -319,11 +353,18 @@ impl LlmConfig {
             openai_api_key: std::env::var("OPENAI_API_KEY").ok(),
             gateway: std::env::var("LLM_GATEWAY").ok(),
             default_model: std::env::var("DEFAULT_MODEL").ok(),
+            fallback_models: std::env::var("LLM_FALLBACK_MODELS")
+                .ok()
+                .as_deref()
+                .map(parse_model_list)
+                .unwrap_or_default(),
             credential_helper,
             anthropic_base_url,
             openai_base_url,
             custom_headers,
             request_tags,
+            model_endpoints,
+            transport: super::transport::TransportConfig::from_env(),
             auth_style: if std::env::var("LLM_AUTH_HEADER")
                 .ok()
                 .is_some_and(|v| v.eq_ignore_ascii_case("bearer"))
@@ -334,14 +375,27 @@ impl LlmConfig {
             },
             use_codex_auth,
             codex_credential,
+            llm_script: std::env::var_os("PHOENIX_LLM_SCRIPT")
+                .filter(|s| !s.is_empty())
+                .map(std::path::PathBuf::from),
         }
     }
 }
 
+/// Parse a comma-separated list of model IDs, dropping blanks.
+fn parse_model_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 /// Parse the `LLM_REQUEST_TAGS` env-var format: comma-separated `key=value`
-/// pairs. Whitespace around keys/values is trimmed. Empty pairs and pairs
-/// without `=` are skipped. Empty keys are skipped (a value with no key has
-/// nothing useful to forward).
+/// pairs (also used for `LLM_MODEL_ENDPOINTS`). Whitespace around
+/// keys/values is trimmed. Empty pairs and pairs without `=` are skipped.
+/// Empty keys are skipped (a value with no key has nothing useful to
+/// forward).
 fn parse_request_tags(raw: &str) -> std::collections::BTreeMap<String, String> {
     raw.split(',')
         .filter_map(|pair| {
@@ -361,7 +415,7 @@ fn parse_request_tags(raw: &str) -> std::collections::BTreeMap<String, String> {
 
 /// Derive a `/v1/models` URL from a base URL like `/v1/messages` or `/v1/responses`.
 /// Replaces the last path segment with `"models"`, stripping any query string first.
-fn derive_models_url(base_url: &str) -> Option<String> {
+pub(super) fn derive_models_url(base_url: &str) -> Option<String> {
     // Strip query string if present (e.g. "https://host/v1/messages?foo=bar")
     let path = base_url.split('?').next().unwrap_or(base_url);
     let last_slash = path.rfind('/')?;
@@ -371,26 +425,70 @@ fn derive_models_url(base_url: &str) -> Option<String> {
 }
 
 /// Registry of available LLM models
+///
+/// The registered models sit behind a lock so provider keys set at runtime
+/// (REQ-LLM-019) apply without a restart: [`Self::reload_with_keys`] builds
+/// a new model set and swaps it in. Lookups read whichever set is
+/// current; a request already holding a service keeps using it.
 pub struct ModelRegistry {
+    /// Configuration the registry was built from. Reloads start from it.
+    config: LlmConfig,
+    current: std::sync::RwLock<Arc<ModelSet>>,
+}
+
+/// The models registered from one configuration
+struct ModelSet {
     services: HashMap<String, Arc<dyn LlmService>>,
     specs: HashMap<String, super::ModelSpec>,
     default_model: String,
-    /// Reachability status of the configured gateway, determined at startup
-    pub gateway_status: GatewayStatus,
+    /// Configured fallback order; see [`ModelRegistry::fallback_chain`]
+    fallback_models: Vec<String>,
+    /// Reachability status of the configured gateway, determined when the
+    /// set was built
+    gateway_status: GatewayStatus,
 }
 
 impl ModelRegistry {
-    /// Create an empty registry for testing purposes
-    pub fn new_empty() -> Self {
+    fn from_set(config: LlmConfig, set: ModelSet) -> Self {
         Self {
-            services: HashMap::new(),
-            specs: HashMap::new(),
-            default_model: "test-model".to_string(),
-            gateway_status: GatewayStatus::NotConfigured,
+            config,
+            current: std::sync::RwLock::new(Arc::new(set)),
         }
     }
 
+    /// The current model set
+    fn models(&self) -> Arc<ModelSet> {
+        Arc::clone(
+            &self
+                .current
+                .read()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        )
+    }
+
+    /// Create an empty registry for testing purposes
+    pub fn new_empty() -> Self {
+        Self::from_set(
+            LlmConfig::default(),
+            ModelSet {
+                services: HashMap::new(),
+                specs: HashMap::new(),
+                default_model: "test-model".to_string(),
+                fallback_models: Vec::new(),
+                gateway_status: GatewayStatus::NotConfigured,
+            },
+        )
+    }
+
     pub fn new(config: &LlmConfig) -> Self {
+        Self::from_set(
+            config.clone(),
+            Self::build_set(config, GatewayStatus::NotConfigured),
+        )
+    }
+
+    /// Register every hardcoded model `config` has credentials for.
+    fn build_set(config: &LlmConfig, gateway_status: GatewayStatus) -> ModelSet {
         let mut services: HashMap<String, Arc<dyn LlmService>> = HashMap::new();
         let mut specs: HashMap<String, super::ModelSpec> = HashMap::new();
 
@@ -404,21 +502,15 @@ impl ModelRegistry {
 
         let default_model = Self::pick_default_model(&services, config);
 
-        Self {
+        ModelSet {
             services,
             specs,
             default_model,
-            gateway_status: GatewayStatus::NotConfigured,
+            fallback_models: config.fallback_models.clone(),
+            gateway_status,
         }
     }
 
-    /// Create a registry with a specific gateway status, using hardcoded models only.
-    fn new_with_status(config: &LlmConfig, status: GatewayStatus) -> Self {
-        let mut reg = Self::new(config);
-        reg.gateway_status = status;
-        reg
-    }
-
     /// Pick the default model from available services.
     /// Prefers claude-sonnet-4-6 > claude-sonnet-4-5 > any available > hardcoded fallback.
     fn pick_default_model(
@@ -432,6 +524,7 @@ impl ModelRegistry {
             "gpt-5.3-codex",
             "gpt-5.4",
             "gpt-5.4-mini",
+            "scripted",
             "mock",
         ];
         // Honor `DEFAULT_MODEL` only if it actually got registered. A
@@ -461,9 +554,46 @@ impl ModelRegistry {
     /// Unknown/dynamic models from the gateway are silently ignored.
     /// Falls back to hardcoded models if discovery fails.
     pub async fn new_with_discovery(config: &LlmConfig) -> Self {
+        Self::from_set(config.clone(), Self::discover(config).await)
+    }
+
+    /// Rebuild the model set from the startup configuration with `keys`,
+    /// `(provider, api_key)` pairs, in place of the provider keys from the
+    /// environment (REQ-LLM-019). Providers missing from `keys` keep their
+    /// environment key. Keys only matter in direct mode; a gateway or
+    /// credential helper still takes precedence.
+    pub async fn reload_with_keys(&self, keys: &[(String, String)]) {
+        let mut config = self.config.clone();
+        for (provider, key) in keys {
+            match provider.as_str() {
+                "anthropic" => config.anthropic_api_key = Some(key.clone()),
+                "openai" => config.openai_api_key = Some(key.clone()),
+                other => tracing::warn!(provider = %other, "Ignoring key for unknown provider"),
+            }
+        }
+        let set = Arc::new(Self::discover(&config).await);
+        tracing::info!(models = set.services.len(), "LLM registry reloaded");
+        *self
+            .current
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = set;
+    }
+
+    /// The configuration the registry was built from
+    pub fn config(&self) -> &LlmConfig {
+        &self.config
+    }
+
+    /// Reachability of the configured gateway when the models were last
+    /// (re)built
+    pub fn gateway_status(&self) -> GatewayStatus {
+        self.models().gateway_status.clone()
+    }
+
+    async fn discover(config: &LlmConfig) -> ModelSet {
         // Build discovery config from available settings
         let Some((discovery, is_gateway_mode)) = Self::build_discovery_config(config).await else {
-            return Self::new(config);
+            return Self::build_set(config, GatewayStatus::NotConfigured);
         };
 
         // Gateway mode: probe reachability first
@@ -482,7 +612,7 @@ impl ModelRegistry {
                     gateway = %gw,
                     "Gateway unreachable during startup probe; falling back to hardcoded models"
                 );
-                return Self::new_with_status(config, GatewayStatus::Unreachable);
+                return Self::build_set(config, GatewayStatus::Unreachable);
             }
         } else {
             tracing::info!("Discovering models via credential_helper auth");
@@ -500,10 +630,10 @@ impl ModelRegistry {
                     "Gateway model discovery returned no models (gateway may not support listing); \
                      using hardcoded model list with Healthy status"
                 );
-                return Self::new_with_status(config, GatewayStatus::Healthy);
+                return Self::build_set(config, GatewayStatus::Healthy);
             }
             tracing::warn!("Model discovery returned no models, falling back to hardcoded list");
-            return Self::new_with_status(config, GatewayStatus::Unreachable);
+            return Self::build_set(config, GatewayStatus::Unreachable);
         }
 
         tracing::info!("Discovered {} models from gateway", discovered.len());
@@ -534,17 +664,18 @@ impl ModelRegistry {
                 discovered = discovered.len(),
                 "No known models found in gateway discovery; falling back to hardcoded list"
             );
-            return Self::new_with_status(config, GatewayStatus::Unreachable);
+            return Self::build_set(config, GatewayStatus::Unreachable);
         }
 
         tracing::info!("Registered {} models (hardcoded only)", services.len());
 
         let default_model = Self::pick_default_model(&services, config);
 
-        Self {
+        ModelSet {
             services,
             specs,
             default_model,
+            fallback_models: config.fallback_models.clone(),
             gateway_status: GatewayStatus::Healthy,
         }
     }
@@ -562,7 +693,8 @@ impl ModelRegistry {
                     anthropic_models_url: Some(format!("{base}/anthropic/v1/models")),
                     openai_models_url: Some(format!("{base}/openai/v1/models")),
                     auth_token: None, // Gateway handles auth
-                    custom_headers: vec![],
+                    // Org IDs and routing hints apply to discovery too (REQ-LLM-016)
+                    custom_headers: config.custom_headers.clone(),
                 },
                 true,
             ))
@@ -598,6 +730,22 @@ impl ModelRegistry {
         spec: &super::ModelSpec,
         config: &LlmConfig,
     ) -> Option<Arc<dyn LlmService>> {
+        // Scripted responses need a script, and nothing else (REQ-LLM-018)
+        if spec.id == "scripted" {
+            let path = config.llm_script.as_deref()?;
+            let service: Arc<dyn LlmService> = match ScriptedLlmClient::load(path) {
+                Ok(client) => Arc::new(client),
+                Err(e) => {
+                    tracing::warn!(
+                        error = %e,
+                        "PHOENIX_LLM_SCRIPT is set but unusable; scripted model unavailable"
+                    );
+                    return None;
+                }
+            };
+            return Some(Arc::new(LoggingService::new(service)));
+        }
+
         // Mock provider needs no credentials
         if spec.provider == Provider::Mock {
             let service: Arc<dyn LlmService> = Arc::new(super::mock::MockLlmService);
@@ -659,12 +807,23 @@ impl ModelRegistry {
             }
         };
 
+        // A per-model endpoint replaces the provider-wide base URL for the
+        // model's wire format (REQ-LLM-016).
+        let endpoint = config.model_endpoints.get(&spec.id);
+        let (anthropic_base_url, openai_base_url) = match (endpoint, spec.api_format) {
+            (Some(url), ApiFormat::Anthropic) => (Some(url.clone()), None),
+            (Some(url), ApiFormat::OpenAIResponses) => (None, Some(url.clone())),
+            (None, _) => (
+                config.anthropic_base_url.clone(),
+                config.openai_base_url.clone(),
+            ),
+        };
         let service = Arc::new(LlmServiceImpl::new(
             spec.clone(),
             auth,
             config.gateway.clone(),
-            config.anthropic_base_url.clone(),
-            config.openai_base_url.clone(),
+            anthropic_base_url,
+            openai_base_url,
             config.custom_headers.clone(),
             config.request_tags.clone(),
         ));
@@ -673,31 +832,74 @@ impl ModelRegistry {
 
     /// Get a model by ID
     pub fn get(&self, model_id: &str) -> Option<Arc<dyn LlmService>> {
-        self.services.get(model_id).cloned()
+        self.models().services.get(model_id).cloned()
     }
 
     /// Get the default model
     pub fn default(&self) -> Option<Arc<dyn LlmService>> {
-        self.get(&self.default_model)
+        self.get(&self.models().default_model)
     }
 
     /// Get the default model ID
-    pub fn default_model_id(&self) -> &str {
-        &self.default_model
+    pub fn default_model_id(&self) -> String {
+        self.models().default_model.clone()
     }
 
     /// Get the context window size for a model (REQ-BED-022)
     pub fn context_window(&self, model_id: &str) -> usize {
         // Look up in stored specs (includes both hardcoded and dynamic)
-        self.specs.get(model_id).map_or(
+        self.models().specs.get(model_id).map_or(
             crate::state_machine::state::DEFAULT_CONTEXT_WINDOW,
             |spec| spec.context_window,
         )
     }
 
+    /// Whether a model accepts images (REQ-BED-040). Unknown models are
+    /// assumed to, so a discovered model is not silently blinded.
+    pub fn supports_vision(&self, model_id: &str) -> bool {
+        self.models()
+            .specs
+            .get(model_id)
+            .is_none_or(|spec| spec.supports_vision)
+    }
+
+    /// Whether a model accepts tool definitions (REQ-BED-041). Unknown
+    /// models are assumed to, like [`Self::supports_vision`].
+    pub fn supports_tools(&self, model_id: &str) -> bool {
+        self.models()
+            .specs
+            .get(model_id)
+            .is_none_or(|spec| spec.supports_tools)
+    }
+
+    /// `requested` output tokens, capped at the model's maximum (REQ-BED-041)
+    pub fn clamp_max_tokens(&self, model_id: &str, requested: u32) -> u32 {
+        self.models()
+            .specs
+            .get(model_id)
+            .map_or(requested, |spec| requested.min(spec.max_output_tokens))
+    }
+
+    /// Provider serving a model, for per-provider token estimates (REQ-BED-036)
+    pub fn provider(&self, model_id: &str) -> Option<crate::llm::models::Provider> {
+        self.models().specs.get(model_id).map(|spec| spec.provider)
+    }
+
+    /// Models to try, in order, once `model_id` has failed (REQ-LLM-013).
+    /// Unavailable entries and `model_id` itself are skipped.
+    pub fn fallback_chain(&self, model_id: &str) -> Vec<String> {
+        let models = self.models();
+        models
+            .fallback_models
+            .iter()
+            .filter(|id| id.as_str() != model_id && models.services.contains_key(id.as_str()))
+            .cloned()
+            .collect()
+    }
+
     /// List all available model IDs
     pub fn available_models(&self) -> Vec<String> {
-        let mut models: Vec<_> = self.services.keys().cloned().collect();
+        let mut models: Vec<_> = self.models().services.keys().cloned().collect();
         models.sort();
         models
     }
@@ -705,16 +907,20 @@ impl ModelRegistry {
     /// Get detailed information about available models
     pub fn available_model_info(&self) -> Vec<crate::api::ModelInfo> {
         let mut model_infos = Vec::new();
+        let models = self.models();
 
         // Get info for each registered model from stored specs
-        for (model_id, spec) in &self.specs {
-            if self.services.contains_key(model_id) {
+        for (model_id, spec) in &models.specs {
+            if models.services.contains_key(model_id) {
                 model_infos.push(crate::api::ModelInfo {
                     id: spec.id.clone(),
                     provider: spec.provider.display_name().to_string(),
                     description: spec.description.clone(),
                     context_window: spec.context_window,
                     recommended: spec.recommended,
+                    supports_vision: spec.supports_vision,
+                    supports_tools: spec.supports_tools,
+                    max_output_tokens: spec.max_output_tokens,
                 });
             }
         }
@@ -724,7 +930,7 @@ impl ModelRegistry {
 
     /// Check if any models are available
     pub fn has_models(&self) -> bool {
-        !self.services.is_empty()
+        !self.models().services.is_empty()
     }
 
     /// Build a registry with a single `claude-sonnet-4-6` slot wired to
@@ -735,12 +941,39 @@ impl ModelRegistry {
     pub fn for_test_with_sonnet(service: Arc<dyn LlmService>) -> Self {
         let mut services: HashMap<String, Arc<dyn LlmService>> = HashMap::new();
         services.insert("claude-sonnet-4-6".to_string(), service);
-        Self {
-            services,
-            specs: HashMap::new(),
-            default_model: "claude-sonnet-4-6".to_string(),
-            gateway_status: GatewayStatus::NotConfigured,
-        }
+        Self::from_set(
+            LlmConfig::default(),
+            ModelSet {
+                services,
+                specs: HashMap::new(),
+                default_model: "claude-sonnet-4-6".to_string(),
+                fallback_models: Vec::new(),
+                gateway_status: GatewayStatus::NotConfigured,
+            },
+        )
+    }
+
+    /// Build a registry from `(model_id, service)` pairs with a fallback
+    /// order. Test-only, like [`Self::for_test_with_sonnet`].
+    #[cfg(test)]
+    pub fn for_test_with_fallbacks(
+        models: Vec<(&str, Arc<dyn LlmService>)>,
+        fallback_models: &[&str],
+    ) -> Self {
+        let default_model = models.first().map(|(id, _)| (*id).to_string());
+        Self::from_set(
+            LlmConfig::default(),
+            ModelSet {
+                services: models
+                    .into_iter()
+                    .map(|(id, service)| (id.to_string(), service))
+                    .collect(),
+                specs: HashMap::new(),
+                default_model: default_model.unwrap_or_default(),
+                fallback_models: fallback_models.iter().map(ToString::to_string).collect(),
+                gateway_status: GatewayStatus::NotConfigured,
+            },
+        )
     }
 
     /// Get a mid-tier "Sonnet-class" model balanced for cost vs accuracy.
@@ -760,7 +993,7 @@ impl ModelRegistry {
                 return Some(((*id).to_string(), service));
             }
         }
-        self.default().map(|s| (self.default_model.clone(), s))
+        self.default().map(|s| (self.default_model_id(), s))
     }
 
     /// Get a cheap/fast model for auxiliary tasks like title generation.
@@ -784,7 +1017,7 @@ impl ModelRegistry {
     pub fn cheap_model_id_for_provider(&self, parent_model_id: &str) -> String {
         use crate::llm::models::Provider;
 
-        let parent_provider = self.specs.get(parent_model_id).map(|s| s.provider);
+        let parent_provider = self.provider(parent_model_id);
 
         let candidates: &[&str] = match parent_provider {
             Some(Provider::Anthropic) => &["claude-haiku-4-5"],
@@ -795,7 +1028,7 @@ impl ModelRegistry {
 
         candidates
             .iter()
-            .find(|id| self.services.contains_key(**id))
+            .find(|id| self.get(id).is_some())
             .map_or_else(
                 || parent_model_id.to_string(),
                 std::string::ToString::to_string,
@@ -815,6 +1048,42 @@ mod tests {
         assert_eq!(registry.available_models(), vec!["mock".to_string()]);
     }
 
+    #[tokio::test]
+    async fn reloading_with_keys_registers_their_models() {
+        let registry = ModelRegistry::new(&LlmConfig::default());
+        assert_eq!(registry.default_model_id(), "mock");
+
+        registry
+            .reload_with_keys(&[("anthropic".to_string(), "sk-ant-test".to_string())])
+            .await;
+        assert!(registry.get("claude-sonnet-4-6").is_some());
+        assert!(registry.get("gpt-5.5").is_none());
+        assert_eq!(registry.default_model_id(), "claude-sonnet-4-6");
+
+        // Dropping the stored key falls back to the environment's (none here)
+        registry.reload_with_keys(&[]).await;
+        assert_eq!(registry.available_models(), vec!["mock".to_string()]);
+    }
+
+    #[test]
+    fn test_scripted_model_needs_a_valid_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("demo.yaml");
+        std::fs::write(&script, "turns:\n  - text: Hello from the script.\n").unwrap();
+        let config = LlmConfig {
+            llm_script: Some(script.clone()),
+            ..Default::default()
+        };
+        let registry = ModelRegistry::new(&config);
+        assert!(registry.get("scripted").is_some());
+        assert_eq!(registry.default_model_id(), "scripted");
+
+        std::fs::write(&script, "not: [a script").unwrap();
+        let registry = ModelRegistry::new(&config);
+        assert!(registry.get("scripted").is_none());
+        assert_eq!(registry.available_models(), vec!["mock".to_string()]);
+    }
+
     #[test]
     fn test_anthropic_key_only_anthropic_and_mock_models() {
         let config = LlmConfig {
@@ -873,6 +1142,35 @@ mod tests {
         assert_eq!(tags.get("query"), Some(&"a=b=c".to_string()));
     }
 
+    #[test]
+    fn test_parse_model_list() {
+        assert_eq!(
+            parse_model_list(" claude-sonnet-4-6, ,gpt-5.5,"),
+            vec!["claude-sonnet-4-6".to_string(), "gpt-5.5".to_string()]
+        );
+        assert!(parse_model_list("").is_empty());
+    }
+
+    #[test]
+    fn test_fallback_chain_skips_self_and_unavailable() {
+        let config = LlmConfig {
+            anthropic_api_key: Some("test-key".to_string()),
+            fallback_models: vec![
+                "claude-sonnet-4-6".to_string(),
+                "gpt-5.5".to_string(),
+                "claude-haiku-4-5".to_string(),
+            ],
+            ..Default::default()
+        };
+        let registry = ModelRegistry::new(&config);
+
+        // gpt-5.5 has no key configured, so only the haiku fallback remains.
+        assert_eq!(
+            registry.fallback_chain("claude-sonnet-4-6"),
+            vec!["claude-haiku-4-5".to_string()]
+        );
+    }
+
     #[test]
     fn test_gateway_enables_all_models() {
         // With gateway, all models become available (gateway handles auth)
@@ -1044,7 +1342,7 @@ mod tests {
         // gpt-5.5 isn't registered (no OpenAI auth), so default must fall
         // back to a model that actually exists.
         assert_ne!(registry.default_model_id(), "gpt-5.5");
-        assert!(registry.get(registry.default_model_id()).is_some());
+        assert!(registry.get(&registry.default_model_id()).is_some());
     }
 
     #[test]
@@ -1074,6 +1372,27 @@ mod tests {
         assert_eq!(opus.provider, "Anthropic");
         assert!(opus.description.contains("most capable"));
         assert_eq!(opus.context_window, 200_000);
+        assert!(opus.supports_vision && opus.supports_tools);
+        assert_eq!(opus.max_output_tokens, 128_000);
+    }
+
+    #[test]
+    fn test_max_tokens_clamped_to_model_limit() {
+        let config = LlmConfig {
+            anthropic_api_key: Some("test-key".to_string()),
+            ..Default::default()
+        };
+        let registry = ModelRegistry::new(&config);
+        assert_eq!(
+            registry.clamp_max_tokens("claude-haiku-4-5", 100_000),
+            64_000
+        );
+        assert_eq!(
+            registry.clamp_max_tokens("claude-haiku-4-5", 16_384),
+            16_384
+        );
+        // Unknown models pass the request through untouched.
+        assert_eq!(registry.clamp_max_tokens("unknown", 100_000), 100_000);
     }
 
     #[test]
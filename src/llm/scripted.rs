@@ -0,0 +1,249 @@
+//! Scripted LLM provider for demos and tutorials (REQ-LLM-018)
+//!
+//! `PHOENIX_LLM_SCRIPT=path/to/demo.yaml` registers a `scripted` model that
+//! answers from a YAML script instead of a provider, so a demo plays out the
+//! same way every time without API keys or network:
+//!
+//! ```yaml
+//! turns:
+//!   - text: Let me run the failing test first.
+//!     tools:
+//!       - name: bash
+//!         input: { command: cargo test pagination }
+//!   - text: Let me read the pagination code.
+//!     tools:
+//!       - name: read_file
+//!         input: { path: src/page.rs }
+//!   - text: The offset is off by one; that's why the last page is empty.
+//! fallback: That's the end of this demo.
+//! ```
+//!
+//! A turn is one model request: the first request of a conversation gets
+//! `turns[0]`, the request after its tool results `turns[1]`, and so on,
+//! counted from the assistant messages the request carries. Tools run for
+//! real, so the script drives the UI the same way a live model would. Once
+//! the turns run out, every request gets `fallback`.
+
+use super::preflight;
+use super::types::{ContentBlock, LlmMessage, LlmRequest, LlmResponse, MessageRole, Usage};
+use super::{LlmError, LlmService, TokenChunk};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::Path;
+use tokio::sync::broadcast;
+
+/// Reply once the script has no turn left, when it sets no `fallback`.
+const DEFAULT_FALLBACK: &str = "This script has no more responses.";
+
+/// Replays a YAML script of responses, one per turn.
+#[derive(Debug)]
+pub struct ScriptedLlmClient {
+    script: Script,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Script {
+    turns: Vec<ScriptedTurn>,
+    #[serde(default)]
+    fallback: Option<String>,
+}
+
+/// One canned response.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScriptedTurn {
+    /// Streamed to the UI before any tool calls.
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    tools: Vec<ScriptedToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScriptedToolCall {
+    name: String,
+    #[serde(default = "empty_input")]
+    input: serde_json::Value,
+}
+
+fn empty_input() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+impl ScriptedLlmClient {
+    /// Parse a script. Errors name the problem in the YAML.
+    pub fn from_yaml(raw: &str) -> Result<Self, String> {
+        let script: Script = serde_yaml::from_str(raw).map_err(|e| e.to_string())?;
+        Ok(Self { script })
+    }
+
+    /// Read and parse the script at `path`.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+        Self::from_yaml(&raw).map_err(|e| format!("invalid script {}: {e}", path.display()))
+    }
+
+    /// The scripted response to `request`, and the text to stream for it.
+    fn respond(&self, request: &LlmRequest) -> (LlmResponse, String) {
+        let turn = request
+            .messages
+            .iter()
+            .filter(|m| m.role == MessageRole::Assistant)
+            .count();
+        let (text, tools) = if let Some(scripted) = self.script.turns.get(turn) {
+            (scripted.text.clone(), scripted.tools.as_slice())
+        } else {
+            let fallback = self.script.fallback.as_deref().unwrap_or(DEFAULT_FALLBACK);
+            (fallback.to_string(), [].as_slice())
+        };
+
+        let mut content = Vec::new();
+        if !text.is_empty() {
+            content.push(ContentBlock::text(text.clone()));
+        }
+        // Ids only have to be unique within the conversation, and the turn
+        // number already is.
+        content.extend(
+            tools
+                .iter()
+                .enumerate()
+                .map(|(i, tool)| ContentBlock::ToolUse {
+                    id: format!("scripted_toolu_{turn}_{i}"),
+                    name: tool.name.clone(),
+                    input: tool.input.clone(),
+                }),
+        );
+
+        let reply = LlmMessage {
+            role: MessageRole::Assistant,
+            content,
+        };
+        let usage = Usage {
+            input_tokens: tokens(preflight::estimate_tokens(request, None)),
+            output_tokens: tokens(preflight::message_tokens(&reply, None)),
+            ..Usage::default()
+        };
+        let response = LlmResponse {
+            content: reply.content,
+            end_turn: tools.is_empty(),
+            usage,
+        };
+        (response, text)
+    }
+}
+
+fn tokens(estimate: usize) -> u64 {
+    u64::try_from(estimate).unwrap_or(u64::MAX)
+}
+
+#[async_trait]
+impl LlmService for ScriptedLlmClient {
+    async fn complete(&self, request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+        Ok(self.respond(request).0)
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: &LlmRequest,
+        chunk_tx: &broadcast::Sender<TokenChunk>,
+    ) -> Result<LlmResponse, LlmError> {
+        let (response, text) = self.respond(request);
+        if !text.is_empty() {
+            super::mock::stream_text(&text, chunk_tx).await;
+        }
+        Ok(response)
+    }
+
+    #[allow(clippy::unnecessary_literal_bound)] // trait signature requires &str, not &'static str
+    fn model_id(&self) -> &str {
+        "scripted"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{PromptCacheKey, SystemContent};
+
+    const SCRIPT: &str = r"
+turns:
+  - text: Checking.
+    tools:
+      - name: bash
+        input: { command: ls }
+      - name: think
+        input: { thought: hmm }
+  - text: Done.
+fallback: End of demo.
+";
+
+    fn request(assistant_turns: usize) -> LlmRequest {
+        let mut messages = vec![LlmMessage {
+            role: MessageRole::User,
+            content: vec![ContentBlock::text("go")],
+        }];
+        for _ in 0..assistant_turns {
+            messages.push(LlmMessage {
+                role: MessageRole::Assistant,
+                content: vec![ContentBlock::text("...")],
+            });
+            messages.push(LlmMessage {
+                role: MessageRole::User,
+                content: vec![ContentBlock::text("...")],
+            });
+        }
+        LlmRequest {
+            system: vec![SystemContent::new("You are a test.")],
+            messages,
+            tools: vec![],
+            max_tokens: None,
+            thinking_budget: None,
+            cache_key: PromptCacheKey::stable("c1"),
+        }
+    }
+
+    #[tokio::test]
+    async fn turns_follow_the_conversation() {
+        let client = ScriptedLlmClient::from_yaml(SCRIPT).unwrap();
+
+        let first = client.complete(&request(0)).await.unwrap();
+        assert!(!first.end_turn);
+        assert_eq!(first.content.len(), 3);
+        assert_eq!(first.content[0], ContentBlock::text("Checking."));
+        let input = serde_json::json!({"command": "ls"});
+        let bash = ContentBlock::tool_use("scripted_toolu_0_0", "bash", input);
+        assert_eq!(first.content[1], bash);
+        assert!(first.usage.input_tokens > 0);
+
+        let second = client.complete(&request(1)).await.unwrap();
+        assert!(second.end_turn);
+        assert_eq!(second.content, vec![ContentBlock::text("Done.")]);
+
+        // Same request, same answer
+        let again = client.complete(&request(1)).await.unwrap();
+        assert_eq!(again.content, second.content);
+    }
+
+    #[tokio::test]
+    async fn falls_back_once_turns_run_out() {
+        let client = ScriptedLlmClient::from_yaml(SCRIPT).unwrap();
+        let late = client.complete(&request(5)).await.unwrap();
+        assert!(late.end_turn);
+        assert_eq!(late.content, vec![ContentBlock::text("End of demo.")]);
+
+        let client = ScriptedLlmClient::from_yaml("turns: []").unwrap();
+        let late = client.complete(&request(0)).await.unwrap();
+        assert_eq!(late.content, vec![ContentBlock::text(DEFAULT_FALLBACK)]);
+    }
+
+    #[test]
+    fn rejects_malformed_scripts() {
+        assert!(ScriptedLlmClient::from_yaml("fallback: hi").is_err());
+        assert!(ScriptedLlmClient::from_yaml("turns:\n  - txt: typo").is_err());
+        let err = ScriptedLlmClient::load(Path::new("/nonexistent/demo.yaml")).unwrap_err();
+        assert!(err.contains("cannot read"), "{err}");
+    }
+}
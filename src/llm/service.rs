@@ -19,6 +19,11 @@ fn empty_tags() -> &'static BTreeMap<String, String> {
     EMPTY.get_or_init(BTreeMap::new)
 }
 
+/// How long a message batch may take before it is cancelled and the
+/// requests fall back to interactive calls. Well inside the sub-agent
+/// timeout, so a slow batch still leaves time to finish the task.
+const BATCH_DEADLINE: std::time::Duration = std::time::Duration::from_mins(10);
+
 /// Unified service implementation that dispatches by API format
 pub struct LlmServiceImpl {
     pub spec: ModelSpec,
@@ -157,6 +162,31 @@ impl LlmService for LlmServiceImpl {
         result
     }
 
+    async fn complete_batch(
+        &self,
+        requests: &[LlmRequest],
+    ) -> Result<Vec<Result<LlmResponse, LlmError>>, LlmError> {
+        // Only the Anthropic Messages API has a batch endpoint we use.
+        if self.spec.api_format != ApiFormat::Anthropic {
+            return Err(LlmError::invalid_request(format!(
+                "Model {} does not support batch requests",
+                self.spec.id
+            )));
+        }
+        let resolved = self.resolve_auth().await?;
+        let headers = self.headers_for_provider();
+        anthropic::complete_batch(
+            &self.spec,
+            &resolved,
+            self.gateway.as_deref(),
+            self.anthropic_base_url.as_deref(),
+            &headers,
+            requests,
+            BATCH_DEADLINE,
+        )
+        .await
+    }
+
     fn model_id(&self) -> &str {
         &self.spec.id
     }
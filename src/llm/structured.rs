@@ -0,0 +1,333 @@
+//! Schema-constrained completions for internal LLM calls (REQ-LLM-015)
+//!
+//! Title generation and keyword-search ranking want data, not prose, and a
+//! model that wraps its answer in a code fence or adds a friendly preamble
+//! used to break them. [`complete_json`] appends the expected JSON Schema to
+//! the system prompt, pulls the first JSON value out of the reply, checks it
+//! against the schema, and on failure makes exactly one repair attempt that
+//! shows the model its reply and what was wrong with it.
+//!
+//! Only the schema keywords these callers need are enforced: `type`, `enum`,
+//! `properties`, `required`, `additionalProperties: false`, `items`,
+//! `minItems`/`maxItems` and `minLength`/`maxLength`. Anything else in the
+//! schema is passed to the model but not checked.
+
+use super::{
+    ContentBlock, LlmError, LlmMessage, LlmRequest, LlmService, MessageRole, SystemContent,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Why a structured completion produced no value.
+#[derive(Debug, thiserror::Error)]
+pub enum StructuredError {
+    #[error("LLM request failed: {}", .0.message)]
+    Llm(#[from] LlmError),
+    #[error("Reply did not match the schema after one repair attempt: {0}")]
+    Invalid(String),
+}
+
+/// Run `request` and decode the reply as a `T` matching `schema`.
+///
+/// The schema instruction is appended to the request's system prompt. A
+/// reply that is not valid JSON, fails validation, or does not deserialize
+/// into `T` is sent back once with the problem spelled out; a second bad
+/// reply is returned as [`StructuredError::Invalid`].
+pub async fn complete_json<T: DeserializeOwned>(
+    llm: &dyn LlmService,
+    mut request: LlmRequest,
+    schema: &Value,
+) -> Result<T, StructuredError> {
+    request.system.push(SystemContent::new(format!(
+        "Respond with a single JSON value and nothing else: no prose, no \
+         code fences. It must match this JSON Schema:\n{schema}"
+    )));
+
+    let reply = llm.complete(&request).await?.text();
+    let problem = match decode(&reply, schema) {
+        Ok(value) => return Ok(value),
+        Err(problem) => problem,
+    };
+    tracing::warn!(%problem, "Structured reply invalid, requesting repair");
+
+    request.messages.push(LlmMessage {
+        role: MessageRole::Assistant,
+        content: vec![ContentBlock::text(reply)],
+    });
+    request.messages.push(LlmMessage {
+        role: MessageRole::User,
+        content: vec![ContentBlock::text(format!(
+            "That reply was rejected: {problem}. Reply again with only the \
+             corrected JSON value."
+        ))],
+    });
+    let reply = llm.complete(&request).await?.text();
+    decode(&reply, schema).map_err(StructuredError::Invalid)
+}
+
+fn decode<T: DeserializeOwned>(reply: &str, schema: &Value) -> Result<T, String> {
+    let value = extract_json(reply).ok_or_else(|| "no JSON value found".to_string())?;
+    validate(&value, schema, "$")?;
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// The first JSON object or array in `text`, ignoring code fences and any
+/// prose around it.
+fn extract_json(text: &str) -> Option<Value> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+    trimmed
+        .char_indices()
+        .filter(|(_, c)| matches!(c, '{' | '['))
+        .find_map(|(start, _)| {
+            serde_json::Deserializer::from_str(trimmed.get(start..)?)
+                .into_iter::<Value>()
+                .next()?
+                .ok()
+        })
+}
+
+/// Check `value` against the supported subset of JSON Schema. Errors name
+/// the offending location as a `$.field[0]` path.
+fn validate(value: &Value, schema: &Value, path: &str) -> Result<(), String> {
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            return Err(format!("{path} should be {}", types.join(" or ")));
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            return Err(format!(
+                "{path} should be one of {}",
+                Value::from(allowed.clone())
+            ));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(key) {
+                        return Err(format!("{path} is missing required field \"{key}\""));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+            for (key, field) in map {
+                match properties.and_then(|p| p.get(key)) {
+                    Some(sub) => validate(field, sub, &format!("{path}.{key}"))?,
+                    None if closed => {
+                        return Err(format!("{path} has unexpected field \"{key}\""));
+                    }
+                    None => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            check_bounds(items.len(), schema, "minItems", "maxItems", "items", path)?;
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate(item, item_schema, &format!("{path}[{i}]"))?;
+                }
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count();
+            check_bounds(len, schema, "minLength", "maxLength", "characters", path)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn check_bounds(
+    len: usize,
+    schema: &Value,
+    min_key: &str,
+    max_key: &str,
+    unit: &str,
+    path: &str,
+) -> Result<(), String> {
+    let bound = |key| {
+        schema
+            .get(key)
+            .and_then(Value::as_u64)
+            .map(|n| usize::try_from(n).unwrap_or(usize::MAX))
+    };
+    if let Some(min) = bound(min_key) {
+        if len < min {
+            return Err(format!("{path} has {len} {unit}, fewer than {min}"));
+        }
+    }
+    if let Some(max) = bound(max_key) {
+        if len > max {
+            return Err(format!("{path} has {len} {unit}, more than {max}"));
+        }
+    }
+    Ok(())
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{LlmResponse, PromptCacheKey, Usage};
+    use async_trait::async_trait;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    /// Replies with each scripted text in turn and records every request.
+    struct Scripted {
+        replies: Mutex<Vec<&'static str>>,
+        requests: Mutex<Vec<LlmRequest>>,
+    }
+
+    impl Scripted {
+        fn new(replies: &[&'static str]) -> Self {
+            Self {
+                replies: Mutex::new(replies.iter().rev().copied().collect()),
+                requests: Mutex::new(vec![]),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LlmService for Scripted {
+        async fn complete(&self, request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+            self.requests.lock().unwrap().push(request.clone());
+            let reply = self.replies.lock().unwrap().pop().expect("unscripted call");
+            Ok(LlmResponse {
+                content: vec![ContentBlock::text(reply)],
+                end_turn: true,
+                usage: Usage::default(),
+            })
+        }
+
+        #[allow(clippy::unnecessary_literal_bound)] // trait signature requires &str
+        fn model_id(&self) -> &str {
+            "test-model"
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Title {
+        title: String,
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["title"],
+            "properties": {"title": {"type": "string", "minLength": 1, "maxLength": 20}},
+            "additionalProperties": false
+        })
+    }
+
+    fn request() -> LlmRequest {
+        LlmRequest {
+            system: vec![],
+            messages: vec![LlmMessage {
+                role: MessageRole::User,
+                content: vec![ContentBlock::text("Name this")],
+            }],
+            tools: vec![],
+            max_tokens: Some(50),
+            thinking_budget: None,
+            cache_key: PromptCacheKey::stable("structured-test"),
+        }
+    }
+
+    #[tokio::test]
+    async fn fenced_reply_with_prose_is_accepted() {
+        let llm = Scripted::new(&["Sure!\n```json\n{\"title\": \"Fix Login\"}\n```"]);
+        let title: Title = complete_json(&llm, request(), &schema()).await.unwrap();
+        assert_eq!(title.title, "Fix Login");
+
+        let requests = llm.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].system[0].text.contains("\"title\""));
+    }
+
+    #[tokio::test]
+    async fn invalid_reply_gets_one_repair_attempt() {
+        let llm = Scripted::new(&["Fix Login", r#"{"title": "Fix Login"}"#]);
+        let title: Title = complete_json(&llm, request(), &schema()).await.unwrap();
+        assert_eq!(title.title, "Fix Login");
+
+        let requests = llm.requests.lock().unwrap();
+        let repair = &requests[1].messages;
+        assert_eq!(repair.len(), 3);
+        assert_eq!(repair[1].role, MessageRole::Assistant);
+        let ContentBlock::Text { text } = &repair[2].content[0] else {
+            panic!("expected repair text");
+        };
+        assert!(text.contains("no JSON value found"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn second_invalid_reply_is_an_error() {
+        let llm = Scripted::new(&[r#"{"name": "x"}"#, r#"{"title": ""}"#]);
+        let err = complete_json::<Title>(&llm, request(), &schema())
+            .await
+            .unwrap_err();
+        let StructuredError::Invalid(problem) = err else {
+            panic!("expected Invalid, got {err:?}");
+        };
+        assert!(problem.contains("$.title has 0 characters"), "{problem}");
+        assert_eq!(llm.requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn validate_reports_the_failing_path() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "files": {
+                    "type": "array",
+                    "items": {"type": "object", "required": ["path"]}
+                },
+                "mode": {"enum": ["fast", "slow"]}
+            }
+        });
+        let ok = json!({"files": [{"path": "a"}], "mode": "fast", "extra": 1});
+        assert_eq!(validate(&ok, &schema, "$"), Ok(()));
+
+        let missing = json!({"files": [{"path": "a"}, {}]});
+        let err = validate(&missing, &schema, "$").unwrap_err();
+        assert_eq!(err, "$.files[1] is missing required field \"path\"");
+
+        let bad_enum = json!({"mode": "medium"});
+        let err = validate(&bad_enum, &schema, "$").unwrap_err();
+        assert!(err.starts_with("$.mode should be one of"), "{err}");
+    }
+
+    #[test]
+    fn extract_json_skips_leading_brackets_that_are_not_json() {
+        let text = "Results [see below]: {\"a\": [1, 2]} trailing";
+        assert_eq!(extract_json(text), Some(json!({"a": [1, 2]})));
+        assert_eq!(extract_json("no json here"), None);
+    }
+}
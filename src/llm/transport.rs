@@ -0,0 +1,171 @@
+//! HTTP transport settings for LLM traffic (REQ-LLM-016)
+//!
+//! Enterprise networks often sit between Phoenix and the model: an explicit
+//! HTTPS proxy, a gateway that demands a client certificate, or a private CA.
+//! These settings apply to every outbound LLM request, including gateway
+//! probes, model discovery, and Codex token refresh, so they are installed
+//! once at startup and picked up by [`client_builder`].
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+/// Transport settings parsed from the environment.
+#[derive(Debug, Clone, Default)]
+pub struct TransportConfig {
+    /// Proxy for all LLM requests (`LLM_PROXY`). Without it, the standard
+    /// `HTTPS_PROXY` / `NO_PROXY` variables still apply.
+    pub proxy: Option<String>,
+    /// PEM client certificate for mTLS (`LLM_CLIENT_CERT`)
+    pub client_cert: Option<PathBuf>,
+    /// PEM private key for mTLS (`LLM_CLIENT_KEY`); may be omitted when the
+    /// certificate file also holds the key
+    pub client_key: Option<PathBuf>,
+    /// Extra PEM root certificate to trust (`LLM_CA_CERT`)
+    pub ca_cert: Option<PathBuf>,
+}
+
+impl TransportConfig {
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|s| !s.trim().is_empty());
+        Self {
+            proxy: var("LLM_PROXY"),
+            client_cert: var("LLM_CLIENT_CERT").map(PathBuf::from),
+            client_key: var("LLM_CLIENT_KEY").map(PathBuf::from),
+            ca_cert: var("LLM_CA_CERT").map(PathBuf::from),
+        }
+    }
+}
+
+/// Parsed, ready-to-apply form of [`TransportConfig`].
+struct Transport {
+    proxy: Option<reqwest::Proxy>,
+    identity: Option<reqwest::Identity>,
+    ca_cert: Option<reqwest::Certificate>,
+}
+
+impl Transport {
+    fn load(config: &TransportConfig) -> Result<Self, String> {
+        let read = |path: &PathBuf| {
+            std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))
+        };
+
+        let proxy = config
+            .proxy
+            .as_deref()
+            .map(|url| reqwest::Proxy::all(url).map_err(|e| format!("Invalid LLM_PROXY: {e}")))
+            .transpose()?;
+
+        let identity = match (&config.client_cert, &config.client_key) {
+            (Some(cert), key) => {
+                let mut pem = read(cert)?;
+                if let Some(key) = key {
+                    pem.push(b'\n');
+                    pem.extend(read(key)?);
+                }
+                let identity = reqwest::Identity::from_pem(&pem)
+                    .map_err(|e| format!("Invalid LLM_CLIENT_CERT/LLM_CLIENT_KEY: {e}"))?;
+                Some(identity)
+            }
+            (None, Some(_)) => return Err("LLM_CLIENT_KEY is set without LLM_CLIENT_CERT".into()),
+            (None, None) => None,
+        };
+
+        let ca_cert = config
+            .ca_cert
+            .as_ref()
+            .map(|path| {
+                reqwest::Certificate::from_pem(&read(path)?)
+                    .map_err(|e| format!("Invalid LLM_CA_CERT: {e}"))
+            })
+            .transpose()?;
+
+        let transport = Self {
+            proxy,
+            identity,
+            ca_cert,
+        };
+        // Certificates are only fully checked when a client is built, so
+        // build one now rather than failing on the first LLM request.
+        transport
+            .apply(reqwest::Client::builder())
+            .build()
+            .map_err(|e| format!("Invalid LLM transport settings: {e}"))?;
+        Ok(transport)
+    }
+
+    fn apply(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(identity.clone());
+        }
+        if let Some(cert) = &self.ca_cert {
+            builder = builder.add_root_certificate(cert.clone());
+        }
+        builder
+    }
+}
+
+static TRANSPORT: OnceLock<Transport> = OnceLock::new();
+
+/// Load and install the process-wide transport settings. Fails on an
+/// unreadable or invalid proxy URL, certificate, or key; calling it again
+/// after a successful install has no effect.
+pub fn install(config: &TransportConfig) -> Result<(), String> {
+    let transport = Transport::load(config)?;
+    let _ = TRANSPORT.set(transport);
+    Ok(())
+}
+
+/// `reqwest::Client::builder()` with the installed transport settings applied.
+pub fn client_builder() -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder();
+    match TRANSPORT.get() {
+        Some(transport) => transport.apply(builder),
+        None => builder,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_config_loads() {
+        let transport = Transport::load(&TransportConfig::default()).unwrap();
+        assert!(transport.proxy.is_none() && transport.identity.is_none());
+        assert!(transport.ca_cert.is_none());
+    }
+
+    #[test]
+    fn invalid_settings_are_reported() {
+        let missing = TransportConfig {
+            client_cert: Some(PathBuf::from("/nonexistent/client.pem")),
+            ..Default::default()
+        };
+        let err = Transport::load(&missing).err().unwrap();
+        assert!(err.contains("/nonexistent/client.pem"), "{err}");
+
+        let key_only = TransportConfig {
+            client_key: Some(PathBuf::from("/tmp/key.pem")),
+            ..Default::default()
+        };
+        assert!(Transport::load(&key_only).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let garbage = dir.path().join("client.pem");
+        std::fs::write(&garbage, "not a certificate").unwrap();
+        let bad_cert = TransportConfig {
+            client_cert: Some(garbage),
+            ..Default::default()
+        };
+        assert!(Transport::load(&bad_cert).is_err());
+
+        let bad_proxy = TransportConfig {
+            proxy: Some("::not a url::".to_string()),
+            ..Default::default()
+        };
+        assert!(Transport::load(&bad_proxy).is_err());
+    }
+}
@@ -53,6 +53,13 @@ impl PromptCacheKey {
     }
 }
 
+/// Smallest extended-thinking budget `Anthropic` accepts.
+pub const MIN_THINKING_BUDGET: u32 = 1_024;
+
+/// Largest budget a conversation may set. Leaves room for the answer inside
+/// the 32k output cap of the smallest thinking-capable model.
+pub const MAX_THINKING_BUDGET: u32 = 24_576;
+
 /// LLM request
 #[derive(Debug, Clone)]
 pub struct LlmRequest {
@@ -60,6 +67,9 @@ pub struct LlmRequest {
     pub messages: Vec<LlmMessage>,
     pub tools: Vec<ToolDefinition>,
     pub max_tokens: Option<u32>,
+    /// Extended-thinking budget in tokens (REQ-LLM-014). `None` disables
+    /// thinking. Honored by `Anthropic`; other providers ignore it.
+    pub thinking_budget: Option<u32>,
     /// Required cache key. See [`PromptCacheKey`] for how to pick one — the
     /// choice is the caller's because only the caller knows its caching
     /// cohort. Used as `prompt_cache_key` on the `OpenAI` Responses path,
@@ -128,6 +138,16 @@ pub enum ContentBlock {
         #[serde(default)]
         is_error: bool,
     },
+    /// Extended-thinking output (REQ-LLM-014). The signature must be sent
+    /// back unchanged with the block; `thinking` is empty once redacted.
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    /// Thinking the provider encrypted for safety reasons -- opaque round-trip.
+    RedactedThinking {
+        data: String,
+    },
 
     // ---- Server-handled blocks (Anthropic) ----
     // These blocks are executed by the API, not by Phoenix. They MUST be
@@ -287,12 +307,25 @@ pub struct Usage {
     pub cache_creation_tokens: u64,
     #[serde(default)]
     pub cache_read_tokens: u64,
+    /// Model that produced the response. Differs from the conversation's
+    /// model when a fallback answered (REQ-LLM-013).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub model: Option<String>,
 }
 
 impl Usage {
     pub fn context_window_used(&self) -> u64 {
         self.input_tokens + self.output_tokens + self.cache_creation_tokens + self.cache_read_tokens
     }
+
+    /// Share of prompt tokens served from the provider's prompt cache, or
+    /// `None` when the turn had no prompt tokens.
+    #[allow(clippy::cast_precision_loss)] // token counts are far below 2^52
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let prompt = self.input_tokens + self.cache_creation_tokens + self.cache_read_tokens;
+        (prompt > 0).then(|| self.cache_read_tokens as f64 / prompt as f64)
+    }
 }
 
 // ContentBlock serde and tool_uses() invariants are covered by property tests
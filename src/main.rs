@@ -4,14 +4,20 @@
 //! interacting with LLM agents.
 
 mod api;
+mod api_client;
 mod chain_qa;
 mod chain_runtime;
 mod db;
+mod eval;
+mod file_chunks;
 pub(crate) mod git_ops;
 mod llm;
 mod message_expander;
+mod phoenixignore;
 mod platform;
+mod push;
 mod runtime;
+mod secrets;
 pub mod skills;
 mod state_machine;
 mod system_prompt;
@@ -20,8 +26,12 @@ mod title_generator;
 mod tls;
 mod tls_certs;
 mod tools;
+mod tui;
 
-use api::{create_router, AppState};
+use api::{
+    create_router, run_headless, spawn_grpc_server, spawn_retention_task, AppState, GrpcConfig,
+    RateLimitConfig, RateLimitLayer, RetentionConfig, RunArgs,
+};
 use db::Database;
 use llm::{LlmConfig, ModelRegistry};
 use std::net::SocketAddr;
@@ -33,13 +43,49 @@ use tower_http::{
     cors::{Any, CorsLayer},
     trace::TraceLayer,
 };
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{
+    fmt::writer::BoxMakeWriter, layer::SubscriberExt, util::SubscriberInitExt,
+};
 
 mod hot_restart;
 
 #[tokio::main]
 #[allow(clippy::too_many_lines)] // Startup sequence is inherently sequential; splitting would obscure the flow.
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // REQ-CLI-010: client mode. Runs before logging so JSON log lines never
+    // land on the TUI's screen, and never touches the database.
+    if std::env::args().any(|a| a == "--tui") {
+        tui::run(tui::TuiConfig::new(cli_flag("--api-url"))).await?;
+        return Ok(());
+    }
+
+    // REQ-CLI-011: `phoenix-ide run` is a one-shot headless task. stdout is
+    // reserved for the agent's answer, so logs go to stderr, and the turn
+    // limit has to be in the environment before any runtime reads it.
+    // REQ-CLI-012: `phoenix-ide eval` replays fixtures the same way.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let parsed = RunArgs::parse(&args).and_then(|run| Ok((run, eval::EvalArgs::parse(&args)?)));
+    let (run_args, eval_args) = match parsed {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{message}");
+            std::process::exit(2);
+        }
+    };
+    let max_turns = run_args
+        .as_ref()
+        .and_then(|r| r.max_turns)
+        .or_else(|| eval_args.as_ref().and_then(|e| e.max_turns));
+    if let Some(max_turns) = max_turns {
+        std::env::set_var("PHOENIX_TURN_MAX_LLM_REQUESTS", max_turns.to_string());
+    }
+    let headless = run_args.is_some() || eval_args.is_some();
+    let log_writer = if headless {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+
     // Initialize logging
     tracing_subscriber::registry()
         .with(
@@ -48,6 +94,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
         .with(
             tracing_subscriber::fmt::layer()
+                .with_writer(log_writer)
                 .json()
                 .with_current_span(true)
                 .with_span_list(false),
@@ -91,35 +138,81 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::fs::create_dir_all(parent)?;
     }
 
+    // Restore from a named backup before anything opens the file (REQ-API-015)
+    if let Some(name) = cli_flag("--restore-backup") {
+        let db_file = PathBuf::from(&db_path);
+        let backups = db::backup::BackupConfig::from_env(&db_file);
+        let moved_aside = db::backup::restore_backup(&db_file, &backups.dir, &name)?;
+        tracing::warn!(
+            backup = %name,
+            previous = ?moved_aside,
+            "Database restored from backup"
+        );
+    }
+
     // Initialize database
     tracing::info!(path = %db_path, "Opening database");
     let db = Database::open(&db_path).await?;
 
-    // Run pending data migrations before anything reads conversation data
+    // `--rollback-migrations <version>`: undo numbered migrations newer than
+    // `version` and exit, so an older release can run against this database.
+    if let Some(target) = cli_flag("--rollback-migrations") {
+        let target: u32 = target
+            .parse()
+            .map_err(|_| format!("invalid --rollback-migrations version: {target}"))?;
+        let rolled_back = db::rollback_migrations(db.pool(), target).await?;
+        tracing::info!(
+            rolled_back,
+            target,
+            "Database migrations rolled back; exiting"
+        );
+        return Ok(());
+    }
+
+    // Verify migration history, then run pending migrations before anything
+    // reads conversation data
     db::run_pending_migrations(db.pool()).await?;
 
-    // Reset all conversations to idle on startup (REQ-BED-007)
-    db.reset_all_to_idle().await?;
-
-    // Reconcile worktrees: revert Work conversations whose worktree is missing
-    reconcile_worktrees(&db).await;
-
-    // REQ-CHN-005 startup sweep: any chain_qa row left in_flight from a
-    // previous process has no live stream behind it; flip it to abandoned
-    // so the UI shows a re-ask affordance instead of an indefinite spinner.
-    match db.sweep_in_flight_chain_qa().await {
-        Ok(0) => {}
-        Ok(n) => tracing::info!(
-            count = n,
-            "Swept stale in_flight chain_qa rows to abandoned"
-        ),
-        Err(e) => tracing::warn!(error = %e, "chain_qa startup sweep failed"),
+    // Startup recovery assumes this process owns every conversation. A
+    // headless run or eval may share the database with a live server, so it
+    // leaves that server's in-flight state alone.
+    if !headless {
+        // Locks left by a crashed or restarted server on this host would
+        // otherwise hold its conversations until they go stale (REQ-BED-052)
+        runtime::lock::release_dead_owners(&db).await;
+
+        // Reset all conversations to idle on startup (REQ-BED-007)
+        db.reset_all_to_idle().await?;
+
+        // Reconcile worktrees: revert Work conversations whose worktree is missing
+        reconcile_worktrees(&db).await;
+
+        // REQ-CHN-005 startup sweep: any chain_qa row left in_flight from a
+        // previous process has no live stream behind it; flip it to abandoned
+        // so the UI shows a re-ask affordance instead of an indefinite spinner.
+        match db.sweep_in_flight_chain_qa().await {
+            Ok(0) => {}
+            Ok(n) => tracing::info!(
+                count = n,
+                "Swept stale in_flight chain_qa rows to abandoned"
+            ),
+            Err(e) => tracing::warn!(error = %e, "chain_qa startup sweep failed"),
+        }
     }
 
     // Initialize LLM registry with model discovery
     let llm_config = LlmConfig::from_env();
+    // Proxy and mTLS settings must be in place before discovery runs, and a
+    // bad certificate should stop startup rather than fail every request.
+    llm::transport::install(&llm_config.transport)?;
     let credential_helper = llm_config.credential_helper.clone();
     let llm_registry = Arc::new(ModelRegistry::new_with_discovery(&llm_config).await);
+    // Keys set through the API replace the environment's (REQ-LLM-019)
+    match db.list_provider_keys().await {
+        Ok(keys) if !keys.is_empty() => llm_registry.reload_with_keys(&keys).await,
+        Ok(_) => {}
+        Err(e) => tracing::warn!(error = %e, "Cannot read stored provider keys"),
+    }
 
     if llm_registry.has_models() {
         tracing::info!(
@@ -188,6 +281,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     mcp_manager.start_background_discovery();
 
+    // Load WASM tool plugins before any runtime builds a tool registry
+    // (REQ-PLUG-001). Failures are logged per plugin and never block startup.
+    if let Some(dir) = crate::tools::plugin::plugins_dir() {
+        let plugins = crate::tools::plugin::load_dir(&dir).await;
+        if !plugins.is_empty() {
+            tracing::info!(count = plugins.len(), dir = %dir.display(), "Loaded WASM plugins");
+        }
+        crate::tools::plugin::install(plugins);
+    }
+
     // Read optional auth password (REQ-AUTH-001)
     let password = std::env::var("PHOENIX_PASSWORD")
         .ok()
@@ -207,6 +310,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     )
     .await;
 
+    if headless {
+        let data_dir = PathBuf::from(&db_path)
+            .parent()
+            .map_or_else(|| PathBuf::from("."), PathBuf::from);
+        let code = match (run_args, eval_args) {
+            (Some(run_args), _) => run_headless(&state, &run_args, &data_dir).await,
+            (None, Some(eval_args)) => eval::run_eval(&state, &eval_args, &data_dir).await,
+            (None, None) => unreachable!("headless implies run or eval arguments"),
+        };
+        crate::tools::bash::shutdown_kill_tree(state.runtime.bash_handles()).await;
+        state.runtime.release_runtime_locks().await;
+        std::process::exit(code);
+    }
+
     // Create router
     let cors = CorsLayer::new()
         .allow_origin(Any)
@@ -257,11 +374,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Hold an Arc to the bash handle registry so the shutdown kill-tree
     // pass (REQ-BASH-007) can reach it after `state` moves into the router.
     let bash_handles_for_shutdown = state.runtime.bash_handles().clone();
+    // Likewise for releasing runtime locks (REQ-BED-052).
+    let runtime_for_shutdown = state.runtime.clone();
+
+    // Optional retention policies (REQ-API-014). The task always runs since
+    // the operator's settings (REQ-API-028) can turn policies on later; its
+    // passes do nothing while none is configured.
+    let retention = RetentionConfig::from_env();
+    if let Some(retention) = retention {
+        tracing::info!(?retention, "Retention policies enabled");
+    }
+    spawn_retention_task(state.clone(), retention.map(|r| r.interval));
 
-    let app = create_router(state)
-        .layer(trace_layer)
-        .layer(cors)
-        .layer(compression);
+    // Optional gRPC mirror of the conversation API (REQ-API-020), sharing
+    // this state and runtime with the HTTP router.
+    if let Some(grpc) = GrpcConfig::from_env() {
+        spawn_grpc_server(state.clone(), grpc);
+    }
+
+    // Optional per-IP / per-token limits (REQ-AUTH-009). Installed inside the
+    // trace layer so rejected requests still show up in the access log.
+    let mut app = create_router(state);
+    if let Some(limits) = RateLimitConfig::from_env() {
+        tracing::info!(?limits, "Rate limiting enabled");
+        app = app.layer(RateLimitLayer::new(limits));
+    }
+    let app = app.layer(trace_layer).layer(cors).layer(compression);
 
     // Get listener (either from systemd socket activation or bind fresh)
     let addr = SocketAddr::from(([0, 0, 0, 0], port));
@@ -285,7 +423,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         );
 
         // Run server with graceful shutdown on signals
-        let server = axum::serve(listener, app);
+        // ConnectInfo gives the rate limiter the peer address (REQ-AUTH-009).
+        let server = axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        );
         server
             .with_graceful_shutdown(hot_restart::shutdown_signal())
             .await?;
@@ -297,6 +439,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // SHUTDOWN_KILL_GRACE_SECONDS so a stuck D-state child cannot delay
     // shutdown indefinitely.
     crate::tools::bash::shutdown_kill_tree(&bash_handles_for_shutdown).await;
+    runtime_for_shutdown.release_runtime_locks().await;
 
     // After graceful shutdown, check if we should hot restart
     // (This does not return if hot restart is performed)
@@ -305,6 +448,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Value of a one-shot startup flag, as `--flag <value>` or `--flag=<value>`.
+///
+/// Used for `--restore-backup` (REQ-API-015), `--rollback-migrations`, and
+/// the TUI's `--api-url` (REQ-CLI-010).
+/// These are command-line flags rather than env vars so they apply to one
+/// launch only: a leftover env var would repeat the operation on every
+/// restart.
+fn cli_flag(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(flag).and_then(|v| v.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
 /// Reconcile Work/Branch conversations whose worktree has been deleted.
 ///
 /// A worktree-bound conversation whose on-disk worktree has vanished is no
@@ -1,13 +1,16 @@
-//! Message expansion layer for inline references (REQ-IR-001 through REQ-IR-007)
+//! Message expansion layer for inline references (REQ-IR-001 through REQ-IR-007,
+//! REQ-IR-009)
 //!
-//! Resolves `@path/to/file` and `/skill-name` tokens in user messages before they
-//! reach the LLM, producing a `display_text` (stored in DB, shown in history) and
-//! an `llm_text` (delivered to the model with file/skill contents injected).
+//! Resolves `@path/to/file`, `/skill-name`, and leading `/command` prompt
+//! template tokens in user messages before they reach the LLM, producing a
+//! `display_text` (stored in DB, shown in history) and an `llm_text` (delivered
+//! to the model with file/skill/template contents injected).
 //!
 //! Path (`./`) references are not expanded here — they are autocomplete-only (Task 572).
 
 use std::path::{Path, PathBuf};
 
+use crate::db::PromptTemplate;
 use crate::system_prompt::discover_skills;
 
 /// The result of expanding a user message.
@@ -34,6 +37,8 @@ pub enum ExpansionError {
     FileNotFound { path: String },
     /// `@` reference points to a binary file
     FileNotText { path: String },
+    /// `@` reference points to a file excluded by `.phoenixignore` (REQ-BED-050)
+    FileIgnored { path: String },
     /// Skill was found but invocation failed (e.g., file read error)
     SkillInvocationFailed { name: String, error: String },
 }
@@ -45,6 +50,9 @@ impl std::fmt::Display for ExpansionError {
             Self::FileNotText { path } => {
                 write!(f, "File is binary and cannot be included: {path}")
             }
+            Self::FileIgnored { path } => {
+                write!(f, "File is excluded by .phoenixignore: {path}")
+            }
             Self::SkillInvocationFailed { name, error } => {
                 write!(f, "Skill '{name}' failed: {error}")
             }
@@ -58,6 +66,7 @@ impl ExpansionError {
         match self {
             Self::FileNotFound { .. } => "file_not_found",
             Self::FileNotText { .. } => "file_not_text",
+            Self::FileIgnored { .. } => "file_ignored",
             Self::SkillInvocationFailed { .. } => "skill_invocation_failed",
         }
     }
@@ -65,7 +74,9 @@ impl ExpansionError {
     /// The reference token that caused the error (`@path` or `/skill-name`)
     pub fn reference(&self) -> String {
         match self {
-            Self::FileNotFound { path } | Self::FileNotText { path } => format!("@{path}"),
+            Self::FileNotFound { path }
+            | Self::FileNotText { path }
+            | Self::FileIgnored { path } => format!("@{path}"),
             Self::SkillInvocationFailed { name, .. } => format!("/{name}"),
         }
     }
@@ -244,20 +255,34 @@ fn is_text_content(content: &[u8]) -> bool {
     !content.contains(&0) && std::str::from_utf8(content).is_ok()
 }
 
+/// Expand inline references with no prompt templates registered.
+#[cfg(test)]
+pub fn expand(text: &str, working_dir: &Path) -> Result<ExpandedMessage, ExpansionError> {
+    expand_with_templates(text, working_dir, &[])
+}
+
 /// Expand all inline references in `text` relative to `working_dir`.
 ///
 /// Tokenizes the ORIGINAL text once for both `@` and `/` sigils, then:
 /// 1. Checks for skill invocations (`/` sigil, validated against discovered skills).
 ///    Skill expansion replaces the entire message, so it takes priority and file
 ///    references in the original text are not expanded.
-/// 2. If no skill matched, expands `@file` references by inlining file contents.
+/// 2. If the message starts with `/command` for one of `templates`, replaces
+///    `llm_text` with the rendered template (REQ-IR-009).
+/// 3. Expands `@file` references by inlining file contents. For a template,
+///    this still applies to `@file` tokens in the arguments, which land
+///    verbatim in the rendered text.
 ///
 /// Tokenizing the original text (not skill-expanded text) prevents skill output
 /// from accidentally introducing `@` tokens that trigger file expansion.
 ///
 /// Returns `Ok(ExpandedMessage)` when all references resolve successfully.
 /// Returns the first `Err(ExpansionError)` encountered when any reference fails.
-pub fn expand(text: &str, working_dir: &Path) -> Result<ExpandedMessage, ExpansionError> {
+pub fn expand_with_templates(
+    text: &str,
+    working_dir: &Path,
+    templates: &[PromptTemplate],
+) -> Result<ExpandedMessage, ExpansionError> {
     let refs = tokenize_references(text, &['/', '@']);
 
     // --- Skill expansion (REQ-IR-002, REQ-IR-003) ----------------------------
@@ -289,8 +314,20 @@ pub fn expand(text: &str, working_dir: &Path) -> Result<ExpandedMessage, Expansi
         }
     }
 
-    // --- File reference expansion (REQ-IR-001, REQ-IR-007) ---------------------
+    // --- Prompt template expansion (REQ-IR-009) --------------------------------
     let mut llm_text = text.to_string();
+    let leading_ws = text.len() - text.trim_start().len();
+    if let Some(cmd_ref) = refs
+        .iter()
+        .find(|r| r.sigil == '/' && r.span.start == leading_ws)
+    {
+        if let Some(template) = templates.iter().find(|t| t.command == cmd_ref.token) {
+            let arguments = text.get(cmd_ref.span.end..).unwrap_or("").trim_start();
+            llm_text = render_template(&template.template, arguments, working_dir);
+        }
+    }
+
+    // --- File reference expansion (REQ-IR-001, REQ-IR-007) ---------------------
     let file_refs: Vec<_> = refs.iter().filter(|r| r.sigil == '@').collect();
 
     for file_ref in file_refs {
@@ -309,6 +346,13 @@ pub fn expand(text: &str, working_dir: &Path) -> Result<ExpandedMessage, Expansi
             });
         }
 
+        // Keep workspace-ignored files out of the prompt (REQ-BED-050)
+        if crate::phoenixignore::PhoenixIgnore::path_is_ignored(&full_path) {
+            return Err(ExpansionError::FileIgnored {
+                path: file_ref.token.clone(),
+            });
+        }
+
         // Read contents
         let content = std::fs::read(&full_path).map_err(|_| ExpansionError::FileNotFound {
             path: file_ref.token.clone(),
@@ -338,6 +382,19 @@ pub fn expand(text: &str, working_dir: &Path) -> Result<ExpandedMessage, Expansi
     })
 }
 
+/// Render a prompt template: `$CWD` becomes the working directory, and the
+/// arguments are substituted with the same `$ARGUMENTS` / `$N` rules as
+/// skills (REQ-SK-004). With no arguments, `$ARGUMENTS` is dropped rather
+/// than reaching the model literally.
+fn render_template(template: &str, arguments: &str, working_dir: &Path) -> String {
+    let body = template.replace("$CWD", &working_dir.display().to_string());
+    if arguments.is_empty() {
+        body.replace("$ARGUMENTS", "").trim_end().to_string()
+    } else {
+        crate::skills::substitute_arguments(&body, arguments)
+    }
+}
+
 /// Resolve a reference path to an absolute filesystem path.
 ///
 /// Absolute paths are used as-is; relative paths are joined to `working_dir`.
@@ -634,6 +691,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_expand_phoenixignored_file_error() {
+        let tmp = make_tmp();
+        fs::create_dir(tmp.path().join("secrets")).unwrap();
+        fs::write(tmp.path().join("secrets/prod.env"), "TOKEN=x").unwrap();
+        fs::write(tmp.path().join(".phoenixignore"), "secrets/\n").unwrap();
+
+        let err = expand("use @secrets/prod.env", tmp.path()).unwrap_err();
+        assert_eq!(
+            err,
+            ExpansionError::FileIgnored {
+                path: "secrets/prod.env".to_string()
+            }
+        );
+        assert_eq!(err.error_type(), "file_ignored");
+    }
+
     #[test]
     fn test_error_type_strings() {
         assert_eq!(
@@ -983,4 +1057,68 @@ mod tests {
         assert!(!looks_like_file_path("repo//pkg:target"));
         assert!(!looks_like_file_path("https://example.com/docs"));
     }
+
+    // -------------------------------------------------------------------------
+    // Prompt templates (REQ-IR-009)
+    // -------------------------------------------------------------------------
+
+    fn template(command: &str, body: &str) -> PromptTemplate {
+        PromptTemplate {
+            command: command.to_string(),
+            description: String::new(),
+            argument_hint: None,
+            template: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_template_expands_with_arguments_and_cwd() {
+        let tmp = make_tmp();
+        let templates = [template("review", "Review in $CWD.\nScope: $ARGUMENTS")];
+        let result = expand_with_templates("/review the parser", tmp.path(), &templates).unwrap();
+        assert_eq!(result.display_text, "/review the parser");
+        assert_eq!(
+            result.llm_text,
+            format!("Review in {}.\nScope: the parser", tmp.path().display())
+        );
+        assert!(result.skill_invocation.is_none());
+    }
+
+    #[test]
+    fn test_template_without_arguments_drops_placeholder() {
+        let tmp = make_tmp();
+        let templates = [template("explain", "Explain it.\nTarget: $ARGUMENTS")];
+        let result = expand_with_templates("/explain", tmp.path(), &templates).unwrap();
+        assert_eq!(result.llm_text, "Explain it.\nTarget:");
+    }
+
+    #[test]
+    fn test_template_only_matches_leading_command() {
+        let tmp = make_tmp();
+        let templates = [template("review", "TEMPLATE")];
+        let result = expand_with_templates("please /review this", tmp.path(), &templates).unwrap();
+        assert_eq!(result.llm_text, "please /review this");
+    }
+
+    #[test]
+    fn test_template_arguments_expand_file_refs() {
+        let tmp = make_tmp();
+        fs::write(tmp.path().join("lib.rs"), "fn lib() {}").unwrap();
+        let templates = [template("tests", "Test $ARGUMENTS")];
+        let result = expand_with_templates("/tests @lib.rs", tmp.path(), &templates).unwrap();
+        assert_eq!(
+            result.llm_text,
+            "Test <file path=\"lib.rs\">\nfn lib() {}\n</file>"
+        );
+    }
+
+    #[test]
+    fn test_skill_wins_over_template_of_same_name() {
+        let tmp = make_tmp();
+        write_skill(tmp.path(), "review", "review", "Skill", "SKILL BODY");
+        let templates = [template("review", "TEMPLATE")];
+        let result = expand_with_templates("/review", tmp.path(), &templates).unwrap();
+        assert!(result.llm_text.contains("SKILL BODY"));
+        assert!(result.skill_invocation.is_some());
+    }
 }
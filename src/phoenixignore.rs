@@ -0,0 +1,131 @@
+//! Workspace ignore rules (REQ-BED-050)
+//!
+//! A `.phoenixignore` file uses gitignore syntax and applies to its own
+//! directory and everything below it, like a `.gitignore`. Paths it matches
+//! stay out of the agent's searches, the file browser, and `@file`
+//! expansion, so a vendored `node_modules` or a directory of secrets never
+//! reaches an index or a prompt even when git tracks it.
+//!
+//! Directory walkers built on [`ignore::WalkBuilder`] register [`FILE_NAME`]
+//! as a custom ignore file; single-path checks use [`PhoenixIgnore`].
+
+use ignore::gitignore::Gitignore;
+use ignore::Match;
+use std::path::{Path, PathBuf};
+
+/// Name of the ignore file, looked up in every directory.
+pub const FILE_NAME: &str = ".phoenixignore";
+
+/// The nearest `.phoenixignore` at or above `dir`, if any.
+pub fn nearest(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join(FILE_NAME))
+        .find(|file| file.is_file())
+}
+
+/// Every `.phoenixignore` from a directory up to the filesystem root.
+pub struct PhoenixIgnore {
+    /// One matcher per file, nearest first. Each is rooted at the directory
+    /// holding its file, so anchored patterns resolve the way git would.
+    layers: Vec<Gitignore>,
+}
+
+impl PhoenixIgnore {
+    /// Load the ignore files that apply to `dir` and its contents. Files
+    /// below `dir` are not read; callers checking deeper paths load from
+    /// the path's own directory.
+    pub fn load(dir: &Path) -> Self {
+        let layers = dir
+            .ancestors()
+            .map(|d| d.join(FILE_NAME))
+            .filter(|file| file.is_file())
+            .map(|file| {
+                let (matcher, error) = Gitignore::new(&file);
+                if let Some(e) = error {
+                    tracing::warn!(path = %file.display(), error = %e, "Bad .phoenixignore line");
+                }
+                matcher
+            })
+            .collect();
+        Self { layers }
+    }
+
+    /// Whether `path` (absolute) or one of its parents is ignored. The
+    /// nearest file with an opinion wins, so a `!pattern` in a nested file
+    /// re-includes what a parent file excluded.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for layer in &self.layers {
+            if !path.starts_with(layer.path()) {
+                continue;
+            }
+            match layer.matched_path_or_any_parents(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+
+    /// Load the rules for `path`'s directory and check `path` against them.
+    pub fn path_is_ignored(path: &Path) -> bool {
+        let is_dir = path.is_dir();
+        let dir = if is_dir { Some(path) } else { path.parent() };
+        dir.is_some_and(|dir| Self::load(dir).is_ignored(path, is_dir))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn nested_files_override_their_parents() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("app/secrets")).unwrap();
+        fs::create_dir_all(root.join("app/node_modules/pkg")).unwrap();
+        fs::write(root.join(FILE_NAME), "node_modules/\nsecrets/\n*.pem\n").unwrap();
+        fs::write(root.join("app").join(FILE_NAME), "!public.pem\n").unwrap();
+
+        let rules = PhoenixIgnore::load(&root.join("app"));
+        assert!(rules.is_ignored(&root.join("app/secrets"), true));
+        assert!(rules.is_ignored(&root.join("app/node_modules/pkg/index.js"), false));
+        assert!(rules.is_ignored(&root.join("app/key.pem"), false));
+        assert!(!rules.is_ignored(&root.join("app/public.pem"), false));
+        assert!(!rules.is_ignored(&root.join("app/main.rs"), false));
+        // Paths outside every layer are never ignored
+        assert!(!rules.is_ignored(Path::new("/elsewhere/key.pem"), false));
+
+        fs::write(root.join("app/secrets/token.txt"), "x").unwrap();
+        assert!(PhoenixIgnore::path_is_ignored(
+            &root.join("app/secrets/token.txt")
+        ));
+        assert_eq!(
+            nearest(&root.join("app/secrets")),
+            Some(root.join("app").join(FILE_NAME))
+        );
+    }
+
+    #[test]
+    fn walkers_skip_ignored_paths() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("target/out.rs"), "").unwrap();
+        fs::write(root.join("lib.rs"), "").unwrap();
+        fs::write(root.join(FILE_NAME), "target/\n").unwrap();
+
+        let files: Vec<String> = ignore::WalkBuilder::new(root)
+            .add_custom_ignore_filename(FILE_NAME)
+            .build()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        assert!(files.contains(&"lib.rs".to_string()), "{files:?}");
+        assert!(!files.contains(&"out.rs".to_string()), "{files:?}");
+    }
+}
@@ -0,0 +1,351 @@
+//! Web Push notifications (REQ-API-024)
+//!
+//! A browser subscribes through the service worker with the server's VAPID
+//! public key, and conversations opt in one at a time. When an opted-in
+//! conversation finishes a turn or lands in `Error`, every stored
+//! subscription gets an encrypted push (RFC 8291) signed with the VAPID key
+//! (RFC 8292), so a long run can report back while its tab sits in the
+//! background. The key pair is generated on first use and kept in the
+//! database, so subscriptions outlive restarts. Subscriptions the push
+//! service reports as gone are dropped.
+
+use crate::db::{Conversation, Database, PushSubscription};
+use crate::runtime::SseEvent;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::Utc;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use web_push::{
+    ContentEncoding, SubscriptionInfo, VapidSignatureBuilder, WebPushMessage, WebPushMessageBuilder,
+};
+
+/// `sub` claim of the VAPID JWT: who the push service can contact about
+/// this sender. Override with `PHOENIX_VAPID_SUBJECT` (a `mailto:` or
+/// `https:` URL).
+const DEFAULT_SUBJECT: &str = "https://github.com/scottopell/phoenix-ide";
+
+/// How long a push service holds a notification for a device that is
+/// offline. A finished run is old news after a day.
+const TTL_SECONDS: u32 = 24 * 60 * 60;
+
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the service worker shows. `tag` is the conversation id, so a newer
+/// notification for a conversation replaces the older one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+    /// Page opened when the notification is clicked.
+    pub url: String,
+    pub tag: String,
+}
+
+struct VapidKeys {
+    private_key_pem: String,
+    /// Uncompressed P-256 point, base64url: the browser's
+    /// `applicationServerKey`.
+    public_key: String,
+}
+
+/// Sends Web Push notifications for opted-in conversations.
+pub struct PushNotifier {
+    db: Database,
+    keys: OnceCell<VapidKeys>,
+    subject: String,
+    client: reqwest::Client,
+}
+
+impl PushNotifier {
+    pub fn new(db: Database) -> Self {
+        let subject = std::env::var("PHOENIX_VAPID_SUBJECT")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_SUBJECT.to_string());
+        let client = reqwest::Client::builder()
+            .timeout(SEND_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            db,
+            keys: OnceCell::new(),
+            subject,
+            client,
+        }
+    }
+
+    /// The VAPID key pair, loaded or generated on first use.
+    async fn keys(&self) -> Result<&VapidKeys, String> {
+        self.keys
+            .get_or_try_init(|| async {
+                let generated = rcgen::KeyPair::generate()
+                    .map_err(|e| format!("cannot generate VAPID key: {e}"))?
+                    .serialize_pem();
+                let private_key_pem = self
+                    .db
+                    .get_or_insert_vapid_key(&generated, Utc::now())
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let public_key = public_key_of(&private_key_pem)?;
+                Ok(VapidKeys {
+                    private_key_pem,
+                    public_key,
+                })
+            })
+            .await
+    }
+
+    /// The VAPID public key browsers subscribe with.
+    pub async fn public_key(&self) -> Result<String, String> {
+        Ok(self.keys().await?.public_key.clone())
+    }
+
+    /// Notify subscribers about `event` if it ends a turn of an opted-in
+    /// conversation. Failures are logged; nothing here affects the
+    /// conversation.
+    pub async fn conversation_event(&self, conversation_id: &str, event: &SseEvent) {
+        if !matches!(
+            event,
+            SseEvent::AgentDone { .. } | SseEvent::ErrorRemediation { .. }
+        ) {
+            return;
+        }
+        match self.db.is_push_enabled(conversation_id).await {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                tracing::warn!(conv_id = %conversation_id, error = %e, "push opt-in lookup failed");
+                return;
+            }
+        }
+        let Ok(conv) = self.db.get_conversation(conversation_id).await else {
+            return;
+        };
+        let (title, url) = conversation_link(&conv);
+        if let Some(notification) = notification(event, conversation_id, &title, &url) {
+            self.notify(&notification).await;
+        }
+    }
+
+    /// Push `notification` to every subscription, dropping the ones the
+    /// push service no longer knows.
+    pub async fn notify(&self, notification: &Notification) {
+        let subscriptions = match self.db.list_push_subscriptions().await {
+            Ok(subs) if !subs.is_empty() => subs,
+            Ok(_) => return,
+            Err(e) => {
+                tracing::warn!(error = %e, "cannot list push subscriptions");
+                return;
+            }
+        };
+        let keys = match self.keys().await {
+            Ok(keys) => keys,
+            Err(e) => {
+                tracing::warn!(error = %e, "VAPID keys unavailable; not pushing");
+                return;
+            }
+        };
+        let Ok(payload) = serde_json::to_vec(notification) else {
+            return;
+        };
+        for subscription in subscriptions {
+            match self.send(keys, &subscription, &payload).await {
+                Ok(Delivery::Sent) => {}
+                Ok(Delivery::Gone) => {
+                    tracing::info!(endpoint = %subscription.endpoint, "push subscription expired");
+                    let _ = self
+                        .db
+                        .delete_push_subscription(&subscription.endpoint)
+                        .await;
+                }
+                Err(e) => {
+                    tracing::warn!(endpoint = %subscription.endpoint, error = %e, "push failed");
+                }
+            }
+        }
+    }
+
+    async fn send(
+        &self,
+        keys: &VapidKeys,
+        subscription: &PushSubscription,
+        payload: &[u8],
+    ) -> Result<Delivery, String> {
+        let message = build_message(keys, &self.subject, subscription, payload)?;
+        let mut request = self
+            .client
+            .post(message.endpoint.to_string())
+            .header("TTL", message.ttl.to_string());
+        if let Some(payload) = message.payload {
+            request = request
+                .header("Content-Encoding", payload.content_encoding.to_str())
+                .header("Content-Type", "application/octet-stream");
+            // Carries the VAPID `Authorization` header too.
+            for (name, value) in payload.crypto_headers {
+                request = request.header(name, value);
+            }
+            request = request.body(payload.content);
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(Delivery::Sent)
+        } else if matches!(status, StatusCode::NOT_FOUND | StatusCode::GONE) {
+            Ok(Delivery::Gone)
+        } else {
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("push service returned {status}: {body}"))
+        }
+    }
+}
+
+enum Delivery {
+    Sent,
+    /// The subscription was revoked or expired.
+    Gone,
+}
+
+/// A conversation's display title and the page that shows it.
+pub fn conversation_link(conv: &Conversation) -> (String, String) {
+    let title = conv
+        .title
+        .clone()
+        .or_else(|| conv.slug.clone())
+        .unwrap_or_else(|| conv.id.clone());
+    let url = conv
+        .slug
+        .as_deref()
+        .map_or_else(|| "/".to_string(), |slug| format!("/c/{slug}"));
+    (title, url)
+}
+
+fn build_message(
+    keys: &VapidKeys,
+    subject: &str,
+    subscription: &PushSubscription,
+    payload: &[u8],
+) -> Result<WebPushMessage, String> {
+    let info = SubscriptionInfo::new(
+        &subscription.endpoint,
+        &subscription.p256dh,
+        &subscription.auth,
+    );
+    let mut signature = VapidSignatureBuilder::from_pem(keys.private_key_pem.as_bytes(), &info)
+        .map_err(|e| e.to_string())?;
+    signature.add_claim("sub", subject);
+    let signature = signature.build().map_err(|e| e.to_string())?;
+
+    let mut builder = WebPushMessageBuilder::new(&info);
+    builder.set_ttl(TTL_SECONDS);
+    builder.set_payload(ContentEncoding::Aes128Gcm, payload);
+    builder.set_vapid_signature(signature);
+    builder.build().map_err(|e| e.to_string())
+}
+
+fn public_key_of(private_key_pem: &str) -> Result<String, String> {
+    let partial = VapidSignatureBuilder::from_pem_no_sub(private_key_pem.as_bytes())
+        .map_err(|e| format!("invalid VAPID key: {e}"))?;
+    Ok(URL_SAFE_NO_PAD.encode(partial.get_public_key()))
+}
+
+/// The notification for `event`, or `None` if it does not end a turn.
+fn notification(
+    event: &SseEvent,
+    conversation_id: &str,
+    title: &str,
+    url: &str,
+) -> Option<Notification> {
+    let (title, body) = match event {
+        SseEvent::AgentDone { .. } => (title.to_string(), "The agent finished.".to_string()),
+        SseEvent::ErrorRemediation { remediation, .. } => (
+            format!("{title}: {}", remediation.title),
+            remediation.suggestion.clone(),
+        ),
+        _ => return None,
+    };
+    Some(Notification {
+        title,
+        body,
+        url: url.to_string(),
+        tag: conversation_id.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::remediation::{ErrorCategory, Remediation};
+
+    #[test]
+    fn turn_endings_become_notifications() {
+        let done = SseEvent::AgentDone { sequence_id: 7 };
+        let n = notification(&done, "c1", "Fix Login Page", "/c/fix-login").unwrap();
+        assert_eq!(n.title, "Fix Login Page");
+        assert_eq!(n.body, "The agent finished.");
+        assert_eq!((n.url.as_str(), n.tag.as_str()), ("/c/fix-login", "c1"));
+
+        let error = SseEvent::ErrorRemediation {
+            sequence_id: 8,
+            remediation: Remediation {
+                category: ErrorCategory::Auth,
+                title: "Authentication failed".to_string(),
+                suggestion: "Check your API key.".to_string(),
+                actions: vec![],
+            },
+        };
+        let n = notification(&error, "c1", "Fix Login Page", "/c/fix-login").unwrap();
+        assert_eq!(n.title, "Fix Login Page: Authentication failed");
+        assert_eq!(n.body, "Check your API key.");
+
+        let token = SseEvent::Token {
+            sequence_id: 9,
+            text: "hi".to_string(),
+            request_id: "r".to_string(),
+        };
+        assert_eq!(notification(&token, "c1", "t", "/"), None);
+    }
+
+    #[tokio::test]
+    async fn vapid_key_is_generated_once() {
+        let db = Database::open_in_memory().await.unwrap();
+        let key = PushNotifier::new(db.clone()).public_key().await.unwrap();
+        let raw = URL_SAFE_NO_PAD.decode(&key).unwrap();
+        // Uncompressed P-256 point
+        assert_eq!(raw.len(), 65);
+        assert_eq!(raw[0], 0x04);
+
+        let again = PushNotifier::new(db).public_key().await.unwrap();
+        assert_eq!(again, key);
+    }
+
+    #[test]
+    fn messages_are_encrypted_and_signed() {
+        let pem = rcgen::KeyPair::generate().unwrap().serialize_pem();
+        let keys = VapidKeys {
+            public_key: public_key_of(&pem).unwrap(),
+            private_key_pem: pem,
+        };
+        // A browser's subscription keys, from the web-push crate's examples
+        let subscription = PushSubscription {
+            endpoint: "https://push.example.com/send/abc".to_string(),
+            p256dh: concat!(
+                "BLMbF9ffKBiWQLCKvTHb6LO8Nb6dcUh6TItC455vu2kElga6PQvUmaFyCdykxY2n",
+                "OSSL3yKgfbmFLRTUaGv4yV8",
+            )
+            .to_string(),
+            auth: "xS03Fi5ErfTNH_l9WHE9Ig".to_string(),
+            created_at: Utc::now(),
+        };
+        let message = build_message(&keys, DEFAULT_SUBJECT, &subscription, b"{}").unwrap();
+        assert_eq!(message.ttl, TTL_SECONDS);
+        let payload = message.payload.unwrap();
+        assert_eq!(payload.content_encoding.to_str(), "aes128gcm");
+        assert!(payload
+            .crypto_headers
+            .iter()
+            .any(|(name, value)| *name == "Authorization" && value.starts_with("vapid ")));
+    }
+}
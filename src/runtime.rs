@@ -9,10 +9,19 @@
 //! REQ-BED-008: Sub-Agent Spawning
 //! REQ-BED-009: Sub-Agent Isolation
 
+mod continuation;
+pub mod council;
 pub(crate) mod executor;
+pub mod follow_up;
+mod history;
+pub mod lock;
+pub mod presence;
 mod recovery;
+pub mod remediation;
 pub mod traits;
 pub mod user_facing_error;
+pub mod verify;
+mod watcher;
 
 #[cfg(test)]
 pub mod testing;
@@ -21,21 +30,24 @@ pub use executor::ConversationRuntime;
 pub use traits::*;
 
 use crate::platform::PlatformCapability;
-use crate::state_machine::state::{ModeKind, SubAgentMode, SubAgentOutcome, SubAgentSpec};
+use crate::state_machine::state::{
+    ModeKind, SubAgentBatch, SubAgentMode, SubAgentOutcome, SubAgentSpec,
+};
 use crate::tools::{BashHandleRegistry, BrowserSessionManager, TmuxRegistry, ToolRegistry};
+use continuation::ContinuationRequest;
 
 /// Type alias for production runtime with concrete implementations
 pub type ProductionRuntime =
     ConversationRuntime<DatabaseStorage, RegistryLlmClient, ToolRegistryExecutor>;
 
-use crate::db::{ConvMode, Database};
+use crate::db::{ConvMode, Conversation, Database, ServerSettings};
 use crate::llm::ModelRegistry;
 use crate::state_machine::{ConvContext, ConvState, Event};
 use crate::system_prompt::ModeContext;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicI64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, PoisonError, Weak};
 use tokio::sync::{broadcast, mpsc, RwLock};
 
 /// Request to spawn a sub-agent
@@ -80,8 +92,34 @@ pub struct RuntimeManager {
     /// Channel for sub-agent cancel requests
     cancel_tx: mpsc::Sender<SubAgentCancelRequest>,
     cancel_rx: RwLock<Option<mpsc::Receiver<SubAgentCancelRequest>>>,
+    /// Channel for automatic continuation requests (REQ-BED-044)
+    continue_tx: mpsc::Sender<ContinuationRequest>,
+    continue_rx: RwLock<Option<mpsc::Receiver<ContinuationRequest>>>,
+    /// `PHOENIX_AUTO_CONTINUE`: whether parent runtimes get `continue_tx`.
+    auto_continue: bool,
     /// Credential helper for recovery settlement (REQ-BED-030).
     credential_helper: Option<Arc<crate::llm::CredentialHelper>>,
+    /// Which clients are streaming each conversation and who holds the
+    /// composer (REQ-API-013).
+    presence: Arc<presence::PresenceRegistry>,
+    /// LLM record/replay cassette from `PHOENIX_LLM_MODE` (REQ-LLM-011).
+    llm_cassette: Option<Arc<crate::llm::LlmCassette>>,
+    /// LLM traffic log from `PHOENIX_LLM_LOG` (REQ-LLM-017).
+    llm_log: Option<Arc<crate::llm::LlmTrafficLog>>,
+    /// First-turn batches of sub-agents spawned together, by
+    /// `SubAgentBatch::key` (REQ-SA-009). Held weakly so a group is dropped
+    /// once its sub-agents have sent their first requests.
+    batch_groups: Mutex<HashMap<String, Weak<crate::llm::BatchGroup>>>,
+    /// Web Push for conversations that opted in (REQ-API-024).
+    push: Arc<crate::push::PushNotifier>,
+    /// This process as the owner of runtime locks (REQ-BED-052).
+    lock_owner: String,
+    /// `PHOENIX_WATCH_FILES`: whether conversations watch their cwd for
+    /// outside changes (REQ-BED-059).
+    watch_files: bool,
+    /// Files the user just wrote through the files API, which the watcher
+    /// does not report again (REQ-FE-011).
+    user_writes: Mutex<HashMap<PathBuf, std::time::Instant>>,
 }
 
 /// Handle to interact with a running conversation
@@ -103,13 +141,39 @@ pub struct ConversationHandle {
 /// (a background tab, a sleeping laptop resume, a long GC pause) during
 /// active LLM streaming. At ~50 tokens/sec this buys ~80 seconds of headroom.
 ///
-/// When the channel overflows, `BroadcastStreamRecvError::Lagged` fires on
-/// the receive side. We handle that in `api::sse::sse_stream` by closing the
-/// stream — the client reconnects, `init` replays current state, and no
-/// silent gap results. Increasing this value reduces how often that resync
-/// dance happens; it does not change correctness.
+/// When the channel overflows, `RecvError::Lagged` fires on the receive
+/// side. We handle that in `api::sse::sse_stream` by resyncing the client in
+/// place — replaying from the buffer or sending a fresh `init` with the
+/// messages it missed — so no silent gap results. Increasing this value
+/// reduces how often that resync happens; it does not change correctness.
 pub const SSE_BROADCAST_CAPACITY: usize = 4096;
 
+/// How many recent events each conversation keeps for `Last-Event-ID`
+/// resume (REQ-API-005). A client that missed more than this gets a full
+/// `init` snapshot instead, which is cheaper than replaying thousands of
+/// tokens anyway.
+pub const SSE_REPLAY_CAPACITY: usize = 1024;
+
+/// Recently broadcast events, replayed to a client reconnecting with
+/// `Last-Event-ID`.
+struct ReplayBuffer {
+    events: VecDeque<SseEvent>,
+    /// Highest `sequence_id` that can no longer be replayed: either evicted
+    /// from `events` or emitted before this broadcaster existed.
+    floor: i64,
+}
+
+impl ReplayBuffer {
+    fn push(&mut self, event: SseEvent) {
+        if self.events.len() == SSE_REPLAY_CAPACITY {
+            if let Some(evicted) = self.events.pop_front() {
+                self.floor = self.floor.max(evicted.sequence_id());
+            }
+        }
+        self.events.push_back(event);
+    }
+}
+
 /// Per-conversation SSE broadcaster with monotonic `sequence_id` allocation.
 ///
 /// Every [`SseEvent`] emitted for a conversation carries a `sequence_id` drawn
@@ -138,6 +202,10 @@ pub struct SseBroadcaster {
     /// bumps this value up to at least `s` so message-originated ids integrate
     /// into the same total order.
     last_seq: Arc<AtomicI64>,
+    /// Recent events for `Last-Event-ID` resume. Held across `tx.send` so a
+    /// resuming subscriber sees every event exactly once: either in the
+    /// replayed batch or on its new receiver.
+    replay: Arc<Mutex<ReplayBuffer>>,
 }
 
 impl SseBroadcaster {
@@ -150,6 +218,10 @@ impl SseBroadcaster {
         Self {
             tx,
             last_seq: Arc::new(AtomicI64::new(initial_last_seq)),
+            replay: Arc::new(Mutex::new(ReplayBuffer {
+                events: VecDeque::new(),
+                floor: initial_last_seq,
+            })),
         }
     }
 
@@ -188,6 +260,24 @@ impl SseBroadcaster {
         self.tx.subscribe()
     }
 
+    /// Subscribe for a client that has already seen every event up to
+    /// `last_seen` (its `Last-Event-ID`). Returns the events it missed plus a
+    /// receiver for everything after them, or `None` when the gap reaches
+    /// past the replay buffer and the client needs a full `init` instead.
+    pub fn resume(&self, last_seen: i64) -> Option<(Vec<SseEvent>, broadcast::Receiver<SseEvent>)> {
+        let replay = self.replay.lock().unwrap_or_else(PoisonError::into_inner);
+        if last_seen < replay.floor || last_seen > self.current_seq() {
+            return None;
+        }
+        let missed = replay
+            .events
+            .iter()
+            .filter(|e| e.sequence_id() > last_seen)
+            .cloned()
+            .collect();
+        Some((missed, self.tx.subscribe()))
+    }
+
     /// Send an event that has already been stamped with a `sequence_id`.
     /// Private on purpose — callers must go through [`SseBroadcaster::send_seq`]
     /// or [`SseBroadcaster::send_message`] so the stamping is done at the
@@ -198,7 +288,12 @@ impl SseBroadcaster {
     /// `broadcast::error::SendError<SseEvent>` is ~320 bytes, which triggers
     /// clippy's `result_large_err` lint, and every call site here only ever
     /// reads `.is_err()`.
+    ///
+    /// Events are recorded for replay even when nobody is listening: that is
+    /// exactly when a reconnecting client will need them.
     fn send(&self, event: SseEvent) -> Result<usize, ()> {
+        let mut replay = self.replay.lock().unwrap_or_else(PoisonError::into_inner);
+        replay.push(event.clone());
         self.tx.send(event).map_err(|_| ())
     }
 
@@ -238,6 +333,11 @@ pub struct ConversationMetadataUpdate {
     pub commits_ahead: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub task_title: Option<String>,
+    /// Set when the conversation is retitled (REQ-API-012).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
 }
 
 /// A conversation enriched with derived display fields for the API layer.
@@ -327,6 +427,8 @@ pub enum SseEvent {
         commits_ahead: u32,
         /// Human-readable project name derived from the repo root directory name.
         project_name: Option<String>,
+        /// Unsent message the user left in the composer (REQ-BED-047).
+        draft: Option<crate::db::ConversationDraft>,
     },
     /// A newly-persisted message joins the conversation. Uses `message.sequence_id`
     /// as its envelope `sequence_id` — no separate field needed because
@@ -388,6 +490,31 @@ pub enum SseEvent {
         sequence_id: i64,
         error: user_facing_error::UserFacingError,
     },
+    /// Recovery suggestion emitted once when the conversation enters
+    /// `Error` (REQ-BED-035). Follows the `StateChange` that carried the
+    /// error state.
+    ErrorRemediation {
+        sequence_id: i64,
+        remediation: remediation::Remediation,
+    },
+    /// The latest turn's context usage reached a warning threshold
+    /// (REQ-BED-043). Sent once per threshold so the UI can suggest
+    /// compaction before the window runs out.
+    ContextWarning {
+        sequence_id: i64,
+        /// Tokens in the context window as of the latest turn.
+        used: u64,
+        /// The model's context window size in tokens.
+        limit: u64,
+        percent: u64,
+    },
+    /// The conversation ran out of context and was continued automatically
+    /// in `conversation_id` (REQ-BED-044). Clients follow it there.
+    ConversationContinued {
+        sequence_id: i64,
+        conversation_id: String,
+        slug: Option<String>,
+    },
     /// REQ-BED-032 step 6: emitted exactly once after a hard-delete cascade
     /// completes. UI consumers (sidebar, navigation) use it to refresh
     /// views. The `conversation_id` field is redundant with the broadcaster
@@ -398,6 +525,60 @@ pub enum SseEvent {
         sequence_id: i64,
         conversation_id: String,
     },
+    /// A client opened an SSE stream on this conversation (REQ-API-013).
+    /// Carries the full presence snapshot so the joining client learns who
+    /// else is connected without a separate fetch.
+    ClientJoined {
+        sequence_id: i64,
+        client_id: String,
+        clients: Vec<String>,
+        composer_holder: Option<String>,
+    },
+    /// A client's SSE stream closed (REQ-API-013).
+    ClientLeft {
+        sequence_id: i64,
+        client_id: String,
+        clients: Vec<String>,
+        composer_holder: Option<String>,
+    },
+    /// The composer lease changed hands, was released, or lapsed on leave
+    /// (REQ-API-013). `None` means nobody is typing.
+    ComposerChanged {
+        sequence_id: i64,
+        composer_holder: Option<String>,
+    },
+    /// Files in the cwd changed outside the conversation (REQ-BED-059).
+    /// `paths` are relative to the cwd.
+    FilesChanged {
+        sequence_id: i64,
+        paths: Vec<String>,
+    },
+}
+
+impl SseEvent {
+    /// The event's position in the conversation's total order; also its SSE
+    /// `id:` field, echoed back by the client as `Last-Event-ID`.
+    pub fn sequence_id(&self) -> i64 {
+        match self {
+            SseEvent::Message { message } => message.sequence_id,
+            SseEvent::Init { sequence_id, .. }
+            | SseEvent::MessageUpdated { sequence_id, .. }
+            | SseEvent::StateChange { sequence_id, .. }
+            | SseEvent::Token { sequence_id, .. }
+            | SseEvent::AgentDone { sequence_id, .. }
+            | SseEvent::ConversationBecameTerminal { sequence_id, .. }
+            | SseEvent::ConversationUpdate { sequence_id, .. }
+            | SseEvent::Error { sequence_id, .. }
+            | SseEvent::ErrorRemediation { sequence_id, .. }
+            | SseEvent::ContextWarning { sequence_id, .. }
+            | SseEvent::ConversationContinued { sequence_id, .. }
+            | SseEvent::ConversationHardDeleted { sequence_id, .. }
+            | SseEvent::ClientJoined { sequence_id, .. }
+            | SseEvent::ClientLeft { sequence_id, .. }
+            | SseEvent::ComposerChanged { sequence_id, .. }
+            | SseEvent::FilesChanged { sequence_id, .. } => *sequence_id,
+        }
+    }
 }
 
 impl RuntimeManager {
@@ -410,6 +591,20 @@ impl RuntimeManager {
     ) -> Self {
         let (spawn_tx, spawn_rx) = mpsc::channel(32);
         let (cancel_tx, cancel_rx) = mpsc::channel(32);
+        let (continue_tx, continue_rx) = mpsc::channel(32);
+        let llm_cassette = crate::llm::LlmCassette::from_env().map(|cassette| {
+            tracing::warn!(
+                mode = ?cassette.mode(),
+                dir = %cassette.dir().display(),
+                "LLM traffic is being recorded or replayed (PHOENIX_LLM_MODE)"
+            );
+            Arc::new(cassette)
+        });
+        let llm_log = crate::llm::LlmTrafficLog::from_env().map(|log| {
+            tracing::info!(dir = %log.dir().display(), "LLM traffic log enabled (PHOENIX_LLM_LOG)");
+            Arc::new(log)
+        });
+        let push = Arc::new(crate::push::PushNotifier::new(db.clone()));
         Self {
             db,
             llm_registry,
@@ -424,10 +619,47 @@ impl RuntimeManager {
             spawn_rx: RwLock::new(Some(spawn_rx)),
             cancel_tx,
             cancel_rx: RwLock::new(Some(cancel_rx)),
+            continue_tx,
+            continue_rx: RwLock::new(Some(continue_rx)),
+            auto_continue: continuation::auto_continue_from_env(),
             credential_helper,
+            presence: Arc::new(presence::PresenceRegistry::new()),
+            llm_cassette,
+            llm_log,
+            batch_groups: Mutex::new(HashMap::new()),
+            push,
+            lock_owner: lock::new_owner_id(),
+            watch_files: watcher::watch_files_from_env(),
+            user_writes: Mutex::new(HashMap::new()),
         }
     }
 
+    /// The batch group for `batch`, created by the first sibling to spawn
+    /// (REQ-SA-009).
+    fn batch_group(&self, batch: &SubAgentBatch) -> Arc<crate::llm::BatchGroup> {
+        let mut groups = self
+            .batch_groups
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        groups.retain(|_, group| group.strong_count() > 0);
+        if let Some(group) = groups.get(&batch.key).and_then(Weak::upgrade) {
+            return group;
+        }
+        let group = Arc::new(crate::llm::BatchGroup::new(batch.size));
+        groups.insert(batch.key.clone(), Arc::downgrade(&group));
+        group
+    }
+
+    /// Web Push notifier (REQ-API-024)
+    pub fn push(&self) -> &Arc<crate::push::PushNotifier> {
+        &self.push
+    }
+
+    /// LLM traffic log, when `PHOENIX_LLM_LOG` is enabled (REQ-LLM-017)
+    pub fn llm_log(&self) -> Option<&Arc<crate::llm::LlmTrafficLog>> {
+        self.llm_log.as_ref()
+    }
+
     /// Get the detected platform capability
     pub fn platform(&self) -> PlatformCapability {
         self.platform
@@ -462,7 +694,119 @@ impl RuntimeManager {
         self.cancel_tx.clone()
     }
 
-    /// Start the background task that handles sub-agent spawn/cancel requests
+    /// Claim the runtime lock on a conversation for this process
+    /// (REQ-BED-052). Fails while another live process holds it.
+    async fn acquire_runtime_lock(&self, conversation_id: &str) -> Result<(), String> {
+        let now = chrono::Utc::now();
+        let acquired = self
+            .db
+            .acquire_runtime_lock(
+                conversation_id,
+                &self.lock_owner,
+                now,
+                lock::stale_before(now),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        if acquired {
+            Ok(())
+        } else {
+            Err(format!(
+                "Conversation {conversation_id} is running in another Phoenix process; \
+                 it frees up when that process stops or misses its heartbeat"
+            ))
+        }
+    }
+
+    async fn release_runtime_lock(&self, conversation_id: &str) {
+        if let Err(e) = self
+            .db
+            .release_runtime_lock(conversation_id, &self.lock_owner)
+            .await
+        {
+            tracing::warn!(
+                conv_id = %conversation_id,
+                error = %e,
+                "Failed to release runtime lock"
+            );
+        }
+    }
+
+    /// Release every runtime lock this process holds, on shutdown, so
+    /// another process can take its conversations without waiting out the
+    /// TTL.
+    pub async fn release_runtime_locks(&self) {
+        match self.db.release_runtime_locks(&self.lock_owner).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!(count, "Released runtime locks"),
+            Err(e) => tracing::warn!(error = %e, "Failed to release runtime locks"),
+        }
+    }
+
+    /// Renew this process's runtime locks every [`lock::HEARTBEAT`] for as
+    /// long as the manager lives. A runtime whose lock another process took
+    /// (this one stalled past the TTL) is evicted, so its next event goes
+    /// through `get_or_create` and is refused rather than racing the new
+    /// owner.
+    pub fn start_lock_heartbeat(self: &Arc<Self>) {
+        let manager = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(lock::HEARTBEAT);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    return;
+                };
+                manager.renew_runtime_locks().await;
+            }
+        });
+    }
+
+    /// Start the background task that reminds users about questions left
+    /// unanswered (REQ-BED-056).
+    pub fn start_follow_up_watchdog(self: &Arc<Self>) {
+        let manager = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let mut interval = tokio::time::interval(follow_up::SWEEP_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    return;
+                };
+                follow_up::sweep(&manager.db, &manager.push, &client).await;
+            }
+        });
+    }
+
+    async fn renew_runtime_locks(&self) {
+        let held: Vec<String> = self.runtimes.read().await.keys().cloned().collect();
+        let now = chrono::Utc::now();
+        for conv_id in held {
+            match self
+                .db
+                .renew_runtime_lock(&conv_id, &self.lock_owner, now)
+                .await
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::error!(
+                        conv_id = %conv_id,
+                        "Runtime lock taken by another process; evicting runtime"
+                    );
+                    self.evict_runtime(&conv_id).await;
+                }
+                Err(e) => {
+                    tracing::warn!(conv_id = %conv_id, error = %e, "Failed to renew runtime lock");
+                }
+            }
+        }
+    }
+
+    /// Start the background task that handles sub-agent spawn/cancel and
+    /// continuation requests
     /// Must be called once after creating the `RuntimeManager`
     pub async fn start_sub_agent_handler(self: &Arc<Self>) {
         let manager = Arc::clone(self);
@@ -470,8 +814,11 @@ impl RuntimeManager {
         // Take the receivers (can only be done once)
         let spawn_rx = self.spawn_rx.write().await.take();
         let cancel_rx = self.cancel_rx.write().await.take();
+        let continue_rx = self.continue_rx.write().await.take();
 
-        if let (Some(mut spawn_rx), Some(mut cancel_rx)) = (spawn_rx, cancel_rx) {
+        if let (Some(mut spawn_rx), Some(mut cancel_rx), Some(mut continue_rx)) =
+            (spawn_rx, cancel_rx, continue_rx)
+        {
             tokio::spawn(async move {
                 loop {
                     tokio::select! {
@@ -481,6 +828,9 @@ impl RuntimeManager {
                         Some(req) = cancel_rx.recv() => {
                             manager.handle_cancel_request(req).await;
                         }
+                        Some(req) = continue_rx.recv() => {
+                            manager.handle_continuation_request(req).await;
+                        }
                         else => break,
                     }
                 }
@@ -619,14 +969,23 @@ impl RuntimeManager {
 
         // 5. Create production adapters
         let storage = DatabaseStorage::new(self.db.clone());
-        let llm_client = RegistryLlmClient::new(self.llm_registry.clone(), spec.model_id.clone());
+        let batch_group = spec.batch.as_ref().map(|batch| self.batch_group(batch));
+        let llm_client = RegistryLlmClient::new(self.llm_registry.clone(), spec.model_id.clone())
+            .with_cassette(self.llm_cassette.clone())
+            .with_traffic_log(self.llm_log.clone(), &conv.id)
+            .with_batch(batch_group);
         // Select tool registry based on sub-agent mode (REQ-PROJ-008).
         // Sub-agents get MCP access via the parent's MCP manager.
         let registry = match spec.mode {
             SubAgentMode::Explore => ToolRegistry::for_subagent_explore(),
             SubAgentMode::Work => ToolRegistry::for_subagent_work(),
         };
-        let tool_executor = ToolRegistryExecutor::with_mcp(registry, self.mcp_manager.clone());
+        // Tools the user switched off for the parent, or the operator for
+        // everyone (REQ-API-028), stay off for its sub-agents.
+        let mut disabled_tools = parent_conv.disabled_tools;
+        disabled_tools.extend(self.server_settings().await.disabled_tools);
+        let tool_executor = ToolRegistryExecutor::with_mcp(registry, self.mcp_manager.clone())
+            .with_disabled_tools(disabled_tools);
 
         // 6. Create runtime with parent notification
         let runtime: ProductionRuntime = ConversationRuntime::new(
@@ -648,7 +1007,12 @@ impl RuntimeManager {
         .with_spawn_channels(self.spawn_tx.clone(), self.cancel_tx.clone())
         .with_credential_helper(self.credential_helper.clone());
 
-        // 7. Store handle
+        // 7. Claim the new conversation (REQ-BED-052) and store the handle.
+        // No other process knows its id yet, so the claim cannot fail on a
+        // held lock.
+        if let Err(e) = self.acquire_runtime_lock(&conv.id).await {
+            tracing::warn!(conv_id = %conv.id, error = %e, "Failed to lock sub-agent runtime");
+        }
         self.runtimes.write().await.insert(
             conv.id.clone(),
             ConversationHandle {
@@ -701,6 +1065,7 @@ impl RuntimeManager {
             // Without this the channel never closes and any other executor holding
             // only its own internal sender would loop forever waiting for recv().
             manager_for_cleanup.runtimes.write().await.remove(&conv_id);
+            manager_for_cleanup.release_runtime_lock(&conv_id).await;
 
             tracing::info!(conv_id = %conv_id, "Sub-agent runtime finished and cleaned up");
         });
@@ -739,42 +1104,126 @@ impl RuntimeManager {
         }
     }
 
-    /// Get or create a runtime for a conversation
-    #[allow(clippy::too_many_lines)]
-    pub async fn get_or_create(
-        self: &Arc<Self>,
-        conversation_id: &str,
-    ) -> Result<ConversationHandle, String> {
-        // Check if already running
-        {
-            let runtimes = self.runtimes.read().await;
-            if let Some(handle) = runtimes.get(conversation_id) {
-                return Ok(ConversationHandle {
-                    event_tx: handle.event_tx.clone(),
-                    broadcast_tx: handle.broadcast_tx.clone(),
-                });
+    /// Continue an exhausted conversation into a new one and start it on the
+    /// summary (REQ-BED-044). Clients of the parent are told to follow.
+    async fn handle_continuation_request(self: &Arc<Self>, req: ContinuationRequest) {
+        use crate::db::ContinueOutcome;
+
+        let ContinuationRequest {
+            parent_conversation_id: parent_id,
+            summary,
+        } = req;
+
+        let continuation = match self.db.continue_conversation(&parent_id).await {
+            Ok(ContinueOutcome::Created(conv)) => conv,
+            Ok(ContinueOutcome::AlreadyContinued(existing)) => {
+                // The user got there first with the Continue button.
+                tracing::info!(
+                    parent_id = %parent_id,
+                    existing_continuation = %existing.id,
+                    "Skipping auto-continuation: already continued"
+                );
+                return;
             }
-        }
+            Ok(ContinueOutcome::ParentNotContextExhausted { state_variant }) => {
+                tracing::warn!(
+                    parent_id = %parent_id,
+                    state = state_variant,
+                    "Skipping auto-continuation: parent is not context-exhausted"
+                );
+                return;
+            }
+            Err(e) => {
+                tracing::error!(parent_id = %parent_id, error = %e, "Auto-continuation failed");
+                return;
+            }
+        };
+        tracing::info!(
+            parent_id = %parent_id,
+            continuation_id = %continuation.id,
+            "Auto-continuing exhausted conversation"
+        );
 
-        // Need to start a new runtime
-        let conv = self
+        let touched = self
             .db
-            .get_conversation(conversation_id)
+            .list_touched_files(&parent_id)
             .await
-            .map_err(|e| e.to_string())?;
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Failed to load touched files for continuation seed");
+                Vec::new()
+            });
+        let pinned = self
+            .db
+            .list_pinned_messages(&parent_id)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(error = %e, "Failed to load pinned messages for continuation seed");
+                Vec::new()
+            });
+        let seed = continuation::seed_message(
+            &continuation.conv_mode,
+            &continuation.cwd,
+            &summary,
+            &pinned,
+            &touched,
+        );
+        let message_id = uuid::Uuid::new_v4().to_string();
+        if !pinned.is_empty() {
+            // Keep the parent's pins pinned down the chain (REQ-BED-045)
+            if let Err(e) = self
+                .db
+                .pin_message(&continuation.id, &message_id, chrono::Utc::now())
+                .await
+            {
+                tracing::warn!(error = %e, "Failed to pin continuation seed");
+            }
+        }
+        let sent = self
+            .send_event(
+                &continuation.id,
+                Event::UserMessage {
+                    text: seed,
+                    llm_text: None,
+                    images: vec![],
+                    message_id,
+                    user_agent: Some("Phoenix Continuation".to_string()),
+                    skill_invocation: None,
+                },
+            )
+            .await;
+        if let Err(e) = sent {
+            // The continuation exists and is linked; the user can still
+            // open it and type, so only the head start is lost.
+            tracing::error!(
+                continuation_id = %continuation.id,
+                error = %e,
+                "Failed to start auto-continuation"
+            );
+        }
 
-        // Check if this is a sub-agent being resumed (shouldn't happen normally)
-        let is_sub_agent = conv.parent_conversation_id.is_some();
+        if let Some(parent) = self.try_get_handle(&parent_id).await {
+            let _ = parent
+                .broadcast_tx
+                .send_seq(|seq| SseEvent::ConversationContinued {
+                    sequence_id: seq,
+                    conversation_id: continuation.id.clone(),
+                    slug: continuation.slug.clone(),
+                });
+        }
+    }
 
-        // Resolve model once: use conversation's stored model, or fall back to registry default
-        let model_id = conv
-            .model
-            .clone()
-            .unwrap_or_else(|| self.llm_registry.default_model_id().to_string());
+    /// The `ConvContext` a runtime for `conv` runs with. Also used to replay
+    /// recorded transitions outside a runtime (REQ-API-017).
+    pub async fn conversation_context(&self, conv: &Conversation) -> ConvContext {
+        // Resolve model once: use conversation's stored model, or fall back to the default
+        let model_id = match conv.model.clone() {
+            Some(model) => model,
+            None => self.default_model_id().await,
+        };
         let context_window = self.llm_registry.context_window(&model_id);
         let mode_context = conv_mode_to_context(&conv.conv_mode);
-        let mut context = if is_sub_agent {
-            let root_id = find_root_conversation_id(&self.db, conversation_id).await;
+        let mut context = if conv.parent_conversation_id.is_some() {
+            let root_id = find_root_conversation_id(&self.db, &conv.id).await;
             ConvContext::sub_agent(
                 &conv.id,
                 PathBuf::from(&conv.cwd),
@@ -791,12 +1240,49 @@ impl RuntimeManager {
             )
         };
         context.mode_context = Some(mode_context);
-        context.desired_base_branch = conv.desired_base_branch.clone();
+        context
+            .desired_base_branch
+            .clone_from(&conv.desired_base_branch);
         context.mode = match &conv.conv_mode {
             ConvMode::Direct => ModeKind::Direct,
             ConvMode::Explore { .. } | ConvMode::Work { .. } => ModeKind::Managed,
             ConvMode::Branch { .. } => ModeKind::Branch,
         };
+        context.review_patches = conv.patch_review && !context.is_sub_agent;
+        context
+    }
+
+    /// Get or create a runtime for a conversation
+    #[allow(clippy::too_many_lines)]
+    pub async fn get_or_create(
+        self: &Arc<Self>,
+        conversation_id: &str,
+    ) -> Result<ConversationHandle, String> {
+        // Check if already running
+        {
+            let runtimes = self.runtimes.read().await;
+            if let Some(handle) = runtimes.get(conversation_id) {
+                return Ok(ConversationHandle {
+                    event_tx: handle.event_tx.clone(),
+                    broadcast_tx: handle.broadcast_tx.clone(),
+                });
+            }
+        }
+
+        // Need to start a new runtime
+        let conv = self
+            .db
+            .get_conversation(conversation_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        // Another process sharing the database may be running it (REQ-BED-052)
+        self.acquire_runtime_lock(conversation_id).await?;
+
+        // Check if this is a sub-agent being resumed (shouldn't happen normally)
+        let is_sub_agent = conv.parent_conversation_id.is_some();
+
+        let context = self.conversation_context(&conv).await;
+        let model_id = context.model_id.clone();
 
         let (event_tx, event_rx) = mpsc::channel(32);
         // Seed the broadcaster's sequence_id counter from the highest seq
@@ -812,7 +1298,9 @@ impl RuntimeManager {
 
         // Create production adapters
         let storage = DatabaseStorage::new(self.db.clone());
-        let llm_client = RegistryLlmClient::new(self.llm_registry.clone(), model_id);
+        let llm_client = RegistryLlmClient::new(self.llm_registry.clone(), model_id)
+            .with_cassette(self.llm_cassette.clone())
+            .with_traffic_log(self.llm_log.clone(), conversation_id);
 
         // Use appropriate tool registry based on sub-agent status and conversation mode.
         // Sub-agents get a restricted tool set (no MCP, no spawn_agents) -- they only
@@ -848,6 +1336,80 @@ impl RuntimeManager {
             ToolRegistryExecutor::with_mcp(registry, self.mcp_manager.clone())
         };
 
+        // Conversation template (REQ-API-018): the preset as it stands now.
+        // A template deleted since creation no longer applies.
+        let template = match conv.template.as_deref() {
+            Some(name) if !is_sub_agent => match self.db.get_conversation_template(name).await {
+                Ok(Some(template)) => Some(template),
+                Ok(None) => {
+                    tracing::warn!(
+                        conv_id = %conversation_id,
+                        template = %name,
+                        "Conversation template no longer exists"
+                    );
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        conv_id = %conversation_id,
+                        error = %e,
+                        "Failed to load conversation template"
+                    );
+                    None
+                }
+            },
+            _ => None,
+        };
+        let (template_prompt, template_tools) =
+            template.map_or((None, None), |t| (Some(t.system_prompt), t.tools));
+        // Directories besides the cwd this conversation may use (REQ-BED-049)
+        let extra_roots = match self.db.list_conversation_roots(conversation_id).await {
+            Ok(roots) => roots,
+            Err(e) => {
+                tracing::warn!(
+                    conv_id = %conversation_id,
+                    error = %e,
+                    "Failed to load conversation roots"
+                );
+                Vec::new()
+            }
+        };
+        // The shell the bash tool runs commands in (REQ-BASH-017)
+        let shell = match self.db.get_conversation_shell(conversation_id).await {
+            Ok(shell) => shell,
+            Err(e) => {
+                tracing::warn!(
+                    conv_id = %conversation_id,
+                    error = %e,
+                    "Failed to load conversation shell"
+                );
+                crate::db::ShellSettings::default()
+            }
+        };
+        // Operator settings (REQ-API-028) as they stand now
+        let settings = self.server_settings().await;
+        // Bash allow/deny lists, the operator's and this conversation's (REQ-BASH-018)
+        let policy = match self.db.get_conversation_bash_policy(conversation_id).await {
+            Ok(policy) => policy,
+            Err(e) => {
+                tracing::warn!(
+                    conv_id = %conversation_id,
+                    error = %e,
+                    "Failed to load conversation bash policy"
+                );
+                crate::db::CommandPolicy::default()
+            }
+        };
+        let bash_policy = crate::tools::bash::policy::BashPolicy {
+            global: settings.bash_policy.clone().unwrap_or_default(),
+            conversation: policy,
+        };
+        let mut disabled_tools = conv.disabled_tools.clone();
+        disabled_tools.extend(settings.disabled_tools.iter().cloned());
+        let tool_executor = tool_executor
+            .with_allowed_tools(template_tools)
+            .with_disabled_tools(disabled_tools);
+
         // Determine initial state: check if conversation needs auto-continuation
         // REQ-BED-007 says resume from idle, but we need to handle interrupted turns
         let (initial_state, needs_auto_continue) =
@@ -869,7 +1431,23 @@ impl RuntimeManager {
             broadcaster.clone(),
         )
         .with_spawn_channels(self.spawn_tx.clone(), self.cancel_tx.clone())
-        .with_credential_helper(self.credential_helper.clone());
+        .with_credential_helper(self.credential_helper.clone())
+        .with_thinking_budget(conv.thinking_budget)
+        .with_history_window(conv.history_window)
+        .with_template_prompt(template_prompt)
+        .with_extra_roots(extra_roots)
+        .with_shell(shell)
+        .with_bash_policy(bash_policy);
+        let runtime = if is_sub_agent {
+            runtime
+        } else {
+            runtime.with_turn_limits(settings.turn_limits(executor::turn_limits_from_env()))
+        };
+        let runtime = if self.auto_continue {
+            runtime.with_continuation_channel(self.continue_tx.clone())
+        } else {
+            runtime
+        };
 
         // If auto-continuing, inject a system message so the LLM knows a restart
         // happened. This also serves as the restart loop counter — recovery.rs
@@ -913,6 +1491,7 @@ impl RuntimeManager {
             // the executor exits (FM-5). A new runtime will be created by
             // get_or_create if the conversation is resumed.
             manager_for_cleanup.runtimes.write().await.remove(&conv_id);
+            manager_for_cleanup.release_runtime_lock(&conv_id).await;
 
             tracing::info!(conv_id = %conv_id, "Conversation runtime finished and cleaned up");
         });
@@ -922,6 +1501,23 @@ impl RuntimeManager {
             broadcast_tx: broadcaster.clone(),
         };
 
+        // REQ-API-012: a conversation still carrying the random fallback slug
+        // gets a real title once its first agent turn completes.
+        if !is_sub_agent
+            && conv
+                .slug
+                .as_deref()
+                .is_some_and(crate::title_generator::is_random_slug)
+        {
+            self.spawn_fallback_title_watcher(conversation_id, broadcaster.subscribe());
+        }
+        if !is_sub_agent {
+            self.spawn_push_watcher(conversation_id, broadcaster.subscribe());
+        }
+        if !is_sub_agent && self.watch_files {
+            self.spawn_file_watcher(conversation_id, &conv.cwd, broadcaster.subscribe());
+        }
+
         // Store handle
         self.runtimes.write().await.insert(
             conversation_id.to_string(),
@@ -934,6 +1530,106 @@ impl RuntimeManager {
         Ok(handle)
     }
 
+    /// Wait for the next `AgentDone` and retitle the conversation from its
+    /// history if the slug is still the random fallback (REQ-API-012).
+    fn spawn_fallback_title_watcher(
+        self: &Arc<Self>,
+        conversation_id: &str,
+        mut rx: broadcast::Receiver<SseEvent>,
+    ) {
+        let manager = Arc::clone(self);
+        let conv_id = conversation_id.to_string();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(SseEvent::AgentDone { .. }) => break,
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+            drop(rx);
+
+            // The user may have renamed the conversation during the turn.
+            let still_random = manager
+                .db
+                .get_conversation(&conv_id)
+                .await
+                .ok()
+                .and_then(|c| c.slug)
+                .is_some_and(|s| crate::title_generator::is_random_slug(&s));
+            if !still_random {
+                return;
+            }
+            match manager.regenerate_title(&conv_id).await {
+                Ok(slug) => tracing::info!(conv_id = %conv_id, %slug, "Replaced fallback title"),
+                Err(e) => {
+                    tracing::debug!(conv_id = %conv_id, error = %e, "Fallback title kept");
+                }
+            }
+        });
+    }
+
+    /// Hand turn endings to the push notifier for as long as the runtime
+    /// lives (REQ-API-024).
+    fn spawn_push_watcher(&self, conversation_id: &str, mut rx: broadcast::Receiver<SseEvent>) {
+        let push = Arc::clone(&self.push);
+        let conv_id = conversation_id.to_string();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(event) => push.conversation_event(&conv_id, &event).await,
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+
+    /// Regenerate a conversation's title from its full history using the
+    /// cheap model, persist it, and push the new slug/title to any connected
+    /// client. Returns the slug actually stored (REQ-API-012).
+    pub async fn regenerate_title(
+        &self,
+        conversation_id: &str,
+    ) -> Result<String, crate::title_generator::RetitleError> {
+        use crate::title_generator::{generate_title_from_history, RetitleError};
+
+        let llm = self
+            .llm_registry
+            .get_cheap_model()
+            .ok_or(RetitleError::NoModel)?;
+        // Surface a missing conversation before spending an LLM call.
+        self.db.get_conversation(conversation_id).await?;
+        let messages = self.db.get_messages(conversation_id).await?;
+        let slug = generate_title_from_history(&messages, llm)
+            .await
+            .filter(|s| !s.is_empty())
+            .ok_or(RetitleError::NoTitle)?;
+        let slug = self.db.retitle_conversation(conversation_id, &slug).await?;
+
+        if let Some(handle) = self.try_get_handle(conversation_id).await {
+            let title = crate::db::title_from_slug(&slug);
+            let _ = handle
+                .broadcast_tx
+                .send_seq(|seq| SseEvent::ConversationUpdate {
+                    sequence_id: seq,
+                    update: ConversationMetadataUpdate {
+                        cwd: None,
+                        branch_name: None,
+                        worktree_path: None,
+                        conv_mode_label: None,
+                        base_branch: None,
+                        commits_behind: None,
+                        commits_ahead: None,
+                        task_title: None,
+                        slug: Some(slug.clone()),
+                        title: Some(title),
+                    },
+                });
+        }
+        Ok(slug)
+    }
+
     /// Send an event to a conversation
     /// Evict an active runtime so it gets recreated with fresh config on next access.
     /// Used after model upgrades to pick up the new model and context window.
@@ -954,6 +1650,46 @@ impl RuntimeManager {
             .map_err(|e| format!("Failed to send event: {e}"))
     }
 
+    /// Put `note` where the agent reads it next. An idle conversation gets it
+    /// as a meta message at the end of its history; a running one gets it as a
+    /// steering note for its next request (REQ-BED-034), since a message
+    /// appended now could split a tool call from its result. In any other state
+    /// the history ends on something awaiting the user, and no note is left.
+    /// Returns whether the note was left.
+    pub async fn note_for_agent(
+        self: &Arc<Self>,
+        conversation_id: &str,
+        note: String,
+    ) -> Result<bool, String> {
+        let conv = self
+            .db
+            .get_conversation(conversation_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        let message_id = uuid::Uuid::new_v4().to_string();
+        if conv.state.is_idle() {
+            let handle = self.get_or_create(conversation_id).await?;
+            let seq = handle.broadcast_tx.next_seq();
+            let content = crate::db::MessageContent::User(crate::db::UserContent::meta(note));
+            let message = self
+                .db
+                .add_message_with_seq(&message_id, conversation_id, seq, &content, None, None)
+                .await
+                .map_err(|e| e.to_string())?;
+            let _ = handle.broadcast_tx.send_message(message);
+            Ok(true)
+        } else if crate::state_machine::check_user_steer_acceptable(&conv.state).is_ok() {
+            let event = Event::UserSteer {
+                text: note,
+                message_id,
+            };
+            self.send_event(conversation_id, event).await?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
     /// Subscribe to conversation updates
     pub async fn subscribe(
         self: &Arc<Self>,
@@ -999,6 +1735,8 @@ impl RuntimeManager {
         match &conv.state {
             ConvState::AwaitingTaskApproval { .. }
             | ConvState::AwaitingUserResponse { .. }
+            | ConvState::AwaitingUserInput { .. }
+            | ConvState::AwaitingCouncilChoice { .. }
             | ConvState::ContextExhausted { .. }
             | ConvState::Terminal => {
                 tracing::debug!(
@@ -1042,10 +1780,35 @@ impl RuntimeManager {
         &self.db
     }
 
+    /// Operator settings (REQ-API-028), read fresh on each call so a change
+    /// applies without a restart. A failed read falls back to the defaults,
+    /// i.e. to the environment.
+    pub async fn server_settings(&self) -> ServerSettings {
+        self.db.get_server_settings().await.unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "Failed to load server settings");
+            ServerSettings::default()
+        })
+    }
+
+    /// Model for conversations that don't name one: the `default_model`
+    /// setting while the registry has it, otherwise the registry default.
+    pub async fn default_model_id(&self) -> String {
+        self.server_settings()
+            .await
+            .default_model
+            .filter(|model| self.llm_registry.get(model).is_some())
+            .unwrap_or_else(|| self.llm_registry.default_model_id())
+    }
+
     pub fn model_registry(&self) -> &ModelRegistry {
         &self.llm_registry
     }
 
+    /// Multi-client presence registry (REQ-API-013)
+    pub fn presence(&self) -> &Arc<presence::PresenceRegistry> {
+        &self.presence
+    }
+
     /// Get the LLM registry
     #[allow(dead_code)] // For future API use
     pub fn llm_registry(&self) -> &Arc<ModelRegistry> {
@@ -1181,4 +1944,48 @@ mod broadcaster_tests {
         let next = b.next_seq();
         assert_eq!(next, 3, "broadcaster must allocate past the DB watermark");
     }
+    fn token(b: &SseBroadcaster) {
+        let _ = b.send_seq(|seq| SseEvent::Token {
+            sequence_id: seq,
+            text: "x".to_string(),
+            request_id: "req".to_string(),
+        });
+    }
+
+    #[test]
+    fn resume_replays_only_missed_events() {
+        let b = SseBroadcaster::new(16, 10);
+        for _ in 0..5 {
+            token(&b);
+        }
+        let (missed, mut rx) = b.resume(13).expect("gap is within the replay buffer");
+        let seqs: Vec<i64> = missed.iter().map(SseEvent::sequence_id).collect();
+        assert_eq!(seqs, vec![14, 15]);
+
+        // Events after the resume point arrive live, not in the batch.
+        token(&b);
+        assert_eq!(rx.try_recv().unwrap().sequence_id(), 16);
+
+        // Fully caught up: nothing to replay.
+        let (missed, _rx) = b.resume(16).unwrap();
+        assert!(missed.is_empty());
+    }
+
+    #[test]
+    fn resume_refuses_gaps_outside_the_buffer() {
+        let b = SseBroadcaster::new(16, 10);
+        // Before this broadcaster existed (e.g. after a server restart).
+        assert!(b.resume(9).is_none());
+        // From the future: a stale id from some other process.
+        assert!(b.resume(11).is_none());
+
+        for _ in 0..=SSE_REPLAY_CAPACITY {
+            token(&b);
+        }
+        // Event 11 has been evicted, so a client that last saw 10 cannot be
+        // caught up by replay; one that saw 11 can.
+        assert!(b.resume(10).is_none());
+        let (missed, _rx) = b.resume(11).unwrap();
+        assert_eq!(missed.len(), SSE_REPLAY_CAPACITY);
+    }
 }
@@ -0,0 +1,176 @@
+//! Automatic continuation of context-exhausted conversations (REQ-BED-044).
+//!
+//! When a parent conversation runs out of context and its summary comes
+//! back, the executor asks the [`super::RuntimeManager`] to continue it the
+//! same way the Continue button does (REQ-BED-030): a new conversation that
+//! inherits the environment, linked from the parent by
+//! `continued_in_conv_id`. The manager then sends the summary to the new
+//! conversation as its first message, so the agent carries on without
+//! waiting for the user, and tells the parent's clients where it went.
+//!
+//! Messages pinned in the parent (REQ-BED-045) are quoted verbatim in that
+//! first message rather than left to the summary, and the first message is
+//! pinned in turn so they survive the next continuation too.
+
+use crate::db::{ConvMode, Message, MessageContent, TouchedFile};
+use crate::llm::ContentBlock;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Sent by a parent executor once its continuation summary is stored.
+#[derive(Debug)]
+pub struct ContinuationRequest {
+    pub parent_conversation_id: String,
+    pub summary: String,
+}
+
+/// Whether exhausted conversations continue on their own. On unless
+/// `PHOENIX_AUTO_CONTINUE` is `0`, `false`, `no` or `off`. Read once per
+/// process.
+pub fn auto_continue_from_env() -> bool {
+    !std::env::var("PHOENIX_AUTO_CONTINUE")
+        .is_ok_and(|v| matches!(v.as_str(), "0" | "false" | "no" | "off"))
+}
+
+/// First user message of an automatic continuation: the parent's summary,
+/// its pinned messages, the task it was working on, and the files it had
+/// touched.
+pub fn seed_message(
+    conv_mode: &ConvMode,
+    cwd: &str,
+    summary: &str,
+    pinned: &[Message],
+    touched: &[TouchedFile],
+) -> String {
+    let mut seed = String::from(
+        "This conversation continues one that ran out of context. \
+         Here is its summary of the work so far:\n\n",
+    );
+    seed.push_str(summary.trim());
+    seed.push_str("\n\n");
+
+    let pinned: Vec<(&str, String)> = pinned.iter().filter_map(pinned_text).collect();
+    if !pinned.is_empty() {
+        seed.push_str("The user pinned these messages; they still apply word for word:\n");
+        seed.push_str("<pinned_messages>\n");
+        for (role, text) in pinned {
+            let _ = writeln!(seed, "<{role}>\n{}\n</{role}>", text.trim());
+        }
+        seed.push_str("</pinned_messages>\n\n");
+    }
+
+    match conv_mode {
+        ConvMode::Work {
+            task_id,
+            task_title,
+            branch_name,
+            ..
+        } => {
+            let _ = writeln!(
+                seed,
+                "You are still working on task {task_id} ({task_title}) on branch `{branch_name}`."
+            );
+        }
+        ConvMode::Branch { branch_name, .. } => {
+            let _ = writeln!(seed, "You are still working on branch `{branch_name}`.");
+        }
+        ConvMode::Explore { .. } | ConvMode::Direct => {}
+    }
+
+    let files = crate::system_prompt::build_touched_files_section(Path::new(cwd), touched);
+    if let Some(files) = files {
+        seed.push_str(&files);
+        seed.push('\n');
+    }
+
+    seed.push_str(
+        "\nPick up where the previous conversation left off. Re-read any file before \
+         editing it; the summary may be out of date.",
+    );
+    seed
+}
+
+/// Role tag and text of a pinned message, if it has any text to carry.
+fn pinned_text(msg: &Message) -> Option<(&'static str, String)> {
+    let (role, text) = match &msg.content {
+        MessageContent::User(user) => ("user", user.llm_text().to_string()),
+        MessageContent::Agent(blocks) => (
+            "assistant",
+            blocks
+                .iter()
+                .filter_map(|b| match b {
+                    ContentBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        MessageContent::Tool(tool) => ("tool_result", tool.content.clone()),
+        MessageContent::Skill(skill) => ("skill", skill.body.clone()),
+        MessageContent::System(_) | MessageContent::Error(_) | MessageContent::Continuation(_) => {
+            return None
+        }
+    };
+    (!text.trim().is_empty()).then_some((role, text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::NonEmptyString;
+
+    fn non_empty(s: &str) -> NonEmptyString {
+        NonEmptyString::new(s).unwrap()
+    }
+
+    #[test]
+    fn seed_includes_summary_task_and_files() {
+        let mode = ConvMode::Work {
+            branch_name: non_empty("task-0042-fix-auth"),
+            worktree_path: non_empty("/repo/.phoenix/worktrees/c1"),
+            base_branch: non_empty("main"),
+            task_id: non_empty("AB042"),
+            task_title: non_empty("Fix auth middleware"),
+        };
+        let touched = vec![TouchedFile {
+            path: "/repo/src/auth.rs".to_string(),
+            read_count: 3,
+            edit_count: 2,
+            first_touched_at: chrono::Utc::now(),
+            last_touched_at: chrono::Utc::now(),
+        }];
+
+        let seed = seed_message(&mode, "/repo", "  Fixed the token check.\n", &[], &touched);
+        assert!(seed.contains("work so far:\n\nFixed the token check.\n\n"));
+        assert!(seed.contains("task AB042 (Fix auth middleware) on branch `task-0042-fix-auth`"));
+        assert!(seed.contains("- src/auth.rs (read 3, edited 2)"));
+    }
+
+    #[test]
+    fn seed_without_task_or_files() {
+        let seed = seed_message(&ConvMode::Direct, "/repo", "Summary.", &[], &[]);
+        assert!(seed.contains("Summary."));
+        assert!(!seed.contains("working on"));
+        assert!(!seed.contains("<files_touched>"));
+        assert!(!seed.contains("<pinned_messages>"));
+    }
+
+    #[test]
+    fn seed_quotes_pinned_messages_verbatim() {
+        let pinned = vec![Message {
+            message_id: "m1".to_string(),
+            conversation_id: "c1".to_string(),
+            sequence_id: 1,
+            message_type: crate::db::MessageType::User,
+            content: MessageContent::user("Never touch the migrations directory."),
+            display_data: None,
+            usage_data: None,
+            model: None,
+            created_at: chrono::Utc::now(),
+        }];
+        let seed = seed_message(&ConvMode::Direct, "/repo", "Summary.", &pinned, &[]);
+        assert!(seed.contains(
+            "<pinned_messages>\n<user>\nNever touch the migrations directory.\n</user>\n"
+        ));
+    }
+}
@@ -0,0 +1,207 @@
+//! Council mode (REQ-BED-058).
+//!
+//! One user message is put to two or three models at once. Each is sent the
+//! request the conversation would have sent its own model, capped to that
+//! model's output limit. Every answer, and every failure, is kept as a
+//! candidate; the user picks the one the conversation continues from.
+
+use super::traits::LlmClient;
+use crate::llm::{LlmError, LlmRequest, ModelRegistry, Usage};
+use crate::state_machine::state::{CouncilCandidate, ToolCall, ToolInput};
+
+/// Fewest models a council asks.
+pub const MIN_COUNCIL_MODELS: usize = 2;
+
+/// Most models a council asks.
+pub const MAX_COUNCIL_MODELS: usize = 3;
+
+/// Check a requested council: 2–3 distinct models, each available and able
+/// to use tools, since the chosen answer's tool calls run in the
+/// conversation.
+pub fn validate(registry: &ModelRegistry, models: &[String]) -> Result<(), String> {
+    if !(MIN_COUNCIL_MODELS..=MAX_COUNCIL_MODELS).contains(&models.len()) {
+        return Err(format!(
+            "A council asks {MIN_COUNCIL_MODELS} to {MAX_COUNCIL_MODELS} models, not {}",
+            models.len()
+        ));
+    }
+    for (i, model) in models.iter().enumerate() {
+        if models[..i].contains(model) {
+            return Err(format!("Model '{model}' is listed twice"));
+        }
+        if registry.get(model).is_none() {
+            return Err(format!("Model '{model}' is not available"));
+        }
+        if !registry.supports_tools(model) {
+            return Err(format!("Model '{model}' cannot use tools"));
+        }
+    }
+    Ok(())
+}
+
+/// Send `request` to every model concurrently. A member that fails is kept
+/// with its error; when all of them fail the first error is returned, so
+/// the turn goes through the ordinary retry path.
+pub async fn ask<C: LlmClient + ?Sized>(
+    client: &C,
+    registry: &ModelRegistry,
+    models: &[String],
+    request: &LlmRequest,
+) -> Result<Vec<CouncilCandidate>, LlmError> {
+    let answers = futures::future::join_all(models.iter().map(|model| {
+        let mut request = request.clone();
+        request.max_tokens = request
+            .max_tokens
+            .map(|max| registry.clamp_max_tokens(model, max));
+        async move { (model, client.complete_as(model, &request).await) }
+    }))
+    .await;
+
+    let mut first_error = None;
+    let mut candidates = Vec::with_capacity(answers.len());
+    for (model, answer) in answers {
+        let id = uuid::Uuid::new_v4().to_string();
+        let candidate = match answer {
+            Ok(response) => {
+                let tool_calls = response
+                    .tool_uses()
+                    .into_iter()
+                    .map(|(id, name, input)| {
+                        let typed_input = ToolInput::from_name_and_value(name, input.clone());
+                        ToolCall::new(id.to_string(), typed_input)
+                    })
+                    .collect();
+                CouncilCandidate {
+                    id,
+                    model: model.clone(),
+                    content: response.content,
+                    tool_calls,
+                    usage: response.usage,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                tracing::warn!(model = %model, error = %e.message, "Council member failed");
+                let message = e.message.clone();
+                if first_error.is_none() {
+                    first_error = Some(e);
+                }
+                CouncilCandidate {
+                    id,
+                    model: model.clone(),
+                    content: Vec::new(),
+                    tool_calls: Vec::new(),
+                    usage: Usage::default(),
+                    error: Some(message),
+                }
+            }
+        };
+        candidates.push(candidate);
+    }
+
+    match first_error {
+        Some(e) if candidates.iter().all(|c| c.error.is_some()) => Err(e),
+        _ => Ok(candidates),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{
+        ContentBlock, LlmErrorKind, LlmMessage, LlmResponse, LlmService, MessageRole,
+        PromptCacheKey, SystemContent,
+    };
+    use crate::runtime::RegistryLlmClient;
+    use async_trait::async_trait;
+    use std::sync::Arc;
+
+    /// Service that answers with its own id, or fails when `fail` is set.
+    struct Member {
+        id: &'static str,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl LlmService for Member {
+        async fn complete(&self, _request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+            if self.fail {
+                return Err(LlmError::new(LlmErrorKind::ServerError, "down"));
+            }
+            Ok(LlmResponse {
+                content: vec![ContentBlock::text(self.id)],
+                end_turn: true,
+                usage: Usage::default(),
+            })
+        }
+
+        fn model_id(&self) -> &str {
+            self.id
+        }
+    }
+
+    fn registry(members: &[(&'static str, bool)]) -> Arc<ModelRegistry> {
+        let services = members
+            .iter()
+            .map(|&(id, fail)| {
+                let service: Arc<dyn LlmService> = Arc::new(Member { id, fail });
+                (id, service)
+            })
+            .collect();
+        Arc::new(ModelRegistry::for_test_with_fallbacks(services, &[]))
+    }
+
+    fn request() -> LlmRequest {
+        LlmRequest {
+            system: vec![SystemContent::new("You are a test.")],
+            messages: vec![LlmMessage {
+                role: MessageRole::User,
+                content: vec![ContentBlock::text("hi")],
+            }],
+            tools: vec![],
+            max_tokens: Some(1024),
+            thinking_budget: None,
+            cache_key: PromptCacheKey::stable("c"),
+        }
+    }
+
+    fn models(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn validate_rejects_bad_councils() {
+        let registry = registry(&[("a", false), ("b", false), ("c", false), ("d", false)]);
+        assert!(validate(&registry, &models(&["a", "b"])).is_ok());
+        assert!(validate(&registry, &models(&["a", "b", "c"])).is_ok());
+        assert!(validate(&registry, &models(&["a"])).is_err());
+        assert!(validate(&registry, &models(&["a", "b", "c", "d"])).is_err());
+        assert!(validate(&registry, &models(&["a", "a"])).is_err());
+        assert!(validate(&registry, &models(&["a", "missing"])).is_err());
+    }
+
+    #[tokio::test]
+    async fn every_member_answers_or_reports_its_failure() {
+        let registry = registry(&[("a", false), ("b", true), ("c", false)]);
+        let client = RegistryLlmClient::new(registry.clone(), "a".to_string());
+        let candidates = ask(&client, &registry, &models(&["a", "b", "c"]), &request())
+            .await
+            .unwrap();
+
+        let summary: Vec<_> = candidates
+            .iter()
+            .map(|c| (c.model.as_str(), c.error.is_some()))
+            .collect();
+        assert_eq!(summary, [("a", false), ("b", true), ("c", false)]);
+        assert_eq!(candidates[2].usage.model.as_deref(), Some("c"));
+        assert!(matches!(&candidates[2].content[..], [ContentBlock::Text { text }] if text == "c"));
+    }
+
+    #[tokio::test]
+    async fn all_members_failing_is_an_error() {
+        let registry = registry(&[("a", true), ("b", true)]);
+        let client = RegistryLlmClient::new(registry.clone(), "a".to_string());
+        let result = ask(&client, &registry, &models(&["a", "b"]), &request()).await;
+        assert!(matches!(result, Err(e) if e.kind == LlmErrorKind::ServerError));
+    }
+}
@@ -2,30 +2,46 @@
 //!
 //! The executor loop receives inputs from two sources:
 //! - User events via `event_rx` (`UserMessage`, `UserCancel`, etc.) → routed to `transition()`
-//! - Effect outcomes via `outcome_rx` (`LlmOutcome`, `ToolOutcome`, etc.) → routed to `handle_outcome()`
+//! - Effect outcomes via `outcome_rx` (`LlmOutcome`, `ToolOutcome`, etc.) → converted by
+//!   `outcome_to_event()`, then routed to `transition()`
 //!
 //! Background tasks receive typed `oneshot::Sender<T>` for their outcome type.
 //! A `Sender<ToolOutcome>` physically cannot send an `LlmOutcome`.
-//! The executor wraps received outcomes in `EffectOutcome` for `handle_outcome()`.
-
-use super::traits::{LlmClient, Storage, ToolExecutor};
+//! The executor wraps received outcomes in `EffectOutcome` for `outcome_to_event()`.
+//! Every applied transition is recorded through `StateStore::record_transition`.
+
+use super::continuation::ContinuationRequest;
+use super::council;
+use super::history::{self, HistoryEntry};
+use super::remediation;
+use super::traits::{LlmClient, StateStore, Storage, ToolExecutor};
+use super::verify::{self, VerifyOutcome};
 use super::{SseBroadcaster, SseEvent, SubAgentCancelRequest, SubAgentSpawnRequest};
 
-use crate::db::{MessageContent, ToolOutcome, ToolResult};
+use crate::db::{
+    AuditEntry, AuditOutcome, EventDisposition, EventRecord, HistoryWindow, MessageContent,
+    ToolContentImage, ToolOutcome, ToolResult, TouchKind, TransitionRecord,
+};
+use crate::llm::images::{self, ImageLimits};
+use crate::llm::preflight::{self, Preflight};
 use crate::llm::{
-    ContentBlock, LlmMessage, LlmRequest, MessageRole, ModelRegistry, PromptCacheKey, SystemContent,
+    ContentBlock, LlmMessage, LlmRequest, MessageRole, ModelRegistry, PromptCacheKey, Provider,
+    SystemContent,
 };
+use crate::state_machine::budget::{TurnBudget, TurnLimits};
 use crate::state_machine::outcome::{EffectOutcome, LlmOutcome, ToolExecOutcome};
 use crate::state_machine::state::ModeKind;
 use crate::state_machine::state::{
-    SubAgentMode, SubAgentOutcome, SubAgentResult, ToolCall, ToolInput,
+    CouncilCandidate, SubAgentMode, SubAgentOutcome, SubAgentResult, ToolCall, ToolInput,
 };
+use crate::state_machine::transition::TransitionResult;
 use crate::state_machine::{
-    handle_outcome, tool_result_message_id, transition, CheckpointData, ConvContext, ConvState,
-    Effect, Event, StepResult,
+    compute_thinking_display_data, outcome_to_event, tool_result_message_id, transition,
+    CheckpointData, ConvContext, ConvState, Effect, Event, StepResult, TransitionError,
 };
 use crate::system_prompt::{build_system_prompt, ModeContext};
-use crate::tools::{BrowserSessionManager, ToolContext};
+use crate::tools::{BrowserSessionManager, ToolContext, ToolOutput};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{broadcast, mpsc, oneshot};
@@ -63,7 +79,171 @@ fn parent_tool_cycle_cap_from_env() -> u32 {
     })
 }
 
+/// Per-turn limits for parent conversations (REQ-BED-038), read once per
+/// runtime. `PHOENIX_TURN_MAX_TOOL_CALLS`, `PHOENIX_TURN_MAX_LLM_REQUESTS`,
+/// `PHOENIX_TURN_MAX_MINUTES` and `PHOENIX_TURN_MAX_REPEATS` override the
+/// matching field of [`TurnLimits::DEFAULT`]; `0` disables that limit and a
+/// malformed value logs a warning and keeps the default.
+pub(crate) fn turn_limits_from_env() -> TurnLimits {
+    turn_limits_from_lookup(|name| std::env::var(name).ok())
+}
+
+fn turn_limits_from_lookup(lookup: impl Fn(&str) -> Option<String>) -> TurnLimits {
+    let read = |var: &str| {
+        let raw = lookup(var)?;
+        raw.trim()
+            .parse::<u32>()
+            .inspect_err(|_| tracing::warn!(var, raw = %raw, "Ignoring invalid turn limit"))
+            .ok()
+    };
+    let defaults = TurnLimits::DEFAULT;
+    TurnLimits {
+        max_tool_calls: read("PHOENIX_TURN_MAX_TOOL_CALLS").unwrap_or(defaults.max_tool_calls),
+        max_llm_requests: read("PHOENIX_TURN_MAX_LLM_REQUESTS")
+            .unwrap_or(defaults.max_llm_requests),
+        max_duration: read("PHOENIX_TURN_MAX_MINUTES").map_or(defaults.max_duration, |mins| {
+            (mins > 0).then_some(Duration::from_mins(u64::from(mins)))
+        }),
+        max_repeats: read("PHOENIX_TURN_MAX_REPEATS").unwrap_or(defaults.max_repeats),
+    }
+}
+
+/// Whether `PHOENIX_REDACT_THINKING` asks for extended-thinking text to be
+/// blanked before it is stored (REQ-LLM-014). Read once per runtime.
+fn redact_thinking_from_env() -> bool {
+    std::env::var("PHOENIX_REDACT_THINKING")
+        .ok()
+        .is_some_and(|v| matches!(v.as_str(), "1" | "true" | "yes" | "on"))
+}
+
+/// Whether `PHOENIX_EVENT_LOG` turns on the event log (REQ-BED-054). Read
+/// once per runtime.
+fn event_log_from_env() -> bool {
+    std::env::var("PHOENIX_EVENT_LOG")
+        .ok()
+        .is_some_and(|v| matches!(v.as_str(), "1" | "true" | "yes" | "on"))
+}
+
+/// Context usage percentages at which the UI is warned (REQ-BED-043).
+const DEFAULT_CONTEXT_WARNING_THRESHOLDS: [u64; 2] = [70, 90];
+
+/// Context warning thresholds, read once per runtime from
+/// `PHOENIX_CONTEXT_WARNING_THRESHOLDS` (comma-separated percentages, e.g.
+/// `60,80,95`). An empty value disables the warnings; a malformed value logs
+/// a warning and keeps the default.
+fn context_warning_thresholds_from_env() -> Vec<u64> {
+    context_warning_thresholds_from_lookup(|name| std::env::var(name).ok())
+}
+
+fn context_warning_thresholds_from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Vec<u64> {
+    let Some(raw) = lookup("PHOENIX_CONTEXT_WARNING_THRESHOLDS") else {
+        return DEFAULT_CONTEXT_WARNING_THRESHOLDS.to_vec();
+    };
+    let parsed: Result<Vec<u64>, _> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::parse::<u64>)
+        .collect();
+    match parsed {
+        Ok(mut thresholds) if thresholds.iter().all(|t| (1..=100).contains(t)) => {
+            thresholds.sort_unstable();
+            thresholds.dedup();
+            thresholds
+        }
+        _ => {
+            tracing::warn!(raw = %raw, "Ignoring invalid PHOENIX_CONTEXT_WARNING_THRESHOLDS");
+            DEFAULT_CONTEXT_WARNING_THRESHOLDS.to_vec()
+        }
+    }
+}
+
+/// Highest threshold that `used` of `limit` tokens has reached, if it is
+/// above `already_warned`.
+fn crossed_context_threshold(
+    thresholds: &[u64],
+    used: u64,
+    limit: u64,
+    already_warned: u64,
+) -> Option<u64> {
+    if limit == 0 {
+        return None;
+    }
+    let percent = used.saturating_mul(100) / limit;
+    thresholds
+        .iter()
+        .copied()
+        .filter(|&t| percent >= t)
+        .max()
+        .filter(|&t| t > already_warned)
+}
+
+/// Default wall-clock budget for a single tool call. Above the bash tool's
+/// own 900s wait ceiling plus its kill grace, so tools with internal limits
+/// report their own timeout first; this is the backstop for tools that hang
+/// without one (a browser wait that never resolves, a stuck MCP server).
+const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_mins(20);
+
+/// Per-tool execution budgets (REQ-BED-033). Read once per runtime from
+/// `PHOENIX_TOOL_TIMEOUT_SECS` (the default for every tool) and
+/// `PHOENIX_TOOL_TIMEOUTS` (comma-separated `name=secs` overrides). A value
+/// of `0` disables the timeout; malformed values log a warning and are
+/// ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ToolTimeouts {
+    default: Option<Duration>,
+    per_tool: HashMap<String, Option<Duration>>,
+}
+
+impl ToolTimeouts {
+    fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        /// `None` for a malformed value, `Some(None)` for a disabled timeout
+        #[allow(clippy::option_option)]
+        fn parse(var: &str, raw: &str) -> Option<Option<Duration>> {
+            match raw.trim().parse::<u64>() {
+                Ok(0) => Some(None),
+                Ok(secs) => Some(Some(Duration::from_secs(secs))),
+                Err(_) => {
+                    tracing::warn!(var, raw = %raw, "Ignoring invalid tool timeout");
+                    None
+                }
+            }
+        }
+
+        let default = lookup("PHOENIX_TOOL_TIMEOUT_SECS")
+            .and_then(|raw| parse("PHOENIX_TOOL_TIMEOUT_SECS", &raw))
+            .unwrap_or(Some(DEFAULT_TOOL_TIMEOUT));
+        let mut per_tool = HashMap::new();
+        for pair in lookup("PHOENIX_TOOL_TIMEOUTS")
+            .unwrap_or_default()
+            .split(',')
+        {
+            if pair.trim().is_empty() {
+                continue;
+            }
+            let Some((name, secs)) = pair.split_once('=') else {
+                tracing::warn!(entry = %pair, "Ignoring PHOENIX_TOOL_TIMEOUTS entry without '='");
+                continue;
+            };
+            if let Some(timeout) = parse("PHOENIX_TOOL_TIMEOUTS", secs) {
+                per_tool.insert(name.trim().to_string(), timeout);
+            }
+        }
+        Self { default, per_tool }
+    }
+
+    /// Budget for `tool`, or `None` if it may run indefinitely.
+    fn for_tool(&self, tool: &str) -> Option<Duration> {
+        self.per_tool.get(tool).copied().unwrap_or(self.default)
+    }
+}
+
 /// Generic conversation runtime that can work with any storage, LLM, and tool implementations
+#[allow(clippy::struct_excessive_bools)] // independent per-conversation switches
 pub struct ConversationRuntime<S, L, T>
 where
     S: Storage + Clone + 'static,
@@ -98,9 +278,15 @@ where
     spawn_tx: Option<mpsc::Sender<SubAgentSpawnRequest>>,
     /// Channel to request sub-agent cancellation (parent only)
     cancel_tx: Option<mpsc::Sender<SubAgentCancelRequest>>,
+    /// Channel to request automatic continuation (REQ-BED-044). `None`
+    /// when auto-continuation is off or for sub-agents.
+    continue_tx: Option<mpsc::Sender<ContinuationRequest>>,
     /// Buffer for `SubAgentResult` events received before entering `AwaitingSubAgents`.
     /// Pre-allocated with capacity = sub-agent count when spawning (FM-6 prevention).
     sub_agent_result_buffer: Vec<Event>,
+    /// Steering notes `(message_id, text)` received mid-run (REQ-BED-034),
+    /// persisted as user messages just before the next LLM request.
+    queued_steers: Vec<(String, String)>,
     /// Deadline for sub-agent completion — set when entering `AwaitingSubAgents` (REQ-SA-006)
     sub_agent_deadline: Option<tokio::time::Instant>,
     /// Count of active Work-mode sub-agents for one-writer constraint (REQ-PROJ-008)
@@ -123,6 +309,52 @@ where
     /// [`DEFAULT_PARENT_TOOL_CYCLE_CAP`] as the fallback. Tests that want
     /// to exercise the cap deterministically use [`Self::with_parent_tool_cycle_cap`].
     parent_tool_cycle_cap: u32,
+    /// Wall-clock budget per tool call (REQ-BED-033), read once at
+    /// construction. A tool that exceeds it is cancelled and reported to the
+    /// LLM as an error result so the turn continues.
+    tool_timeouts: ToolTimeouts,
+    /// Extended-thinking budget sent with every LLM request (REQ-LLM-014).
+    /// Set from the conversation row when the runtime is created.
+    thinking_budget: Option<u32>,
+    /// How much history each LLM request carries (REQ-BED-046). Set from
+    /// the conversation row when the runtime is created.
+    history_window: HistoryWindow,
+    /// System prompt addendum from the conversation's template (REQ-API-018).
+    template_prompt: Option<String>,
+    /// Directories besides the cwd the conversation may use (REQ-BED-049).
+    /// Set from the database when the runtime is created.
+    extra_roots: Vec<crate::db::ConversationRoot>,
+    /// The shell the bash tool uses (REQ-BASH-017). Set from the database
+    /// when the runtime is created.
+    shell: crate::db::ShellSettings,
+    /// Bash allow/deny lists (REQ-BASH-018). Set from the server settings
+    /// and the database when the runtime is created.
+    bash_policy: crate::tools::bash::policy::BashPolicy,
+    /// Blank thinking text before persisting it (`PHOENIX_REDACT_THINKING`).
+    redact_thinking: bool,
+    /// Append every handled event to the event log (`PHOENIX_EVENT_LOG`).
+    event_log: bool,
+    /// `(signature, text)` of thinking blanked from the latest agent message.
+    /// Restored into the next request so a tool loop can keep thinking; lost
+    /// with the runtime, after which that loop continues without it.
+    held_thinking: Vec<(String, String)>,
+    /// When the in-flight LLM request was sent.
+    llm_request_started: Option<std::time::Instant>,
+    /// Wall-clock time of the latest LLM response, recorded on the agent
+    /// message it produces (REQ-BED-048).
+    llm_duration_ms: Option<u64>,
+    /// A patch ran since the last verify run (REQ-BED-037). Cleared when a
+    /// run starts or the user cancels.
+    unverified_edits: bool,
+    /// Verify failures fed back to the agent since the last user message.
+    verify_attempts: u32,
+    /// Attempt budget from the project settings read for the latest run.
+    verify_max_attempts: u32,
+    /// Context usage percentages that trigger `SseEvent::ContextWarning`
+    /// (REQ-BED-043), ascending.
+    context_warning_thresholds: Vec<u64>,
+    /// Highest threshold already warned about, so each fires once.
+    context_warned_at: u64,
     /// Typed outcome channel — background tasks send `EffectOutcome` here.
     /// Each task gets a typed `oneshot::Sender<T>` that constrains what it can send,
     /// then the forwarder wraps the result in `EffectOutcome` for this channel.
@@ -161,6 +393,12 @@ where
         // for this unified channel.
         let (outcome_tx, outcome_rx) = mpsc::channel::<EffectOutcome>(64);
 
+        // Sub-agents have no user to ask for guidance; `max_turns` bounds them.
+        let mut context = context;
+        if !context.is_sub_agent {
+            context.turn_budget = TurnBudget::new(turn_limits_from_env());
+        }
+
         Self {
             context,
             state,
@@ -180,13 +418,32 @@ where
             parent_event_tx: None,
             spawn_tx: None,
             cancel_tx: None,
+            continue_tx: None,
             sub_agent_result_buffer: Vec::new(),
+            queued_steers: Vec::new(),
             sub_agent_deadline: None,
             active_work_subagents: 0,
             llm_turn_count: 0,
             grace_turn_granted: false,
             parent_tool_cycle_count: 0,
             parent_tool_cycle_cap: parent_tool_cycle_cap_from_env(),
+            tool_timeouts: ToolTimeouts::from_env(),
+            thinking_budget: None,
+            history_window: HistoryWindow::Full,
+            template_prompt: None,
+            extra_roots: Vec::new(),
+            shell: crate::db::ShellSettings::default(),
+            bash_policy: crate::tools::bash::policy::BashPolicy::default(),
+            redact_thinking: redact_thinking_from_env(),
+            event_log: event_log_from_env(),
+            held_thinking: Vec::new(),
+            llm_request_started: None,
+            llm_duration_ms: None,
+            unverified_edits: false,
+            verify_attempts: 0,
+            verify_max_attempts: 0,
+            context_warning_thresholds: context_warning_thresholds_from_env(),
+            context_warned_at: 0,
             outcome_tx,
             outcome_rx,
             credential_helper: None,
@@ -202,6 +459,43 @@ where
         self
     }
 
+    /// Enable extended thinking with this token budget (REQ-LLM-014).
+    pub fn with_thinking_budget(mut self, budget: Option<u32>) -> Self {
+        self.thinking_budget = budget;
+        self
+    }
+
+    /// Send only part of the history with each request (REQ-BED-046).
+    pub fn with_history_window(mut self, window: HistoryWindow) -> Self {
+        self.history_window = window;
+        self
+    }
+
+    /// Append a conversation template's instructions to the system prompt
+    /// (REQ-API-018).
+    pub fn with_template_prompt(mut self, prompt: Option<String>) -> Self {
+        self.template_prompt = prompt;
+        self
+    }
+
+    /// Give tools and the model the conversation's extra roots (REQ-BED-049).
+    pub fn with_extra_roots(mut self, roots: Vec<crate::db::ConversationRoot>) -> Self {
+        self.extra_roots = roots;
+        self
+    }
+
+    /// Run the bash tool in the conversation's shell (REQ-BASH-017).
+    pub fn with_shell(mut self, shell: crate::db::ShellSettings) -> Self {
+        self.shell = shell;
+        self
+    }
+
+    /// Check bash commands against these lists before they run (REQ-BASH-018).
+    pub fn with_bash_policy(mut self, policy: crate::tools::bash::policy::BashPolicy) -> Self {
+        self.bash_policy = policy;
+        self
+    }
+
     /// Override the parent tool-use cycle cap. Test-only: production code
     /// relies on the env-var default set in [`Self::new`].
     #[cfg(test)]
@@ -210,6 +504,32 @@ where
         self
     }
 
+    /// Override the per-turn limits (REQ-BED-038) read from the environment
+    /// in [`Self::new`], e.g. with the operator's settings (REQ-API-028).
+    pub fn with_turn_limits(mut self, limits: TurnLimits) -> Self {
+        self.context.turn_budget = TurnBudget::new(limits);
+        self
+    }
+
+    /// Turn the event log on or off. Test-only: production code relies on
+    /// `PHOENIX_EVENT_LOG`, read in [`Self::new`].
+    #[cfg(test)]
+    pub fn with_event_log(mut self, enabled: bool) -> Self {
+        self.event_log = enabled;
+        self
+    }
+
+    /// Override the default tool timeout. Test-only: production code relies
+    /// on the env-var configuration read in [`Self::new`].
+    #[cfg(test)]
+    pub fn with_tool_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.tool_timeouts = ToolTimeouts {
+            default: timeout,
+            per_tool: HashMap::new(),
+        };
+        self
+    }
+
     /// Set the parent event channel (for sub-agents)
     pub fn with_parent(mut self, parent_tx: mpsc::Sender<Event>) -> Self {
         self.parent_event_tx = Some(parent_tx);
@@ -227,6 +547,16 @@ where
         self
     }
 
+    /// Hand exhausted conversations to the manager for automatic
+    /// continuation (REQ-BED-044).
+    pub fn with_continuation_channel(
+        mut self,
+        continue_tx: mpsc::Sender<ContinuationRequest>,
+    ) -> Self {
+        self.continue_tx = Some(continue_tx);
+        self
+    }
+
     #[allow(clippy::too_many_lines)] // Sequential event loop; splitting hurts readability
     pub async fn run(mut self) {
         tracing::info!(conv_id = %self.context.conversation_id, "Starting conversation runtime");
@@ -290,6 +620,7 @@ where
                         // (task 24682). No double-broadcast here.
                         tracing::error!(error = %e, "Error handling event");
                     }
+                    self.maybe_start_verification().await;
                     // FM-5 prevention: terminal states exit the loop explicitly.
                     if let StepResult::Terminal(outcome) = self.state.step_result() {
                         tracing::info!(
@@ -305,6 +636,7 @@ where
                     if let Err(e) = self.process_outcome(outcome).await {
                         tracing::warn!(error = %e, "Outcome rejected by state machine");
                     }
+                    self.maybe_start_verification().await;
                     // FM-5 prevention: terminal states exit the loop explicitly.
                     if let StepResult::Terminal(outcome) = self.state.step_result() {
                         tracing::info!(
@@ -440,18 +772,31 @@ where
 
     /// Process a typed effect outcome from a background task.
     ///
-    /// Routes through `handle_outcome()` (pure SM function). Invalid outcomes
-    /// are logged and discarded — state unchanged.
+    /// Converts via `outcome_to_event()` and routes through `transition()`
+    /// (pure SM functions), exactly as `handle_outcome()` does, so the applied
+    /// event can be recorded. Invalid outcomes are logged and discarded —
+    /// state unchanged.
     async fn process_outcome(&mut self, outcome: EffectOutcome) -> Result<(), String> {
-        let result = match handle_outcome(&self.state, &self.context, outcome) {
+        if matches!(outcome, EffectOutcome::Llm(LlmOutcome::Response { .. })) {
+            self.llm_duration_ms = self
+                .llm_request_started
+                .take()
+                .map(|started| u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX));
+        }
+        let converted = match outcome_to_event(&self.state, outcome) {
+            Ok(Some(event)) => self.run_transition(event).await.map_err(|e| e.to_string()),
+            Ok(None) => Ok(TransitionResult::new(self.state.clone())),
+            Err(invalid) => Err(invalid.reason),
+        };
+        let result = match converted {
             Ok(r) => r,
-            Err(invalid) => {
+            Err(reason) => {
                 tracing::warn!(
-                    reason = %invalid.reason,
+                    reason = %reason,
                     state = self.state.variant_name(),
                     "Rejected invalid outcome — state unchanged"
                 );
-                return Err(invalid.reason);
+                return Err(reason);
             }
         };
 
@@ -460,7 +805,7 @@ where
 
         // Process chained events (e.g., SpawnAgentsComplete from execute_effect)
         while let Some(event) = events_to_process.pop() {
-            let chained_result = match transition(&self.state, &self.context, event) {
+            let chained_result = match self.run_transition(event).await {
                 Ok(r) => r,
                 Err(e) => {
                     tracing::warn!(error = %e, "Chained event from outcome rejected");
@@ -474,17 +819,110 @@ where
         Ok(())
     }
 
+    /// Run `transition()` and append the step to the transition log
+    /// (REQ-API-017). Rejected events are not recorded there, only in the
+    /// event log when it is on (REQ-BED-054). The writes are awaited so rows
+    /// keep application order; a failed write is logged and the transition
+    /// still applies.
+    async fn run_transition(&self, event: Event) -> Result<TransitionResult, TransitionError> {
+        let logged_event = event.clone();
+        let result = match transition(&self.state, &self.context, event) {
+            Ok(result) => result,
+            Err(e) => {
+                let error = Some(e.to_string());
+                self.log_event(&logged_event, EventDisposition::Rejected, error)
+                    .await;
+                return Err(e);
+            }
+        };
+        self.log_event(&logged_event, EventDisposition::Applied, None)
+            .await;
+        let record = TransitionRecord::capture(
+            &self.context.conversation_id,
+            &logged_event,
+            &self.state,
+            &result.new_state,
+            &result.effects,
+        );
+        if let Err(e) = self.storage.record_transition(&record).await {
+            tracing::warn!(error = %e, "Failed to record transition");
+        }
+        Ok(result)
+    }
+
+    /// Append `event`, in the current state, to the event log when it is on
+    /// (REQ-BED-054). A failed write is logged.
+    async fn log_event(&self, event: &Event, disposition: EventDisposition, error: Option<String>) {
+        if !self.event_log {
+            return;
+        }
+        let record = EventRecord::capture(
+            &self.context.conversation_id,
+            event,
+            &self.state,
+            disposition,
+            error,
+        );
+        if let Err(e) = self.storage.record_event(&record).await {
+            tracing::warn!(error = %e, "Failed to record event");
+        }
+    }
+
     async fn process_event(&mut self, event: Event) -> Result<(), String> {
         // A fresh user turn always resets the parent tool-cycle counter
-        // (task 24680). Cap logic lives in the `Effect::RequestLlm` handler.
-        if matches!(event, Event::UserMessage { .. }) {
+        // (task 24680) and the verify budget (REQ-BED-037). Cap logic lives
+        // in the `Effect::RequestLlm` handler.
+        if matches!(
+            event,
+            Event::UserMessage { .. } | Event::UserCouncilMessage { .. }
+        ) {
             self.parent_tool_cycle_count = 0;
+            self.verify_attempts = 0;
+        }
+        // Anything the user does starts the per-turn budget over
+        // (REQ-BED-038), so time spent waiting on them never counts.
+        if matches!(
+            event,
+            Event::UserMessage { .. }
+                | Event::UserCouncilMessage { .. }
+                | Event::UserCancel { .. }
+                | Event::UserRetry
+                | Event::TaskApprovalResponse { .. }
+                | Event::UserQuestionResponse { .. }
+                | Event::UserInputAnswer { .. }
+                | Event::CouncilChoice { .. }
+                | Event::PatchReviewResponse { .. }
+        ) {
+            self.context
+                .turn_budget
+                .start_turn(std::time::Instant::now());
+        }
+        // Steering notes (REQ-BED-034) and pending verification (REQ-BED-037)
+        // belong to the run being cancelled.
+        if matches!(event, Event::UserCancel { .. }) {
+            self.queued_steers.clear();
+            self.unverified_edits = false;
+        }
+        // Past the verify budget the failure is left for the user instead of
+        // starting another turn (REQ-BED-037).
+        if let Event::VerifyFailed { report, .. } = &event {
+            if self.state.is_idle() {
+                if self.verify_attempts >= self.verify_max_attempts {
+                    self.log_event(&event, EventDisposition::Dropped, None)
+                        .await;
+                    self.note_verify_budget_spent(report).await;
+                    return Ok(());
+                }
+                self.verify_attempts += 1;
+            }
         }
 
         // Check if this is a SubAgentResult that needs buffering
         if let Event::SubAgentResult { .. } = &event {
             if !self.can_handle_sub_agent_result() {
                 tracing::debug!("Buffering SubAgentResult, parent not in AwaitingSubAgents");
+                self.log_event(&event, EventDisposition::Buffered, None)
+                    .await;
                 self.sub_agent_result_buffer.push(event);
                 return Ok(());
             }
@@ -509,7 +947,7 @@ where
             }
 
             // Pure state transition
-            let result = match transition(&self.state, &self.context, current_event) {
+            let result = match self.run_transition(current_event).await {
                 Ok(r) => r,
                 Err(e) => {
                     // Task 24682: surface a humanised, kind-aware error
@@ -531,19 +969,228 @@ where
 
             let generated_events = self.apply_transition_result(result).await?;
             events_to_process.extend(generated_events);
+            events_to_process.extend(self.settle_queued_steers());
+        }
+
+        Ok(())
+    }
+
+    /// Decide what happens to queued steering notes after a transition
+    /// (REQ-BED-034). While the agent is still working they wait for the
+    /// next LLM request. If the turn ended on its own before they were sent,
+    /// the first note starts a new turn as an ordinary user message and the
+    /// rest are persisted with that turn's request. Any other exit (error,
+    /// awaiting the user, context exhaustion) drops them: the guidance was
+    /// for a run that is no longer going.
+    fn settle_queued_steers(&mut self) -> Option<Event> {
+        if self.queued_steers.is_empty() {
+            return None;
+        }
+        match self.state {
+            ConvState::LlmRequesting { .. }
+            | ConvState::ToolExecuting { .. }
+            | ConvState::AwaitingSubAgents { .. } => None,
+            ConvState::Idle | ConvState::AwaitingUserInput { ask: None, .. } => {
+                let (message_id, text) = self.queued_steers.remove(0);
+                self.parent_tool_cycle_count = 0;
+                self.context
+                    .turn_budget
+                    .start_turn(std::time::Instant::now());
+                Some(Event::UserMessage {
+                    text,
+                    llm_text: None,
+                    images: vec![],
+                    message_id,
+                    user_agent: None,
+                    skill_invocation: None,
+                })
+            }
+            _ => {
+                tracing::info!(
+                    conv_id = %self.context.conversation_id,
+                    dropped = self.queued_steers.len(),
+                    state = self.state.variant_name(),
+                    "Dropping steering notes: run ended"
+                );
+                self.queued_steers.clear();
+                None
+            }
+        }
+    }
+
+    /// Persist steering notes queued during the run as user messages, so the
+    /// request about to be built sees them after the latest tool results.
+    async fn persist_queued_steers(&mut self) -> Result<(), String> {
+        for (message_id, text) in std::mem::take(&mut self.queued_steers) {
+            self.persist_message(&message_id, &MessageContent::user(text), None, None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Start the project's verify command once a turn that applied a patch
+    /// has ended (REQ-BED-037). Runs in the background; a failure comes back
+    /// as `Event::VerifyFailed`, which is dropped if the user has moved on.
+    async fn maybe_start_verification(&mut self) {
+        if !self.unverified_edits || self.context.is_sub_agent || !self.state.is_idle() {
+            return;
+        }
+        self.unverified_edits = false;
+
+        let conv_id = self.context.conversation_id.clone();
+        let settings = match self.storage.get_verify_settings(&conv_id).await {
+            Ok(Some(settings)) => settings,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!(conv_id = %conv_id, error = %e, "Failed to load verify settings");
+                return;
+            }
+        };
+        self.verify_max_attempts = settings.max_attempts;
+
+        let cwd = self.context.working_dir.clone();
+        let event_tx = self.event_tx.clone();
+        tokio::spawn(async move {
+            tracing::info!(
+                conv_id = %conv_id,
+                command = %settings.command,
+                "Running verify command"
+            );
+            match verify::run(&settings.command, &cwd, verify::VERIFY_TIMEOUT).await {
+                Ok(VerifyOutcome::Passed) => {
+                    tracing::info!(conv_id = %conv_id, "Verify command passed");
+                }
+                Ok(VerifyOutcome::Failed { status, output }) => {
+                    tracing::info!(conv_id = %conv_id, %status, "Verify command failed");
+                    let _ = event_tx
+                        .send(Event::VerifyFailed {
+                            report: verify::failure_report(&settings.command, &status, &output),
+                            message_id: uuid::Uuid::new_v4().to_string(),
+                        })
+                        .await;
+                }
+                Err(e) => {
+                    tracing::warn!(conv_id = %conv_id, error = %e, "Verify command did not run");
+                }
+            }
+        });
+    }
+
+    /// Record that verification still fails after the agent used up its
+    /// attempts, so the user sees why the loop stopped (REQ-BED-037).
+    async fn note_verify_budget_spent(&self, report: &str) {
+        let text = format!(
+            "Verification still failing after {} automatic fix attempts; stopped retrying.\n\n\
+             {report}",
+            self.verify_attempts
+        );
+        let msg_id = uuid::Uuid::new_v4().to_string();
+        if let Err(e) = self
+            .persist_message(&msg_id, &MessageContent::system(text), None, None)
+            .await
+        {
+            tracing::warn!(error = %e, "Failed to persist verify budget note");
+        }
+    }
+
+    /// Add the thinking summary to an agent message's `display_data` and,
+    /// when redaction is on, blank the thinking text before it is stored
+    /// (REQ-LLM-014). Blanked text is held for the next request.
+    fn prepare_agent_thinking(
+        &mut self,
+        blocks: &mut [ContentBlock],
+        display_data: &mut Option<serde_json::Value>,
+    ) {
+        let Some(summary) = compute_thinking_display_data(blocks, self.redact_thinking) else {
+            return;
+        };
+        if let Some(obj) = display_data
+            .get_or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+        {
+            obj.insert("thinking".to_string(), summary);
+        }
+        if self.redact_thinking {
+            self.held_thinking = redact_thinking(blocks);
+        }
+    }
+
+    /// Record how long the LLM took to produce an agent message in its
+    /// `display_data` (REQ-BED-048). Used once, by the first agent message
+    /// persisted after the response.
+    fn add_llm_duration(&mut self, display_data: &mut Option<serde_json::Value>) {
+        let Some(duration_ms) = self.llm_duration_ms.take() else {
+            return;
+        };
+        if let Some(obj) = display_data
+            .get_or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+        {
+            obj.insert("llm_duration_ms".to_string(), duration_ms.into());
         }
+    }
 
+    /// Write a message row and broadcast it to clients.
+    async fn persist_message(
+        &self,
+        message_id: &str,
+        content: &MessageContent,
+        display_data: Option<&serde_json::Value>,
+        usage_data: Option<&crate::db::UsageData>,
+    ) -> Result<(), String> {
+        let seq = self.broadcast_tx.next_seq();
+        let msg = self
+            .storage
+            .add_message_with_seq(
+                message_id,
+                &self.context.conversation_id,
+                seq,
+                content,
+                display_data,
+                usage_data,
+            )
+            .await?;
+        let _ = self.broadcast_tx.send_message(msg);
         Ok(())
     }
 
-    /// Apply a `TransitionResult` from either `transition()` or `handle_outcome()`.
+    /// Tell clients the context window is filling up (REQ-BED-043) when the
+    /// usage just recorded reaches a threshold not yet warned about.
+    fn warn_if_context_filling(&mut self, usage: &crate::db::UsageData) {
+        let used = usage.context_window_used();
+        let limit = u64::try_from(self.context.context_window).unwrap_or(u64::MAX);
+        let Some(threshold) = crossed_context_threshold(
+            &self.context_warning_thresholds,
+            used,
+            limit,
+            self.context_warned_at,
+        ) else {
+            return;
+        };
+        self.context_warned_at = threshold;
+        let percent = used.saturating_mul(100) / limit;
+        tracing::info!(
+            used,
+            limit,
+            percent,
+            "Context window warning threshold reached"
+        );
+        let _ = self.broadcast_tx.send_seq(|seq| SseEvent::ContextWarning {
+            sequence_id: seq,
+            used,
+            limit,
+            percent,
+        });
+    }
+
+    /// Apply a `TransitionResult` from `transition()`.
     ///
     /// Updates state, drains sub-agent buffer if entering `AwaitingSubAgents`,
     /// dispatches effects. Returns any synchronously generated events
     /// (e.g., from `SpawnAgentsComplete`).
     async fn apply_transition_result(
         &mut self,
-        result: crate::state_machine::transition::TransitionResult,
+        result: TransitionResult,
     ) -> Result<Vec<Event>, String> {
         let mut generated_events = Vec::new();
 
@@ -570,6 +1217,10 @@ where
                         | ConvState::ContextExhausted { .. }
                         | ConvState::AwaitingTaskApproval { .. }
                         | ConvState::AwaitingUserResponse { .. }
+                        | ConvState::AwaitingUserGuidance { .. }
+                        | ConvState::AwaitingUserInput { .. }
+                        | ConvState::AwaitingCouncilChoice { .. }
+                        | ConvState::AwaitingPatchReview { .. }
                         | ConvState::Terminal
                 );
                 if notable {
@@ -632,6 +1283,24 @@ where
             }
         }
 
+        // Recovery suggestion on entering Error (REQ-BED-035). Sent after the
+        // effects so it follows the state_change that carried the error.
+        if let ConvState::Error {
+            message,
+            error_kind,
+        } = &self.state
+        {
+            if !matches!(old_state, ConvState::Error { .. }) {
+                let remediation = remediation::classify(message, error_kind);
+                let _ = self
+                    .broadcast_tx
+                    .send_seq(|seq| SseEvent::ErrorRemediation {
+                        sequence_id: seq,
+                        remediation,
+                    });
+            }
+        }
+
         Ok(generated_events)
     }
 
@@ -803,7 +1472,9 @@ where
     /// 3. Return `SpawnAgentsComplete` event
     #[allow(clippy::too_many_lines)]
     async fn handle_spawn_agents_tool(&mut self, tool: ToolCall) -> Result<Option<Event>, String> {
-        use crate::state_machine::state::{PendingSubAgent, SpawnAgentsInput, SubAgentSpec};
+        use crate::state_machine::state::{
+            PendingSubAgent, SpawnAgentsInput, SubAgentBatch, SubAgentSpec,
+        };
 
         let tool_use_id = tool.id.clone();
         let input_value = tool.input.to_value();
@@ -893,6 +1564,11 @@ where
         // Generate agent IDs and prepare spawn specs
         let mut spawned = Vec::new();
         let parent_cwd = self.context.working_dir.to_string_lossy().to_string();
+        // A lone sub-agent has nothing to share a batch with (REQ-SA-009).
+        let batch = (input.batch && input.tasks.len() > 1).then(|| SubAgentBatch {
+            key: format!("{}:{tool_use_id}", self.context.conversation_id),
+            size: input.tasks.len(),
+        });
 
         for task in &input.tasks {
             let agent_id = uuid::Uuid::new_v4().to_string();
@@ -947,6 +1623,7 @@ where
                     mode,
                     model_id: resolved_model,
                     max_turns,
+                    batch: batch.clone(),
                 };
                 let request = SubAgentSpawnRequest {
                     spec,
@@ -1002,26 +1679,27 @@ where
     async fn execute_effect(&mut self, effect: Effect) -> Result<Option<Event>, String> {
         match effect {
             Effect::PersistMessage {
-                content,
-                display_data,
+                mut content,
+                mut display_data,
                 usage_data,
                 message_id,
             } => {
-                let seq = self.broadcast_tx.next_seq();
-                let msg = self
-                    .storage
-                    .add_message_with_seq(
-                        &message_id,
-                        &self.context.conversation_id,
-                        seq,
-                        &content,
-                        display_data.as_ref(),
-                        usage_data.as_ref(),
-                    )
-                    .await?;
-
-                // Broadcast to clients (display_data already computed at effect creation)
-                let _ = self.broadcast_tx.send_message(msg);
+                // display_data already computed at effect creation; thinking
+                // is summarized here since redaction is a runtime setting.
+                if let MessageContent::Agent(blocks) = &mut content {
+                    self.prepare_agent_thinking(blocks, &mut display_data);
+                    self.add_llm_duration(&mut display_data);
+                }
+                self.persist_message(
+                    &message_id,
+                    &content,
+                    display_data.as_ref(),
+                    usage_data.as_ref(),
+                )
+                .await?;
+                if let Some(usage) = &usage_data {
+                    self.warn_if_context_filling(usage);
+                }
                 Ok(None)
             }
 
@@ -1040,7 +1718,25 @@ where
                 Ok(None)
             }
 
-            Effect::RequestLlm => self.dispatch_llm_request().await,
+            Effect::RequestLlm => {
+                self.persist_queued_steers().await?;
+                self.dispatch_llm_request(None).await
+            }
+
+            Effect::RequestCouncil { models } => {
+                self.persist_queued_steers().await?;
+                self.dispatch_llm_request(Some(models)).await
+            }
+
+            Effect::QueueSteer { text, message_id } => {
+                tracing::info!(
+                    conv_id = %self.context.conversation_id,
+                    message_id = %message_id,
+                    "Queued steering note for next LLM request"
+                );
+                self.queued_steers.push((message_id, text));
+                Ok(None)
+            }
 
             Effect::ExecuteTool { tool } => self.dispatch_tool_execution(tool).await,
 
@@ -1117,6 +1813,38 @@ where
                 Ok(None)
             }
 
+            Effect::ApplyPatch { tool_use_id, patch } => {
+                // REQ-PATCH-010: the user approved; write it now and finish
+                // the call as though the tool had written it itself
+                tracing::info!(
+                    tool_id = %tool_use_id,
+                    path = %patch.path,
+                    "Applying reviewed patch"
+                );
+                let output = patch.apply().await;
+                if output.success {
+                    let touches: Vec<_> = patch
+                        .effects
+                        .iter()
+                        .map(|effect| {
+                            let crate::tools::patch::PatchEffect::WriteFile { path, .. } = effect;
+                            (path.display().to_string(), TouchKind::Edit)
+                        })
+                        .collect();
+                    let conv_id = &self.context.conversation_id;
+                    record_touches(&self.storage, conv_id, &touches).await;
+                }
+                let result = ToolResult {
+                    tool_use_id: tool_use_id.clone(),
+                    outcome: tool_outcome_from_output(output),
+                    duration_ms: None,
+                };
+                Ok(Some(Event::ToolComplete {
+                    tool_use_id,
+                    result,
+                }))
+            }
+
             Effect::AbortLlm => {
                 tracing::info!("Aborting LLM request");
                 if let Some(handle) = self.llm_task_handle.take() {
@@ -1188,6 +1916,19 @@ where
                 Ok(None)
             }
 
+            Effect::AutoContinue { summary } => {
+                if let Some(continue_tx) = &self.continue_tx {
+                    let request = ContinuationRequest {
+                        parent_conversation_id: self.context.conversation_id.clone(),
+                        summary,
+                    };
+                    if let Err(e) = continue_tx.send(request).await {
+                        tracing::error!(error = %e, "Failed to send continuation request");
+                    }
+                }
+                Ok(None)
+            }
+
             Effect::ApproveTask {
                 title,
                 priority,
@@ -1209,8 +1950,28 @@ where
 
     /// Dispatch an LLM request: enforce turn/cycle caps, inject grace-turn
     /// messages, build the streaming pipeline, and spawn the LLM task.
+    /// `council` names the models to ask at once instead of the
+    /// conversation's own (REQ-BED-058).
     #[allow(clippy::too_many_lines)]
-    async fn dispatch_llm_request(&mut self) -> Result<Option<Event>, String> {
+    async fn dispatch_llm_request(
+        &mut self,
+        council: Option<Vec<String>>,
+    ) -> Result<Option<Event>, String> {
+        // Per-turn budget and loop detection (REQ-BED-038): pause for the
+        // user instead of sending the request. Unlimited for sub-agents.
+        let now = std::time::Instant::now();
+        self.context.turn_budget.record_llm_request(now);
+        if let Some(breach) = self.context.turn_budget.breach(now) {
+            tracing::warn!(
+                conv_id = %self.context.conversation_id,
+                %breach,
+                "Turn budget exceeded; pausing for user guidance"
+            );
+            return Ok(Some(Event::TurnBudgetExceeded {
+                reason: breach.to_string(),
+            }));
+        }
+
         // Parent-conversation tool-use cycle cap (task 24680). Sub-agents
         // have their own lifetime cap below (REQ-PROJ-008); this branch
         // only fires for parent conversations. The counter is reset at
@@ -1289,6 +2050,7 @@ where
         let outcome_tx = self.outcome_tx.clone();
 
         let llm_client = self.llm_client.clone();
+        let registry = self.llm_registry.clone();
         let tool_executor = self.tool_executor.clone();
         let storage = self.storage.clone();
         let conv_id = self.context.conversation_id.clone();
@@ -1297,6 +2059,16 @@ where
         let working_dir = self.context.working_dir.clone();
         let is_sub_agent = self.context.is_sub_agent;
         let mode_context = self.context.mode_context.clone();
+        let context_window = self.context.context_window;
+        let provider = self.llm_registry.provider(&model_id);
+        let supports_vision = self.llm_registry.supports_vision(&model_id);
+        let supports_tools = self.llm_registry.supports_tools(&model_id);
+        let max_tokens = self.llm_registry.clamp_max_tokens(&model_id, 16_384);
+        let thinking_budget = self.thinking_budget;
+        let history_window = self.history_window;
+        let template_prompt = self.template_prompt.clone();
+        let extra_roots = crate::system_prompt::build_extra_roots_section(&self.extra_roots);
+        let held_thinking = self.held_thinking.clone();
 
         // Token streaming channel (REQ-BED-025).
         //
@@ -1338,6 +2110,7 @@ where
             }
         });
 
+        self.llm_request_started = Some(std::time::Instant::now());
         let handle = tokio::spawn(async move {
             if is_sub_agent {
                 tracing::info!(
@@ -1355,7 +2128,15 @@ where
             }
 
             // Build messages from history
-            let messages = match Self::build_llm_messages_static(&storage, &conv_id).await {
+            let messages = Self::build_llm_messages_static(
+                &storage,
+                &conv_id,
+                &model_id,
+                supports_vision,
+                history_window,
+                provider,
+            );
+            let mut messages = match messages.await {
                 Ok(m) => m,
                 Err(e) => {
                     // Build error → treated as InvalidRequest
@@ -1363,80 +2144,176 @@ where
                     return;
                 }
             };
+            restore_thinking(&mut messages, &held_thinking);
 
             // Build system prompt with AGENTS.md content + mode context
-            let system_prompt =
+            let mut system_prompt =
                 build_system_prompt(&working_dir, is_sub_agent, mode_context.as_ref());
+            if let Some(addendum) = &template_prompt {
+                system_prompt.push_str("\n\n<conversation_template>\n");
+                system_prompt.push_str(addendum.trim_end());
+                system_prompt.push_str("\n</conversation_template>");
+            }
+            if let Some(section) = &extra_roots {
+                system_prompt.push_str("\n\n");
+                system_prompt.push_str(section);
+            }
+
+            // Library/project skills whose trigger keywords appear in the latest
+            // user message (REQ-SK-008). Kept in a separate, uncached block so
+            // the cached system prompt prefix stays byte-stable across turns.
+            let triggered_skills = latest_user_text(&messages).and_then(|text| {
+                crate::system_prompt::build_triggered_skills_section(&working_dir, &text)
+            });
+
+            // Files this conversation has already read or edited (REQ-BED-042),
+            // also uncached since the list changes every tool round.
+            let touched_files = match storage.get_touched_files(&conv_id).await {
+                Ok(files) => {
+                    crate::system_prompt::build_touched_files_section(&working_dir, &files)
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to load touched files for system prompt");
+                    None
+                }
+            };
 
             // Build request — normalize messages against current tool set
             // to remove tool_use/tool_result blocks for tools no longer
             // available (e.g., propose_task after Explore→Work transition).
-            let tools = tool_executor.definitions().await;
+            // Models without tool support get a plain chat request (REQ-BED-041).
+            let tools = if supports_tools {
+                tool_executor.definitions().await
+            } else {
+                Vec::new()
+            };
             let tool_names: std::collections::HashSet<&str> =
                 tools.iter().map(|t| t.name.as_str()).collect();
             let messages = strip_unavailable_tool_blocks(messages, &tool_names);
 
-            let request = LlmRequest {
-                system: vec![SystemContent::cached(&system_prompt)],
+            let mut request = LlmRequest {
+                system: std::iter::once(SystemContent::cached(&system_prompt))
+                    .chain(triggered_skills.map(SystemContent::new))
+                    .chain(touched_files.map(SystemContent::new))
+                    .collect(),
                 messages,
                 tools,
-                max_tokens: Some(16_384),
+                max_tokens: Some(max_tokens),
+                thinking_budget,
                 // Every turn in a conversation reuses the same prefix
                 // (system prompt + earlier turns), so all turns share one key.
                 cache_key: PromptCacheKey::stable(&conv_id),
             };
 
-            // Use streaming — chunk_tx forwards text tokens to SSE clients.
-            let llm_outcome = match llm_client.complete_streaming(&request, &chunk_tx).await {
-                Ok(response) => {
-                    // Extract tool calls from content and convert to typed ToolCall
-                    let tool_calls: Vec<ToolCall> = response
-                        .tool_uses()
-                        .into_iter()
-                        .map(|(id, name, input)| {
-                            let typed_input = ToolInput::from_name_and_value(name, input.clone());
-                            ToolCall::new(id.to_string(), typed_input)
-                        })
-                        .collect();
-
-                    let usage = &response.usage;
+            // Preflight context guard (REQ-BED-036): blank the oldest tool
+            // outputs if the prompt won't fit; if it still won't, skip the
+            // call and let the state machine compact the conversation.
+            // Pinned tool outputs stay verbatim (REQ-BED-045).
+            let pinned = match storage.get_pinned_messages(&conv_id).await {
+                Ok(pinned) => pinned_tool_use_ids(&pinned),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to load pinned messages");
+                    std::collections::HashSet::new()
+                }
+            };
+            match preflight::fit_request(&mut request, context_window, provider, &pinned) {
+                Preflight::Fits { .. } => {}
+                Preflight::Truncated {
+                    outputs,
+                    before,
+                    after,
+                } => {
                     tracing::info!(
-                        input = usage.input_tokens,
-                        output = usage.output_tokens,
-                        cache_write = usage.cache_creation_tokens,
-                        cache_read = usage.cache_read_tokens,
-                        "LLM response token usage"
+                        conv_id = %conv_id,
+                        outputs,
+                        before,
+                        after,
+                        "Trimmed old tool outputs to fit the context window"
+                    );
+                }
+                Preflight::Overflow { estimate, budget } => {
+                    tracing::warn!(
+                        conv_id = %conv_id,
+                        estimate,
+                        budget,
+                        "Prompt exceeds the context window; not sending"
                     );
+                    let _ = llm_tx.send(LlmOutcome::TokenBudgetExceeded);
+                    return;
+                }
+            }
 
-                    // Fire-and-forget: persist token usage for this turn.
-                    // Errors are logged and do not affect the conversation.
-                    let storage_for_usage = storage.clone();
-                    let conv_id_for_usage = conv_id.clone();
-                    let root_id_for_usage = root_conv_id.clone();
-                    let model_for_usage = model_id.clone();
-                    let usage_for_insert = usage.clone();
-                    tokio::spawn(async move {
-                        if let Err(e) = storage_for_usage
-                            .insert_turn_usage(
-                                &conv_id_for_usage,
-                                &root_id_for_usage,
-                                &model_for_usage,
-                                &usage_for_insert,
-                            )
-                            .await
-                        {
-                            tracing::warn!(error = %e, "failed to write turn_usage row");
-                        }
-                    });
+            let tool_tokens = preflight::tool_output_tokens(&request.messages, provider);
+            Self::record_tool_tokens(&storage, &conv_id, tool_tokens);
 
-                    LlmOutcome::Response {
-                        content: response.content,
-                        tool_calls,
-                        end_turn: response.end_turn,
-                        usage: response.usage,
+            let llm_outcome = if let Some(models) = &council {
+                // Council answers are not streamed; they are shown together
+                // once every model is done (REQ-BED-058).
+                match council::ask(&*llm_client, &registry, models, &request).await {
+                    Ok(candidates) => {
+                        Self::record_council(&storage, &conv_id, &root_conv_id, &candidates);
+                        LlmOutcome::Council { candidates }
+                    }
+                    Err(e) => llm_error_to_outcome(e),
+                }
+            } else {
+                // Use streaming — chunk_tx forwards text tokens to SSE clients.
+                match llm_client.complete_streaming(&request, &chunk_tx).await {
+                    Ok(response) => {
+                        // Extract tool calls from content and convert to typed ToolCall
+                        let tool_calls: Vec<ToolCall> = response
+                            .tool_uses()
+                            .into_iter()
+                            .map(|(id, name, input)| {
+                                let typed_input =
+                                    ToolInput::from_name_and_value(name, input.clone());
+                                ToolCall::new(id.to_string(), typed_input)
+                            })
+                            .collect();
+
+                        let usage = &response.usage;
+                        tracing::info!(
+                            input = usage.input_tokens,
+                            output = usage.output_tokens,
+                            cache_write = usage.cache_creation_tokens,
+                            cache_read = usage.cache_read_tokens,
+                            cache_hit_rate = usage.cache_hit_rate().unwrap_or(0.0),
+                            "LLM response token usage"
+                        );
+
+                        // Fire-and-forget: persist token usage for this turn.
+                        // Errors are logged and do not affect the conversation.
+                        let storage_for_usage = storage.clone();
+                        let conv_id_for_usage = conv_id.clone();
+                        let root_id_for_usage = root_conv_id.clone();
+                        // A fallback may have answered (REQ-LLM-013); bill the model
+                        // that actually served the turn.
+                        let model_for_usage =
+                            usage.model.clone().unwrap_or_else(|| model_id.clone());
+                        let usage_for_insert = usage.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = storage_for_usage
+                                .insert_turn_usage(
+                                    &conv_id_for_usage,
+                                    &root_id_for_usage,
+                                    &model_for_usage,
+                                    &usage_for_insert,
+                                )
+                                .await
+                            {
+                                tracing::warn!(error = %e, "failed to write turn_usage row");
+                            }
+                        });
+
+                        LlmOutcome::Response {
+                            content: response.content,
+                            tool_calls,
+                            end_turn: response.end_turn,
+                            usage: response.usage,
+                        }
                     }
+                    Err(e) => llm_error_to_outcome(e),
                 }
-                Err(e) => llm_error_to_outcome(e),
             };
 
             // Happens-before barrier for task 24683: close the chunk
@@ -1475,10 +2352,17 @@ where
     /// spawn the background task, and wire up the outcome channel.
     #[allow(clippy::too_many_lines)]
     async fn dispatch_tool_execution(&mut self, tool: ToolCall) -> Result<Option<Event>, String> {
+        self.context
+            .turn_budget
+            .record_tool_call(tool.name(), &tool.input.to_value());
+
         // Special handling for spawn_agents tool
         if tool.name() == "spawn_agents" {
             return self.handle_spawn_agents_tool(tool).await;
         }
+        if tool.name() == "patch" {
+            self.unverified_edits = true;
+        }
 
         // Typed oneshot channel: background task gets Sender<ToolExecOutcome>,
         // physically cannot send an LlmOutcome or other type.
@@ -1495,6 +2379,29 @@ where
         // None for Direct (no worktree — socket keyed to conv_id). Task 03001.
         let tmux_worktree =
             (self.context.mode != ModeKind::Direct).then(|| self.context.working_dir.clone());
+        // REQ-KWS-006: search ranking boosts files the conversation touched.
+        // REQ-BASH-019: the dirty check treats its own edits as related.
+        let touched = if matches!(tool.name(), "keyword_search" | "patch" | "bash") {
+            match self
+                .storage
+                .get_touched_files(&self.context.conversation_id)
+                .await
+            {
+                Ok(files) => files,
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to load touched files");
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        let edited_files = touched
+            .iter()
+            .filter(|f| f.edit_count > 0)
+            .map(|f| f.path.clone())
+            .collect();
+        let referenced_files = touched.into_iter().map(|f| f.path).collect();
         let tool_ctx = ToolContext::new(
             cancel_token,
             self.context.conversation_id.clone(),
@@ -1505,13 +2412,25 @@ where
             self.terminals.clone(),
             self.tmux_registry.clone(),
             tmux_worktree,
-        );
+        )
+        .with_patch_review(self.context.review_patches)
+        .with_referenced_files(referenced_files)
+        .with_edited_files(edited_files)
+        .with_extra_roots(self.extra_roots.clone())
+        .with_shell(self.shell.clone())
+        .with_bash_policy(self.bash_policy.clone());
 
         let conv_id = self.context.conversation_id.clone();
         let tool_executor = self.tool_executor.clone();
         let tool_use_id = tool.id.clone();
         let tool_name = tool.name().to_string();
         let tool_input = tool.input.to_value();
+        let (input_hash, input_preview) = AuditEntry::fingerprint_input(&tool_input);
+        let file_touches = touched_paths(&tool_name, &tool_input, &self.context.working_dir);
+        let audit_tool_use_id = tool_use_id.clone();
+        let audit_cwd = self.context.working_dir.display().to_string();
+        let storage = self.storage.clone();
+        let timeout = self.tool_timeouts.for_tool(&tool_name);
 
         tokio::spawn(async move {
             tracing::info!(
@@ -1520,17 +2439,58 @@ where
                 id = %tool_use_id,
                 "Executing tool"
             );
+            let started_at = chrono::Utc::now();
             let tool_start = std::time::Instant::now();
 
-            let output = tool_executor
-                .execute(&tool_name, tool_input, tool_ctx)
-                .await;
+            let execution = tool_executor.execute(&tool_name, tool_input, tool_ctx);
+            let (output, timed_out) = if let Some(budget) = timeout {
+                if let Ok(output) = tokio::time::timeout(budget, execution).await {
+                    (output, false)
+                } else {
+                    // A user cancel that raced the deadline still wins:
+                    // the state machine is already in CancellingTool.
+                    let timed_out = !cancel_token_check.is_cancelled();
+                    // The future is dropped; cancelling the token lets
+                    // anything the tool spawned (child processes,
+                    // browser waits) clean up.
+                    cancel_token_check.cancel();
+                    (None, timed_out)
+                }
+            } else {
+                (execution.await, false)
+            };
 
             // Check if the tool was cancelled via the cancellation token.
             // IMPORTANT: We check the token state, NOT the output string.
             // The state machine only accepts ToolAborted from CancellingTool state,
             // which is entered when AbortTool effect cancels the token.
-            let tool_outcome = if cancel_token_check.is_cancelled() {
+            let mut policy_hit = None;
+            let tool_outcome = if timed_out {
+                let budget = timeout.unwrap_or_default();
+                tracing::warn!(
+                    conv_id = %conv_id,
+                    tool = %tool_name,
+                    id = %tool_use_id,
+                    timeout_secs = budget.as_secs(),
+                    "Tool timed out"
+                );
+                // Reported as an ordinary error result so the LLM sees it and
+                // the tool chain carries on (REQ-BED-033).
+                ToolExecOutcome::Completed(ToolResult {
+                    tool_use_id: tool_use_id.clone(),
+                    outcome: ToolOutcome::Error {
+                        output: format!(
+                            "Tool `{tool_name}` timed out after {}s and was cancelled.",
+                            budget.as_secs()
+                        ),
+                        display_data: None,
+                        images: vec![],
+                    },
+                    duration_ms: Some(
+                        u64::try_from(tool_start.elapsed().as_millis()).unwrap_or(u64::MAX),
+                    ),
+                })
+            } else if cancel_token_check.is_cancelled() {
                 tracing::info!(
                     conv_id = %conv_id,
                     tool = %tool_name,
@@ -1541,58 +2501,92 @@ where
                     tool_use_id,
                     reason: crate::state_machine::AbortReason::CancellationRequested,
                 }
-            } else {
-                use crate::db::ToolContentImage;
-                if let Some(out) = output {
-                    let duration_ms =
-                        u64::try_from(tool_start.elapsed().as_millis()).unwrap_or(u64::MAX);
-                    tracing::info!(
-                        conv_id = %conv_id,
-                        tool = %tool_name,
-                        id = %tool_use_id,
-                        duration_ms,
-                        success = out.success,
-                        "Tool completed"
-                    );
-                    let images: Vec<ToolContentImage> = out
-                        .images
-                        .into_iter()
-                        .map(|img| ToolContentImage {
-                            media_type: img.media_type,
-                            data: img.data,
-                        })
-                        .collect();
-                    let outcome = if out.success {
-                        ToolOutcome::Success {
-                            output: out.output,
-                            display_data: out.display_data,
-                            images,
-                        }
-                    } else {
-                        ToolOutcome::Error {
-                            output: out.output,
-                            display_data: out.display_data,
-                            images,
-                        }
-                    };
+            } else if let Some(mut out) = output {
+                let duration_ms =
+                    u64::try_from(tool_start.elapsed().as_millis()).unwrap_or(u64::MAX);
+                tracing::info!(
+                    conv_id = %conv_id,
+                    tool = %tool_name,
+                    id = %tool_use_id,
+                    duration_ms,
+                    success = out.success,
+                    "Tool completed"
+                );
+                policy_hit = out.policy_hit.take();
+                // REQ-PATCH-010: the state machine waits for the user
+                if let Some(patch) = out.staged_patch.take() {
+                    ToolExecOutcome::Staged {
+                        tool_use_id: tool_use_id.clone(),
+                        patch,
+                    }
+                } else {
                     ToolExecOutcome::Completed(ToolResult {
                         tool_use_id: tool_use_id.clone(),
-                        outcome,
+                        outcome: tool_outcome_from_output(out),
                         duration_ms: Some(duration_ms),
                     })
-                } else {
-                    tracing::warn!(
-                        conv_id = %conv_id,
-                        tool = %tool_name,
-                        id = %tool_use_id,
-                        "Tool not found"
-                    );
-                    ToolExecOutcome::Failed {
-                        tool_use_id,
-                        error: format!("Unknown tool: {tool_name}"),
-                    }
+                }
+            } else {
+                tracing::warn!(
+                    conv_id = %conv_id,
+                    tool = %tool_name,
+                    id = %tool_use_id,
+                    "Tool not found"
+                );
+                ToolExecOutcome::Failed {
+                    tool_use_id,
+                    error: format!("Unknown tool: {tool_name}"),
                 }
             };
+
+            // Audit trail (REQ-API-016). Fire-and-forget so a slow or failed
+            // insert never delays the tool result.
+            let outcome = match &tool_outcome {
+                ToolExecOutcome::Completed(result) => match result.outcome {
+                    ToolOutcome::Success { .. } => AuditOutcome::Success,
+                    ToolOutcome::Error { .. } => AuditOutcome::Error,
+                    ToolOutcome::Cancelled { .. } => AuditOutcome::Cancelled,
+                },
+                ToolExecOutcome::Aborted { .. } => AuditOutcome::Cancelled,
+                ToolExecOutcome::Failed { .. } => AuditOutcome::UnknownTool,
+                ToolExecOutcome::Staged { .. } => AuditOutcome::Success,
+            };
+            // REQ-BASH-018: a refusal is its own outcome, not a failed run
+            let outcome = match &policy_hit {
+                Some(hit) if hit.denied => AuditOutcome::Denied,
+                _ => outcome,
+            };
+            let audit = AuditEntry {
+                conversation_id: conv_id,
+                tool_use_id: audit_tool_use_id,
+                tool_name,
+                input_hash,
+                input_preview,
+                cwd: audit_cwd,
+                os_user: std::env::var("USER").ok(),
+                started_at,
+                duration_ms: i64::try_from(tool_start.elapsed().as_millis()).unwrap_or(i64::MAX),
+                outcome,
+                policy: policy_hit.map(|hit| hit.audit_label()),
+            };
+            // Touched-files index (REQ-BED-042): only calls that did their
+            // work count; a staged patch is recorded when it is applied.
+            let succeeded = matches!(
+                &tool_outcome,
+                ToolExecOutcome::Completed(ToolResult {
+                    outcome: ToolOutcome::Success { .. },
+                    ..
+                })
+            );
+            tokio::spawn(async move {
+                if let Err(e) = storage.record_tool_audit(&audit).await {
+                    tracing::warn!(error = %e, "failed to write audit_log row");
+                }
+                if succeeded {
+                    record_touches(&storage, &audit.conversation_id, &file_touches).await;
+                }
+            });
+
             // Send typed outcome through oneshot channel
             let _ = tool_tx.send(tool_outcome);
         });
@@ -1611,10 +2605,15 @@ where
     async fn persist_checkpoint(&mut self, data: CheckpointData) -> Result<Option<Event>, String> {
         match data {
             CheckpointData::ToolRound {
-                assistant_message,
+                mut assistant_message,
                 tool_results,
             } => {
                 // Persist assistant message
+                self.prepare_agent_thinking(
+                    &mut assistant_message.content,
+                    &mut assistant_message.display_data,
+                );
+                self.add_llm_duration(&mut assistant_message.display_data);
                 let agent_content = MessageContent::agent(assistant_message.content);
                 let agent_seq = self.broadcast_tx.next_seq();
                 let agent_msg = self
@@ -1629,6 +2628,9 @@ where
                     )
                     .await?;
                 let _ = self.broadcast_tx.send_message(agent_msg);
+                if let Some(usage) = &assistant_message.usage {
+                    self.warn_if_context_filling(usage);
+                }
 
                 // Persist all tool results
                 for result in tool_results {
@@ -1787,23 +2789,100 @@ where
     /// Build LLM messages from conversation history (instance method)
     #[allow(dead_code)] // May be useful for non-spawned code paths
     async fn build_llm_messages(&self) -> Result<Vec<LlmMessage>, String> {
-        Self::build_llm_messages_static(&self.storage, &self.context.conversation_id).await
+        let supports_vision = self.llm_registry.supports_vision(&self.context.model_id);
+        Self::build_llm_messages_static(
+            &self.storage,
+            &self.context.conversation_id,
+            &self.context.model_id,
+            supports_vision,
+            self.history_window,
+            self.llm_registry.provider(&self.context.model_id),
+        )
+        .await
+    }
+
+    /// Keep a council's answers and bill each model that answered
+    /// (REQ-BED-058). Fire-and-forget, like the usage row of a single reply.
+    fn record_council(
+        storage: &S,
+        conv_id: &str,
+        root_conv_id: &str,
+        candidates: &[CouncilCandidate],
+    ) {
+        let storage = storage.clone();
+        let conv_id = conv_id.to_string();
+        let root_conv_id = root_conv_id.to_string();
+        let candidates = candidates.to_vec();
+        tokio::spawn(async move {
+            for candidate in candidates.iter().filter(|c| c.error.is_none()) {
+                if let Err(e) = storage
+                    .insert_turn_usage(&conv_id, &root_conv_id, &candidate.model, &candidate.usage)
+                    .await
+                {
+                    tracing::warn!(error = %e, "failed to write turn_usage row");
+                }
+            }
+            if let Err(e) = storage
+                .record_council_candidates(&conv_id, &candidates)
+                .await
+            {
+                tracing::warn!(error = %e, "failed to record council candidates");
+            }
+        });
+    }
+
+    /// Add what each tool's outputs cost one request to the conversation's
+    /// per-tool totals (REQ-API-031). Fire-and-forget.
+    fn record_tool_tokens(storage: &S, conv_id: &str, tokens: Vec<(String, usize)>) {
+        if tokens.is_empty() {
+            return;
+        }
+        let storage = storage.clone();
+        let conv_id = conv_id.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = storage.record_tool_token_usage(&conv_id, &tokens).await {
+                tracing::warn!(error = %e, "failed to record tool token usage");
+            }
+        });
     }
 
     /// Build LLM messages from conversation history (static, for spawned tasks)
+    ///
+    /// Only the part of the history `window` selects is sent (REQ-BED-046).
+    /// Images are made provider-safe on the way out (REQ-BED-040); models
+    /// without vision see a placeholder instead. Replies another model wrote
+    /// lose their thinking (REQ-LLM-020).
+    #[allow(clippy::too_many_lines)]
     async fn build_llm_messages_static(
         storage: &S,
         conv_id: &str,
+        model_id: &str,
+        supports_vision: bool,
+        window: HistoryWindow,
+        provider: Option<Provider>,
     ) -> Result<Vec<LlmMessage>, String> {
         use crate::db::{MessageContent, ToolContent};
         use crate::llm::ImageSource;
 
         let db_messages = storage.get_messages(conv_id).await?;
 
-        let mut messages = Vec::new();
+        // Pins only matter when part of the history is left out
+        let pinned: std::collections::HashSet<String> = if window == HistoryWindow::Full {
+            std::collections::HashSet::new()
+        } else {
+            match storage.get_pinned_messages(conv_id).await {
+                Ok(pinned) => pinned.into_iter().map(|m| m.message_id).collect(),
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to load pinned messages");
+                    std::collections::HashSet::new()
+                }
+            }
+        };
+
+        let mut entries = Vec::new();
 
         for msg in db_messages {
-            match &msg.content {
+            let message = match &msg.content {
                 MessageContent::User(user_content) => {
                     // Use llm_text when expansion occurred (REQ-IR-001, REQ-IR-006):
                     // the model sees the fully resolved form while the DB stores the shorthand.
@@ -1817,17 +2896,39 @@ where
                         });
                     }
 
-                    messages.push(LlmMessage {
+                    LlmMessage {
                         role: MessageRole::User,
                         content,
-                    });
+                    }
                 }
 
                 MessageContent::Agent(blocks) => {
-                    messages.push(LlmMessage {
+                    let content = match &msg.model {
+                        Some(model) if model != model_id => without_thinking(blocks),
+                        _ => blocks.clone(),
+                    };
+                    if content.is_empty() {
+                        continue;
+                    }
+                    LlmMessage {
                         role: MessageRole::Assistant,
-                        content: blocks.clone(),
-                    });
+                        content,
+                    }
+                }
+
+                // A command the user ran themselves answers no tool call; the
+                // agent reads it as the user telling it what they ran (REQ-BASH-020)
+                MessageContent::Tool(ToolContent {
+                    content,
+                    is_error,
+                    user_command: Some(command),
+                    ..
+                }) => {
+                    let text = user_command_text(command, content, *is_error);
+                    LlmMessage {
+                        role: MessageRole::User,
+                        content: vec![ContentBlock::text(text)],
+                    }
                 }
 
                 MessageContent::Tool(ToolContent {
@@ -1835,6 +2936,7 @@ where
                     content,
                     is_error,
                     images,
+                    user_command: None,
                 }) => {
                     // Convert stored ToolContentImages to LLM ImageSources
                     let image_sources: Vec<ImageSource> = images
@@ -1846,7 +2948,7 @@ where
                         .collect();
 
                     // Tool results go in user message
-                    messages.push(LlmMessage {
+                    LlmMessage {
                         role: MessageRole::User,
                         content: vec![ContentBlock::ToolResult {
                             tool_use_id: tool_use_id.clone(),
@@ -1854,16 +2956,14 @@ where
                             images: image_sources,
                             is_error: *is_error,
                         }],
-                    });
+                    }
                 }
 
                 // Skill messages are delivered as user-role messages (REQ-SK-002)
-                MessageContent::Skill(skill_content) => {
-                    messages.push(LlmMessage {
-                        role: MessageRole::User,
-                        content: vec![ContentBlock::text(&skill_content.body)],
-                    });
-                }
+                MessageContent::Skill(skill_content) => LlmMessage {
+                    role: MessageRole::User,
+                    content: vec![ContentBlock::text(&skill_content.body)],
+                },
 
                 // Ignore system, error, and continuation messages.
                 // System messages are UI-only bookkeeping (restart markers, task
@@ -1871,8 +2971,24 @@ where
                 // MessageContent::User with is_meta (e.g., grace turn prompt).
                 MessageContent::System(_)
                 | MessageContent::Error(_)
-                | MessageContent::Continuation(_) => {}
-            }
+                | MessageContent::Continuation(_) => continue,
+            };
+            entries.push(HistoryEntry {
+                message,
+                pinned: pinned.contains(&msg.message_id),
+            });
+        }
+
+        let mut messages = history::apply(entries, window, provider);
+        let report = images::prepare_images(&mut messages, supports_vision, ImageLimits::DEFAULT);
+        if report.omitted > 0 || report.retyped > 0 {
+            tracing::debug!(
+                conv_id = %conv_id,
+                kept = report.kept,
+                omitted = report.omitted,
+                retyped = report.retyped,
+                "Prepared images for LLM request"
+            );
         }
 
         Ok(messages)
@@ -1885,13 +3001,26 @@ where
         let storage = self.storage.clone();
         let event_tx = self.event_tx.clone();
         let conv_id = self.context.conversation_id.clone();
+        let context_window = self.context.context_window;
+        let model_id = self.context.model_id.clone();
+        let provider = self.llm_registry.provider(&model_id);
+        let supports_vision = self.llm_registry.supports_vision(&model_id);
 
         // Build continuation prompt
         let continuation_prompt = build_continuation_prompt(&rejected_tool_calls);
 
         let handle = tokio::spawn(async move {
-            // Build messages from history and add continuation request
-            let messages = match Self::build_llm_messages_static(&storage, &conv_id).await {
+            // Build messages from history and add continuation request. The
+            // summary covers everything, whatever the conversation's window.
+            let messages = Self::build_llm_messages_static(
+                &storage,
+                &conv_id,
+                &model_id,
+                supports_vision,
+                HistoryWindow::Full,
+                provider,
+            );
+            let messages = match messages.await {
                 Ok(m) => m,
                 Err(e) => {
                     tracing::error!(error = %e, "Failed to build messages for continuation");
@@ -1916,7 +3045,7 @@ where
             });
 
             // Build a tool-less request
-            let request = LlmRequest {
+            let mut request = LlmRequest {
                 messages,
                 system: vec![SystemContent::new(
                     "You are wrapping up a conversation that has reached its context limit. \
@@ -1924,12 +3053,24 @@ where
                 )],
                 tools: vec![],          // No tools for continuation
                 max_tokens: Some(2000), // Limit summary length
+                thinking_budget: None,
                 // Same conversation as the main loop — different system
                 // prompt won't share a prefix in practice, but using the
                 // conv id keeps the cache cohort coherent.
                 cache_key: PromptCacheKey::stable(&conv_id),
             };
 
+            // Even without tool blocks the history may not fit; summarize
+            // the most recent part rather than nothing (REQ-BED-036).
+            let dropped = preflight::drop_oldest_messages(&mut request, context_window, provider);
+            if dropped > 0 {
+                tracing::info!(
+                    conv_id = %conv_id,
+                    dropped,
+                    "Dropped oldest messages to fit the continuation request"
+                );
+            }
+
             match llm_client.complete(&request).await {
                 Ok(response) => {
                     // Extract the text content as summary
@@ -2023,6 +3164,8 @@ where
                     commits_behind: None,
                     commits_ahead: None,
                     task_title: None,
+                    slug: None,
+                    title: None,
                 },
             });
 
@@ -2172,6 +3315,8 @@ where
                             commits_behind: None,
                             commits_ahead: None,
                             task_title: Some(approval_result.task_title.clone()),
+                            slug: None,
+                            title: None,
                         },
                     });
 
@@ -2230,6 +3375,68 @@ struct TaskApprovalResult {
 /// `tool_search_tool_result`, or MCP block in history would cause the
 /// API to 400 with "Tool reference X not found in available tools".
 /// The summary still has the assistant's text narration to work with.
+/// Convert a tool's output into the outcome stored on its `ToolResult`.
+fn tool_outcome_from_output(out: ToolOutput) -> ToolOutcome {
+    let images: Vec<ToolContentImage> = out
+        .images
+        .into_iter()
+        .map(|img| ToolContentImage {
+            media_type: img.media_type,
+            data: img.data,
+        })
+        .collect();
+    if out.success {
+        ToolOutcome::Success {
+            output: out.output,
+            display_data: out.display_data,
+            images,
+        }
+    } else {
+        ToolOutcome::Error {
+            output: out.output,
+            display_data: out.display_data,
+            images,
+        }
+    }
+}
+
+/// Files a tool call reads or edits, resolved against the working directory
+/// (REQ-BED-042). Only `read_file`, `read_image`, `inspect_binary` and
+/// `patch` name files in their input; other tools touch nothing we can
+/// attribute.
+fn touched_paths(
+    tool_name: &str,
+    input: &serde_json::Value,
+    working_dir: &std::path::Path,
+) -> Vec<(String, TouchKind)> {
+    let kind = match tool_name {
+        "read_file" | "read_image" | "inspect_binary" => TouchKind::Read,
+        "patch" => TouchKind::Edit,
+        _ => return Vec::new(),
+    };
+    let files = input["files"].as_array().into_iter().flatten();
+    std::iter::once(&input["path"])
+        .chain(files.map(|file| &file["path"]))
+        .filter_map(serde_json::Value::as_str)
+        .filter(|path| !path.is_empty())
+        .map(|path| (working_dir.join(path).display().to_string(), kind))
+        .collect()
+}
+
+/// Count each touch in the index. Failures are logged: the index is a hint
+/// for the model, not a record anything depends on.
+async fn record_touches<S: StateStore>(
+    storage: &S,
+    conv_id: &str,
+    touches: &[(String, TouchKind)],
+) {
+    for (path, kind) in touches {
+        if let Err(e) = storage.record_file_touch(conv_id, path, *kind).await {
+            tracing::warn!(error = %e, path = %path, "Failed to record touched file");
+        }
+    }
+}
+
 fn strip_all_tool_blocks(messages: Vec<LlmMessage>) -> Vec<LlmMessage> {
     use crate::llm::ContentBlock;
 
@@ -2255,6 +3462,84 @@ fn strip_all_tool_blocks(messages: Vec<LlmMessage>) -> Vec<LlmMessage> {
         .collect()
 }
 
+/// Text of the most recent user-authored message. Tool-result-only user
+/// messages are skipped so triggers keep tracking what the user last typed.
+fn latest_user_text(messages: &[LlmMessage]) -> Option<String> {
+    messages
+        .iter()
+        .rev()
+        .filter(|m| m.role == MessageRole::User)
+        .find_map(|m| {
+            let parts: Vec<&str> = m
+                .content
+                .iter()
+                .filter_map(|b| match b {
+                    ContentBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect();
+            (!parts.is_empty()).then(|| parts.join("\n"))
+        })
+}
+
+/// Blank thinking text in place (REQ-LLM-014), returning the removed text
+/// keyed by signature so [`restore_thinking`] can put it back.
+fn redact_thinking(blocks: &mut [ContentBlock]) -> Vec<(String, String)> {
+    blocks
+        .iter_mut()
+        .filter_map(|block| match block {
+            ContentBlock::Thinking {
+                thinking,
+                signature,
+            } if !thinking.is_empty() => Some((signature.clone(), std::mem::take(thinking))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// `blocks` without thinking, for a reply another model wrote
+/// (REQ-LLM-020). A thinking signature only verifies against the model that
+/// produced it, so a history that switched models would be rejected; the
+/// text and tool calls stay.
+fn without_thinking(blocks: &[ContentBlock]) -> Vec<ContentBlock> {
+    blocks
+        .iter()
+        .filter(|b| {
+            !matches!(
+                b,
+                ContentBlock::Thinking { .. } | ContentBlock::RedactedThinking { .. }
+            )
+        })
+        .cloned()
+        .collect()
+}
+
+/// How a command the user ran themselves reads to the agent (REQ-BASH-020).
+fn user_command_text(command: &str, output: &str, is_error: bool) -> String {
+    let outcome = if is_error { "It failed" } else { "Output" };
+    format!("I ran this command myself:\n$ {command}\n\n{outcome}:\n{output}")
+}
+
+/// Refill blanked thinking blocks whose signature is in `held`.
+fn restore_thinking(messages: &mut [LlmMessage], held: &[(String, String)]) {
+    if held.is_empty() {
+        return;
+    }
+    for block in messages.iter_mut().flat_map(|m| m.content.iter_mut()) {
+        if let ContentBlock::Thinking {
+            thinking,
+            signature,
+        } = block
+        {
+            if let Some((_, text)) = held.iter().find(|(sig, _)| sig == signature) {
+                if thinking.is_empty() {
+                    thinking.clone_from(text);
+                }
+            }
+        }
+    }
+}
+
 /// Remove `tool_use` and `tool_result` blocks that reference tools not in the current set.
 ///
 /// Handles mode transitions (e.g., Explore -> Work) where the tool set changes
@@ -2289,6 +3574,18 @@ fn merge_duration_into_display_data(
     }
 }
 
+/// `tool_use_id`s of pinned tool results, whose outputs preflight must keep
+/// (REQ-BED-045).
+fn pinned_tool_use_ids(pinned: &[crate::db::Message]) -> std::collections::HashSet<String> {
+    pinned
+        .iter()
+        .filter_map(|msg| match &msg.content {
+            MessageContent::Tool(tool) => Some(tool.tool_use_id.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
 fn strip_unavailable_tool_blocks(
     messages: Vec<LlmMessage>,
     available_tools: &std::collections::HashSet<&str>,
@@ -2622,6 +3919,27 @@ mod strip_tool_blocks_tests {
         assert_eq!(out.len(), 1);
         assert!(matches!(&out[0].content[0], ContentBlock::Text { text } if text == "survives"));
     }
+
+    // ----- latest_user_text -----
+
+    #[test]
+    fn latest_user_text_skips_tool_result_messages() {
+        let msgs = vec![
+            user_text("please review this"),
+            assistant(vec![tool_use("t1", "bash")]),
+            user(vec![tool_result("t1")]),
+        ];
+        assert_eq!(
+            latest_user_text(&msgs).as_deref(),
+            Some("please review this")
+        );
+    }
+
+    #[test]
+    fn latest_user_text_none_without_user_text() {
+        let msgs = vec![assistant(vec![ContentBlock::text("hi")])];
+        assert_eq!(latest_user_text(&msgs), None);
+    }
 }
 
 /// Get the next task ID using taskmd-core library.
@@ -2986,6 +4304,93 @@ fn llm_error_to_outcome(error: crate::llm::LlmError) -> LlmOutcome {
     }
 }
 
+#[cfg(test)]
+mod thinking_tests {
+    use super::*;
+
+    fn thinking(text: &str, signature: &str) -> ContentBlock {
+        ContentBlock::Thinking {
+            thinking: text.into(),
+            signature: signature.into(),
+        }
+    }
+
+    #[test]
+    fn redacted_thinking_is_restored_by_signature() {
+        let mut blocks = vec![
+            thinking("Check the tests first.", "sig-1"),
+            ContentBlock::tool_use("t1", "bash", serde_json::json!({})),
+        ];
+        let held = redact_thinking(&mut blocks);
+        assert_eq!(blocks[0], thinking("", "sig-1"));
+
+        let mut messages = vec![LlmMessage {
+            role: MessageRole::Assistant,
+            content: vec![thinking("", "sig-0"), blocks[0].clone()],
+        }];
+        restore_thinking(&mut messages, &held);
+        // Only the block this runtime blanked comes back.
+        assert_eq!(messages[0].content[0], thinking("", "sig-0"));
+        assert_eq!(
+            messages[0].content[1],
+            thinking("Check the tests first.", "sig-1")
+        );
+    }
+
+    #[test]
+    fn thinking_display_data_summarizes_unless_redacted() {
+        let blocks = vec![
+            thinking("\nFirst, read the diff.\nThen run tests.", "s"),
+            ContentBlock::RedactedThinking { data: "x".into() },
+            ContentBlock::text("Done."),
+        ];
+        let shown = compute_thinking_display_data(&blocks, false).unwrap();
+        assert_eq!(shown["blocks"], 2);
+        assert_eq!(shown["summary"], "First, read the diff.");
+
+        let hidden = compute_thinking_display_data(&blocks, true).unwrap();
+        assert_eq!(hidden["redacted"], true);
+        assert!(hidden.get("summary").is_none());
+
+        assert!(compute_thinking_display_data(&blocks[2..], false).is_none());
+    }
+
+    #[tokio::test]
+    async fn thinking_another_model_wrote_is_left_out_of_the_request() {
+        use crate::db::UsageData;
+        use crate::runtime::testing::{InMemoryStorage, MockLlmClient, MockToolExecutor};
+        use crate::runtime::traits::MessageStore;
+        type Runtime =
+            ConversationRuntime<Arc<InMemoryStorage>, Arc<MockLlmClient>, Arc<MockToolExecutor>>;
+
+        let storage = Arc::new(InMemoryStorage::new());
+        let reply = MessageContent::agent(vec![thinking("plan", "sig"), ContentBlock::text("ok")]);
+        for (id, model) in [("m-1", "other-model"), ("m-2", "test-model")] {
+            let usage = UsageData {
+                model: Some(model.to_string()),
+                ..UsageData::default()
+            };
+            storage
+                .add_message(id, "c", &reply, None, Some(&usage))
+                .await
+                .unwrap();
+        }
+
+        let messages = Runtime::build_llm_messages_static(
+            &storage,
+            "c",
+            "test-model",
+            true,
+            HistoryWindow::Full,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(messages[0].content, [ContentBlock::text("ok")]);
+        assert_eq!(messages[1].content.len(), 2);
+    }
+}
+
 #[cfg(test)]
 mod error_mapping_tests {
     use super::*;
@@ -3037,6 +4442,55 @@ mod error_mapping_tests {
     }
 }
 
+#[cfg(test)]
+mod tool_timeout_tests {
+    use super::*;
+
+    fn timeouts(vars: &[(&str, &str)]) -> ToolTimeouts {
+        ToolTimeouts::from_lookup(|name| {
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| (*v).to_string())
+        })
+    }
+
+    #[test]
+    fn default_applies_to_every_tool() {
+        let t = timeouts(&[]);
+        assert_eq!(t.for_tool("bash"), Some(DEFAULT_TOOL_TIMEOUT));
+
+        let t = timeouts(&[("PHOENIX_TOOL_TIMEOUT_SECS", "90")]);
+        assert_eq!(
+            t.for_tool("browser_navigate"),
+            Some(Duration::from_secs(90))
+        );
+
+        let t = timeouts(&[("PHOENIX_TOOL_TIMEOUT_SECS", "0")]);
+        assert_eq!(t.for_tool("bash"), None);
+
+        let t = timeouts(&[("PHOENIX_TOOL_TIMEOUT_SECS", "soon")]);
+        assert_eq!(t.for_tool("bash"), Some(DEFAULT_TOOL_TIMEOUT));
+    }
+
+    #[test]
+    fn per_tool_overrides_win() {
+        let t = timeouts(&[
+            ("PHOENIX_TOOL_TIMEOUT_SECS", "60"),
+            (
+                "PHOENIX_TOOL_TIMEOUTS",
+                "browser_wait_for=15, bash=0,bogus,tmux=x",
+            ),
+        ]);
+        assert_eq!(
+            t.for_tool("browser_wait_for"),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(t.for_tool("bash"), None);
+        assert_eq!(t.for_tool("tmux"), Some(Duration::from_secs(60)));
+        assert_eq!(t.for_tool("read_file"), Some(Duration::from_secs(60)));
+    }
+}
+
 /// Task 24696 Phase 3: verify the `Effect::NotifyContextExhausted` handler
 /// preserves the worktree and does NOT demote `conv_mode`. The old
 /// `cleanup_context_exhausted_worktree` path is gone — worktree handoff to a
@@ -3345,6 +4799,75 @@ mod context_exhausted_preserves_worktree_tests {
              (the original 4a94509 intent for Explore-mode leaks)"
         );
     }
+
+    /// REQ-API-017: applied transitions are recorded in order; rejected
+    /// events leave no row.
+    #[tokio::test]
+    async fn applied_transitions_are_recorded() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let conv_id = "transitions-1";
+        let (mut rt, _rx) = build_runtime_with_state(
+            storage.clone(),
+            conv_id,
+            PathBuf::from("/tmp"),
+            ConvState::LlmRequesting { attempt: 1 },
+        );
+
+        rt.process_event(Event::UserCancel { reason: None })
+            .await
+            .unwrap();
+        assert!(rt
+            .process_event(Event::RetryTimeout { attempt: 1 })
+            .await
+            .is_err());
+
+        let recorded = storage.get_transitions(conv_id);
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].event_type, "UserCancel");
+        assert_eq!(recorded[0].old_state["type"], "llm_requesting");
+        assert_eq!(recorded[0].new_state["type"], "idle");
+    }
+
+    /// REQ-BED-054: with the event log on, rejected events are kept next to
+    /// applied ones, each with the state it arrived in.
+    #[tokio::test]
+    async fn event_log_keeps_rejected_events() {
+        let storage = Arc::new(InMemoryStorage::new());
+        let conv_id = "events-1";
+        let (rt, _rx) = build_runtime_with_state(
+            storage.clone(),
+            conv_id,
+            PathBuf::from("/tmp"),
+            ConvState::LlmRequesting { attempt: 1 },
+        );
+        let mut rt = rt.with_event_log(true);
+
+        rt.process_event(Event::UserCancel { reason: None })
+            .await
+            .unwrap();
+        assert!(rt
+            .process_event(Event::RetryTimeout { attempt: 1 })
+            .await
+            .is_err());
+
+        let events = storage.get_events(conv_id);
+        let logged: Vec<(&str, EventDisposition)> = events
+            .iter()
+            .map(|e| (e.event_type.as_str(), e.disposition))
+            .collect();
+        assert_eq!(
+            logged,
+            vec![
+                ("UserCancel", EventDisposition::Applied),
+                ("RetryTimeout", EventDisposition::Rejected),
+            ]
+        );
+        assert_eq!(events[0].state["type"], "llm_requesting");
+        assert_eq!(events[1].state["type"], "idle");
+        assert!(events[1].error.is_some());
+        // The transition log still holds only the applied step
+        assert_eq!(storage.get_transitions(conv_id).len(), 1);
+    }
 }
 
 // ============================================================
@@ -3500,3 +5023,106 @@ mod cwd_immutability_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod turn_limit_tests {
+    use super::*;
+
+    #[test]
+    fn env_overrides_and_disables_turn_limits() {
+        let vars = [
+            ("PHOENIX_TURN_MAX_TOOL_CALLS", "50"),
+            ("PHOENIX_TURN_MAX_MINUTES", "0"),
+            ("PHOENIX_TURN_MAX_REPEATS", "many"),
+        ];
+        let limits = turn_limits_from_lookup(|name| {
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| (*v).to_string())
+        });
+        assert_eq!(
+            limits,
+            TurnLimits {
+                max_tool_calls: 50,
+                max_duration: None,
+                ..TurnLimits::DEFAULT
+            }
+        );
+    }
+}
+
+#[cfg(test)]
+mod context_warning_tests {
+    use super::*;
+
+    #[test]
+    fn thresholds_from_env() {
+        let lookup = |raw: &'static str| {
+            move |name: &str| (name == "PHOENIX_CONTEXT_WARNING_THRESHOLDS").then(|| raw.into())
+        };
+        assert_eq!(
+            context_warning_thresholds_from_lookup(|_| None),
+            vec![70, 90]
+        );
+        assert_eq!(
+            context_warning_thresholds_from_lookup(lookup("95, 60,80")),
+            vec![60, 80, 95]
+        );
+        assert!(context_warning_thresholds_from_lookup(lookup("")).is_empty());
+        assert_eq!(
+            context_warning_thresholds_from_lookup(lookup("50,150")),
+            vec![70, 90]
+        );
+        assert_eq!(
+            context_warning_thresholds_from_lookup(lookup("most")),
+            vec![70, 90]
+        );
+    }
+
+    #[test]
+    fn each_threshold_fires_once() {
+        let thresholds = [70, 90];
+        assert_eq!(crossed_context_threshold(&thresholds, 69, 100, 0), None);
+        assert_eq!(crossed_context_threshold(&thresholds, 70, 100, 0), Some(70));
+        assert_eq!(crossed_context_threshold(&thresholds, 85, 100, 70), None);
+        assert_eq!(
+            crossed_context_threshold(&thresholds, 95, 100, 70),
+            Some(90)
+        );
+        assert_eq!(crossed_context_threshold(&thresholds, 95, 100, 0), Some(90));
+        assert_eq!(crossed_context_threshold(&thresholds, 99, 100, 90), None);
+        assert_eq!(crossed_context_threshold(&thresholds, 50, 0, 0), None);
+    }
+}
+
+#[cfg(test)]
+mod touched_paths_tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::Path;
+
+    #[test]
+    fn resolves_read_and_patch_paths() {
+        let dir = Path::new("/work");
+        assert_eq!(
+            touched_paths("read_file", &json!({"path": "src/a.rs"}), dir),
+            vec![("/work/src/a.rs".to_string(), TouchKind::Read)]
+        );
+        assert_eq!(
+            touched_paths("read_image", &json!({"path": "/tmp/shot.png"}), dir),
+            vec![("/tmp/shot.png".to_string(), TouchKind::Read)]
+        );
+        assert_eq!(
+            touched_paths(
+                "patch",
+                &json!({"files": [{"path": "a.rs"}, {"path": "b.rs"}]}),
+                dir
+            ),
+            vec![
+                ("/work/a.rs".to_string(), TouchKind::Edit),
+                ("/work/b.rs".to_string(), TouchKind::Edit),
+            ]
+        );
+        assert!(touched_paths("bash", &json!({"command": "cat src/a.rs"}), dir).is_empty());
+    }
+}
@@ -0,0 +1,226 @@
+//! Reminders for unanswered questions (REQ-BED-056).
+//!
+//! A conversation can opt in to follow-ups: when the agent has asked the
+//! user something and no answer came within `after_hours`, a watchdog sends
+//! one reminder by Web Push (REQ-API-024) and, if set, by `POST` to a
+//! webhook. Until the user answers, the conversation list flags the
+//! conversation as needing input.
+//!
+//! Each wait is reminded about once: a reminder sent after the conversation
+//! entered its current state covers it.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::db::{Database, FollowUpSettings, FollowUpWatch};
+use crate::push::{conversation_link, Notification, PushNotifier};
+use crate::state_machine::ConvState;
+
+/// How often the watchdog looks for overdue questions.
+pub const SWEEP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Longest accepted wait: a month.
+const MAX_AFTER_HOURS: u32 = 24 * 30;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body of the webhook `POST`.
+#[derive(Debug, Serialize)]
+struct Reminder<'a> {
+    conversation_id: &'a str,
+    title: &'a str,
+    /// Path of the conversation's page, relative to the server.
+    path: &'a str,
+    waiting_since: DateTime<Utc>,
+    questions: Vec<&'a str>,
+}
+
+/// Check follow-up settings before they are stored.
+pub fn validate(settings: &FollowUpSettings) -> Result<(), String> {
+    if !(1..=MAX_AFTER_HOURS).contains(&settings.after_hours) {
+        return Err(format!(
+            "after_hours must be between 1 and {MAX_AFTER_HOURS}"
+        ));
+    }
+    if let Some(url) = &settings.webhook_url {
+        let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid webhook URL: {e}"))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err("Webhook URL must be http or https".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Whether `state` is the agent waiting on the user's answer, asked with
+/// `ask_user_question`, `ask_user` (REQ-AUQ-009) or in prose (REQ-BED-057).
+pub fn awaits_answer(state: &ConvState) -> bool {
+    matches!(
+        state,
+        ConvState::AwaitingUserResponse { .. } | ConvState::AwaitingUserInput { .. }
+    )
+}
+
+/// Whether the conversation was reminded about the wait it is in now.
+pub fn needs_input(
+    state: &ConvState,
+    state_updated_at: DateTime<Utc>,
+    reminded_at: Option<DateTime<Utc>>,
+) -> bool {
+    awaits_answer(state) && reminded_at.is_some_and(|at| at >= state_updated_at)
+}
+
+fn is_due(watch: &FollowUpWatch, now: DateTime<Utc>) -> bool {
+    let waited = now - watch.state_updated_at;
+    awaits_answer(&watch.state)
+        && waited >= chrono::Duration::hours(i64::from(watch.settings.after_hours))
+        && watch
+            .reminded_at
+            .is_none_or(|at| at < watch.state_updated_at)
+}
+
+/// Send the reminders that are due. Failures are logged and retried on
+/// the next sweep; a reminder counts as sent once push has been tried and
+/// the webhook, if any, accepted it.
+pub async fn sweep(db: &Database, push: &PushNotifier, client: &reqwest::Client) {
+    let watches = match db.list_follow_up_watches().await {
+        Ok(watches) => watches,
+        Err(e) => {
+            tracing::warn!(error = %e, "Follow-up sweep: cannot list conversations");
+            return;
+        }
+    };
+    let now = Utc::now();
+    for watch in watches.iter().filter(|w| is_due(w, now)) {
+        let id = &watch.conversation_id;
+        let Ok(conv) = db.get_conversation(id).await else {
+            continue;
+        };
+        let (title, path) = conversation_link(&conv);
+        let questions = questions(&watch.state);
+        let hours = watch.settings.after_hours;
+        push.notify(&Notification {
+            title: format!("{title} needs your input"),
+            body: questions.first().map_or_else(
+                || format!("Waiting for your answer for {hours}h."),
+                |q| (*q).to_string(),
+            ),
+            url: path.clone(),
+            tag: id.clone(),
+        })
+        .await;
+
+        if let Some(url) = &watch.settings.webhook_url {
+            let reminder = Reminder {
+                conversation_id: id,
+                title: &title,
+                path: &path,
+                waiting_since: watch.state_updated_at,
+                questions,
+            };
+            let sent = client
+                .post(url)
+                .timeout(WEBHOOK_TIMEOUT)
+                .json(&reminder)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status);
+            if let Err(e) = sent {
+                tracing::warn!(conv_id = %id, error = %e, "Follow-up webhook failed");
+                continue;
+            }
+        }
+
+        tracing::info!(conv_id = %id, "Sent follow-up reminder");
+        if let Err(e) = db.mark_follow_up_reminded(id, now).await {
+            tracing::warn!(conv_id = %id, error = %e, "Cannot record follow-up reminder");
+        }
+    }
+}
+
+fn questions(state: &ConvState) -> Vec<&str> {
+    match state {
+        ConvState::AwaitingUserResponse { questions, .. } => {
+            questions.iter().map(|q| q.question.as_str()).collect()
+        }
+        ConvState::AwaitingUserInput { question, .. } => vec![question.as_str()],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_machine::state::UserQuestion;
+
+    fn watch(state: ConvState, hours_ago: i64, reminded: Option<i64>) -> FollowUpWatch {
+        let now = Utc::now();
+        FollowUpWatch {
+            conversation_id: "c1".to_string(),
+            settings: FollowUpSettings {
+                after_hours: 4,
+                webhook_url: None,
+            },
+            state,
+            state_updated_at: now - chrono::Duration::hours(hours_ago),
+            reminded_at: reminded.map(|h| now - chrono::Duration::hours(h)),
+        }
+    }
+
+    fn asking() -> ConvState {
+        ConvState::AwaitingUserResponse {
+            questions: vec![UserQuestion {
+                question: "Which database?".to_string(),
+                header: "Database".to_string(),
+                options: vec![],
+                multi_select: false,
+            }],
+            tool_use_id: "t1".to_string(),
+        }
+    }
+
+    #[test]
+    fn reminders_are_due_once_per_unanswered_wait() {
+        let now = Utc::now();
+        assert!(is_due(&watch(asking(), 5, None), now));
+        assert!(!is_due(&watch(asking(), 3, None), now));
+        assert!(!is_due(&watch(ConvState::Idle, 5, None), now));
+        let prose = ConvState::AwaitingUserInput {
+            question: "Should I push?".to_string(),
+            options: vec![],
+            ask: None,
+        };
+        assert!(is_due(&watch(prose, 5, None), now));
+        // Already reminded about this wait
+        assert!(!is_due(&watch(asking(), 5, Some(1)), now));
+        // Reminded about an earlier wait
+        assert!(is_due(&watch(asking(), 5, Some(8)), now));
+    }
+
+    #[test]
+    fn needs_input_until_answered() {
+        let w = watch(asking(), 5, Some(1));
+        assert!(needs_input(&w.state, w.state_updated_at, w.reminded_at));
+        assert!(!needs_input(&w.state, w.state_updated_at, None));
+        assert!(!needs_input(
+            &ConvState::Idle,
+            w.state_updated_at,
+            w.reminded_at
+        ));
+        assert_eq!(questions(&w.state), ["Which database?"]);
+    }
+
+    #[test]
+    fn validate_bounds_hours_and_webhook_scheme() {
+        let settings = |after_hours, url: Option<&str>| FollowUpSettings {
+            after_hours,
+            webhook_url: url.map(str::to_string),
+        };
+        assert!(validate(&settings(24, Some("https://hooks.example.com/x"))).is_ok());
+        assert!(validate(&settings(0, None)).is_err());
+        assert!(validate(&settings(MAX_AFTER_HOURS + 1, None)).is_err());
+        assert!(validate(&settings(1, Some("ftp://example.com"))).is_err());
+        assert!(validate(&settings(1, Some("not a url"))).is_err());
+    }
+}
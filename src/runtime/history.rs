@@ -0,0 +1,323 @@
+//! History window strategies (REQ-BED-046)
+//!
+//! By default every LLM request carries the whole conversation. A
+//! conversation can instead send only its most recent turns, optionally
+//! behind a condensed transcript of the older ones, or as many recent turns
+//! as fit a token budget. Windows only cut where a turn starts (a user
+//! message that is not a tool result), so tool calls stay paired with their
+//! results. Pinned messages (REQ-BED-045) that fall outside the window are
+//! quoted verbatim ahead of it.
+
+use crate::db::HistoryWindow;
+use crate::llm::preflight;
+use crate::llm::{ContentBlock, LlmMessage, MessageRole, Provider};
+use std::fmt::Write;
+
+/// Longest quote of one older message in the condensed transcript, in chars.
+const TRANSCRIPT_MESSAGE_CHARS: usize = 500;
+
+/// Cap on the whole condensed transcript, in chars. The oldest lines go
+/// first.
+const TRANSCRIPT_CHARS: usize = 8_000;
+
+/// One stored message, converted for the LLM.
+#[derive(Debug)]
+pub struct HistoryEntry {
+    pub message: LlmMessage,
+    /// The user pinned the stored message (REQ-BED-045).
+    pub pinned: bool,
+}
+
+/// The messages `window` sends out of the full history in `entries`.
+pub fn apply(
+    entries: Vec<HistoryEntry>,
+    window: HistoryWindow,
+    provider: Option<Provider>,
+) -> Vec<LlmMessage> {
+    let starts = turn_starts(&entries);
+    let keep = match window {
+        HistoryWindow::Full => starts.len(),
+        HistoryWindow::LastTurns { turns } | HistoryWindow::SummaryRecent { turns } => {
+            usize::try_from(turns).unwrap_or(usize::MAX)
+        }
+        HistoryWindow::TokenBudget { tokens } => {
+            let budget = usize::try_from(tokens).unwrap_or(usize::MAX);
+            turns_within_budget(&entries, &starts, budget, provider)
+        }
+    }
+    .max(1);
+    if keep >= starts.len() {
+        return entries.into_iter().map(|e| e.message).collect();
+    }
+
+    let mut older = entries;
+    let recent = older.split_off(starts[starts.len() - keep]);
+
+    let mut preface = String::new();
+    if matches!(window, HistoryWindow::SummaryRecent { .. }) {
+        preface.push_str("<earlier_conversation>\n");
+        preface.push_str(&transcript(&older));
+        preface.push_str("</earlier_conversation>\n");
+    } else {
+        let _ = writeln!(
+            preface,
+            "[{} earlier messages of this conversation are not shown.]",
+            older.len()
+        );
+    }
+    let pinned: Vec<&HistoryEntry> = older.iter().filter(|e| e.pinned).collect();
+    if !pinned.is_empty() {
+        preface.push_str("<pinned_messages>\n");
+        for entry in pinned {
+            let role = role_name(&entry.message);
+            let _ = writeln!(preface, "<{role}>\n{}\n</{role}>", text_of(&entry.message));
+        }
+        preface.push_str("</pinned_messages>\n");
+    }
+
+    let mut messages: Vec<LlmMessage> = recent.into_iter().map(|e| e.message).collect();
+    // The window opens on a user turn, so the preface joins that message.
+    if let Some(first) = messages.first_mut() {
+        first.content.insert(0, ContentBlock::text(preface));
+    }
+    messages
+}
+
+/// Indices of messages that start a turn.
+fn turn_starts(entries: &[HistoryEntry]) -> Vec<usize> {
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| is_turn_start(&e.message))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn is_turn_start(message: &LlmMessage) -> bool {
+    message.role == MessageRole::User
+        && message
+            .content
+            .iter()
+            .any(|b| !matches!(b, ContentBlock::ToolResult { .. }))
+}
+
+/// How many of the latest turns fit in `budget` tokens.
+fn turns_within_budget(
+    entries: &[HistoryEntry],
+    starts: &[usize],
+    budget: usize,
+    provider: Option<Provider>,
+) -> usize {
+    let mut used = 0;
+    let mut end = entries.len();
+    let mut turns = 0;
+    for &start in starts.iter().rev() {
+        used += entries[start..end]
+            .iter()
+            .map(|e| preflight::message_tokens(&e.message, provider))
+            .sum::<usize>();
+        if used > budget {
+            break;
+        }
+        turns += 1;
+        end = start;
+    }
+    turns
+}
+
+/// `User:`/`Assistant:` lines for the text of `entries`, clipped per
+/// message, keeping the most recent lines that fit `TRANSCRIPT_CHARS`.
+fn transcript(entries: &[HistoryEntry]) -> String {
+    let lines: Vec<String> = entries
+        .iter()
+        .filter_map(|entry| {
+            let speaker = match entry.message.role {
+                MessageRole::User if is_turn_start(&entry.message) => "User",
+                MessageRole::User => return None,
+                MessageRole::Assistant => "Assistant",
+            };
+            let text = text_of(&entry.message);
+            let text = text.trim();
+            if text.is_empty() {
+                return None;
+            }
+            let clipped = match text.char_indices().nth(TRANSCRIPT_MESSAGE_CHARS) {
+                Some((cut, _)) => format!("{}...", text.get(..cut).unwrap_or(text)),
+                None => text.to_string(),
+            };
+            Some(format!("{speaker}: {clipped}\n"))
+        })
+        .collect();
+
+    let mut total = 0;
+    let first_kept = lines
+        .iter()
+        .rposition(|line| {
+            total += line.len();
+            total > TRANSCRIPT_CHARS
+        })
+        .map_or(0, |i| i + 1);
+    lines[first_kept..].concat()
+}
+
+fn role_name(message: &LlmMessage) -> &'static str {
+    match message.role {
+        MessageRole::User if is_turn_start(message) => "user",
+        MessageRole::User => "tool_result",
+        MessageRole::Assistant => "assistant",
+    }
+}
+
+/// Text and tool output of a message, without tool calls or images.
+fn text_of(message: &LlmMessage) -> String {
+    message
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            ContentBlock::ToolResult { content, .. } => Some(content.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(role: MessageRole, content: ContentBlock) -> HistoryEntry {
+        HistoryEntry {
+            message: LlmMessage {
+                role,
+                content: vec![content],
+            },
+            pinned: false,
+        }
+    }
+
+    /// `turns` turns of: user text, assistant tool call, tool result,
+    /// assistant reply.
+    fn history(turns: usize) -> Vec<HistoryEntry> {
+        (0..turns)
+            .flat_map(|i| {
+                let id = format!("t{i}");
+                [
+                    entry(
+                        MessageRole::User,
+                        ContentBlock::text(format!("question {i}")),
+                    ),
+                    entry(
+                        MessageRole::Assistant,
+                        ContentBlock::tool_use(&id, "bash", serde_json::json!({})),
+                    ),
+                    entry(
+                        MessageRole::User,
+                        ContentBlock::ToolResult {
+                            tool_use_id: id,
+                            content: format!("output {i}"),
+                            images: vec![],
+                            is_error: false,
+                        },
+                    ),
+                    entry(
+                        MessageRole::Assistant,
+                        ContentBlock::text(format!("answer {i}")),
+                    ),
+                ]
+            })
+            .collect()
+    }
+
+    fn first_text(messages: &[LlmMessage]) -> &str {
+        match &messages[0].content[0] {
+            ContentBlock::Text { text } => text,
+            other => panic!("expected text, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn full_history_is_untouched() {
+        let messages = apply(history(3), HistoryWindow::Full, None);
+        assert_eq!(messages.len(), 12);
+        assert_eq!(first_text(&messages), "question 0");
+    }
+
+    #[test]
+    fn last_turns_cut_at_a_user_turn() {
+        let messages = apply(history(5), HistoryWindow::LastTurns { turns: 2 }, None);
+        assert_eq!(messages.len(), 8);
+        assert_eq!(messages[0].role, MessageRole::User);
+        assert!(first_text(&messages).contains("12 earlier messages"));
+        assert!(matches!(
+            &messages[0].content[1],
+            ContentBlock::Text { text } if text == "question 3"
+        ));
+    }
+
+    #[test]
+    fn window_larger_than_history_keeps_everything() {
+        let messages = apply(history(2), HistoryWindow::LastTurns { turns: 10 }, None);
+        assert_eq!(messages.len(), 8);
+        assert_eq!(first_text(&messages), "question 0");
+    }
+
+    #[test]
+    fn summary_recent_condenses_older_turns() {
+        let messages = apply(history(3), HistoryWindow::SummaryRecent { turns: 1 }, None);
+        assert_eq!(messages.len(), 4);
+        let preface = first_text(&messages);
+        assert!(
+            preface.contains("User: question 0\nAssistant: answer 0\n"),
+            "{preface}"
+        );
+        assert!(preface.contains("User: question 1"));
+        assert!(
+            !preface.contains("output 0"),
+            "tool output is left out: {preface}"
+        );
+    }
+
+    #[test]
+    fn token_budget_keeps_recent_turns_that_fit() {
+        let entries = history(4);
+        let per_turn: usize = entries[..4]
+            .iter()
+            .map(|e| preflight::message_tokens(&e.message, None))
+            .sum();
+        let budget = u32::try_from(per_turn * 2 + 1).unwrap();
+        let messages = apply(entries, HistoryWindow::TokenBudget { tokens: budget }, None);
+        assert_eq!(messages.len(), 8);
+
+        // Always at least the latest turn
+        let messages = apply(history(4), HistoryWindow::TokenBudget { tokens: 1 }, None);
+        assert_eq!(messages.len(), 4);
+    }
+
+    #[test]
+    fn pinned_messages_outside_the_window_are_quoted() {
+        let mut entries = history(3);
+        entries[0].pinned = true;
+        entries[2].pinned = true;
+        let messages = apply(entries, HistoryWindow::LastTurns { turns: 1 }, None);
+        let preface = first_text(&messages);
+        assert!(preface.contains("<pinned_messages>\n<user>\nquestion 0\n</user>\n"));
+        assert!(preface.contains("<tool_result>\noutput 0\n</tool_result>\n"));
+    }
+
+    #[test]
+    fn transcript_keeps_latest_lines_under_cap() {
+        let entries: Vec<HistoryEntry> = (0..40)
+            .map(|i| {
+                let text = format!("{i:02} {}", "x".repeat(400));
+                entry(MessageRole::User, ContentBlock::text(text))
+            })
+            .collect();
+        let text = transcript(&entries);
+        assert!(text.len() <= TRANSCRIPT_CHARS);
+        assert!(text.starts_with("User: "));
+        assert!(text.trim_end().ends_with(&"x".repeat(10)));
+        assert!(text.contains("User: 39 "));
+        assert!(!text.contains("User: 00 "));
+    }
+}
@@ -0,0 +1,151 @@
+//! Runtime locks across processes (REQ-BED-052).
+//!
+//! Several Phoenix processes may share one database. Each conversation's
+//! row names the process running its runtime (`runtime_owner`) and when that
+//! process last renewed the claim (`runtime_heartbeat_at`). A process only
+//! starts a runtime once it holds the lock, renews its locks every
+//! [`HEARTBEAT`], and releases them when a runtime ends or the server shuts
+//! down. A lock not renewed for [`crate::db::RUNTIME_LOCK_TTL_SECS`] is
+//! stale and any process may take it.
+//!
+//! Owners are `host:pid:nonce`. A process that dies without releasing its
+//! locks (a crash, or the socket-activated restart that exits at once)
+//! leaves them behind; the next server on the same host finds the pid gone
+//! and releases them at startup instead of waiting out the TTL.
+
+use crate::db::{Database, RUNTIME_LOCK_TTL_SECS};
+use chrono::{DateTime, Utc};
+use nix::errno::Errno;
+use nix::sys::signal::kill;
+use nix::unistd::Pid;
+use std::time::Duration;
+
+/// How often a process renews the locks it holds: a quarter of the TTL, so
+/// a lock survives a few missed renewals.
+pub const HEARTBEAT: Duration = Duration::from_secs(15);
+
+/// A lock owner id for this process.
+pub fn new_owner_id() -> String {
+    let mut nonce = uuid::Uuid::new_v4().simple().to_string();
+    nonce.truncate(8);
+    format!("{}:{}:{nonce}", host_name(), std::process::id())
+}
+
+/// Locks last renewed before this are stale.
+pub fn stale_before(now: DateTime<Utc>) -> DateTime<Utc> {
+    now - chrono::Duration::seconds(RUNTIME_LOCK_TTL_SECS)
+}
+
+/// Release the locks of processes on this host that no longer run. Returns
+/// how many locks were freed. Run at startup, before this process takes a
+/// lock: its own pid counts as a previous incarnation.
+pub async fn release_dead_owners(db: &Database) -> u64 {
+    let owners = match db.list_runtime_lock_owners().await {
+        Ok(owners) => owners,
+        Err(e) => {
+            tracing::warn!(error = %e, "Cannot list runtime lock owners");
+            return 0;
+        }
+    };
+    let host = host_name();
+    let mut released = 0;
+    for owner in owners {
+        if !is_dead_local_owner(&owner, &host) {
+            continue;
+        }
+        match db.release_runtime_locks(&owner).await {
+            Ok(count) => {
+                tracing::info!(%owner, count, "Released runtime locks of a stopped process");
+                released += count;
+            }
+            Err(e) => tracing::warn!(%owner, error = %e, "Cannot release runtime locks"),
+        }
+    }
+    released
+}
+
+/// Whether `owner` names a process on `host` that is not running. Owners
+/// from other hosts, or in a form this build does not write, are assumed
+/// alive; their locks expire with the TTL.
+fn is_dead_local_owner(owner: &str, host: &str) -> bool {
+    let mut parts = owner.rsplitn(3, ':');
+    let (Some(_nonce), Some(pid), Some(owner_host)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    let Ok(pid) = pid.parse::<i32>() else {
+        return false;
+    };
+    if owner_host != host {
+        return false;
+    }
+    // A process that exec'd itself keeps its pid but not its nonce.
+    if u32::try_from(pid).is_ok_and(|pid| pid == std::process::id()) {
+        return true;
+    }
+    kill(Pid::from_raw(pid), None) == Err(Errno::ESRCH)
+}
+
+fn host_name() -> String {
+    nix::unistd::gethostname()
+        .ok()
+        .and_then(|name| name.into_string().ok())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_stopped_local_processes_are_dead() {
+        let host = host_name();
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let gone = child.id();
+        child.wait().unwrap();
+
+        assert!(is_dead_local_owner(
+            &format!("{host}:{gone}:abcd1234"),
+            &host
+        ));
+        // The pid of this test process, under another nonce, is a previous
+        // incarnation of it
+        assert!(is_dead_local_owner(
+            &format!("{host}:{}:abcd1234", std::process::id()),
+            &host
+        ));
+        // pid 1 always runs
+        assert!(!is_dead_local_owner(&format!("{host}:1:abcd1234"), &host));
+        assert!(!is_dead_local_owner(
+            &format!("elsewhere:{gone}:abcd1234"),
+            &host
+        ));
+        assert!(!is_dead_local_owner("not an owner", &host));
+
+        let own = new_owner_id();
+        assert!(own.starts_with(&format!("{host}:{}:", std::process::id())));
+    }
+
+    #[tokio::test]
+    async fn dead_owners_are_released_at_startup() {
+        let db = Database::open_in_memory().await.unwrap();
+        db.create_conversation("c1", "c1", "/tmp", true, None, None)
+            .await
+            .unwrap();
+        db.create_conversation("c2", "c2", "/tmp", true, None, None)
+            .await
+            .unwrap();
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let dead = format!("{}:{}:abcd1234", host_name(), child.id());
+        child.wait().unwrap();
+        let live = format!("{}:1:abcd1234", host_name());
+        let now = Utc::now();
+        for (conv, owner) in [("c1", &dead), ("c2", &live)] {
+            let acquired = db.acquire_runtime_lock(conv, owner, now, stale_before(now));
+            assert!(acquired.await.unwrap());
+        }
+
+        assert_eq!(release_dead_owners(&db).await, 1);
+        assert_eq!(db.list_runtime_lock_owners().await.unwrap(), vec![live]);
+    }
+}
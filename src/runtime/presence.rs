@@ -0,0 +1,501 @@
+//! Multi-client presence and composer coordination (REQ-API-013)
+//!
+//! Two browser tabs streaming the same conversation both see every SSE event,
+//! but without coordination they can both submit: the user types in one tab,
+//! forgets, and types the same thing in the other. Each SSE connection
+//! registers a client here; the composer is a short lease one client holds
+//! while typing, and a submission from any other client is rejected while the
+//! lease is live. Identical text from a different client inside
+//! [`SUBMISSION_DEDUP_WINDOW`] is rejected as a duplicate — `message_id`
+//! idempotency only covers retries from the *same* tab.
+//!
+//! State is in-memory only. A server restart drops every SSE connection, and
+//! reconnecting clients re-register on their next `/stream`.
+
+use super::{SseBroadcaster, SseEvent};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a composer claim lasts without renewal. Clients renew while the
+/// user keeps typing; a tab that goes away mid-sentence frees the composer
+/// after this long even if its SSE connection lingers.
+pub const COMPOSER_LEASE: Duration = Duration::from_secs(15);
+
+/// Window in which identical text from a different client is a duplicate.
+pub const SUBMISSION_DEDUP_WINDOW: Duration = Duration::from_secs(10);
+
+/// Point-in-time view of who is connected and who holds the composer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresenceSnapshot {
+    /// Connected client ids, sorted.
+    pub clients: Vec<String>,
+    /// Client currently holding an unexpired composer lease.
+    pub composer_holder: Option<String>,
+}
+
+/// Why a submission was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmissionRejection {
+    /// Another client holds the composer.
+    ComposerLocked { holder: String },
+    /// The same text was just submitted from another client.
+    Duplicate,
+}
+
+impl std::fmt::Display for SubmissionRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmissionRejection::ComposerLocked { .. } => {
+                write!(
+                    f,
+                    "Another window is composing a message in this conversation"
+                )
+            }
+            SubmissionRejection::Duplicate => {
+                write!(f, "This message was just sent from another window")
+            }
+        }
+    }
+}
+
+impl SubmissionRejection {
+    /// Machine-readable `error_type` for the 409 response body.
+    pub fn error_type(&self) -> &'static str {
+        match self {
+            SubmissionRejection::ComposerLocked { .. } => "composer_locked",
+            SubmissionRejection::Duplicate => "duplicate_submission",
+        }
+    }
+}
+
+struct ComposerLease {
+    client_id: String,
+    expires_at: Instant,
+}
+
+struct Submission {
+    client_id: String,
+    fingerprint: [u8; 32],
+    at: Instant,
+}
+
+#[derive(Default)]
+struct ConversationPresence {
+    /// Open SSE connections per client. A client can briefly hold two while
+    /// its `EventSource` reconnects, so this is a count rather than a set.
+    connections: HashMap<String, usize>,
+    composer: Option<ComposerLease>,
+    last_submission: Option<Submission>,
+}
+
+impl ConversationPresence {
+    fn holder(&self, now: Instant) -> Option<&str> {
+        self.composer
+            .as_ref()
+            .filter(|lease| lease.expires_at > now)
+            .map(|lease| lease.client_id.as_str())
+    }
+
+    fn snapshot(&self, now: Instant) -> PresenceSnapshot {
+        let mut clients: Vec<String> = self.connections.keys().cloned().collect();
+        clients.sort();
+        PresenceSnapshot {
+            clients,
+            composer_holder: self.holder(now).map(str::to_string),
+        }
+    }
+}
+
+/// Process-wide presence table, keyed by conversation id.
+#[derive(Default)]
+pub struct PresenceRegistry {
+    conversations: Mutex<HashMap<String, ConversationPresence>>,
+}
+
+impl PresenceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an SSE connection and broadcast `ClientJoined`.
+    ///
+    /// Every connection broadcasts, not just a client's first: a reconnecting
+    /// tab needs the snapshot even when its previous connection has not been
+    /// dropped yet. The returned guard unregisters on drop, so it must live
+    /// exactly as long as the SSE response stream.
+    pub fn join(
+        self: &Arc<Self>,
+        conversation_id: &str,
+        client_id: &str,
+        broadcaster: SseBroadcaster,
+    ) -> PresenceGuard {
+        let snapshot = self.register(conversation_id, client_id, Instant::now());
+        let _ = broadcaster.send_seq(|seq| SseEvent::ClientJoined {
+            sequence_id: seq,
+            client_id: client_id.to_string(),
+            clients: snapshot.clients,
+            composer_holder: snapshot.composer_holder,
+        });
+        PresenceGuard {
+            registry: Arc::clone(self),
+            conversation_id: conversation_id.to_string(),
+            client_id: client_id.to_string(),
+            broadcaster,
+        }
+    }
+
+    /// Current presence for a conversation.
+    pub fn snapshot(&self, conversation_id: &str) -> PresenceSnapshot {
+        let now = Instant::now();
+        let conversations = self.lock();
+        conversations.get(conversation_id).map_or_else(
+            || PresenceSnapshot {
+                clients: Vec::new(),
+                composer_holder: None,
+            },
+            |p| p.snapshot(now),
+        )
+    }
+
+    /// Claim (or renew) the composer for `client_id`.
+    ///
+    /// Returns `Err(holder)` while another client's lease is live.
+    pub fn claim_composer(
+        &self,
+        conversation_id: &str,
+        client_id: &str,
+    ) -> Result<PresenceSnapshot, String> {
+        self.claim_composer_at(conversation_id, client_id, Instant::now())
+    }
+
+    /// Release the composer if `client_id` holds it. Returns the new snapshot
+    /// when the holder changed, `None` when there was nothing to release.
+    pub fn release_composer(
+        &self,
+        conversation_id: &str,
+        client_id: &str,
+    ) -> Option<PresenceSnapshot> {
+        let now = Instant::now();
+        let mut conversations = self.lock();
+        let presence = conversations.get_mut(conversation_id)?;
+        if presence.holder(now) != Some(client_id) {
+            return None;
+        }
+        presence.composer = None;
+        Some(presence.snapshot(now))
+    }
+
+    /// Gate a chat submission from `client_id`.
+    ///
+    /// On success the submission is recorded for dedup and the submitter's
+    /// composer lease is released; the returned snapshot is `Some` when that
+    /// release changed the holder and should be broadcast.
+    pub fn check_submission(
+        &self,
+        conversation_id: &str,
+        client_id: &str,
+        text: &str,
+    ) -> Result<Option<PresenceSnapshot>, SubmissionRejection> {
+        self.check_submission_at(conversation_id, client_id, text, Instant::now())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ConversationPresence>> {
+        self.conversations
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn register(&self, conversation_id: &str, client_id: &str, now: Instant) -> PresenceSnapshot {
+        let mut conversations = self.lock();
+        let presence = conversations
+            .entry(conversation_id.to_string())
+            .or_default();
+        *presence
+            .connections
+            .entry(client_id.to_string())
+            .or_insert(0) += 1;
+        presence.snapshot(now)
+    }
+
+    /// Drop one connection. Returns the snapshot after removal and whether
+    /// the client's composer lease was released with its last connection.
+    fn unregister(
+        &self,
+        conversation_id: &str,
+        client_id: &str,
+        now: Instant,
+    ) -> Option<(PresenceSnapshot, bool)> {
+        let mut conversations = self.lock();
+        let presence = conversations.get_mut(conversation_id)?;
+        let count = presence.connections.get_mut(client_id)?;
+        *count = count.saturating_sub(1);
+        let mut released = false;
+        if *count == 0 {
+            presence.connections.remove(client_id);
+            if presence.holder(now) == Some(client_id) {
+                presence.composer = None;
+                released = true;
+            }
+        }
+        let snapshot = presence.snapshot(now);
+        if presence.connections.is_empty() {
+            conversations.remove(conversation_id);
+        }
+        Some((snapshot, released))
+    }
+
+    fn claim_composer_at(
+        &self,
+        conversation_id: &str,
+        client_id: &str,
+        now: Instant,
+    ) -> Result<PresenceSnapshot, String> {
+        let mut conversations = self.lock();
+        let presence = conversations
+            .entry(conversation_id.to_string())
+            .or_default();
+        if let Some(holder) = presence.holder(now) {
+            if holder != client_id {
+                return Err(holder.to_string());
+            }
+        }
+        presence.composer = Some(ComposerLease {
+            client_id: client_id.to_string(),
+            expires_at: now + COMPOSER_LEASE,
+        });
+        Ok(presence.snapshot(now))
+    }
+
+    fn check_submission_at(
+        &self,
+        conversation_id: &str,
+        client_id: &str,
+        text: &str,
+        now: Instant,
+    ) -> Result<Option<PresenceSnapshot>, SubmissionRejection> {
+        let fingerprint: [u8; 32] = Sha256::digest(text.trim().as_bytes()).into();
+        let mut conversations = self.lock();
+        let presence = conversations
+            .entry(conversation_id.to_string())
+            .or_default();
+
+        if let Some(holder) = presence.holder(now) {
+            if holder != client_id {
+                return Err(SubmissionRejection::ComposerLocked {
+                    holder: holder.to_string(),
+                });
+            }
+        }
+        if let Some(last) = &presence.last_submission {
+            if last.client_id != client_id
+                && last.fingerprint == fingerprint
+                && now.duration_since(last.at) < SUBMISSION_DEDUP_WINDOW
+            {
+                return Err(SubmissionRejection::Duplicate);
+            }
+        }
+
+        presence.last_submission = Some(Submission {
+            client_id: client_id.to_string(),
+            fingerprint,
+            at: now,
+        });
+        let released = presence.holder(now) == Some(client_id);
+        if released {
+            presence.composer = None;
+        }
+        Ok(released.then(|| presence.snapshot(now)))
+    }
+}
+
+/// Keeps a client registered for the lifetime of one SSE connection.
+///
+/// On drop the connection is unregistered and `ClientLeft` is broadcast,
+/// followed by `ComposerChanged` if the departing client held the composer.
+pub struct PresenceGuard {
+    registry: Arc<PresenceRegistry>,
+    conversation_id: String,
+    client_id: String,
+    broadcaster: SseBroadcaster,
+}
+
+impl Drop for PresenceGuard {
+    fn drop(&mut self) {
+        let now = Instant::now();
+        let Some((snapshot, released)) =
+            self.registry
+                .unregister(&self.conversation_id, &self.client_id, now)
+        else {
+            return;
+        };
+        let composer_holder = snapshot.composer_holder.clone();
+        let _ = self.broadcaster.send_seq(|seq| SseEvent::ClientLeft {
+            sequence_id: seq,
+            client_id: self.client_id.clone(),
+            clients: snapshot.clients,
+            composer_holder: snapshot.composer_holder,
+        });
+        if released {
+            let _ = self.broadcaster.send_seq(|seq| SseEvent::ComposerChanged {
+                sequence_id: seq,
+                composer_holder,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> Arc<PresenceRegistry> {
+        Arc::new(PresenceRegistry::new())
+    }
+
+    fn broadcaster() -> SseBroadcaster {
+        SseBroadcaster::new(16, 0)
+    }
+
+    #[test]
+    fn join_and_leave_broadcast_snapshots() {
+        let reg = registry();
+        let tx = broadcaster();
+        let mut rx = tx.subscribe();
+
+        let a = reg.join("c1", "tab-a", tx.clone());
+        let b = reg.join("c1", "tab-b", tx.clone());
+        assert_eq!(reg.snapshot("c1").clients, vec!["tab-a", "tab-b"]);
+
+        drop(a);
+        assert_eq!(reg.snapshot("c1").clients, vec!["tab-b"]);
+        drop(b);
+        assert!(reg.snapshot("c1").clients.is_empty());
+
+        let mut kinds = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                SseEvent::ClientJoined { client_id, .. } => kinds.push(format!("+{client_id}")),
+                SseEvent::ClientLeft { client_id, .. } => kinds.push(format!("-{client_id}")),
+                other => panic!("unexpected event {other:?}"),
+            }
+        }
+        assert_eq!(kinds, vec!["+tab-a", "+tab-b", "-tab-a", "-tab-b"]);
+    }
+
+    #[test]
+    fn reconnect_overlap_keeps_client_present() {
+        let reg = registry();
+        let old = reg.join("c1", "tab-a", broadcaster());
+        let new = reg.join("c1", "tab-a", broadcaster());
+        drop(old);
+        assert_eq!(reg.snapshot("c1").clients, vec!["tab-a"]);
+        drop(new);
+        assert!(reg.snapshot("c1").clients.is_empty());
+    }
+
+    #[test]
+    fn composer_claim_blocks_other_clients_until_expiry() {
+        let reg = registry();
+        let now = Instant::now();
+        reg.claim_composer_at("c1", "tab-a", now).unwrap();
+
+        // Renewal by the holder succeeds; another client is refused.
+        reg.claim_composer_at("c1", "tab-a", now).unwrap();
+        assert_eq!(
+            reg.claim_composer_at("c1", "tab-b", now),
+            Err("tab-a".to_string())
+        );
+
+        // After the lease lapses the composer is free.
+        let later = now + COMPOSER_LEASE + Duration::from_secs(1);
+        let snap = reg.claim_composer_at("c1", "tab-b", later).unwrap();
+        assert_eq!(snap.composer_holder.as_deref(), Some("tab-b"));
+    }
+
+    #[test]
+    fn release_only_by_holder() {
+        let reg = registry();
+        reg.claim_composer("c1", "tab-a").unwrap();
+        assert!(reg.release_composer("c1", "tab-b").is_none());
+        let snap = reg.release_composer("c1", "tab-a").unwrap();
+        assert_eq!(snap.composer_holder, None);
+        assert!(reg.release_composer("c1", "tab-a").is_none());
+    }
+
+    #[test]
+    fn leaving_holder_frees_composer() {
+        let reg = registry();
+        let tx = broadcaster();
+        let mut rx = tx.subscribe();
+        let a = reg.join("c1", "tab-a", tx.clone());
+        let _b = reg.join("c1", "tab-b", tx.clone());
+        reg.claim_composer("c1", "tab-a").unwrap();
+
+        drop(a);
+        assert_eq!(reg.snapshot("c1").composer_holder, None);
+        let mut saw_composer_change = false;
+        while let Ok(event) = rx.try_recv() {
+            if let SseEvent::ComposerChanged {
+                composer_holder, ..
+            } = event
+            {
+                assert_eq!(composer_holder, None);
+                saw_composer_change = true;
+            }
+        }
+        assert!(saw_composer_change);
+    }
+
+    #[test]
+    fn submission_rejected_while_other_client_composes() {
+        let reg = registry();
+        reg.claim_composer("c1", "tab-a").unwrap();
+        assert_eq!(
+            reg.check_submission("c1", "tab-b", "hello"),
+            Err(SubmissionRejection::ComposerLocked {
+                holder: "tab-a".to_string()
+            })
+        );
+
+        // The holder's own submission goes through and releases the lease.
+        let snap = reg.check_submission("c1", "tab-a", "hello").unwrap();
+        assert_eq!(snap.unwrap().composer_holder, None);
+    }
+
+    #[test]
+    fn duplicate_text_from_other_client_rejected_within_window() {
+        let reg = registry();
+        let now = Instant::now();
+        reg.check_submission_at("c1", "tab-a", "run the tests", now)
+            .unwrap();
+
+        let soon = now + Duration::from_secs(2);
+        assert_eq!(
+            reg.check_submission_at("c1", "tab-b", "  run the tests\n", soon),
+            Err(SubmissionRejection::Duplicate)
+        );
+        // The same client repeating itself is intentional, not a duplicate.
+        assert!(reg
+            .check_submission_at("c1", "tab-a", "run the tests", soon)
+            .is_ok());
+        // Different text is fine.
+        assert!(reg
+            .check_submission_at("c1", "tab-b", "and lint", soon)
+            .is_ok());
+
+        let later = soon + SUBMISSION_DEDUP_WINDOW;
+        assert!(reg
+            .check_submission_at("c1", "tab-a", "and lint", later)
+            .is_ok());
+    }
+
+    #[test]
+    fn conversations_are_isolated() {
+        let reg = registry();
+        reg.claim_composer("c1", "tab-a").unwrap();
+        assert!(reg.claim_composer("c2", "tab-b").is_ok());
+        assert!(reg.check_submission("c2", "tab-b", "hi").is_ok());
+    }
+}
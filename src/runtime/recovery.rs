@@ -75,9 +75,10 @@ pub fn should_auto_continue(messages: &[Message]) -> RecoveryDecision {
         return RecoveryDecision::idle(RecoveryReason::EmptyConversation);
     }
 
-    // Last message must be a tool result
+    // Last message must be a tool result, and not one of a command the
+    // user ran themselves (REQ-BASH-020)
     let last_msg = messages.last().unwrap();
-    if !matches!(last_msg.message_type, MessageType::Tool) {
+    if !matches!(last_msg.message_type, MessageType::Tool) || is_user_command(last_msg) {
         return RecoveryDecision::idle(RecoveryReason::LastMessageNotTool);
     }
 
@@ -117,6 +118,10 @@ pub fn should_auto_continue(messages: &[Message]) -> RecoveryDecision {
     }
 }
 
+fn is_user_command(message: &Message) -> bool {
+    matches!(&message.content, MessageContent::Tool(tool) if tool.user_command.is_some())
+}
+
 /// Count restart system messages since the last user message.
 /// A user message starts a new turn, so restart markers from before
 /// that point are historical and don't count toward the loop threshold.
@@ -158,6 +163,7 @@ mod tests {
             }),
             display_data: None,
             usage_data: None,
+            model: None,
             created_at: Utc::now(),
         }
     }
@@ -182,6 +188,7 @@ mod tests {
             content: MessageContent::Agent(blocks),
             display_data: None,
             usage_data: None,
+            model: None,
             created_at: Utc::now(),
         }
     }
@@ -198,6 +205,7 @@ mod tests {
             }]),
             display_data: None,
             usage_data: None,
+            model: None,
             created_at: Utc::now(),
         }
     }
@@ -223,6 +231,7 @@ mod tests {
             content: MessageContent::Agent(blocks),
             display_data: None,
             usage_data: None,
+            model: None,
             created_at: Utc::now(),
         }
     }
@@ -239,9 +248,11 @@ mod tests {
                 content: output.to_string(),
                 is_error: false,
                 images: vec![],
+                user_command: None,
             }),
             display_data: None,
             usage_data: None,
+            model: None,
             created_at: Utc::now(),
         }
     }
@@ -389,6 +400,27 @@ mod tests {
         assert_eq!(decision.reason, RecoveryReason::NoAgentMessage);
     }
 
+    #[test]
+    fn test_user_command_after_cancelled_tool() {
+        // The user ran a command themselves after cancelling mid-tool
+        // (REQ-BASH-020): their output is not a result the agent awaits
+        let mut command = tool_result(4, "user-cmd-1", "On branch main");
+        command.content = MessageContent::Tool(ToolContent::user_command(
+            "git status",
+            "On branch main".to_string(),
+            false,
+        ));
+        let messages = vec![
+            user_msg(1, "List files"),
+            agent_tool_use_only(2, &["bash"]),
+            tool_result(3, "tool-2-0", "cancelled"),
+            command,
+        ];
+        let decision = should_auto_continue(&messages);
+        assert!(!decision.needs_auto_continue);
+        assert_eq!(decision.reason, RecoveryReason::LastMessageNotTool);
+    }
+
     #[test]
     fn test_agent_with_empty_blocks() {
         // Agent message with no content blocks at all
@@ -402,6 +434,7 @@ mod tests {
                 content: MessageContent::Agent(vec![]),
                 display_data: None,
                 usage_data: None,
+                model: None,
                 created_at: Utc::now(),
             },
             tool_result(3, "some-tool", "output"),
@@ -494,6 +527,7 @@ mod tests {
             }),
             display_data: None,
             usage_data: None,
+            model: None,
             created_at: Utc::now(),
         }
     }
@@ -634,6 +668,7 @@ mod proptests {
                 content: "tool output".to_string(),
                 is_error: false,
                 images: vec![],
+                user_command: None,
             }),
             _ => MessageContent::User(UserContent {
                 text: "fallback".to_string(),
@@ -651,6 +686,7 @@ mod proptests {
             content,
             display_data: None,
             usage_data: None,
+            model: None,
             created_at: Utc::now(),
         }
     }
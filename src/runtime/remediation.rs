@@ -0,0 +1,218 @@
+//! Recovery suggestions for conversations that land in `Error` (REQ-BED-035).
+//!
+//! `ConvState::Error` carries the provider's message and a coarse
+//! [`ErrorKind`]. That is enough for the state machine but not for a user
+//! deciding what to do next: "invalid request" covers both an over-long
+//! prompt (compact and carry on) and a malformed tool exchange (retrying
+//! will not help). [`classify`] refines the pair into an [`ErrorCategory`]
+//! and attaches the actions the UI should offer.
+//!
+//! Every string here is hand-written copy, in the same spirit as
+//! [`super::user_facing_error`]: the provider message is only *inspected*,
+//! never echoed.
+
+use crate::db::ErrorKind;
+use serde::Serialize;
+
+/// What went wrong, from the user's point of view.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, ts_rs::TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../ui/src/generated/")]
+pub enum ErrorCategory {
+    /// Credentials were rejected or could not be obtained.
+    Auth,
+    /// The provider throttled us and retries ran out.
+    RateLimit,
+    /// Connection failures or timeouts.
+    Network,
+    /// The provider returned 5xx until retries ran out.
+    ProviderOutage,
+    /// The prompt no longer fits the model's context window.
+    ContextTooLong,
+    /// The provider's safety filter blocked the response.
+    ContentFilter,
+    /// A tool call or tool result was malformed, or a sub-agent failed.
+    ToolFailure,
+    /// Any other rejected request.
+    InvalidRequest,
+    /// The request was cancelled before it completed.
+    Cancelled,
+}
+
+/// A next step the UI can offer as a button.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, ts_rs::TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export, export_to = "../ui/src/generated/")]
+pub enum RemediationAction {
+    /// Re-send the last user turn (`POST /api/conversations/:id/retry`).
+    Retry,
+    /// Summarize into a continuation (`POST .../trigger-continuation`).
+    Compact,
+    /// Open model/credential settings.
+    CheckCredentials,
+    /// Rephrase and send a new message.
+    EditMessage,
+    /// Start over in a fresh conversation.
+    NewConversation,
+}
+
+/// Structured recovery suggestion broadcast when a conversation enters
+/// `Error`. `actions` is ordered by preference.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, ts_rs::TS)]
+#[ts(export, export_to = "../ui/src/generated/")]
+pub struct Remediation {
+    pub category: ErrorCategory,
+    pub title: String,
+    pub suggestion: String,
+    pub actions: Vec<RemediationAction>,
+}
+
+/// Phrases providers use when the prompt exceeds the context window.
+const CONTEXT_TOO_LONG_MARKERS: &[&str] = &[
+    "prompt is too long",
+    "context length",
+    "context window",
+    "maximum context",
+    "too many tokens",
+];
+
+/// Phrases that point at a malformed tool exchange.
+const TOOL_FAILURE_MARKERS: &[&str] = &["tool_use", "tool_result"];
+
+/// Phrases that point at bad credentials behind a non-auth status.
+const AUTH_MARKERS: &[&str] = &["api key", "api_key", "unauthorized"];
+
+/// Classify an `Error` state's message and kind into a remediation.
+pub fn classify(message: &str, error_kind: &ErrorKind) -> Remediation {
+    let lower = message.to_lowercase();
+    let mentions = |markers: &[&str]| markers.iter().any(|m| lower.contains(m));
+
+    let category = match error_kind {
+        ErrorKind::Auth => ErrorCategory::Auth,
+        ErrorKind::RateLimit => ErrorCategory::RateLimit,
+        ErrorKind::Network | ErrorKind::TimedOut => ErrorCategory::Network,
+        ErrorKind::ServerError => ErrorCategory::ProviderOutage,
+        ErrorKind::ContextExhausted => ErrorCategory::ContextTooLong,
+        ErrorKind::ContentFilter => ErrorCategory::ContentFilter,
+        ErrorKind::SubAgentError => ErrorCategory::ToolFailure,
+        ErrorKind::Cancelled => ErrorCategory::Cancelled,
+        ErrorKind::InvalidRequest if mentions(CONTEXT_TOO_LONG_MARKERS) => {
+            ErrorCategory::ContextTooLong
+        }
+        ErrorKind::InvalidRequest if mentions(TOOL_FAILURE_MARKERS) => ErrorCategory::ToolFailure,
+        ErrorKind::InvalidRequest if mentions(AUTH_MARKERS) => ErrorCategory::Auth,
+        ErrorKind::InvalidRequest => ErrorCategory::InvalidRequest,
+    };
+    remediation_for(category)
+}
+
+fn remediation_for(category: ErrorCategory) -> Remediation {
+    use RemediationAction::{CheckCredentials, Compact, EditMessage, NewConversation, Retry};
+
+    let (title, suggestion, actions) = match category {
+        ErrorCategory::Auth => (
+            "Authentication failed",
+            "The model provider rejected Phoenix's credentials. Check the API key or \
+             credential helper in settings, then retry.",
+            vec![CheckCredentials, Retry],
+        ),
+        ErrorCategory::RateLimit => (
+            "Rate limited",
+            "The model provider is throttling requests. Wait a minute, then retry.",
+            vec![Retry],
+        ),
+        ErrorCategory::Network => (
+            "Could not reach the model provider",
+            "The request failed on the network or timed out. Check connectivity, then retry.",
+            vec![Retry],
+        ),
+        ErrorCategory::ProviderOutage => (
+            "Model provider unavailable",
+            "The model provider kept returning server errors. Retry in a few minutes.",
+            vec![Retry],
+        ),
+        ErrorCategory::ContextTooLong => (
+            "Context window exceeded",
+            "The conversation no longer fits the model's context window. Compact it into \
+             a summary to keep going, or start a new conversation.",
+            vec![Compact, NewConversation],
+        ),
+        ErrorCategory::ContentFilter => (
+            "Response blocked",
+            "The model provider's content filter blocked the response. Rephrase the \
+             request and send it again.",
+            vec![EditMessage],
+        ),
+        ErrorCategory::ToolFailure => (
+            "Tool step failed",
+            "A tool call or sub-agent failed in a way the agent could not recover from. \
+             Retry, or send a message describing how to proceed.",
+            vec![Retry, EditMessage],
+        ),
+        ErrorCategory::InvalidRequest => (
+            "Request rejected",
+            "The model provider rejected the request. Rephrase the last message, or \
+             start a new conversation if the problem persists.",
+            vec![EditMessage, NewConversation],
+        ),
+        ErrorCategory::Cancelled => (
+            "Request cancelled",
+            "The request was cancelled before it completed. Retry to send it again.",
+            vec![Retry],
+        ),
+    };
+    Remediation {
+        category,
+        title: title.to_string(),
+        suggestion: suggestion.to_string(),
+        actions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_request_is_refined_by_message() {
+        let too_long = classify(
+            "Failed after 1 attempts: prompt is too long: 210000 tokens > 200000 maximum",
+            &ErrorKind::InvalidRequest,
+        );
+        assert_eq!(too_long.category, ErrorCategory::ContextTooLong);
+        assert_eq!(too_long.actions[0], RemediationAction::Compact);
+
+        let tool = classify(
+            "messages.3: `tool_use` ids were found without `tool_result` blocks",
+            &ErrorKind::InvalidRequest,
+        );
+        assert_eq!(tool.category, ErrorCategory::ToolFailure);
+
+        let key = classify("Invalid API key provided", &ErrorKind::InvalidRequest);
+        assert_eq!(key.category, ErrorCategory::Auth);
+
+        let other = classify("temperature must be <= 1", &ErrorKind::InvalidRequest);
+        assert_eq!(other.category, ErrorCategory::InvalidRequest);
+    }
+
+    #[test]
+    fn auth_offers_settings_before_retry() {
+        let remediation = classify("401", &ErrorKind::Auth);
+        assert_eq!(
+            remediation.actions,
+            vec![
+                RemediationAction::CheckCredentials,
+                RemediationAction::Retry,
+            ]
+        );
+    }
+
+    #[test]
+    fn suggestion_never_echoes_provider_message() {
+        let message = "secret-internal-detail {\"code\": 500}";
+        let remediation = classify(message, &ErrorKind::ServerError);
+        assert_eq!(remediation.category, ErrorCategory::ProviderOutage);
+        assert!(!remediation.suggestion.contains("secret-internal-detail"));
+        assert!(!remediation.title.contains('{'));
+    }
+}
@@ -139,6 +139,8 @@ impl LlmClient for StreamingMockLlmClient {
 pub struct MockToolExecutor {
     outputs: HashMap<String, ToolOutput>,
     definitions: Vec<ToolDefinition>,
+    /// Sleep before answering, to simulate a hung tool
+    delay: Option<Duration>,
     /// Record of tool executions
     pub executions: Mutex<Vec<(String, Value)>>,
 }
@@ -149,6 +151,7 @@ impl MockToolExecutor {
         Self {
             outputs: HashMap::new(),
             definitions: Vec::new(),
+            delay: None,
             executions: Mutex::new(Vec::new()),
         }
     }
@@ -166,6 +169,12 @@ impl MockToolExecutor {
         self
     }
 
+    /// Delay every execution by `delay`
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
     /// Get recorded executions
     pub fn recorded_executions(&self) -> Vec<(String, Value)> {
         self.executions.lock().unwrap().clone()
@@ -185,6 +194,9 @@ impl ToolExecutor for MockToolExecutor {
             .lock()
             .unwrap()
             .push((name.to_string(), input));
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
         self.outputs.get(name).cloned()
     }
 
@@ -304,6 +316,9 @@ pub struct InMemoryStorage {
     messages: Mutex<HashMap<String, Vec<Message>>>,
     states: Mutex<HashMap<String, ConvState>>,
     modes: Mutex<HashMap<String, crate::db::ConvMode>>,
+    transitions: Mutex<Vec<crate::db::TransitionRecord>>,
+    events: Mutex<Vec<crate::db::EventRecord>>,
+    verify: Mutex<Option<crate::db::VerifySettings>>,
     next_msg_id: Mutex<u64>,
 }
 
@@ -314,10 +329,40 @@ impl InMemoryStorage {
             messages: Mutex::new(HashMap::new()),
             states: Mutex::new(HashMap::new()),
             modes: Mutex::new(HashMap::new()),
+            transitions: Mutex::new(Vec::new()),
+            events: Mutex::new(Vec::new()),
+            verify: Mutex::new(None),
             next_msg_id: Mutex::new(1),
         }
     }
 
+    /// Logged events for a conversation, in handling order.
+    pub fn get_events(&self, conv_id: &str) -> Vec<crate::db::EventRecord> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.conversation_id == conv_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Recorded transitions for a conversation, in application order.
+    pub fn get_transitions(&self, conv_id: &str) -> Vec<crate::db::TransitionRecord> {
+        self.transitions
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.conversation_id == conv_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Configure the project verify phase (REQ-BED-037) for every conversation.
+    pub fn set_verify_settings(&self, settings: Option<crate::db::VerifySettings>) {
+        *self.verify.lock().unwrap() = settings;
+    }
+
     /// Seed the `conv_mode` for a conversation (used by tests that need to
     /// exercise mode-aware effect handlers like `NotifyContextExhausted`).
     pub fn set_mode(&self, conv_id: &str, mode: crate::db::ConvMode) {
@@ -375,6 +420,7 @@ impl MessageStore for InMemoryStorage {
             content: content.clone(),
             display_data: display_data.cloned(),
             usage_data: usage_data.cloned(),
+            model: crate::db::message_model(content.message_type(), usage_data),
             created_at: chrono::Utc::now(),
         };
 
@@ -417,6 +463,7 @@ impl MessageStore for InMemoryStorage {
             content: content.clone(),
             display_data: display_data.cloned(),
             usage_data: usage_data.cloned(),
+            model: crate::db::message_model(content.message_type(), usage_data),
             created_at: chrono::Utc::now(),
         };
 
@@ -540,6 +587,69 @@ impl StateStore for InMemoryStorage {
     ) -> Result<(), String> {
         Ok(())
     }
+
+    async fn record_tool_audit(&self, _entry: &crate::db::AuditEntry) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn record_transition(&self, record: &crate::db::TransitionRecord) -> Result<(), String> {
+        let mut transitions = self.transitions.lock().unwrap();
+        let mut record = record.clone();
+        record.id = i64::try_from(transitions.len()).unwrap_or(i64::MAX) + 1;
+        transitions.push(record);
+        Ok(())
+    }
+
+    async fn record_event(&self, record: &crate::db::EventRecord) -> Result<(), String> {
+        let mut events = self.events.lock().unwrap();
+        let mut record = record.clone();
+        record.id = i64::try_from(events.len()).unwrap_or(i64::MAX) + 1;
+        events.push(record);
+        Ok(())
+    }
+
+    async fn get_verify_settings(
+        &self,
+        _conv_id: &str,
+    ) -> Result<Option<crate::db::VerifySettings>, String> {
+        Ok(self.verify.lock().unwrap().clone())
+    }
+
+    async fn record_file_touch(
+        &self,
+        _conv_id: &str,
+        _path: &str,
+        _kind: crate::db::TouchKind,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn get_touched_files(
+        &self,
+        _conv_id: &str,
+    ) -> Result<Vec<crate::db::TouchedFile>, String> {
+        Ok(Vec::new())
+    }
+
+    async fn get_pinned_messages(&self, _conv_id: &str) -> Result<Vec<Message>, String> {
+        Ok(Vec::new())
+    }
+
+    async fn record_council_candidates(
+        &self,
+        _conv_id: &str,
+        _candidates: &[crate::state_machine::state::CouncilCandidate],
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn record_tool_token_usage(
+        &self,
+        _conv_id: &str,
+        _tokens: &[(String, usize)],
+    ) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -547,6 +657,7 @@ impl StateStore for InMemoryStorage {
 // ============================================================================
 
 use crate::runtime::{ConversationRuntime, SseEvent};
+use crate::state_machine::budget::TurnLimits;
 use crate::state_machine::{ConvContext, Event};
 use std::path::PathBuf;
 use tokio::sync::{broadcast, mpsc};
@@ -574,6 +685,7 @@ pub struct TestRuntimeBuilder<L, T> {
     working_dir: PathBuf,
     llm: Option<L>,
     tools: Option<T>,
+    turn_limits: Option<TurnLimits>,
 }
 
 impl<L: LlmClient + 'static, T: ToolExecutor + 'static> TestRuntimeBuilder<L, T> {
@@ -591,6 +703,11 @@ impl<L: LlmClient + 'static, T: ToolExecutor + 'static> TestRuntimeBuilder<L, T>
         self.conv_id = id.into();
         self
     }
+
+    pub fn turn_limits(mut self, limits: TurnLimits) -> Self {
+        self.turn_limits = Some(limits);
+        self
+    }
 }
 
 impl TestRuntimeBuilder<MockLlmClient, MockToolExecutor> {
@@ -600,6 +717,7 @@ impl TestRuntimeBuilder<MockLlmClient, MockToolExecutor> {
             working_dir: PathBuf::from("/tmp"),
             llm: None,
             tools: None,
+            turn_limits: None,
         }
     }
 
@@ -628,6 +746,10 @@ impl TestRuntimeBuilder<MockLlmClient, MockToolExecutor> {
             event_tx.clone(),
             broadcaster,
         );
+        let runtime = match self.turn_limits {
+            Some(limits) => runtime.with_turn_limits(limits),
+            None => runtime,
+        };
 
         let handle = tokio::spawn(async move {
             runtime.run().await;
@@ -751,6 +873,7 @@ mod tests {
             messages: vec![],
             tools: vec![],
             max_tokens: Some(100),
+            thinking_budget: None,
             cache_key: PromptCacheKey::ephemeral(),
         };
 
@@ -867,6 +990,53 @@ mod tests {
         assert_eq!(msgs[3].message_type, MessageType::Agent);
     }
 
+    /// Agent messages carry the model's response time, tool results their
+    /// run time (REQ-BED-048).
+    #[tokio::test]
+    async fn test_turn_timing_in_display_data() {
+        use crate::llm::ContentBlock;
+
+        let llm = MockLlmClient::new("test-model");
+        llm.queue_response(LlmResponse {
+            content: vec![ContentBlock::tool_use(
+                "tool-1",
+                "bash",
+                serde_json::json!({"command": "ls"}),
+            )],
+            end_turn: false,
+            usage: Usage::default(),
+        });
+        llm.queue_response(LlmResponse {
+            content: vec![ContentBlock::text("Done!")],
+            end_turn: true,
+            usage: Usage::default(),
+        });
+        let tools = MockToolExecutor::new().with_tool("bash", ToolOutput::success("file1"));
+
+        let mut rt = TestRuntime::new().llm(llm).tools(tools).build();
+        rt.send_message("List files").await;
+        assert!(rt.wait_for_done(Duration::from_secs(2)).await);
+
+        let msgs = rt.messages();
+        let timing = |i: usize, key: &str| {
+            msgs[i]
+                .display_data
+                .as_ref()
+                .and_then(|d| d.get(key))
+                .and_then(serde_json::Value::as_u64)
+        };
+        assert!(
+            timing(1, "llm_duration_ms").is_some(),
+            "tool-round agent message"
+        );
+        assert!(timing(2, "duration_ms").is_some(), "tool result");
+        assert!(
+            timing(3, "llm_duration_ms").is_some(),
+            "final agent message"
+        );
+        assert!(timing(0, "llm_duration_ms").is_none());
+    }
+
     /// Integration test: LLM error triggers error state
     #[tokio::test]
     async fn test_llm_error_handling() {
@@ -880,6 +1050,190 @@ mod tests {
         assert!(rt.wait_for_state("error", Duration::from_secs(2)).await);
     }
 
+    /// Entering Error broadcasts a remediation; retry re-sends the last
+    /// user turn (REQ-BED-035).
+    #[tokio::test]
+    async fn test_error_remediation_then_retry() {
+        use crate::runtime::remediation::{ErrorCategory, RemediationAction};
+
+        let llm = MockLlmClient::new("test-model");
+        llm.queue_error(LlmError::auth("Invalid API key"));
+
+        let mut rt = TestRuntime::new().llm(llm).build();
+        rt.send_message("Hi").await;
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(2);
+        let mut remediation = None;
+        while remediation.is_none() && tokio::time::Instant::now() < deadline {
+            if let Ok(Ok(SseEvent::ErrorRemediation { remediation: r, .. })) =
+                tokio::time::timeout(Duration::from_millis(50), rt.broadcast_rx.recv()).await
+            {
+                remediation = Some(r);
+            }
+        }
+        let remediation = remediation.expect("remediation broadcast on entering Error");
+        assert_eq!(remediation.category, ErrorCategory::Auth);
+        assert_eq!(remediation.actions[0], RemediationAction::CheckCredentials);
+
+        rt.llm.queue_response(LlmResponse {
+            content: vec![ContentBlock::text("Hello!")],
+            end_turn: true,
+            usage: Usage::default(),
+        });
+        rt.event_tx.send(Event::UserRetry).await.unwrap();
+        assert!(rt.wait_for_done(Duration::from_secs(2)).await);
+
+        let requests = rt.llm.recorded_requests();
+        assert_eq!(requests.len(), 2);
+        let last_user = requests[1].messages.last().expect("history re-sent");
+        assert_eq!(last_user.content, vec![ContentBlock::text("Hi")]);
+        // The retried turn is not persisted a second time.
+        let user_messages = rt
+            .messages()
+            .iter()
+            .filter(|m| m.message_type == MessageType::User)
+            .count();
+        assert_eq!(user_messages, 1);
+    }
+
+    /// A turn that applied a patch runs the project's verify command; each
+    /// failure is fed back until the attempt budget is spent (REQ-BED-037).
+    #[tokio::test]
+    async fn test_verify_failure_feeds_back_until_budget_spent() {
+        use crate::db::{MessageContent, VerifySettings};
+
+        let patch = |id: &str| LlmResponse {
+            content: vec![ContentBlock::tool_use(id, "patch", serde_json::json!({}))],
+            end_turn: false,
+            usage: Usage::default(),
+        };
+        let done = || LlmResponse {
+            content: vec![ContentBlock::text("Done.")],
+            end_turn: true,
+            usage: Usage::default(),
+        };
+        let llm = MockLlmClient::new("test-model");
+        llm.queue_response(patch("p1"));
+        llm.queue_response(done());
+        llm.queue_response(patch("p2"));
+        llm.queue_response(done());
+        let tools = MockToolExecutor::new().with_tool("patch", ToolOutput::success("Applied"));
+
+        let rt = TestRuntime::new().llm(llm).tools(tools).build();
+        rt.storage.set_verify_settings(Some(VerifySettings {
+            command: "echo broken; exit 1".to_string(),
+            max_attempts: 1,
+        }));
+        rt.send_message("Fix the build").await;
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while !rt
+            .messages()
+            .iter()
+            .any(|m| matches!(m.content, MessageContent::System(_)))
+        {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "verify budget note never appeared"
+            );
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let msgs = rt.messages();
+        let feedback: Vec<&str> = msgs
+            .iter()
+            .filter_map(|m| match &m.content {
+                MessageContent::User(u) if u.is_meta => Some(u.text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(feedback.len(), 1, "one feedback turn for a budget of 1");
+        assert!(feedback[0].contains("`echo broken; exit 1` exited with status 1"));
+        assert!(feedback[0].contains("broken\n```"));
+        assert_eq!(rt.llm.recorded_requests().len(), 4);
+    }
+
+    /// An agent that keeps repeating the same tool call is paused for
+    /// guidance, and the user's next message resumes with a fresh budget
+    /// (REQ-BED-038).
+    #[tokio::test]
+    async fn test_repeated_tool_call_pauses_for_user_guidance() {
+        use crate::db::MessageContent;
+
+        let make = |id: &str| LlmResponse {
+            content: vec![ContentBlock::tool_use(
+                id,
+                "bash",
+                serde_json::json!({ "command": "make" }),
+            )],
+            end_turn: false,
+            usage: Usage::default(),
+        };
+        let llm = MockLlmClient::new("test-model");
+        llm.queue_response(make("t1"));
+        llm.queue_response(make("t2"));
+        llm.queue_response(make("t3"));
+        llm.queue_response(LlmResponse {
+            content: vec![ContentBlock::text("Done.")],
+            end_turn: true,
+            usage: Usage::default(),
+        });
+        let tools = MockToolExecutor::new().with_tool("bash", ToolOutput::success("ok"));
+
+        let mut rt = TestRuntime::new()
+            .llm(llm)
+            .tools(tools)
+            .turn_limits(TurnLimits {
+                max_repeats: 2,
+                ..TurnLimits::default()
+            })
+            .build();
+        rt.send_message("Build it").await;
+
+        assert!(
+            rt.wait_for_state("awaiting_user_guidance", Duration::from_secs(2))
+                .await
+        );
+        assert_eq!(
+            rt.llm.recorded_requests().len(),
+            2,
+            "third request withheld"
+        );
+        assert!(rt.messages().iter().any(|m| matches!(
+            &m.content,
+            MessageContent::System(s) if s.text.contains("`bash` with identical input 2 times")
+        )));
+
+        rt.send_message("Read the make output before retrying")
+            .await;
+        assert!(rt.wait_for_done(Duration::from_secs(2)).await);
+        assert_eq!(rt.llm.recorded_requests().len(), 4);
+    }
+
+    /// A prompt that cannot fit the window is never sent; the conversation
+    /// compacts instead (REQ-BED-036).
+    #[tokio::test]
+    async fn test_oversized_prompt_compacts_without_calling_provider() {
+        let llm = MockLlmClient::new("test-model");
+        llm.queue_response(LlmResponse {
+            content: vec![ContentBlock::text("Summary of the work so far.")],
+            end_turn: true,
+            usage: Usage::default(),
+        });
+
+        let mut rt = TestRuntime::new().llm(llm).build();
+        // ~230k estimated tokens against a 200k window.
+        rt.send_message(&"x".repeat(800_000)).await;
+
+        assert!(
+            rt.wait_for_state("context_exhausted", Duration::from_secs(2))
+                .await
+        );
+        let requests = rt.llm.recorded_requests();
+        assert_eq!(requests.len(), 1, "only the summary request is sent");
+        assert!(requests[0].tools.is_empty());
+    }
+
     /// Integration test: cancel during LLM request (REQ-BED-005)
     ///
     /// LLM requests are spawned as background tasks and can be cancelled
@@ -1742,6 +2096,213 @@ mod tests {
         );
     }
 
+    /// REQ-BED-033: a tool that outlives its budget is reported to the LLM
+    /// as an error result and the turn runs to completion.
+    #[tokio::test]
+    async fn test_tool_timeout_produces_error_result_and_continues() {
+        use crate::runtime::{ConversationRuntime, SseEvent};
+        use crate::state_machine::ConvContext;
+        use std::path::PathBuf;
+        use tokio::sync::mpsc;
+
+        let llm = Arc::new(MockLlmClient::new("test-model"));
+        llm.queue_response(LlmResponse {
+            content: vec![ContentBlock::tool_use(
+                "tool-1",
+                "browser_wait",
+                serde_json::json!({}),
+            )],
+            end_turn: false,
+            usage: Usage::default(),
+        });
+        llm.queue_response(LlmResponse {
+            content: vec![ContentBlock::text("Moving on")],
+            end_turn: true,
+            usage: Usage::default(),
+        });
+
+        let tools = Arc::new(
+            MockToolExecutor::new()
+                .with_tool("browser_wait", ToolOutput::success("never seen"))
+                .with_delay(Duration::from_secs(30)),
+        );
+        let storage = Arc::new(InMemoryStorage::new());
+        let context =
+            ConvContext::new("timeout-conv", PathBuf::from("/tmp"), "test-model", 200_000);
+        let (event_tx, event_rx) = mpsc::channel(32);
+        let broadcast_tx = crate::runtime::SseBroadcaster::new(128, 0);
+        let mut broadcast_rx = broadcast_tx.subscribe();
+
+        let runtime = ConversationRuntime::new(
+            context,
+            ConvState::Idle,
+            storage.clone(),
+            llm,
+            tools,
+            Arc::new(BrowserSessionManager::default()),
+            Arc::new(crate::tools::BashHandleRegistry::new()),
+            Arc::new(crate::tools::TmuxRegistry::new()),
+            Arc::new(ModelRegistry::new_empty()),
+            crate::terminal::ActiveTerminals::new(),
+            event_rx,
+            event_tx.clone(),
+            broadcast_tx,
+        )
+        .with_tool_timeout(Some(Duration::from_millis(100)));
+
+        tokio::spawn(async move { runtime.run().await });
+
+        event_tx
+            .send(Event::UserMessage {
+                text: "Wait for the page".to_string(),
+                llm_text: None,
+                images: vec![],
+                message_id: uuid::Uuid::new_v4().to_string(),
+                user_agent: None,
+                skill_invocation: None,
+            })
+            .await
+            .unwrap();
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        let mut agent_done = false;
+        while tokio::time::Instant::now() < deadline {
+            if let Ok(Ok(SseEvent::AgentDone { .. })) =
+                tokio::time::timeout(Duration::from_millis(50), broadcast_rx.recv()).await
+            {
+                agent_done = true;
+                break;
+            }
+        }
+        assert!(agent_done, "Turn should finish after the tool times out");
+
+        let messages = storage.get_messages("timeout-conv").await.unwrap();
+        let tool_result = messages
+            .iter()
+            .find_map(|m| match &m.content {
+                MessageContent::Tool(t) => Some(t),
+                _ => None,
+            })
+            .expect("timed-out tool should still produce a tool result");
+        assert!(tool_result.is_error);
+        assert!(
+            tool_result.content.contains("timed out"),
+            "unexpected tool result: {}",
+            tool_result.content
+        );
+    }
+
+    /// REQ-BED-034: a steering note sent while a tool runs is persisted after
+    /// that tool's result and reaches the model with the next request,
+    /// without interrupting the run.
+    #[tokio::test]
+    async fn test_user_steer_reaches_next_llm_request() {
+        use crate::llm::MessageRole;
+        use crate::runtime::{ConversationRuntime, SseEvent};
+        use crate::state_machine::ConvContext;
+        use std::path::PathBuf;
+        use tokio::sync::mpsc;
+
+        let llm = Arc::new(MockLlmClient::new("test-model"));
+        llm.queue_response(LlmResponse {
+            content: vec![ContentBlock::tool_use(
+                "tool-1",
+                "bash",
+                serde_json::json!({ "command": "cargo test" }),
+            )],
+            end_turn: false,
+            usage: Usage::default(),
+        });
+        llm.queue_response(LlmResponse {
+            content: vec![ContentBlock::text("Skipping the tests")],
+            end_turn: true,
+            usage: Usage::default(),
+        });
+
+        let tools = Arc::new(
+            MockToolExecutor::new()
+                .with_tool("bash", ToolOutput::success("running..."))
+                .with_delay(Duration::from_millis(300)),
+        );
+        let storage = Arc::new(InMemoryStorage::new());
+        let context = ConvContext::new("steer-conv", PathBuf::from("/tmp"), "test-model", 200_000);
+        let (event_tx, event_rx) = mpsc::channel(32);
+        let broadcast_tx = crate::runtime::SseBroadcaster::new(128, 0);
+        let mut broadcast_rx = broadcast_tx.subscribe();
+
+        let runtime = ConversationRuntime::new(
+            context,
+            ConvState::Idle,
+            storage.clone(),
+            llm.clone(),
+            tools,
+            Arc::new(BrowserSessionManager::default()),
+            Arc::new(crate::tools::BashHandleRegistry::new()),
+            Arc::new(crate::tools::TmuxRegistry::new()),
+            Arc::new(ModelRegistry::new_empty()),
+            crate::terminal::ActiveTerminals::new(),
+            event_rx,
+            event_tx.clone(),
+            broadcast_tx,
+        );
+
+        tokio::spawn(async move { runtime.run().await });
+
+        event_tx
+            .send(Event::UserMessage {
+                text: "Fix the bug".to_string(),
+                llm_text: None,
+                images: vec![],
+                message_id: uuid::Uuid::new_v4().to_string(),
+                user_agent: None,
+                skill_invocation: None,
+            })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        event_tx
+            .send(Event::UserSteer {
+                text: "skip the tests, just fix the bug".to_string(),
+                message_id: "steer-1".to_string(),
+            })
+            .await
+            .unwrap();
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        let mut agent_done = false;
+        while tokio::time::Instant::now() < deadline {
+            if let Ok(Ok(SseEvent::AgentDone { .. })) =
+                tokio::time::timeout(Duration::from_millis(50), broadcast_rx.recv()).await
+            {
+                agent_done = true;
+                break;
+            }
+        }
+        assert!(agent_done, "Steering must not interrupt the run");
+
+        let requests = llm.requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 2);
+        let last = requests[1]
+            .messages
+            .last()
+            .expect("second request has messages");
+        assert_eq!(last.role, MessageRole::User);
+        assert_eq!(
+            last.content,
+            vec![ContentBlock::text("skip the tests, just fix the bug")]
+        );
+
+        let messages = storage.get_messages("steer-conv").await.unwrap();
+        let steer_pos = messages
+            .iter()
+            .position(|m| m.message_id == "steer-1")
+            .expect("steering note persisted");
+        assert!(
+            matches!(messages[steer_pos - 1].content, MessageContent::Tool(_)),
+            "note must follow the tool result it was sent during"
+        );
+    }
+
     /// Regression test for task 24680: a parent conversation whose LLM
     /// keeps issuing tool calls without ever producing a final answer must
     /// be capped, not loop forever.
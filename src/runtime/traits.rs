@@ -4,6 +4,7 @@
 
 use crate::db::{ConvMode, Message, MessageContent, UsageData};
 use crate::llm::{LlmError, LlmRequest, LlmResponse};
+use crate::state_machine::state::CouncilCandidate;
 use crate::state_machine::ConvState;
 use crate::tools::ToolOutput;
 use async_trait::async_trait;
@@ -104,6 +105,58 @@ pub trait StateStore: Send + Sync {
         model: &str,
         usage: &crate::llm::Usage,
     ) -> Result<(), String>;
+
+    /// Append one tool execution to the audit log (REQ-API-016).
+    /// Fire-and-forget like `insert_turn_usage`.
+    async fn record_tool_audit(&self, entry: &crate::db::AuditEntry) -> Result<(), String>;
+
+    /// Append one applied state-machine transition to the transition log
+    /// (REQ-API-017). Errors are logged by the caller and never fatal.
+    async fn record_transition(&self, record: &crate::db::TransitionRecord) -> Result<(), String>;
+
+    /// Append one handled event to the event log (REQ-BED-054). Only
+    /// called with the log on; errors are logged by the caller.
+    async fn record_event(&self, record: &crate::db::EventRecord) -> Result<(), String>;
+
+    /// Verify settings of the conversation's project (REQ-BED-037). Read
+    /// each time a verify run starts, so edits apply to live runtimes.
+    async fn get_verify_settings(
+        &self,
+        conv_id: &str,
+    ) -> Result<Option<crate::db::VerifySettings>, String>;
+
+    /// Count a tool's read or edit of `path` in the touched-files index
+    /// (REQ-BED-042). Errors are logged by the caller and never fatal.
+    async fn record_file_touch(
+        &self,
+        conv_id: &str,
+        path: &str,
+        kind: crate::db::TouchKind,
+    ) -> Result<(), String>;
+
+    /// Files touched in the conversation, most recently touched first.
+    async fn get_touched_files(&self, conv_id: &str)
+        -> Result<Vec<crate::db::TouchedFile>, String>;
+
+    /// Messages pinned to stay in context verbatim, in conversation order
+    /// (REQ-BED-045).
+    async fn get_pinned_messages(&self, conv_id: &str) -> Result<Vec<Message>, String>;
+
+    /// Keep every answer to a council prompt (REQ-BED-058), chosen or not.
+    /// Errors are logged by the caller and never fatal.
+    async fn record_council_candidates(
+        &self,
+        conv_id: &str,
+        candidates: &[CouncilCandidate],
+    ) -> Result<(), String>;
+
+    /// Add one request's estimated input tokens from tool outputs, per tool
+    /// (REQ-API-031). Errors are logged by the caller and never fatal.
+    async fn record_tool_token_usage(
+        &self,
+        conv_id: &str,
+        tokens: &[(String, usize)],
+    ) -> Result<(), String>;
 }
 
 /// Client for making LLM requests
@@ -124,6 +177,20 @@ pub trait LlmClient: Send + Sync {
         self.complete(request).await
     }
 
+    /// Complete a request with `model_id` rather than the conversation's
+    /// model, for council mode (REQ-BED-058). No streaming and no fallback:
+    /// a council member that fails is reported as failed. Default answers
+    /// through `complete()`, for clients that serve a single model.
+    async fn complete_as(
+        &self,
+        model_id: &str,
+        request: &LlmRequest,
+    ) -> Result<LlmResponse, LlmError> {
+        let mut response = self.complete(request).await?;
+        response.usage.model = Some(model_id.to_string());
+        Ok(response)
+    }
+
     /// Get the model ID
     #[allow(dead_code)] // API completeness
     fn model_id(&self) -> &str;
@@ -253,6 +320,63 @@ impl<T: StateStore + ?Sized> StateStore for Arc<T> {
             .insert_turn_usage(conversation_id, root_conversation_id, model, usage)
             .await
     }
+
+    async fn record_tool_audit(&self, entry: &crate::db::AuditEntry) -> Result<(), String> {
+        (**self).record_tool_audit(entry).await
+    }
+
+    async fn record_transition(&self, record: &crate::db::TransitionRecord) -> Result<(), String> {
+        (**self).record_transition(record).await
+    }
+
+    async fn record_event(&self, record: &crate::db::EventRecord) -> Result<(), String> {
+        (**self).record_event(record).await
+    }
+
+    async fn get_verify_settings(
+        &self,
+        conv_id: &str,
+    ) -> Result<Option<crate::db::VerifySettings>, String> {
+        (**self).get_verify_settings(conv_id).await
+    }
+
+    async fn record_file_touch(
+        &self,
+        conv_id: &str,
+        path: &str,
+        kind: crate::db::TouchKind,
+    ) -> Result<(), String> {
+        (**self).record_file_touch(conv_id, path, kind).await
+    }
+
+    async fn get_touched_files(
+        &self,
+        conv_id: &str,
+    ) -> Result<Vec<crate::db::TouchedFile>, String> {
+        (**self).get_touched_files(conv_id).await
+    }
+
+    async fn get_pinned_messages(&self, conv_id: &str) -> Result<Vec<Message>, String> {
+        (**self).get_pinned_messages(conv_id).await
+    }
+
+    async fn record_council_candidates(
+        &self,
+        conv_id: &str,
+        candidates: &[CouncilCandidate],
+    ) -> Result<(), String> {
+        (**self)
+            .record_council_candidates(conv_id, candidates)
+            .await
+    }
+
+    async fn record_tool_token_usage(
+        &self,
+        conv_id: &str,
+        tokens: &[(String, usize)],
+    ) -> Result<(), String> {
+        (**self).record_tool_token_usage(conv_id, tokens).await
+    }
 }
 
 #[async_trait]
@@ -269,6 +393,14 @@ impl<T: LlmClient + ?Sized> LlmClient for Arc<T> {
         (**self).complete_streaming(request, chunk_tx).await
     }
 
+    async fn complete_as(
+        &self,
+        model_id: &str,
+        request: &LlmRequest,
+    ) -> Result<LlmResponse, LlmError> {
+        (**self).complete_as(model_id, request).await
+    }
+
     fn model_id(&self) -> &str {
         (**self).model_id()
     }
@@ -294,8 +426,12 @@ impl<T: ToolExecutor + ?Sized> ToolExecutor for Arc<T> {
 // ============================================================================
 
 use crate::db::Database;
-use crate::llm::ModelRegistry;
+use crate::llm::{
+    BatchGroup, CassetteMode, LlmCassette, LlmErrorKind, LlmService, LlmTrafficLog, ModelRegistry,
+};
+use crate::state_machine::transition::MAX_RETRY_ATTEMPTS;
 use crate::tools::ToolRegistry;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
 /// Adapter to use Database as Storage
@@ -443,30 +579,298 @@ impl StateStore for DatabaseStorage {
             .await
             .map_err(|e| e.to_string())
     }
+
+    async fn record_tool_audit(&self, entry: &crate::db::AuditEntry) -> Result<(), String> {
+        self.db
+            .insert_audit_entry(entry)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn record_transition(&self, record: &crate::db::TransitionRecord) -> Result<(), String> {
+        self.db
+            .insert_transition(record)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn record_event(&self, record: &crate::db::EventRecord) -> Result<(), String> {
+        self.db
+            .insert_event(record)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn get_verify_settings(
+        &self,
+        conv_id: &str,
+    ) -> Result<Option<crate::db::VerifySettings>, String> {
+        self.db
+            .get_conversation_verify_settings(conv_id)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn record_file_touch(
+        &self,
+        conv_id: &str,
+        path: &str,
+        kind: crate::db::TouchKind,
+    ) -> Result<(), String> {
+        self.db
+            .record_file_touch(conv_id, path, kind, chrono::Utc::now())
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn get_touched_files(
+        &self,
+        conv_id: &str,
+    ) -> Result<Vec<crate::db::TouchedFile>, String> {
+        self.db
+            .list_touched_files(conv_id)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn get_pinned_messages(&self, conv_id: &str) -> Result<Vec<Message>, String> {
+        self.db
+            .list_pinned_messages(conv_id)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn record_council_candidates(
+        &self,
+        conv_id: &str,
+        candidates: &[CouncilCandidate],
+    ) -> Result<(), String> {
+        let now = chrono::Utc::now();
+        for candidate in candidates {
+            let content = serde_json::to_string(&candidate.content).map_err(|e| e.to_string())?;
+            self.db
+                .record_council_candidate(
+                    &candidate.id,
+                    conv_id,
+                    &candidate.model,
+                    &content,
+                    candidate.error.as_deref(),
+                    now,
+                )
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    async fn record_tool_token_usage(
+        &self,
+        conv_id: &str,
+        tokens: &[(String, usize)],
+    ) -> Result<(), String> {
+        self.db
+            .record_tool_token_usage(conv_id, tokens, chrono::Utc::now())
+            .await
+            .map_err(|e| e.to_string())
+    }
 }
 
 /// Adapter to use `ModelRegistry` as `LlmClient`
+///
+/// With a cassette attached (`PHOENIX_LLM_MODE`, REQ-LLM-011), successful
+/// completions are recorded to disk, or served from disk without touching
+/// the registry.
+///
+/// Live calls fall back down the registry's fallback chain (REQ-LLM-013)
+/// once the conversation's model has failed with network or server errors
+/// for as many consecutive calls as the state machine retries. Every
+/// response records the model that answered in its usage.
+///
+/// With a traffic log attached (`PHOENIX_LLM_LOG`, REQ-LLM-017), every live
+/// attempt, failed or not, is appended to it under the conversation's id.
+///
+/// A sub-agent spawned with `batch` (REQ-SA-009) sends its first live
+/// request through a shared [`BatchGroup`]; if the batch fails, that request
+/// is retried interactively, and every later request is interactive.
 pub struct RegistryLlmClient {
     registry: Arc<ModelRegistry>,
     model_id: String,
+    cassette: Option<Arc<LlmCassette>>,
+    /// Traffic log and the conversation id its entries are filed under
+    traffic_log: Option<(Arc<LlmTrafficLog>, String)>,
+    /// Batch for the first live request; taken on first use
+    batch: std::sync::Mutex<Option<Arc<BatchGroup>>>,
+    /// Consecutive network/server failures of `model_id`
+    outage_failures: AtomicU32,
 }
 
 impl RegistryLlmClient {
     pub fn new(registry: Arc<ModelRegistry>, model_id: String) -> Self {
-        Self { registry, model_id }
+        Self {
+            registry,
+            model_id,
+            cassette: None,
+            traffic_log: None,
+            batch: std::sync::Mutex::new(None),
+            outage_failures: AtomicU32::new(0),
+        }
+    }
+
+    /// Record or replay through `cassette`; `None` keeps live traffic.
+    pub fn with_cassette(mut self, cassette: Option<Arc<LlmCassette>>) -> Self {
+        self.cassette = cassette;
+        self
+    }
+
+    /// Log live traffic for `conversation_id`; `None` disables logging.
+    pub fn with_traffic_log(
+        mut self,
+        log: Option<Arc<LlmTrafficLog>>,
+        conversation_id: &str,
+    ) -> Self {
+        self.traffic_log = log.map(|log| (log, conversation_id.to_string()));
+        self
+    }
+
+    /// Send the first live request through `batch`; `None` sends it
+    /// interactively.
+    pub fn with_batch(mut self, batch: Option<Arc<BatchGroup>>) -> Self {
+        self.batch = std::sync::Mutex::new(batch);
+        self
+    }
+
+    fn service(&self, model_id: &str) -> Result<Arc<dyn LlmService>, LlmError> {
+        self.registry.get(model_id).ok_or_else(|| {
+            LlmError::network(format!(
+                "Model '{model_id}' is not available in the registry"
+            ))
+        })
+    }
+
+    fn cassette_in(&self, mode: CassetteMode) -> Option<&LlmCassette> {
+        self.cassette.as_deref().filter(|c| c.mode() == mode)
+    }
+
+    /// One live call to `model_id`, streaming when `chunk_tx` is given.
+    async fn complete_with(
+        &self,
+        model_id: &str,
+        request: &LlmRequest,
+        chunk_tx: Option<&tokio::sync::broadcast::Sender<crate::llm::TokenChunk>>,
+    ) -> Result<LlmResponse, LlmError> {
+        let service = self.service(model_id)?;
+        let started = std::time::Instant::now();
+        let result = match chunk_tx {
+            Some(chunk_tx) => service.complete_streaming(request, chunk_tx).await,
+            None => service.complete(request).await,
+        };
+        if let Some((log, conversation_id)) = &self.traffic_log {
+            log.record(
+                conversation_id,
+                model_id,
+                request,
+                result.as_ref(),
+                started.elapsed(),
+            )
+            .await;
+        }
+        let mut response = result?;
+        if let Some(cassette) = self.cassette_in(CassetteMode::Record) {
+            cassette.record(model_id, request, &response).await;
+        }
+        response.usage.model = Some(model_id.to_string());
+        Ok(response)
+    }
+
+    /// The first live request, through the batch if one is attached.
+    /// Returns `None` when there is no batch or it failed, so the caller
+    /// makes the request interactively.
+    async fn complete_batched(&self, request: &LlmRequest) -> Option<LlmResponse> {
+        let batch = self
+            .batch
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take()?;
+        let service = self.service(&self.model_id).ok()?;
+        let started = std::time::Instant::now();
+        let result = batch.submit(service, request.clone()).await;
+        if let Some((log, conversation_id)) = &self.traffic_log {
+            let elapsed = started.elapsed();
+            log.record(
+                conversation_id,
+                &self.model_id,
+                request,
+                result.as_ref(),
+                elapsed,
+            )
+            .await;
+        }
+        let mut response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::info!(
+                    model = %self.model_id,
+                    error = %e.message,
+                    "Batched request failed; sending interactively"
+                );
+                return None;
+            }
+        };
+        if let Some(cassette) = self.cassette_in(CassetteMode::Record) {
+            cassette.record(&self.model_id, request, &response).await;
+        }
+        response.usage.model = Some(self.model_id.clone());
+        Some(response)
+    }
+
+    /// Call the conversation's model; once its outage has outlasted the
+    /// state machine's retries, try each fallback before giving up.
+    async fn complete_live(
+        &self,
+        request: &LlmRequest,
+        chunk_tx: Option<&tokio::sync::broadcast::Sender<crate::llm::TokenChunk>>,
+    ) -> Result<LlmResponse, LlmError> {
+        let error = match self.complete_with(&self.model_id, request, chunk_tx).await {
+            Err(e) if matches!(e.kind, LlmErrorKind::Network | LlmErrorKind::ServerError) => e,
+            other => {
+                self.outage_failures.store(0, Ordering::Relaxed);
+                return other;
+            }
+        };
+        let failures = self.outage_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures < MAX_RETRY_ATTEMPTS {
+            return Err(error);
+        }
+        self.outage_failures.store(0, Ordering::Relaxed);
+
+        for fallback in self.registry.fallback_chain(&self.model_id) {
+            tracing::warn!(
+                model = %self.model_id,
+                fallback = %fallback,
+                error = %error.message,
+                "Model failed after retries; trying fallback"
+            );
+            match self.complete_with(&fallback, request, chunk_tx).await {
+                Ok(response) => return Ok(response),
+                Err(e) => {
+                    tracing::warn!(model = %fallback, error = %e.message, "Fallback model failed");
+                }
+            }
+        }
+        Err(error)
     }
 }
 
 #[async_trait]
 impl LlmClient for RegistryLlmClient {
     async fn complete(&self, request: &LlmRequest) -> Result<LlmResponse, LlmError> {
-        let llm = self.registry.get(&self.model_id).ok_or_else(|| {
-            LlmError::network(format!(
-                "Model '{}' is not available in the registry",
-                self.model_id
-            ))
-        })?;
-        llm.complete(request).await
+        if let Some(cassette) = self.cassette_in(CassetteMode::Replay) {
+            return cassette.replay(&self.model_id, request).await;
+        }
+        if let Some(response) = self.complete_batched(request).await {
+            return Ok(response);
+        }
+        self.complete_live(request, None).await
     }
 
     async fn complete_streaming(
@@ -474,13 +878,35 @@ impl LlmClient for RegistryLlmClient {
         request: &LlmRequest,
         chunk_tx: &tokio::sync::broadcast::Sender<crate::llm::TokenChunk>,
     ) -> Result<LlmResponse, LlmError> {
-        let llm = self.registry.get(&self.model_id).ok_or_else(|| {
-            LlmError::network(format!(
-                "Model '{}' is not available in the registry",
-                self.model_id
-            ))
-        })?;
-        llm.complete_streaming(request, chunk_tx).await
+        if let Some(cassette) = self.cassette_in(CassetteMode::Replay) {
+            // Replayed text arrives as one chunk so the UI still renders it live.
+            let response = cassette.replay(&self.model_id, request).await?;
+            let text = response.text();
+            if !text.is_empty() {
+                let _ = chunk_tx.send(crate::llm::TokenChunk::Text(text));
+            }
+            return Ok(response);
+        }
+        if let Some(response) = self.complete_batched(request).await {
+            // Batched text arrives as one chunk, as replayed text does.
+            let text = response.text();
+            if !text.is_empty() {
+                let _ = chunk_tx.send(crate::llm::TokenChunk::Text(text));
+            }
+            return Ok(response);
+        }
+        self.complete_live(request, Some(chunk_tx)).await
+    }
+
+    async fn complete_as(
+        &self,
+        model_id: &str,
+        request: &LlmRequest,
+    ) -> Result<LlmResponse, LlmError> {
+        if let Some(cassette) = self.cassette_in(CassetteMode::Replay) {
+            return cassette.replay(model_id, request).await;
+        }
+        self.complete_with(model_id, request, None).await
     }
 
     fn model_id(&self) -> &str {
@@ -499,6 +925,12 @@ pub struct ToolRegistryExecutor {
     /// into the registry. This means enable/disable and reload take effect
     /// immediately across all conversations.
     mcp_manager: Option<Arc<crate::tools::mcp::McpClientManager>>,
+    /// Conversation-template tool selection (REQ-API-018). Applies to
+    /// built-in and MCP tools alike and survives registry swaps.
+    allowed_tools: Option<std::collections::HashSet<String>>,
+    /// Tools the user switched off for this conversation. Like
+    /// `allowed_tools`, applies to MCP tools and survives registry swaps.
+    disabled_tools: std::collections::HashSet<String>,
 }
 
 impl ToolRegistryExecutor {
@@ -508,6 +940,8 @@ impl ToolRegistryExecutor {
         Self {
             registry: std::sync::RwLock::new(registry),
             mcp_manager: None,
+            allowed_tools: None,
+            disabled_tools: std::collections::HashSet::new(),
         }
     }
 
@@ -521,9 +955,31 @@ impl ToolRegistryExecutor {
         Self {
             registry: std::sync::RwLock::new(registry),
             mcp_manager: Some(manager),
+            allowed_tools: None,
+            disabled_tools: std::collections::HashSet::new(),
         }
     }
 
+    /// Restrict the conversation to the named tools; `None` allows all.
+    pub fn with_allowed_tools(mut self, tools: Option<Vec<String>>) -> Self {
+        self.allowed_tools = tools.map(|names| names.into_iter().collect());
+        self
+    }
+
+    /// Hide and refuse the named tools.
+    pub fn with_disabled_tools(mut self, tools: Vec<String>) -> Self {
+        self.disabled_tools = tools.into_iter().collect();
+        self
+    }
+
+    fn is_allowed(&self, name: &str) -> bool {
+        !self.disabled_tools.contains(name)
+            && self
+                .allowed_tools
+                .as_ref()
+                .is_none_or(|allowed| allowed.contains(name))
+    }
+
     /// Replace the inner `ToolRegistry` (e.g., after Explore -> Work mode transition).
     pub fn swap_registry(&self, new_registry: ToolRegistry) {
         let mut guard = self
@@ -537,6 +993,10 @@ impl ToolRegistryExecutor {
 #[async_trait]
 impl ToolExecutor for ToolRegistryExecutor {
     async fn execute(&self, name: &str, input: Value, ctx: ToolContext) -> Option<ToolOutput> {
+        if !self.is_allowed(name) {
+            return None;
+        }
+
         // Look up the tool while holding the read lock, then drop the guard
         // before the async .run() call (RwLockReadGuard is !Send).
         let tool = {
@@ -596,6 +1056,8 @@ impl ToolExecutor for ToolRegistryExecutor {
             }
         }
 
+        defs.retain(|d| self.is_allowed(&d.name));
+
         if defs.len() > 50 {
             let deferred = defs.iter().filter(|d| d.defer_loading).count();
             if deferred == 0 {
@@ -615,3 +1077,155 @@ impl ToolExecutor for ToolRegistryExecutor {
         tracing::info!("Tool registry upgraded to Work mode (full tool suite)");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{ContentBlock, LlmMessage, MessageRole, PromptCacheKey, SystemContent};
+
+    /// Service that fails with `fail` when set, otherwise answers "ok".
+    struct Scripted {
+        id: &'static str,
+        fail: Option<LlmErrorKind>,
+        calls: AtomicU32,
+    }
+
+    impl Scripted {
+        fn new(id: &'static str, fail: Option<LlmErrorKind>) -> Arc<Self> {
+            Arc::new(Self {
+                id,
+                fail,
+                calls: AtomicU32::new(0),
+            })
+        }
+
+        fn calls(&self) -> u32 {
+            self.calls.load(Ordering::Relaxed)
+        }
+    }
+
+    #[async_trait]
+    impl LlmService for Scripted {
+        async fn complete(&self, _request: &LlmRequest) -> Result<LlmResponse, LlmError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            match self.fail {
+                Some(kind) => Err(LlmError::new(kind, format!("{} is down", self.id))),
+                None => Ok(LlmResponse {
+                    content: vec![ContentBlock::text("ok")],
+                    end_turn: true,
+                    usage: crate::llm::Usage::default(),
+                }),
+            }
+        }
+
+        fn model_id(&self) -> &str {
+            self.id
+        }
+    }
+
+    fn request() -> LlmRequest {
+        LlmRequest {
+            system: vec![SystemContent::new("You are a test.")],
+            messages: vec![LlmMessage {
+                role: MessageRole::User,
+                content: vec![ContentBlock::text("hi")],
+            }],
+            tools: vec![],
+            max_tokens: Some(1024),
+            thinking_budget: None,
+            cache_key: PromptCacheKey::stable("c"),
+        }
+    }
+
+    fn client(primary: &Arc<Scripted>, backup: &Arc<Scripted>) -> RegistryLlmClient {
+        let primary: Arc<dyn LlmService> = primary.clone();
+        let backup: Arc<dyn LlmService> = backup.clone();
+        let registry = ModelRegistry::for_test_with_fallbacks(
+            vec![("primary", primary), ("backup", backup)],
+            &["backup"],
+        );
+        RegistryLlmClient::new(Arc::new(registry), "primary".to_string())
+    }
+
+    #[tokio::test]
+    async fn falls_back_once_retries_are_exhausted() {
+        let primary = Scripted::new("primary", Some(LlmErrorKind::ServerError));
+        let backup = Scripted::new("backup", None);
+        let client = client(&primary, &backup);
+
+        for _ in 1..MAX_RETRY_ATTEMPTS {
+            assert!(client.complete(&request()).await.is_err());
+        }
+        assert_eq!(backup.calls(), 0, "state machine retries hit the primary");
+
+        let response = client.complete(&request()).await.unwrap();
+        assert_eq!(response.usage.model.as_deref(), Some("backup"));
+        assert_eq!(primary.calls(), MAX_RETRY_ATTEMPTS);
+        assert_eq!(backup.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn non_outage_errors_never_fall_back() {
+        let primary = Scripted::new("primary", Some(LlmErrorKind::InvalidRequest));
+        let backup = Scripted::new("backup", None);
+        let client = client(&primary, &backup);
+
+        for _ in 0..=MAX_RETRY_ATTEMPTS {
+            assert!(client.complete(&request()).await.is_err());
+        }
+        assert_eq!(backup.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn answering_model_is_recorded() {
+        let primary = Scripted::new("primary", None);
+        let backup = Scripted::new("backup", None);
+        let response = client(&primary, &backup)
+            .complete(&request())
+            .await
+            .unwrap();
+        assert_eq!(response.usage.model.as_deref(), Some("primary"));
+    }
+
+    #[tokio::test]
+    async fn template_tool_selection_filters_definitions() {
+        let executor = ToolRegistryExecutor::builtin_only(ToolRegistry::direct())
+            .with_allowed_tools(Some(vec!["think".to_string(), "read_file".to_string()]));
+        let mut names: Vec<String> = executor
+            .definitions()
+            .await
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, ["read_file", "think"]);
+
+        executor.upgrade_to_work_mode();
+        assert_eq!(executor.definitions().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn disabled_tools_are_hidden_and_refused() {
+        let executor = ToolRegistryExecutor::builtin_only(ToolRegistry::direct())
+            .with_disabled_tools(vec!["bash".to_string()]);
+        let defs = executor.definitions().await;
+        assert!(!defs.iter().any(|d| d.name == "bash"));
+        assert!(defs.iter().any(|d| d.name == "patch"));
+
+        let ctx = ToolContext::new(
+            tokio_util::sync::CancellationToken::new(),
+            "test-conv".to_string(),
+            std::env::temp_dir(),
+            Arc::new(crate::tools::browser::BrowserSessionManager::default()),
+            Arc::new(crate::tools::BashHandleRegistry::new()),
+            Arc::new(ModelRegistry::new_empty()),
+            crate::terminal::ActiveTerminals::new(),
+            Arc::new(crate::tools::TmuxRegistry::new()),
+            None,
+        );
+        let output = executor
+            .execute("bash", serde_json::json!({"command": "true"}), ctx)
+            .await;
+        assert!(output.is_none());
+    }
+}
@@ -150,11 +150,31 @@ pub fn from_transition_error(err: &TransitionError) -> UserFacingError {
             "Conversation is awaiting your response",
             "Answer the agent's pending question before sending a new message.",
         ),
+        TransitionError::AwaitingPatchReview => UserFacingError::retryable(
+            "Conversation is awaiting patch review",
+            "Apply or reject the pending patch before sending a new message.",
+        ),
+        TransitionError::AwaitingCouncilChoice => UserFacingError::retryable(
+            "Conversation is awaiting a council choice",
+            "Pick one of the council's answers before sending a new message.",
+        ),
+        TransitionError::UnknownCouncilCandidate => UserFacingError::retryable(
+            "Council answer not found",
+            "That answer failed or is no longer on offer. Pick another one.",
+        ),
         TransitionError::ConversationTerminal => UserFacingError::fatal(
             "Conversation already finished",
             "This conversation has been completed or abandoned. Start a new one to \
              continue.",
         ),
+        TransitionError::AgentNotRunning => UserFacingError::retryable(
+            "Agent is not running",
+            "The agent finished before your note arrived. Send it as a regular message.",
+        ),
+        TransitionError::ToolNotQueued => UserFacingError::retryable(
+            "Tool is no longer queued",
+            "The tool already started or finished. Use Stop to cancel the running tool.",
+        ),
         // Catch-all for (state, event) pairs the state machine doesn't
         // have an arm for. The variant payload is now structured
         // (`&'static str` discriminators, never `Debug`-formatted payloads
@@ -0,0 +1,162 @@
+//! Post-edit verification (REQ-BED-037).
+//!
+//! A project can name a command (`cargo test`, `npm run lint`, ...) that
+//! checks the agent's work. When a turn that applied a patch ends, the
+//! runtime runs it in the conversation's working directory. A failure is
+//! fed back to the agent as a meta user message carrying the tail of the
+//! output, which starts another turn; once the project's attempt budget is
+//! spent the runtime stops looping and leaves a system note instead.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Attempt budget when a project sets a command without one.
+pub const DEFAULT_VERIFY_ATTEMPTS: u32 = 3;
+
+/// Upper bound on the attempt budget accepted from the API.
+pub const MAX_VERIFY_ATTEMPTS: u32 = 10;
+
+/// Wall-clock budget for one verify run. A hung test suite counts as a
+/// failure so the agent hears about it.
+pub const VERIFY_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Output kept for the report. Compiler and test failures put the useful
+/// part last.
+const REPORT_TAIL_BYTES: usize = 8 * 1024;
+
+/// Result of one verify run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    Passed,
+    /// `status` describes how the command ended; `output` is the tail of
+    /// its combined stdout and stderr.
+    Failed {
+        status: String,
+        output: String,
+    },
+}
+
+/// Run `command` through `sh -c` in `cwd`. `Err` means the command could
+/// not be started at all, which is a configuration problem rather than
+/// something the agent can fix.
+pub async fn run(command: &str, cwd: &Path, timeout: Duration) -> Result<VerifyOutcome, String> {
+    let child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start verify command: {e}"))?;
+
+    let Ok(output) = tokio::time::timeout(timeout, child.wait_with_output()).await else {
+        return Ok(VerifyOutcome::Failed {
+            status: format!("timed out after {}s", timeout.as_secs()),
+            output: String::new(),
+        });
+    };
+    let output = output.map_err(|e| format!("Failed to wait for verify command: {e}"))?;
+    if output.status.success() {
+        return Ok(VerifyOutcome::Passed);
+    }
+
+    let mut combined = String::from_utf8_lossy(&output.stdout).into_owned();
+    combined.push_str(&String::from_utf8_lossy(&output.stderr));
+    let status = output.status.code().map_or_else(
+        || "was killed by a signal".to_string(),
+        |c| format!("exited with status {c}"),
+    );
+    Ok(VerifyOutcome::Failed {
+        status,
+        output: tail(combined.trim_end(), REPORT_TAIL_BYTES),
+    })
+}
+
+/// The message fed back to the agent after a failed run.
+pub fn failure_report(command: &str, status: &str, output: &str) -> String {
+    let mut report = format!(
+        "Verification failed after your edits: `{command}` {status}. Fix the problems \
+         below; verification runs again when you finish."
+    );
+    if !output.is_empty() {
+        report.push_str("\n\n```\n");
+        report.push_str(output);
+        report.push_str("\n```");
+    }
+    report
+}
+
+/// The last `max_bytes` of `text`, cut on a line boundary where possible.
+fn tail(text: &str, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+    let mut start = text.len() - max_bytes;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    let clipped = text.get(start..).unwrap_or_default();
+    let clipped = clipped.split_once('\n').map_or(clipped, |(_, rest)| rest);
+    format!("[... earlier output omitted ...]\n{clipped}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn passing_command_passes() {
+        let outcome = run("true", Path::new("/tmp"), VERIFY_TIMEOUT).await;
+        assert_eq!(outcome, Ok(VerifyOutcome::Passed));
+    }
+
+    #[tokio::test]
+    async fn failing_command_reports_status_and_output() {
+        let outcome = run(
+            "echo out; echo err >&2; exit 3",
+            Path::new("/tmp"),
+            VERIFY_TIMEOUT,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            outcome,
+            VerifyOutcome::Failed {
+                status: "exited with status 3".to_string(),
+                output: "out\nerr".to_string(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn hung_command_times_out() {
+        let outcome = run("sleep 5", Path::new("/tmp"), Duration::from_millis(100))
+            .await
+            .unwrap();
+        let VerifyOutcome::Failed { status, .. } = outcome else {
+            panic!("expected a failure, got {outcome:?}");
+        };
+        assert!(status.contains("timed out"), "{status}");
+    }
+
+    #[test]
+    fn tail_keeps_the_end_on_a_line_boundary() {
+        let text = "first line\nsecond line\nthird line";
+        assert_eq!(tail(text, 100), text);
+        assert_eq!(
+            tail(text, 15),
+            "[... earlier output omitted ...]\nthird line"
+        );
+    }
+
+    #[test]
+    fn report_names_the_command_and_fences_output() {
+        let report = failure_report("cargo test", "exited with status 101", "boom");
+        assert!(report.starts_with("Verification failed after your edits: `cargo test`"));
+        assert!(report.ends_with("```\nboom\n```"));
+    }
+}
@@ -0,0 +1,547 @@
+//! External file changes (REQ-BED-059).
+//!
+//! A top-level conversation watches its working directory for as long as
+//! its runtime lives. Changes the agent did not make, such as the user
+//! saving in their editor or switching branches, are collected until a
+//! quiet second passes. The agent gets a note to re-read those files, and
+//! clients get a `files_changed` event. Anything written while a tool runs
+//! is the agent's own and is dropped.
+//!
+//! Directories are watched one by one, found with the same ignore-aware
+//! walker as file search, so ignored trees such as `target/` and
+//! `node_modules/` never take up inotify watches. Watching is off unless
+//! `PHOENIX_WATCH_FILES` turns it on.
+
+use super::{RuntimeManager, SseEvent};
+use crate::phoenixignore::PhoenixIgnore;
+use crate::state_machine::ConvState;
+use ignore::gitignore::Gitignore;
+use notify::event::{EventKind, ModifyKind};
+use notify::{RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, Notify};
+
+/// Quiet period that ends a burst of changes.
+const DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// Longest a burst is held before it is reported anyway.
+const MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Changes this soon after a tool finishes are still the tool's: the
+/// kernel reports them a little late.
+const TOOL_GRACE: Duration = Duration::from_secs(2);
+
+/// Most paths named in one note to the agent.
+const MAX_LISTED: usize = 10;
+
+/// Most directories one conversation watches. Each takes an inotify watch
+/// from the user's `max_user_watches`, shared by every process.
+const MAX_WATCHED_DIRS: usize = 4096;
+
+/// Most distinct changed paths held between two passes of the watcher
+/// task; later paths are dropped until it catches up.
+const MAX_PENDING: usize = 4096;
+
+/// `PHOENIX_WATCH_FILES`: whether conversations watch their cwd. Off
+/// unless set to an on value.
+pub fn watch_files_from_env() -> bool {
+    std::env::var("PHOENIX_WATCH_FILES")
+        .is_ok_and(|v| matches!(v.as_str(), "1" | "true" | "yes" | "on"))
+}
+
+/// Whether the agent may be writing files in this state: a tool is running,
+/// being cancelled, or sub-agents are working in the same tree.
+fn agent_writes(state: &ConvState) -> bool {
+    matches!(
+        state,
+        ConvState::ToolExecuting { .. }
+            | ConvState::CancellingTool { .. }
+            | ConvState::AwaitingSubAgents { .. }
+            | ConvState::CancellingSubAgents { .. }
+    )
+}
+
+/// Whether a notify event is a change to file contents or names, as
+/// opposed to an access or a metadata touch.
+fn is_change(kind: EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::Create(_)
+            | EventKind::Remove(_)
+            | EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Name(_) | ModifyKind::Any)
+    )
+}
+
+/// Which changed paths under a root are worth telling the agent about.
+struct ChangeFilter {
+    root: PathBuf,
+    gitignore: Gitignore,
+}
+
+impl ChangeFilter {
+    fn new(root: PathBuf) -> Self {
+        let (gitignore, _) = Gitignore::new(root.join(".gitignore"));
+        Self { root, gitignore }
+    }
+
+    /// `path` relative to the root, or `None` for a directory, anything in
+    /// `.git`, and anything the root `.gitignore` or a `.phoenixignore`
+    /// excludes (build output, dependencies, secrets).
+    fn relevant(&self, path: &Path) -> Option<PathBuf> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        if relative.as_os_str().is_empty()
+            || path.is_dir()
+            || relative.components().any(|c| c.as_os_str() == ".git")
+            || self
+                .gitignore
+                .matched_path_or_any_parents(path, false)
+                .is_ignore()
+            || PhoenixIgnore::path_is_ignored(path)
+        {
+            return None;
+        }
+        Some(relative.to_path_buf())
+    }
+
+    /// Whether a directory that appeared under the root should be watched:
+    /// not in `.git` and not ignored.
+    fn watchable(&self, dir: &Path) -> bool {
+        let Ok(relative) = dir.strip_prefix(&self.root) else {
+            return false;
+        };
+        !relative.components().any(|c| c.as_os_str() == ".git")
+            && !self
+                .gitignore
+                .matched_path_or_any_parents(dir, true)
+                .is_ignore()
+            && !PhoenixIgnore::path_is_ignored(dir)
+    }
+}
+
+/// The directories a conversation watches, each without recursion.
+struct Watches {
+    watcher: notify::RecommendedWatcher,
+    dirs: HashSet<PathBuf>,
+}
+
+impl Watches {
+    /// Watch `dir` and every directory under it that the walker does not
+    /// skip for `.gitignore` or `.phoenixignore`. Blocking: run it off the
+    /// async runtime. Fails only when `dir` itself cannot be watched.
+    fn add_tree(&mut self, dir: &Path) -> notify::Result<()> {
+        let walker = ignore::WalkBuilder::new(dir)
+            .hidden(false)
+            .require_git(false)
+            .add_custom_ignore_filename(crate::phoenixignore::FILE_NAME)
+            .filter_entry(|e| e.file_name() != ".git")
+            .build();
+        let dirs = walker
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_some_and(|t| t.is_dir()));
+        for entry in dirs {
+            let path = entry.into_path();
+            if self.dirs.contains(&path) {
+                continue;
+            }
+            if self.dirs.len() >= MAX_WATCHED_DIRS {
+                tracing::warn!(dir = %dir.display(), limit = MAX_WATCHED_DIRS, "Too many directories to watch");
+                break;
+            }
+            match self.watcher.watch(&path, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    self.dirs.insert(path);
+                }
+                Err(e) if path == dir => return Err(e),
+                Err(e) if matches!(e.kind, notify::ErrorKind::MaxFilesWatch) => {
+                    tracing::warn!(dir = %dir.display(), error = %e, "Out of file watches");
+                    break;
+                }
+                // Removed since the walk saw it
+                Err(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop watching everything.
+    fn clear(&mut self) {
+        for dir in self.dirs.drain() {
+            let _ = self.watcher.unwatch(&dir);
+        }
+    }
+}
+
+/// Paths the watcher reported and the task has not handled yet. Repeated
+/// changes to one path share an entry, and the set is capped, so a flood
+/// of writes cannot grow it without bound.
+#[derive(Default)]
+struct Pending {
+    paths: Mutex<HashMap<PathBuf, Instant>>,
+    ready: Notify,
+}
+
+impl Pending {
+    fn push(&self, path: PathBuf, at: Instant) {
+        let mut paths = self.paths.lock().unwrap_or_else(PoisonError::into_inner);
+        if paths.len() < MAX_PENDING || paths.contains_key(&path) {
+            paths.insert(path, at);
+        }
+        drop(paths);
+        self.ready.notify_one();
+    }
+
+    fn take(&self) -> HashMap<PathBuf, Instant> {
+        std::mem::take(&mut *self.paths.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Changes collected since the last report.
+#[derive(Default)]
+struct Burst {
+    paths: BTreeSet<PathBuf>,
+    first: Option<Instant>,
+    last: Option<Instant>,
+}
+
+impl Burst {
+    fn add(&mut self, path: PathBuf, at: Instant) {
+        self.first.get_or_insert(at);
+        self.last = Some(at);
+        self.paths.insert(path);
+    }
+
+    /// When the burst is reported: a quiet second after its last change,
+    /// but no later than `MAX_DELAY` after its first.
+    fn due(&self) -> Option<Instant> {
+        Some((self.last? + DEBOUNCE).min(self.first? + MAX_DELAY))
+    }
+
+    fn take(&mut self) -> Vec<PathBuf> {
+        std::mem::take(self).paths.into_iter().collect()
+    }
+}
+
+/// What the agent is told about files that changed outside the conversation.
+fn changed_note(paths: &[PathBuf]) -> String {
+    let mut listed: Vec<String> = paths
+        .iter()
+        .take(MAX_LISTED)
+        .map(|p| format!("`{}`", p.display()))
+        .collect();
+    if paths.len() > MAX_LISTED {
+        listed.push(format!("{} more", paths.len() - MAX_LISTED));
+    }
+    let (noun, pronoun) = if paths.len() == 1 {
+        ("file", "it")
+    } else {
+        ("files", "them")
+    };
+    format!(
+        "These {noun} changed outside this conversation, probably in my editor: {}. \
+         Re-read {pronoun} before changing {pronoun}.",
+        listed.join(", ")
+    )
+}
+
+impl RuntimeManager {
+    /// Watch `cwd` and report outside changes until the runtime's
+    /// broadcaster closes.
+    pub(super) fn spawn_file_watcher(
+        self: &Arc<Self>,
+        conversation_id: &str,
+        cwd: &str,
+        mut rx: broadcast::Receiver<SseEvent>,
+    ) {
+        let pending = Arc::new(Pending::default());
+        let sink = Arc::clone(&pending);
+        let handler = move |result: notify::Result<notify::Event>| {
+            let Ok(event) = result else { return };
+            if is_change(event.kind) {
+                let at = Instant::now();
+                for path in event.paths {
+                    sink.push(path, at);
+                }
+            }
+        };
+        let watches = match notify::recommended_watcher(handler) {
+            Ok(watcher) => Arc::new(Mutex::new(Watches {
+                watcher,
+                dirs: HashSet::new(),
+            })),
+            Err(e) => {
+                tracing::warn!(conv_id = %conversation_id, error = %e, "File watcher unavailable");
+                return;
+            }
+        };
+
+        let manager = Arc::clone(self);
+        let conv_id = conversation_id.to_string();
+        let cwd = cwd.to_string();
+        tokio::spawn(async move {
+            let Some(mut filter) = watch(&watches, &conv_id, &cwd).await else {
+                return;
+            };
+            let mut burst = Burst::default();
+            let mut agent_busy = false;
+            let mut agent_until: Option<Instant> = None;
+            // Paths already noted since the agent last ran, so repeated
+            // saves of one file do not pile up notes.
+            let mut noted = HashSet::new();
+            loop {
+                let due = burst.due();
+                let wake = tokio::time::Instant::from_std(due.unwrap_or_else(Instant::now));
+                tokio::select! {
+                    event = rx.recv() => match event {
+                        Ok(SseEvent::StateChange { state, .. }) => {
+                            let busy = agent_writes(&state);
+                            if busy {
+                                noted.clear();
+                            } else if agent_busy {
+                                agent_until = Some(Instant::now() + TOOL_GRACE);
+                            }
+                            agent_busy = busy;
+                        }
+                        Ok(SseEvent::ConversationUpdate { update, .. }) => {
+                            // The conversation moved, e.g. into a new worktree
+                            if let Some(cwd) = update.cwd {
+                                match watch(&watches, &conv_id, &cwd).await {
+                                    Some(moved) => filter = moved,
+                                    None => return,
+                                }
+                            }
+                        }
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    },
+                    () = pending.ready.notified() => {
+                        let mut new_dirs = Vec::new();
+                        for (path, at) in pending.take() {
+                            if path.is_dir() {
+                                if filter.watchable(&path) {
+                                    new_dirs.push(path);
+                                }
+                                continue;
+                            }
+                            let agents = agent_busy || agent_until.is_some_and(|t| at < t);
+                            if agents || manager.is_recent_user_write(&path) {
+                                continue;
+                            }
+                            if let Some(relative) = filter.relevant(&path) {
+                                burst.add(relative, at);
+                            }
+                        }
+                        if !new_dirs.is_empty() {
+                            watch_new_dirs(&watches, new_dirs).await;
+                        }
+                    }
+                    () = tokio::time::sleep_until(wake), if due.is_some() => {
+                        manager.report_file_changes(&conv_id, burst.take(), &mut noted).await;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Tell clients which files changed, and the agent which of them it
+    /// has not already been told about.
+    async fn report_file_changes(
+        self: &Arc<Self>,
+        conversation_id: &str,
+        paths: Vec<PathBuf>,
+        noted: &mut HashSet<PathBuf>,
+    ) {
+        let Some(handle) = self.try_get_handle(conversation_id).await else {
+            return;
+        };
+        tracing::info!(conv_id = %conversation_id, count = paths.len(), "Files changed outside");
+        let shown = paths.iter().map(|p| p.display().to_string()).collect();
+        let _ = handle.broadcast_tx.send_seq(|seq| SseEvent::FilesChanged {
+            sequence_id: seq,
+            paths: shown,
+        });
+
+        let fresh: Vec<PathBuf> = paths.into_iter().filter(|p| !noted.contains(p)).collect();
+        if fresh.is_empty() {
+            return;
+        }
+        match self
+            .note_for_agent(conversation_id, changed_note(&fresh))
+            .await
+        {
+            Ok(true) => noted.extend(fresh),
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!(conv_id = %conversation_id, error = %e, "Cannot note file changes");
+            }
+        }
+    }
+
+    /// Remember that the user just wrote `path` through the files API
+    /// (REQ-FE-011), which leaves its own note, so the watcher does not
+    /// report the same save again.
+    pub fn record_user_write(&self, path: &Path) {
+        let mut writes = self
+            .user_writes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        writes.retain(|_, at| at.elapsed() < TOOL_GRACE);
+        writes.insert(path.to_path_buf(), Instant::now());
+    }
+
+    fn is_recent_user_write(&self, path: &Path) -> bool {
+        let writes = self
+            .user_writes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        writes.get(path).is_some_and(|at| at.elapsed() < TOOL_GRACE)
+    }
+}
+
+/// Watch `cwd` in place of whatever was watched before, returning the
+/// filter for its changes. The walk runs on the blocking pool.
+async fn watch(
+    watches: &Arc<Mutex<Watches>>,
+    conversation_id: &str,
+    cwd: &str,
+) -> Option<ChangeFilter> {
+    let root = std::fs::canonicalize(cwd).unwrap_or_else(|_| PathBuf::from(cwd));
+    let watches = Arc::clone(watches);
+    let dir = root.clone();
+    let added = tokio::task::spawn_blocking(move || {
+        let mut watches = watches.lock().unwrap_or_else(PoisonError::into_inner);
+        watches.clear();
+        watches.add_tree(&dir)
+    })
+    .await;
+    match added {
+        Ok(Ok(())) => Some(ChangeFilter::new(root)),
+        Ok(Err(e)) => {
+            tracing::warn!(conv_id = %conversation_id, cwd, error = %e, "Cannot watch cwd");
+            None
+        }
+        Err(e) => {
+            tracing::warn!(conv_id = %conversation_id, cwd, error = %e, "Watch task failed");
+            None
+        }
+    }
+}
+
+/// Start watching directories created since the last walk, and the
+/// directories inside them.
+async fn watch_new_dirs(watches: &Arc<Mutex<Watches>>, dirs: Vec<PathBuf>) {
+    let watches = Arc::clone(watches);
+    let _ = tokio::task::spawn_blocking(move || {
+        let mut watches = watches.lock().unwrap_or_else(PoisonError::into_inner);
+        for dir in dirs {
+            let _ = watches.add_tree(&dir);
+        }
+    })
+    .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn filter_skips_git_ignored_and_directories() {
+        let tmp = TempDir::new().unwrap();
+        let root = fs::canonicalize(tmp.path()).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        for dir in [".git", "target/debug", "src"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let filter = ChangeFilter::new(root.clone());
+
+        let kept = filter.relevant(&root.join("src/lib.rs"));
+        assert_eq!(kept, Some(PathBuf::from("src/lib.rs")));
+        for skipped in [".git/index", "target/debug/app", "build.log", "src"] {
+            assert_eq!(filter.relevant(&root.join(skipped)), None, "{skipped}");
+        }
+        assert_eq!(filter.relevant(Path::new("/elsewhere/lib.rs")), None);
+    }
+
+    #[test]
+    fn watches_skip_ignored_directories() {
+        let tmp = TempDir::new().unwrap();
+        let root = fs::canonicalize(tmp.path()).unwrap();
+        fs::write(root.join(".gitignore"), "target/\nnode_modules/\n").unwrap();
+        fs::write(root.join(crate::phoenixignore::FILE_NAME), "secrets/\n").unwrap();
+        for dir in [
+            ".git/objects",
+            "target/debug",
+            "node_modules/x",
+            "secrets",
+            "src/nested",
+        ] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        let mut watches = Watches {
+            watcher: notify::recommended_watcher(|_: notify::Result<notify::Event>| {}).unwrap(),
+            dirs: HashSet::new(),
+        };
+
+        watches.add_tree(&root).unwrap();
+        let expected: HashSet<PathBuf> = ["", "src", "src/nested"]
+            .iter()
+            .map(|d| root.join(d).components().collect())
+            .collect();
+        assert_eq!(watches.dirs, expected);
+
+        let filter = ChangeFilter::new(root.clone());
+        assert!(filter.watchable(&root.join("src/new")));
+        assert!(!filter.watchable(&root.join("target/release")));
+        assert!(!filter.watchable(&root.join(".git/refs")));
+
+        watches.clear();
+        assert!(watches.dirs.is_empty());
+    }
+
+    #[test]
+    fn pending_coalesces_and_is_capped() {
+        let pending = Pending::default();
+        let at = Instant::now();
+        pending.push(PathBuf::from("a.rs"), at);
+        pending.push(PathBuf::from("a.rs"), at);
+        assert_eq!(pending.take().len(), 1);
+
+        for i in 0..MAX_PENDING + 10 {
+            pending.push(PathBuf::from(format!("f{i}.rs")), at);
+        }
+        assert_eq!(pending.take().len(), MAX_PENDING);
+        assert!(pending.take().is_empty());
+    }
+
+    #[test]
+    fn burst_waits_for_quiet_but_not_forever() {
+        let start = Instant::now();
+        let mut burst = Burst::default();
+        assert_eq!(burst.due(), None);
+
+        burst.add(PathBuf::from("a.rs"), start);
+        assert_eq!(burst.due(), Some(start + DEBOUNCE));
+        burst.add(PathBuf::from("b.rs"), start + Duration::from_millis(4500));
+        assert_eq!(burst.due(), Some(start + MAX_DELAY));
+        burst.add(PathBuf::from("a.rs"), start + Duration::from_millis(4600));
+
+        assert_eq!(burst.take(), [PathBuf::from("a.rs"), PathBuf::from("b.rs")]);
+        assert_eq!(burst.due(), None);
+    }
+
+    #[test]
+    fn note_caps_the_listed_paths() {
+        let one = changed_note(&[PathBuf::from("src/main.rs")]);
+        assert!(one.contains("file changed") && one.contains("`src/main.rs`"));
+        assert!(one.ends_with("Re-read it before changing it."));
+
+        let many: Vec<_> = (0..12).map(|i| PathBuf::from(format!("f{i}.rs"))).collect();
+        let note = changed_note(&many);
+        assert!(note.contains("`f9.rs`, 2 more"));
+        assert!(!note.contains("f10.rs"));
+    }
+}
@@ -0,0 +1,206 @@
+//! Secrets at rest (REQ-BED-051)
+//!
+//! Credentials the server keeps in `phoenix.db`, such as the VAPID signing
+//! key, are stored as libsodium-compatible sealed boxes (X25519 and
+//! XSalsa20-Poly1305), so a copy of the database file alone reveals none of
+//! them. The box key comes from, in order:
+//!
+//! - `PHOENIX_SECRET_KEY`: 32 bytes, base64
+//! - the macOS keychain, created on first use
+//! - `secret.key` next to the database (mode 0600), created on first use
+//!
+//! Backups of the database need the key to be useful; keep it alongside
+//! them, not inside. Sealed values are text: [`PREFIX`] then base64. Values
+//! without the prefix predate sealing and read as plaintext.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use crypto_box::aead::OsRng;
+use crypto_box::{PublicKey, SecretKey};
+use std::path::Path;
+
+/// Marks a stored value as sealed, and by which scheme.
+pub const PREFIX: &str = "sealed:v1:";
+
+/// Environment variable holding the box key.
+const KEY_VAR: &str = "PHOENIX_SECRET_KEY";
+
+/// Key file written next to the database when no other source has a key.
+const KEY_FILE: &str = "secret.key";
+
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("invalid secret key: {0}")]
+    InvalidKey(String),
+    #[error("cannot read or create the secret key: {0}")]
+    KeyStore(String),
+    #[error("cannot seal value")]
+    Seal,
+    #[error("sealed value is corrupt or was sealed with another key")]
+    Open,
+}
+
+/// Seals and opens stored secrets with one key pair.
+pub struct SecretBox {
+    secret: SecretKey,
+    public: PublicKey,
+}
+
+impl SecretBox {
+    fn from_key(bytes: [u8; 32]) -> Self {
+        let secret = SecretKey::from_bytes(bytes);
+        let public = secret.public_key();
+        Self { secret, public }
+    }
+
+    /// A box with a fresh key that is never stored, for in-memory databases.
+    pub fn ephemeral() -> Self {
+        let secret = SecretKey::generate(&mut OsRng);
+        let public = secret.public_key();
+        Self { secret, public }
+    }
+
+    /// The box for the database at `db_path`, with the key from the first
+    /// source that has one (see the module docs).
+    pub fn load(db_path: &Path) -> Result<Self, SecretError> {
+        if let Ok(encoded) = std::env::var(KEY_VAR) {
+            return decode_key(&encoded).map(Self::from_key);
+        }
+        #[cfg(target_os = "macos")]
+        match keychain_key(db_path) {
+            Ok(key) => return Ok(Self::from_key(key)),
+            Err(e) => tracing::warn!(error = %e, "Keychain unavailable; using the key file"),
+        }
+        let dir = db_path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        file_key(&dir.join(KEY_FILE)).map(Self::from_key)
+    }
+
+    /// `plaintext` sealed for storage.
+    pub fn seal(&self, plaintext: &str) -> Result<String, SecretError> {
+        let sealed = self
+            .public
+            .seal(&mut OsRng, plaintext.as_bytes())
+            .map_err(|_| SecretError::Seal)?;
+        Ok(format!("{PREFIX}{}", STANDARD.encode(sealed)))
+    }
+
+    /// The plaintext of a stored value. Values without [`PREFIX`] were
+    /// stored before sealing and are returned as they are.
+    pub fn open(&self, stored: &str) -> Result<String, SecretError> {
+        let Some(encoded) = stored.strip_prefix(PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let sealed = STANDARD.decode(encoded).map_err(|_| SecretError::Open)?;
+        let plaintext = self.secret.unseal(&sealed).map_err(|_| SecretError::Open)?;
+        String::from_utf8(plaintext).map_err(|_| SecretError::Open)
+    }
+}
+
+/// Whether a stored value is sealed rather than legacy plaintext.
+pub fn is_sealed(stored: &str) -> bool {
+    stored.starts_with(PREFIX)
+}
+
+fn decode_key(encoded: &str) -> Result<[u8; 32], SecretError> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| SecretError::InvalidKey(e.to_string()))?;
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .map_err(|_| SecretError::InvalidKey(format!("expected 32 bytes, got {}", bytes.len())))
+}
+
+fn new_key() -> [u8; 32] {
+    SecretBox::ephemeral().secret.to_bytes()
+}
+
+/// The key stored in `path`, creating the file with a new key if there is
+/// none. The file is created owner-only and never overwritten.
+fn file_key(path: &Path) -> Result<[u8; 32], SecretError> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    match std::fs::read_to_string(path) {
+        Ok(encoded) => return decode_key(&encoded),
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            return Err(SecretError::KeyStore(format!("{}: {e}", path.display())));
+        }
+        Err(_) => {}
+    }
+    let key = new_key();
+    let created = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", STANDARD.encode(key)));
+    match created {
+        Ok(()) => {
+            tracing::info!(path = %path.display(), "Created secret key file");
+            Ok(key)
+        }
+        // Another process won the race; use its key.
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => file_key(path),
+        Err(e) => Err(SecretError::KeyStore(format!("{}: {e}", path.display()))),
+    }
+}
+
+/// The key in the login keychain for this database, created on first use.
+#[cfg(target_os = "macos")]
+fn keychain_key(db_path: &Path) -> Result<[u8; 32], SecretError> {
+    let store = |e: keyring::Error| SecretError::KeyStore(e.to_string());
+    let entry =
+        keyring::Entry::new("phoenix-ide", &db_path.display().to_string()).map_err(store)?;
+    match entry.get_password() {
+        Ok(encoded) => decode_key(&encoded),
+        Err(keyring::Error::NoEntry) => {
+            let key = new_key();
+            entry.set_password(&STANDARD.encode(key)).map_err(store)?;
+            Ok(key)
+        }
+        Err(e) => Err(store(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn sealed_values_open_only_with_their_key() {
+        let secrets = SecretBox::ephemeral();
+        let sealed = secrets.seal("sk-live-123").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.contains("sk-live-123"));
+        assert_eq!(secrets.open(&sealed).unwrap(), "sk-live-123");
+        // Each seal uses a fresh ephemeral key
+        assert_ne!(secrets.seal("sk-live-123").unwrap(), sealed);
+
+        assert!(matches!(
+            SecretBox::ephemeral().open(&sealed),
+            Err(SecretError::Open)
+        ));
+        assert_eq!(
+            secrets.open("legacy plaintext").unwrap(),
+            "legacy plaintext"
+        );
+    }
+
+    #[test]
+    fn key_file_is_created_once_and_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join(KEY_FILE);
+        let key = file_key(&path).unwrap();
+        assert_eq!(file_key(&path).unwrap(), key);
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        std::fs::write(&path, "not base64!").unwrap();
+        assert!(matches!(file_key(&path), Err(SecretError::InvalidKey(_))));
+    }
+}
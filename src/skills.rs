@@ -2,11 +2,17 @@
 //!
 //! Both the user `/skill` path (`message_expander`) and the LLM Skill tool
 //! (`tools/skill.rs`) call `invoke_skill()` to produce identical output.
+//!
+//! Also owns reads and writes of the user skill library under
+//! `$HOME/.phoenix-ide/skills/` (REQ-SK-009).
 
-use crate::system_prompt::SkillMetadata;
+use crate::system_prompt::{list_library_skills, SkillMetadata, LIBRARY_SKILL_DIR};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
 
 /// The result of invoking a skill.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SkillInvocation {
     /// The skill name (e.g., "build")
     pub name: String,
@@ -95,7 +101,7 @@ fn strip_frontmatter(content: &str) -> String {
 /// Order: `$ARGUMENTS[N]` and `$N` first (to prevent `$ARGUMENTS` from
 /// corrupting them), then `$ARGUMENTS`. If no placeholder exists, append
 /// arguments.
-fn substitute_arguments(body: &str, arguments: &str) -> String {
+pub(crate) fn substitute_arguments(body: &str, arguments: &str) -> String {
     if arguments.is_empty() {
         return body.to_string();
     }
@@ -121,6 +127,166 @@ fn substitute_arguments(body: &str, arguments: &str) -> String {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Skill library (REQ-SK-009)
+// ---------------------------------------------------------------------------
+
+/// Errors surfaced by skill library CRUD.
+#[derive(thiserror::Error, Debug)]
+pub enum SkillLibraryError {
+    #[error("invalid skill name '{0}': use 1-64 lowercase letters, digits, '-' or '_'")]
+    InvalidName(String),
+    #[error("skill '{0}' not found in library")]
+    NotFound(String),
+    #[error("skill '{0}' already exists in library")]
+    AlreadyExists(String),
+    #[error("skill library I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// A library skill as authored from the UI: frontmatter fields plus body.
+#[derive(Debug, Clone)]
+pub struct LibrarySkill {
+    pub name: String,
+    pub description: String,
+    pub argument_hint: Option<String>,
+    pub triggers: Vec<String>,
+    /// Markdown body, without frontmatter
+    pub body: String,
+}
+
+/// `$HOME/.phoenix-ide/skills`, or `None` when `$HOME` is unset.
+pub(crate) fn library_dir() -> Option<PathBuf> {
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(LIBRARY_SKILL_DIR))
+}
+
+/// Library skill names double as file names, so they are restricted to a
+/// conservative character set (no separators, no `..`).
+fn validate_library_name(name: &str) -> Result<(), SkillLibraryError> {
+    let valid = (1..=64).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric());
+    if valid {
+        Ok(())
+    } else {
+        Err(SkillLibraryError::InvalidName(name.to_string()))
+    }
+}
+
+/// Collapse a frontmatter value onto one line; the parser is line-based.
+fn single_line(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Render a library skill as frontmatter + body, in the shape
+/// `parse_skill_frontmatter` reads back.
+fn render_library_skill(skill: &LibrarySkill) -> String {
+    let mut out = format!(
+        "---\nname: {}\ndescription: {}\n",
+        skill.name,
+        single_line(&skill.description)
+    );
+    if let Some(hint) = skill.argument_hint.as_deref().map(single_line) {
+        if !hint.is_empty() {
+            let _ = writeln!(out, "argument-hint: {hint}");
+        }
+    }
+    let triggers: Vec<String> = skill
+        .triggers
+        .iter()
+        .map(|t| single_line(&t.replace([',', '[', ']'], " ")).to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    if !triggers.is_empty() {
+        let _ = writeln!(out, "triggers: [{}]", triggers.join(", "));
+    }
+    out.push_str("---\n\n");
+    out.push_str(skill.body.trim_start_matches('\n'));
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out
+}
+
+fn find_library_skill(dir: &Path, name: &str) -> Result<SkillMetadata, SkillLibraryError> {
+    validate_library_name(name)?;
+    list_library_skills(dir)
+        .into_iter()
+        .find(|s| s.name == name)
+        .ok_or_else(|| SkillLibraryError::NotFound(name.to_string()))
+}
+
+/// Read a library skill, including its body.
+///
+/// # Errors
+///
+/// `NotFound` if no library file declares `name`; `Io` if it cannot be read.
+pub fn read_library_skill(dir: &Path, name: &str) -> Result<LibrarySkill, SkillLibraryError> {
+    let meta = find_library_skill(dir, name)?;
+    let content = std::fs::read_to_string(&meta.path)?;
+    Ok(LibrarySkill {
+        name: meta.name,
+        description: meta.description,
+        argument_hint: meta.argument_hint,
+        triggers: meta.triggers,
+        body: strip_frontmatter(&content),
+    })
+}
+
+/// Create a new library skill as `<dir>/<name>.md`.
+///
+/// # Errors
+///
+/// `InvalidName`, `AlreadyExists` if any library file already declares the
+/// name, or `Io` on write failure.
+pub fn create_library_skill(
+    dir: &Path,
+    skill: &LibrarySkill,
+) -> Result<PathBuf, SkillLibraryError> {
+    validate_library_name(&skill.name)?;
+    let path = dir.join(format!("{}.md", skill.name));
+    if path.exists()
+        || list_library_skills(dir)
+            .iter()
+            .any(|s| s.name == skill.name)
+    {
+        return Err(SkillLibraryError::AlreadyExists(skill.name.clone()));
+    }
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(&path, render_library_skill(skill))?;
+    Ok(path)
+}
+
+/// Overwrite an existing library skill in place. The name is fixed; renames
+/// are a delete + create.
+///
+/// # Errors
+///
+/// `NotFound` if no library file declares `skill.name`; `Io` on write failure.
+pub fn update_library_skill(
+    dir: &Path,
+    skill: &LibrarySkill,
+) -> Result<PathBuf, SkillLibraryError> {
+    let meta = find_library_skill(dir, &skill.name)?;
+    std::fs::write(&meta.path, render_library_skill(skill))?;
+    Ok(meta.path)
+}
+
+/// Delete a library skill file.
+///
+/// # Errors
+///
+/// `NotFound` if no library file declares `name`; `Io` on removal failure.
+pub fn delete_library_skill(dir: &Path, name: &str) -> Result<(), SkillLibraryError> {
+    let meta = find_library_skill(dir, name)?;
+    std::fs::remove_file(&meta.path)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -306,4 +472,73 @@ mod tests {
         let result = invoke_skill("simple", "extra args", &skills).unwrap();
         assert!(result.body.contains("ARGUMENTS: extra args"));
     }
+
+    // -------------------------------------------------------------------------
+    // Skill library CRUD (REQ-SK-009)
+    // -------------------------------------------------------------------------
+
+    fn library_skill(name: &str) -> LibrarySkill {
+        LibrarySkill {
+            name: name.to_string(),
+            description: "Review a diff\nthoroughly".to_string(),
+            argument_hint: Some("[path]".to_string()),
+            triggers: vec!["Code Review".to_string(), "diff".to_string()],
+            body: "Check $ARGUMENTS for bugs.".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_library_create_read_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("skills");
+        let path = create_library_skill(&dir, &library_skill("review")).unwrap();
+        assert_eq!(path, dir.join("review.md"));
+
+        let skill = read_library_skill(&dir, "review").unwrap();
+        assert_eq!(skill.description, "Review a diff thoroughly");
+        assert_eq!(skill.argument_hint.as_deref(), Some("[path]"));
+        assert_eq!(skill.triggers, vec!["code review", "diff"]);
+        assert_eq!(skill.body, "Check $ARGUMENTS for bugs.\n");
+    }
+
+    #[test]
+    fn test_library_create_rejects_duplicate() {
+        let tmp = TempDir::new().unwrap();
+        create_library_skill(tmp.path(), &library_skill("review")).unwrap();
+        let err = create_library_skill(tmp.path(), &library_skill("review")).unwrap_err();
+        assert!(matches!(err, SkillLibraryError::AlreadyExists(_)));
+    }
+
+    #[test]
+    fn test_library_rejects_path_like_names() {
+        let tmp = TempDir::new().unwrap();
+        for name in ["../escape", "a/b", "", "Upper", "-lead"] {
+            let err = create_library_skill(tmp.path(), &library_skill(name)).unwrap_err();
+            assert!(matches!(err, SkillLibraryError::InvalidName(_)), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_library_update_and_delete() {
+        let tmp = TempDir::new().unwrap();
+        create_library_skill(tmp.path(), &library_skill("review")).unwrap();
+
+        let mut edited = library_skill("review");
+        edited.body = "New body.".to_string();
+        edited.triggers.clear();
+        update_library_skill(tmp.path(), &edited).unwrap();
+        let skill = read_library_skill(tmp.path(), "review").unwrap();
+        assert_eq!(skill.body, "New body.\n");
+        assert!(skill.triggers.is_empty());
+
+        delete_library_skill(tmp.path(), "review").unwrap();
+        assert!(matches!(
+            read_library_skill(tmp.path(), "review"),
+            Err(SkillLibraryError::NotFound(_))
+        ));
+        assert!(matches!(
+            update_library_skill(tmp.path(), &edited),
+            Err(SkillLibraryError::NotFound(_))
+        ));
+    }
 }
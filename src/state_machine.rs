@@ -4,12 +4,17 @@
 //! Two pure entry points: `transition()` for user events, `handle_outcome()`
 //! for executor-produced outcomes.
 
+pub mod budget;
 pub(crate) mod effect;
 pub mod event;
 pub mod outcome;
+pub mod question;
+pub mod replay;
 pub mod state;
 pub(crate) mod transition;
 
+#[cfg(test)]
+mod conformance;
 #[cfg(test)]
 mod project_proptests;
 #[cfg(test)]
@@ -22,10 +27,15 @@ pub use state::{ConvContext, ConvState, StepResult};
 // Re-exports for split state types (used by future callers that adopt the split API)
 #[allow(unused_imports)]
 pub use state::{CoreState, ParentState, SubAgentState};
-pub use transition::{check_user_message_acceptable, handle_outcome, transition, TransitionError};
+#[allow(unused_imports)]
+pub use transition::handle_outcome;
+pub use transition::{
+    check_tool_cancellable, check_user_message_acceptable, check_user_steer_acceptable,
+    outcome_to_event, transition, TransitionError,
+};
 
 // Re-exports for atomic persistence types (used by runtime/executor)
-pub use effect::tool_result_message_id;
+pub use effect::{compute_thinking_display_data, tool_result_message_id};
 #[allow(unused_imports)]
 pub use effect::{CheckpointData, PersistError};
 #[allow(unused_imports)]
@@ -0,0 +1,210 @@
+//! Per-turn budget and loop detection (REQ-BED-038)
+//!
+//! A parent turn runs from a user message until the agent stops on its own.
+//! The executor records every LLM request and tool call on the context's
+//! [`TurnBudget`] and checks it before each LLM request. A breach pauses the
+//! conversation in `AwaitingUserGuidance` instead of letting a stuck agent
+//! spend tokens until the hard tool-cycle cap halts it.
+//!
+//! Checks happen between tool rounds, where the persisted history is
+//! complete, so a round already under way always finishes first.
+
+use serde_json::Value;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Limits for one turn. `0` (or `None` for the duration) disables a limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[allow(clippy::struct_field_names)] // every field is a maximum
+pub struct TurnLimits {
+    pub max_tool_calls: u32,
+    pub max_llm_requests: u32,
+    pub max_duration: Option<Duration>,
+    /// Identical tool calls in a row (same tool, same input) that count as
+    /// a loop.
+    pub max_repeats: u32,
+}
+
+impl TurnLimits {
+    /// Parent-conversation defaults. Well above what a real task needs in
+    /// one turn; they are there to catch an agent that has stopped making
+    /// progress, not to ration work.
+    pub const DEFAULT: Self = Self {
+        max_tool_calls: 400,
+        max_llm_requests: 250,
+        max_duration: Some(Duration::from_mins(60)),
+        max_repeats: 5,
+    };
+}
+
+/// The limit a turn ran past.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BudgetBreach {
+    ToolCalls(u32),
+    LlmRequests(u32),
+    Duration(Duration),
+    Repetition { tool: String, count: u32 },
+}
+
+impl fmt::Display for BudgetBreach {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetBreach::ToolCalls(n) => write!(f, "made {n} tool calls this turn"),
+            BudgetBreach::LlmRequests(n) => write!(f, "made {n} model requests this turn"),
+            BudgetBreach::Duration(d) => {
+                write!(f, "has been working for {} minutes", d.as_secs() / 60)
+            }
+            BudgetBreach::Repetition { tool, count } => {
+                write!(
+                    f,
+                    "called `{tool}` with identical input {count} times in a row"
+                )
+            }
+        }
+    }
+}
+
+/// Usage of the current turn against its [`TurnLimits`].
+#[derive(Debug, Clone, Default)]
+pub struct TurnBudget {
+    limits: TurnLimits,
+    tool_calls: u32,
+    llm_requests: u32,
+    started_at: Option<Instant>,
+    /// `(tool name, input JSON)` of the latest call
+    last_call: Option<(String, String)>,
+    repeats: u32,
+}
+
+impl TurnBudget {
+    pub fn new(limits: TurnLimits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    /// Reset usage for a turn the user just started.
+    pub fn start_turn(&mut self, now: Instant) {
+        *self = Self {
+            started_at: Some(now),
+            ..Self::new(self.limits)
+        };
+    }
+
+    pub fn record_llm_request(&mut self, now: Instant) {
+        self.started_at.get_or_insert(now);
+        self.llm_requests += 1;
+    }
+
+    pub fn record_tool_call(&mut self, name: &str, input: &Value) {
+        self.tool_calls += 1;
+        let call = (name.to_string(), input.to_string());
+        if self.last_call.as_ref() == Some(&call) {
+            self.repeats += 1;
+        } else {
+            self.last_call = Some(call);
+            self.repeats = 1;
+        }
+    }
+
+    /// The first limit the turn has run past, if any. A repeated call is
+    /// reported ahead of the counters since it is the more specific signal.
+    pub fn breach(&self, now: Instant) -> Option<BudgetBreach> {
+        let limits = &self.limits;
+        if limits.max_repeats > 0 && self.repeats >= limits.max_repeats {
+            let tool = self.last_call.as_ref().map(|(name, _)| name.clone());
+            return Some(BudgetBreach::Repetition {
+                tool: tool.unwrap_or_default(),
+                count: self.repeats,
+            });
+        }
+        if limits.max_tool_calls > 0 && self.tool_calls > limits.max_tool_calls {
+            return Some(BudgetBreach::ToolCalls(limits.max_tool_calls));
+        }
+        if limits.max_llm_requests > 0 && self.llm_requests > limits.max_llm_requests {
+            return Some(BudgetBreach::LlmRequests(limits.max_llm_requests));
+        }
+        let elapsed = self.started_at.map(|t| now.saturating_duration_since(t));
+        match (limits.max_duration, elapsed) {
+            (Some(max), Some(elapsed)) if elapsed > max => Some(BudgetBreach::Duration(max)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn limits() -> TurnLimits {
+        TurnLimits {
+            max_tool_calls: 3,
+            max_llm_requests: 2,
+            max_duration: Some(Duration::from_secs(60)),
+            max_repeats: 2,
+        }
+    }
+
+    #[test]
+    fn default_budget_never_breaches() {
+        let mut budget = TurnBudget::default();
+        let now = Instant::now();
+        for _ in 0..100 {
+            budget.record_llm_request(now);
+            budget.record_tool_call("bash", &json!({"command": "ls"}));
+        }
+        assert_eq!(budget.breach(now + Duration::from_secs(86_400)), None);
+    }
+
+    #[test]
+    fn counters_breach_once_past_the_limit() {
+        let mut budget = TurnBudget::new(limits());
+        let now = Instant::now();
+        budget.start_turn(now);
+        budget.record_llm_request(now);
+        budget.record_llm_request(now);
+        assert_eq!(budget.breach(now), None);
+        budget.record_llm_request(now);
+        assert_eq!(budget.breach(now), Some(BudgetBreach::LlmRequests(2)));
+
+        budget.start_turn(now);
+        for i in 0..4 {
+            budget.record_tool_call("bash", &json!({"command": i}));
+        }
+        assert_eq!(budget.breach(now), Some(BudgetBreach::ToolCalls(3)));
+    }
+
+    #[test]
+    fn identical_calls_in_a_row_are_a_loop() {
+        let mut budget = TurnBudget::new(limits());
+        let ls = json!({"command": "ls"});
+        budget.record_tool_call("bash", &ls);
+        budget.record_tool_call("think", &json!({"thoughts": "hm"}));
+        budget.record_tool_call("bash", &ls);
+        assert_eq!(budget.breach(Instant::now()), None);
+
+        budget.record_tool_call("bash", &ls);
+        let breach = budget.breach(Instant::now()).unwrap();
+        assert_eq!(
+            breach.to_string(),
+            "called `bash` with identical input 2 times in a row"
+        );
+    }
+
+    #[test]
+    fn wall_clock_runs_from_turn_start() {
+        let mut budget = TurnBudget::new(limits());
+        let start = Instant::now();
+        budget.start_turn(start);
+        assert_eq!(budget.breach(start + Duration::from_secs(60)), None);
+        assert_eq!(
+            budget.breach(start + Duration::from_secs(61)),
+            Some(BudgetBreach::Duration(Duration::from_secs(60)))
+        );
+
+        budget.start_turn(start + Duration::from_secs(61));
+        assert_eq!(budget.breach(start + Duration::from_secs(90)), None);
+    }
+}
@@ -0,0 +1,195 @@
+//! Transition table snapshot for the state machine
+//!
+//! Walks every (state, event) pair the property-test generators produce
+//! through [`transition`] and renders what happens as a Markdown table: the
+//! next state and its effects, or why the event was rejected. The table is
+//! checked in at `specs/bedrock/transitions.md`, so every behaviour change
+//! shows up as a reviewable diff. `transition_table_matches_snapshot` fails
+//! when the table changes; rerun it with `UPDATE_TRANSITION_TABLE=1` to
+//! accept the new one. A missing table is an error too, so the snapshot
+//! cannot silently go unchecked; the same variable writes it.
+//! `transition_table_covers_every_event_variant` fails when `arb_event` misses
+//! an `Event` variant, so a new event cannot drop out of the table.
+
+use super::proptests::{arb_event, arb_state, test_context};
+use super::{transition, ConvState, Effect, Event};
+use proptest::strategy::{Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Values drawn from each generator. Enough to reach every variant many
+/// times over.
+const DRAWS: usize = 2_000;
+
+/// Values kept per variant. Each state sample meets each event sample.
+const SAMPLES_PER_VARIANT: usize = 12;
+
+/// Snapshot path, relative to the crate root.
+const SNAPSHOT: &str = "specs/bedrock/transitions.md";
+
+/// Up to `SAMPLES_PER_VARIANT` values of each variant `strategy` produces,
+/// by variant name. The runner is seeded, so the samples are the same on
+/// every run.
+fn samples<T>(
+    strategy: &impl Strategy<Value = T>,
+    variant: impl Fn(&T) -> &'static str,
+) -> BTreeMap<&'static str, Vec<T>> {
+    let mut runner = TestRunner::deterministic();
+    let mut by_variant: BTreeMap<&'static str, Vec<T>> = BTreeMap::new();
+    for _ in 0..DRAWS {
+        let value = strategy
+            .new_tree(&mut runner)
+            .expect("generator rejected its own input")
+            .current();
+        let bucket = by_variant.entry(variant(&value)).or_default();
+        if bucket.len() < SAMPLES_PER_VARIANT {
+            bucket.push(value);
+        }
+    }
+    by_variant
+}
+
+/// The variant name a `Debug` rendering starts with, without its payload.
+fn debug_variant(debug: &str) -> &str {
+    let end = debug
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(debug.len());
+    debug.get(..end).unwrap_or(debug)
+}
+
+fn effect_name(effect: &Effect) -> String {
+    match effect {
+        Effect::NotifyClient { event_type, .. } => format!("NotifyClient({event_type})"),
+        other => debug_variant(&format!("{other:?}")).to_string(),
+    }
+}
+
+/// One line for what `event` does to `state`.
+fn outcome(state: &ConvState, event: Event) -> String {
+    match transition(state, &test_context(), event) {
+        Ok(result) => {
+            let effects: Vec<String> = result.effects.iter().map(effect_name).collect();
+            if effects.is_empty() {
+                result.new_state.variant_name().to_string()
+            } else {
+                format!(
+                    "{} [{}]",
+                    result.new_state.variant_name(),
+                    effects.join(", ")
+                )
+            }
+        }
+        Err(e) => format!("rejected: {}", debug_variant(&format!("{e:?}"))),
+    }
+}
+
+/// The whole table, one row per (state, event) pair with every distinct
+/// outcome seen for it.
+fn render() -> String {
+    let states = samples(&arb_state(), ConvState::variant_name);
+    let events = samples(&arb_event(), Event::variant_name);
+
+    let mut table = String::from(
+        "# State Machine Transitions\n\n\
+         Generated by `src/state_machine/conformance.rs` from the property-test \
+         generators. Do not edit by hand: run `UPDATE_TRANSITION_TABLE=1 cargo test \
+         transition_table` and review the diff.\n\n\
+         Each row lists every distinct outcome of applying the event to sampled \
+         states of that variant, as the next state and its effects in order.\n\n\
+         | State | Event | Outcome |\n\
+         |-------|-------|---------|\n",
+    );
+    for (state_name, state_samples) in &states {
+        for (event_name, event_samples) in &events {
+            let outcomes: BTreeSet<String> = state_samples
+                .iter()
+                .flat_map(|state| {
+                    event_samples
+                        .iter()
+                        .map(move |event| outcome(state, event.clone()))
+                })
+                .collect();
+            let outcomes: Vec<String> = outcomes.into_iter().collect();
+            let outcomes = outcomes.join("<br>");
+            let _ = writeln!(table, "| {state_name} | {event_name} | {outcomes} |");
+        }
+    }
+    table
+}
+
+#[test]
+fn transition_table_is_deterministic() {
+    assert_eq!(render(), render());
+}
+
+#[test]
+fn transition_table_covers_every_generated_pair() {
+    let table = render();
+    let states = samples(&arb_state(), ConvState::variant_name);
+    let events = samples(&arb_event(), Event::variant_name);
+    let rows = table.lines().filter(|l| l.starts_with("| ")).count();
+    // Header row plus one per pair
+    assert_eq!(rows, 1 + states.len() * events.len());
+    assert!(
+        table.contains("| Idle | UserMessage | LlmRequesting ["),
+        "{table}"
+    );
+}
+
+/// Every `Event` variant name, read from the arms of `Event::variant_name`.
+/// That match is exhaustive, so a new variant shows up here as soon as it
+/// compiles.
+fn event_variant_names() -> BTreeSet<&'static str> {
+    let source = include_str!("event.rs");
+    let start = source
+        .find("impl Event {")
+        .expect("event.rs has an impl Event block");
+    let body = source.get(start..).unwrap_or_default();
+    let end = body.find("\n}\n").unwrap_or(body.len());
+    body.get(..end)
+        .unwrap_or_default()
+        .lines()
+        .filter(|line| line.trim_start().starts_with("Event::"))
+        .filter_map(|line| line.split('"').nth(1))
+        .collect()
+}
+
+#[test]
+fn transition_table_covers_every_event_variant() {
+    let expected = event_variant_names();
+    assert!(expected.contains("UserMessage"), "{expected:?}");
+    let generated: BTreeSet<&str> = samples(&arb_event(), Event::variant_name)
+        .into_keys()
+        .collect();
+    let missing: Vec<&&str> = expected.difference(&generated).collect();
+    assert!(
+        missing.is_empty(),
+        "arb_event never generates {missing:?}; add them so the transition table covers them"
+    );
+}
+
+#[test]
+fn transition_table_matches_snapshot() {
+    let table = render();
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(SNAPSHOT);
+    let update = std::env::var("UPDATE_TRANSITION_TABLE").is_ok_and(|v| v == "1");
+    match std::fs::read_to_string(&path) {
+        Ok(existing) if existing == table => {}
+        Ok(existing) if !update => {
+            let diff = similar::TextDiff::from_lines(&existing, &table)
+                .unified_diff()
+                .header(SNAPSHOT, "current")
+                .to_string();
+            panic!(
+                "State machine transitions changed. Review the diff and rerun with \
+                 UPDATE_TRANSITION_TABLE=1 to accept it.\n\n{diff}"
+            );
+        }
+        Err(e) if !update => panic!(
+            "Cannot read {SNAPSHOT} ({e}). Rerun with UPDATE_TRANSITION_TABLE=1 to write it."
+        ),
+        _ => std::fs::write(&path, &table).expect("write transition table"),
+    }
+}
@@ -4,6 +4,8 @@ use crate::db::{ImageData, MessageContent, ToolContent, ToolContentImage, ToolRe
 use crate::llm::ContentBlock;
 use crate::state_machine::state::{AssistantMessage, SubAgentOutcome, SubAgentResult, ToolCall};
 use crate::tools::bash_check::display_command;
+use crate::tools::patch::StagedPatch;
+use serde::Serialize;
 use serde_json::Value;
 use std::fmt;
 use std::path::Path;
@@ -16,7 +18,8 @@ use std::time::Duration;
 /// Data to persist atomically. The `ToolRound` variant enforces that assistant
 /// messages and tool results are always written together — half-written history
 /// is structurally unrepresentable.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum CheckpointData {
     /// A complete tool round: assistant message + all tool results.
     /// Constructor enforces matching counts.
@@ -48,18 +51,28 @@ impl fmt::Display for PersistError {
 impl CheckpointData {
     /// Construct a `ToolRound` checkpoint, enforcing that the number of
     /// `tool_use` blocks in the assistant message matches the number of
-    /// tool results.
+    /// tool results. Results are put in `tool_use` order: a tool skipped
+    /// from the queue (REQ-BED-055) is answered before the ones ahead of it
+    /// finish.
     pub fn tool_round(
         assistant_message: AssistantMessage,
-        tool_results: Vec<ToolResult>,
+        mut tool_results: Vec<ToolResult>,
     ) -> Result<Self, PersistError> {
-        let tool_use_count = assistant_message.tool_uses().len();
-        if tool_use_count != tool_results.len() {
+        let tool_uses = assistant_message.tool_uses();
+        if tool_uses.len() != tool_results.len() {
             return Err(PersistError::ResultCountMismatch {
-                tool_uses: tool_use_count,
+                tool_uses: tool_uses.len(),
                 results: tool_results.len(),
             });
         }
+        let order: Vec<&str> = tool_uses
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { id, .. } => Some(id.as_str()),
+                _ => None,
+            })
+            .collect();
+        tool_results.sort_by_key(|r| order.iter().position(|id| *id == r.tool_use_id));
         Ok(Self::ToolRound {
             assistant_message,
             tool_results,
@@ -78,7 +91,8 @@ pub fn tool_result_message_id(tool_use_id: &str) -> String {
 }
 
 /// Effects to be executed after state transition
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Effect {
     /// Persist a message to the database
     PersistMessage {
@@ -96,12 +110,25 @@ pub enum Effect {
     /// Make an LLM request
     RequestLlm,
 
+    /// Ask several models the same request at once (REQ-BED-058)
+    RequestCouncil { models: Vec<String> },
+
     /// Execute a tool (spawns as background task)
     ExecuteTool { tool: ToolCall },
 
+    /// Hold a user steering note until the next LLM request (REQ-BED-034)
+    QueueSteer { text: String, message_id: String },
+
     /// Abort the currently running tool
     AbortTool { tool_use_id: String },
 
+    /// Write a patch the user approved and report it as the tool result
+    /// (REQ-PATCH-010)
+    ApplyPatch {
+        tool_use_id: String,
+        patch: StagedPatch,
+    },
+
     /// Abort the currently running LLM request
     AbortLlm,
 
@@ -145,6 +172,10 @@ pub enum Effect {
     /// Notify client of context exhaustion - REQ-BED-021
     NotifyContextExhausted { summary: String },
 
+    /// Continue in a new linked conversation seeded with the summary
+    /// (REQ-BED-044). A no-op when the runtime has auto-continuation off.
+    AutoContinue { summary: String },
+
     /// Execute git operations for task approval (REQ-BED-028).
     /// The executor handles: assign task ID, write task file, commit to main,
     /// create branch, checkout branch. Placeholder — executor implementation in later batch.
@@ -235,6 +266,7 @@ impl Effect {
                 content: output.into(),
                 is_error,
                 images,
+                user_command: None,
             }),
             display_data,
             usage_data: None,
@@ -320,3 +352,50 @@ pub fn compute_bash_display_data(blocks: &[ContentBlock], cwd: &Path) -> Option<
         Some(serde_json::json!({ "bash": bash_displays }))
     }
 }
+
+/// Longest `summary` shown on a collapsed thinking aside, in characters.
+const THINKING_SUMMARY_CHARS: usize = 120;
+
+/// Compute the collapsed view of extended-thinking blocks (REQ-LLM-014).
+///
+/// Returns `{ "blocks", "chars", "redacted" }` plus a one-line `summary`
+/// (the first non-empty line, truncated) unless the text is being redacted
+/// at rest. Returns `None` when there is no thinking.
+pub fn compute_thinking_display_data(blocks: &[ContentBlock], redacted: bool) -> Option<Value> {
+    let mut count = 0;
+    let mut chars = 0;
+    let mut first_line: Option<&str> = None;
+    for block in blocks {
+        match block {
+            ContentBlock::Thinking { thinking, .. } => {
+                count += 1;
+                chars += thinking.chars().count();
+                first_line = first_line.or_else(|| {
+                    thinking
+                        .lines()
+                        .map(str::trim)
+                        .find(|line| !line.is_empty())
+                });
+            }
+            ContentBlock::RedactedThinking { .. } => count += 1,
+            _ => {}
+        }
+    }
+    if count == 0 {
+        return None;
+    }
+
+    let mut data = serde_json::json!({
+        "blocks": count,
+        "chars": chars,
+        "redacted": redacted,
+    });
+    if let Some(line) = first_line.filter(|_| !redacted) {
+        let mut summary: String = line.chars().take(THINKING_SUMMARY_CHARS).collect();
+        if summary.len() < line.len() {
+            summary.push('…');
+        }
+        data["summary"] = Value::String(summary);
+    }
+    Some(data)
+}
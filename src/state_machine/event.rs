@@ -3,12 +3,16 @@
 use crate::db::{ErrorKind, ImageData, ToolResult};
 use crate::llm::{ContentBlock, Usage};
 use crate::state_machine::state::{
-    PendingSubAgent, QuestionAnnotation, SubAgentOutcome, TaskApprovalOutcome, ToolCall,
+    CouncilCandidate, PendingSubAgent, QuestionAnnotation, SubAgentOutcome, TaskApprovalOutcome,
+    ToolCall,
 };
+use crate::tools::patch::StagedPatch;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Events that trigger state transitions
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Event {
     // User events
     UserMessage {
@@ -30,6 +34,38 @@ pub enum Event {
         /// Why the cancel was issued. `None` means user-initiated or parent-propagated.
         reason: Option<String>,
     },
+    /// Guidance typed while the agent is working (REQ-BED-034). Held until
+    /// the next LLM request instead of cancelling the run.
+    UserSteer {
+        text: String,
+        /// Client-generated UUID, used when the note is persisted
+        message_id: String,
+    },
+    /// A user message answered by several models at once (REQ-BED-058).
+    /// Fields as in `UserMessage`, plus the models to ask.
+    UserCouncilMessage {
+        text: String,
+        llm_text: Option<String>,
+        images: Vec<ImageData>,
+        message_id: String,
+        user_agent: Option<String>,
+        skill_invocation: Option<crate::skills::SkillInvocation>,
+        models: Vec<String>,
+    },
+    /// Re-run the last user turn from `Error` (REQ-BED-035). History is
+    /// rebuilt from the database, so nothing needs to be re-persisted.
+    UserRetry,
+    /// The project's verify command failed after the agent's edits
+    /// (REQ-BED-037). `report` is fed back as a meta user message.
+    VerifyFailed {
+        report: String,
+        message_id: String,
+    },
+    /// The turn ran past a per-turn limit or the agent repeated itself
+    /// (REQ-BED-038). Raised by the executor in place of an LLM request.
+    TurnBudgetExceeded {
+        reason: String,
+    },
 
     // LLM events
     LlmResponse {
@@ -53,6 +89,15 @@ pub enum Event {
     RetryTimeout {
         attempt: u32,
     },
+    /// Every council model has answered or failed (REQ-BED-058)
+    CouncilResponses {
+        candidates: Vec<CouncilCandidate>,
+    },
+    /// User picked the council answer to continue from
+    /// (POST /api/conversations/{id}/council/choose)
+    CouncilChoice {
+        candidate_id: String,
+    },
 
     // Tool events
     ToolComplete {
@@ -63,6 +108,11 @@ pub enum Event {
     ToolAborted {
         tool_use_id: String,
     },
+    /// User dropped one queued tool of the round (REQ-BED-055); the rest
+    /// of the round carries on.
+    CancelSpecificTool {
+        tool_use_id: String,
+    },
 
     // Sub-agent events
     /// `spawn_agents` tool completed, sub-agents are now running
@@ -103,6 +153,24 @@ pub enum Event {
         answers: HashMap<String, String>,
         annotations: Option<HashMap<String, QuestionAnnotation>>,
     },
+    /// User answered an `ask_user` call (POST /api/conversations/{id}/answer,
+    /// REQ-AUQ-009)
+    UserInputAnswer {
+        answer: String,
+    },
+
+    // Patch review events (REQ-PATCH-010)
+    /// Patch tool planned an edit in review mode instead of writing it
+    PatchStaged {
+        tool_use_id: String,
+        patch: StagedPatch,
+    },
+    /// User applied or rejected the pending patch
+    /// (POST /api/conversations/{id}/pending-patches/{tool_use_id}/apply|reject)
+    PatchReviewResponse {
+        tool_use_id: String,
+        approved: bool,
+    },
 
     /// Grace turn exhausted -- sub-agent used its extra turn without calling `submit_result`.
     /// The executor extracted the last assistant text (if any) before sending this event.
@@ -143,11 +211,19 @@ impl Event {
         match self {
             Event::UserMessage { .. } => "UserMessage",
             Event::UserCancel { .. } => "UserCancel",
+            Event::UserSteer { .. } => "UserSteer",
+            Event::UserCouncilMessage { .. } => "UserCouncilMessage",
+            Event::UserRetry => "UserRetry",
+            Event::VerifyFailed { .. } => "VerifyFailed",
+            Event::TurnBudgetExceeded { .. } => "TurnBudgetExceeded",
             Event::LlmResponse { .. } => "LlmResponse",
             Event::LlmError { .. } => "LlmError",
             Event::RetryTimeout { .. } => "RetryTimeout",
+            Event::CouncilResponses { .. } => "CouncilResponses",
+            Event::CouncilChoice { .. } => "CouncilChoice",
             Event::ToolComplete { .. } => "ToolComplete",
             Event::ToolAborted { .. } => "ToolAborted",
+            Event::CancelSpecificTool { .. } => "CancelSpecificTool",
             Event::SpawnAgentsComplete { .. } => "SpawnAgentsComplete",
             Event::SubAgentResult { .. } => "SubAgentResult",
             Event::ContinuationResponse { .. } => "ContinuationResponse",
@@ -155,6 +231,9 @@ impl Event {
             Event::UserTriggerContinuation => "UserTriggerContinuation",
             Event::TaskApprovalResponse { .. } => "TaskApprovalResponse",
             Event::UserQuestionResponse { .. } => "UserQuestionResponse",
+            Event::UserInputAnswer { .. } => "UserInputAnswer",
+            Event::PatchStaged { .. } => "PatchStaged",
+            Event::PatchReviewResponse { .. } => "PatchReviewResponse",
             Event::GraceTurnExhausted { .. } => "GraceTurnExhausted",
             Event::CredentialBecameAvailable => "CredentialBecameAvailable",
             Event::CredentialHelperFailed { .. } => "CredentialHelperFailed",
@@ -204,6 +283,9 @@ pub enum CoreEvent {
     ToolAborted {
         tool_use_id: String,
     },
+    CancelSpecificTool {
+        tool_use_id: String,
+    },
     SpawnAgentsComplete {
         tool_use_id: String,
         result: ToolResult,
@@ -226,6 +308,33 @@ pub enum CoreEvent {
 #[derive(Debug, Clone)]
 #[allow(dead_code)] // Variants used by split transition functions
 pub enum ParentOnlyEvent {
+    UserSteer {
+        text: String,
+        message_id: String,
+    },
+    UserCouncilMessage {
+        text: String,
+        llm_text: Option<String>,
+        images: Vec<ImageData>,
+        message_id: String,
+        user_agent: Option<String>,
+        skill_invocation: Option<crate::skills::SkillInvocation>,
+        models: Vec<String>,
+    },
+    UserRetry,
+    VerifyFailed {
+        report: String,
+        message_id: String,
+    },
+    TurnBudgetExceeded {
+        reason: String,
+    },
+    CouncilResponses {
+        candidates: Vec<CouncilCandidate>,
+    },
+    CouncilChoice {
+        candidate_id: String,
+    },
     TaskApprovalResponse {
         outcome: TaskApprovalOutcome,
     },
@@ -233,6 +342,17 @@ pub enum ParentOnlyEvent {
         answers: HashMap<String, String>,
         annotations: Option<HashMap<String, QuestionAnnotation>>,
     },
+    UserInputAnswer {
+        answer: String,
+    },
+    PatchStaged {
+        tool_use_id: String,
+        patch: StagedPatch,
+    },
+    PatchReviewResponse {
+        tool_use_id: String,
+        approved: bool,
+    },
     CredentialBecameAvailable,
     CredentialHelperFailed {
         message: String,
@@ -348,6 +468,11 @@ impl TryFrom<Event> for ParentEvent {
             Event::ToolAborted { tool_use_id } => {
                 Ok(ParentEvent::Core(CoreEvent::ToolAborted { tool_use_id }))
             }
+            Event::CancelSpecificTool { tool_use_id } => {
+                Ok(ParentEvent::Core(CoreEvent::CancelSpecificTool {
+                    tool_use_id,
+                }))
+            }
             Event::SpawnAgentsComplete {
                 tool_use_id,
                 result,
@@ -375,6 +500,51 @@ impl TryFrom<Event> for ParentEvent {
                 Ok(ParentEvent::Core(CoreEvent::UserTriggerContinuation))
             }
             // Parent-only events
+            Event::UserSteer { text, message_id } => {
+                Ok(ParentEvent::Parent(ParentOnlyEvent::UserSteer {
+                    text,
+                    message_id,
+                }))
+            }
+            Event::UserCouncilMessage {
+                text,
+                llm_text,
+                images,
+                message_id,
+                user_agent,
+                skill_invocation,
+                models,
+            } => Ok(ParentEvent::Parent(ParentOnlyEvent::UserCouncilMessage {
+                text,
+                llm_text,
+                images,
+                message_id,
+                user_agent,
+                skill_invocation,
+                models,
+            })),
+            Event::UserRetry => Ok(ParentEvent::Parent(ParentOnlyEvent::UserRetry)),
+            Event::VerifyFailed { report, message_id } => {
+                Ok(ParentEvent::Parent(ParentOnlyEvent::VerifyFailed {
+                    report,
+                    message_id,
+                }))
+            }
+            Event::TurnBudgetExceeded { reason } => {
+                Ok(ParentEvent::Parent(ParentOnlyEvent::TurnBudgetExceeded {
+                    reason,
+                }))
+            }
+            Event::CouncilResponses { candidates } => {
+                Ok(ParentEvent::Parent(ParentOnlyEvent::CouncilResponses {
+                    candidates,
+                }))
+            }
+            Event::CouncilChoice { candidate_id } => {
+                Ok(ParentEvent::Parent(ParentOnlyEvent::CouncilChoice {
+                    candidate_id,
+                }))
+            }
             Event::TaskApprovalResponse { outcome } => {
                 Ok(ParentEvent::Parent(ParentOnlyEvent::TaskApprovalResponse {
                     outcome,
@@ -387,6 +557,24 @@ impl TryFrom<Event> for ParentEvent {
                 answers,
                 annotations,
             })),
+            Event::UserInputAnswer { answer } => {
+                Ok(ParentEvent::Parent(ParentOnlyEvent::UserInputAnswer {
+                    answer,
+                }))
+            }
+            Event::PatchStaged { tool_use_id, patch } => {
+                Ok(ParentEvent::Parent(ParentOnlyEvent::PatchStaged {
+                    tool_use_id,
+                    patch,
+                }))
+            }
+            Event::PatchReviewResponse {
+                tool_use_id,
+                approved,
+            } => Ok(ParentEvent::Parent(ParentOnlyEvent::PatchReviewResponse {
+                tool_use_id,
+                approved,
+            })),
             Event::CredentialBecameAvailable => Ok(ParentEvent::Parent(
                 ParentOnlyEvent::CredentialBecameAvailable,
             )),
@@ -469,6 +657,11 @@ impl TryFrom<Event> for SubAgentEvent {
             Event::ToolAborted { tool_use_id } => {
                 Ok(SubAgentEvent::Core(CoreEvent::ToolAborted { tool_use_id }))
             }
+            Event::CancelSpecificTool { tool_use_id } => {
+                Ok(SubAgentEvent::Core(CoreEvent::CancelSpecificTool {
+                    tool_use_id,
+                }))
+            }
             Event::SpawnAgentsComplete {
                 tool_use_id,
                 result,
@@ -500,8 +693,18 @@ impl TryFrom<Event> for SubAgentEvent {
                 SubAgentOnlyEvent::GraceTurnExhausted { result },
             )),
             // Parent-only events are invalid for sub-agent
-            Event::TaskApprovalResponse { .. }
+            Event::UserSteer { .. }
+            | Event::UserCouncilMessage { .. }
+            | Event::UserRetry
+            | Event::VerifyFailed { .. }
+            | Event::TurnBudgetExceeded { .. }
+            | Event::CouncilResponses { .. }
+            | Event::CouncilChoice { .. }
+            | Event::TaskApprovalResponse { .. }
             | Event::UserQuestionResponse { .. }
+            | Event::UserInputAnswer { .. }
+            | Event::PatchStaged { .. }
+            | Event::PatchReviewResponse { .. }
             | Event::CredentialBecameAvailable
             | Event::CredentialHelperFailed { .. }
             | Event::TaskResolved { .. } => Err(EventConversionError {
@@ -523,6 +726,7 @@ impl CoreEvent {
             CoreEvent::RetryTimeout { .. } => "RetryTimeout",
             CoreEvent::ToolComplete { .. } => "ToolComplete",
             CoreEvent::ToolAborted { .. } => "ToolAborted",
+            CoreEvent::CancelSpecificTool { .. } => "CancelSpecificTool",
             CoreEvent::SpawnAgentsComplete { .. } => "SpawnAgentsComplete",
             CoreEvent::SubAgentResult { .. } => "SubAgentResult",
             CoreEvent::ContinuationResponse { .. } => "ContinuationResponse",
@@ -538,8 +742,18 @@ impl ParentEvent {
         match self {
             ParentEvent::Core(e) => e.variant_name(),
             ParentEvent::Parent(e) => match e {
+                ParentOnlyEvent::UserSteer { .. } => "UserSteer",
+                ParentOnlyEvent::UserCouncilMessage { .. } => "UserCouncilMessage",
+                ParentOnlyEvent::UserRetry => "UserRetry",
+                ParentOnlyEvent::VerifyFailed { .. } => "VerifyFailed",
+                ParentOnlyEvent::TurnBudgetExceeded { .. } => "TurnBudgetExceeded",
+                ParentOnlyEvent::CouncilResponses { .. } => "CouncilResponses",
+                ParentOnlyEvent::CouncilChoice { .. } => "CouncilChoice",
                 ParentOnlyEvent::TaskApprovalResponse { .. } => "TaskApprovalResponse",
                 ParentOnlyEvent::UserQuestionResponse { .. } => "UserQuestionResponse",
+                ParentOnlyEvent::UserInputAnswer { .. } => "UserInputAnswer",
+                ParentOnlyEvent::PatchStaged { .. } => "PatchStaged",
+                ParentOnlyEvent::PatchReviewResponse { .. } => "PatchReviewResponse",
                 ParentOnlyEvent::CredentialBecameAvailable => "CredentialBecameAvailable",
                 ParentOnlyEvent::CredentialHelperFailed { .. } => "CredentialHelperFailed",
                 ParentOnlyEvent::TaskResolved { .. } => "TaskResolved",
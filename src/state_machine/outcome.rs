@@ -9,7 +9,8 @@
 
 use crate::db::ToolResult;
 use crate::llm::{ContentBlock, Usage};
-use crate::state_machine::state::{SubAgentOutcome, ToolCall};
+use crate::state_machine::state::{CouncilCandidate, SubAgentOutcome, ToolCall};
+use crate::tools::patch::StagedPatch;
 use std::time::Duration;
 
 // ============================================================================
@@ -26,6 +27,9 @@ pub enum LlmOutcome {
         end_turn: bool,
         usage: Usage,
     },
+    /// Every council model answered or failed, and at least one answered
+    /// (REQ-BED-058)
+    Council { candidates: Vec<CouncilCandidate> },
     /// Rate limited (429) — retryable
     RateLimited {
         #[allow(dead_code)] // Populated when provider sends Retry-After header
@@ -67,6 +71,11 @@ pub enum ToolExecOutcome {
     },
     /// Tool execution failed (e.g., unknown tool)
     Failed { tool_use_id: String, error: String },
+    /// Patch planned but held for user review (REQ-PATCH-010)
+    Staged {
+        tool_use_id: String,
+        patch: StagedPatch,
+    },
 }
 
 /// Why a tool was aborted. Set by the component requesting cancellation,
@@ -447,12 +447,18 @@ mod state_machine_props {
     // Property 4: TaskResolved only from Idle
     //
     // Non-Idle, non-Terminal states must not successfully transition to
-    // Terminal via TaskResolved.
+    // Terminal via TaskResolved. A prose question (REQ-BED-057) is Idle
+    // with the question on display, so it counts as Idle here.
     // ====================================================================
 
     fn arb_non_idle_non_terminal_state() -> impl Strategy<Value = ConvState> {
         arb_state().prop_filter("must be non-Idle and non-Terminal", |s| {
-            !matches!(s, ConvState::Idle | ConvState::Terminal)
+            !matches!(
+                s,
+                ConvState::Idle
+                    | ConvState::Terminal
+                    | ConvState::AwaitingUserInput { ask: None, .. }
+            )
         })
     }
 
@@ -761,6 +767,49 @@ mod random_walk {
                 }
             }
 
+            ConvState::AwaitingUserInput { ask: Some(_), .. } => match rng.gen_range(0..2) {
+                0 => Event::UserInputAnswer {
+                    answer: random_string(rng, 5),
+                },
+                _ => Event::UserCancel { reason: None },
+            },
+
+            ConvState::AwaitingUserGuidance { .. } | ConvState::AwaitingUserInput { .. } => {
+                match rng.gen_range(0..2) {
+                    0 => Event::UserMessage {
+                        text: random_string(rng, 10),
+                        llm_text: None,
+                        images: vec![],
+                        message_id: uuid::Uuid::new_v4().to_string(),
+                        user_agent: None,
+                        skill_invocation: None,
+                    },
+                    _ => Event::UserCancel { reason: None },
+                }
+            }
+
+            ConvState::AwaitingCouncilChoice { candidates } => {
+                let answered: Vec<_> = candidates.iter().filter(|c| c.error.is_none()).collect();
+                match rng.gen_range(0..2) {
+                    0 if !answered.is_empty() => Event::CouncilChoice {
+                        candidate_id: answered[rng.gen_range(0..answered.len())].id.clone(),
+                    },
+                    _ => Event::UserCancel { reason: None },
+                }
+            }
+
+            ConvState::AwaitingPatchReview { current_tool, .. } => match rng.gen_range(0..3) {
+                0 => Event::PatchReviewResponse {
+                    tool_use_id: current_tool.id.clone(),
+                    approved: true,
+                },
+                1 => Event::PatchReviewResponse {
+                    tool_use_id: current_tool.id.clone(),
+                    approved: false,
+                },
+                _ => Event::UserCancel { reason: None },
+            },
+
             // Terminal states -- events are absorbed, generate anything
             ConvState::ContextExhausted { .. }
             | ConvState::Terminal
@@ -10,6 +10,7 @@ use super::transition::*;
 use super::*;
 use crate::db::{ErrorKind, ToolResult};
 use crate::llm::{ContentBlock, Usage};
+use crate::tools::patch::StagedPatch;
 use proptest::prelude::*;
 use std::path::PathBuf;
 
@@ -236,6 +237,75 @@ fn arb_awaiting_user_response_state() -> impl Strategy<Value = ConvState> {
         })
 }
 
+fn arb_awaiting_user_guidance_state() -> impl Strategy<Value = ConvState> {
+    "[a-z ]{5,40}".prop_map(|reason| ConvState::AwaitingUserGuidance { reason })
+}
+
+fn arb_awaiting_user_input_state() -> impl Strategy<Value = ConvState> {
+    (
+        "[A-Z][a-z ]{5,40}\\?",
+        proptest::collection::vec("[a-z]{1,10}", 0..=3),
+        any::<bool>(),
+    )
+        .prop_map(|(question, options, asked)| ConvState::AwaitingUserInput {
+            question,
+            options,
+            ask: asked.then(|| PendingAsk {
+                tool_use_id: "ask-1".to_string(),
+                assistant_message: assistant_message_for_tools(&["ask-1"]),
+            }),
+        })
+}
+
+fn arb_council_candidate() -> impl Strategy<Value = CouncilCandidate> {
+    ("[a-z]{4,8}", any::<bool>()).prop_map(|(model, failed)| CouncilCandidate {
+        id: uuid::Uuid::new_v4().to_string(),
+        content: if failed {
+            vec![]
+        } else {
+            vec![ContentBlock::text(format!("answer from {model}"))]
+        },
+        model,
+        tool_calls: vec![],
+        usage: Usage::default(),
+        error: failed.then(|| "model failed".to_string()),
+    })
+}
+
+fn arb_awaiting_council_choice_state() -> impl Strategy<Value = ConvState> {
+    proptest::collection::vec(arb_council_candidate(), 2..=3)
+        .prop_map(|candidates| ConvState::AwaitingCouncilChoice { candidates })
+}
+
+fn arb_awaiting_patch_review_state() -> impl Strategy<Value = ConvState> {
+    arb_tool_executing_state().prop_map(|state| {
+        let ConvState::ToolExecuting {
+            current_tool,
+            remaining_tools,
+            completed_results,
+            pending_sub_agents,
+            assistant_message,
+        } = state
+        else {
+            unreachable!("arb_tool_executing_state yields ToolExecuting");
+        };
+        ConvState::AwaitingPatchReview {
+            patch: StagedPatch {
+                path: "/tmp/file.txt".to_string(),
+                diff: "-old\n+new\n".to_string(),
+                effects: vec![],
+                autogenerated_warning: false,
+                converted_line_endings: vec![],
+            },
+            current_tool,
+            remaining_tools,
+            completed_results,
+            pending_sub_agents,
+            assistant_message,
+        }
+    })
+}
+
 fn arb_awaiting_recovery_state() -> impl Strategy<Value = ConvState> {
     ("[a-zA-Z ]{1,30}", arb_error_kind()).prop_map(|(message, error_kind)| {
         ConvState::AwaitingRecovery {
@@ -257,6 +327,10 @@ pub(crate) fn arb_state() -> impl Strategy<Value = ConvState> {
         arb_context_exhausted_state(),
         arb_awaiting_task_approval_state(),
         arb_awaiting_user_response_state(),
+        arb_awaiting_user_guidance_state(),
+        arb_awaiting_user_input_state(),
+        arb_awaiting_council_choice_state(),
+        arb_awaiting_patch_review_state(),
         arb_terminal_state(),
         arb_awaiting_recovery_state(),
     ]
@@ -345,6 +419,27 @@ fn arb_user_question_response_event() -> impl Strategy<Value = Event> {
     })
 }
 
+fn arb_user_input_answer_event() -> impl Strategy<Value = Event> {
+    "[a-zA-Z ]{1,20}".prop_map(|answer| Event::UserInputAnswer { answer })
+}
+
+fn arb_user_council_message_event() -> impl Strategy<Value = Event> {
+    "[a-zA-Z ]{1,30}".prop_map(|text| Event::UserCouncilMessage {
+        text,
+        llm_text: None,
+        images: vec![],
+        message_id: uuid::Uuid::new_v4().to_string(),
+        user_agent: None,
+        skill_invocation: None,
+        models: vec!["model-a".to_string(), "model-b".to_string()],
+    })
+}
+
+fn arb_council_responses_event() -> impl Strategy<Value = Event> {
+    proptest::collection::vec(arb_council_candidate(), 2..=3)
+        .prop_map(|candidates| Event::CouncilResponses { candidates })
+}
+
 fn arb_task_approval_event() -> impl Strategy<Value = Event> {
     arb_task_approval_outcome().prop_map(|outcome| Event::TaskApprovalResponse { outcome })
 }
@@ -354,6 +449,31 @@ fn arb_grace_turn_exhausted_event() -> impl Strategy<Value = Event> {
         .prop_map(|result| Event::GraceTurnExhausted { result })
 }
 
+fn arb_spawn_agents_complete_event() -> impl Strategy<Value = Event> {
+    ("[a-z]{8}", proptest::collection::vec("[a-z]{8}", 1..3)).prop_map(|(tool_use_id, ids)| {
+        Event::SpawnAgentsComplete {
+            result: ToolResult::success(tool_use_id.clone(), "spawned".to_string()),
+            tool_use_id,
+            spawned: ids.iter().map(|id| pending_agent(id)).collect(),
+        }
+    })
+}
+
+fn arb_patch_staged_event() -> impl Strategy<Value = Event> {
+    "[a-z]{8}".prop_map(|tool_use_id| Event::PatchStaged {
+        tool_use_id,
+        patch: StagedPatch {
+            path: "/tmp/file.txt".to_string(),
+            diff: "-old\n+new\n".to_string(),
+            effects: vec![],
+            autogenerated_warning: false,
+            converted_line_endings: vec![],
+        },
+    })
+}
+
+/// Every `Event` variant. The transition table in `conformance.rs` is built
+/// from these, and fails if a variant is never generated.
 pub(crate) fn arb_event() -> impl Strategy<Value = Event> {
     prop_oneof![
         arb_user_message_event(),
@@ -362,9 +482,44 @@ pub(crate) fn arb_event() -> impl Strategy<Value = Event> {
         arb_llm_error_event(),
         arb_retry_timeout_event(),
         Just(Event::UserCancel { reason: None }),
+        "[a-zA-Z ]{1,30}".prop_map(|text| Event::UserSteer {
+            text,
+            message_id: uuid::Uuid::new_v4().to_string(),
+        }),
+        Just(Event::UserRetry),
+        "[a-zA-Z ]{1,30}".prop_map(|report| Event::VerifyFailed {
+            report,
+            message_id: uuid::Uuid::new_v4().to_string(),
+        }),
+        "[a-z ]{5,30}".prop_map(|reason| Event::TurnBudgetExceeded { reason }),
+        "[a-z]{8}".prop_map(|tool_use_id| Event::ToolAborted { tool_use_id }),
+        "[a-z]{8}".prop_map(|tool_use_id| Event::CancelSpecificTool { tool_use_id }),
+        arb_spawn_agents_complete_event(),
+        ("[a-z]{8}", arb_sub_agent_outcome())
+            .prop_map(|(agent_id, outcome)| Event::SubAgentResult { agent_id, outcome }),
+        "[a-zA-Z ]{1,50}".prop_map(|summary| Event::ContinuationResponse { summary }),
+        "[a-zA-Z ]{1,30}".prop_map(|error| Event::ContinuationFailed { error }),
+        Just(Event::UserTriggerContinuation),
         arb_task_approval_event(),
         arb_user_question_response_event(),
+        arb_user_input_answer_event(),
+        arb_user_council_message_event(),
+        arb_council_responses_event(),
+        "[a-z]{8}".prop_map(|candidate_id| Event::CouncilChoice { candidate_id }),
+        arb_patch_staged_event(),
+        ("[a-z]{8}", any::<bool>()).prop_map(|(tool_use_id, approved)| {
+            Event::PatchReviewResponse {
+                tool_use_id,
+                approved,
+            }
+        }),
         arb_grace_turn_exhausted_event(),
+        Just(Event::CredentialBecameAvailable),
+        "[a-zA-Z ]{1,30}".prop_map(|message| Event::CredentialHelperFailed { message }),
+        "[a-zA-Z ]{1,30}".prop_map(|system_message| Event::TaskResolved {
+            system_message,
+            repo_root: "/tmp".to_string(),
+        }),
     ]
 }
 
@@ -570,7 +725,9 @@ proptest! {
     #[test]
     fn prop_context_exhausted_stable(
         summary in "[a-zA-Z0-9 ]{0,50}",
-        event in arb_event().prop_filter("not UserMessage", |e| !matches!(e, Event::UserMessage { .. }))
+        event in arb_event().prop_filter("not a user message", |e| {
+            !matches!(e, Event::UserMessage { .. } | Event::UserCouncilMessage { .. })
+        })
     ) {
         let state = ConvState::ContextExhausted { summary: summary.clone() };
         let result = transition(&state, &test_context(), event);
@@ -636,8 +793,12 @@ proptest! {
     fn prop_state_changes_persist(state in arb_state(), event in arb_event()) {
         if let Ok(result) = transition(&state, &test_context(), event) {
             if result.new_state != state {
+                // ResolveTask writes Terminal itself once the task is resolved
                 prop_assert!(
-                    result.effects.iter().any(|e| matches!(e, Effect::PersistState)),
+                    result
+                        .effects
+                        .iter()
+                        .any(|e| matches!(e, Effect::PersistState | Effect::ResolveTask { .. })),
                     "State changed but no PersistState effect: {:?} -> {:?}",
                     state,
                     result.new_state
@@ -1010,6 +1171,15 @@ proptest! {
             "Should reject tool completion with wrong ID"
         );
     }
+
+    // Invariant 19: The persisted state JSON (conversations.state) parses
+    // back to the same state, payload included
+    #[test]
+    fn prop_state_json_round_trips(state in arb_state()) {
+        let json = serde_json::to_string(&state).unwrap();
+        let parsed: ConvState = serde_json::from_str(&json).unwrap();
+        prop_assert_eq!(parsed, state);
+    }
 }
 
 // ============================================================================
@@ -0,0 +1,98 @@
+//! Spotting a turn that ends on a question (REQ-BED-057)
+//!
+//! An agent that needs something from the user often asks in prose rather
+//! than through `ask_user_question`. When a text-only response ends with a
+//! question, the conversation goes to `AwaitingUserInput` instead of `Idle`,
+//! so the user can tell a finished turn from one waiting on their answer.
+//!
+//! The check looks only at the last line of the response. It is a cheap
+//! heuristic, pure so it can run inside the transition function; a wrong
+//! guess costs nothing, as `AwaitingUserInput` accepts everything `Idle`
+//! does.
+
+use crate::llm::ContentBlock;
+
+/// Longest question kept for display.
+const MAX_QUESTION_CHARS: usize = 300;
+
+/// The question a response ends with, if any.
+pub fn trailing_question(content: &[ContentBlock]) -> Option<String> {
+    let text = content.iter().rev().find_map(|block| match block {
+        ContentBlock::Text { text } if !text.trim().is_empty() => Some(text.trim_end()),
+        _ => None,
+    })?;
+    // A question at the end of a code block belongs to the code
+    if text.ends_with("```") {
+        return None;
+    }
+    let line = text
+        .lines()
+        .next_back()?
+        .trim_start_matches(['-', '*', '>', '#', ' '])
+        .trim_end_matches(['*', '_', ' ']);
+    let body = line.strip_suffix('?')?;
+
+    // The question is the last sentence of the line
+    let start = body
+        .char_indices()
+        .rfind(|&(i, c)| {
+            matches!(c, '.' | '!' | '?')
+                && body
+                    .get(i + 1..)
+                    .is_some_and(|after| after.starts_with(char::is_whitespace))
+        })
+        .map_or(0, |(i, _)| i + 1);
+    let question = line.get(start..)?.trim_start_matches(['*', '_', ' ']);
+    if question.len() < 2 {
+        return None;
+    }
+    Some(question.chars().take(MAX_QUESTION_CHARS).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ask(text: &str) -> Option<String> {
+        trailing_question(&[ContentBlock::text(text)])
+    }
+
+    #[test]
+    fn finds_the_closing_question() {
+        assert_eq!(
+            ask("I updated the parser.\n\nShould I also update the tests?\n").as_deref(),
+            Some("Should I also update the tests?")
+        );
+        assert_eq!(
+            ask("Done. Want me to push it? ").as_deref(),
+            Some("Want me to push it?")
+        );
+        assert_eq!(
+            ask("**Which database should I use?**").as_deref(),
+            Some("Which database should I use?")
+        );
+        assert_eq!(
+            ask("Options:\n- Should I keep the old API?").as_deref(),
+            Some("Should I keep the old API?")
+        );
+    }
+
+    #[test]
+    fn statements_and_code_are_not_questions() {
+        assert_eq!(ask("Done, the tests pass."), None);
+        assert_eq!(ask("Is it fixed? Yes, the tests pass."), None);
+        assert_eq!(ask("```python\nprint('why?')\n```"), None);
+        assert_eq!(ask("?"), None);
+        assert_eq!(trailing_question(&[]), None);
+    }
+
+    #[test]
+    fn looks_at_the_last_text_block() {
+        let content = [
+            ContentBlock::text("Should I start?"),
+            ContentBlock::text("Started and finished."),
+            ContentBlock::text("  "),
+        ];
+        assert_eq!(trailing_question(&content), None);
+    }
+}
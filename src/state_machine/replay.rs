@@ -0,0 +1,362 @@
+//! Replay recorded transitions through the pure state machine (REQ-API-017)
+//!
+//! The executor appends every transition it applies to the `transitions`
+//! table. Re-running those rows through `transition()` shows whether the
+//! current state machine still makes the same decisions, and where a stuck
+//! conversation first went somewhere unexpected.
+//!
+//! With the event log on (REQ-BED-054), [`replay_events`] re-drives the
+//! logged events instead, including the ones the runtime rejected.
+//!
+//! Comparison ignores values that are generated fresh on every run: any
+//! `message_id` key is stripped before states are compared, and effects are
+//! compared by variant only since their payloads embed those ids.
+
+use super::{transition, ConvContext, ConvState, Event};
+use crate::db::{EventDisposition, EventRecord, TransitionRecord};
+use serde::Serialize;
+use serde_json::Value;
+
+/// One recorded transition, decoded.
+#[derive(Debug, Clone)]
+pub struct ReplayStep {
+    pub event: Event,
+    pub old_state: ConvState,
+    pub new_state: ConvState,
+    /// Effect variant tags (`"persist_message"`, `"request_llm"`, ...) in order.
+    pub effects: Vec<String>,
+}
+
+impl TryFrom<&TransitionRecord> for ReplayStep {
+    type Error = serde_json::Error;
+
+    fn try_from(record: &TransitionRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            event: serde_json::from_value(record.event.clone())?,
+            old_state: serde_json::from_value(record.old_state.clone())?,
+            new_state: serde_json::from_value(record.new_state.clone())?,
+            effects: effect_types(&record.effects),
+        })
+    }
+}
+
+/// One logged event, decoded (REQ-BED-054).
+#[derive(Debug, Clone)]
+pub struct EventStep {
+    pub event: Event,
+    /// The state the event arrived in.
+    pub state: ConvState,
+    pub disposition: EventDisposition,
+}
+
+impl TryFrom<&EventRecord> for EventStep {
+    type Error = serde_json::Error;
+
+    fn try_from(record: &EventRecord) -> Result<Self, Self::Error> {
+        Ok(Self {
+            event: serde_json::from_value(record.event.clone())?,
+            state: serde_json::from_value(record.state.clone())?,
+            disposition: record.disposition,
+        })
+    }
+}
+
+/// Why replay stopped at a step.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DivergenceKind {
+    /// `transition()` now rejects an event the recording accepted.
+    Rejected { error: String },
+    /// The event leads to a different state than recorded.
+    StateMismatch { expected: Value, actual: Value },
+    /// `transition()` now accepts an event the recording rejected.
+    Accepted { new_state: Value },
+    /// Same state, but different effects were emitted.
+    EffectsMismatch {
+        expected: Vec<String>,
+        actual: Vec<String>,
+    },
+}
+
+/// First step where replay disagreed with the recording.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Divergence {
+    /// Index into the replayed steps.
+    pub index: usize,
+    pub event_type: &'static str,
+    #[serde(flatten)]
+    pub kind: DivergenceKind,
+}
+
+/// Result of [`replay`].
+#[derive(Debug, Default, Serialize)]
+pub struct ReplayReport {
+    /// Steps that reproduced exactly.
+    pub matched: usize,
+    /// Steps whose recorded `old_state` did not follow from the previous
+    /// step. State also changes outside the log (restart recovery, direct
+    /// DB updates), so replay adopts the recorded state and carries on.
+    pub resyncs: usize,
+    pub divergence: Option<Divergence>,
+}
+
+/// Re-run `steps` in order under `context`, stopping at the first
+/// divergence. Pure: no effects are executed.
+pub fn replay(context: &ConvContext, steps: &[ReplayStep]) -> ReplayReport {
+    let mut report = ReplayReport::default();
+    let mut current: Option<ConvState> = None;
+
+    for (index, step) in steps.iter().enumerate() {
+        let state = match current.take() {
+            Some(state) if normalized(&state) == normalized(&step.old_state) => state,
+            Some(_) => {
+                report.resyncs += 1;
+                step.old_state.clone()
+            }
+            None => step.old_state.clone(),
+        };
+        let event_type = step.event.variant_name();
+
+        let result = match transition(&state, context, step.event.clone()) {
+            Ok(result) => result,
+            Err(e) => {
+                report.divergence = Some(Divergence {
+                    index,
+                    event_type,
+                    kind: DivergenceKind::Rejected {
+                        error: e.to_string(),
+                    },
+                });
+                return report;
+            }
+        };
+
+        let expected = normalized(&step.new_state);
+        let actual = normalized(&result.new_state);
+        if expected != actual {
+            report.divergence = Some(Divergence {
+                index,
+                event_type,
+                kind: DivergenceKind::StateMismatch { expected, actual },
+            });
+            return report;
+        }
+
+        let effects = effect_types(&serde_json::to_value(&result.effects).unwrap_or_default());
+        if effects != step.effects {
+            report.divergence = Some(Divergence {
+                index,
+                event_type,
+                kind: DivergenceKind::EffectsMismatch {
+                    expected: step.effects.clone(),
+                    actual: effects,
+                },
+            });
+            return report;
+        }
+
+        report.matched += 1;
+        current = Some(result.new_state);
+    }
+    report
+}
+
+/// Re-drive logged events through `transition()` under `context`, stopping
+/// at the first that now fares differently: applied events must still
+/// apply and rejected ones must still be rejected. Buffered and dropped
+/// events never reached the state machine and are skipped. The log holds
+/// no resulting states, so when an event arrived in a state other than the
+/// one the previous step produced, replay adopts the logged state and
+/// counts a resync; [`replay`] compares states step by step.
+pub fn replay_events(context: &ConvContext, steps: &[EventStep]) -> ReplayReport {
+    let mut report = ReplayReport::default();
+    let mut current: Option<ConvState> = None;
+
+    for (index, step) in steps.iter().enumerate() {
+        if !matches!(
+            step.disposition,
+            EventDisposition::Applied | EventDisposition::Rejected
+        ) {
+            continue;
+        }
+        let state = match current.take() {
+            Some(state) if normalized(&state) == normalized(&step.state) => state,
+            Some(_) => {
+                report.resyncs += 1;
+                step.state.clone()
+            }
+            None => step.state.clone(),
+        };
+
+        let kind = match (
+            transition(&state, context, step.event.clone()),
+            step.disposition,
+        ) {
+            (Ok(result), EventDisposition::Applied) => {
+                report.matched += 1;
+                current = Some(result.new_state);
+                continue;
+            }
+            (Err(_), EventDisposition::Rejected) => {
+                report.matched += 1;
+                current = Some(state);
+                continue;
+            }
+            (Err(e), _) => DivergenceKind::Rejected {
+                error: e.to_string(),
+            },
+            (Ok(result), _) => DivergenceKind::Accepted {
+                new_state: normalized(&result.new_state),
+            },
+        };
+        report.divergence = Some(Divergence {
+            index,
+            event_type: step.event.variant_name(),
+            kind,
+        });
+        return report;
+    }
+    report
+}
+
+/// The `type` tag of each effect in a serialized effect list.
+fn effect_types(effects: &Value) -> Vec<String> {
+    effects
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|effect| effect.get("type").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect()
+}
+
+/// `state` as JSON with every `message_id` key removed.
+fn normalized(state: &ConvState) -> Value {
+    let mut value = serde_json::to_value(state).unwrap_or_default();
+    strip_message_ids(&mut value);
+    value
+}
+
+fn strip_message_ids(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.remove("message_id");
+            map.values_mut().for_each(strip_message_ids);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_message_ids),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn context() -> ConvContext {
+        ConvContext::new("conv", PathBuf::from("/tmp"), "test-model", 200_000)
+    }
+
+    fn user_message(text: &str) -> Event {
+        Event::UserMessage {
+            text: text.to_string(),
+            llm_text: None,
+            images: vec![],
+            message_id: uuid::Uuid::new_v4().to_string(),
+            user_agent: None,
+            skill_invocation: None,
+        }
+    }
+
+    /// Record steps the way the executor does: capture, then decode.
+    fn record(state: &ConvState, event: &Event) -> (ReplayStep, ConvState) {
+        let result = transition(state, &context(), event.clone()).unwrap();
+        let record =
+            TransitionRecord::capture("conv", event, state, &result.new_state, &result.effects);
+        (ReplayStep::try_from(&record).unwrap(), result.new_state)
+    }
+
+    #[test]
+    fn recorded_steps_replay_cleanly() {
+        let (first, state) = record(&ConvState::Idle, &user_message("hello"));
+        let (second, _) = record(&state, &Event::UserCancel { reason: None });
+
+        let report = replay(&context(), &[first, second]);
+        assert_eq!(report.matched, 2);
+        assert_eq!(report.resyncs, 0);
+        assert_eq!(report.divergence, None);
+    }
+
+    #[test]
+    fn reports_first_divergence() {
+        let (mut step, _) = record(&ConvState::Idle, &user_message("hello"));
+        step.new_state = ConvState::Idle;
+
+        let report = replay(&context(), &[step]);
+        let divergence = report.divergence.expect("state mismatch");
+        assert_eq!(divergence.index, 0);
+        assert_eq!(divergence.event_type, "UserMessage");
+        assert!(matches!(
+            divergence.kind,
+            DivergenceKind::StateMismatch { .. }
+        ));
+    }
+
+    fn logged(state: &ConvState, event: &Event, disposition: EventDisposition) -> EventStep {
+        let record = EventRecord::capture("conv", event, state, disposition, None);
+        EventStep::try_from(&record).unwrap()
+    }
+
+    #[test]
+    fn logged_events_replay_with_their_rejections() {
+        let hello = user_message("hello");
+        let state = transition(&ConvState::Idle, &context(), hello.clone())
+            .unwrap()
+            .new_state;
+        let idle = ConvState::Idle;
+        let retry = Event::RetryTimeout { attempt: 1 };
+        let steps = [
+            logged(&idle, &hello, EventDisposition::Applied),
+            logged(
+                &state,
+                &Event::UserCancel { reason: None },
+                EventDisposition::Applied,
+            ),
+            logged(&idle, &retry, EventDisposition::Rejected),
+            logged(&idle, &retry, EventDisposition::Buffered),
+        ];
+        let report = replay_events(&context(), &steps);
+        assert_eq!(report.matched, 3);
+        assert_eq!(report.resyncs, 0);
+        assert_eq!(report.divergence, None);
+
+        // A logged rejection the state machine now accepts
+        let cancel = logged(
+            &state,
+            &Event::UserCancel { reason: None },
+            EventDisposition::Rejected,
+        );
+        let report = replay_events(&context(), &[cancel]);
+        let divergence = report.divergence.expect("accepted");
+        assert_eq!(divergence.event_type, "UserCancel");
+        assert!(matches!(divergence.kind, DivergenceKind::Accepted { .. }));
+    }
+
+    #[test]
+    fn rejected_event_and_resync_are_reported() {
+        let (first, _) = record(&ConvState::Idle, &user_message("hello"));
+        // Recorded from Idle again: the log skipped whatever happened between.
+        let (second, _) = record(&ConvState::Idle, &user_message("again"));
+        let report = replay(&context(), &[first.clone(), second]);
+        assert_eq!(report.matched, 2);
+        assert_eq!(report.resyncs, 1);
+
+        let mut rejected = first;
+        rejected.old_state = ConvState::LlmRequesting { attempt: 1 };
+        let report = replay(&context(), &[rejected]);
+        assert!(matches!(
+            report.divergence.map(|d| d.kind),
+            Some(DivergenceKind::Rejected { .. })
+        ));
+    }
+}
@@ -1,8 +1,9 @@
 //! Conversation state types
 
+use super::budget::TurnBudget;
 use crate::db::{ErrorKind, ToolResult, UsageData};
 use crate::llm::ContentBlock;
-use crate::tools::patch::types::PatchInput;
+use crate::tools::patch::types::{PatchInput, StagedPatch};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::path::PathBuf;
@@ -67,6 +68,9 @@ pub struct SubAgentTask {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SpawnAgentsInput {
     pub tasks: Vec<SubAgentTask>,
+    /// Send the sub-agents' first requests as one provider batch (REQ-SA-009)
+    #[serde(default)]
+    pub batch: bool,
 }
 
 /// Input for the `submit_result` tool (sub-agent only)
@@ -126,6 +130,24 @@ pub struct AskUserQuestionInput {
     pub metadata: Option<QuestionMetadata>,
 }
 
+/// Input for the `ask_user` tool (REQ-AUQ-009): one question, optionally
+/// with choices. The answer comes back as the call's result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AskUserInput {
+    pub question: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+}
+
+/// An `ask_user` call waiting for its answer (REQ-AUQ-009). The response
+/// that made it is held here and persisted with the answer as one tool
+/// round.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingAsk {
+    pub tool_use_id: String,
+    pub assistant_message: AssistantMessage,
+}
+
 /// Optional metadata for an `ask_user_question` invocation
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct QuestionMetadata {
@@ -147,6 +169,7 @@ pub enum ToolInput {
     SubmitError(SubmitErrorInput),
     ProposeTask(ProposeTaskInput),
     AskUserQuestion(AskUserQuestionInput),
+    AskUser(AskUserInput),
     /// Fallback for unknown tools or parsing failures
     Unknown {
         name: String,
@@ -168,6 +191,7 @@ impl ToolInput {
             ToolInput::SubmitError(_) => "submit_error",
             ToolInput::ProposeTask(_) => "propose_task",
             ToolInput::AskUserQuestion(_) => "ask_user_question",
+            ToolInput::AskUser(_) => "ask_user",
             ToolInput::Unknown { name, .. } => name,
         }
     }
@@ -190,6 +214,7 @@ impl ToolInput {
             ToolInput::SubmitError(input) => serde_json::to_value(input).unwrap_or(Value::Null),
             ToolInput::ProposeTask(input) => serde_json::to_value(input).unwrap_or(Value::Null),
             ToolInput::AskUserQuestion(input) => serde_json::to_value(input).unwrap_or(Value::Null),
+            ToolInput::AskUser(input) => serde_json::to_value(input).unwrap_or(Value::Null),
             ToolInput::Unknown { input, .. } => input.clone(),
         }
     }
@@ -267,6 +292,13 @@ impl ToolInput {
                 },
                 ToolInput::AskUserQuestion,
             ),
+            "ask_user" => serde_json::from_value(value.clone()).map_or_else(
+                |_| ToolInput::Unknown {
+                    name: name.to_string(),
+                    input: value,
+                },
+                ToolInput::AskUser,
+            ),
             _ => ToolInput::Unknown {
                 name: name.to_string(),
                 input: value,
@@ -339,6 +371,22 @@ impl AssistantMessage {
     }
 }
 
+/// One model's answer to a council prompt (REQ-BED-058). Failed models are
+/// kept with `error` set so the user sees who dropped out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CouncilCandidate {
+    pub id: String,
+    pub model: String,
+    #[serde(default)]
+    pub content: Vec<ContentBlock>,
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(default)]
+    pub usage: UsageData,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 // ============================================================================
 // Conversation State
 // ============================================================================
@@ -466,6 +514,50 @@ pub enum ConvState {
         tool_use_id: String,
     },
 
+    /// Paused mid-turn because the agent hit a per-turn limit or kept
+    /// repeating the same tool call (REQ-BED-038). A user message resumes
+    /// with a fresh budget; cancel returns to `Idle`.
+    AwaitingUserGuidance {
+        /// Which limit was hit, for display
+        reason: String,
+    },
+
+    /// The agent asked the user one question: in prose at the end of its
+    /// turn (REQ-BED-057), where the state behaves as `Idle` and cancel
+    /// dismisses the question, or with `ask_user` (REQ-AUQ-009), where the
+    /// answer resumes the turn as the call's result.
+    AwaitingUserInput {
+        /// The question, for display
+        question: String,
+        /// Choices offered through `ask_user`; a free answer is also taken
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        options: Vec<String>,
+        /// The `ask_user` call to answer; `None` for a question in prose
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ask: Option<PendingAsk>,
+    },
+
+    /// Several models answered the same prompt and the user picks the one
+    /// the conversation continues from (REQ-BED-058).
+    AwaitingCouncilChoice { candidates: Vec<CouncilCandidate> },
+
+    /// A patch was planned in review mode and waits for the user to apply
+    /// or reject it (REQ-PATCH-010). Carries the `ToolExecuting` fields so
+    /// the tool round resumes exactly where it paused.
+    AwaitingPatchReview {
+        /// The staged edit, not yet written
+        patch: StagedPatch,
+        /// The patch tool call awaiting review
+        current_tool: ToolCall,
+        remaining_tools: Vec<ToolCall>,
+        #[serde(default)]
+        completed_results: Vec<ToolResult>,
+        #[serde(default)]
+        pending_sub_agents: Vec<PendingSubAgent>,
+        #[serde(default)]
+        assistant_message: AssistantMessage,
+    },
+
     /// Context window exhausted - conversation is read-only
     ContextExhausted {
         /// The continuation summary
@@ -557,6 +649,25 @@ pub enum ParentState {
         questions: Vec<UserQuestion>,
         tool_use_id: String,
     },
+    AwaitingUserGuidance {
+        reason: String,
+    },
+    AwaitingUserInput {
+        question: String,
+        options: Vec<String>,
+        ask: Option<PendingAsk>,
+    },
+    AwaitingCouncilChoice {
+        candidates: Vec<CouncilCandidate>,
+    },
+    AwaitingPatchReview {
+        patch: StagedPatch,
+        current_tool: ToolCall,
+        remaining_tools: Vec<ToolCall>,
+        completed_results: Vec<ToolResult>,
+        pending_sub_agents: Vec<PendingSubAgent>,
+        assistant_message: AssistantMessage,
+    },
     ContextExhausted {
         summary: String,
     },
@@ -611,6 +722,36 @@ impl From<ParentState> for ConvState {
                 questions,
                 tool_use_id,
             },
+            ParentState::AwaitingUserGuidance { reason } => {
+                ConvState::AwaitingUserGuidance { reason }
+            }
+            ParentState::AwaitingUserInput {
+                question,
+                options,
+                ask,
+            } => ConvState::AwaitingUserInput {
+                question,
+                options,
+                ask,
+            },
+            ParentState::AwaitingCouncilChoice { candidates } => {
+                ConvState::AwaitingCouncilChoice { candidates }
+            }
+            ParentState::AwaitingPatchReview {
+                patch,
+                current_tool,
+                remaining_tools,
+                completed_results,
+                pending_sub_agents,
+                assistant_message,
+            } => ConvState::AwaitingPatchReview {
+                patch,
+                current_tool,
+                remaining_tools,
+                completed_results,
+                pending_sub_agents,
+                assistant_message,
+            },
             ParentState::ContextExhausted { summary } => ConvState::ContextExhausted { summary },
             ParentState::Terminal => ConvState::Terminal,
         }
@@ -714,6 +855,7 @@ impl std::error::Error for StateConversionError {}
 impl TryFrom<ConvState> for ParentState {
     type Error = StateConversionError;
 
+    #[allow(clippy::too_many_lines)] // one arm per state
     fn try_from(cs: ConvState) -> Result<Self, Self::Error> {
         match cs {
             // Core states
@@ -803,6 +945,36 @@ impl TryFrom<ConvState> for ParentState {
                 questions,
                 tool_use_id,
             }),
+            ConvState::AwaitingUserGuidance { reason } => {
+                Ok(ParentState::AwaitingUserGuidance { reason })
+            }
+            ConvState::AwaitingUserInput {
+                question,
+                options,
+                ask,
+            } => Ok(ParentState::AwaitingUserInput {
+                question,
+                options,
+                ask,
+            }),
+            ConvState::AwaitingCouncilChoice { candidates } => {
+                Ok(ParentState::AwaitingCouncilChoice { candidates })
+            }
+            ConvState::AwaitingPatchReview {
+                patch,
+                current_tool,
+                remaining_tools,
+                completed_results,
+                pending_sub_agents,
+                assistant_message,
+            } => Ok(ParentState::AwaitingPatchReview {
+                patch,
+                current_tool,
+                remaining_tools,
+                completed_results,
+                pending_sub_agents,
+                assistant_message,
+            }),
             ConvState::ContextExhausted { summary } => {
                 Ok(ParentState::ContextExhausted { summary })
             }
@@ -891,6 +1063,10 @@ impl TryFrom<ConvState> for SubAgentState {
             ConvState::AwaitingRecovery { .. }
             | ConvState::AwaitingTaskApproval { .. }
             | ConvState::AwaitingUserResponse { .. }
+            | ConvState::AwaitingUserGuidance { .. }
+            | ConvState::AwaitingUserInput { .. }
+            | ConvState::AwaitingCouncilChoice { .. }
+            | ConvState::AwaitingPatchReview { .. }
             | ConvState::ContextExhausted { .. }
             | ConvState::Terminal => Err(StateConversionError {
                 from_variant: cs.variant_name(),
@@ -924,6 +1100,10 @@ impl ParentState {
             ParentState::AwaitingRecovery { .. } => "AwaitingRecovery",
             ParentState::AwaitingTaskApproval { .. } => "AwaitingTaskApproval",
             ParentState::AwaitingUserResponse { .. } => "AwaitingUserResponse",
+            ParentState::AwaitingUserGuidance { .. } => "AwaitingUserGuidance",
+            ParentState::AwaitingUserInput { .. } => "AwaitingUserInput",
+            ParentState::AwaitingCouncilChoice { .. } => "AwaitingCouncilChoice",
+            ParentState::AwaitingPatchReview { .. } => "AwaitingPatchReview",
             ParentState::ContextExhausted { .. } => "ContextExhausted",
             ParentState::Terminal => "Terminal",
         }
@@ -989,7 +1169,9 @@ pub enum DisplayState {
     Error,
     /// Conversation cannot continue — context exhausted, completed, or failed (gray dot, static)
     Terminal,
-    /// Awaiting user action on a proposed task plan (REQ-BED-028)
+    /// Awaiting user action: a proposed task plan (REQ-BED-028), questions,
+    /// guidance after a paused turn (REQ-BED-038), or a staged patch
+    /// (REQ-PATCH-010)
     AwaitingApproval,
 }
 
@@ -1042,6 +1224,16 @@ impl ConvState {
         )
     }
 
+    /// Nothing is running and a message starts the next turn: `Idle`, or
+    /// `AwaitingUserInput` for a question asked in prose, which is `Idle`
+    /// with the question on display (REQ-BED-057).
+    pub fn is_idle(&self) -> bool {
+        matches!(
+            self,
+            ConvState::Idle | ConvState::AwaitingUserInput { ask: None, .. }
+        )
+    }
+
     /// Mirror of the Allium-defined `is_busy` derivation in
     /// `specs/bedrock/bedrock.allium`:
     ///
@@ -1085,6 +1277,10 @@ impl ConvState {
             ConvState::ContextExhausted { .. } => "ContextExhausted",
             ConvState::AwaitingTaskApproval { .. } => "AwaitingTaskApproval",
             ConvState::AwaitingUserResponse { .. } => "AwaitingUserResponse",
+            ConvState::AwaitingUserGuidance { .. } => "AwaitingUserGuidance",
+            ConvState::AwaitingUserInput { .. } => "AwaitingUserInput",
+            ConvState::AwaitingCouncilChoice { .. } => "AwaitingCouncilChoice",
+            ConvState::AwaitingPatchReview { .. } => "AwaitingPatchReview",
             ConvState::Terminal => "Terminal",
         }
     }
@@ -1117,7 +1313,11 @@ impl ConvState {
             | ConvState::AwaitingRecovery { .. }
             | ConvState::AwaitingContinuation { .. }
             | ConvState::AwaitingTaskApproval { .. }
-            | ConvState::AwaitingUserResponse { .. } => StepResult::Continue,
+            | ConvState::AwaitingUserResponse { .. }
+            | ConvState::AwaitingUserGuidance { .. }
+            | ConvState::AwaitingUserInput { .. }
+            | ConvState::AwaitingCouncilChoice { .. }
+            | ConvState::AwaitingPatchReview { .. } => StepResult::Continue,
         }
     }
 
@@ -1127,9 +1327,12 @@ impl ConvState {
         match self {
             ConvState::Idle => DisplayState::Idle,
             ConvState::Error { .. } => DisplayState::Error,
-            ConvState::AwaitingTaskApproval { .. } | ConvState::AwaitingUserResponse { .. } => {
-                DisplayState::AwaitingApproval
-            }
+            ConvState::AwaitingTaskApproval { .. }
+            | ConvState::AwaitingUserResponse { .. }
+            | ConvState::AwaitingUserGuidance { .. }
+            | ConvState::AwaitingUserInput { .. }
+            | ConvState::AwaitingCouncilChoice { .. }
+            | ConvState::AwaitingPatchReview { .. } => DisplayState::AwaitingApproval,
             ConvState::ContextExhausted { .. }
             | ConvState::Completed { .. }
             | ConvState::Failed { .. }
@@ -1206,6 +1409,17 @@ pub struct SubAgentSpec {
     pub model_id: String,
     /// Maximum LLM turns before forced completion
     pub max_turns: u32,
+    /// First-turn batch shared with sibling sub-agents (REQ-SA-009)
+    pub batch: Option<SubAgentBatch>,
+}
+
+/// Identifies the first-turn batch a group of sub-agents shares
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubAgentBatch {
+    /// Unique per `spawn_agents` call
+    pub key: String,
+    /// Number of sub-agents in the group
+    pub size: usize,
 }
 
 /// How a conversation handles approaching context limits
@@ -1228,7 +1442,8 @@ pub enum ModeKind {
     Branch,
 }
 
-/// Context for a conversation (immutable configuration)
+/// Context for a conversation. Configuration is fixed for the runtime's
+/// life; only `turn_budget` counts up as the turn runs.
 #[derive(Debug, Clone)]
 pub struct ConvContext {
     pub conversation_id: String,
@@ -1253,6 +1468,12 @@ pub struct ConvContext {
     pub desired_base_branch: Option<String>,
     /// Mode category for transition-level guards (defense-in-depth behind tool registry)
     pub mode: ModeKind,
+    /// Per-turn limits and usage so far (REQ-BED-038). Unlimited unless the
+    /// runtime configures it; sub-agents rely on `max_turns` instead.
+    pub turn_budget: TurnBudget,
+    /// Stage patches for the user to apply or reject (REQ-PATCH-010).
+    /// Always off for sub-agents, which have no one to ask.
+    pub review_patches: bool,
 }
 
 /// Default context window for unknown models (conservative)
@@ -1278,6 +1499,8 @@ impl ConvContext {
             max_turns: 0,
             desired_base_branch: None,
             mode: ModeKind::Managed,
+            turn_budget: TurnBudget::default(),
+            review_patches: false,
         }
     }
 
@@ -1301,6 +1524,8 @@ impl ConvContext {
             max_turns: 0,
             desired_base_branch: None,
             mode: ModeKind::Managed,
+            turn_budget: TurnBudget::default(),
+            review_patches: false,
         }
     }
 }
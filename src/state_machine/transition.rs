@@ -14,9 +14,11 @@
 use super::effect::{compute_bash_display_data, CheckpointData};
 use super::event::{CoreEvent, ParentEvent, ParentOnlyEvent, SubAgentEvent, SubAgentOnlyEvent};
 use super::outcome::{EffectOutcome, InvalidOutcome, LlmOutcome, PersistOutcome, ToolExecOutcome};
+use super::question;
 use super::state::{
-    AssistantMessage, ContextExhaustionBehavior, CoreState, ModeKind, ParentState, PendingSubAgent,
-    RecoveryKind, SubAgentResult, SubAgentState, TaskApprovalOutcome, ToolCall, ToolInput,
+    AssistantMessage, ContextExhaustionBehavior, CoreState, ModeKind, ParentState, PendingAsk,
+    PendingSubAgent, RecoveryKind, SubAgentResult, SubAgentState, TaskApprovalOutcome, ToolCall,
+    ToolInput,
 };
 use super::{ConvContext, ConvState, Effect, Event};
 use crate::db::{ErrorKind, ToolResult, UsageData};
@@ -24,7 +26,7 @@ use serde_json::json;
 use std::time::Duration;
 use thiserror::Error;
 
-const MAX_RETRY_ATTEMPTS: u32 = 3;
+pub(crate) const MAX_RETRY_ATTEMPTS: u32 = 3;
 
 /// Result of a state transition
 #[derive(Debug)]
@@ -75,8 +77,18 @@ pub enum TransitionError {
     AwaitingTaskApproval,
     #[error("Conversation is awaiting user response to questions")]
     AwaitingUserResponse,
+    #[error("Conversation is awaiting review of a pending patch")]
+    AwaitingPatchReview,
+    #[error("Conversation is waiting for a council answer to be chosen")]
+    AwaitingCouncilChoice,
+    #[error("No council answer with that id can be chosen")]
+    UnknownCouncilCandidate,
     #[error("Conversation has reached terminal state (completed or abandoned)")]
     ConversationTerminal,
+    #[error("Agent is not running; send a regular message instead")]
+    AgentNotRunning,
+    #[error("Tool is not queued; it is running or has already finished")]
+    ToolNotQueued,
     #[error("Invalid transition: no arm for state={state} event={event}")]
     InvalidTransition {
         /// Variant name of the `ConvState` that didn't have a matching
@@ -113,8 +125,13 @@ pub enum TransitionError {
 /// don't accept chat HTTP traffic.
 pub fn check_user_message_acceptable(state: &ConvState) -> Result<(), TransitionError> {
     match state {
-        // Idle and Error: transition_core arm (Idle | Error, UserMessage) → LlmRequesting
-        ConvState::Idle | ConvState::Error { .. } => Ok(()),
+        // Idle and Error: transition_core arm (Idle | Error, UserMessage) → LlmRequesting;
+        // AwaitingUserGuidance and AwaitingUserInput for a prose question
+        // delegate to the Idle arm
+        ConvState::Idle
+        | ConvState::Error { .. }
+        | ConvState::AwaitingUserGuidance { .. }
+        | ConvState::AwaitingUserInput { ask: None, .. } => Ok(()),
 
         // transition_core: AgentBusy
         ConvState::LlmRequesting { .. }
@@ -128,7 +145,12 @@ pub fn check_user_message_acceptable(state: &ConvState) -> Result<(), Transition
 
         // transition_parent: explicit reject arms
         ConvState::AwaitingTaskApproval { .. } => Err(TransitionError::AwaitingTaskApproval),
-        ConvState::AwaitingUserResponse { .. } => Err(TransitionError::AwaitingUserResponse),
+        ConvState::AwaitingUserResponse { .. }
+        | ConvState::AwaitingUserInput { ask: Some(_), .. } => {
+            Err(TransitionError::AwaitingUserResponse)
+        }
+        ConvState::AwaitingPatchReview { .. } => Err(TransitionError::AwaitingPatchReview),
+        ConvState::AwaitingCouncilChoice { .. } => Err(TransitionError::AwaitingCouncilChoice),
         ConvState::ContextExhausted { .. } => Err(TransitionError::ContextExhausted),
         ConvState::Terminal => Err(TransitionError::ConversationTerminal),
 
@@ -145,6 +167,37 @@ pub fn check_user_message_acceptable(state: &ConvState) -> Result<(), Transition
     }
 }
 
+/// Synchronously check whether a `UserSteer` event would be accepted
+/// (REQ-BED-034). Steering only applies while the agent is mid-run; in every
+/// other state the note should be sent as an ordinary message.
+pub fn check_user_steer_acceptable(state: &ConvState) -> Result<(), TransitionError> {
+    match state {
+        ConvState::LlmRequesting { .. } | ConvState::ToolExecuting { .. } => Ok(()),
+        ConvState::CancellingTool { .. } | ConvState::CancellingSubAgents { .. } => {
+            Err(TransitionError::CancellationInProgress)
+        }
+        _ => Err(TransitionError::AgentNotRunning),
+    }
+}
+
+/// Synchronously check whether a `CancelSpecificTool` event would be
+/// accepted (REQ-BED-055). Only a tool still waiting behind the running one
+/// can be dropped; the running tool is stopped with the ordinary cancel.
+pub fn check_tool_cancellable(state: &ConvState, tool_use_id: &str) -> Result<(), TransitionError> {
+    match state {
+        ConvState::ToolExecuting {
+            remaining_tools, ..
+        }
+        | ConvState::AwaitingPatchReview {
+            remaining_tools, ..
+        } if remaining_tools.iter().any(|t| t.id == tool_use_id) => Ok(()),
+        ConvState::CancellingTool { .. } | ConvState::CancellingSubAgents { .. } => {
+            Err(TransitionError::CancellationInProgress)
+        }
+        _ => Err(TransitionError::ToolNotQueued),
+    }
+}
+
 /// Pure transition function — compatibility wrapper.
 ///
 /// Dispatches to `transition_parent` or `transition_sub_agent` based on
@@ -315,6 +368,7 @@ impl CoreTransitionResult {
 /// Does NOT handle: `propose_task` interception (parent-only), terminal tools
 /// (sub-agent-only), `LlmError` -> `Error` vs `Failed` (diverges by type),
 /// `UserCancel` from `LlmRequesting` (parent -> `Idle`, sub-agent -> `Failed`).
+#[allow(clippy::too_many_lines)]
 pub fn transition_core(
     state: &CoreState,
     context: &ConvContext,
@@ -376,6 +430,36 @@ pub fn transition_core(
             handle_core_tool_complete(state, event)
         }
 
+        // Dropping one queued tool (REQ-BED-055)
+        (
+            CoreState::ToolExecuting {
+                current_tool,
+                remaining_tools,
+                completed_results,
+                pending_sub_agents,
+                assistant_message,
+            },
+            CoreEvent::CancelSpecificTool { tool_use_id },
+        ) => {
+            let (remaining_tools, completed_results) =
+                skip_queued_tool(remaining_tools, completed_results, tool_use_id)?;
+            let notify = notify_tool_executing(
+                current_tool.name(),
+                &current_tool.id,
+                remaining_tools.len(),
+                completed_results.len(),
+            );
+            Ok(CoreTransitionResult::new(CoreState::ToolExecuting {
+                current_tool: current_tool.clone(),
+                remaining_tools,
+                completed_results,
+                pending_sub_agents: pending_sub_agents.clone(),
+                assistant_message: assistant_message.clone(),
+            })
+            .with_effect(Effect::PersistState)
+            .with_effect(notify))
+        }
+
         // Cancellation (REQ-BED-005)
         (CoreState::AwaitingSubAgents { .. }, CoreEvent::UserCancel { .. })
         | (CoreState::ToolExecuting { .. }, CoreEvent::UserCancel { .. })
@@ -395,7 +479,7 @@ pub fn transition_core(
         // Context Continuation (REQ-BED-019 through REQ-BED-024)
         (CoreState::AwaitingContinuation { .. }, CoreEvent::LlmError { .. })
         | (CoreState::AwaitingContinuation { .. }, CoreEvent::RetryTimeout { .. })
-        | (CoreState::Idle, CoreEvent::UserTriggerContinuation) => {
+        | (CoreState::Idle | CoreState::Error { .. }, CoreEvent::UserTriggerContinuation) => {
             handle_core_continuation(state, event)
         }
 
@@ -404,7 +488,7 @@ pub fn transition_core(
             Ok(CoreTransitionResult::new(CoreState::Idle))
         }
 
-        // Stale UserTriggerContinuation: any non-Idle Core state means the
+        // Stale UserTriggerContinuation: any other Core state means the
         // conversation is already in flight (LLM round, tools, sub-agents,
         // continuation summary) or in a sub-agent terminal state. The user's
         // intent ("summarize now") is either being served by the in-flight
@@ -862,6 +946,29 @@ fn build_cancellation_results(
     all_results
 }
 
+/// Removes `tool_use_id` from the queue and records a skipped result for it
+/// in its place, so the round still answers every `tool_use`.
+fn skip_queued_tool(
+    remaining_tools: &[ToolCall],
+    completed_results: &[ToolResult],
+    tool_use_id: &str,
+) -> Result<(Vec<ToolCall>, Vec<ToolResult>), TransitionError> {
+    if !remaining_tools.iter().any(|t| t.id == tool_use_id) {
+        return Err(TransitionError::ToolNotQueued);
+    }
+    let remaining = remaining_tools
+        .iter()
+        .filter(|t| t.id != tool_use_id)
+        .cloned()
+        .collect();
+    let mut results = completed_results.to_vec();
+    results.push(ToolResult::cancelled(
+        tool_use_id.to_string(),
+        "Skipped by user",
+    ));
+    Ok((remaining, results))
+}
+
 /// Handles `SubAgentResult` events in `AwaitingSubAgents` and `CancellingSubAgents` states.
 #[allow(clippy::too_many_lines)]
 fn handle_core_sub_agents(
@@ -1068,7 +1175,7 @@ fn handle_core_error_retry(
 }
 
 /// Handles continuation-related events: `LlmError`/`RetryTimeout` during
-/// `AwaitingContinuation`, and `UserTriggerContinuation` from Idle.
+/// `AwaitingContinuation`, and `UserTriggerContinuation` from Idle or Error.
 fn handle_core_continuation(
     state: &CoreState,
     event: CoreEvent,
@@ -1123,8 +1230,9 @@ fn handle_core_continuation(
             }))
         }
 
-        // UserTriggerContinuation from Idle (REQ-BED-023)
-        (CoreState::Idle, CoreEvent::UserTriggerContinuation) => {
+        // UserTriggerContinuation from Idle (REQ-BED-023), or from Error so a
+        // context-too-long failure can be compacted (REQ-BED-035)
+        (CoreState::Idle | CoreState::Error { .. }, CoreEvent::UserTriggerContinuation) => {
             Ok(CoreTransitionResult::new(CoreState::AwaitingContinuation {
                 rejected_tool_calls: vec![],
                 attempt: 1,
@@ -1159,6 +1267,173 @@ pub fn transition_parent(
     event: ParentEvent,
 ) -> Result<ParentTransitionResult, TransitionError> {
     match (state, event) {
+        // ============================================================
+        // Council mode (REQ-BED-058). The message is taken exactly as a
+        // plain one, but the request goes to every chosen model and the
+        // user picks the answer the conversation continues from.
+        // ============================================================
+        (
+            _,
+            ParentEvent::Parent(ParentOnlyEvent::UserCouncilMessage {
+                text,
+                llm_text,
+                images,
+                message_id,
+                user_agent,
+                skill_invocation,
+                models,
+            }),
+        ) => {
+            let message = CoreEvent::UserMessage {
+                text,
+                llm_text,
+                images,
+                message_id,
+                user_agent,
+                skill_invocation,
+            };
+            let mut result = transition_parent(state, context, ParentEvent::Core(message))?;
+            for effect in &mut result.effects {
+                if matches!(effect, Effect::RequestLlm) {
+                    *effect = Effect::RequestCouncil {
+                        models: models.clone(),
+                    };
+                }
+            }
+            Ok(result)
+        }
+
+        (
+            ParentState::Core(CoreState::LlmRequesting { .. }),
+            ParentEvent::Parent(ParentOnlyEvent::CouncilResponses { candidates }),
+        ) => {
+            let notify = Effect::notify_state_change(
+                "awaiting_council_choice",
+                json!({ "candidates": candidates }),
+            );
+            Ok(
+                ParentTransitionResult::new(ParentState::AwaitingCouncilChoice { candidates })
+                    .with_effect(Effect::PersistState)
+                    .with_effect(notify),
+            )
+        }
+
+        // Answers that arrive after a cancel are dropped
+        (state, ParentEvent::Parent(ParentOnlyEvent::CouncilResponses { .. })) => {
+            tracing::debug!("Absorbing stale CouncilResponses");
+            Ok(ParentTransitionResult::new(state.clone()))
+        }
+
+        (
+            ParentState::AwaitingCouncilChoice { candidates },
+            ParentEvent::Parent(ParentOnlyEvent::CouncilChoice { candidate_id }),
+        ) => {
+            let chosen = candidates
+                .iter()
+                .find(|c| c.id == candidate_id && c.error.is_none())
+                .ok_or(TransitionError::UnknownCouncilCandidate)?;
+            // The chosen answer goes through the ordinary response path, so
+            // its tool calls run and it is persisted like any other reply.
+            let response = CoreEvent::LlmResponse {
+                content: chosen.content.clone(),
+                tool_calls: chosen.tool_calls.clone(),
+                end_turn: chosen.tool_calls.is_empty(),
+                usage: chosen.usage.clone(),
+            };
+            transition_parent(
+                &ParentState::Core(CoreState::LlmRequesting { attempt: 1 }),
+                context,
+                ParentEvent::Core(response),
+            )
+        }
+
+        // Cancel abandons the pending choice the same way it abandons
+        // AwaitingUserGuidance.
+        (
+            ParentState::AwaitingCouncilChoice { .. } | ParentState::AwaitingUserGuidance { .. },
+            ParentEvent::Core(CoreEvent::UserCancel { .. }),
+        ) => Ok(
+            ParentTransitionResult::new(ParentState::Core(CoreState::Idle))
+                .with_effect(Effect::PersistState)
+                .with_effect(Effect::notify_agent_done()),
+        ),
+
+        (
+            ParentState::AwaitingCouncilChoice { .. },
+            ParentEvent::Core(CoreEvent::UserMessage { .. } | CoreEvent::UserTriggerContinuation),
+        ) => Err(TransitionError::AwaitingCouncilChoice),
+
+        // ============================================================
+        // Parent-only state: AwaitingUserInput on an `ask_user` call
+        // (REQ-AUQ-009). The answer is the call's result and resumes the
+        // turn; cancel settles the call and ends it.
+        // ============================================================
+        (
+            ParentState::AwaitingUserInput { ask: Some(ask), .. },
+            ParentEvent::Parent(ParentOnlyEvent::UserInputAnswer { answer }),
+        ) => {
+            let result = ToolResult::success(ask.tool_use_id.clone(), answer);
+            let checkpoint = CheckpointData::tool_round(ask.assistant_message.clone(), vec![result])
+                .expect("ask_user produces exactly one tool_use and one result");
+            Ok(
+                ParentTransitionResult::new(ParentState::Core(CoreState::LlmRequesting {
+                    attempt: 1,
+                }))
+                .with_effect(Effect::PersistCheckpoint { data: checkpoint })
+                .with_effect(Effect::PersistState)
+                .with_effect(notify_llm_requesting(1))
+                .with_effect(Effect::RequestLlm),
+            )
+        }
+
+        (
+            ParentState::AwaitingUserInput { ask: Some(ask), .. },
+            ParentEvent::Core(CoreEvent::UserCancel { .. }),
+        ) => {
+            let result =
+                ToolResult::cancelled(ask.tool_use_id.clone(), "The user declined to answer");
+            let checkpoint = CheckpointData::tool_round(ask.assistant_message.clone(), vec![result])
+                .expect("ask_user produces exactly one tool_use and one result");
+            Ok(ParentTransitionResult::new(ParentState::Core(CoreState::Idle))
+                .with_effect(Effect::PersistCheckpoint { data: checkpoint })
+                .with_effect(Effect::PersistState)
+                .with_effect(Effect::notify_agent_done()))
+        }
+
+        // A pending `ask_user` call blocks messages the same way a pending
+        // AwaitingUserResponse question form does.
+        (
+            ParentState::AwaitingUserInput { ask: Some(_), .. }
+            | ParentState::AwaitingUserResponse { .. },
+            ParentEvent::Core(CoreEvent::UserMessage { .. } | CoreEvent::UserTriggerContinuation),
+        ) => Err(TransitionError::AwaitingUserResponse),
+
+        // ============================================================
+        // Parent-only state: AwaitingUserInput on a prose question
+        // (REQ-BED-057). It is Idle with the agent's question on display:
+        // cancel dismisses the question, every other event is handled as
+        // in Idle.
+        // ============================================================
+        (
+            ParentState::AwaitingUserInput { ask: None, .. },
+            ParentEvent::Core(CoreEvent::UserCancel { .. }),
+        ) => Ok(
+            ParentTransitionResult::new(ParentState::Core(CoreState::Idle))
+                .with_effect(Effect::PersistState)
+                .with_effect(Effect::notify_state_change("idle", json!({}))),
+        ),
+
+        (ParentState::AwaitingUserInput { ask: None, .. }, event) => {
+            let result = transition_parent(&ParentState::Core(CoreState::Idle), context, event)?;
+            // An event Idle ignores (a stale LLM response) leaves the
+            // question on display
+            if result.new_state == ParentState::Core(CoreState::Idle) && result.effects.is_empty() {
+                Ok(ParentTransitionResult::new(state.clone()))
+            } else {
+                Ok(result)
+            }
+        }
+
         // ============================================================
         // Parent-only state: AwaitingTaskApproval
         // ============================================================
@@ -1235,13 +1510,9 @@ pub fn transition_parent(
         ),
 
         // ============================================================
-        // Parent-only state: AwaitingUserResponse
+        // Parent-only state: AwaitingUserResponse (messages are refused
+        // together with AwaitingUserInput above)
         // ============================================================
-        (
-            ParentState::AwaitingUserResponse { .. },
-            ParentEvent::Core(CoreEvent::UserMessage { .. } | CoreEvent::UserTriggerContinuation),
-        ) => Err(TransitionError::AwaitingUserResponse),
-
         (
             ParentState::AwaitingUserResponse { questions, .. },
             ParentEvent::Parent(ParentOnlyEvent::UserQuestionResponse {
@@ -1317,6 +1588,186 @@ pub fn transition_parent(
             .with_effect(Effect::RequestLlm),
         ),
 
+        // ============================================================
+        // Parent-only state: AwaitingUserGuidance (REQ-BED-038). The
+        // paused turn's history is complete, so a message (or a request to
+        // compact) proceeds exactly as it would from Idle. Cancel shares the
+        // AwaitingCouncilChoice arm above.
+        // ============================================================
+        (
+            ParentState::AwaitingUserGuidance { .. },
+            ParentEvent::Core(
+                core_event @ (CoreEvent::UserMessage { .. } | CoreEvent::UserTriggerContinuation),
+            ),
+        ) => Ok(transition_core(&CoreState::Idle, context, core_event)?.into_parent_result()),
+
+        // ============================================================
+        // Parent-only state: AwaitingPatchReview (REQ-PATCH-010). The
+        // tool round is paused on the patch call; approving writes it and
+        // resumes, rejecting reports an error result for the call.
+        // ============================================================
+        (
+            ParentState::Core(CoreState::ToolExecuting {
+                current_tool,
+                remaining_tools,
+                completed_results,
+                pending_sub_agents,
+                assistant_message,
+            }),
+            ParentEvent::Parent(ParentOnlyEvent::PatchStaged { tool_use_id, patch }),
+        ) if tool_use_id == current_tool.id => {
+            let notify = Effect::notify_state_change(
+                "awaiting_patch_review",
+                json!({ "tool_use_id": tool_use_id, "path": patch.path }),
+            );
+            Ok(ParentTransitionResult::new(ParentState::AwaitingPatchReview {
+                patch,
+                current_tool: current_tool.clone(),
+                remaining_tools: remaining_tools.clone(),
+                completed_results: completed_results.clone(),
+                pending_sub_agents: pending_sub_agents.clone(),
+                assistant_message: assistant_message.clone(),
+            })
+            .with_effect(Effect::PersistState)
+            .with_effect(notify))
+        }
+
+        (
+            ParentState::AwaitingPatchReview { .. },
+            ParentEvent::Core(CoreEvent::UserMessage { .. } | CoreEvent::UserTriggerContinuation),
+        ) => Err(TransitionError::AwaitingPatchReview),
+
+        (
+            ParentState::AwaitingPatchReview {
+                patch,
+                current_tool,
+                remaining_tools,
+                completed_results,
+                pending_sub_agents,
+                assistant_message,
+            },
+            ParentEvent::Core(CoreEvent::CancelSpecificTool { tool_use_id }),
+        ) => {
+            let (remaining_tools, completed_results) =
+                skip_queued_tool(remaining_tools, completed_results, &tool_use_id)?;
+            Ok(ParentTransitionResult::new(ParentState::AwaitingPatchReview {
+                patch: patch.clone(),
+                current_tool: current_tool.clone(),
+                remaining_tools,
+                completed_results,
+                pending_sub_agents: pending_sub_agents.clone(),
+                assistant_message: assistant_message.clone(),
+            })
+            .with_effect(Effect::PersistState))
+        }
+
+        (
+            ParentState::AwaitingPatchReview {
+                patch,
+                current_tool,
+                remaining_tools,
+                completed_results,
+                pending_sub_agents,
+                assistant_message,
+            },
+            ParentEvent::Parent(ParentOnlyEvent::PatchReviewResponse {
+                tool_use_id,
+                approved,
+            }),
+        ) if tool_use_id == current_tool.id => {
+            let executing = CoreState::ToolExecuting {
+                current_tool: current_tool.clone(),
+                remaining_tools: remaining_tools.clone(),
+                completed_results: completed_results.clone(),
+                pending_sub_agents: pending_sub_agents.clone(),
+                assistant_message: assistant_message.clone(),
+            };
+            if approved {
+                return Ok(ParentTransitionResult::new(ParentState::Core(executing))
+                    .with_effect(Effect::PersistState)
+                    .with_effect(notify_tool_executing(
+                        current_tool.name(),
+                        &current_tool.id,
+                        remaining_tools.len(),
+                        completed_results.len(),
+                    ))
+                    .with_effect(Effect::ApplyPatch {
+                        tool_use_id,
+                        patch: patch.clone(),
+                    }));
+            }
+            let result = ToolResult::error(
+                tool_use_id.clone(),
+                format!("The user rejected this patch; {} was not changed.", patch.path),
+            );
+            let event = CoreEvent::ToolComplete {
+                tool_use_id,
+                result,
+            };
+            Ok(handle_core_tool_complete(&executing, event)?.into_parent_result())
+        }
+
+        (
+            ParentState::AwaitingPatchReview {
+                current_tool,
+                remaining_tools,
+                completed_results,
+                pending_sub_agents,
+                assistant_message,
+                ..
+            },
+            ParentEvent::Core(CoreEvent::UserCancel { .. }),
+        ) => {
+            // Nothing is running, so settle the round as if the abort had
+            // already been confirmed.
+            let cancelling = CoreState::CancellingTool {
+                tool_use_id: current_tool.id.clone(),
+                skipped_tools: remaining_tools.clone(),
+                completed_results: completed_results.clone(),
+                assistant_message: assistant_message.clone(),
+                pending_sub_agents: pending_sub_agents.clone(),
+            };
+            let aborted = CoreEvent::ToolAborted {
+                tool_use_id: current_tool.id.clone(),
+            };
+            let mut result = handle_core_cancellation(&cancelling, aborted)?.into_parent_result();
+            if !pending_sub_agents.is_empty() {
+                let ids = pending_sub_agents
+                    .iter()
+                    .map(|p| p.agent_id.clone())
+                    .collect();
+                result = result.with_effect(Effect::CancelSubAgents { ids });
+            }
+            Ok(result)
+        }
+
+        (
+            ParentState::Core(CoreState::LlmRequesting { .. }),
+            ParentEvent::Parent(ParentOnlyEvent::TurnBudgetExceeded { reason }),
+        ) => Ok(ParentTransitionResult::new(ParentState::AwaitingUserGuidance {
+            reason: reason.clone(),
+        })
+        .with_effect(Effect::PersistMessage {
+            content: crate::db::MessageContent::system(format!(
+                "Paused: the agent {reason}. Send a message to continue with a fresh \
+                 budget, or say what to do differently."
+            )),
+            display_data: None,
+            usage_data: None,
+            message_id: uuid::Uuid::new_v4().to_string(),
+        })
+        .with_effect(Effect::PersistState)
+        .with_effect(Effect::notify_state_change(
+            "awaiting_user_guidance",
+            json!({ "reason": reason }),
+        ))),
+
+        // Raised only in place of an LLM request; anywhere else the turn
+        // has already stopped.
+        (_, ParentEvent::Parent(ParentOnlyEvent::TurnBudgetExceeded { .. })) => {
+            Ok(ParentTransitionResult::new(state.clone()))
+        }
+
         // ============================================================
         // Parent-only state: AwaitingRecovery (REQ-BED-030)
         // ============================================================
@@ -1374,25 +1825,86 @@ pub fn transition_parent(
         (ParentState::Terminal, _event) => Ok(ParentTransitionResult::new(ParentState::Terminal)),
 
         // ============================================================
-        // Task resolution: Idle + TaskResolved -> Terminal (REQ-BED-029)
+        // User steering (REQ-BED-034): no state change; the executor
+        // holds the note and persists it before the next LLM request,
+        // after any tool results still being gathered.
         // ============================================================
         (
-            ParentState::Core(CoreState::Idle),
-            ParentEvent::Parent(ParentOnlyEvent::TaskResolved {
-                system_message,
-                repo_root,
-            }),
-        ) => Ok(
-            ParentTransitionResult::new(ParentState::Terminal).with_effect(Effect::ResolveTask {
-                system_message,
-                repo_root,
-            }),
-        ),
+            ParentState::Core(CoreState::LlmRequesting { .. } | CoreState::ToolExecuting { .. }),
+            ParentEvent::Parent(ParentOnlyEvent::UserSteer { text, message_id }),
+        ) => Ok(ParentTransitionResult::new(state.clone())
+            .with_effect(Effect::QueueSteer { text, message_id })),
 
-        // ============================================================
-        // Parent-specific LLM response interceptions (before core)
-        //
-        // Combined into a single match arm to avoid borrow-after-move
+        (
+            ParentState::Core(
+                CoreState::CancellingTool { .. } | CoreState::CancellingSubAgents { .. },
+            ),
+            ParentEvent::Parent(ParentOnlyEvent::UserSteer { .. }),
+        ) => Err(TransitionError::CancellationInProgress),
+
+        (_, ParentEvent::Parent(ParentOnlyEvent::UserSteer { .. })) => {
+            Err(TransitionError::AgentNotRunning)
+        }
+
+        // ============================================================
+        // Retry from Error (REQ-BED-035): the last user turn is still the
+        // tail of the persisted history, so a fresh LLM request re-sends it.
+        // ============================================================
+        (
+            ParentState::Core(CoreState::Error { .. }),
+            ParentEvent::Parent(ParentOnlyEvent::UserRetry),
+        ) => Ok(
+            ParentTransitionResult::new(ParentState::Core(CoreState::LlmRequesting { attempt: 1 }))
+                .with_effect(Effect::PersistState)
+                .with_effect(notify_llm_requesting(1))
+                .with_effect(Effect::RequestLlm),
+        ),
+
+        // ============================================================
+        // Verification feedback (REQ-BED-037): a failed verify run after
+        // the turn ended starts another turn with the report. A result that
+        // arrives once the user has moved on is stale and dropped.
+        // ============================================================
+        (
+            ParentState::Core(CoreState::Idle),
+            ParentEvent::Parent(ParentOnlyEvent::VerifyFailed { report, message_id }),
+        ) => Ok(
+            ParentTransitionResult::new(ParentState::Core(CoreState::LlmRequesting { attempt: 1 }))
+                .with_effect(Effect::PersistMessage {
+                    content: crate::db::MessageContent::User(crate::db::UserContent::meta(report)),
+                    display_data: None,
+                    usage_data: None,
+                    message_id,
+                })
+                .with_effect(Effect::PersistState)
+                .with_effect(notify_llm_requesting(1))
+                .with_effect(Effect::RequestLlm),
+        ),
+
+        (_, ParentEvent::Parent(ParentOnlyEvent::VerifyFailed { .. })) => {
+            Ok(ParentTransitionResult::new(state.clone()))
+        }
+
+        // ============================================================
+        // Task resolution: Idle + TaskResolved -> Terminal (REQ-BED-029)
+        // ============================================================
+        (
+            ParentState::Core(CoreState::Idle),
+            ParentEvent::Parent(ParentOnlyEvent::TaskResolved {
+                system_message,
+                repo_root,
+            }),
+        ) => Ok(
+            ParentTransitionResult::new(ParentState::Terminal).with_effect(Effect::ResolveTask {
+                system_message,
+                repo_root,
+            }),
+        ),
+
+        // ============================================================
+        // Parent-specific LLM response interceptions (before core)
+        //
+        // Combined into a single match arm to avoid borrow-after-move
         // issues with guards on the same event payload.
         // ============================================================
         (
@@ -1533,7 +2045,59 @@ pub fn transition_parent(
                 unreachable!("ask_question_tool matched but input was not AskUserQuestion");
             }
 
-            // REQ-BED-019: Context exhaustion check (after propose_task/ask_user_question)
+            // REQ-AUQ-009: ask_user interception. The call's result is the
+            // user's answer, so the round is held until it arrives.
+            if let Some(tool) = tool_calls
+                .iter()
+                .find(|t| matches!(t.input, ToolInput::AskUser(_)))
+            {
+                if tool_calls.len() > 1 {
+                    let msg = "ask_user must be the only tool in response".to_string();
+                    let display_data = compute_bash_display_data(&content, &context.working_dir);
+                    let assistant_message =
+                        AssistantMessage::new(content, Some(usage_data), display_data);
+                    let error_results: Vec<ToolResult> = tool_calls
+                        .iter()
+                        .map(|t| ToolResult::error(t.id.clone(), msg.clone()))
+                        .collect();
+                    let checkpoint =
+                        CheckpointData::tool_round(assistant_message, error_results)
+                            .expect("error_results.len() == tool_calls.len()");
+                    return Ok(ParentTransitionResult::new(ParentState::Core(
+                        CoreState::LlmRequesting { attempt: 1 },
+                    ))
+                    .with_effect(Effect::PersistCheckpoint { data: checkpoint })
+                    .with_effect(Effect::PersistState)
+                    .with_effect(notify_llm_requesting(1))
+                    .with_effect(Effect::RequestLlm));
+                }
+                if let ToolInput::AskUser(ref input) = tool.input {
+                    let display_data = compute_bash_display_data(&content, &context.working_dir);
+                    let assistant_message =
+                        AssistantMessage::new(content, Some(usage_data), display_data);
+                    let notify = Effect::notify_state_change(
+                        "awaiting_user_input",
+                        json!({
+                            "question": input.question,
+                            "options": input.options,
+                            "tool_use_id": tool.id
+                        }),
+                    );
+                    return Ok(ParentTransitionResult::new(ParentState::AwaitingUserInput {
+                        question: input.question.clone(),
+                        options: input.options.clone(),
+                        ask: Some(PendingAsk {
+                            tool_use_id: tool.id.clone(),
+                            assistant_message,
+                        }),
+                    })
+                    .with_effect(Effect::PersistState)
+                    .with_effect(notify));
+                }
+                unreachable!("ask_user matched but input was not AskUser");
+            }
+
+            // REQ-BED-019: Context exhaustion check (after the interceptions above)
             if should_trigger_continuation(&usage_data, context.context_window) {
                 let tr = handle_context_exhaustion(context, content, tool_calls, usage_data);
                 return Ok(ParentTransitionResult {
@@ -1543,6 +2107,14 @@ pub fn transition_parent(
                 });
             }
 
+            // REQ-BED-057: a text-only turn that ends on a question waits
+            // for the answer instead of going Idle
+            let question = if tool_calls.is_empty() {
+                question::trailing_question(&content)
+            } else {
+                None
+            };
+
             // No interception needed — delegate to core
             let core_event = CoreEvent::LlmResponse {
                 content,
@@ -1554,7 +2126,26 @@ pub fn transition_parent(
                 unreachable!()
             };
             let core_result = transition_core(core_state, context, core_event)?;
-            Ok(core_result.into_parent_result())
+            let ends_turn = matches!(core_result.new_state, CoreState::Idle);
+            let result = core_result.into_parent_result();
+            match question {
+                Some(question) if ends_turn => {
+                    let notify = Effect::notify_state_change(
+                        "awaiting_user_input",
+                        json!({ "question": question }),
+                    );
+                    Ok(ParentTransitionResult {
+                        new_state: ParentState::AwaitingUserInput {
+                            question,
+                            options: vec![],
+                            ask: None,
+                        },
+                        effects: result.effects,
+                    }
+                    .with_effect(notify))
+                }
+                _ => Ok(result),
+            }
         }
 
         // AwaitingRecovery interception for auth errors
@@ -1582,6 +2173,30 @@ pub fn transition_parent(
             )))
         }
 
+        // Prompt no longer fits the window (preflight guard or provider
+        // rejection): compact instead of failing (REQ-BED-036)
+        (
+            ParentState::Core(CoreState::LlmRequesting { .. }),
+            ParentEvent::Core(CoreEvent::LlmError {
+                error_kind: ErrorKind::ContextExhausted,
+                ..
+            }),
+        ) => {
+            let next = ParentState::Core(CoreState::AwaitingContinuation {
+                rejected_tool_calls: vec![],
+                attempt: 1,
+            });
+            Ok(ParentTransitionResult::new(next)
+                .with_effect(Effect::PersistState)
+                .with_effect(Effect::notify_state_change(
+                    "awaiting_continuation",
+                    json!({ "context_overflow": true }),
+                ))
+                .with_effect(Effect::RequestContinuation {
+                    rejected_tool_calls: vec![],
+                }))
+        }
+
         // ============================================================
         // Parent-specific continuation transitions
         // ============================================================
@@ -1593,7 +2208,12 @@ pub fn transition_parent(
         })
         .with_effect(Effect::persist_continuation_message(&summary))
         .with_effect(Effect::PersistState)
-        .with_effect(Effect::NotifyContextExhausted { summary })),
+        .with_effect(Effect::NotifyContextExhausted {
+            summary: summary.clone(),
+        })
+        // Only a real summary is worth continuing from; failures and
+        // cancels stop at ContextExhausted for the user to decide.
+        .with_effect(Effect::AutoContinue { summary })),
 
         (
             ParentState::Core(CoreState::AwaitingContinuation { .. }),
@@ -1925,11 +2545,30 @@ pub fn transition_sub_agent(
 /// current state. The executor logs and discards `Err` — state unchanged.
 ///
 /// REQ-BED-001: Pure function — given the same inputs, always the same outputs.
+#[allow(dead_code)] // Executor calls `outcome_to_event` + `transition` to log the event
 pub fn handle_outcome(
     state: &ConvState,
     context: &ConvContext,
     outcome: EffectOutcome,
 ) -> Result<TransitionResult, InvalidOutcome> {
+    let Some(event) = outcome_to_event(state, outcome)? else {
+        return Ok(TransitionResult::new(state.clone()));
+    };
+
+    transition(state, context, event).map_err(|e| InvalidOutcome {
+        reason: e.to_string(),
+    })
+}
+
+/// The `Event` that `handle_outcome()` feeds to `transition()` for `outcome`.
+///
+/// `Ok(None)` means the outcome needs no transition (a successful persist).
+/// Exposed so the executor can record the event it actually applied
+/// (REQ-API-017) while still going through the same conversion.
+pub fn outcome_to_event(
+    state: &ConvState,
+    outcome: EffectOutcome,
+) -> Result<Option<Event>, InvalidOutcome> {
     let event = match outcome {
         EffectOutcome::Llm(llm) => llm_outcome_to_event(llm, state),
         EffectOutcome::Tool(tool) => tool_outcome_to_event(tool),
@@ -1937,14 +2576,12 @@ pub fn handle_outcome(
             Event::SubAgentResult { agent_id, outcome }
         }
         EffectOutcome::Persist(persist) => {
-            return handle_persist_outcome(state, persist);
+            handle_persist_outcome(persist)?;
+            return Ok(None);
         }
         EffectOutcome::RetryTimeout { attempt } => Event::RetryTimeout { attempt },
     };
-
-    transition(state, context, event).map_err(|e| InvalidOutcome {
-        reason: e.to_string(),
-    })
+    Ok(Some(event))
 }
 
 /// Convert `LlmOutcome` to the equivalent `Event` for delegation to `transition()`.
@@ -1961,6 +2598,7 @@ fn llm_outcome_to_event(outcome: LlmOutcome, state: &ConvState) -> Event {
             end_turn,
             usage,
         },
+        LlmOutcome::Council { candidates } => Event::CouncilResponses { candidates },
         LlmOutcome::RateLimited { retry_after: _ } => {
             let attempt = current_attempt(state);
             Event::LlmError {
@@ -2045,17 +2683,15 @@ fn tool_outcome_to_event(outcome: ToolExecOutcome) -> Event {
             tool_use_id: tool_use_id.clone(),
             result: ToolResult::error(tool_use_id, error),
         },
+        ToolExecOutcome::Staged { tool_use_id, patch } => Event::PatchStaged { tool_use_id, patch },
     }
 }
 
 /// Handle `PersistOutcome` directly — no Event equivalent exists.
 /// Persistence failures are logged but don't change state.
-fn handle_persist_outcome(
-    state: &ConvState,
-    outcome: PersistOutcome,
-) -> Result<TransitionResult, InvalidOutcome> {
+fn handle_persist_outcome(outcome: PersistOutcome) -> Result<(), InvalidOutcome> {
     match outcome {
-        PersistOutcome::Ok => Ok(TransitionResult::new(state.clone())),
+        PersistOutcome::Ok => Ok(()),
         PersistOutcome::Failed { error } => Err(InvalidOutcome {
             reason: format!("Persistence failed: {error}"),
         }),
@@ -2220,6 +2856,7 @@ pub fn llm_error_to_db_error(kind: crate::llm::LlmErrorKind) -> ErrorKind {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::state_machine::budget::TurnBudget;
     use std::path::PathBuf;
 
     fn test_context() -> ConvContext {
@@ -2389,6 +3026,7 @@ mod tests {
             output_tokens: 0,
             cache_read_tokens: 0,
             cache_creation_tokens: 0,
+            model: None,
         };
         assert!(
             !should_trigger_continuation(&usage, 100_000),
@@ -2404,6 +3042,7 @@ mod tests {
             output_tokens: 0,
             cache_read_tokens: 0,
             cache_creation_tokens: 0,
+            model: None,
         };
         assert!(
             should_trigger_continuation(&usage, 100_000),
@@ -2419,6 +3058,7 @@ mod tests {
             output_tokens: 0,
             cache_read_tokens: 0,
             cache_creation_tokens: 0,
+            model: None,
         };
         assert!(
             should_trigger_continuation(&usage, 100_000),
@@ -2434,6 +3074,7 @@ mod tests {
             output_tokens: 45_000,
             cache_read_tokens: 0,
             cache_creation_tokens: 0,
+            model: None,
         };
         assert!(
             should_trigger_continuation(&usage, 100_000),
@@ -2459,6 +3100,8 @@ mod tests {
             max_turns: 0,
             desired_base_branch: None,
             mode: ModeKind::Managed,
+            turn_budget: TurnBudget::default(),
+            review_patches: false,
         };
 
         let result = handle_context_exhaustion(
@@ -2470,6 +3113,7 @@ mod tests {
                 output_tokens: 0,
                 cache_read_tokens: 0,
                 cache_creation_tokens: 0,
+                model: None,
             },
         );
 
@@ -2529,6 +3173,7 @@ mod tests {
                 output_tokens: 0,
                 cache_read_tokens: 0,
                 cache_creation_tokens: 0,
+                model: None,
             },
         );
 
@@ -2575,6 +3220,8 @@ mod tests {
             max_turns: 0,
             desired_base_branch: None,
             mode: ModeKind::Managed,
+            turn_budget: TurnBudget::default(),
+            review_patches: false,
         };
 
         let result = transition(
@@ -2589,6 +3236,7 @@ mod tests {
                     output_tokens: 500,
                     cache_creation_tokens: 0,
                     cache_read_tokens: 0,
+                    model: None,
                 },
             },
         )
@@ -2638,6 +3286,7 @@ mod tests {
                     output_tokens: 500,
                     cache_creation_tokens: 0,
                     cache_read_tokens: 0,
+                    model: None,
                 },
             },
         )
@@ -2676,6 +3325,8 @@ mod tests {
             max_turns: 0,
             desired_base_branch: None,
             mode: ModeKind::Managed,
+            turn_budget: TurnBudget::default(),
+            review_patches: false,
         };
 
         // attempt == MAX_RETRY_ATTEMPTS (3), retryable error → retries exhausted
@@ -2724,6 +3375,8 @@ mod tests {
             max_turns: 0,
             desired_base_branch: None,
             mode: ModeKind::Managed,
+            turn_budget: TurnBudget::default(),
+            review_patches: false,
         };
 
         // Non-retryable error at attempt 1 → immediate failure
@@ -3145,119 +3798,792 @@ mod tests {
         );
     }
 
-    /// Race scenario: SSE-stream connect triggers `should_auto_continue`,
-    /// state moves Idle -> `LlmRequesting` before the client receives the
-    /// state change. User clicks "trigger continuation" against the stale
-    /// Idle UI. The state machine must absorb the event, not surface it as
-    /// an `InvalidTransition` error to the user.
+    // ========================================================================
+    // Prose Question Tests (REQ-BED-057)
+    // ========================================================================
+
+    fn text_response(text: &str) -> Event {
+        use crate::llm::{ContentBlock, Usage};
+        Event::LlmResponse {
+            content: vec![ContentBlock::text(text)],
+            tool_calls: vec![],
+            end_turn: true,
+            usage: Usage::default(),
+        }
+    }
+
     #[test]
-    fn user_trigger_continuation_in_llm_requesting_is_absorbed() {
+    fn a_turn_ending_on_a_question_awaits_user_input() {
+        use crate::state_machine::state::DisplayState;
+
+        let requesting = ConvState::LlmRequesting { attempt: 1 };
         let result = transition(
-            &ConvState::LlmRequesting { attempt: 1 },
+            &requesting,
             &test_context(),
-            Event::UserTriggerContinuation,
+            text_response("Fixed the bug.\n\nShould I also add a regression test?"),
         )
-        .expect("absorb, not error");
-
-        assert!(
-            matches!(result.new_state, ConvState::LlmRequesting { attempt: 1 }),
-            "state must not change when absorbing, got {:?}",
-            result.new_state
+        .unwrap();
+        assert_eq!(
+            result.new_state,
+            ConvState::AwaitingUserInput {
+                question: "Should I also add a regression test?".to_string(),
+                options: vec![],
+                ask: None,
+            }
         );
-        assert!(
-            result.effects.is_empty(),
-            "absorb arm must produce no effects, got {} effects",
-            result.effects.len()
+        assert_eq!(
+            result.new_state.display_state(),
+            DisplayState::AwaitingApproval
         );
+        let events: Vec<&str> = result
+            .effects
+            .iter()
+            .filter_map(|e| match e {
+                Effect::NotifyClient { event_type, .. } => Some(event_type.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(events, ["agent_done", "state_change"]);
+
+        let done = transition(&requesting, &test_context(), text_response("All done."));
+        assert_eq!(done.unwrap().new_state, ConvState::Idle);
     }
 
     #[test]
-    fn user_trigger_continuation_in_tool_executing_is_absorbed() {
-        use crate::state_machine::state::{
-            AssistantMessage, BashInput, BashMode, ToolCall, ToolInput,
+    fn awaiting_user_input_behaves_as_idle() {
+        let state = ConvState::AwaitingUserInput {
+            question: "Should I push?".to_string(),
+            options: vec![],
+            ask: None,
         };
-
-        let state = ConvState::ToolExecuting {
-            current_tool: ToolCall::new(
-                "tool-1",
-                ToolInput::Bash(BashInput {
-                    command: "echo".to_string(),
-                    mode: BashMode::Default,
-                }),
-            ),
-            remaining_tools: vec![],
-            completed_results: vec![],
-            pending_sub_agents: vec![],
-            assistant_message: AssistantMessage::default(),
+        assert!(state.is_idle());
+        assert!(check_user_message_acceptable(&state).is_ok());
+
+        let answer = Event::UserMessage {
+            text: "yes".to_string(),
+            llm_text: None,
+            images: vec![],
+            message_id: "msg-1".to_string(),
+            user_agent: None,
+            skill_invocation: None,
         };
+        let result = transition(&state, &test_context(), answer).unwrap();
+        assert_eq!(result.new_state, ConvState::LlmRequesting { attempt: 1 });
 
-        let result = transition(&state, &test_context(), Event::UserTriggerContinuation)
-            .expect("absorb, not error");
-
-        assert!(matches!(result.new_state, ConvState::ToolExecuting { .. }));
-        assert!(result.effects.is_empty());
+        let cancel = Event::UserCancel { reason: None };
+        let result = transition(&state, &test_context(), cancel).unwrap();
+        assert_eq!(result.new_state, ConvState::Idle);
     }
 
-    #[test]
-    fn user_trigger_continuation_in_awaiting_continuation_is_absorbed() {
-        // Already summarizing — clicking again is a redundant intent, not
-        // an invalid one.
-        let state = ConvState::AwaitingContinuation {
-            rejected_tool_calls: vec![],
-            attempt: 1,
-        };
+    // ========================================================================
+    // ask_user Tests (REQ-AUQ-009)
+    // ========================================================================
 
-        let result = transition(&state, &test_context(), Event::UserTriggerContinuation)
-            .expect("absorb, not error");
+    fn ask_user_response(extra_tool: Option<ToolCall>) -> Event {
+        use crate::llm::{ContentBlock, Usage};
+        use crate::state_machine::state::AskUserInput;
 
-        assert!(matches!(
-            result.new_state,
-            ConvState::AwaitingContinuation { attempt: 1, .. }
-        ));
-        assert!(result.effects.is_empty());
+        let input = AskUserInput {
+            question: "Which database?".to_string(),
+            options: vec!["SQLite".to_string(), "Postgres".to_string()],
+        };
+        let mut content = vec![ContentBlock::tool_use(
+            "tool-ask-1",
+            "ask_user",
+            serde_json::to_value(&input).unwrap(),
+        )];
+        let mut tool_calls = vec![ToolCall::new("tool-ask-1", ToolInput::AskUser(input))];
+        if let Some(tool) = extra_tool {
+            content.push(ContentBlock::tool_use(&tool.id, tool.name(), json!({})));
+            tool_calls.push(tool);
+        }
+        Event::LlmResponse {
+            content,
+            tool_calls,
+            end_turn: false,
+            usage: Usage::default(),
+        }
     }
 
-    #[test]
-    fn check_user_message_acceptable_idle_ok() {
-        assert!(check_user_message_acceptable(&ConvState::Idle).is_ok());
+    fn tool_round_results(effects: &[Effect]) -> Vec<ToolResult> {
+        effects
+            .iter()
+            .find_map(|e| match e {
+                Effect::PersistCheckpoint {
+                    data: CheckpointData::ToolRound { tool_results, .. },
+                } => Some(tool_results.clone()),
+                _ => None,
+            })
+            .unwrap_or_default()
     }
 
     #[test]
-    fn check_user_message_acceptable_context_exhausted_returns_typed_error() {
-        let state = ConvState::ContextExhausted {
-            summary: "summary".to_string(),
+    fn ask_user_waits_for_the_answer_as_its_result() {
+        let ctx = test_context();
+        let requesting = ConvState::LlmRequesting { attempt: 1 };
+        let asked = transition(&requesting, &ctx, ask_user_response(None)).unwrap();
+        let ConvState::AwaitingUserInput {
+            question,
+            options,
+            ask: Some(ask),
+        } = &asked.new_state
+        else {
+            panic!("Should await the answer, got {:?}", asked.new_state);
         };
-        let err = check_user_message_acceptable(&state).expect_err("must reject");
-        assert!(matches!(err, TransitionError::ContextExhausted));
-    }
+        assert_eq!(question, "Which database?");
+        assert_eq!(options, &["SQLite", "Postgres"]);
+        assert_eq!(ask.tool_use_id, "tool-ask-1");
+        // The round is persisted with the answer, not before
+        assert!(tool_round_results(&asked.effects).is_empty());
+        assert!(!asked.new_state.is_idle());
+
+        let message = Event::UserMessage {
+            text: "hello".to_string(),
+            llm_text: None,
+            images: vec![],
+            message_id: "msg-1".to_string(),
+            user_agent: None,
+            skill_invocation: None,
+        };
+        assert!(matches!(
+            transition(&asked.new_state, &ctx, message),
+            Err(TransitionError::AwaitingUserResponse)
+        ));
+        assert!(check_user_message_acceptable(&asked.new_state).is_err());
 
-    #[test]
-    fn check_user_message_acceptable_terminal_returns_typed_error() {
-        let err = check_user_message_acceptable(&ConvState::Terminal).expect_err("must reject");
-        assert!(matches!(err, TransitionError::ConversationTerminal));
-    }
+        let answer = Event::UserInputAnswer {
+            answer: "Postgres".to_string(),
+        };
+        let resumed = transition(&asked.new_state, &ctx, answer).unwrap();
+        assert_eq!(resumed.new_state, ConvState::LlmRequesting { attempt: 1 });
+        let results = tool_round_results(&resumed.effects);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].tool_use_id, "tool-ask-1");
+        assert_eq!(results[0].output(), "Postgres");
+        assert!(resumed
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::RequestLlm)));
 
-    #[test]
-    fn check_user_message_acceptable_busy_returns_agent_busy() {
-        let err = check_user_message_acceptable(&ConvState::LlmRequesting { attempt: 1 })
-            .expect_err("must reject");
-        assert!(matches!(err, TransitionError::AgentBusy));
+        let cancel = Event::UserCancel { reason: None };
+        let cancelled = transition(&asked.new_state, &ctx, cancel).unwrap();
+        assert_eq!(cancelled.new_state, ConvState::Idle);
+        assert_eq!(tool_round_results(&cancelled.effects).len(), 1);
     }
 
     #[test]
-    fn user_trigger_continuation_from_idle_still_starts_continuation() {
-        // Regression guard: the absorb arm must not steal the Idle path,
-        // which is the actual user-initiated continuation flow.
+    fn ask_user_must_be_the_only_tool() {
+        use crate::state_machine::state::{BashInput, BashMode};
+
+        let bash = ToolCall::new(
+            "tool-bash-1",
+            ToolInput::Bash(BashInput {
+                command: "ls".to_string(),
+                mode: BashMode::Default,
+            }),
+        );
         let result = transition(
-            &ConvState::Idle,
+            &ConvState::LlmRequesting { attempt: 1 },
             &test_context(),
-            Event::UserTriggerContinuation,
+            ask_user_response(Some(bash)),
         )
-        .expect("Idle path should succeed");
+        .unwrap();
+        assert_eq!(result.new_state, ConvState::LlmRequesting { attempt: 1 });
+        let results = tool_round_results(&result.effects);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(ToolResult::is_error));
+    }
 
-        assert!(
-            matches!(
-                result.new_state,
+    // ========================================================================
+    // Patch Review Tests (REQ-PATCH-010)
+    // ========================================================================
+
+    fn make_staged_patch() -> crate::tools::patch::StagedPatch {
+        crate::tools::patch::StagedPatch {
+            path: "/tmp/file.txt".to_string(),
+            diff: "-old\n+new\n".to_string(),
+            effects: vec![],
+            autogenerated_warning: false,
+            converted_line_endings: vec![],
+        }
+    }
+
+    fn make_awaiting_patch_review_state() -> ConvState {
+        use crate::llm::ContentBlock;
+        use crate::state_machine::state::{AssistantMessage, ToolCall, ToolInput};
+        use crate::tools::patch::PatchInput;
+
+        let assistant_message = AssistantMessage::new(
+            vec![ContentBlock::tool_use(
+                "tool-patch-1",
+                "patch",
+                serde_json::json!({"path": "/tmp/file.txt", "patches": []}),
+            )],
+            None,
+            None,
+        );
+        ConvState::AwaitingPatchReview {
+            patch: make_staged_patch(),
+            current_tool: ToolCall::new(
+                "tool-patch-1",
+                ToolInput::Patch(PatchInput {
+                    path: "/tmp/file.txt".to_string(),
+                    patches: vec![],
+                    files: vec![],
+                }),
+            ),
+            remaining_tools: vec![],
+            completed_results: vec![],
+            pending_sub_agents: vec![],
+            assistant_message,
+        }
+    }
+
+    #[test]
+    fn test_patch_staged_goes_to_awaiting_patch_review() {
+        let ConvState::AwaitingPatchReview {
+            current_tool,
+            remaining_tools,
+            completed_results,
+            pending_sub_agents,
+            assistant_message,
+            ..
+        } = make_awaiting_patch_review_state()
+        else {
+            unreachable!();
+        };
+        let state = ConvState::ToolExecuting {
+            current_tool,
+            remaining_tools,
+            completed_results,
+            pending_sub_agents,
+            assistant_message,
+        };
+
+        let result = transition(
+            &state,
+            &test_context(),
+            Event::PatchStaged {
+                tool_use_id: "tool-patch-1".to_string(),
+                patch: make_staged_patch(),
+            },
+        )
+        .unwrap();
+
+        assert!(
+            matches!(result.new_state, ConvState::AwaitingPatchReview { .. }),
+            "Should pause for review, got {:?}",
+            result.new_state
+        );
+        assert!(result.effects.iter().any(|e| matches!(
+            e,
+            Effect::NotifyClient { event_type, .. } if event_type == "state_change"
+        )));
+    }
+
+    #[test]
+    fn test_patch_review_approve_applies_patch() {
+        let result = transition(
+            &make_awaiting_patch_review_state(),
+            &test_context(),
+            Event::PatchReviewResponse {
+                tool_use_id: "tool-patch-1".to_string(),
+                approved: true,
+            },
+        )
+        .unwrap();
+
+        assert!(matches!(result.new_state, ConvState::ToolExecuting { .. }));
+        assert!(result.effects.iter().any(|e| matches!(
+            e,
+            Effect::ApplyPatch { tool_use_id, .. } if tool_use_id == "tool-patch-1"
+        )));
+    }
+
+    #[test]
+    fn test_patch_review_reject_completes_tool_with_error() {
+        let result = transition(
+            &make_awaiting_patch_review_state(),
+            &test_context(),
+            Event::PatchReviewResponse {
+                tool_use_id: "tool-patch-1".to_string(),
+                approved: false,
+            },
+        )
+        .unwrap();
+
+        assert!(matches!(result.new_state, ConvState::LlmRequesting { .. }));
+        assert!(!result
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::ApplyPatch { .. })));
+        let rejected = result.effects.iter().any(|e| match e {
+            Effect::PersistCheckpoint {
+                data: CheckpointData::ToolRound { tool_results, .. },
+            } => tool_results
+                .iter()
+                .any(|r| r.is_error() && r.output().contains("rejected")),
+            _ => false,
+        });
+        assert!(
+            rejected,
+            "Should persist an error result, got {:?}",
+            result.effects
+        );
+    }
+
+    #[test]
+    fn test_patch_review_ignores_mismatched_tool_id() {
+        let result = transition(
+            &make_awaiting_patch_review_state(),
+            &test_context(),
+            Event::PatchReviewResponse {
+                tool_use_id: "other".to_string(),
+                approved: true,
+            },
+        );
+
+        assert!(result.is_err(), "Mismatched id must not apply the patch");
+    }
+
+    #[test]
+    fn test_patch_review_cancel_goes_idle() {
+        let result = transition(
+            &make_awaiting_patch_review_state(),
+            &test_context(),
+            Event::UserCancel { reason: None },
+        )
+        .unwrap();
+
+        assert!(matches!(result.new_state, ConvState::Idle));
+        assert!(!result
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::ApplyPatch { .. })));
+    }
+
+    #[test]
+    fn test_patch_review_rejects_user_message() {
+        let result = transition(
+            &make_awaiting_patch_review_state(),
+            &test_context(),
+            Event::UserMessage {
+                text: "hello".to_string(),
+                llm_text: None,
+                images: vec![],
+                message_id: "msg-1".to_string(),
+                user_agent: None,
+                skill_invocation: None,
+            },
+        );
+
+        assert!(matches!(result, Err(TransitionError::AwaitingPatchReview)));
+    }
+
+    /// Race scenario: SSE-stream connect triggers `should_auto_continue`,
+    /// state moves Idle -> `LlmRequesting` before the client receives the
+    /// state change. User clicks "trigger continuation" against the stale
+    /// Idle UI. The state machine must absorb the event, not surface it as
+    /// an `InvalidTransition` error to the user.
+    #[test]
+    fn user_trigger_continuation_in_llm_requesting_is_absorbed() {
+        let result = transition(
+            &ConvState::LlmRequesting { attempt: 1 },
+            &test_context(),
+            Event::UserTriggerContinuation,
+        )
+        .expect("absorb, not error");
+
+        assert!(
+            matches!(result.new_state, ConvState::LlmRequesting { attempt: 1 }),
+            "state must not change when absorbing, got {:?}",
+            result.new_state
+        );
+        assert!(
+            result.effects.is_empty(),
+            "absorb arm must produce no effects, got {} effects",
+            result.effects.len()
+        );
+    }
+
+    #[test]
+    fn user_trigger_continuation_in_tool_executing_is_absorbed() {
+        use crate::state_machine::state::{
+            AssistantMessage, BashInput, BashMode, ToolCall, ToolInput,
+        };
+
+        let state = ConvState::ToolExecuting {
+            current_tool: ToolCall::new(
+                "tool-1",
+                ToolInput::Bash(BashInput {
+                    command: "echo".to_string(),
+                    mode: BashMode::Default,
+                }),
+            ),
+            remaining_tools: vec![],
+            completed_results: vec![],
+            pending_sub_agents: vec![],
+            assistant_message: AssistantMessage::default(),
+        };
+
+        let result = transition(&state, &test_context(), Event::UserTriggerContinuation)
+            .expect("absorb, not error");
+
+        assert!(matches!(result.new_state, ConvState::ToolExecuting { .. }));
+        assert!(result.effects.is_empty());
+    }
+
+    #[test]
+    fn user_trigger_continuation_in_awaiting_continuation_is_absorbed() {
+        // Already summarizing — clicking again is a redundant intent, not
+        // an invalid one.
+        let state = ConvState::AwaitingContinuation {
+            rejected_tool_calls: vec![],
+            attempt: 1,
+        };
+
+        let result = transition(&state, &test_context(), Event::UserTriggerContinuation)
+            .expect("absorb, not error");
+
+        assert!(matches!(
+            result.new_state,
+            ConvState::AwaitingContinuation { attempt: 1, .. }
+        ));
+        assert!(result.effects.is_empty());
+    }
+
+    #[test]
+    fn continuation_summary_requests_auto_continue() {
+        let state = ConvState::AwaitingContinuation {
+            rejected_tool_calls: vec![],
+            attempt: 1,
+        };
+
+        let ok = transition(
+            &state,
+            &test_context(),
+            Event::ContinuationResponse {
+                summary: "did things".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(matches!(ok.new_state, ConvState::ContextExhausted { .. }));
+        assert!(matches!(
+            ok.effects.last(),
+            Some(Effect::AutoContinue { summary }) if summary == "did things"
+        ));
+
+        let failed = transition(
+            &state,
+            &test_context(),
+            Event::ContinuationFailed {
+                error: "boom".to_string(),
+            },
+        )
+        .unwrap();
+        assert!(matches!(
+            failed.new_state,
+            ConvState::ContextExhausted { .. }
+        ));
+        assert!(!failed
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::AutoContinue { .. })));
+    }
+
+    #[test]
+    fn check_user_message_acceptable_idle_ok() {
+        assert!(check_user_message_acceptable(&ConvState::Idle).is_ok());
+    }
+
+    #[test]
+    fn check_user_message_acceptable_context_exhausted_returns_typed_error() {
+        let state = ConvState::ContextExhausted {
+            summary: "summary".to_string(),
+        };
+        let err = check_user_message_acceptable(&state).expect_err("must reject");
+        assert!(matches!(err, TransitionError::ContextExhausted));
+    }
+
+    #[test]
+    fn check_user_message_acceptable_terminal_returns_typed_error() {
+        let err = check_user_message_acceptable(&ConvState::Terminal).expect_err("must reject");
+        assert!(matches!(err, TransitionError::ConversationTerminal));
+    }
+
+    #[test]
+    fn check_user_message_acceptable_busy_returns_agent_busy() {
+        let err = check_user_message_acceptable(&ConvState::LlmRequesting { attempt: 1 })
+            .expect_err("must reject");
+        assert!(matches!(err, TransitionError::AgentBusy));
+    }
+
+    fn user_steer(text: &str) -> Event {
+        Event::UserSteer {
+            text: text.to_string(),
+            message_id: "steer-1".to_string(),
+        }
+    }
+
+    #[test]
+    fn user_steer_while_running_queues_note_without_state_change() {
+        let state = ConvState::LlmRequesting { attempt: 2 };
+        let result = transition(&state, &test_context(), user_steer("skip the tests"))
+            .expect("steer accepted while running");
+
+        assert!(matches!(
+            result.new_state,
+            ConvState::LlmRequesting { attempt: 2 }
+        ));
+        assert!(matches!(
+            result.effects.as_slice(),
+            [Effect::QueueSteer { text, message_id }]
+                if text == "skip the tests" && message_id == "steer-1"
+        ));
+    }
+
+    #[test]
+    fn user_steer_rejected_when_not_running() {
+        let err = transition(&ConvState::Idle, &test_context(), user_steer("hi"))
+            .expect_err("nothing to steer");
+        assert!(matches!(err, TransitionError::AgentNotRunning));
+        assert!(matches!(
+            check_user_steer_acceptable(&ConvState::Idle),
+            Err(TransitionError::AgentNotRunning)
+        ));
+        assert!(check_user_steer_acceptable(&ConvState::LlmRequesting { attempt: 1 }).is_ok());
+    }
+
+    #[test]
+    fn user_steer_rejected_for_sub_agents() {
+        let context = ConvContext::sub_agent(
+            "sub",
+            PathBuf::from("/tmp"),
+            "test-model",
+            200_000,
+            "parent",
+        );
+        let err = transition(
+            &ConvState::LlmRequesting { attempt: 1 },
+            &context,
+            user_steer("x"),
+        )
+        .expect_err("parent-only event");
+        assert!(matches!(err, TransitionError::InvalidTransition { .. }));
+    }
+
+    fn error_state() -> ConvState {
+        ConvState::Error {
+            message: "Failed after 3 attempts: overloaded".to_string(),
+            error_kind: ErrorKind::ServerError,
+        }
+    }
+
+    #[test]
+    fn user_retry_from_error_requests_llm_again() {
+        let result = transition(&error_state(), &test_context(), Event::UserRetry)
+            .expect("retry accepted from Error");
+
+        assert!(matches!(
+            result.new_state,
+            ConvState::LlmRequesting { attempt: 1 }
+        ));
+        assert!(result
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::RequestLlm)));
+        assert!(!result
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::PersistMessage { .. })));
+    }
+
+    #[test]
+    fn user_retry_rejected_outside_error() {
+        let err = transition(&ConvState::Idle, &test_context(), Event::UserRetry)
+            .expect_err("nothing to retry");
+        assert!(matches!(err, TransitionError::InvalidTransition { .. }));
+    }
+
+    fn context_overflow() -> Event {
+        Event::LlmError {
+            message: "Token budget exceeded".to_string(),
+            error_kind: ErrorKind::ContextExhausted,
+            attempt: 1,
+            recovery_in_progress: false,
+        }
+    }
+
+    #[test]
+    fn context_overflow_compacts_parent() {
+        let state = ConvState::LlmRequesting { attempt: 1 };
+        let result =
+            transition(&state, &test_context(), context_overflow()).expect("overflow handled");
+
+        assert!(matches!(
+            result.new_state,
+            ConvState::AwaitingContinuation { attempt: 1, .. }
+        ));
+        assert!(result
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::RequestContinuation { .. })));
+    }
+
+    #[test]
+    fn context_overflow_fails_sub_agent() {
+        let context = ConvContext::sub_agent(
+            "sub",
+            PathBuf::from("/tmp"),
+            "test-model",
+            200_000,
+            "parent",
+        );
+        let state = ConvState::LlmRequesting { attempt: 1 };
+        let result = transition(&state, &context, context_overflow()).expect("overflow handled");
+
+        assert!(matches!(
+            result.new_state,
+            ConvState::Failed {
+                error_kind: ErrorKind::ContextExhausted,
+                ..
+            }
+        ));
+    }
+
+    fn verify_failed() -> Event {
+        Event::VerifyFailed {
+            report: "cargo test failed".to_string(),
+            message_id: "verify-1".to_string(),
+        }
+    }
+
+    #[test]
+    fn verify_failed_from_idle_feeds_report_back_as_meta_message() {
+        let result = transition(&ConvState::Idle, &test_context(), verify_failed())
+            .expect("verify feedback accepted from Idle");
+
+        assert!(matches!(
+            result.new_state,
+            ConvState::LlmRequesting { attempt: 1 }
+        ));
+        assert!(result.effects.iter().any(|e| matches!(
+            e,
+            Effect::PersistMessage {
+                content: crate::db::MessageContent::User(user),
+                message_id,
+                ..
+            } if user.is_meta && user.text == "cargo test failed" && message_id == "verify-1"
+        )));
+        assert!(result
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::RequestLlm)));
+    }
+
+    #[test]
+    fn stale_verify_failed_is_dropped() {
+        let state = ConvState::LlmRequesting { attempt: 1 };
+        let result = transition(&state, &test_context(), verify_failed())
+            .expect("stale verify result is not an error");
+        assert!(matches!(
+            result.new_state,
+            ConvState::LlmRequesting { attempt: 1 }
+        ));
+        assert!(result.effects.is_empty());
+    }
+
+    fn paused_state() -> ConvState {
+        ConvState::AwaitingUserGuidance {
+            reason: "made 400 tool calls this turn".to_string(),
+        }
+    }
+
+    #[test]
+    fn turn_budget_exceeded_pauses_for_guidance() {
+        let event = Event::TurnBudgetExceeded {
+            reason: "made 400 tool calls this turn".to_string(),
+        };
+        let state = ConvState::LlmRequesting { attempt: 1 };
+        let result = transition(&state, &test_context(), event).expect("pause accepted");
+
+        assert_eq!(result.new_state, paused_state());
+        assert!(result.effects.iter().any(|e| matches!(
+            e,
+            Effect::PersistMessage {
+                content: crate::db::MessageContent::System(msg),
+                ..
+            } if msg.text.contains("made 400 tool calls")
+        )));
+        assert!(!result
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::RequestLlm)));
+    }
+
+    #[test]
+    fn user_message_resumes_a_paused_turn() {
+        let event = Event::UserMessage {
+            text: "keep going".to_string(),
+            llm_text: None,
+            images: vec![],
+            message_id: "msg-1".to_string(),
+            user_agent: None,
+            skill_invocation: None,
+        };
+        assert!(check_user_message_acceptable(&paused_state()).is_ok());
+        let result = transition(&paused_state(), &test_context(), event).expect("resume accepted");
+
+        assert!(matches!(
+            result.new_state,
+            ConvState::LlmRequesting { attempt: 1 }
+        ));
+        assert!(result
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::RequestLlm)));
+    }
+
+    #[test]
+    fn cancel_from_paused_turn_goes_idle() {
+        let event = Event::UserCancel { reason: None };
+        let result = transition(&paused_state(), &test_context(), event).expect("cancel accepted");
+        assert_eq!(result.new_state, ConvState::Idle);
+    }
+
+    #[test]
+    fn user_trigger_continuation_from_error_starts_continuation() {
+        let result = transition(
+            &error_state(),
+            &test_context(),
+            Event::UserTriggerContinuation,
+        )
+        .expect("compaction offered from Error");
+
+        assert!(matches!(
+            result.new_state,
+            ConvState::AwaitingContinuation { attempt: 1, .. }
+        ));
+        assert!(result
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::RequestContinuation { .. })));
+    }
+
+    #[test]
+    fn user_trigger_continuation_from_idle_still_starts_continuation() {
+        // Regression guard: the absorb arm must not steal the Idle path,
+        // which is the actual user-initiated continuation flow.
+        let result = transition(
+            &ConvState::Idle,
+            &test_context(),
+            Event::UserTriggerContinuation,
+        )
+        .expect("Idle path should succeed");
+
+        assert!(
+            matches!(
+                result.new_state,
                 ConvState::AwaitingContinuation { attempt: 1, .. }
             ),
             "Idle + UserTriggerContinuation must enter AwaitingContinuation, got {:?}",
@@ -3271,4 +4597,218 @@ mod tests {
             "Idle path must fire RequestContinuation effect"
         );
     }
+
+    fn three_bash_tools() -> ConvState {
+        use crate::llm::ContentBlock;
+        use crate::state_machine::state::{
+            AssistantMessage, BashInput, BashMode, ToolCall, ToolInput,
+        };
+
+        let bash = |id: &str| {
+            ToolCall::new(
+                id,
+                ToolInput::Bash(BashInput {
+                    command: format!("echo {id}"),
+                    mode: BashMode::Default,
+                }),
+            )
+        };
+        let assistant_message = AssistantMessage::new(
+            ["tool-1", "tool-2", "tool-3"]
+                .into_iter()
+                .map(|id| ContentBlock::tool_use(id, "bash", serde_json::json!({})))
+                .collect(),
+            None,
+            None,
+        );
+        ConvState::ToolExecuting {
+            current_tool: bash("tool-1"),
+            remaining_tools: vec![bash("tool-2"), bash("tool-3")],
+            completed_results: vec![],
+            pending_sub_agents: vec![],
+            assistant_message,
+        }
+    }
+
+    fn complete(state: &ConvState, id: &str) -> TransitionResult {
+        let event = Event::ToolComplete {
+            tool_use_id: id.to_string(),
+            result: ToolResult::success(id.to_string(), "ok".to_string()),
+        };
+        transition(state, &test_context(), event).unwrap()
+    }
+
+    #[test]
+    fn cancelling_a_queued_tool_skips_it_and_keeps_the_round_going() {
+        let state = three_bash_tools();
+        assert!(check_tool_cancellable(&state, "tool-2").is_ok());
+        let event = Event::CancelSpecificTool {
+            tool_use_id: "tool-2".to_string(),
+        };
+        let skipped = transition(&state, &test_context(), event).unwrap();
+        let ConvState::ToolExecuting {
+            current_tool,
+            remaining_tools,
+            ..
+        } = &skipped.new_state
+        else {
+            panic!("expected ToolExecuting, got {:?}", skipped.new_state);
+        };
+        assert_eq!(current_tool.id, "tool-1");
+        assert_eq!(remaining_tools.len(), 1);
+        assert!(!skipped
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::AbortTool { .. })));
+
+        let next = complete(&skipped.new_state, "tool-1");
+        let ConvState::ToolExecuting { current_tool, .. } = &next.new_state else {
+            panic!("expected ToolExecuting, got {:?}", next.new_state);
+        };
+        assert_eq!(current_tool.id, "tool-3");
+
+        let done = complete(&next.new_state, "tool-3");
+        assert!(matches!(done.new_state, ConvState::LlmRequesting { .. }));
+        let Some(Effect::PersistCheckpoint { data }) = done
+            .effects
+            .iter()
+            .find(|e| matches!(e, Effect::PersistCheckpoint { .. }))
+        else {
+            panic!("expected a checkpoint");
+        };
+        let CheckpointData::ToolRound { tool_results, .. } = data;
+        let ids: Vec<&str> = tool_results
+            .iter()
+            .map(|r| r.tool_use_id.as_str())
+            .collect();
+        assert_eq!(ids, ["tool-1", "tool-2", "tool-3"]);
+        assert!(!tool_results[1].is_success());
+    }
+
+    #[test]
+    fn only_queued_tools_can_be_cancelled_one_by_one() {
+        let state = three_bash_tools();
+        for id in ["tool-1", "no-such-tool"] {
+            assert!(matches!(
+                check_tool_cancellable(&state, id),
+                Err(TransitionError::ToolNotQueued)
+            ));
+            let event = Event::CancelSpecificTool {
+                tool_use_id: id.to_string(),
+            };
+            let err = transition(&state, &test_context(), event).unwrap_err();
+            assert!(matches!(err, TransitionError::ToolNotQueued), "{id}: {err}");
+        }
+        assert!(matches!(
+            check_tool_cancellable(&ConvState::Idle, "tool-2"),
+            Err(TransitionError::ToolNotQueued)
+        ));
+    }
+
+    fn council_state() -> ConvState {
+        use crate::llm::ContentBlock;
+        use crate::state_machine::state::CouncilCandidate;
+        let candidate = |id: &str, error: Option<&str>| CouncilCandidate {
+            id: id.to_string(),
+            model: format!("model-{id}"),
+            content: match error {
+                None => vec![ContentBlock::text(format!("answer {id}"))],
+                Some(_) => vec![],
+            },
+            tool_calls: vec![],
+            usage: UsageData::default(),
+            error: error.map(ToString::to_string),
+        };
+        ConvState::AwaitingCouncilChoice {
+            candidates: vec![candidate("a", None), candidate("b", Some("down"))],
+        }
+    }
+
+    #[test]
+    fn council_message_requests_the_council_instead_of_the_model() {
+        let event = Event::UserCouncilMessage {
+            text: "which is faster?".to_string(),
+            llm_text: None,
+            images: vec![],
+            message_id: "msg-1".to_string(),
+            user_agent: None,
+            skill_invocation: None,
+            models: vec!["model-a".to_string(), "model-b".to_string()],
+        };
+        let result = transition(&ConvState::Idle, &test_context(), event).expect("accepted");
+
+        assert!(matches!(
+            result.new_state,
+            ConvState::LlmRequesting { attempt: 1 }
+        ));
+        assert!(result.effects.iter().any(|e| matches!(
+            e,
+            Effect::RequestCouncil { models } if models.len() == 2
+        )));
+        assert!(!result
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::RequestLlm)));
+    }
+
+    #[test]
+    fn council_responses_wait_for_a_choice() {
+        let ConvState::AwaitingCouncilChoice { candidates } = council_state() else {
+            unreachable!()
+        };
+        let event = Event::CouncilResponses { candidates };
+        let state = ConvState::LlmRequesting { attempt: 1 };
+        let result = transition(&state, &test_context(), event).expect("responses accepted");
+
+        assert_eq!(result.new_state, council_state());
+        assert!(result
+            .effects
+            .iter()
+            .any(|e| matches!(e, Effect::PersistState)));
+        assert!(check_user_message_acceptable(&result.new_state).is_err());
+    }
+
+    #[test]
+    fn chosen_council_answer_continues_as_the_response() {
+        let event = Event::CouncilChoice {
+            candidate_id: "a".to_string(),
+        };
+        let result = transition(&council_state(), &test_context(), event).expect("choice");
+
+        assert!(!matches!(
+            result.new_state,
+            ConvState::AwaitingCouncilChoice { .. }
+        ));
+        assert!(result.effects.iter().any(|e| matches!(
+            e,
+            Effect::PersistMessage {
+                content: crate::db::MessageContent::Agent(blocks),
+                ..
+            } if blocks.iter().any(|b| matches!(
+                b,
+                crate::llm::ContentBlock::Text { text } if text == "answer a"
+            ))
+        )));
+    }
+
+    #[test]
+    fn failed_or_unknown_council_answers_cannot_be_chosen() {
+        for id in ["b", "zzz"] {
+            let event = Event::CouncilChoice {
+                candidate_id: id.to_string(),
+            };
+            let err = transition(&council_state(), &test_context(), event).unwrap_err();
+            assert!(
+                matches!(err, TransitionError::UnknownCouncilCandidate),
+                "{id}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn cancel_from_council_choice_goes_idle() {
+        let event = Event::UserCancel { reason: None };
+        let result = transition(&council_state(), &test_context(), event).expect("cancel");
+        assert_eq!(result.new_state, ConvState::Idle);
+    }
 }
@@ -62,6 +62,10 @@ pub struct SkillMetadata {
     pub argument_hint: Option<String>,
     /// Where this skill was discovered (e.g., ".claude/skills" or ".agents/skills")
     pub source: String,
+    /// Lowercased trigger keywords (from the `triggers:` frontmatter field).
+    /// A skill whose trigger appears in the latest user message has its body
+    /// included in the system prompt for that turn (REQ-SK-008).
+    pub triggers: Vec<String>,
 }
 
 /// Parsed frontmatter fields from a SKILL.md file
@@ -69,9 +73,14 @@ struct SkillFrontmatter {
     name: String,
     description: String,
     argument_hint: Option<String>,
+    triggers: Vec<String>,
 }
 
-/// Parse `name`, `description`, and optional `argument-hint` from SKILL.md YAML frontmatter.
+/// Parse `name`, `description`, and optional `argument-hint` / `triggers`
+/// from SKILL.md YAML frontmatter.
+///
+/// `triggers` accepts either a comma-separated list (`triggers: a, b`) or a
+/// YAML flow sequence (`triggers: [a, b]`).
 ///
 /// Expects the file to start with `---\n`, followed by `key: value` lines,
 /// closed by `\n---\n`. Returns `None` if either required field is missing or the
@@ -89,6 +98,7 @@ fn parse_skill_frontmatter(content: &str) -> Option<SkillFrontmatter> {
     let mut name: Option<String> = None;
     let mut description: Option<String> = None;
     let mut argument_hint: Option<String> = None;
+    let mut triggers: Vec<String> = Vec::new();
 
     for line in frontmatter.lines() {
         if let Some(val) = line.strip_prefix("name:") {
@@ -100,6 +110,8 @@ fn parse_skill_frontmatter(content: &str) -> Option<SkillFrontmatter> {
             if !hint.is_empty() {
                 argument_hint = Some(hint);
             }
+        } else if let Some(val) = line.strip_prefix("triggers:") {
+            triggers = parse_trigger_list(val);
         }
     }
 
@@ -107,12 +119,36 @@ fn parse_skill_frontmatter(content: &str) -> Option<SkillFrontmatter> {
         name: name?,
         description: description?,
         argument_hint,
+        triggers,
     })
 }
 
+/// Split a `triggers:` value into lowercased, de-quoted keywords.
+fn parse_trigger_list(raw: &str) -> Vec<String> {
+    let trimmed = raw.trim();
+    let inner = trimmed
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .unwrap_or(trimmed);
+    inner
+        .split(',')
+        .map(|t| {
+            t.trim()
+                .trim_matches(|c: char| c == '"' || c == '\'')
+                .to_lowercase()
+        })
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
 /// Subdirectories to scan for skill directories at each level of the tree.
 const SKILL_DIRS: &[&str] = &[".claude/skills", ".agents/skills"];
 
+/// User-level skill library, relative to `$HOME`. Unlike `SKILL_DIRS`, skills
+/// here are flat `<name>.md` files managed through the `/api/skills` CRUD
+/// endpoints (REQ-SK-009).
+pub const LIBRARY_SKILL_DIR: &str = ".phoenix-ide/skills";
+
 /// Collect skills from a single skills directory (e.g., `.claude/skills/`).
 ///
 /// Scans immediate child directories for `SKILL.md` files. For each skill found,
@@ -170,6 +206,7 @@ fn collect_skills_from_dir(
                     argument_hint: fm.argument_hint,
                     path: skill_md,
                     source: source.to_string(),
+                    triggers: fm.triggers,
                 });
             }
             // Recurse into skills/ subdirectory for namespaced sub-skills
@@ -189,6 +226,75 @@ fn collect_skills_from_dir(
     }
 }
 
+/// Collect flat `<name>.md` skills from the user-level skill library.
+///
+/// Library skills have the lowest precedence: a project or `$HOME/.claude`
+/// skill with the same name shadows the library entry.
+fn collect_library_skills(
+    library_dir: &Path,
+    skills: &mut Vec<SkillMetadata>,
+    seen_names: &mut HashSet<String>,
+    seen_paths: &mut HashSet<PathBuf>,
+    seen_content: &mut HashSet<u64>,
+) {
+    let Ok(entries) = std::fs::read_dir(library_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some("md") {
+            continue;
+        }
+        let canonical = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if !seen_paths.insert(canonical) {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let content_hash = {
+            let mut hasher = std::hash::DefaultHasher::new();
+            content.hash(&mut hasher);
+            hasher.finish()
+        };
+        if !seen_content.insert(content_hash) {
+            continue;
+        }
+        let Some(fm) = parse_skill_frontmatter(&content) else {
+            tracing::debug!(
+                path = %path.display(),
+                "Skipping library skill with invalid frontmatter"
+            );
+            continue;
+        };
+        if seen_names.insert(fm.name.clone()) {
+            skills.push(SkillMetadata {
+                name: fm.name,
+                description: fm.description,
+                argument_hint: fm.argument_hint,
+                path,
+                source: LIBRARY_SKILL_DIR.to_string(),
+                triggers: fm.triggers,
+            });
+        }
+    }
+}
+
+/// List the skills in a library directory on their own, sorted by name.
+/// Backs the skill library CRUD endpoints (REQ-SK-009).
+pub fn list_library_skills(library_dir: &Path) -> Vec<SkillMetadata> {
+    let mut skills = Vec::new();
+    collect_library_skills(
+        library_dir,
+        &mut skills,
+        &mut HashSet::new(),
+        &mut HashSet::new(),
+        &mut HashSet::new(),
+    );
+    skills.sort_by(|a, b| a.name.cmp(&b.name));
+    skills
+}
+
 /// Discover skills by walking from `working_dir` up to the filesystem root.
 ///
 /// At each level, scans `SKILL_DIRS` (`.claude/skills/` and `.agents/skills/`)
@@ -200,7 +306,8 @@ fn collect_skills_from_dir(
 /// counted once (first discovered wins).
 ///
 /// After the walk-up, explicitly scans `$HOME/.claude/skills/` and
-/// `$HOME/.agents/skills/` in case `$HOME` is not an ancestor of `working_dir`.
+/// `$HOME/.agents/skills/` in case `$HOME` is not an ancestor of `working_dir`,
+/// then the flat skill library at `$HOME/.phoenix-ide/skills/`.
 /// Pass `home_override` to control which directory is treated as `$HOME`
 /// (useful for testing without mutating process-global env vars).
 ///
@@ -308,12 +415,136 @@ pub fn discover_skills_with_home(
                 &mut seen_content,
             );
         }
+        collect_library_skills(
+            &home.join(LIBRARY_SKILL_DIR),
+            &mut skills,
+            &mut seen_names,
+            &mut seen_paths,
+            &mut seen_content,
+        );
     }
 
     skills.sort_by(|a, b| a.name.cmp(&b.name));
     skills
 }
 
+/// Skills whose trigger keywords appear in `text` (REQ-SK-008).
+///
+/// Matching is case-insensitive and whole-word: a trigger of `test` matches
+/// "write a test" but not "attestation". Multi-word triggers match as a
+/// phrase.
+pub fn triggered_skills<'a>(skills: &'a [SkillMetadata], text: &str) -> Vec<&'a SkillMetadata> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric() && c != '-' && c != '_')
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let normalized = format!(" {} ", words.join(" "));
+    skills
+        .iter()
+        .filter(|s| {
+            s.triggers
+                .iter()
+                .any(|t| normalized.contains(&format!(" {t} ")))
+        })
+        .collect()
+}
+
+/// Render the bodies of triggered skills as a system prompt section.
+///
+/// Returns `None` when no skill matches. Bodies go through
+/// [`crate::skills::invoke_skill`] so they are expanded exactly as they would
+/// be for `/skill` or the `skill` tool (REQ-SK-005).
+pub fn build_triggered_skills_section(working_dir: &Path, user_text: &str) -> Option<String> {
+    let skills = discover_skills(working_dir);
+    let matched = triggered_skills(&skills, user_text);
+    if matched.is_empty() {
+        return None;
+    }
+    let mut section = String::from("<triggered_skills>\n");
+    section.push_str(
+        "The following skills matched keywords in the user's latest message. \
+         Follow them where they apply.\n",
+    );
+    for skill in matched {
+        match crate::skills::invoke_skill(&skill.name, "", &skills) {
+            Ok(invocation) => {
+                let _ = write!(
+                    section,
+                    "\n<skill name=\"{}\">\n{}\n</skill>\n",
+                    invocation.name, invocation.body
+                );
+            }
+            Err(e) => {
+                tracing::warn!(skill = %skill.name, error = %e, "Failed to load triggered skill");
+            }
+        }
+    }
+    section.push_str("</triggered_skills>");
+    Some(section)
+}
+
+/// Most files listed in the touched-files section; the rest are counted.
+const MAX_TOUCHED_FILES: usize = 50;
+
+/// List the files this conversation's tools have read or edited, most
+/// recent first (REQ-BED-042).
+///
+/// Returns `None` before any file has been touched. Paths under
+/// `working_dir` are shown relative to it.
+pub fn build_touched_files_section(
+    working_dir: &Path,
+    files: &[crate::db::TouchedFile],
+) -> Option<String> {
+    if files.is_empty() {
+        return None;
+    }
+    let mut section = String::from("<files_touched>\n");
+    section.push_str(
+        "Files you have already read or edited in this conversation, most recent first. \
+         Re-read a file before editing it if it may have changed since.\n",
+    );
+    for file in files.iter().take(MAX_TOUCHED_FILES) {
+        let path = Path::new(&file.path);
+        let shown = path.strip_prefix(working_dir).unwrap_or(path);
+        let _ = writeln!(
+            section,
+            "- {} (read {}, edited {})",
+            shown.display(),
+            file.read_count,
+            file.edit_count
+        );
+    }
+    if files.len() > MAX_TOUCHED_FILES {
+        let _ = writeln!(section, "...and {} more", files.len() - MAX_TOUCHED_FILES);
+    }
+    section.push_str("</files_touched>");
+    Some(section)
+}
+
+/// Describe the directories the conversation may use besides its working
+/// directory (REQ-BED-049), or `None` when it has none.
+pub fn build_extra_roots_section(roots: &[crate::db::ConversationRoot]) -> Option<String> {
+    if roots.is_empty() {
+        return None;
+    }
+    let mut section = String::from("<additional_roots>\n");
+    section.push_str(
+        "Besides the working directory, this conversation may use these directories. \
+         Refer to them by absolute path. Do not modify the read-only ones.\n",
+    );
+    for root in roots {
+        let access = if root.writable {
+            "read-write"
+        } else {
+            "read-only"
+        };
+        let _ = writeln!(section, "- {} ({access})", root.path);
+    }
+    section.push_str("</additional_roots>");
+    Some(section)
+}
+
 /// Discover guidance files from the working directory up to the root.
 /// Returns files in order from root to cwd (more specific files last).
 pub fn discover_guidance_files(working_dir: &Path) -> Vec<GuidanceFile> {
@@ -937,6 +1168,136 @@ mod tests {
         );
     }
 
+    // -------------------------------------------------------------------------
+    // Skill library + triggers (REQ-SK-008, REQ-SK-009)
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_parse_frontmatter_triggers() {
+        let flow = "---\nname: a\ndescription: d\ntriggers: [Review, \"code review\"]\n---\n";
+        assert_eq!(
+            parse_skill_frontmatter(flow).unwrap().triggers,
+            vec!["review", "code review"]
+        );
+        let plain = "---\nname: a\ndescription: d\ntriggers: deploy, 'ship it'\n---\n";
+        assert_eq!(
+            parse_skill_frontmatter(plain).unwrap().triggers,
+            vec!["deploy", "ship it"]
+        );
+        let none = "---\nname: a\ndescription: d\n---\n";
+        assert!(parse_skill_frontmatter(none).unwrap().triggers.is_empty());
+    }
+
+    #[test]
+    fn test_discover_library_skills_lowest_precedence() {
+        let temp = TempDir::new().unwrap();
+        let library = temp.path().join(LIBRARY_SKILL_DIR);
+        fs::create_dir_all(&library).unwrap();
+        fs::write(
+            library.join("review.md"),
+            "---\nname: review\ndescription: Library review\ntriggers: [review]\n---\nBody\n",
+        )
+        .unwrap();
+        fs::write(
+            library.join("build.md"),
+            "---\nname: build\ndescription: Library build\n---\nBody\n",
+        )
+        .unwrap();
+        fs::write(library.join("notes.txt"), "not a skill").unwrap();
+        write_skill(
+            temp.path(),
+            ".claude/skills",
+            "build",
+            "build",
+            "Project build",
+        );
+
+        let skills = discover_skills_with_home(temp.path(), Some(temp.path()));
+        assert_eq!(skills.len(), 2);
+        assert_eq!(skills[0].name, "build");
+        assert_eq!(skills[0].description, "Project build");
+        assert_eq!(skills[1].name, "review");
+        assert_eq!(skills[1].source, LIBRARY_SKILL_DIR);
+        assert_eq!(skills[1].triggers, vec!["review"]);
+    }
+
+    #[test]
+    fn test_triggered_skills_whole_word_match() {
+        let skill = |name: &str, triggers: &[&str]| SkillMetadata {
+            name: name.to_string(),
+            description: String::new(),
+            path: PathBuf::new(),
+            argument_hint: None,
+            source: LIBRARY_SKILL_DIR.to_string(),
+            triggers: triggers.iter().map(ToString::to_string).collect(),
+        };
+        let skills = vec![
+            skill("testing", &["test"]),
+            skill("review", &["code review"]),
+            skill("silent", &[]),
+        ];
+
+        let names = |text: &str| -> Vec<String> {
+            triggered_skills(&skills, text)
+                .into_iter()
+                .map(|s| s.name.clone())
+                .collect()
+        };
+        assert_eq!(names("Please write a TEST."), vec!["testing"]);
+        assert!(names("check the attestation").is_empty());
+        assert_eq!(names("Do a code  review, thanks"), vec!["review"]);
+        assert!(names("review the code").is_empty());
+    }
+
+    #[test]
+    fn test_touched_files_section() {
+        let file = |path: &str, reads: i64, edits: i64| crate::db::TouchedFile {
+            path: path.to_string(),
+            read_count: reads,
+            edit_count: edits,
+            first_touched_at: chrono::Utc::now(),
+            last_touched_at: chrono::Utc::now(),
+        };
+        let dir = Path::new("/work/repo");
+        assert!(build_touched_files_section(dir, &[]).is_none());
+
+        let files = vec![
+            file("/work/repo/src/lib.rs", 2, 1),
+            file("/etc/hosts", 1, 0),
+        ];
+        let section = build_touched_files_section(dir, &files).unwrap();
+        assert!(section.starts_with("<files_touched>"));
+        assert!(section.contains("- src/lib.rs (read 2, edited 1)"));
+        assert!(section.contains("- /etc/hosts (read 1, edited 0)"));
+
+        let many: Vec<_> = (0..60)
+            .map(|i| file(&format!("/work/repo/{i}.rs"), 1, 0))
+            .collect();
+        let section = build_touched_files_section(dir, &many).unwrap();
+        assert!(section.contains("- 49.rs"));
+        assert!(!section.contains("- 50.rs"));
+        assert!(section.contains("...and 10 more"));
+    }
+
+    #[test]
+    fn test_extra_roots_section() {
+        assert!(build_extra_roots_section(&[]).is_none());
+        let roots = [
+            crate::db::ConversationRoot {
+                path: "/srv/shared-lib".to_string(),
+                writable: false,
+            },
+            crate::db::ConversationRoot {
+                path: "/srv/scratch".to_string(),
+                writable: true,
+            },
+        ];
+        let section = build_extra_roots_section(&roots).unwrap();
+        assert!(section.starts_with("<additional_roots>"));
+        assert!(section.contains("- /srv/shared-lib (read-only)"));
+        assert!(section.contains("- /srv/scratch (read-write)"));
+    }
+
     #[test]
     fn test_work_mode_prompt_includes_worktree_boundary() {
         let temp = TempDir::new().unwrap();
@@ -1,23 +1,109 @@
 //! Conversation title generation using a fast/cheap LLM
 //!
-//! Generates short, meaningful titles based on the initial user message.
+//! Generates short, meaningful titles based on the initial user message, or
+//! on the conversation so far when retitling (REQ-API-012). Also owns the
+//! random `day-time-word-word` fallback slug (REQ-API-002).
 
+use crate::db::{Message, MessageContent};
 use crate::llm::{
-    ContentBlock, LlmMessage, LlmRequest, LlmResponse, LlmService, MessageRole, PromptCacheKey,
+    complete_json, ContentBlock, LlmMessage, LlmRequest, LlmService, MessageRole, PromptCacheKey,
 };
+use chrono::{Datelike, Local, Timelike};
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+use serde_json::json;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::timeout;
 
-const TITLE_PROMPT: &str = r#"Generate a very short (3-6 words) title summarizing this request. Output only the title, no quotes or punctuation. Examples:
+const TITLE_PROMPT: &str = r#"Generate a very short (3-6 words) title summarizing this request. No quotes or punctuation in the title. Examples:
 - "Fix login page CSS bug" -> Fix Login Page CSS
 - "Help me write a Python script to parse CSV files" -> Python CSV Parser Script
 - "What's the best way to implement caching?" -> Implementing Caching Strategy
 
 Request:"#;
 
+const HISTORY_TITLE_PROMPT: &str = r"Generate a very short (3-6 words) title summarizing what this conversation is about. Weigh the whole exchange, not just the opening request. No quotes or punctuation in the title.
+
+Conversation:";
+
+/// Covers the repair attempt too (REQ-LLM-015).
 const TITLE_TIMEOUT: Duration = Duration::from_secs(5);
 const MAX_TITLE_LENGTH: usize = 60;
+/// Budget for the transcript excerpt sent with `HISTORY_TITLE_PROMPT`.
+const HISTORY_EXCERPT_CHARS: usize = 4000;
+/// Per-message cap so one long paste cannot crowd out the rest of the excerpt.
+const HISTORY_MESSAGE_CHARS: usize = 600;
+
+const SLUG_DAYS: &[&str] = &[
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+const SLUG_TIMES: &[&str] = &["morning", "afternoon", "evening", "night"];
+const SLUG_WORDS: &[&str] = &[
+    "autumn",
+    "river",
+    "mountain",
+    "forest",
+    "meadow",
+    "ocean",
+    "desert",
+    "valley",
+    "sunrise",
+    "sunset",
+    "thunder",
+    "lightning",
+    "rainbow",
+    "crystal",
+    "shadow",
+    "light",
+    "ancient",
+    "swift",
+    "quiet",
+    "brave",
+    "golden",
+    "silver",
+    "azure",
+    "emerald",
+    "phoenix",
+    "dragon",
+    "falcon",
+    "wolf",
+    "raven",
+    "tiger",
+    "eagle",
+    "fox",
+    "dream",
+    "spark",
+    "flame",
+    "frost",
+    "storm",
+    "breeze",
+    "tide",
+    "star",
+];
+
+/// Why a history-based retitle did not happen (REQ-API-012).
+#[derive(thiserror::Error, Debug)]
+pub enum RetitleError {
+    #[error("No cheap model available for title generation")]
+    NoModel,
+    #[error("Title generation produced no usable title")]
+    NoTitle,
+    #[error(transparent)]
+    Db(#[from] crate::db::DbError),
+}
+
+/// Structured reply from the title model (REQ-LLM-015).
+#[derive(Deserialize)]
+struct TitleReply {
+    title: String,
+}
 
 /// Generate a title for a conversation based on the initial message.
 ///
@@ -34,8 +120,26 @@ pub async fn generate_title(
         message_text.to_string()
     };
 
-    let prompt = format!("{TITLE_PROMPT}\n{truncated}");
+    request_title(format!("{TITLE_PROMPT}\n{truncated}"), llm_service).await
+}
 
+/// Generate a title from the conversation so far (REQ-API-012).
+///
+/// Uses user messages and assistant text; tool traffic, system notes, and
+/// meta messages are skipped. Returns None when there is nothing to
+/// summarize or the LLM call fails.
+pub async fn generate_title_from_history(
+    messages: &[Message],
+    llm_service: Arc<dyn LlmService>,
+) -> Option<String> {
+    let excerpt = history_excerpt(messages);
+    if excerpt.is_empty() {
+        return None;
+    }
+    request_title(format!("{HISTORY_TITLE_PROMPT}\n{excerpt}"), llm_service).await
+}
+
+async fn request_title(prompt: String, llm_service: Arc<dyn LlmService>) -> Option<String> {
     let request = LlmRequest {
         system: vec![],
         messages: vec![LlmMessage {
@@ -43,22 +147,29 @@ pub async fn generate_title(
             content: vec![ContentBlock::text(prompt)],
         }],
         tools: vec![],
-        max_tokens: Some(50), // Title should be very short
-        // Shared by every title-generation call so TITLE_PROMPT caches.
+        max_tokens: Some(64), // Title should be very short
+        thinking_budget: None,
+        // Shared by every title-generation call so the prompt prefix caches.
         cache_key: PromptCacheKey::stable("title-generator"),
     };
 
+    let schema = json!({
+        "type": "object",
+        "required": ["title"],
+        "properties": {"title": {"type": "string", "minLength": 1}}
+    });
+
     // Apply timeout
-    let result = timeout(TITLE_TIMEOUT, llm_service.complete(&request)).await;
+    let result = timeout(
+        TITLE_TIMEOUT,
+        complete_json::<TitleReply>(&*llm_service, request, &schema),
+    )
+    .await;
 
     match result {
-        Ok(Ok(response)) => {
-            // Extract text from response
-            let title = extract_title_from_response(&response);
-            title.map(|t| sanitize_title(&t))
-        }
+        Ok(Ok(reply)) => Some(sanitize_title(&reply.title)).filter(|t| !t.is_empty()),
         Ok(Err(e)) => {
-            tracing::warn!("Title generation LLM error: {}", e.message);
+            tracing::warn!("Title generation failed: {e}");
             None
         }
         Err(_) => {
@@ -68,17 +179,88 @@ pub async fn generate_title(
     }
 }
 
-/// Extract the title text from the LLM response
-fn extract_title_from_response(response: &LlmResponse) -> Option<String> {
-    for block in &response.content {
-        if let ContentBlock::Text { text } = block {
-            let trimmed = text.trim();
-            if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
-            }
+/// Render user and assistant text as a `Speaker: text` transcript, clipped
+/// per message and overall.
+fn history_excerpt(messages: &[Message]) -> String {
+    let mut out = String::new();
+    for msg in messages {
+        let (speaker, text) = match &msg.content {
+            MessageContent::User(u) if !u.is_meta => ("User", u.text.clone()),
+            MessageContent::Agent(blocks) => (
+                "Assistant",
+                blocks
+                    .iter()
+                    .filter_map(|b| match b {
+                        ContentBlock::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ),
+            _ => continue,
+        };
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let clipped = match text.char_indices().nth(HISTORY_MESSAGE_CHARS) {
+            Some((cut, _)) => format!("{}...", text.get(..cut).unwrap_or(text)),
+            None => text.to_string(),
+        };
+        let line = format!("{speaker}: {clipped}\n");
+        if out.len() + line.len() > HISTORY_EXCERPT_CHARS {
+            break;
         }
+        out.push_str(&line);
     }
-    None
+    out
+}
+
+/// Random fallback slug (`day-time-word-word`) used when no LLM title is
+/// available (REQ-API-002).
+pub fn random_slug() -> String {
+    let now = Local::now();
+    let day = match now.weekday() {
+        chrono::Weekday::Mon => "monday",
+        chrono::Weekday::Tue => "tuesday",
+        chrono::Weekday::Wed => "wednesday",
+        chrono::Weekday::Thu => "thursday",
+        chrono::Weekday::Fri => "friday",
+        chrono::Weekday::Sat => "saturday",
+        chrono::Weekday::Sun => "sunday",
+    };
+    let time = match now.hour() {
+        6..=11 => "morning",
+        12..=16 => "afternoon",
+        17..=20 => "evening",
+        _ => "night",
+    };
+
+    let mut rng = rand::thread_rng();
+    let adjective = SLUG_WORDS.choose(&mut rng).unwrap_or(&"blue");
+    let noun = SLUG_WORDS.choose(&mut rng).unwrap_or(&"sky");
+
+    format!("{day}-{time}-{adjective}-{noun}")
+}
+
+/// Whether `slug` has the shape produced by [`random_slug`], including the
+/// hex suffix the DB appends on collision. Used to decide whether a
+/// conversation still needs a meaningful title (REQ-API-012).
+pub fn is_random_slug(slug: &str) -> bool {
+    let parts: Vec<&str> = slug.split('-').collect();
+    let suffix_ok = match parts.get(4..) {
+        Some([]) => true,
+        Some([suffix]) => {
+            matches!(suffix.len(), 4 | 8) && suffix.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        _ => false,
+    };
+    suffix_ok
+        && parts.len() >= 4
+        && SLUG_DAYS.contains(&parts[0])
+        && SLUG_TIMES.contains(&parts[1])
+        && SLUG_WORDS.contains(&parts[2])
+        && SLUG_WORDS.contains(&parts[3])
 }
 
 /// Sanitize the title for use as a slug
@@ -136,4 +318,60 @@ mod tests {
         let result = sanitize_title(long_title);
         assert!(result.len() <= MAX_TITLE_LENGTH);
     }
+
+    fn msg(seq: i64, content: MessageContent) -> Message {
+        Message {
+            message_id: format!("m{seq}"),
+            conversation_id: "c".to_string(),
+            sequence_id: seq,
+            message_type: content.message_type(),
+            content,
+            display_data: None,
+            usage_data: None,
+            model: None,
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn random_slug_is_recognized() {
+        for _ in 0..20 {
+            let slug = random_slug();
+            assert!(is_random_slug(&slug), "{slug}");
+            assert!(is_random_slug(&format!("{slug}-a3f0")));
+        }
+    }
+
+    #[test]
+    fn generated_titles_are_not_random_slugs() {
+        assert!(!is_random_slug("fix-login-page-css"));
+        assert!(!is_random_slug("monday-morning-fox"));
+        assert!(!is_random_slug("monday-morning-fox-star-extra-words"));
+        assert!(!is_random_slug("monday-morning-fox-star-zzzz"));
+    }
+
+    #[test]
+    fn history_excerpt_keeps_user_and_assistant_text() {
+        let messages = vec![
+            msg(1, MessageContent::user("Fix the flaky test")),
+            msg(
+                2,
+                MessageContent::agent(vec![ContentBlock::text("Looking at the test now.")]),
+            ),
+            msg(3, MessageContent::tool("t1", "cargo output", false)),
+        ];
+        let excerpt = history_excerpt(&messages);
+        assert_eq!(
+            excerpt,
+            "User: Fix the flaky test\nAssistant: Looking at the test now.\n"
+        );
+    }
+
+    #[test]
+    fn history_excerpt_clips_long_messages() {
+        let long = "é".repeat(HISTORY_MESSAGE_CHARS * 2);
+        let excerpt = history_excerpt(&[msg(1, MessageContent::user(long))]);
+        assert!(excerpt.ends_with("...\n"));
+        assert!(excerpt.chars().count() < HISTORY_MESSAGE_CHARS + 20);
+    }
 }
@@ -1,4 +1,5 @@
-use axum::Router;
+use axum::{extract::ConnectInfo, Router};
+use hyper::body::Incoming;
 use hyper_util::{
     rt::{TokioExecutor, TokioIo},
     server::graceful::GracefulShutdown,
@@ -19,6 +20,7 @@ use std::{
 };
 use tokio::net::TcpListener;
 use tokio_rustls::TlsAcceptor;
+use tower::ServiceExt;
 
 const SHUTDOWN_GRACE: Duration = Duration::from_secs(30);
 
@@ -177,6 +179,12 @@ pub async fn serve_https(
                     log_alpn(peer_addr, &stream);
 
                     let io = TokioIo::new(stream);
+                    // Same ConnectInfo the plain-HTTP path gets from
+                    // `into_make_service_with_connect_info` (REQ-AUTH-009).
+                    let app = app.map_request(move |mut req: hyper::Request<Incoming>| {
+                        req.extensions_mut().insert(ConnectInfo(peer_addr));
+                        req
+                    });
                     let service = TowerToHyperService::new(app);
                     let conn = server.serve_connection_with_upgrades(io, service);
                     let conn = watcher.watch(conn);
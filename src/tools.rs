@@ -2,13 +2,19 @@
 //!
 //! REQ-BASH-010, REQ-BT-012: Stateless Tools with Context Injection
 
+mod archive;
+mod ask_user;
 mod ask_user_question;
 pub mod bash;
 pub mod bash_check;
 pub mod browser;
+mod inspect_binary;
 mod keyword_search;
 pub mod mcp;
 pub mod patch;
+pub mod plugin;
+pub mod preflight;
+mod processes;
 mod propose_task;
 mod read_file;
 mod read_image;
@@ -20,17 +26,23 @@ mod terminal_last_command;
 mod think;
 pub mod tmux;
 
+pub use archive::ArchiveTool;
+pub use ask_user::AskUserTool;
 pub use ask_user_question::AskUserQuestionTool;
 pub use bash::{
     BashHandleError, BashHandleRegistry, BashTool, ConversationHandles as BashConversationHandles,
 };
 pub use browser::{
     BrowserClearConsoleLogsTool, BrowserClickTool, BrowserError, BrowserEvalTool,
-    BrowserKeyPressTool, BrowserNavigateTool, BrowserRecentConsoleLogsTool, BrowserResizeTool,
-    BrowserSessionManager, BrowserTakeScreenshotTool, BrowserTypeTool, BrowserWaitForSelectorTool,
+    BrowserHandleDialogTool, BrowserKeyPressTool, BrowserNavigateTool, BrowserPrintToPdfTool,
+    BrowserQueryTool, BrowserRecentConsoleLogsTool, BrowserResizeTool, BrowserSessionManager,
+    BrowserSetGeolocationTool, BrowserSetLocaleTool, BrowserTakeScreenshotTool,
+    BrowserThrottleTool, BrowserTypeTool, BrowserWaitForSelectorTool,
 };
+pub use inspect_binary::InspectBinaryTool;
 pub use keyword_search::KeywordSearchTool;
 pub use patch::PatchTool;
+pub use processes::{KillProcessTool, ListProcessesTool};
 pub use propose_task::ProposeTaskTool;
 pub use read_file::ReadFileTool;
 pub use read_image::ReadImageTool;
@@ -70,6 +82,12 @@ pub struct ToolOutput {
     pub images: Vec<ToolImage>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display_data: Option<Value>,
+    /// A patch planned in review mode and not yet written (REQ-PATCH-010)
+    #[serde(skip)]
+    pub staged_patch: Option<patch::StagedPatch>,
+    /// The bash command policy's decision, for the audit log (REQ-BASH-018)
+    #[serde(skip)]
+    pub policy_hit: Option<bash::policy::PolicyHit>,
 }
 
 impl ToolOutput {
@@ -79,6 +97,8 @@ impl ToolOutput {
             output: output.into(),
             images: vec![],
             display_data: None,
+            staged_patch: None,
+            policy_hit: None,
         }
     }
 
@@ -88,6 +108,8 @@ impl ToolOutput {
             output: message.into(),
             images: vec![],
             display_data: None,
+            staged_patch: None,
+            policy_hit: None,
         }
     }
 
@@ -101,6 +123,18 @@ impl ToolOutput {
         self.images = images;
         self
     }
+
+    /// Hold `patch` for user review instead of reporting it applied.
+    pub fn with_staged_patch(mut self, patch: patch::StagedPatch) -> Self {
+        self.staged_patch = Some(patch);
+        self
+    }
+
+    /// Record what the bash command policy decided, if anything.
+    pub fn with_policy_hit(mut self, hit: Option<bash::policy::PolicyHit>) -> Self {
+        self.policy_hit = hit;
+        self
+    }
 }
 
 /// All context needed for a tool invocation.
@@ -148,6 +182,30 @@ pub struct ToolContext {
     /// key the socket to the worktree rather than the conversation ID so
     /// the session survives context-exhaustion continuations (task 03001).
     pub worktree_path: Option<PathBuf>,
+
+    /// Stage file edits for user review instead of writing them
+    /// (REQ-PATCH-010). Set per call by the executor.
+    pub review_patches: bool,
+
+    /// Files earlier tool calls in the conversation read or edited, from the
+    /// touched-files index. Filled by the executor for `keyword_search`
+    /// (REQ-KWS-006, REQ-BED-042).
+    pub referenced_files: Vec<String>,
+
+    /// Files earlier `patch` calls in the conversation edited, from the same
+    /// index. Filled by the executor for `patch` and `bash`, whose dirty
+    /// check treats changes to them as the conversation's own (REQ-BASH-019).
+    pub edited_files: Vec<String>,
+
+    /// Directories the conversation may use besides `working_dir`
+    /// (REQ-BED-049). Plugins get them preopened alongside it.
+    pub extra_roots: Vec<crate::db::ConversationRoot>,
+
+    /// The shell the bash tool spawns commands in (REQ-BASH-017).
+    pub shell: crate::db::ShellSettings,
+
+    /// Allow/deny lists checked before a bash command spawns (REQ-BASH-018).
+    pub bash_policy: bash::policy::BashPolicy,
 }
 
 impl ToolContext {
@@ -174,9 +232,57 @@ impl ToolContext {
             terminals,
             tmux_registry,
             worktree_path,
+            review_patches: false,
+            referenced_files: Vec::new(),
+            edited_files: Vec::new(),
+            extra_roots: Vec::new(),
+            shell: crate::db::ShellSettings::default(),
+            bash_policy: bash::policy::BashPolicy::default(),
         }
     }
 
+    /// Stage patches for review rather than writing them (REQ-PATCH-010).
+    #[must_use]
+    pub fn with_patch_review(mut self, enabled: bool) -> Self {
+        self.review_patches = enabled;
+        self
+    }
+
+    /// Files the conversation already touched, for search ranking (REQ-KWS-006).
+    #[must_use]
+    pub fn with_referenced_files(mut self, files: Vec<String>) -> Self {
+        self.referenced_files = files;
+        self
+    }
+
+    /// Files the conversation already edited, for the dirty check (REQ-BASH-019).
+    #[must_use]
+    pub fn with_edited_files(mut self, files: Vec<String>) -> Self {
+        self.edited_files = files;
+        self
+    }
+
+    /// The conversation's extra roots (REQ-BED-049).
+    #[must_use]
+    pub fn with_extra_roots(mut self, roots: Vec<crate::db::ConversationRoot>) -> Self {
+        self.extra_roots = roots;
+        self
+    }
+
+    /// The conversation's shell for the bash tool (REQ-BASH-017).
+    #[must_use]
+    pub fn with_shell(mut self, shell: crate::db::ShellSettings) -> Self {
+        self.shell = shell;
+        self
+    }
+
+    /// The server's and the conversation's bash policy (REQ-BASH-018).
+    #[must_use]
+    pub fn with_bash_policy(mut self, policy: bash::policy::BashPolicy) -> Self {
+        self.bash_policy = policy;
+        self
+    }
+
     /// Get or create the browser session for this conversation.
     ///
     /// Lazily initializes Chrome on first call. Subsequent calls return
@@ -185,7 +291,7 @@ impl ToolContext {
     /// REQ-BT-010: Implicit Session Model
     pub async fn browser(&self) -> Result<Arc<RwLock<BrowserSession>>, BrowserError> {
         self.browser_sessions
-            .get_session(&self.conversation_id)
+            .get_session(&self.conversation_id, &self.working_dir)
             .await
     }
 
@@ -205,6 +311,14 @@ impl ToolContext {
         Ok(self.bash_handles.get_or_create(&self.conversation_id).await)
     }
 
+    /// Every bash handle this conversation has spawned, live or exited
+    /// (REQ-BASH-021).
+    pub async fn spawned_bash_handles(&self) -> Vec<Arc<bash::handle::Handle>> {
+        self.bash_handles
+            .conversation_handles(&self.conversation_id)
+            .await
+    }
+
     /// Direct access to the registry (used by the hard-delete cascade
     /// integration in task 02696, and by the shutdown kill-tree pass).
     pub fn bash_handle_registry(&self) -> &Arc<BashHandleRegistry> {
@@ -314,6 +428,7 @@ fn read_only_tools() -> Vec<Arc<dyn Tool>> {
         Arc::new(SearchTool),
         Arc::new(KeywordSearchTool),
         Arc::new(ReadImageTool),
+        Arc::new(InspectBinaryTool),
     ]
 }
 
@@ -326,11 +441,16 @@ fn read_only_tools() -> Vec<Arc<dyn Tool>> {
 /// persistence model (REQ-TMUX-003 / REQ-TMUX-009). When the tmux
 /// binary is unavailable the tool's first invocation returns
 /// `tmux_binary_unavailable` rather than failing at registration.
+/// `ArchiveTool` writes extracted files, so it belongs here with patch.
+/// The process tools manage what bash started and come with it.
 fn write_tools() -> Vec<Arc<dyn Tool>> {
     vec![
         Arc::new(BashTool),
+        Arc::new(ListProcessesTool),
+        Arc::new(KillProcessTool),
         Arc::new(PatchTool::default()),
         Arc::new(TmuxTool),
+        Arc::new(ArchiveTool),
     ]
 }
 
@@ -347,6 +467,12 @@ fn browser_tools() -> Vec<Arc<dyn Tool>> {
         Arc::new(BrowserClickTool),
         Arc::new(BrowserTypeTool),
         Arc::new(BrowserKeyPressTool),
+        Arc::new(BrowserHandleDialogTool),
+        Arc::new(BrowserThrottleTool),
+        Arc::new(BrowserSetGeolocationTool),
+        Arc::new(BrowserSetLocaleTool),
+        Arc::new(BrowserPrintToPdfTool),
+        Arc::new(BrowserQueryTool),
     ]
 }
 
@@ -357,6 +483,7 @@ fn parent_coordination_tools() -> Vec<Arc<dyn Tool>> {
     vec![
         Arc::new(SpawnAgentsTool),
         Arc::new(AskUserQuestionTool),
+        Arc::new(AskUserTool),
         Arc::new(SkillTool),
     ]
 }
@@ -389,6 +516,7 @@ impl ToolRegistry {
         let mut tools = read_only_tools();
         tools.extend(browser_tools());
         tools.extend(parent_coordination_tools());
+        tools.extend(plugin::installed_tools(true));
         tools.push(Arc::new(ProposeTaskTool));
         Self { tools }
     }
@@ -414,9 +542,18 @@ impl ToolRegistry {
         Self::new_with_options(false)
     }
 
+    /// Every built-in and plugin tool a parent conversation can have in some
+    /// mode: the choices offered for per-conversation tool selection
+    /// (REQ-BED-039).
+    pub fn catalog() -> Self {
+        // Sandboxed Explore is the superset: the full suite plus propose_task.
+        Self::explore_with_sandbox()
+    }
+
     /// Tool registry for Explore-mode sub-agents (REQ-PROJ-008).
-    /// Read-only tools + bash + `submit_result`/`submit_error`. No tmux, no
-    /// patch, no spawn, no `ask_user`, no skill, no `propose_task`.
+    /// Read-only tools + bash and its process tools + `submit_result`/
+    /// `submit_error`. No tmux, no patch, no spawn, no `ask_user`, no skill,
+    /// no `propose_task`.
     /// The tmux session belongs to the worktree's owning conversation, not
     /// its sub-agents (task 03001).
     // TODO: read-only bash enforcement not yet implemented --
@@ -424,16 +561,20 @@ impl ToolRegistry {
     pub fn for_subagent_explore() -> Self {
         let mut tools = read_only_tools();
         tools.push(Arc::new(BashTool));
+        tools.push(Arc::new(ListProcessesTool));
+        tools.push(Arc::new(KillProcessTool));
         tools.extend(browser_tools());
         tools.extend(sub_agent_terminal_tools());
         Self { tools }
     }
 
     /// Tool registry for Work-mode sub-agents (REQ-PROJ-008).
-    /// Everything Explore has PLUS patch. No spawn, no `ask_user`, no skill, no `propose_task`.
+    /// Everything Explore has PLUS patch and archive. No spawn, no `ask_user`,
+    /// no skill, no `propose_task`.
     pub fn for_subagent_work() -> Self {
         let mut registry = Self::for_subagent_explore();
         registry.tools.push(Arc::new(PatchTool::default()));
+        registry.tools.push(Arc::new(ArchiveTool));
         registry
     }
 
@@ -454,9 +595,11 @@ impl ToolRegistry {
             tools.extend(sub_agent_terminal_tools());
         } else {
             // Parent conversations can read the terminal, spawn sub-agents,
-            // ask user questions, and invoke skills.
+            // ask user questions, invoke skills, and use WASM plugins
+            // (REQ-PLUG-003).
             tools.extend(parent_terminal_tools());
             tools.extend(parent_coordination_tools());
+            tools.extend(plugin::installed_tools(false));
         }
 
         Self { tools }
@@ -543,13 +686,19 @@ mod tests {
             "browser_recent_console_logs",
             "browser_clear_console_logs",
             "browser_resize",
+            "browser_handle_dialog",
+            "browser_throttle",
+            "browser_set_geolocation",
+            "browser_set_locale",
+            "browser_print_to_pdf",
+            "browser_query",
         ] {
             assert!(names.contains(expected), "Missing {expected}");
         }
     }
 
     /// Read-only tools (`read_file`, `search`, `keyword_search`, `read_image`,
-    /// `think`) must be present in every registry. Drift here caused the
+    /// `inspect_binary`, `think`) must be present in every registry. Drift here caused the
     /// original "Unknown tool: `read_file`" infinite loop in Direct mode — the
     /// mock provider emitted a `read_file` call that the registry didn't
     /// recognise, which fed back into the LLM unbounded.
@@ -566,6 +715,7 @@ mod tests {
             "search",
             "keyword_search",
             "read_image",
+            "inspect_binary",
         ]
         .into_iter()
         .collect();
@@ -589,6 +739,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn catalog_covers_every_parent_registry() {
+        let catalog = names(&ToolRegistry::catalog());
+        for registry in [
+            ToolRegistry::direct(),
+            ToolRegistry::explore_no_sandbox(),
+            ToolRegistry::explore_with_sandbox(),
+        ] {
+            let missing: Vec<_> = names(&registry).difference(&catalog).cloned().collect();
+            assert!(missing.is_empty(), "catalog is missing {missing:?}");
+        }
+    }
+
     /// Per-mode capability matrix. If a constructor starts handing out the
     /// wrong capability set — e.g. giving sub-agents `spawn_agents`, or
     /// Explore-no-sandbox a `bash` — this test fails loudly instead of
@@ -609,11 +772,15 @@ mod tests {
         assert!(direct.contains("bash"));
         assert!(direct.contains("patch"));
         assert!(direct.contains("tmux"));
+        assert!(direct.contains("archive"));
+        assert!(direct.contains("list_processes"));
+        assert!(direct.contains("kill_process"));
         for tool in PARENT_TERMINAL_TOOLS {
             assert!(direct.contains(*tool), "Direct missing {tool}");
         }
         assert!(direct.contains("spawn_agents"));
         assert!(direct.contains("ask_user_question"));
+        assert!(direct.contains("ask_user"));
         assert!(!direct.contains("propose_task"));
         assert!(!direct.contains("submit_result"));
         assert!(!direct.contains("submit_error"));
@@ -623,6 +790,7 @@ mod tests {
         assert!(work.contains("bash"));
         assert!(work.contains("patch"));
         assert!(work.contains("tmux"));
+        assert!(work.contains("archive"));
         assert!(work.contains("propose_task"));
         for tool in PARENT_TERMINAL_TOOLS {
             assert!(work.contains(*tool), "Work missing {tool}");
@@ -636,6 +804,9 @@ mod tests {
         assert!(!explore.contains("bash"));
         assert!(!explore.contains("patch"));
         assert!(!explore.contains("tmux"));
+        assert!(!explore.contains("archive"));
+        assert!(!explore.contains("list_processes"));
+        assert!(!explore.contains("kill_process"));
         for tool in PARENT_TERMINAL_TOOLS {
             assert!(
                 !explore.contains(*tool),
@@ -647,6 +818,7 @@ mod tests {
         // no ask_user, no propose_task, no parent-terminal tools.
         let sub_explore = names(&ToolRegistry::for_subagent_explore());
         assert!(sub_explore.contains("bash"));
+        assert!(sub_explore.contains("kill_process"));
         assert!(
             !sub_explore.contains("tmux"),
             "sub-agent explore must not have tmux (task 03001)"
@@ -654,8 +826,10 @@ mod tests {
         assert!(sub_explore.contains("submit_result"));
         assert!(sub_explore.contains("submit_error"));
         assert!(!sub_explore.contains("patch"));
+        assert!(!sub_explore.contains("archive"));
         assert!(!sub_explore.contains("spawn_agents"));
         assert!(!sub_explore.contains("ask_user_question"));
+        assert!(!sub_explore.contains("ask_user"));
         assert!(!sub_explore.contains("propose_task"));
         for tool in PARENT_TERMINAL_TOOLS {
             assert!(
@@ -664,10 +838,11 @@ mod tests {
             );
         }
 
-        // Sub-agent Work: Explore + patch.
+        // Sub-agent Work: Explore + patch + archive.
         let sub_work = names(&ToolRegistry::for_subagent_work());
         assert!(sub_work.contains("bash"));
         assert!(sub_work.contains("patch"));
+        assert!(sub_work.contains("archive"));
         assert!(
             !sub_work.contains("tmux"),
             "sub-agent work must not have tmux (task 03001)"
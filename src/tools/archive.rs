@@ -0,0 +1,884 @@
+//! `Archive` tool - list, extract and create zip and tar.gz archives
+//!
+//! REQ-PROJ-031: structured results in place of `unzip` and `tar` output,
+//! with every path kept inside the conversation's roots and every entry
+//! kept inside the extraction directory
+
+use super::{Tool, ToolContext, ToolOutput};
+use crate::phoenixignore;
+use async_trait::async_trait;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Most entries one call reads or writes.
+const MAX_ENTRIES: usize = 100_000;
+
+/// Most bytes one extraction writes, against archives that expand without
+/// bound.
+const MAX_EXTRACT_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Most entries named in a result; the totals count all of them.
+const MAX_LISTED: usize = 200;
+
+/// File type bits of a unix mode, and the value for a symbolic link.
+const S_IFMT: u32 = 0o170_000;
+const S_IFLNK: u32 = 0o120_000;
+
+/// List, extract or create an archive.
+pub struct ArchiveTool;
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Operation {
+    List,
+    Extract,
+    Create,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArchiveInput {
+    operation: Operation,
+    archive: String,
+    destination: Option<String>,
+    #[serde(default)]
+    paths: Vec<String>,
+    #[serde(default)]
+    overwrite: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl Format {
+    /// The format an archive's name implies.
+    fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        let has = |suffix| strip_suffix_ignore_case(name, suffix).is_some();
+        if has(".zip") {
+            Some(Self::Zip)
+        } else if has(".tar.gz") || has(".tgz") {
+            Some(Self::TarGz)
+        } else if has(".tar") {
+            Some(Self::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    File,
+    Dir,
+    Link,
+    Other,
+}
+
+/// An entry as the archive stores it, before its name is checked.
+struct RawEntry<'a> {
+    name: String,
+    kind: Kind,
+    size: u64,
+    mode: Option<u32>,
+    reader: &'a mut dyn Read,
+}
+
+#[derive(Debug, Serialize)]
+struct Entry {
+    path: String,
+    kind: Kind,
+    size: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct Skipped {
+    path: String,
+    reason: &'static str,
+}
+
+/// What one call did, returned to the model as JSON.
+#[derive(Debug, Serialize)]
+struct Report {
+    operation: &'static str,
+    archive: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    destination: Option<String>,
+    total_entries: usize,
+    total_bytes: u64,
+    entries: Vec<Entry>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    skipped: Vec<Skipped>,
+}
+
+impl Report {
+    fn new(operation: &'static str, archive: String) -> Self {
+        Self {
+            operation,
+            archive,
+            destination: None,
+            total_entries: 0,
+            total_bytes: 0,
+            entries: Vec::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, path: String, kind: Kind, size: u64) -> Result<(), String> {
+        self.total_entries += 1;
+        self.total_bytes += size;
+        if self.total_entries > MAX_ENTRIES {
+            return Err(format!("More than {MAX_ENTRIES} entries"));
+        }
+        if self.entries.len() < MAX_LISTED {
+            self.entries.push(Entry { path, kind, size });
+        }
+        Ok(())
+    }
+
+    fn skip(&mut self, path: impl Into<String>, reason: &'static str) {
+        if self.skipped.len() < MAX_LISTED {
+            self.skipped.push(Skipped {
+                path: path.into(),
+                reason,
+            });
+        }
+    }
+}
+
+/// The directories a call may touch: the working directory, which is
+/// writable, then the conversation's extra roots (REQ-BED-049).
+struct Roots {
+    working_dir: PathBuf,
+    roots: Vec<(PathBuf, bool)>,
+}
+
+impl Roots {
+    fn new(ctx: &ToolContext) -> Self {
+        let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+        let mut roots = vec![(canonical(&ctx.working_dir), true)];
+        roots.extend(
+            ctx.extra_roots
+                .iter()
+                .map(|r| (canonical(Path::new(&r.path)), r.writable)),
+        );
+        Self {
+            working_dir: ctx.working_dir.clone(),
+            roots,
+        }
+    }
+
+    /// `raw` made absolute and canonical, if it lies in a root, and in a
+    /// writable one when `write`. The path need not exist yet.
+    fn resolve(&self, raw: &str, write: bool) -> Result<PathBuf, String> {
+        let path = self.working_dir.join(raw);
+        let resolved =
+            canonicalize_lenient(&path).ok_or_else(|| format!("Cannot resolve '{raw}'"))?;
+        let root = self
+            .roots
+            .iter()
+            .find(|(root, _)| resolved.starts_with(root))
+            .ok_or_else(|| format!("'{raw}' is outside the conversation's directories"))?;
+        if write && !root.1 {
+            return Err(format!("'{raw}' is in a read-only directory"));
+        }
+        Ok(resolved)
+    }
+
+    /// The canonical working directory.
+    fn base(&self) -> &Path {
+        &self.roots[0].0
+    }
+
+    /// How results name `path`: relative to the working directory when
+    /// inside it.
+    fn display(&self, path: &Path) -> String {
+        path.strip_prefix(self.base())
+            .ok()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+}
+
+/// Canonicalize the longest existing prefix of `path` and append the rest.
+/// `None` when the missing part climbs with `..`.
+fn canonicalize_lenient(path: &Path) -> Option<PathBuf> {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(found) = existing.canonicalize() {
+            return Some(missing.iter().rev().fold(found, |p, name| p.join(name)));
+        }
+        missing.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+}
+
+/// An entry name as a path under the extraction directory: relative, and
+/// never climbing out with `..`.
+fn safe_relative(name: &str) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    (!relative.as_os_str().is_empty()).then_some(relative)
+}
+
+/// Call `visit` with each entry of the archive at `path`, in order.
+fn for_each_entry(
+    path: &Path,
+    format: Format,
+    mut visit: impl FnMut(RawEntry<'_>) -> Result<(), String>,
+) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open archive: {e}"))?;
+    let reader = BufReader::new(file);
+    match format {
+        Format::Zip => {
+            let mut zip = ZipArchive::new(reader).map_err(|e| format!("Not a zip archive: {e}"))?;
+            for i in 0..zip.len() {
+                let mut entry = zip.by_index(i).map_err(|e| format!("Bad zip entry: {e}"))?;
+                let mode = entry.unix_mode();
+                let kind = if entry.is_dir() {
+                    Kind::Dir
+                } else if mode.is_some_and(|m| m & S_IFMT == S_IFLNK) {
+                    Kind::Link
+                } else {
+                    Kind::File
+                };
+                visit(RawEntry {
+                    name: entry.name().to_string(),
+                    kind,
+                    size: entry.size(),
+                    mode,
+                    reader: &mut entry,
+                })?;
+            }
+            Ok(())
+        }
+        Format::Tar => tar_entries(reader, visit),
+        Format::TarGz => tar_entries(GzDecoder::new(reader), visit),
+    }
+}
+
+fn tar_entries(
+    reader: impl Read,
+    mut visit: impl FnMut(RawEntry<'_>) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut archive = tar::Archive::new(reader);
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Not a tar archive: {e}"))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Bad tar entry: {e}"))?;
+        let header = entry.header();
+        let kind = match header.entry_type() {
+            tar::EntryType::Regular | tar::EntryType::Continuous => Kind::File,
+            tar::EntryType::Directory => Kind::Dir,
+            tar::EntryType::Symlink | tar::EntryType::Link => Kind::Link,
+            _ => Kind::Other,
+        };
+        let mode = header.mode().ok();
+        let name = entry
+            .path()
+            .map_err(|e| format!("Bad tar entry name: {e}"))?
+            .to_string_lossy()
+            .into_owned();
+        visit(RawEntry {
+            name,
+            kind,
+            size: entry.size(),
+            mode,
+            reader: &mut entry,
+        })?;
+    }
+    Ok(())
+}
+
+fn list(path: &Path, format: Format, report: &mut Report) -> Result<(), String> {
+    for_each_entry(path, format, |entry| {
+        report.add(entry.name, entry.kind, entry.size)
+    })
+}
+
+/// Extract regular files and directories into `dest`, skipping links,
+/// unsafe names and, unless `overwrite`, files that already exist.
+fn extract(
+    path: &Path,
+    format: Format,
+    dest: &Path,
+    overwrite: bool,
+    cancel: &CancellationToken,
+    report: &mut Report,
+) -> Result<(), String> {
+    fs::create_dir_all(dest).map_err(|e| format!("Failed to create destination: {e}"))?;
+    let dest = dest
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve destination: {e}"))?;
+    let mut written = 0u64;
+    for_each_entry(path, format, |entry| {
+        if cancel.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        let Some(relative) = safe_relative(&entry.name) else {
+            report.skip(entry.name, "path leaves the destination");
+            return Ok(());
+        };
+        match entry.kind {
+            Kind::Link => {
+                report.skip(entry.name, "link");
+                return Ok(());
+            }
+            Kind::Other => {
+                report.skip(entry.name, "unsupported entry type");
+                return Ok(());
+            }
+            Kind::File | Kind::Dir => {}
+        }
+        let target = dest.join(&relative);
+        let parent = if entry.kind == Kind::Dir {
+            target.as_path()
+        } else {
+            target.parent().unwrap_or(&dest)
+        };
+        // A link already in the destination must not carry writes out of it
+        if !canonicalize_lenient(parent).is_some_and(|p| p.starts_with(&dest)) {
+            report.skip(entry.name, "path leaves the destination through a link");
+            return Ok(());
+        }
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create '{}': {e}", relative.display()))?;
+        if entry.kind == Kind::Dir {
+            return report.add(entry.name, Kind::Dir, 0);
+        }
+
+        if let Ok(existing) = target.symlink_metadata() {
+            if !overwrite {
+                report.skip(entry.name, "already exists");
+                return Ok(());
+            }
+            if existing.is_dir() {
+                report.skip(entry.name, "a directory is in the way");
+                return Ok(());
+            }
+            // Replace a link rather than write through it
+            fs::remove_file(&target)
+                .map_err(|e| format!("Failed to replace '{}': {e}", relative.display()))?;
+        }
+        let remaining = MAX_EXTRACT_BYTES - written;
+        let mut file = File::create(&target)
+            .map_err(|e| format!("Failed to create '{}': {e}", relative.display()))?;
+        let copied = io::copy(&mut entry.reader.take(remaining + 1), &mut file)
+            .map_err(|e| format!("Failed to extract '{}': {e}", relative.display()))?;
+        if copied > remaining {
+            drop(file);
+            let _ = fs::remove_file(&target);
+            return Err(format!(
+                "Archive expands past the {} GB extraction limit",
+                MAX_EXTRACT_BYTES >> 30
+            ));
+        }
+        written += copied;
+        set_executable(&target, entry.mode)
+            .map_err(|e| format!("Failed to set mode of '{}': {e}", relative.display()))?;
+        report.add(entry.name, Kind::File, copied)
+    })
+}
+
+/// Keep the executable bit an archive records.
+#[cfg(unix)]
+fn set_executable(path: &Path, mode: Option<u32>) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    match mode {
+        Some(mode) if mode & 0o111 != 0 => {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path, _mode: Option<u32>) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn file_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o777
+}
+
+#[cfg(not(unix))]
+fn file_mode(_metadata: &fs::Metadata) -> u32 {
+    0o644
+}
+
+/// A file or directory to store, with its name in the archive.
+struct Source {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+    size: u64,
+    mode: u32,
+}
+
+/// Everything under `sources`, directories recursively, named relative to
+/// the working directory (or to a source's parent outside it). Skips `.git`,
+/// what `.phoenixignore` excludes, links, and the archive itself.
+fn collect_sources(
+    roots: &Roots,
+    sources: &[PathBuf],
+    archive: &Path,
+    report: &mut Report,
+) -> Result<Vec<Source>, String> {
+    let mut found = Vec::new();
+    for source in sources {
+        let base = if source.starts_with(roots.base()) {
+            roots.base()
+        } else {
+            source.parent().unwrap_or(source)
+        };
+        let walker = ignore::WalkBuilder::new(source)
+            .standard_filters(false)
+            .add_custom_ignore_filename(phoenixignore::FILE_NAME)
+            .filter_entry(|e| e.file_name() != ".git")
+            .build();
+        for entry in walker {
+            let entry = entry.map_err(|e| format!("Failed to read '{}': {e}", source.display()))?;
+            let path = entry.path();
+            let name = path
+                .strip_prefix(base)
+                .unwrap_or(path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if path == archive || name.is_empty() {
+                continue;
+            }
+            let metadata = path
+                .symlink_metadata()
+                .map_err(|e| format!("Failed to stat '{name}': {e}"))?;
+            if metadata.is_symlink() {
+                report.skip(name, "link");
+                continue;
+            }
+            if !metadata.is_dir() && !metadata.is_file() {
+                report.skip(name, "unsupported entry type");
+                continue;
+            }
+            found.push(Source {
+                path: path.to_path_buf(),
+                name,
+                is_dir: metadata.is_dir(),
+                size: if metadata.is_dir() { 0 } else { metadata.len() },
+                mode: file_mode(&metadata),
+            });
+            if found.len() > MAX_ENTRIES {
+                return Err(format!("More than {MAX_ENTRIES} entries"));
+            }
+        }
+    }
+    Ok(found)
+}
+
+fn create(
+    path: &Path,
+    format: Format,
+    sources: &[Source],
+    cancel: &CancellationToken,
+    report: &mut Report,
+) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Failed to create archive: {e}"))?;
+    let failed = |e: &dyn std::fmt::Display| format!("Failed to write archive: {e}");
+    match format {
+        Format::Zip => {
+            let mut zip = ZipWriter::new(file);
+            for source in sources {
+                if cancel.is_cancelled() {
+                    return Err("Cancelled".to_string());
+                }
+                let options = SimpleFileOptions::default()
+                    .compression_method(CompressionMethod::Deflated)
+                    .unix_permissions(source.mode);
+                if source.is_dir {
+                    zip.add_directory(source.name.as_str(), options)
+                        .map_err(|e| failed(&e))?;
+                } else {
+                    zip.start_file(source.name.as_str(), options)
+                        .map_err(|e| failed(&e))?;
+                    let mut input = File::open(&source.path)
+                        .map_err(|e| format!("Failed to read '{}': {e}", source.name))?;
+                    io::copy(&mut input, &mut zip).map_err(|e| failed(&e))?;
+                }
+                report.add(source.name.clone(), kind_of(source), source.size)?;
+            }
+            zip.finish().map_err(|e| failed(&e))?;
+        }
+        Format::Tar => {
+            let mut tar = tar::Builder::new(file);
+            append_sources(&mut tar, sources, cancel, report)?;
+            tar.into_inner().map_err(|e| failed(&e))?;
+        }
+        Format::TarGz => {
+            let gzip = GzEncoder::new(file, flate2::Compression::default());
+            let mut tar = tar::Builder::new(gzip);
+            append_sources(&mut tar, sources, cancel, report)?;
+            tar.into_inner()
+                .and_then(GzEncoder::finish)
+                .map_err(|e| failed(&e))?;
+        }
+    }
+    Ok(())
+}
+
+fn append_sources<W: io::Write>(
+    tar: &mut tar::Builder<W>,
+    sources: &[Source],
+    cancel: &CancellationToken,
+    report: &mut Report,
+) -> Result<(), String> {
+    for source in sources {
+        if cancel.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+        let added = if source.is_dir {
+            tar.append_dir(&source.name, &source.path)
+        } else {
+            tar.append_path_with_name(&source.path, &source.name)
+        };
+        added.map_err(|e| format!("Failed to add '{}': {e}", source.name))?;
+        report.add(source.name.clone(), kind_of(source), source.size)?;
+    }
+    Ok(())
+}
+
+fn kind_of(source: &Source) -> Kind {
+    if source.is_dir {
+        Kind::Dir
+    } else {
+        Kind::File
+    }
+}
+
+/// Carry out one call. Blocking, so it runs on the blocking pool.
+fn run_operation(
+    input: &ArchiveInput,
+    roots: &Roots,
+    cancel: &CancellationToken,
+) -> Result<Report, String> {
+    let write = matches!(input.operation, Operation::Create);
+    let archive = roots.resolve(&input.archive, write)?;
+    let format = Format::of(&archive).ok_or_else(|| {
+        format!(
+            "Cannot tell the format of '{}': use .zip, .tar.gz, .tgz or .tar",
+            input.archive
+        )
+    })?;
+
+    match input.operation {
+        Operation::List => {
+            let mut report = Report::new("list", roots.display(&archive));
+            list(&archive, format, &mut report)?;
+            Ok(report)
+        }
+        Operation::Extract => {
+            // `build.tar.gz` extracts into `build/` beside it by default
+            let dest = if let Some(dest) = &input.destination {
+                roots.resolve(dest, true)?
+            } else {
+                let name = archive
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let stem = [".tar.gz", ".tgz", ".tar", ".zip"]
+                    .iter()
+                    .find_map(|ext| strip_suffix_ignore_case(&name, ext))
+                    .unwrap_or(name.as_str());
+                let dest = archive.with_file_name(stem);
+                roots.resolve(&dest.to_string_lossy(), true)?
+            };
+            let mut report = Report::new("extract", roots.display(&archive));
+            report.destination = Some(roots.display(&dest));
+            extract(
+                &archive,
+                format,
+                &dest,
+                input.overwrite,
+                cancel,
+                &mut report,
+            )?;
+            Ok(report)
+        }
+        Operation::Create => {
+            if input.paths.is_empty() {
+                return Err("create needs at least one entry in paths".to_string());
+            }
+            if archive.exists() && !input.overwrite {
+                return Err(format!(
+                    "'{}' already exists; set overwrite to replace it",
+                    input.archive
+                ));
+            }
+            let sources = input
+                .paths
+                .iter()
+                .map(|p| {
+                    let path = roots.resolve(p, false)?;
+                    if path.exists() {
+                        Ok(path)
+                    } else {
+                        Err(format!("'{p}' does not exist"))
+                    }
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let mut report = Report::new("create", roots.display(&archive));
+            let sources = collect_sources(roots, &sources, &archive, &mut report)?;
+            if let Err(e) = create(&archive, format, &sources, cancel, &mut report) {
+                let _ = fs::remove_file(&archive);
+                return Err(e);
+            }
+            Ok(report)
+        }
+    }
+}
+
+fn strip_suffix_ignore_case<'a>(name: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = name.len().checked_sub(suffix.len())?;
+    let (stem, tail) = (name.get(..split)?, name.get(split..)?);
+    (tail.eq_ignore_ascii_case(suffix) && !stem.is_empty()).then_some(stem)
+}
+
+#[async_trait]
+impl Tool for ArchiveTool {
+    fn name(&self) -> &'static str {
+        "archive"
+    }
+
+    fn description(&self) -> String {
+        "List, extract or create .zip, .tar.gz (.tgz) and .tar archives, with a JSON result. \
+         Use instead of unzip or tar in bash. Extraction skips links, existing files (unless \
+         overwrite) and entries whose paths would leave the destination. Creating adds \
+         directories recursively, except .git and paths .phoenixignore excludes. All paths \
+         must be in the working directory or the conversation's other directories."
+            .to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "required": ["operation", "archive"],
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["list", "extract", "create"]
+                },
+                "archive": {
+                    "type": "string",
+                    "description": "Archive path (absolute or relative to working directory); \
+                                    its extension picks the format"
+                },
+                "destination": {
+                    "type": "string",
+                    "description": "extract: directory to extract into. Default: a directory \
+                                    beside the archive named after it"
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "create: files and directories to add"
+                },
+                "overwrite": {
+                    "type": "boolean",
+                    "description": "Replace existing files when extracting, or an existing \
+                                    archive when creating. Default: false"
+                }
+            }
+        })
+    }
+
+    async fn run(&self, input: Value, ctx: ToolContext) -> ToolOutput {
+        let input: ArchiveInput = match serde_json::from_value(input) {
+            Ok(i) => i,
+            Err(e) => return ToolOutput::error(format!("Invalid input: {e}")),
+        };
+        let roots = Roots::new(&ctx);
+        let cancel = ctx.cancel.clone();
+
+        let task = move || run_operation(&input, &roots, &cancel);
+        match tokio::task::spawn_blocking(task).await {
+            Ok(Ok(report)) => {
+                let data = serde_json::to_value(&report).unwrap_or_default();
+                let output = serde_json::to_string_pretty(&data).unwrap_or_default();
+                ToolOutput::success(output).with_display(data)
+            }
+            Ok(Err(e)) => ToolOutput::error(e),
+            Err(e) => ToolOutput::error(format!("Archive task failed: {e}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::BrowserSessionManager;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    fn test_context(working_dir: PathBuf) -> ToolContext {
+        ToolContext::new(
+            CancellationToken::new(),
+            "test-conv".to_string(),
+            working_dir,
+            Arc::new(BrowserSessionManager::default()),
+            Arc::new(crate::tools::BashHandleRegistry::new()),
+            Arc::new(crate::llm::ModelRegistry::new_empty()),
+            crate::terminal::ActiveTerminals::new(),
+            Arc::new(crate::tools::TmuxRegistry::new()),
+            None,
+        )
+    }
+
+    async fn run(dir: &Path, input: Value) -> ToolOutput {
+        ArchiveTool
+            .run(input, test_context(dir.to_path_buf()))
+            .await
+    }
+
+    fn project(dir: &Path) {
+        fs::create_dir_all(dir.join("site/assets")).unwrap();
+        fs::create_dir_all(dir.join("site/.git")).unwrap();
+        fs::write(dir.join("site/index.html"), "<h1>hi</h1>").unwrap();
+        fs::write(dir.join("site/assets/app.js"), "console.log(1)").unwrap();
+        fs::write(dir.join("site/.git/HEAD"), "ref: main").unwrap();
+        fs::write(dir.join("site/.env"), "TOKEN=1").unwrap();
+        fs::write(dir.join("site/.phoenixignore"), ".env\n").unwrap();
+    }
+
+    async fn round_trip(name: &str) {
+        let dir = tempfile::tempdir().unwrap();
+        project(dir.path());
+
+        let created = run(
+            dir.path(),
+            json!({"operation": "create", "archive": name, "paths": ["site"]}),
+        )
+        .await;
+        assert!(created.success, "{}", created.output);
+
+        let listed = run(dir.path(), json!({"operation": "list", "archive": name})).await;
+        assert!(listed.success, "{}", listed.output);
+        let data = listed.display_data.unwrap();
+        let names: Vec<&str> = data["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["path"].as_str().unwrap().trim_end_matches('/'))
+            .collect();
+        assert!(names.contains(&"site/index.html"), "{names:?}");
+        assert!(names.contains(&"site/assets/app.js"), "{names:?}");
+        assert!(!names
+            .iter()
+            .any(|n| n.split('/').any(|part| part == ".git" || part == ".env")));
+
+        let extracted = run(
+            dir.path(),
+            json!({"operation": "extract", "archive": name, "destination": "out"}),
+        )
+        .await;
+        assert!(extracted.success, "{}", extracted.output);
+        let app = fs::read_to_string(dir.path().join("out/site/assets/app.js")).unwrap();
+        assert_eq!(app, "console.log(1)");
+
+        // Existing files are kept unless overwrite is set
+        let again = run(
+            dir.path(),
+            json!({"operation": "extract", "archive": name, "destination": "out"}),
+        )
+        .await;
+        assert!(again.output.contains("already exists"), "{}", again.output);
+    }
+
+    #[tokio::test]
+    async fn zip_round_trip() {
+        round_trip("site.zip").await;
+    }
+
+    #[tokio::test]
+    async fn tar_gz_round_trip() {
+        round_trip("site.tar.gz").await;
+    }
+
+    #[tokio::test]
+    async fn extract_skips_entries_that_escape() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = dir.path().join("work");
+        fs::create_dir_all(&work).unwrap();
+        let mut zip = ZipWriter::new(File::create(work.join("evil.zip")).unwrap());
+        for name in ["../escaped.txt", "/tmp/absolute.txt", "ok/fine.txt"] {
+            zip.start_file(name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(b"payload").unwrap();
+        }
+        zip.finish().unwrap();
+
+        let result = run(
+            &work,
+            json!({"operation": "extract", "archive": "evil.zip"}),
+        )
+        .await;
+        assert!(result.success, "{}", result.output);
+        assert!(work.join("evil/ok/fine.txt").is_file());
+        assert!(!dir.path().join("escaped.txt").exists());
+        assert!(!work.join("escaped.txt").exists());
+        let data = result.display_data.unwrap();
+        let skipped = data["skipped"].as_array().unwrap();
+        assert!(
+            skipped.iter().any(|s| s["path"] == "../escaped.txt"),
+            "{data}"
+        );
+        assert!(skipped
+            .iter()
+            .all(|s| s["reason"] == "path leaves the destination"));
+    }
+
+    #[tokio::test]
+    async fn paths_stay_in_the_conversation_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let work = dir.path().join("work");
+        project(&work);
+        let outside = dir.path().join("elsewhere.zip");
+
+        let result = run(
+            &work,
+            json!({"operation": "create", "archive": outside, "paths": ["site"]}),
+        )
+        .await;
+        assert!(!result.success);
+        assert!(result.output.contains("outside"), "{}", result.output);
+        assert!(!outside.exists());
+
+        let climbing = run(
+            &work,
+            json!({"operation": "extract", "archive": "x.zip", "destination": "../out"}),
+        )
+        .await;
+        assert!(!climbing.success);
+    }
+}
@@ -0,0 +1,58 @@
+//! `ask_user` tool — one question answered as the tool result (REQ-AUQ-009)
+//!
+//! Like `ask_user_question`, this tool is intercepted at the state machine
+//! level: the conversation waits in `AwaitingUserInput` and the user's answer
+//! becomes the call's result, so the turn carries on where it paused. The
+//! `run()` method exists only as a fallback for input that failed to parse.
+
+use super::{Tool, ToolContext, ToolOutput};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+/// Tool definition for `ask_user`. Registered with the other parent-only
+/// coordination tools; intercepted before execution.
+pub struct AskUserTool;
+
+#[async_trait]
+impl Tool for AskUserTool {
+    fn name(&self) -> &'static str {
+        "ask_user"
+    }
+
+    fn description(&self) -> String {
+        "Ask the user a single question and wait for the answer, which is \
+         returned as this tool's result. Offer options when the answer is \
+         one of a few choices; the user can still type their own. Use \
+         ask_user_question instead for several questions at once. This \
+         must be the only tool call in the response."
+            .to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "required": ["question"],
+            "properties": {
+                "question": {
+                    "type": "string",
+                    "description": "The question to ask"
+                },
+                "options": {
+                    "type": "array",
+                    "description": "Optional choices shown as buttons",
+                    "maxItems": 6,
+                    "items": { "type": "string" }
+                }
+            }
+        })
+    }
+
+    async fn run(&self, _input: Value, _ctx: ToolContext) -> ToolOutput {
+        // Reached only when the input failed to parse as AskUserInput and
+        // fell through to ToolInput::Unknown.
+        ToolOutput::error(
+            "Invalid input: provide 'question' (string) and optionally \
+             'options' (array of strings).",
+        )
+    }
+}
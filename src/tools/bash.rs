@@ -20,9 +20,11 @@
 // Foundation submodules (task 02693) — used by the operations dispatch below.
 pub mod handle;
 mod operations;
+pub mod policy;
 pub mod reaper;
 pub mod registry;
 pub mod ring;
+pub mod shell;
 
 pub use reaper::{install_reaper, shutdown_kill_tree};
 pub use registry::{BashHandleError, BashHandleRegistry, ConversationHandles};
@@ -49,8 +51,11 @@ impl Tool for BashTool {
         // `EXACTLY ONCE` / `does not auto-escalate`) is load-bearing —
         // affirmative descriptions get pattern-matched into the POSIX
         // `timeout(1)` / `kill PID` priors. See REQ-BASH-002 rationale.
-        r#"Executes shell commands via bash -c, capturing combined stdout/stderr.
-Bash state changes (working dir, variables, aliases) don't persist between calls.
+        r#"Executes shell commands via bash -c (or the shell the user configured for this
+conversation), capturing combined stdout/stderr.
+Under bash, exported variables and shell functions carry over to later calls
+in this conversation, so `export FOO=1` or `source .venv/bin/activate`
+sticks. The working directory, aliases and unexported variables do not.
 
 Modes (exactly one per call):
 
@@ -98,6 +103,15 @@ handles do NOT survive Phoenix process restart. For processes that need
 to survive Phoenix restart, that need a TTY, that need stdin, or that
 are interactive REPLs, use the tmux tool instead.
 
+error="uncommitted_changes" means a git command (reset --hard, checkout --,
+restore, clean -f, ...) would throw away changes this conversation did not
+make, likely the user's work in progress. Ask the user first; only if they
+agree, re-run with confirm_discard=true.
+
+error="command_policy_denied" means the user or operator does not let
+this conversation run that command. Do not rephrase it to get around the
+rule; ask the user to run it or to change the policy.
+
 IMPORTANT: Keep commands concise. The cmd input must be < 60k tokens.
 For complex scripts, write them to a file first and execute the file."#
             .to_string()
@@ -122,6 +136,10 @@ For complex scripts, write them to a file first and execute the file."#
                     "maximum": 900,
                     "description": "How long this single tool call blocks before handing back a handle (default 30). This is NOT a process kill timeout: the process is NEVER killed when wait_seconds elapses; it keeps running and you receive a handle. Use kill=<handle> to actually terminate."
                 },
+                "confirm_discard": {
+                    "type": "boolean",
+                    "description": "Spawn only. Run a git command even though it discards uncommitted changes this conversation did not make. Set it only after the user agreed."
+                },
                 "peek": { "type": "string", "description": "Handle id to peek" },
                 "wait": { "type": "string", "description": "Handle id to wait on" },
                 "kill": { "type": "string", "description": "Handle id to kill" },
@@ -828,4 +846,160 @@ mod tests {
         let h = v["handle"].as_str().unwrap().to_string();
         let _ = tool.run(json!({"kill": h, "signal": "KILL"}), c).await;
     }
+
+    fn output_lines(v: &Value) -> Vec<String> {
+        v["lines"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|l| l["bytes"].as_str().unwrap_or("").to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn exported_env_and_functions_persist_across_spawns() {
+        // REQ-BASH-016: exports and functions carry over; the working
+        // directory does not.
+        let tool = BashTool;
+        let c = ctx_with_registry(Arc::new(BashHandleRegistry::new()));
+        let setup = "export PHX_KEPT=kept; PHX_LOCAL=local; greet() { echo \"hi $1\"; }; cd /";
+        let first = tool
+            .run(json!({"cmd": setup, "wait_seconds": 5}), c.clone())
+            .await;
+        assert_eq!(parse_response(&first)["exit_code"], 0);
+
+        let check = "echo \"[$PHX_KEPT][$PHX_LOCAL]\"; greet there; pwd";
+        let second = tool.run(json!({"cmd": check, "wait_seconds": 5}), c).await;
+        let v = parse_response(&second);
+        assert_eq!(v["exit_code"], 0, "got: {v}");
+        let expected_cwd = temp_dir().canonicalize().unwrap();
+        assert_eq!(
+            output_lines(&v),
+            [
+                "[kept][]".to_string(),
+                "hi there".to_string(),
+                expected_cwd.display().to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn env_snapshot_keeps_exit_code_and_stays_in_its_conversation() {
+        let tool = BashTool;
+        let registry = Arc::new(BashHandleRegistry::new());
+        let c = ctx_for("conv-env-a", registry.clone());
+
+        let setup = json!({"cmd": "export PHX_VENV=on; exit 3", "wait_seconds": 5});
+        let first = tool.run(setup, c.clone()).await;
+        assert_eq!(parse_response(&first)["exit_code"], 3);
+
+        let read = json!({"cmd": "echo \"[$PHX_VENV]\"", "wait_seconds": 5});
+        let same = parse_response(&tool.run(read.clone(), c).await);
+        assert_eq!(output_lines(&same), ["[on]"]);
+
+        let other = ctx_for("conv-env-b", registry);
+        let other = parse_response(&tool.run(read, other).await);
+        assert_eq!(output_lines(&other), ["[]"]);
+    }
+
+    #[tokio::test]
+    async fn env_snapshot_is_private_to_the_server_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tool = BashTool;
+        let registry = Arc::new(BashHandleRegistry::new());
+        let c = ctx_for("conv-env-private", registry.clone());
+        let setup = json!({"cmd": "export PHX_TOKEN=secret; umask", "wait_seconds": 5});
+        let v = parse_response(&tool.run(setup, c).await);
+        assert_eq!(v["exit_code"], 0, "got: {v}");
+        // The snapshot's umask does not leak into the command
+        assert_ne!(output_lines(&v), ["0077"]);
+
+        let handles = registry.get_or_create("conv-env-private").await;
+        let env_dir = handles.read().await.env_dir().to_path_buf();
+        let mode =
+            |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&env_dir), 0o700);
+        assert_eq!(mode(&env_dir.join("env.sh")), 0o600);
+    }
+
+    #[tokio::test]
+    async fn configured_shell_runs_the_command() {
+        // REQ-BASH-017: `$$` is the shell itself, so `ps` names it
+        let tool = BashTool;
+        let c = ctx().with_shell(crate::db::ShellSettings {
+            program: "/bin/sh".to_string(),
+            load_profile: false,
+        });
+        let result = tool
+            .run(json!({"cmd": "ps -o comm= -p $$", "wait_seconds": 5}), c)
+            .await;
+        let v = parse_response(&result);
+        assert_eq!(v["exit_code"], 0, "got: {v}");
+        let lines = output_lines(&v);
+        assert_eq!(lines.len(), 1, "got: {v}");
+        assert_ne!(lines[0].trim(), "bash");
+    }
+
+    #[tokio::test]
+    async fn policy_denied_command_is_refused_before_it_runs() {
+        // REQ-BASH-018
+        use super::policy::BashPolicy;
+        use crate::db::{CommandPolicy, PolicyRule};
+
+        let tool = BashTool;
+        let marker = temp_dir().join(format!("phoenix-policy-{}", uuid::Uuid::new_v4()));
+        let c = ctx().with_bash_policy(BashPolicy {
+            global: CommandPolicy::default(),
+            conversation: CommandPolicy {
+                allow: vec![],
+                deny: vec![PolicyRule::Prefix("touch".to_string())],
+            },
+        });
+        let cmd = format!("echo start && touch {}", marker.display());
+        let result = tool.run(json!({"cmd": cmd, "wait_seconds": 5}), c).await;
+        assert!(!result.success);
+        let v = parse_response(&result);
+        assert_eq!(v["error"], "command_policy_denied", "got: {v}");
+        assert_eq!(v["scope"], "conversation");
+        assert_eq!(v["rule"], r#"prefix "touch""#);
+        assert!(!marker.exists());
+        let hit = result
+            .policy_hit
+            .expect("the hit is kept for the audit log");
+        assert_eq!(hit.audit_label(), r#"deny conversation prefix "touch""#);
+    }
+
+    #[tokio::test]
+    async fn discarding_unrelated_changes_needs_confirmation() {
+        // REQ-BASH-019
+        let repo = tempfile::tempdir().unwrap();
+        let setup = "git init -q && echo a > notes.txt && git add . && \
+            git -c user.name=t -c user.email=t@example.com -c commit.gpgsign=false \
+            commit -qm init && echo b > notes.txt";
+        let status = std::process::Command::new("sh")
+            .args(["-c", setup])
+            .current_dir(repo.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let tool = BashTool;
+        let mut c = ctx();
+        c.working_dir = repo.path().to_path_buf();
+        let cmd = format!("cd {} && git checkout -- .", repo.path().display());
+        let result = tool
+            .run(json!({"cmd": cmd, "wait_seconds": 5}), c.clone())
+            .await;
+        let v = parse_response(&result);
+        assert_eq!(v["error"], "uncommitted_changes", "got: {v}");
+        assert_eq!(v["files"], json!(["notes.txt"]));
+        let notes = repo.path().join("notes.txt");
+        assert_eq!(std::fs::read_to_string(&notes).unwrap(), "b\n");
+
+        let input = json!({"cmd": cmd, "wait_seconds": 5, "confirm_discard": true});
+        let v = parse_response(&tool.run(input, c).await);
+        assert_eq!(v["exit_code"], 0, "got: {v}");
+        assert_eq!(std::fs::read_to_string(&notes).unwrap(), "a\n");
+    }
 }
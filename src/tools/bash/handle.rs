@@ -200,6 +200,10 @@ pub struct Handle {
     pub handle_id: HandleId,
     pub cmd: String,
     pub started_at: SystemTime,
+    /// Process group the command was started in. Kept after exit, since
+    /// background jobs the command left behind are still in it
+    /// (REQ-BASH-021).
+    pub pgid: i32,
     /// The current handle state. Always written through
     /// [`Self::transition_to_terminal`].
     state: RwLock<Arc<HandleState>>,
@@ -253,6 +257,7 @@ impl Handle {
             handle_id,
             cmd,
             started_at: SystemTime::now(),
+            pgid,
             state: RwLock::new(Arc::new(HandleState::Live(live))),
             kill_attempt: RwLock::new(None),
             exit_signal: tx,
@@ -12,13 +12,14 @@
 //! "exactly one operation per call" mutual exclusion structurally
 //! representable rather than runtime-checked.
 
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use serde::Deserialize;
 use serde_json::{json, Value};
-use tokio::process::Command;
 use tokio::sync::RwLock;
 
 #[cfg(unix)]
@@ -28,14 +29,16 @@ use super::handle::{
     ExitState, ExitWatchPanicGuard, FinalCause, Handle, HandleId, HandleState, KillSignal,
     TOMBSTONE_TAIL_LINES,
 };
+use super::policy::PolicyHit;
 use super::registry::{BashHandleError, ConversationHandles, LiveHandleSummary};
 use super::ring::{RingLine, WindowView};
+use super::shell::{self, ShellKind};
 use crate::api::wire::{
     BashErrorResponse, BashKillPendingKernelPayload, BashLiveHandleSummary, BashResponse,
     BashRingLine, BashRingWindow, BashRunningPayload, BashSpawnTombstonePayload,
     BashStillRunningPayload, BashTombstonedPayload, BashWaiterPanickedPayload,
 };
-use crate::tools::{ToolContext, ToolOutput};
+use crate::tools::{preflight, ToolContext, ToolOutput};
 
 // ---------------------------------------------------------------------------
 // Configuration constants (REQ-BASH config)
@@ -59,6 +62,14 @@ pub const DEFAULT_PEEK_LINES: usize = 200;
 const CAP_HINT: &str =
     "kill or wait on a handle before spawning more, or use the tmux tool for long-runners";
 
+// Hint text for uncommitted_changes refusals (REQ-BASH-019).
+const UNCOMMITTED_HINT: &str =
+    "these may be the user's work in progress. Ask the user before discarding them; if they \
+     agree, re-run the same cmd with confirm_discard=true";
+
+// Files named in an uncommitted_changes message; the `files` field has all.
+const UNCOMMITTED_LISTED: usize = 10;
+
 // Hint text for handle_not_found responses (REQ-BASH-008/010): tmux pointer
 // for handles that may predate this Phoenix process.
 const HANDLE_NOT_FOUND_HINT: &str =
@@ -96,6 +107,8 @@ struct RawBashInput {
     since: Option<i64>,
     #[serde(default)]
     mode: Option<String>,
+    #[serde(default)]
+    confirm_discard: bool,
     /// Legacy alias from the pre-handle revision. Some sub-agents and old
     /// fixtures still pass `command=...`; treat it as `cmd`.
     #[serde(default)]
@@ -119,6 +132,8 @@ pub enum BashRequest {
         wait_seconds: u64,
         read_args: ReadArgs,
         deprecation_notice: Option<String>,
+        /// The user agreed to discard unrelated changes (REQ-BASH-019)
+        confirm_discard: bool,
     },
     Peek {
         handle_id: String,
@@ -151,6 +166,11 @@ pub enum BashError {
     CommandSafetyRejected {
         reason: String,
     },
+    CommandPolicyDenied(PolicyHit),
+    UncommittedChanges {
+        command: String,
+        files: Vec<String>,
+    },
     SpawnFailed {
         error_message: String,
     },
@@ -165,6 +185,7 @@ pub enum BashError {
 }
 
 impl BashError {
+    #[allow(clippy::too_many_lines)] // one arm per error
     fn into_tool_output(self) -> ToolOutput {
         let typed: BashErrorResponse = match self {
             BashError::HandleNotFound { handle_id } => BashErrorResponse::HandleNotFound {
@@ -213,6 +234,35 @@ impl BashError {
                     reason,
                 }
             }
+            BashError::CommandPolicyDenied(hit) => BashErrorResponse::CommandPolicyDenied {
+                error_message: hit.message(),
+                scope: hit
+                    .rule
+                    .as_ref()
+                    .map(|(scope, _)| scope.as_str().to_string()),
+                rule: hit.rule_text(),
+                command: hit.command,
+            },
+            BashError::UncommittedChanges { command, files } => {
+                let mut listed = files
+                    .iter()
+                    .take(UNCOMMITTED_LISTED)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if files.len() > UNCOMMITTED_LISTED {
+                    let _ = write!(listed, " and {} more", files.len() - UNCOMMITTED_LISTED);
+                }
+                BashErrorResponse::UncommittedChanges {
+                    error_message: format!(
+                        "`{command}` would discard uncommitted changes this conversation did \
+                         not make: {listed}"
+                    ),
+                    command,
+                    files,
+                    hint: UNCOMMITTED_HINT.to_string(),
+                }
+            }
             BashError::SpawnFailed { error_message } => {
                 BashErrorResponse::SpawnFailed { error_message }
             }
@@ -365,6 +415,7 @@ pub fn parse_request(input: Value) -> Result<BashRequest, BashError> {
             wait_seconds,
             read_args,
             deprecation_notice,
+            confirm_discard: raw.confirm_discard,
         });
     }
     if let Some(handle_id) = raw.peek {
@@ -469,7 +520,23 @@ pub async fn dispatch(input: Value, ctx: ToolContext) -> ToolOutput {
             wait_seconds,
             read_args,
             deprecation_notice,
-        } => run_spawn(&cmd, wait_seconds, read_args, deprecation_notice, &ctx).await,
+            confirm_discard,
+        } => {
+            // REQ-BASH-018: the command policy decides before anything runs
+            let hit = ctx.bash_policy.evaluate(&cmd);
+            let output = match &hit {
+                Some(hit) if hit.denied => {
+                    BashError::CommandPolicyDenied(hit.clone()).into_tool_output()
+                }
+                _ => match check_uncommitted(&cmd, confirm_discard, &ctx).await {
+                    Err(e) => e.into_tool_output(),
+                    Ok(()) => {
+                        run_spawn(&cmd, wait_seconds, read_args, deprecation_notice, &ctx).await
+                    }
+                },
+            };
+            output.with_policy_hit(hit)
+        }
         BashRequest::Peek {
             handle_id,
             read_args,
@@ -497,6 +564,29 @@ pub async fn dispatch(input: Value, ctx: ToolContext) -> ToolOutput {
 // Spawn
 // ---------------------------------------------------------------------------
 
+/// REQ-BASH-019: refuse a git command that would discard uncommitted
+/// changes the conversation did not make, until the agent confirms the
+/// user agreed.
+async fn check_uncommitted(
+    cmd: &str,
+    confirm_discard: bool,
+    ctx: &ToolContext,
+) -> Result<(), BashError> {
+    if confirm_discard {
+        return Ok(());
+    }
+    let Some((command, discards)) = preflight::destructive_git_command(cmd) else {
+        return Ok(());
+    };
+    let target = preflight::Target::Tree(discards);
+    let files = preflight::unrelated_changes(&ctx.working_dir, target, &ctx.edited_files).await;
+    if files.is_empty() {
+        Ok(())
+    } else {
+        Err(BashError::UncommittedChanges { command, files })
+    }
+}
+
 async fn run_spawn(
     cmd: &str,
     wait_seconds: u64,
@@ -535,7 +625,13 @@ async fn run_spawn(
         // from the spawned child. We hold the write lock across the spawn
         // so no other spawn can race the cap check, then insert below.
         // Spawn is fast (a fork+exec) so this lock-hold is bounded.
-        match spawn_child(cmd, ctx, handle_id.clone(), ring_bytes_cap) {
+        match spawn_child(
+            cmd,
+            handles.env_dir(),
+            ctx,
+            handle_id.clone(),
+            ring_bytes_cap,
+        ) {
             Ok((handle, child)) => {
                 let inserted = handles.insert(handle.clone());
                 drop(handles);
@@ -566,6 +662,7 @@ async fn run_spawn(
 #[allow(clippy::similar_names)]
 fn spawn_child(
     cmd: &str,
+    env_dir: &Path,
     ctx: &ToolContext,
     handle_id: HandleId,
     ring_bytes_cap: usize,
@@ -587,15 +684,31 @@ fn spawn_child(
     // itself is what gets signaled — same outcome as exec'd bash. The
     // load-bearing piece is the process-group leader bit (setpgid below)
     // so that `kill(-pgid, sig)` reaches the user's processes.
-    let mut command = Command::new("bash");
+    //
+    // REQ-BASH-017: the conversation may pick another shell, or a login
+    // shell; the same `-c` and process-group handling applies.
+    let mut command = shell::command(&ctx.shell, cmd);
     command
-        .arg("-c")
-        .arg(cmd)
         .current_dir(&ctx.working_dir)
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
+    // REQ-BASH-016: bash sources BASH_ENV before running `cmd`, so the
+    // argv, `$0` and line numbers in error messages stay the user's own.
+    // Other shells have no such hook and start from the process's
+    // environment each time.
+    if ShellKind::of(&ctx.shell.program) == ShellKind::Bash {
+        match write_env_prelude(env_dir) {
+            Ok(prelude) => {
+                command.env("BASH_ENV", prelude);
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "no bash environment snapshot; spawning without it");
+            }
+        }
+    }
+
     #[cfg(unix)]
     unsafe {
         command.pre_exec(|| {
@@ -612,7 +725,7 @@ fn spawn_child(
 
     let child = command
         .spawn()
-        .map_err(|e| format!("failed to spawn bash child: {e}"))?;
+        .map_err(|e| format!("failed to spawn {} child: {e}", ctx.shell.program))?;
 
     let pid = child
         .id()
@@ -631,6 +744,55 @@ fn spawn_child(
     Ok((handle, child))
 }
 
+/// Sourced by every spawned shell through `BASH_ENV` (REQ-BASH-016).
+///
+/// Restores the exported variables and functions the last command left
+/// behind, then arranges for this shell to write its own on exit. The
+/// snapshot is replaced by rename so concurrent handles never read a
+/// half-written file; with several handles exiting, the last one wins.
+/// `PWD`/`OLDPWD`/`SHLVL`/`_` are left out: bash maintains them itself,
+/// and a restored `PWD` would disagree with the real working directory.
+/// `BASH_ENV` is un-exported first so scripts the command runs do not
+/// source this file and clobber the snapshot.
+///
+/// The EXIT trap runs on `exit`, on errors and on signals bash can catch,
+/// and does not change the exit status bash reports, so the REQ-BASH-006
+/// signal and exit-code semantics are unchanged.
+///
+/// Exported variables often hold secrets, so the snapshot is written under
+/// `umask 077`. The umask is set in a subshell around the write only; the
+/// command's own files keep the server's umask.
+const ENV_PRELUDE: &str = r#"export -n BASH_ENV
+if [ -r "${BASH_ENV%/*}/env.sh" ]; then . "${BASH_ENV%/*}/env.sh"; fi
+trap '(
+  umask 077
+  {
+    export -p | grep -Ev "^declare -[^ ]* (PWD|OLDPWD|SHLVL|_)(=|$)"
+    declare -f
+  } >"${BASH_ENV%/*}/env.sh.$$"
+) 2>/dev/null &&
+  mv -f "${BASH_ENV%/*}/env.sh.$$" "${BASH_ENV%/*}/env.sh"' EXIT
+"#;
+
+/// Write the prelude into the conversation's snapshot directory if it is
+/// not there yet, returning its path. The directory is private to the
+/// server's user.
+fn write_env_prelude(env_dir: &Path) -> std::io::Result<PathBuf> {
+    let prelude = env_dir.join("prelude.sh");
+    if !prelude.exists() {
+        let mut builder = std::fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder.create(env_dir)?;
+        std::fs::write(&prelude, ENV_PRELUDE)?;
+    }
+    Ok(prelude)
+}
+
 fn start_io_tasks(handle: &Arc<Handle>, mut child: tokio::process::Child) {
     let stdout = child.stdout.take();
     let stderr = child.stderr.take();
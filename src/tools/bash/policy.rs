@@ -0,0 +1,290 @@
+//! Command allow/deny policy (REQ-BASH-018).
+//!
+//! The operator sets lists for the whole server and the user sets lists per
+//! conversation; both are checked before a command spawns. Deny rules win
+//! over allow rules from either list. Once any allow rule exists, every
+//! simple command in the script must match one.
+//!
+//! Rules are checked against the whole script and against each simple
+//! command the parser finds, so `cd repo && git push` meets a `git push`
+//! rule. This is a guardrail against an agent's habits, not a sandbox: a
+//! command assembled at run time (`eval`, `$(...)`, a script file) is not
+//! seen.
+
+use regex::Regex;
+
+use crate::db::{CommandPolicy, PolicyRule};
+use crate::tools::bash_check::simple_commands;
+
+/// Words that run the rest of the command; a rule for `rm` also meets
+/// `sudo rm`.
+const WRAPPERS: [&str; 5] = ["sudo", "command", "exec", "nohup", "time"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyScope {
+    Global,
+    Conversation,
+}
+
+impl PolicyScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Global => "global",
+            Self::Conversation => "conversation",
+        }
+    }
+}
+
+/// What the policy made of a script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyHit {
+    pub denied: bool,
+    /// The rule that decided; `None` when a command matched no allow rule.
+    pub rule: Option<(PolicyScope, PolicyRule)>,
+    /// The command the decision is about.
+    pub command: String,
+}
+
+impl PolicyHit {
+    /// The rule as written, e.g. `prefix "git push"`.
+    pub fn rule_text(&self) -> Option<String> {
+        self.rule.as_ref().map(|(_, rule)| describe(rule))
+    }
+
+    /// One line for the audit log, e.g. `deny conversation prefix "git push"`.
+    pub fn audit_label(&self) -> String {
+        let verdict = if self.denied { "deny" } else { "allow" };
+        match &self.rule {
+            Some((scope, rule)) => format!("{verdict} {} {}", scope.as_str(), describe(rule)),
+            None => format!("{verdict}: not on the allow list"),
+        }
+    }
+
+    /// The refusal shown to the agent.
+    pub fn message(&self) -> String {
+        match &self.rule {
+            Some((scope, rule)) => format!(
+                "`{}` is refused by the {} bash policy ({}). Do not try to work around \
+                 it; ask the user to run it or to change the policy.",
+                self.command,
+                scope.as_str(),
+                describe(rule)
+            ),
+            None => format!(
+                "`{}` matches no allow rule of the bash policy. Ask the user to run it \
+                 or to allow it.",
+                self.command
+            ),
+        }
+    }
+}
+
+/// The server's and a conversation's lists together.
+#[derive(Debug, Clone, Default)]
+pub struct BashPolicy {
+    pub global: CommandPolicy,
+    pub conversation: CommandPolicy,
+}
+
+impl BashPolicy {
+    pub fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.conversation.is_empty()
+    }
+
+    /// The decision for `script`, or `None` when no rule applies.
+    pub fn evaluate(&self, script: &str) -> Option<PolicyHit> {
+        if self.is_empty() {
+            return None;
+        }
+        let script = script.trim();
+        // A script the parser rejects is judged as a single command
+        let commands = simple_commands(script).unwrap_or_else(|| vec![script.to_string()]);
+        let lists = [
+            (PolicyScope::Global, &self.global),
+            (PolicyScope::Conversation, &self.conversation),
+        ];
+
+        let candidates = std::iter::once(script).chain(commands.iter().map(String::as_str));
+        for (scope, policy) in lists {
+            for rule in &policy.deny {
+                if let Some(command) = candidates.clone().find(|c| matches(rule, c)) {
+                    return Some(PolicyHit {
+                        denied: true,
+                        rule: Some((scope, rule.clone())),
+                        command: command.to_string(),
+                    });
+                }
+            }
+        }
+
+        let allow: Vec<(PolicyScope, &PolicyRule)> = lists
+            .iter()
+            .flat_map(|(scope, policy)| policy.allow.iter().map(|rule| (*scope, rule)))
+            .collect();
+        if allow.is_empty() {
+            return None;
+        }
+        let mut first = None;
+        for command in &commands {
+            match allow.iter().find(|(_, rule)| matches(rule, command)) {
+                Some((scope, rule)) if first.is_none() => {
+                    first = Some(PolicyHit {
+                        denied: false,
+                        rule: Some((*scope, (*rule).clone())),
+                        command: command.clone(),
+                    });
+                }
+                Some(_) => {}
+                None => {
+                    return Some(PolicyHit {
+                        denied: true,
+                        rule: None,
+                        command: command.clone(),
+                    });
+                }
+            }
+        }
+        first
+    }
+}
+
+/// Check a policy before it is stored.
+pub fn validate(policy: &CommandPolicy) -> Result<(), String> {
+    for rule in policy.allow.iter().chain(&policy.deny) {
+        match rule {
+            PolicyRule::Prefix(prefix) if prefix.trim().is_empty() => {
+                return Err("Prefix rules must not be empty".to_string());
+            }
+            PolicyRule::Regex(pattern) => {
+                Regex::new(pattern).map_err(|e| format!("Invalid regex {pattern:?}: {e}"))?;
+            }
+            PolicyRule::Prefix(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn describe(rule: &PolicyRule) -> String {
+    match rule {
+        PolicyRule::Prefix(prefix) => format!("prefix {prefix:?}"),
+        PolicyRule::Regex(pattern) => format!("regex {pattern:?}"),
+    }
+}
+
+fn matches(rule: &PolicyRule, command: &str) -> bool {
+    let mut command = command.trim();
+    loop {
+        let hit = match rule {
+            PolicyRule::Prefix(prefix) => command
+                .strip_prefix(prefix.trim())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace)),
+            PolicyRule::Regex(pattern) => match Regex::new(pattern) {
+                Ok(re) => re.is_match(command),
+                Err(e) => {
+                    tracing::warn!(pattern, error = %e, "Ignoring invalid bash policy regex");
+                    false
+                }
+            },
+        };
+        if hit {
+            return true;
+        }
+        match command.split_once(char::is_whitespace) {
+            Some((word, rest)) if WRAPPERS.contains(&word) => command = rest.trim_start(),
+            _ => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefix(p: &str) -> PolicyRule {
+        PolicyRule::Prefix(p.to_string())
+    }
+
+    fn regex(p: &str) -> PolicyRule {
+        PolicyRule::Regex(p.to_string())
+    }
+
+    fn policy(global: CommandPolicy, conversation: CommandPolicy) -> BashPolicy {
+        BashPolicy {
+            global,
+            conversation,
+        }
+    }
+
+    #[test]
+    fn prefixes_match_whole_words_through_wrappers() {
+        assert!(matches(&prefix("git push"), "git push origin main"));
+        assert!(matches(&prefix("git push"), "git push"));
+        assert!(!matches(&prefix("git push"), "git pushy"));
+        assert!(matches(&prefix("rm"), "sudo rm -rf /tmp/x"));
+        assert!(matches(&regex(r"--force\b"), "git push --force"));
+    }
+
+    #[test]
+    fn deny_rules_see_every_command_of_the_script() {
+        let deny = CommandPolicy {
+            allow: vec![prefix("git")],
+            deny: vec![prefix("git push")],
+        };
+        let policy = policy(CommandPolicy::default(), deny);
+        let hit = policy.evaluate("cd /repo && git push origin main").unwrap();
+        assert!(hit.denied);
+        assert_eq!(hit.command, "git push origin main");
+        assert_eq!(hit.audit_label(), r#"deny conversation prefix "git push""#);
+
+        let hit = policy.evaluate("git status").unwrap();
+        assert!(!hit.denied);
+        assert_eq!(hit.audit_label(), r#"allow conversation prefix "git""#);
+    }
+
+    #[test]
+    fn an_allow_list_refuses_commands_it_does_not_name() {
+        let global = CommandPolicy {
+            allow: vec![prefix("cargo"), regex("^(ls|cat) ")],
+            deny: vec![],
+        };
+        let policy = policy(global, CommandPolicy::default());
+        assert!(!policy.evaluate("cargo build && cargo test").unwrap().denied);
+        let hit = policy.evaluate("ls -la && curl example.com").unwrap();
+        assert!(hit.denied);
+        assert_eq!(hit.rule, None);
+        assert_eq!(hit.command, "curl example.com");
+        assert!(!policy.evaluate("cat Cargo.toml").unwrap().denied);
+    }
+
+    #[test]
+    fn no_rules_means_no_decision() {
+        assert_eq!(BashPolicy::default().evaluate("rm -rf /tmp/x"), None);
+        let deny_only = CommandPolicy {
+            allow: vec![],
+            deny: vec![prefix("git push")],
+        };
+        assert_eq!(
+            policy(deny_only, CommandPolicy::default()).evaluate("ls"),
+            None
+        );
+    }
+
+    #[test]
+    fn validate_rejects_empty_prefixes_and_bad_regexes() {
+        let ok = CommandPolicy {
+            allow: vec![prefix("cargo")],
+            deny: vec![regex(r"^git\s+push")],
+        };
+        assert!(validate(&ok).is_ok());
+        let empty = CommandPolicy {
+            allow: vec![prefix(" ")],
+            deny: vec![],
+        };
+        assert!(validate(&empty).is_err());
+        let bad = CommandPolicy {
+            allow: vec![],
+            deny: vec![regex("(")],
+        };
+        assert!(validate(&bad).unwrap_err().contains("Invalid regex"));
+    }
+}
@@ -6,7 +6,8 @@
 //! Lifetime: registries live in process memory only. A Phoenix restart
 //! drops them and any handles they hold; agents see `handle_not_found` on
 //! a previously-known handle (matching the spec's "handles do NOT survive
-//! Phoenix restart" guarantee).
+//! Phoenix restart" guarantee). The same goes for each conversation's
+//! environment snapshot (REQ-BASH-016), which is deleted with its table.
 //!
 //! Lock ordering for cap enforcement and spawn (consumed by task 02694's
 //! `BashTool::spawn`): acquire the registry's `RwLock<HashMap>` for read,
@@ -16,6 +17,7 @@
 //! `count == cap - 1` and racing past the cap.
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::SystemTime;
 
@@ -59,17 +61,38 @@ pub struct LiveHandleSummary {
 /// This keeps the lookup path single-source — a handle that transitions
 /// from `Live` to `Tombstoned` is the SAME `Arc<Handle>` (its `state`
 /// field swaps), and lookup never has to "follow" between two maps.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct ConversationHandles {
     /// Next sequential handle index for this conversation (`b-1`, `b-2`, ...).
     next_id: u64,
     /// All handles, by id. Live and tombstoned alike.
     handles: HashMap<HandleId, Arc<Handle>>,
+    /// REQ-BASH-016: directory holding the environment snapshot that
+    /// carries exported variables and functions from one command to the
+    /// next. Created by the first spawn; removed when the table is dropped.
+    env_dir: PathBuf,
+}
+
+impl Default for ConversationHandles {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ConversationHandles {
     pub fn new() -> Self {
-        Self::default()
+        let name = format!("phoenix-bash-{}", uuid::Uuid::new_v4());
+        Self {
+            next_id: 0,
+            handles: HashMap::new(),
+            env_dir: std::env::temp_dir().join(name),
+        }
+    }
+
+    /// Directory of this conversation's environment snapshot
+    /// (REQ-BASH-016). It may not exist yet.
+    pub fn env_dir(&self) -> &Path {
+        &self.env_dir
     }
 
     /// Allocate the next handle id and increment the counter. Format:
@@ -157,6 +180,13 @@ impl ConversationHandles {
     }
 }
 
+impl Drop for ConversationHandles {
+    fn drop(&mut self) {
+        // Not-found is the common case: the conversation never ran bash
+        let _ = std::fs::remove_dir_all(&self.env_dir);
+    }
+}
+
 /// Top-level registry: maps `conversation_id` -> per-conversation handle table.
 ///
 /// One registry instance per Phoenix process. Owned by the runtime layer
@@ -244,6 +274,18 @@ impl BashHandleRegistry {
         out
     }
 
+    /// Every handle `conversation_id` has spawned, live or exited, for
+    /// finding the processes it left running (REQ-BASH-021). Does not
+    /// create a table for a conversation that never ran bash.
+    pub async fn conversation_handles(&self, conversation_id: &str) -> Vec<Arc<Handle>> {
+        let entry = self.inner.read().await.get(conversation_id).cloned();
+        let Some(entry) = entry else {
+            return Vec::new();
+        };
+        let handles = entry.read().await;
+        handles.all().cloned().collect()
+    }
+
     /// Remove a conversation's handle table outright. Used by the
     /// hard-delete cascade (REQ-BASH-006). Returns the removed entry so
     /// the caller can SIGKILL its live process groups synchronously.
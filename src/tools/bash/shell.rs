@@ -0,0 +1,120 @@
+//! Which shell runs a spawned command (REQ-BASH-017).
+//!
+//! A conversation picks `bash` (the default), `zsh`, `fish`, or any shell
+//! by absolute path, and whether it starts as a login shell so the user's
+//! profile runs first. Flags follow the shell's kind, read from the
+//! program's file name; a shell of unknown kind gets only `-l` and `-c`.
+//!
+//! Only a non-login `bash` is the pre-REQ-BASH-017 behaviour; the others
+//! trade reproducibility for matching the user's terminal.
+
+use std::path::Path;
+
+use tokio::process::Command;
+
+use crate::db::ShellSettings;
+
+/// Shells that may be named without a path; found on `PATH` at spawn.
+const NAMED_SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ShellKind {
+    Bash,
+    Zsh,
+    Fish,
+    Other,
+}
+
+impl ShellKind {
+    pub(super) fn of(program: &str) -> Self {
+        match Path::new(program).file_name().and_then(|n| n.to_str()) {
+            Some("bash") => Self::Bash,
+            Some("zsh") => Self::Zsh,
+            Some("fish") => Self::Fish,
+            _ => Self::Other,
+        }
+    }
+
+    /// Startup flags. Without a profile, zsh and fish are told to skip
+    /// their rc files so commands behave the same on every machine, as
+    /// non-interactive bash already does.
+    fn flags(self, load_profile: bool) -> &'static [&'static str] {
+        match (self, load_profile) {
+            // Non-interactive bash ignores aliases unless told otherwise
+            (Self::Bash, true) => &["-l", "-O", "expand_aliases"],
+            (Self::Bash | Self::Other, false) => &[],
+            (Self::Zsh | Self::Fish | Self::Other, true) => &["-l"],
+            (Self::Zsh, false) => &["-f"],
+            (Self::Fish, false) => &["--no-config"],
+        }
+    }
+}
+
+/// Check a shell setting before it is stored.
+pub fn validate(settings: &ShellSettings) -> Result<(), String> {
+    let program = settings.program.as_str();
+    if NAMED_SHELLS.contains(&program) {
+        return Ok(());
+    }
+    let path = Path::new(program);
+    if !path.is_absolute() {
+        return Err(format!(
+            "Shell must be one of {} or an absolute path: {program}",
+            NAMED_SHELLS.join(", ")
+        ));
+    }
+    if !path.is_file() {
+        return Err(format!("Shell does not exist: {program}"));
+    }
+    Ok(())
+}
+
+/// A command that runs `cmd` in the configured shell.
+pub(super) fn command(settings: &ShellSettings, cmd: &str) -> Command {
+    let kind = ShellKind::of(&settings.program);
+    let mut command = Command::new(&settings.program);
+    command
+        .args(kind.flags(settings.load_profile))
+        .arg("-c")
+        .arg(cmd);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell(program: &str, load_profile: bool) -> ShellSettings {
+        ShellSettings {
+            program: program.to_string(),
+            load_profile,
+        }
+    }
+
+    #[test]
+    fn kind_comes_from_the_file_name() {
+        assert_eq!(ShellKind::of("bash"), ShellKind::Bash);
+        assert_eq!(ShellKind::of("/opt/homebrew/bin/zsh"), ShellKind::Zsh);
+        assert_eq!(ShellKind::of("/usr/local/bin/fish"), ShellKind::Fish);
+        assert_eq!(ShellKind::of("/bin/dash"), ShellKind::Other);
+    }
+
+    #[test]
+    fn flags_follow_kind_and_profile() {
+        assert!(ShellKind::Bash.flags(false).is_empty());
+        assert_eq!(ShellKind::Bash.flags(true), ["-l", "-O", "expand_aliases"]);
+        assert_eq!(ShellKind::Zsh.flags(false), ["-f"]);
+        assert_eq!(ShellKind::Fish.flags(false), ["--no-config"]);
+        assert_eq!(ShellKind::Other.flags(true), ["-l"]);
+    }
+
+    #[test]
+    fn shells_are_named_or_absolute_paths_that_exist() {
+        assert!(validate(&shell("zsh", true)).is_ok());
+        assert!(validate(&shell("/bin/sh", false)).is_ok());
+        let err = validate(&shell("tcsh", false)).unwrap_err();
+        assert!(err.contains("absolute path"), "{err}");
+        let err = validate(&shell("/no/such/shell", false)).unwrap_err();
+        assert!(err.contains("does not exist"), "{err}");
+    }
+}
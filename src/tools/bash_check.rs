@@ -34,11 +34,33 @@ pub fn check(script: &str) -> Result<(), CheckError> {
     })?;
 
     for complete_cmd in &program.complete_commands {
-        check_compound_list(complete_cmd)?;
+        walk_compound_list(complete_cmd, &mut check_simple_command)?;
     }
     Ok(())
 }
 
+/// The simple commands of a script as `name arg...`, in source order, or
+/// `None` if it does not parse. Assignments and redirects are left out.
+/// Used by the command policy (REQ-BASH-018).
+pub fn simple_commands(script: &str) -> Option<Vec<String>> {
+    let cursor = Cursor::new(script);
+    let mut parser = Parser::new(cursor, &ParserOptions::default(), &SourceInfo::default());
+    let program = parser.parse_program().ok()?;
+
+    let mut commands = Vec::new();
+    let mut collect = |cmd: &SimpleCommand| -> Result<(), CheckError> {
+        let args = collect_simple_command_args(cmd);
+        if !args.is_empty() {
+            commands.push(args.join(" "));
+        }
+        Ok(())
+    };
+    for complete_cmd in &program.complete_commands {
+        walk_compound_list(complete_cmd, &mut collect).ok()?;
+    }
+    Some(commands)
+}
+
 /// Extract the "interesting" part of a bash command for display purposes.
 ///
 /// LLMs commonly emit commands like `cd /path && actual_command`. For UI display,
@@ -290,63 +312,66 @@ fn paths_match(target: &str, cwd: &str) -> bool {
     target_canonical == cwd_canonical
 }
 
-/// Recursively check all nodes in the AST
-/// Check a `CompoundList` (sequence of commands separated by ; or &)
-fn check_compound_list(list: &CompoundList) -> Result<(), CheckError> {
+/// Called on every simple command the walk reaches
+type Visit<'a> = dyn FnMut(&SimpleCommand) -> Result<(), CheckError> + 'a;
+
+/// Recursively visit all simple commands in the AST.
+/// Walk a `CompoundList` (sequence of commands separated by ; or &)
+fn walk_compound_list(list: &CompoundList, visit: &mut Visit) -> Result<(), CheckError> {
     for item in &list.0 {
-        check_and_or_list(&item.0)?;
+        walk_and_or_list(&item.0, visit)?;
     }
     Ok(())
 }
 
-/// Check an `AndOrList` (commands connected by && or ||)
-fn check_and_or_list(list: &AndOrList) -> Result<(), CheckError> {
-    check_pipeline(&list.first)?;
+/// Walk an `AndOrList` (commands connected by && or ||)
+fn walk_and_or_list(list: &AndOrList, visit: &mut Visit) -> Result<(), CheckError> {
+    walk_pipeline(&list.first, visit)?;
     for and_or in &list.additional {
         match and_or {
-            AndOr::And(pipeline) | AndOr::Or(pipeline) => check_pipeline(pipeline)?,
+            AndOr::And(pipeline) | AndOr::Or(pipeline) => walk_pipeline(pipeline, visit)?,
         }
     }
     Ok(())
 }
 
-/// Check a Pipeline (commands connected by |)
-fn check_pipeline(pipeline: &Pipeline) -> Result<(), CheckError> {
+/// Walk a Pipeline (commands connected by |)
+fn walk_pipeline(pipeline: &Pipeline, visit: &mut Visit) -> Result<(), CheckError> {
     for cmd in &pipeline.seq {
-        check_command(cmd)?;
+        walk_command(cmd, visit)?;
     }
     Ok(())
 }
 
-/// Check a single Command node
-fn check_command(cmd: &Command) -> Result<(), CheckError> {
+/// Walk a single Command node
+fn walk_command(cmd: &Command, visit: &mut Visit) -> Result<(), CheckError> {
     match cmd {
-        Command::Simple(simple) => check_simple_command(simple),
-        Command::Compound(compound, _redirects) => check_compound_command(compound),
-        Command::Function(func) => check_compound_command(&func.body.0),
+        Command::Simple(simple) => visit(simple),
+        Command::Compound(compound, _redirects) => walk_compound_command(compound, visit),
+        Command::Function(func) => walk_compound_command(&func.body.0, visit),
         Command::ExtendedTest(_) => Ok(()), // [[ ... ]] doesn't execute commands
     }
 }
 
-/// Check a `CompoundCommand` (loops, conditionals, subshells, brace groups)
-fn check_compound_command(cmd: &CompoundCommand) -> Result<(), CheckError> {
+/// Walk a `CompoundCommand` (loops, conditionals, subshells, brace groups)
+fn walk_compound_command(cmd: &CompoundCommand, visit: &mut Visit) -> Result<(), CheckError> {
     match cmd {
-        CompoundCommand::BraceGroup(bg) => check_compound_list(&bg.list),
-        CompoundCommand::Subshell(sub) => check_compound_list(&sub.list),
-        CompoundCommand::ForClause(fc) => check_compound_list(&fc.body.list),
+        CompoundCommand::BraceGroup(bg) => walk_compound_list(&bg.list, visit),
+        CompoundCommand::Subshell(sub) => walk_compound_list(&sub.list, visit),
+        CompoundCommand::ForClause(fc) => walk_compound_list(&fc.body.list, visit),
         CompoundCommand::WhileClause(wc) | CompoundCommand::UntilClause(wc) => {
-            check_compound_list(&wc.0)?; // condition
-            check_compound_list(&wc.1.list) // body
+            walk_compound_list(&wc.0, visit)?; // condition
+            walk_compound_list(&wc.1.list, visit) // body
         }
         CompoundCommand::IfClause(ic) => {
-            check_compound_list(&ic.condition)?;
-            check_compound_list(&ic.then)?;
+            walk_compound_list(&ic.condition, visit)?;
+            walk_compound_list(&ic.then, visit)?;
             if let Some(elses) = &ic.elses {
                 for else_clause in elses {
                     if let Some(cond) = &else_clause.condition {
-                        check_compound_list(cond)?;
+                        walk_compound_list(cond, visit)?;
                     }
-                    check_compound_list(&else_clause.body)?;
+                    walk_compound_list(&else_clause.body, visit)?;
                 }
             }
             Ok(())
@@ -354,7 +379,7 @@ fn check_compound_command(cmd: &CompoundCommand) -> Result<(), CheckError> {
         CompoundCommand::CaseClause(cc) => {
             for item in &cc.cases {
                 if let Some(cmd) = &item.cmd {
-                    check_compound_list(cmd)?;
+                    walk_compound_list(cmd, visit)?;
                 }
             }
             Ok(())
@@ -949,4 +974,23 @@ mod tests {
         // cd . && command with cwd /foo should strip the cd
         assert_eq!(display_command("cd . && cargo test", "/tmp"), "cargo test");
     }
+
+    // ==================== Simple Command Listing ====================
+
+    #[test]
+    fn test_simple_commands_lists_every_command() {
+        let commands = simple_commands(
+            "cd /repo && FOO=1 cargo test | tee out.log; if true; then git push; fi",
+        )
+        .unwrap();
+        assert_eq!(
+            commands,
+            ["cd /repo", "cargo test", "tee out.log", "true", "git push"]
+        );
+    }
+
+    #[test]
+    fn test_simple_commands_none_when_unparseable() {
+        assert!(simple_commands("echo 'unterminated").is_none());
+    }
 }
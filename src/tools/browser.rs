@@ -4,8 +4,16 @@
 //! REQ-BT-011: State Persistence
 //! REQ-BT-012: Stateless Tools with Context Injection
 //! REQ-BT-017: React Component Access
+//! REQ-BT-027: JavaScript Dialog Handling
+//! REQ-BT-028: Iframe Targeting
+//! REQ-BT-030: Geolocation, Timezone and Locale Emulation
+//! REQ-BT-031: PDF Rendering
+//! REQ-BT-032: Structured Element Query
+//! REQ-BT-033: Live View and Headful Mode
 
+mod frames;
 pub mod react;
+pub mod screencast;
 pub mod session;
 mod tools;
 
@@ -14,7 +22,9 @@ mod tests;
 
 pub use session::{BrowserError, BrowserSessionManager};
 pub use tools::{
-    BrowserClearConsoleLogsTool, BrowserClickTool, BrowserEvalTool, BrowserKeyPressTool,
-    BrowserNavigateTool, BrowserRecentConsoleLogsTool, BrowserResizeTool,
-    BrowserTakeScreenshotTool, BrowserTypeTool, BrowserWaitForSelectorTool,
+    BrowserClearConsoleLogsTool, BrowserClickTool, BrowserEvalTool, BrowserHandleDialogTool,
+    BrowserKeyPressTool, BrowserNavigateTool, BrowserPrintToPdfTool, BrowserQueryTool,
+    BrowserRecentConsoleLogsTool, BrowserResizeTool, BrowserSetGeolocationTool,
+    BrowserSetLocaleTool, BrowserTakeScreenshotTool, BrowserThrottleTool, BrowserTypeTool,
+    BrowserWaitForSelectorTool,
 };
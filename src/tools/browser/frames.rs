@@ -0,0 +1,219 @@
+//! Acting inside iframes (REQ-BT-028)
+//!
+//! Selector-based tools take an optional `frame`: the frame's name, or a
+//! substring of its URL. The selector is then resolved in that frame's
+//! JavaScript context instead of the top document. Clicks still go through
+//! CDP mouse events at the element's on-screen position, so pages see the
+//! same trusted events as on the top document.
+//!
+//! Only frames rendered in the page's own process are reachable. Under site
+//! isolation a cross-origin iframe runs out of process and is not listed.
+
+use chromiumoxide::cdp::browser_protocol::dom::GetContentQuadsParams;
+use chromiumoxide::cdp::browser_protocol::input::{
+    DispatchMouseEventParams, DispatchMouseEventType, MouseButton,
+};
+use chromiumoxide::cdp::js_protocol::runtime::{EvaluateParams, ExecutionContextId};
+use chromiumoxide::error::CdpError;
+use chromiumoxide::Page;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub(super) enum FrameError {
+    /// The frame or element is not there, or not yet
+    #[error("{0}")]
+    Missing(String),
+    #[error("Browser operation failed: {0}")]
+    Failed(String),
+}
+
+impl From<CdpError> for FrameError {
+    fn from(e: CdpError) -> Self {
+        Self::Failed(e.to_string())
+    }
+}
+
+/// Input schema property shared by the tools that accept a frame.
+pub(super) fn frame_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "string",
+        "description": "Act inside an iframe instead of the top document: the frame's name \
+                        attribute, or part of its URL"
+    })
+}
+
+/// Whether a frame with `name` and `url` is the one `pattern` asks for.
+fn frame_matches(pattern: &str, name: Option<&str>, url: Option<&str>) -> bool {
+    !pattern.is_empty() && (name == Some(pattern) || url.is_some_and(|u| u.contains(pattern)))
+}
+
+/// The JavaScript context of the first child frame whose name is `pattern`
+/// or whose URL contains it. The error lists the frames there are, so the
+/// caller can pick one.
+pub(super) async fn resolve(page: &Page, pattern: &str) -> Result<ExecutionContextId, FrameError> {
+    let main = page.mainframe().await?;
+    let frames = page.frames().await?;
+
+    let mut available = Vec::new();
+    for id in frames {
+        if main.as_ref() == Some(&id) {
+            continue;
+        }
+        let name = page.frame_name(id.clone()).await.ok().flatten();
+        let url = page.frame_url(id.clone()).await.ok().flatten();
+        if frame_matches(pattern, name.as_deref(), url.as_deref()) {
+            return page.frame_execution_context(id).await?.ok_or_else(|| {
+                FrameError::Missing(format!("Frame '{pattern}' has not loaded yet"))
+            });
+        }
+        available.push(match (name, url) {
+            (Some(name), Some(url)) if !name.is_empty() => format!("'{name}' ({url})"),
+            (_, Some(url)) => url,
+            (Some(name), None) => format!("'{name}'"),
+            (None, None) => "(unnamed)".to_string(),
+        });
+    }
+
+    Err(FrameError::Missing(if available.is_empty() {
+        format!("No frame matches '{pattern}': the page has no iframes")
+    } else {
+        format!(
+            "No frame matches '{pattern}'. Frames: {}",
+            available.join(", ")
+        )
+    }))
+}
+
+/// Evaluate `expression` in a frame's context, awaiting promises.
+pub(super) fn evaluate_params(
+    expression: impl Into<String>,
+    context: ExecutionContextId,
+) -> EvaluateParams {
+    EvaluateParams::builder()
+        .expression(expression)
+        .context_id(context)
+        .await_promise(true)
+        .build()
+        .unwrap()
+}
+
+/// Click the element `selector` matches in a frame with CDP mouse events
+/// at its center.
+pub(super) async fn click(
+    page: &Page,
+    context: ExecutionContextId,
+    selector: &str,
+) -> Result<(), FrameError> {
+    let find = format!(
+        "(() => {{
+            const el = document.querySelector({selector});
+            if (el) el.scrollIntoView({{ block: 'center', inline: 'center' }});
+            return el;
+        }})()",
+        selector = serde_json::to_string(selector).unwrap()
+    );
+    let mut params = evaluate_params(find, context);
+    // Keep the element as a remote object so its position can be asked for
+    params.return_by_value = Some(false);
+    let element = page.evaluate(params).await?;
+    let Some(object_id) = element.object().object_id.clone() else {
+        return Err(FrameError::Missing(format!(
+            "Could not find element '{selector}' in frame"
+        )));
+    };
+
+    let quads = page
+        .execute(
+            GetContentQuadsParams::builder()
+                .object_id(object_id)
+                .build(),
+        )
+        .await?
+        .result
+        .quads;
+    let (x, y) = quads
+        .first()
+        .and_then(|quad| quad_center(quad.inner()))
+        .ok_or_else(|| {
+            FrameError::Missing(format!("Element '{selector}' has no visible area to click"))
+        })?;
+
+    for kind in [
+        DispatchMouseEventType::MouseMoved,
+        DispatchMouseEventType::MousePressed,
+        DispatchMouseEventType::MouseReleased,
+    ] {
+        let moving = matches!(kind, DispatchMouseEventType::MouseMoved);
+        let mut event = DispatchMouseEventParams::builder().r#type(kind).x(x).y(y);
+        if !moving {
+            event = event.button(MouseButton::Left).click_count(1);
+        }
+        page.execute(event.build().map_err(FrameError::Failed)?)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Center of a CDP quad: four `x, y` corner pairs.
+fn quad_center(points: &[f64]) -> Option<(f64, f64)> {
+    if points.len() < 8 {
+        return None;
+    }
+    let x = (points[0] + points[2] + points[4] + points[6]) / 4.0;
+    let y = (points[1] + points[3] + points[5] + points[7]) / 4.0;
+    Some((x, y))
+}
+
+/// Focus the element `selector` matches in a frame, selecting its text
+/// when `select` is set so the next key replaces it.
+pub(super) async fn focus(
+    page: &Page,
+    context: ExecutionContextId,
+    selector: &str,
+    select: bool,
+) -> Result<(), FrameError> {
+    let script = format!(
+        "(() => {{
+            const el = document.querySelector({selector});
+            if (!el) return false;
+            el.focus();
+            if ({select} && typeof el.select === 'function') el.select();
+            return true;
+        }})()",
+        selector = serde_json::to_string(selector).unwrap()
+    );
+    let found = page
+        .evaluate(evaluate_params(script, context))
+        .await?
+        .into_value::<bool>()
+        .unwrap_or(false);
+    if found {
+        Ok(())
+    } else {
+        Err(FrameError::Missing(format!(
+            "Could not find element '{selector}' in frame"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_match_by_name_or_url_part() {
+        let url = Some("https://pay.example.com/checkout?step=2");
+        assert!(frame_matches("payment", Some("payment"), url));
+        assert!(frame_matches("pay.example.com", None, url));
+        assert!(frame_matches("checkout", Some("other"), url));
+        assert!(!frame_matches("pay", Some("payment"), None));
+        assert!(!frame_matches("", Some(""), url));
+    }
+
+    #[test]
+    fn quad_center_averages_the_corners() {
+        let quad = [10.0, 20.0, 30.0, 20.0, 30.0, 60.0, 10.0, 60.0];
+        assert_eq!(quad_center(&quad), Some((20.0, 40.0)));
+        assert_eq!(quad_center(&quad[..6]), None);
+    }
+}
@@ -0,0 +1,275 @@
+//! Live view of a session's page (REQ-BT-033)
+//!
+//! A person can watch the page the agent is driving and take over with the
+//! mouse and keyboard when the agent gets stuck. Frames come from CDP's
+//! `Page.startScreencast`; input goes back through the same `Input` domain
+//! the tools use, so the page cannot tell the two apart.
+//!
+//! There is one viewer per session at a time: a second `startScreencast`
+//! would take the frames away from the first.
+
+use super::session::{push_console_entry, BrowserError, BrowserSession, ConsoleEntry};
+use chromiumoxide::cdp::browser_protocol::input::{
+    DispatchKeyEventParams, DispatchKeyEventType, DispatchMouseEventParams, DispatchMouseEventType,
+    InsertTextParams, MouseButton,
+};
+use chromiumoxide::cdp::browser_protocol::page::{
+    EventScreencastFrame, ScreencastFrameAckParams, StartScreencastFormat, StartScreencastParams,
+    StopScreencastParams,
+};
+use chromiumoxide::Page;
+use futures::Stream;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::RwLock;
+
+/// JPEG quality of streamed frames; enough to read text at a fraction of
+/// PNG's size
+const FRAME_QUALITY: i64 = 70;
+
+/// Frames are scaled down to fit, keeping bandwidth bounded on large windows
+const FRAME_MAX_WIDTH: i64 = 1600;
+const FRAME_MAX_HEIGHT: i64 = 1200;
+
+/// Input from the person watching, as sent over the live-view socket.
+/// Coordinates are CSS pixels of the page, as in the frame metadata.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ViewerInput {
+    Mouse {
+        event: MouseAction,
+        x: f64,
+        y: f64,
+        #[serde(default)]
+        button: ViewerButton,
+        #[serde(default)]
+        delta_x: f64,
+        #[serde(default)]
+        delta_y: f64,
+    },
+    Key {
+        event: KeyAction,
+        key: String,
+        #[serde(default)]
+        code: Option<String>,
+        /// Text the key types, if any (e.g. "a", "\r" for Enter)
+        #[serde(default)]
+        text: Option<String>,
+    },
+    /// Paste-like insertion of a whole string
+    Text { text: String },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseAction {
+    Down,
+    Up,
+    Move,
+    Wheel,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ViewerButton {
+    #[default]
+    Left,
+    Middle,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAction {
+    Down,
+    Up,
+}
+
+/// The claim on a session's live view. Dropping it frees the view for the
+/// next viewer.
+pub struct Viewer {
+    page: Page,
+    console_logs: Arc<StdMutex<VecDeque<ConsoleEntry>>>,
+    viewing: Arc<AtomicBool>,
+    took_over: AtomicBool,
+}
+
+impl Viewer {
+    /// Claim the session's live view, or `None` when someone is already
+    /// watching.
+    pub async fn claim(session: &Arc<RwLock<BrowserSession>>) -> Option<Self> {
+        let guard = session.read().await;
+        let claimed =
+            guard
+                .viewing
+                .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire);
+        if claimed.is_err() {
+            return None;
+        }
+        Some(Self {
+            page: guard.page.clone(),
+            console_logs: guard.console_logs.clone(),
+            viewing: guard.viewing.clone(),
+            took_over: AtomicBool::new(false),
+        })
+    }
+
+    /// Start streaming frames. Each frame must be passed to [`Self::ack`]
+    /// before Chrome sends the next one.
+    pub async fn start(
+        &self,
+    ) -> Result<impl Stream<Item = Arc<EventScreencastFrame>> + Unpin, BrowserError> {
+        let frames = self.page.event_listener::<EventScreencastFrame>().await?;
+        let params = StartScreencastParams::builder()
+            .format(StartScreencastFormat::Jpeg)
+            .quality(FRAME_QUALITY)
+            .max_width(FRAME_MAX_WIDTH)
+            .max_height(FRAME_MAX_HEIGHT)
+            .build();
+        self.page.execute(params).await?;
+        Ok(frames)
+    }
+
+    pub async fn ack(&self, frame: &EventScreencastFrame) -> Result<(), BrowserError> {
+        self.page
+            .execute(ScreencastFrameAckParams::new(frame.session_id))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn stop(&self) {
+        if let Err(e) = self.page.execute(StopScreencastParams::default()).await {
+            tracing::debug!(error = %e, "Failed to stop screencast");
+        }
+    }
+
+    /// Replay the viewer's input on the page. The first input is noted in
+    /// the console log so the agent knows a person took over.
+    pub async fn input(&self, input: ViewerInput) -> Result<(), BrowserError> {
+        if !self.took_over.swap(true, Ordering::Relaxed) {
+            push_console_entry(
+                &self.console_logs,
+                "viewer".to_string(),
+                "A person watching the live view took control of the page".to_string(),
+            );
+        }
+
+        match input {
+            ViewerInput::Mouse {
+                event,
+                x,
+                y,
+                button,
+                delta_x,
+                delta_y,
+            } => {
+                let kind = match event {
+                    MouseAction::Down => DispatchMouseEventType::MousePressed,
+                    MouseAction::Up => DispatchMouseEventType::MouseReleased,
+                    MouseAction::Move => DispatchMouseEventType::MouseMoved,
+                    MouseAction::Wheel => DispatchMouseEventType::MouseWheel,
+                };
+                let mut params = DispatchMouseEventParams::builder().r#type(kind).x(x).y(y);
+                match event {
+                    MouseAction::Down | MouseAction::Up => {
+                        params = params.button(button.cdp()).click_count(1);
+                    }
+                    MouseAction::Wheel => params = params.delta_x(delta_x).delta_y(delta_y),
+                    MouseAction::Move => {}
+                }
+                self.page
+                    .execute(params.build().map_err(BrowserError::OperationFailed)?)
+                    .await?;
+            }
+            ViewerInput::Key {
+                event,
+                key,
+                code,
+                text,
+            } => {
+                let kind = match (event, &text) {
+                    (KeyAction::Down, Some(_)) => DispatchKeyEventType::KeyDown,
+                    (KeyAction::Down, None) => DispatchKeyEventType::RawKeyDown,
+                    (KeyAction::Up, _) => DispatchKeyEventType::KeyUp,
+                };
+                let mut params = DispatchKeyEventParams::builder().r#type(kind).key(key);
+                if let Some(code) = code {
+                    params = params.code(code);
+                }
+                if let (KeyAction::Down, Some(text)) = (event, text) {
+                    params = params.text(text);
+                }
+                self.page
+                    .execute(params.build().map_err(BrowserError::OperationFailed)?)
+                    .await?;
+            }
+            ViewerInput::Text { text } => {
+                self.page.execute(InsertTextParams::new(text)).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Viewer {
+    fn drop(&mut self) {
+        self.viewing.store(false, Ordering::Release);
+    }
+}
+
+impl ViewerButton {
+    fn cdp(self) -> MouseButton {
+        match self {
+            Self::Left => MouseButton::Left,
+            Self::Middle => MouseButton::Middle,
+            Self::Right => MouseButton::Right,
+        }
+    }
+}
+
+/// The socket message for one frame: the JPEG as base64 and the page size
+/// it shows, for scaling the viewer's clicks back to page coordinates.
+pub fn frame_message(frame: &EventScreencastFrame) -> Value {
+    json!({
+        "type": "frame",
+        "data": frame.data,
+        "width": frame.metadata.device_width,
+        "height": frame.metadata.device_height,
+        "scroll_x": frame.metadata.scroll_offset_x,
+        "scroll_y": frame.metadata.scroll_offset_y,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn viewer_input_parses_socket_messages() {
+        let click: ViewerInput =
+            serde_json::from_str(r#"{"type": "mouse", "event": "down", "x": 10, "y": 20}"#)
+                .unwrap();
+        assert!(matches!(
+            click,
+            ViewerInput::Mouse {
+                event: MouseAction::Down,
+                button: ViewerButton::Left,
+                ..
+            }
+        ));
+
+        let enter: ViewerInput = serde_json::from_str(
+            r#"{"type": "key", "event": "down", "key": "Enter", "code": "Enter", "text": "\r"}"#,
+        )
+        .unwrap();
+        assert!(matches!(enter, ViewerInput::Key { text: Some(t), .. } if t == "\r"));
+
+        let text: ViewerInput = serde_json::from_str(r#"{"type": "text", "text": "hi"}"#).unwrap();
+        assert!(matches!(text, ViewerInput::Text { text } if text == "hi"));
+
+        assert!(serde_json::from_str::<ViewerInput>(r#"{"type": "touch"}"#).is_err());
+    }
+}
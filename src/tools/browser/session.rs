@@ -2,11 +2,21 @@
 //!
 //! REQ-BT-010: Implicit Session Model
 //! REQ-BT-011: State Persistence
+//! REQ-BT-027: JavaScript Dialog Handling
+//! REQ-BT-030: Geolocation, Timezone and Locale Emulation
+//! REQ-BT-033: Live View and Headful Mode
+//! REQ-BT-034: Download Capture
 
 #![allow(dead_code)] // Work in progress - browser tools being integrated
 
 use chromiumoxide::{
     browser::{Browser, BrowserConfig},
+    cdp::browser_protocol::browser::{
+        DownloadProgressState, EventDownloadProgress, EventDownloadWillBegin,
+        GrantPermissionsParams, PermissionType, SetDownloadBehaviorBehavior,
+        SetDownloadBehaviorParams,
+    },
+    cdp::browser_protocol::page::{EventJavascriptDialogOpening, HandleJavaScriptDialogParams},
     cdp::js_protocol::runtime::{EventConsoleApiCalled, RemoteObject},
     fetcher::{BrowserFetcher, BrowserFetcherOptions, BrowserKind},
     Page,
@@ -14,6 +24,7 @@ use chromiumoxide::{
 use futures::StreamExt;
 use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex as StdMutex};
 use std::time::{Duration, Instant};
 use thiserror::Error;
@@ -29,6 +40,10 @@ const IDLE_TIMEOUT: Duration = Duration::from_mins(30);
 /// Cleanup check interval (60 seconds)
 const CLEANUP_INTERVAL: Duration = Duration::from_mins(1);
 
+/// Set to `1` to open a visible Chrome window on machines with a display
+/// (REQ-BT-033). Without a display the browser stays headless.
+const HEADFUL_ENV: &str = "PHOENIX_BROWSER_HEADFUL";
+
 /// Default viewport dimensions
 const DEFAULT_VIEWPORT_WIDTH: u32 = 1024;
 const DEFAULT_VIEWPORT_HEIGHT: u32 = 768;
@@ -62,6 +77,240 @@ pub struct ConsoleEntry {
     pub timestamp: Instant,
 }
 
+/// How a JavaScript dialog is answered when no answer was set up for it
+/// (REQ-BT-027). Configured with `PHOENIX_BROWSER_DIALOGS`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DialogPolicy {
+    Accept,
+    /// Like a user pressing Cancel. `beforeunload` prompts are still
+    /// accepted so navigation is never blocked.
+    #[default]
+    Dismiss,
+    /// Leave the dialog open until `browser_handle_dialog` answers it
+    Manual,
+}
+
+impl DialogPolicy {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "accept" => Some(Self::Accept),
+            "dismiss" => Some(Self::Dismiss),
+            "manual" => Some(Self::Manual),
+            _ => None,
+        }
+    }
+
+    /// Policy from `PHOENIX_BROWSER_DIALOGS`; unset or unknown values dismiss.
+    fn from_env() -> Self {
+        let Ok(value) = std::env::var("PHOENIX_BROWSER_DIALOGS") else {
+            return Self::default();
+        };
+        Self::parse(&value).unwrap_or_else(|| {
+            tracing::warn!(%value, "Unknown PHOENIX_BROWSER_DIALOGS value; dismissing dialogs");
+            Self::default()
+        })
+    }
+}
+
+/// An answer to a dialog, with the text to enter for a `prompt()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogResponse {
+    pub accept: bool,
+    pub prompt_text: Option<String>,
+}
+
+impl DialogResponse {
+    pub fn params(&self) -> HandleJavaScriptDialogParams {
+        HandleJavaScriptDialogParams {
+            accept: self.accept,
+            prompt_text: self.prompt_text.clone(),
+        }
+    }
+
+    pub fn verb(&self) -> &'static str {
+        if self.accept {
+            "accepted"
+        } else {
+            "dismissed"
+        }
+    }
+}
+
+/// A dialog the page opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenDialog {
+    /// `alert`, `confirm`, `prompt`, or `beforeunload`
+    pub kind: String,
+    pub message: String,
+    pub default_prompt: Option<String>,
+}
+
+impl OpenDialog {
+    /// e.g. `confirm("Delete this item?")`
+    pub fn summary(&self) -> String {
+        format!("{}({:?})", self.kind, self.message)
+    }
+}
+
+/// Dialog handling shared by the session's dialog listener and
+/// `browser_handle_dialog` (REQ-BT-027).
+#[derive(Debug, Default)]
+pub struct DialogState {
+    pub policy: DialogPolicy,
+    /// Answer for the next dialog, set by `browser_handle_dialog`
+    pub next: Option<DialogResponse>,
+    /// A dialog waiting for an answer under `DialogPolicy::Manual`
+    pub open: Option<OpenDialog>,
+}
+
+impl DialogState {
+    /// How to answer a dialog that just opened, or `None` to leave it open.
+    fn answer(&mut self, dialog: OpenDialog) -> Option<DialogResponse> {
+        if let Some(response) = self.next.take() {
+            return Some(response);
+        }
+        match self.policy {
+            DialogPolicy::Accept => Some(DialogResponse {
+                accept: true,
+                prompt_text: dialog.default_prompt,
+            }),
+            DialogPolicy::Dismiss => Some(DialogResponse {
+                accept: dialog.kind == "beforeunload",
+                prompt_text: None,
+            }),
+            DialogPolicy::Manual => {
+                self.open = Some(dialog);
+                None
+            }
+        }
+    }
+}
+
+/// Folder under the conversation's working directory that browser
+/// downloads are saved to (REQ-BT-034)
+pub const DOWNLOADS_DIR: &str = "downloads";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadStatus {
+    InProgress,
+    Completed,
+    Canceled,
+}
+
+#[derive(Debug, Clone)]
+pub struct Download {
+    pub url: String,
+    /// Name in the downloads folder once complete
+    pub filename: String,
+    pub status: DownloadStatus,
+    pub bytes: u64,
+}
+
+/// Downloads the session's browser made (REQ-BT-034). Chrome saves each
+/// file under its download guid; the listener renames it to `filename`
+/// when it completes.
+#[derive(Debug, Default)]
+pub struct DownloadState {
+    pub dir: PathBuf,
+    /// By CDP download guid, in the order they began
+    downloads: Vec<(String, Download)>,
+    /// Guids whose status changed since tool results last reported them
+    unreported: Vec<String>,
+}
+
+impl DownloadState {
+    fn get_mut(&mut self, guid: &str) -> Option<&mut Download> {
+        self.downloads
+            .iter_mut()
+            .find(|(g, _)| g == guid)
+            .map(|(_, d)| d)
+    }
+
+    /// Record a download that just began, picking a name no other file in
+    /// the folder has.
+    fn begin(&mut self, guid: &str, url: &str, suggested: &str) {
+        let filename = unique_filename(suggested, |name| {
+            self.dir.join(name).exists() || self.downloads.iter().any(|(_, d)| d.filename == name)
+        });
+        self.downloads.push((
+            guid.to_string(),
+            Download {
+                url: url.to_string(),
+                filename,
+                status: DownloadStatus::InProgress,
+                bytes: 0,
+            },
+        ));
+        self.unreported.push(guid.to_string());
+    }
+
+    /// Update a download's progress. Returns its name when it just finished.
+    fn progress(&mut self, guid: &str, status: DownloadStatus, bytes: u64) -> Option<String> {
+        let download = self.get_mut(guid)?;
+        download.bytes = bytes;
+        if download.status == status {
+            return None;
+        }
+        download.status = status;
+        let finished = (status == DownloadStatus::Completed).then(|| download.filename.clone());
+        if !self.unreported.iter().any(|g| g == guid) {
+            self.unreported.push(guid.to_string());
+        }
+        finished
+    }
+
+    pub fn has_unreported(&self) -> bool {
+        !self.unreported.is_empty()
+    }
+
+    /// One line per download that began or ended since the last call, for
+    /// the tool result that follows.
+    pub fn take_notes(&mut self) -> Vec<String> {
+        let unreported = std::mem::take(&mut self.unreported);
+        unreported
+            .iter()
+            .filter_map(|guid| self.downloads.iter().find(|(g, _)| g == guid))
+            .map(|(_, d)| {
+                let path = self.dir.join(&d.filename);
+                match d.status {
+                    DownloadStatus::InProgress => {
+                        format!("Download started: {} (saving to {})", d.url, path.display())
+                    }
+                    DownloadStatus::Completed => {
+                        format!("Downloaded {} ({} bytes)", path.display(), d.bytes)
+                    }
+                    DownloadStatus::Canceled => format!("Download canceled: {}", d.filename),
+                }
+            })
+            .collect()
+    }
+}
+
+/// `suggested` reduced to a plain file name, numbered like `report (1).csv`
+/// until `taken` says it is free.
+fn unique_filename(suggested: &str, taken: impl Fn(&str) -> bool) -> String {
+    let name = Path::new(suggested)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .filter(|n| !n.starts_with('.'))
+        .unwrap_or("download");
+    if !taken(name) {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+        _ => (name, String::new()),
+    };
+    let mut n = 1;
+    loop {
+        let candidate = format!("{stem} ({n}){ext}");
+        if !taken(&candidate) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
 /// Per-conversation browser instance
 pub struct BrowserSession {
     #[allow(dead_code)] // Browser must stay alive
@@ -70,10 +319,20 @@ pub struct BrowserSession {
     handler_task: JoinHandle<()>,
     #[allow(dead_code)] // Task must stay alive
     console_task: Option<JoinHandle<()>>,
+    #[allow(dead_code)] // Task must stay alive
+    dialog_task: Option<JoinHandle<()>>,
+    #[allow(dead_code)] // Task must stay alive
+    download_task: Option<JoinHandle<()>>,
     /// The current page (public for tool access)
     pub page: Page,
     /// Console logs captured from the page (separate lock to avoid contention)
     pub console_logs: Arc<StdMutex<VecDeque<ConsoleEntry>>>,
+    /// How JavaScript dialogs are answered (REQ-BT-027)
+    pub dialogs: Arc<StdMutex<DialogState>>,
+    /// Someone is watching through the live view (REQ-BT-033)
+    pub viewing: Arc<AtomicBool>,
+    /// Files the browser downloaded (REQ-BT-034)
+    pub downloads: Arc<StdMutex<DownloadState>>,
     /// Last activity timestamp (for idle timeout)
     pub last_activity: Instant,
 }
@@ -188,8 +447,13 @@ impl BrowserSession {
         // (e.g. from a previous crash or test run that didn't clean up)
         let _ = std::fs::remove_dir_all(&user_data_dir);
 
-        let mut builder = BrowserConfig::builder()
-            .new_headless_mode()
+        let builder = if show_window() {
+            tracing::info!("Launching a visible browser window ({HEADFUL_ENV})");
+            BrowserConfig::builder().with_head()
+        } else {
+            BrowserConfig::builder().new_headless_mode()
+        };
+        let mut builder = builder
             .no_sandbox()
             .arg("--disable-gpu")
             .arg("--disable-software-rasterizer")
@@ -252,8 +516,16 @@ impl BrowserSession {
             browser,
             handler_task,
             console_task: None,
+            dialog_task: None,
+            download_task: None,
             page,
             console_logs: Arc::new(StdMutex::new(VecDeque::with_capacity(MAX_CONSOLE_LOGS))),
+            dialogs: Arc::new(StdMutex::new(DialogState {
+                policy: DialogPolicy::from_env(),
+                ..DialogState::default()
+            })),
+            viewing: Arc::new(AtomicBool::new(false)),
+            downloads: Arc::new(StdMutex::new(DownloadState::default())),
             last_activity: Instant::now(),
         })
     }
@@ -356,16 +628,7 @@ impl BrowserSession {
 
                 // Add to console logs using separate lock (won't block tool execution)
                 tracing::debug!(level = %level, text = %text, "Console event captured");
-                if let Ok(mut logs) = console_logs.lock() {
-                    if logs.len() >= MAX_CONSOLE_LOGS {
-                        logs.pop_front();
-                    }
-                    logs.push_back(ConsoleEntry {
-                        level,
-                        text,
-                        timestamp: Instant::now(),
-                    });
-                }
+                push_console_entry(&console_logs, level, text);
             }
         });
 
@@ -377,6 +640,174 @@ impl BrowserSession {
 
         Ok(())
     }
+
+    /// Answer JavaScript dialogs as they open (REQ-BT-027). An unanswered
+    /// dialog blocks the page, so without this a `confirm()` hangs whichever
+    /// tool triggered it. Each dialog is noted in the console log, where the
+    /// agent can see what was answered on its behalf.
+    pub async fn setup_dialog_listener(session: Arc<RwLock<Self>>) -> Result<(), BrowserError> {
+        let (mut dialog_events, page, dialogs, console_logs) = {
+            let guard = session.read().await;
+            let events = guard
+                .page
+                .event_listener::<EventJavascriptDialogOpening>()
+                .await?;
+            (
+                events,
+                guard.page.clone(),
+                guard.dialogs.clone(),
+                guard.console_logs.clone(),
+            )
+        };
+
+        let task = tokio::spawn(async move {
+            while let Some(event) = dialog_events.next().await {
+                let dialog = OpenDialog {
+                    kind: format!("{:?}", event.r#type).to_lowercase(),
+                    message: event.message.clone(),
+                    default_prompt: event.default_prompt.clone(),
+                };
+                let summary = dialog.summary();
+                let response = match dialogs.lock() {
+                    Ok(mut state) => state.answer(dialog),
+                    Err(_) => None,
+                };
+
+                let Some(response) = response else {
+                    let text = format!("{summary} left open for browser_handle_dialog");
+                    push_console_entry(&console_logs, "dialog".to_string(), text);
+                    continue;
+                };
+                let text = format!("{summary} {}", response.verb());
+                push_console_entry(&console_logs, "dialog".to_string(), text);
+                if let Err(e) = page.execute(response.params()).await {
+                    tracing::warn!(error = %e, "Failed to answer JavaScript dialog");
+                }
+            }
+        });
+
+        {
+            let mut guard = session.write().await;
+            guard.dialog_task = Some(task);
+        }
+
+        Ok(())
+    }
+
+    /// Save downloads into `dir` and track them (REQ-BT-034). Headless
+    /// Chrome otherwise refuses downloads, so a click on an export button
+    /// looked like it did nothing.
+    pub async fn setup_download_listener(
+        session: Arc<RwLock<Self>>,
+        dir: PathBuf,
+    ) -> Result<(), BrowserError> {
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| BrowserError::OperationFailed(format!("{}: {e}", dir.display())))?;
+
+        let (mut begins, mut progress, downloads) = {
+            let guard = session.read().await;
+            let begins = guard
+                .browser
+                .event_listener::<EventDownloadWillBegin>()
+                .await?;
+            let progress = guard
+                .browser
+                .event_listener::<EventDownloadProgress>()
+                .await?;
+            let mut params =
+                SetDownloadBehaviorParams::new(SetDownloadBehaviorBehavior::AllowAndName);
+            params.download_path = Some(dir.to_string_lossy().into_owned());
+            params.events_enabled = Some(true);
+            guard.browser.execute(params).await?;
+            if let Ok(mut state) = guard.downloads.lock() {
+                state.dir.clone_from(&dir);
+            }
+            (begins, progress, guard.downloads.clone())
+        };
+
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(event) = begins.next() => {
+                        if let Ok(mut state) = downloads.lock() {
+                            state.begin(&event.guid, &event.url, &event.suggested_filename);
+                        }
+                    }
+                    Some(event) = progress.next() => {
+                        let status = match event.state {
+                            DownloadProgressState::InProgress => DownloadStatus::InProgress,
+                            DownloadProgressState::Completed => DownloadStatus::Completed,
+                            DownloadProgressState::Canceled => DownloadStatus::Canceled,
+                        };
+                        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                        let bytes = event.received_bytes.max(0.0) as u64;
+                        let finished = downloads
+                            .lock()
+                            .ok()
+                            .and_then(|mut state| state.progress(&event.guid, status, bytes));
+                        // Chrome saved it under the guid; give it its real name
+                        if let Some(filename) = finished {
+                            let from = dir.join(&event.guid);
+                            if let Err(e) = tokio::fs::rename(&from, dir.join(&filename)).await {
+                                tracing::warn!(error = %e, %filename, "Failed to name download");
+                            }
+                        }
+                    }
+                    else => break,
+                }
+            }
+        });
+
+        {
+            let mut guard = session.write().await;
+            guard.download_task = Some(task);
+        }
+
+        Ok(())
+    }
+
+    /// Let pages read an emulated position (REQ-BT-030). Permissions belong
+    /// to the browser, not the page, so this goes through the browser.
+    pub async fn grant_geolocation(&self) -> Result<(), BrowserError> {
+        let grant = GrantPermissionsParams::new(vec![PermissionType::Geolocation]);
+        self.browser.execute(grant).await?;
+        Ok(())
+    }
+}
+
+/// Whether to launch Chrome with a visible window: asked for with
+/// `PHOENIX_BROWSER_HEADFUL`, and there is a display to show it on.
+fn show_window() -> bool {
+    let wanted = matches!(std::env::var(HEADFUL_ENV).as_deref(), Ok("1" | "true"));
+    if !wanted {
+        return false;
+    }
+    let display = ["DISPLAY", "WAYLAND_DISPLAY"]
+        .iter()
+        .any(|var| std::env::var_os(var).is_some_and(|v| !v.is_empty()));
+    if !display {
+        tracing::warn!("{HEADFUL_ENV} is set but there is no display; staying headless");
+    }
+    display
+}
+
+/// Append to a session's console buffer, dropping the oldest entry when full.
+pub(super) fn push_console_entry(
+    logs: &StdMutex<VecDeque<ConsoleEntry>>,
+    level: String,
+    text: String,
+) {
+    if let Ok(mut logs) = logs.lock() {
+        if logs.len() >= MAX_CONSOLE_LOGS {
+            logs.pop_front();
+        }
+        logs.push_back(ConsoleEntry {
+            level,
+            text,
+            timestamp: Instant::now(),
+        });
+    }
 }
 
 /// RAII guard for browser session access
@@ -482,6 +913,7 @@ impl BrowserSessionManager {
     pub async fn get_session(
         &self,
         conversation_id: &str,
+        working_dir: &Path,
     ) -> Result<Arc<RwLock<BrowserSession>>, BrowserError> {
         // Check if session exists
         {
@@ -507,12 +939,29 @@ impl BrowserSessionManager {
         if let Err(e) = BrowserSession::setup_console_listener(session_arc.clone()).await {
             tracing::warn!(error = %e, "Failed to set up console listener");
         }
+        if let Err(e) = BrowserSession::setup_dialog_listener(session_arc.clone()).await {
+            tracing::warn!(error = %e, "Failed to set up dialog listener");
+        }
+        let downloads = working_dir.join(DOWNLOADS_DIR);
+        if let Err(e) =
+            BrowserSession::setup_download_listener(session_arc.clone(), downloads).await
+        {
+            tracing::warn!(error = %e, "Failed to set up download capture");
+        }
 
         sessions.insert(conversation_id.to_string(), session_arc.clone());
 
         Ok(session_arc)
     }
 
+    /// The session a conversation already has, without launching one
+    pub async fn existing_session(
+        &self,
+        conversation_id: &str,
+    ) -> Option<Arc<RwLock<BrowserSession>>> {
+        self.sessions.read().await.get(conversation_id).cloned()
+    }
+
     /// Kill a specific session (called on conversation delete)
     pub async fn kill_session(&self, conversation_id: &str) {
         let mut sessions = self.sessions.write().await;
@@ -575,6 +1024,9 @@ impl Drop for BrowserSession {
         if let Some(task) = &self.console_task {
             task.abort();
         }
+        if let Some(task) = &self.dialog_task {
+            task.abort();
+        }
     }
 }
 
@@ -800,3 +1252,119 @@ mod console_arg_tests {
         assert_eq!(extract_console_arg_text(&arg), "undefined");
     }
 }
+
+#[cfg(test)]
+mod dialog_tests {
+    use super::{DialogPolicy, DialogResponse, DialogState, OpenDialog};
+
+    fn dialog(kind: &str) -> OpenDialog {
+        OpenDialog {
+            kind: kind.to_string(),
+            message: "Sure?".to_string(),
+            default_prompt: Some("42".to_string()),
+        }
+    }
+
+    fn state(policy: DialogPolicy) -> DialogState {
+        DialogState {
+            policy,
+            ..DialogState::default()
+        }
+    }
+
+    #[test]
+    fn policy_parses_case_insensitively() {
+        assert_eq!(DialogPolicy::parse("Accept"), Some(DialogPolicy::Accept));
+        assert_eq!(DialogPolicy::parse(" manual "), Some(DialogPolicy::Manual));
+        assert_eq!(DialogPolicy::parse("dismiss"), Some(DialogPolicy::Dismiss));
+        assert_eq!(DialogPolicy::parse("ignore"), None);
+        assert_eq!(DialogPolicy::default(), DialogPolicy::Dismiss);
+    }
+
+    #[test]
+    fn dismiss_policy_still_lets_navigation_leave() {
+        let mut state = state(DialogPolicy::Dismiss);
+        let confirm = state.answer(dialog("confirm")).unwrap();
+        assert!(!confirm.accept);
+        let unload = state.answer(dialog("beforeunload")).unwrap();
+        assert!(unload.accept);
+        assert!(state.open.is_none());
+    }
+
+    #[test]
+    fn accept_policy_keeps_the_default_prompt() {
+        let mut state = state(DialogPolicy::Accept);
+        let response = state.answer(dialog("prompt")).unwrap();
+        assert_eq!(response.prompt_text.as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn prepared_answer_is_used_once() {
+        let mut state = state(DialogPolicy::Dismiss);
+        state.next = Some(DialogResponse {
+            accept: true,
+            prompt_text: Some("yes".to_string()),
+        });
+        let first = state.answer(dialog("prompt")).unwrap();
+        assert!(first.accept);
+        assert_eq!(first.prompt_text.as_deref(), Some("yes"));
+        let second = state.answer(dialog("confirm")).unwrap();
+        assert!(!second.accept);
+    }
+
+    #[test]
+    fn manual_policy_leaves_the_dialog_open() {
+        let mut state = state(DialogPolicy::Manual);
+        assert!(state.answer(dialog("confirm")).is_none());
+        let open = state.open.as_ref().unwrap();
+        assert_eq!(open.summary(), "confirm(\"Sure?\")");
+    }
+}
+
+#[cfg(test)]
+mod download_tests {
+    use super::{unique_filename, DownloadState, DownloadStatus};
+    use std::path::PathBuf;
+
+    #[test]
+    fn filenames_are_plain_and_unique() {
+        let taken = ["report.csv", "report (1).csv", "README"];
+        let taken = |name: &str| taken.contains(&name);
+        assert_eq!(unique_filename("data.json", taken), "data.json");
+        assert_eq!(unique_filename("report.csv", taken), "report (2).csv");
+        assert_eq!(unique_filename("README", taken), "README (1)");
+        assert_eq!(unique_filename("../../etc/passwd", taken), "passwd");
+        assert_eq!(unique_filename(".bashrc", taken), "download");
+        assert_eq!(unique_filename("", taken), "download");
+    }
+
+    #[test]
+    fn notes_report_each_change_once() {
+        let mut state = DownloadState {
+            dir: PathBuf::from("/work/downloads"),
+            ..DownloadState::default()
+        };
+        state.begin("g1", "https://example.com/export", "export.csv");
+        assert!(state.has_unreported());
+        assert_eq!(
+            state.take_notes(),
+            ["Download started: https://example.com/export (saving to /work/downloads/export.csv)"]
+        );
+        assert!(state.take_notes().is_empty());
+
+        assert_eq!(state.progress("g1", DownloadStatus::InProgress, 10), None);
+        assert!(!state.has_unreported());
+        assert_eq!(
+            state
+                .progress("g1", DownloadStatus::Completed, 42)
+                .as_deref(),
+            Some("export.csv")
+        );
+        assert_eq!(
+            state.take_notes(),
+            ["Downloaded /work/downloads/export.csv (42 bytes)"]
+        );
+
+        assert_eq!(state.progress("unknown", DownloadStatus::Canceled, 0), None);
+    }
+}
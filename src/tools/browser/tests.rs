@@ -22,7 +22,7 @@ use tokio_util::sync::CancellationToken;
 fn chrome_available() -> bool {
     !matches!(
         std::env::var("PHOENIX_SKIP_BROWSER_TESTS").as_deref(),
-        Ok("1") | Ok("true"),
+        Ok("1" | "true"),
     )
 }
 
@@ -35,7 +35,7 @@ fn chrome_available() -> bool {
 fn network_available() -> bool {
     !matches!(
         std::env::var("PHOENIX_SKIP_NETWORK_TESTS").as_deref(),
-        Ok("1") | Ok("true"),
+        Ok("1" | "true"),
     )
 }
 
@@ -1926,3 +1926,582 @@ async fn test_key_press_unknown_key_returns_error() {
 
     shutdown_test(_manager, server).await;
 }
+
+// ============================================================================
+// browser_handle_dialog (REQ-BT-027)
+// ============================================================================
+
+const DIALOG_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><title>Dialog Test</title></head>
+<body>
+    <button id="delete" onclick="
+        document.getElementById('result').textContent = confirm('Delete?') ? 'yes' : 'no';
+    ">Delete</button>
+    <button id="name" onclick="
+        document.getElementById('result').textContent = prompt('Name?') || 'none';
+    ">Name</button>
+    <div id="result">pending</div>
+</body>
+</html>"#;
+
+async fn result_text(ctx: &ToolContext) -> String {
+    BrowserEvalTool
+        .run(
+            json!({"expression": "document.getElementById('result').textContent"}),
+            ctx.clone(),
+        )
+        .await
+        .output
+}
+
+#[tokio::test]
+async fn test_confirm_is_dismissed_without_hanging() {
+    require_chrome!();
+
+    let server = TestServer::start(DIALOG_PAGE).await;
+    let (ctx, _manager) = test_context("test-dialog-dismiss");
+    BrowserNavigateTool
+        .run(json!({"url": server.url()}), ctx.clone())
+        .await;
+
+    let click = tokio::time::timeout(
+        Duration::from_secs(10),
+        BrowserClickTool.run(json!({"selector": "#delete"}), ctx.clone()),
+    )
+    .await
+    .expect("click hung on the confirm dialog");
+    assert!(click.success, "click failed: {}", click.output);
+    assert!(result_text(&ctx).await.contains("no"));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let logs = BrowserRecentConsoleLogsTool
+        .run(json!({}), ctx.clone())
+        .await;
+    assert!(
+        logs.output.contains("confirm(\"Delete?\") dismissed"),
+        "Dialog not noted in console logs: {}",
+        logs.output
+    );
+
+    shutdown_test(_manager, server).await;
+}
+
+#[tokio::test]
+async fn test_handle_dialog_prepares_the_next_answer() {
+    require_chrome!();
+
+    let server = TestServer::start(DIALOG_PAGE).await;
+    let (ctx, _manager) = test_context("test-dialog-accept");
+    BrowserNavigateTool
+        .run(json!({"url": server.url()}), ctx.clone())
+        .await;
+
+    let prepared = BrowserHandleDialogTool
+        .run(json!({"action": "accept"}), ctx.clone())
+        .await;
+    assert!(
+        prepared.success,
+        "handle_dialog failed: {}",
+        prepared.output
+    );
+    assert!(
+        prepared.output.contains("next dialog will be accepted"),
+        "Unexpected output: {}",
+        prepared.output
+    );
+
+    BrowserClickTool
+        .run(json!({"selector": "#delete"}), ctx.clone())
+        .await;
+    assert!(result_text(&ctx).await.contains("yes"));
+
+    // The prepared answer is used once; the next confirm is dismissed again
+    BrowserClickTool
+        .run(json!({"selector": "#delete"}), ctx.clone())
+        .await;
+    assert!(result_text(&ctx).await.contains("no"));
+
+    shutdown_test(_manager, server).await;
+}
+
+#[tokio::test]
+async fn test_handle_dialog_fills_prompt() {
+    require_chrome!();
+
+    let server = TestServer::start(DIALOG_PAGE).await;
+    let (ctx, _manager) = test_context("test-dialog-prompt");
+    BrowserNavigateTool
+        .run(json!({"url": server.url()}), ctx.clone())
+        .await;
+
+    BrowserHandleDialogTool
+        .run(
+            json!({"action": "accept", "prompt_text": "Ada"}),
+            ctx.clone(),
+        )
+        .await;
+    BrowserClickTool
+        .run(json!({"selector": "#name"}), ctx.clone())
+        .await;
+    assert!(result_text(&ctx).await.contains("Ada"));
+
+    shutdown_test(_manager, server).await;
+}
+
+#[tokio::test]
+async fn test_handle_dialog_rejects_unknown_action() {
+    let (ctx, _manager) = test_context("test-dialog-invalid");
+    let result = BrowserHandleDialogTool
+        .run(json!({"action": "ignore"}), ctx)
+        .await;
+    assert!(!result.success);
+    assert!(result.output.contains("Invalid input"), "{}", result.output);
+}
+
+// ============================================================================
+// Iframe targeting (REQ-BT-028)
+// ============================================================================
+
+const FRAME_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><title>Frame Test</title></head>
+<body>
+    <h1>Outer</h1>
+    <iframe name="inner" srcdoc="
+        <input id='field'>
+        <button id='go' onclick='out.textContent = field.value'>Go</button>
+        <div id='out'>empty</div>
+    "></iframe>
+</body>
+</html>"#;
+
+#[tokio::test]
+async fn test_frame_tools_act_inside_iframe() {
+    require_chrome!();
+
+    let server = TestServer::start(FRAME_PAGE).await;
+    let (ctx, _manager) = test_context("test-frame-tools");
+    BrowserNavigateTool
+        .run(json!({"url": server.url()}), ctx.clone())
+        .await;
+
+    let wait = BrowserWaitForSelectorTool
+        .run(json!({"selector": "#go", "frame": "inner"}), ctx.clone())
+        .await;
+    assert!(wait.success, "wait in frame failed: {}", wait.output);
+
+    // The top document has no such element
+    let top = BrowserEvalTool
+        .run(
+            json!({"expression": "document.getElementById('go') === null"}),
+            ctx.clone(),
+        )
+        .await;
+    assert!(top.output.contains("true"), "{}", top.output);
+
+    let typed = BrowserTypeTool
+        .run(
+            json!({"selector": "#field", "text": "hi there", "frame": "inner"}),
+            ctx.clone(),
+        )
+        .await;
+    assert!(typed.success, "type in frame failed: {}", typed.output);
+
+    let clicked = BrowserClickTool
+        .run(json!({"selector": "#go", "frame": "inner"}), ctx.clone())
+        .await;
+    assert!(clicked.success, "click in frame failed: {}", clicked.output);
+
+    let out = BrowserEvalTool
+        .run(
+            json!({
+                "expression": "document.getElementById('out').textContent",
+                "frame": "inner"
+            }),
+            ctx.clone(),
+        )
+        .await;
+    assert!(out.output.contains("hi there"), "{}", out.output);
+
+    shutdown_test(_manager, server).await;
+}
+
+#[tokio::test]
+async fn test_unknown_frame_lists_available_frames() {
+    require_chrome!();
+
+    let server = TestServer::start(FRAME_PAGE).await;
+    let (ctx, _manager) = test_context("test-frame-unknown");
+    BrowserNavigateTool
+        .run(json!({"url": server.url()}), ctx.clone())
+        .await;
+
+    let result = BrowserClickTool
+        .run(json!({"selector": "#go", "frame": "missing"}), ctx.clone())
+        .await;
+    assert!(!result.success);
+    assert!(
+        result.output.contains("No frame matches 'missing'") && result.output.contains("inner"),
+        "Unexpected error: {}",
+        result.output
+    );
+
+    shutdown_test(_manager, server).await;
+}
+
+// ============================================================================
+// Network and CPU throttling (REQ-BT-029)
+// ============================================================================
+
+#[tokio::test]
+async fn test_throttle_offline_and_back() {
+    require_chrome!();
+
+    let server = TestServer::start(
+        r"<!DOCTYPE html>
+        <html>
+        <head><title>Throttle Test</title></head>
+        <body><h1>Throttle</h1></body>
+        </html>",
+    )
+    .await;
+    let (ctx, _manager) = test_context("test-throttle-offline");
+    BrowserNavigateTool
+        .run(json!({"url": server.url()}), ctx.clone())
+        .await;
+
+    let fetch = json!({
+        "expression": "fetch(location.href).then(() => 'reached', () => 'failed')"
+    });
+
+    let offline = BrowserThrottleTool
+        .run(
+            json!({"network": "offline", "cpu_slowdown": 2}),
+            ctx.clone(),
+        )
+        .await;
+    assert!(offline.success, "throttle failed: {}", offline.output);
+    assert!(
+        offline.output.contains("network offline"),
+        "{}",
+        offline.output
+    );
+    assert!(
+        offline.output.contains("CPU 2x slower"),
+        "{}",
+        offline.output
+    );
+    let result = BrowserEvalTool.run(fetch.clone(), ctx.clone()).await;
+    assert!(result.output.contains("failed"), "{}", result.output);
+
+    let restored = BrowserThrottleTool
+        .run(json!({"network": "none", "cpu_slowdown": 1}), ctx.clone())
+        .await;
+    assert!(restored.success, "unthrottle failed: {}", restored.output);
+    let result = BrowserEvalTool.run(fetch, ctx.clone()).await;
+    assert!(result.output.contains("reached"), "{}", result.output);
+
+    shutdown_test(_manager, server).await;
+}
+
+#[tokio::test]
+async fn test_throttle_rejects_bad_settings() {
+    let (ctx, _manager) = test_context("test-throttle-invalid");
+
+    for (input, expected) in [
+        (json!({}), "Nothing to change"),
+        (json!({"network": "2g"}), "Unknown network preset"),
+        (json!({"cpu_slowdown": 0.5}), "at least 1"),
+        (
+            json!({"network": "offline", "latency_ms": 100}),
+            "do not apply",
+        ),
+        (json!({"download_kbps": -1}), "zero or more"),
+    ] {
+        let result = BrowserThrottleTool.run(input, ctx.clone()).await;
+        assert!(!result.success);
+        assert!(result.output.contains(expected), "{}", result.output);
+    }
+}
+
+// ============================================================================
+// Geolocation, timezone and locale emulation (REQ-BT-030)
+// ============================================================================
+
+const EMULATION_PAGE: &str = r"<!DOCTYPE html>
+<html>
+<head><title>Emulation Test</title></head>
+<body><h1>Emulation</h1></body>
+</html>";
+
+#[tokio::test]
+async fn test_set_locale_and_timezone() {
+    require_chrome!();
+
+    let server = TestServer::start(EMULATION_PAGE).await;
+    let (ctx, _manager) = test_context("test-set-locale");
+    BrowserNavigateTool
+        .run(json!({"url": server.url()}), ctx.clone())
+        .await;
+
+    let result = BrowserSetLocaleTool
+        .run(
+            json!({"locale": "de-DE", "timezone": "Asia/Tokyo"}),
+            ctx.clone(),
+        )
+        .await;
+    assert!(result.success, "set locale failed: {}", result.output);
+    assert!(
+        result.output.contains("Accept-Language de-DE,de;q=0.9"),
+        "{}",
+        result.output
+    );
+
+    let seen = BrowserEvalTool
+        .run(
+            json!({
+                "expression": "[Intl.DateTimeFormat().resolvedOptions().timeZone, \
+                               navigator.language, (1234.5).toLocaleString()].join(' ')"
+            }),
+            ctx.clone(),
+        )
+        .await;
+    assert!(
+        seen.output.contains("Asia/Tokyo de-DE 1.234,5"),
+        "{}",
+        seen.output
+    );
+
+    let reset = BrowserSetLocaleTool
+        .run(json!({"reset": true}), ctx.clone())
+        .await;
+    assert!(reset.success, "reset failed: {}", reset.output);
+
+    let bad = BrowserSetLocaleTool
+        .run(json!({"timezone": "Not/AZone"}), ctx.clone())
+        .await;
+    assert!(!bad.success, "invalid timezone accepted: {}", bad.output);
+
+    shutdown_test(_manager, server).await;
+}
+
+#[tokio::test]
+async fn test_set_geolocation() {
+    require_chrome!();
+
+    let server = TestServer::start(EMULATION_PAGE).await;
+    let (ctx, _manager) = test_context("test-set-geolocation");
+    BrowserNavigateTool
+        .run(json!({"url": server.url()}), ctx.clone())
+        .await;
+
+    let result = BrowserSetGeolocationTool
+        .run(
+            json!({"latitude": 48.8584, "longitude": 2.2945}),
+            ctx.clone(),
+        )
+        .await;
+    assert!(result.success, "set geolocation failed: {}", result.output);
+
+    let seen = BrowserEvalTool
+        .run(
+            json!({
+                "expression": "new Promise((resolve, reject) => \
+                    navigator.geolocation.getCurrentPosition(\
+                        (p) => resolve(p.coords.latitude + ',' + p.coords.longitude), \
+                        (e) => reject(e.message)))"
+            }),
+            ctx.clone(),
+        )
+        .await;
+    assert!(seen.output.contains("48.8584,2.2945"), "{}", seen.output);
+
+    shutdown_test(_manager, server).await;
+}
+
+#[tokio::test]
+async fn test_emulation_rejects_bad_input() {
+    let (ctx, _manager) = test_context("test-emulation-invalid");
+
+    for input in [
+        json!({"latitude": 91, "longitude": 0}),
+        json!({"latitude": 10}),
+        json!({"clear": true, "latitude": 10, "longitude": 10}),
+    ] {
+        let result = BrowserSetGeolocationTool.run(input, ctx.clone()).await;
+        assert!(!result.success, "accepted: {}", result.output);
+    }
+    for input in [json!({}), json!({"reset": true, "locale": "fr-FR"})] {
+        let result = BrowserSetLocaleTool.run(input, ctx.clone()).await;
+        assert!(!result.success, "accepted: {}", result.output);
+    }
+}
+
+// ============================================================================
+// PDF rendering (REQ-BT-031)
+// ============================================================================
+
+#[tokio::test]
+async fn test_print_to_pdf_saves_in_working_dir() {
+    require_chrome!();
+
+    let server = TestServer::start(EMULATION_PAGE).await;
+    let (ctx, _manager) = test_context("test-print-to-pdf");
+    BrowserNavigateTool
+        .run(json!({"url": server.url()}), ctx.clone())
+        .await;
+
+    let dir = format!("phoenix-pdf-test-{}", uuid::Uuid::new_v4());
+    let relative = format!("{dir}/report.pdf");
+    let result = BrowserPrintToPdfTool
+        .run(
+            json!({"path": &relative, "paper": "a4", "landscape": true, "print_background": true}),
+            ctx.clone(),
+        )
+        .await;
+    assert!(result.success, "print failed: {}", result.output);
+    assert!(result.output.contains("a4, landscape"), "{}", result.output);
+
+    let pdf = std::fs::read(ctx.working_dir.join(&relative)).expect("PDF written");
+    assert!(pdf.starts_with(b"%PDF"), "not a PDF");
+    let _ = std::fs::remove_dir_all(ctx.working_dir.join(&dir));
+
+    let bad = BrowserPrintToPdfTool
+        .run(json!({"paper": "a4", "margin_inches": 5}), ctx.clone())
+        .await;
+    assert!(!bad.success, "oversized margins accepted: {}", bad.output);
+
+    shutdown_test(_manager, server).await;
+}
+
+// ============================================================================
+// Structured element query (REQ-BT-032)
+// ============================================================================
+
+#[tokio::test]
+async fn test_query_reports_matches() {
+    require_chrome!();
+
+    let server = TestServer::start(
+        r#"<!DOCTYPE html>
+        <html>
+        <head><title>Query Test</title></head>
+        <body>
+            <ul>
+                <li class="item" data-id="1">First</li>
+                <li class="item" data-id="2" style="display: none">Second</li>
+                <li class="item" data-id="3">Third</li>
+            </ul>
+            <input id="name" value="Ada">
+            <input id="agree" type="checkbox" checked>
+        </body>
+        </html>"#,
+    )
+    .await;
+    let (ctx, _manager) = test_context("test-query");
+    BrowserNavigateTool
+        .run(json!({"url": server.url()}), ctx.clone())
+        .await;
+
+    let result = BrowserQueryTool
+        .run(
+            json!({"selector": "li.item", "attributes": ["data-id"], "limit": 2}),
+            ctx.clone(),
+        )
+        .await;
+    assert!(result.success, "query failed: {}", result.output);
+    let body = result
+        .output
+        .trim_start_matches("<query_result>")
+        .trim_end_matches("</query_result>");
+    let parsed: serde_json::Value = serde_json::from_str(body).expect("JSON result");
+    assert_eq!(parsed["count"], 3);
+    let elements = parsed["elements"].as_array().unwrap();
+    assert_eq!(elements.len(), 2);
+    assert_eq!(elements[0]["text"], "First");
+    assert_eq!(elements[0]["attributes"], json!({"data-id": "1"}));
+    assert_eq!(elements[0]["visible"], true);
+    assert_eq!(elements[1]["visible"], false);
+
+    let inputs = BrowserQueryTool
+        .run(json!({"selector": "input"}), ctx.clone())
+        .await;
+    assert!(
+        inputs.output.contains(r#""value": "Ada""#),
+        "{}",
+        inputs.output
+    );
+    assert!(
+        inputs.output.contains(r#""checked": true"#),
+        "{}",
+        inputs.output
+    );
+
+    let invalid = BrowserQueryTool
+        .run(json!({"selector": "li[["}), ctx.clone())
+        .await;
+    assert!(
+        !invalid.success,
+        "invalid selector accepted: {}",
+        invalid.output
+    );
+
+    shutdown_test(_manager, server).await;
+}
+
+// ============================================================================
+// Download capture (REQ-BT-034)
+// ============================================================================
+
+#[tokio::test]
+async fn test_click_download_lands_in_downloads_dir() {
+    require_chrome!();
+
+    let server = TestServer::start(
+        r#"<!DOCTYPE html>
+        <html>
+        <head><title>Download Test</title></head>
+        <body>
+            <a id="export" download="phoenix-export.csv"
+               href="data:text/csv,a%2Cb%0A1%2C2">Export</a>
+        </body>
+        </html>"#,
+    )
+    .await;
+    let (ctx, _manager) = test_context("test-download");
+    BrowserNavigateTool
+        .run(json!({"url": server.url()}), ctx.clone())
+        .await;
+
+    let clicked = BrowserClickTool
+        .run(json!({"selector": "#export"}), ctx.clone())
+        .await;
+    assert!(clicked.success, "click failed: {}", clicked.output);
+
+    // The download finishes after the click; a later browser call reports it
+    let mut report = clicked.output.clone();
+    for _ in 0..50 {
+        if report.contains("Downloaded ") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let probe = BrowserEvalTool
+            .run(json!({"expression": "1"}), ctx.clone())
+            .await;
+        report.push_str(&probe.output);
+    }
+    let path = report
+        .lines()
+        .find_map(|line| line.strip_prefix("Downloaded "))
+        .and_then(|rest| rest.split(" (").next())
+        .unwrap_or_else(|| panic!("download never reported: {report}"));
+    assert!(
+        path.starts_with(&ctx.working_dir.join("downloads").display().to_string()),
+        "{path}"
+    );
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "a,b\n1,2");
+    let _ = std::fs::remove_file(path);
+
+    shutdown_test(_manager, server).await;
+}
@@ -5,8 +5,15 @@
 //! REQ-BT-003: Take Screenshots
 //! REQ-BT-004: Capture Console Logs
 //! REQ-BT-005: Resize Viewport
-
-use super::session::BrowserSession;
+//! REQ-BT-027: JavaScript Dialog Handling
+//! REQ-BT-028: Iframe Targeting
+//! REQ-BT-029: Network and CPU Throttling
+//! REQ-BT-030: Geolocation, Timezone and Locale Emulation
+//! REQ-BT-031: PDF Rendering
+//! REQ-BT-032: Structured Element Query
+
+use super::frames;
+use super::session::{BrowserSession, DialogResponse};
 use crate::tools::{Tool, ToolContext, ToolOutput};
 use async_trait::async_trait;
 use chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotFormat;
@@ -101,7 +108,7 @@ impl Tool for BrowserNavigateTool {
         // Navigate with timeout
         let result = tokio::time::timeout(timeout, guard.page.goto(&input.url)).await;
 
-        match result {
+        let output = match result {
             Ok(Ok(_)) => {
                 // Detect React and enrich the navigate result with __phoenix hints
                 let react_info = match tokio::time::timeout(
@@ -120,10 +127,31 @@ impl Tool for BrowserNavigateTool {
                     ToolOutput::success(format!("done\n\n{react_info}"))
                 }
             }
+            // A URL that serves a file aborts the navigation and downloads it
+            // instead (REQ-BT-034)
+            Ok(Err(_)) if guard.downloads.lock().is_ok_and(|d| d.has_unreported()) => {
+                ToolOutput::success("The URL started a download instead of loading a page")
+            }
             Ok(Err(e)) => ToolOutput::error(format!("Navigation failed: {e}")),
             Err(_) => ToolOutput::error(format!("Timeout after {timeout:?} waiting for page load")),
-        }
+        };
+        with_downloads(output, &guard)
+    }
+}
+
+/// Append the downloads that began or finished since the last report
+/// (REQ-BT-034). A download usually finishes after the call that started
+/// it, so a later browser call reports it.
+fn with_downloads(mut output: ToolOutput, session: &BrowserSession) -> ToolOutput {
+    let notes = session
+        .downloads
+        .lock()
+        .map(|mut downloads| downloads.take_notes())
+        .unwrap_or_default();
+    if !notes.is_empty() {
+        output.output = format!("{}\n\n{}", output.output, notes.join("\n"));
     }
+    output
 }
 
 // ============================================================================
@@ -137,6 +165,9 @@ struct EvalInput {
     timeout: Option<String>,
     #[serde(default = "default_true")]
     r#await: bool,
+    /// Evaluate inside this iframe (REQ-BT-028)
+    #[serde(default)]
+    frame: Option<String>,
 }
 
 fn default_true() -> bool {
@@ -170,7 +201,8 @@ impl Tool for BrowserEvalTool {
                 "await": {
                     "type": "boolean",
                     "description": "If true, wait for promises to resolve and return their resolved value (default: true)"
-                }
+                },
+                "frame": frames::frame_schema()
             },
             "required": ["expression"]
         })
@@ -202,15 +234,21 @@ impl Tool for BrowserEvalTool {
         // the IIFE approach returns a Promise that CDP must await AND serialize,
         // which may silently fail on complex pages. Direct evaluation with
         // await_promise set via CDP is more reliable and explicit.
-        let params = EvaluateParams::builder()
+        let mut params = EvaluateParams::builder()
             .expression(&input.expression)
             .await_promise(input.r#await)
             .build()
             .unwrap();
+        if let Some(frame) = &input.frame {
+            match frames::resolve(&guard.page, frame).await {
+                Ok(context) => params.context_id = Some(context),
+                Err(e) => return ToolOutput::error(e.to_string()),
+            }
+        }
 
         let result = tokio::time::timeout(timeout, guard.page.evaluate(params)).await;
 
-        match result {
+        let output = match result {
             Ok(Ok(eval_result)) => {
                 let json_str = if let Some(v) = eval_result.value() {
                     serde_json::to_string_pretty(v).unwrap_or_else(|_| "null".to_string())
@@ -257,7 +295,8 @@ impl Tool for BrowserEvalTool {
             }
             Ok(Err(e)) => ToolOutput::error(format!("JavaScript error: {e}")),
             Err(_) => ToolOutput::error(format!("Timeout after {timeout:?}")),
-        }
+        };
+        with_downloads(output, &guard)
     }
 }
 
@@ -633,6 +672,9 @@ struct WaitForSelectorInput {
     /// If true, wait for element to be visible (not just present in DOM)
     #[serde(default)]
     visible: bool,
+    /// Look inside this iframe (REQ-BT-028)
+    #[serde(default)]
+    frame: Option<String>,
 }
 
 pub struct BrowserWaitForSelectorTool;
@@ -662,7 +704,8 @@ impl Tool for BrowserWaitForSelectorTool {
                 "visible": {
                     "type": "boolean",
                     "description": "If true, wait for element to be visible, not just in DOM (default: false)"
-                }
+                },
+                "frame": frames::frame_schema()
             },
             "required": ["selector"]
         })
@@ -712,10 +755,29 @@ impl Tool for BrowserWaitForSelectorTool {
         let poll_interval = Duration::from_millis(100);
         let start = std::time::Instant::now();
 
+        // Why the frame could not be found on the last poll (REQ-BT-028)
+        let mut frame_error = None;
+
         loop {
-            // Check if element exists/is visible
-            match guard.page.evaluate(check_script.clone()).await {
-                Ok(result) => {
+            // Check if element exists/is visible. A frame is looked up on
+            // every poll: it may still be loading, or navigate in between.
+            let evaluation = match &input.frame {
+                None => Some(guard.page.evaluate(check_script.clone()).await),
+                Some(frame) => match frames::resolve(&guard.page, frame).await {
+                    Ok(context) => {
+                        frame_error = None;
+                        let params = frames::evaluate_params(check_script.clone(), context);
+                        Some(guard.page.evaluate(params).await)
+                    }
+                    Err(e) => {
+                        frame_error = Some(e.to_string());
+                        None
+                    }
+                },
+            };
+            match evaluation {
+                None => {}
+                Some(Ok(result)) => {
                     if let Ok(found) = result.into_value::<bool>() {
                         if found {
                             let elapsed = start.elapsed();
@@ -727,7 +789,7 @@ impl Tool for BrowserWaitForSelectorTool {
                         }
                     }
                 }
-                Err(e) => {
+                Some(Err(e)) => {
                     // Check if it's a selector syntax error
                     let err_str = e.to_string();
                     if err_str.contains("SyntaxError")
@@ -744,6 +806,9 @@ impl Tool for BrowserWaitForSelectorTool {
 
             // Check timeout
             if start.elapsed() >= timeout {
+                if let Some(e) = frame_error {
+                    return ToolOutput::error(format!("Timeout after {timeout:?}: {e}"));
+                }
                 return ToolOutput::error(format!(
                     "Timeout after {:?}: element '{}' not found{}",
                     timeout,
@@ -772,6 +837,9 @@ struct ClickInput {
     /// Timeout for waiting (default: "30s")
     #[serde(default)]
     timeout: Option<String>,
+    /// Click inside this iframe (REQ-BT-028)
+    #[serde(default)]
+    frame: Option<String>,
 }
 
 pub struct BrowserClickTool;
@@ -801,7 +869,8 @@ impl Tool for BrowserClickTool {
                 "timeout": {
                     "type": "string",
                     "description": "Timeout for waiting (default: 30s)"
-                }
+                },
+                "frame": frames::frame_schema()
             },
             "required": ["selector"]
         })
@@ -827,6 +896,12 @@ impl Tool for BrowserClickTool {
 
         let guard = session.read().await;
 
+        if let Some(frame) = &input.frame {
+            let wait = input.wait.then_some(timeout);
+            let output = click_in_frame(&guard.page, frame, &input.selector, wait).await;
+            return with_downloads(output, &guard);
+        }
+
         // Optionally wait for element
         if input.wait {
             let check_script = format!(
@@ -864,9 +939,40 @@ impl Tool for BrowserClickTool {
         };
 
         // Click using CDP (works with React, Vue, etc.)
-        match element.click().await {
+        let output = match element.click().await {
             Ok(_) => ToolOutput::success(format!("Clicked element '{}'", input.selector)),
             Err(e) => ToolOutput::error(format!("Click failed: {e}")),
+        };
+        with_downloads(output, &guard)
+    }
+}
+
+/// `browser_click` inside an iframe (REQ-BT-028). With `wait`, a frame or
+/// element that is not there yet is retried until the timeout.
+async fn click_in_frame(
+    page: &chromiumoxide::Page,
+    frame: &str,
+    selector: &str,
+    wait: Option<Duration>,
+) -> ToolOutput {
+    let start = std::time::Instant::now();
+    loop {
+        let clicked = match frames::resolve(page, frame).await {
+            Ok(context) => frames::click(page, context, selector).await,
+            Err(e) => Err(e),
+        };
+        match clicked {
+            Ok(()) => {
+                return ToolOutput::success(format!(
+                    "Clicked element '{selector}' in frame '{frame}'"
+                ));
+            }
+            Err(frames::FrameError::Missing(_))
+                if wait.is_some_and(|timeout| start.elapsed() < timeout) =>
+            {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            Err(e) => return ToolOutput::error(e.to_string()),
         }
     }
 }
@@ -887,6 +993,9 @@ struct TypeInput {
     /// Timeout (default: "30s")
     #[serde(default)]
     timeout: Option<String>,
+    /// Type inside this iframe (REQ-BT-028)
+    #[serde(default)]
+    frame: Option<String>,
 }
 
 pub struct BrowserTypeTool;
@@ -920,7 +1029,8 @@ impl Tool for BrowserTypeTool {
                 "timeout": {
                     "type": "string",
                     "description": "Timeout (default: 30s)"
-                }
+                },
+                "frame": frames::frame_schema()
             },
             "required": ["selector", "text"]
         })
@@ -946,6 +1056,10 @@ impl Tool for BrowserTypeTool {
 
         let guard = session.read().await;
 
+        if let Some(frame) = &input.frame {
+            return type_in_frame(&guard.page, frame, &input).await;
+        }
+
         // Find the element
         let element = match guard.page.find_element(&input.selector).await {
             Ok(el) => el,
@@ -1011,6 +1125,62 @@ impl Tool for BrowserTypeTool {
     }
 }
 
+/// `browser_type` inside an iframe (REQ-BT-028). The element is focused
+/// from the frame's own context; key events then go to it like any other
+/// focused element.
+async fn type_in_frame(page: &chromiumoxide::Page, frame: &str, input: &TypeInput) -> ToolOutput {
+    let focused = match frames::resolve(page, frame).await {
+        Ok(context) => frames::focus(page, context, &input.selector, input.clear).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = focused {
+        return ToolOutput::error(e.to_string());
+    }
+
+    if input.clear {
+        let cleared =
+            dispatch_key_cdp(page, "Backspace", "Backspace", "Backspace", 8, None, "").await;
+        if !cleared.success {
+            return cleared;
+        }
+    }
+    // Letters, digits, and newlines go through key events; anything else
+    // (spaces, punctuation, non-ASCII) is inserted as text.
+    for c in input.text.chars() {
+        let key = if c == '\n' {
+            "Enter".to_string()
+        } else {
+            c.to_string()
+        };
+        let typed = match key_info(&key) {
+            Some((name, code, vk)) => {
+                dispatch_key_cdp(page, &name, &code, &key, vk, None, "").await
+            }
+            None => insert_text(page, &key).await,
+        };
+        if !typed.success {
+            return typed;
+        }
+    }
+
+    ToolOutput::success(format!(
+        "Typed {} characters into '{}' in frame '{frame}'",
+        input.text.len(),
+        input.selector
+    ))
+}
+
+/// Insert `text` at the focus as if typed, for characters without a key
+/// definition.
+async fn insert_text(page: &chromiumoxide::Page, text: &str) -> ToolOutput {
+    use chromiumoxide::cdp::browser_protocol::input::InsertTextParams;
+
+    match page.execute(InsertTextParams::new(text)).await {
+        Ok(_) => ToolOutput::success(format!("Inserted {text:?}")),
+        Err(e) => ToolOutput::error(format!("Failed to insert text: {e}")),
+    }
+}
+
 // ============================================================================
 // browser_key_press
 // ============================================================================
@@ -1307,3 +1477,943 @@ async fn dispatch_key_cdp(
 
     ToolOutput::success(format!("Pressed {chord} [cdp]"))
 }
+
+// ============================================================================
+// browser_handle_dialog (REQ-BT-027)
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum DialogAction {
+    Accept,
+    Dismiss,
+}
+
+#[derive(Debug, Deserialize)]
+struct HandleDialogInput {
+    action: DialogAction,
+    /// Text to enter into a `prompt()` dialog when accepting
+    #[serde(default)]
+    prompt_text: Option<String>,
+}
+
+pub struct BrowserHandleDialogTool;
+
+#[async_trait]
+impl Tool for BrowserHandleDialogTool {
+    fn name(&self) -> &'static str {
+        "browser_handle_dialog"
+    }
+
+    fn description(&self) -> String {
+        "Answer a JavaScript alert/confirm/prompt dialog. If a dialog is open, it is answered now; \
+         otherwise the answer is used for the next dialog the page opens, so call this before \
+         the click or navigation that triggers one. Dialogs without a prepared answer are \
+         dismissed automatically unless the server is configured otherwise; every dialog is \
+         noted in browser_recent_console_logs with level \"dialog\"."
+            .to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["accept", "dismiss"],
+                    "description": "accept presses OK; dismiss presses Cancel"
+                },
+                "prompt_text": {
+                    "type": "string",
+                    "description": "Text to enter into a prompt() dialog when accepting"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn run(&self, input: Value, ctx: ToolContext) -> ToolOutput {
+        let input: HandleDialogInput = match serde_json::from_value(input) {
+            Ok(i) => i,
+            Err(e) => return ToolOutput::error(format!("Invalid input: {e}")),
+        };
+        let accept = matches!(input.action, DialogAction::Accept);
+        let response = DialogResponse {
+            accept,
+            prompt_text: input.prompt_text.filter(|_| accept),
+        };
+
+        let session: Arc<RwLock<BrowserSession>> = match ctx.browser().await {
+            Ok(s) => s,
+            Err(e) => return ToolOutput::error(format!("Failed to get browser: {e}")),
+        };
+
+        let mut guard = session.write().await;
+        guard.last_activity = std::time::Instant::now();
+
+        // Take an open dialog, or leave the answer for the next one, under
+        // one lock so a dialog opening in between is not missed.
+        let open = {
+            let mut dialogs = guard.dialogs.lock().unwrap();
+            let open = dialogs.open.take();
+            if open.is_none() {
+                dialogs.next = Some(response.clone());
+            }
+            open
+        };
+        let Some(dialog) = open else {
+            return ToolOutput::success(format!(
+                "No dialog is open. The next dialog will be {}.",
+                response.verb()
+            ));
+        };
+
+        let answer = guard.page.execute(response.params());
+        match tokio::time::timeout(DEFAULT_TIMEOUT, answer).await {
+            Ok(Ok(_)) => ToolOutput::success(format!("{} {}", dialog.summary(), response.verb())),
+            Ok(Err(e)) => ToolOutput::error(format!("Failed to answer {}: {e}", dialog.summary())),
+            Err(_) => ToolOutput::error(format!("Timeout after {DEFAULT_TIMEOUT:?}")),
+        }
+    }
+}
+
+// ============================================================================
+// browser_throttle (REQ-BT-029)
+// ============================================================================
+
+/// Network presets, matching Chrome `DevTools`: latency in ms, then download
+/// and upload in kbit/s. `None` for throughput means unlimited.
+fn network_preset(name: &str) -> Option<(f64, Option<f64>, Option<f64>)> {
+    match name {
+        "none" | "offline" => Some((0.0, None, None)),
+        "slow-3g" => Some((2000.0, Some(400.0), Some(400.0))),
+        "fast-3g" => Some((562.5, Some(1440.0), Some(675.0))),
+        "4g" => Some((150.0, Some(9000.0), Some(9000.0))),
+        _ => None,
+    }
+}
+
+// Deprecated in favour of emulateNetworkConditionsByRule, which older
+// Chrome builds do not have yet
+#[allow(deprecated)]
+fn network_conditions(
+    offline: bool,
+    latency: f64,
+    download: Option<f64>,
+    upload: Option<f64>,
+) -> chromiumoxide::cdp::browser_protocol::network::EmulateNetworkConditionsParams {
+    use chromiumoxide::cdp::browser_protocol::network::EmulateNetworkConditionsParams;
+
+    // CDP takes bytes per second, with -1 for unlimited
+    let throughput = |kbps: Option<f64>| kbps.map_or(-1.0, |k| k * 1000.0 / 8.0);
+    EmulateNetworkConditionsParams::new(offline, latency, throughput(download), throughput(upload))
+}
+
+fn bandwidth(kbps: Option<f64>) -> String {
+    kbps.map_or_else(|| "unlimited".to_string(), |k| format!("{k} kbit/s"))
+}
+
+#[derive(Debug, Deserialize)]
+struct ThrottleInput {
+    /// Preset: none, offline, slow-3g, fast-3g, 4g
+    #[serde(default)]
+    network: Option<String>,
+    /// Overrides on top of the preset
+    #[serde(default)]
+    latency_ms: Option<f64>,
+    #[serde(default)]
+    download_kbps: Option<f64>,
+    #[serde(default)]
+    upload_kbps: Option<f64>,
+    /// CPU slowdown factor; 1 turns throttling off
+    #[serde(default)]
+    cpu_slowdown: Option<f64>,
+    #[serde(default)]
+    timeout: Option<String>,
+}
+
+impl ThrottleInput {
+    fn custom_network(&self) -> bool {
+        self.latency_ms.is_some() || self.download_kbps.is_some() || self.upload_kbps.is_some()
+    }
+}
+
+pub struct BrowserThrottleTool;
+
+#[async_trait]
+impl Tool for BrowserThrottleTool {
+    fn name(&self) -> &'static str {
+        "browser_throttle"
+    }
+
+    fn description(&self) -> String {
+        "Emulate a slow network and/or a slow CPU to reproduce performance problems users see on \
+         slow devices. Pick a network preset (none, offline, slow-3g, fast-3g, 4g), optionally \
+         override latency and bandwidth, and set a CPU slowdown factor (4 = four times slower). \
+         Settings last until changed or the browser session ends; use network \"none\" and \
+         cpu_slowdown 1 to turn throttling off."
+            .to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "network": {
+                    "type": "string",
+                    "enum": ["none", "offline", "slow-3g", "fast-3g", "4g"],
+                    "description": "Network preset; none removes network throttling"
+                },
+                "latency_ms": {
+                    "type": "number",
+                    "description": "Added round-trip latency in milliseconds"
+                },
+                "download_kbps": {
+                    "type": "number",
+                    "description": "Download bandwidth in kbit/s"
+                },
+                "upload_kbps": {
+                    "type": "number",
+                    "description": "Upload bandwidth in kbit/s"
+                },
+                "cpu_slowdown": {
+                    "type": "number",
+                    "description": "CPU slowdown factor, at least 1; 1 removes CPU throttling"
+                },
+                "timeout": {
+                    "type": "string",
+                    "description": "Timeout duration (default: 15s). Examples: '5s', '1m', '500ms'"
+                }
+            }
+        })
+    }
+
+    async fn run(&self, input: Value, ctx: ToolContext) -> ToolOutput {
+        use chromiumoxide::cdp::browser_protocol::emulation::SetCpuThrottlingRateParams;
+
+        let input: ThrottleInput = match serde_json::from_value(input) {
+            Ok(i) => i,
+            Err(e) => return ToolOutput::error(format!("Invalid input: {e}")),
+        };
+
+        let network = input.network.as_deref();
+        let custom = input.custom_network();
+        if network.is_none() && !custom && input.cpu_slowdown.is_none() {
+            return ToolOutput::error(
+                "Nothing to change: give network, latency/bandwidth, or cpu_slowdown",
+            );
+        }
+        let preset = match network {
+            Some(name) => match network_preset(name) {
+                Some(preset) => preset,
+                None => {
+                    return ToolOutput::error(format!(
+                        "Unknown network preset '{name}'. Use none, offline, slow-3g, fast-3g, \
+                         or 4g"
+                    ));
+                }
+            },
+            None => (0.0, None, None),
+        };
+        let offline = network == Some("offline");
+        if offline && custom {
+            return ToolOutput::error("Latency and bandwidth do not apply when offline");
+        }
+        let values = [input.latency_ms, input.download_kbps, input.upload_kbps];
+        if values
+            .into_iter()
+            .flatten()
+            .any(|v| !v.is_finite() || v < 0.0)
+        {
+            return ToolOutput::error("Latency and bandwidth must be zero or more");
+        }
+        if let Some(rate) = input.cpu_slowdown {
+            if !rate.is_finite() || rate < 1.0 {
+                return ToolOutput::error("cpu_slowdown must be at least 1");
+            }
+        }
+
+        let latency = input.latency_ms.unwrap_or(preset.0);
+        let download = input.download_kbps.or(preset.1);
+        let upload = input.upload_kbps.or(preset.2);
+
+        let timeout = input
+            .timeout
+            .as_deref()
+            .and_then(parse_duration)
+            .unwrap_or(DEFAULT_TIMEOUT);
+
+        let session: Arc<RwLock<BrowserSession>> = match ctx.browser().await {
+            Ok(s) => s,
+            Err(e) => return ToolOutput::error(format!("Failed to get browser: {e}")),
+        };
+
+        let mut guard = session.write().await;
+        guard.last_activity = std::time::Instant::now();
+        let page = &guard.page;
+
+        let apply = async {
+            let mut applied = Vec::new();
+            if network.is_some() || custom {
+                page.execute(network_conditions(offline, latency, download, upload))
+                    .await
+                    .map_err(|e| format!("Network throttling failed: {e}"))?;
+                applied.push(if offline {
+                    "network offline".to_string()
+                } else if network == Some("none") && !custom {
+                    "network unthrottled".to_string()
+                } else {
+                    format!(
+                        "network {latency} ms latency, {} down, {} up",
+                        bandwidth(download),
+                        bandwidth(upload)
+                    )
+                });
+            }
+            if let Some(rate) = input.cpu_slowdown {
+                page.execute(SetCpuThrottlingRateParams::new(rate))
+                    .await
+                    .map_err(|e| format!("CPU throttling failed: {e}"))?;
+                applied.push(if rate <= 1.0 {
+                    "CPU unthrottled".to_string()
+                } else {
+                    format!("CPU {rate}x slower")
+                });
+            }
+            Ok::<_, String>(applied.join("; "))
+        };
+
+        match tokio::time::timeout(timeout, apply).await {
+            Ok(Ok(summary)) => ToolOutput::success(summary),
+            Ok(Err(e)) => ToolOutput::error(e),
+            Err(_) => ToolOutput::error(format!("Timeout after {timeout:?}")),
+        }
+    }
+}
+
+// ============================================================================
+// browser_set_geolocation (REQ-BT-030)
+// ============================================================================
+
+/// Accuracy reported with an emulated position when none is given, in meters
+const DEFAULT_GEO_ACCURACY: f64 = 10.0;
+
+#[derive(Debug, Deserialize)]
+struct GeolocationInput {
+    #[serde(default)]
+    latitude: Option<f64>,
+    #[serde(default)]
+    longitude: Option<f64>,
+    /// Meters
+    #[serde(default)]
+    accuracy: Option<f64>,
+    /// Remove the override instead of setting one
+    #[serde(default)]
+    clear: bool,
+}
+
+pub struct BrowserSetGeolocationTool;
+
+#[async_trait]
+impl Tool for BrowserSetGeolocationTool {
+    fn name(&self) -> &'static str {
+        "browser_set_geolocation"
+    }
+
+    fn description(&self) -> String {
+        "Override the position the page sees through navigator.geolocation, and allow pages to \
+         read it. Use to test location-dependent features. Pass clear: true to remove the \
+         override. Reload the page if it read the position on load."
+            .to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "latitude": {
+                    "type": "number",
+                    "description": "Latitude in degrees, -90 to 90"
+                },
+                "longitude": {
+                    "type": "number",
+                    "description": "Longitude in degrees, -180 to 180"
+                },
+                "accuracy": {
+                    "type": "number",
+                    "description": "Accuracy in meters (default: 10)"
+                },
+                "clear": {
+                    "type": "boolean",
+                    "description": "Remove the geolocation override"
+                }
+            }
+        })
+    }
+
+    async fn run(&self, input: Value, ctx: ToolContext) -> ToolOutput {
+        use chromiumoxide::cdp::browser_protocol::emulation::SetGeolocationOverrideParams;
+
+        let input: GeolocationInput = match serde_json::from_value(input) {
+            Ok(i) => i,
+            Err(e) => return ToolOutput::error(format!("Invalid input: {e}")),
+        };
+
+        let position = match (input.clear, input.latitude, input.longitude) {
+            (true, None, None) => None,
+            (true, _, _) => {
+                return ToolOutput::error("Give either clear or a latitude and longitude");
+            }
+            (false, Some(latitude), Some(longitude)) => Some((latitude, longitude)),
+            (false, _, _) => return ToolOutput::error("Both latitude and longitude are required"),
+        };
+        let accuracy = input.accuracy.unwrap_or(DEFAULT_GEO_ACCURACY);
+        if let Some((latitude, longitude)) = position {
+            if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+                return ToolOutput::error(
+                    "Latitude must be within -90..90 and longitude within -180..180",
+                );
+            }
+            if !accuracy.is_finite() || accuracy < 0.0 {
+                return ToolOutput::error("Accuracy must be zero or more");
+            }
+        }
+
+        let session: Arc<RwLock<BrowserSession>> = match ctx.browser().await {
+            Ok(s) => s,
+            Err(e) => return ToolOutput::error(format!("Failed to get browser: {e}")),
+        };
+
+        let mut guard = session.write().await;
+        guard.last_activity = std::time::Instant::now();
+
+        // All fields empty removes the override
+        let mut params = SetGeolocationOverrideParams::default();
+        if let Some((latitude, longitude)) = position {
+            if let Err(e) = guard.grant_geolocation().await {
+                return ToolOutput::error(format!("Failed to allow geolocation: {e}"));
+            }
+            params.latitude = Some(latitude);
+            params.longitude = Some(longitude);
+            params.accuracy = Some(accuracy);
+        }
+
+        let result = tokio::time::timeout(DEFAULT_TIMEOUT, guard.page.execute(params)).await;
+        match (result, position) {
+            (Ok(Ok(_)), Some((latitude, longitude))) => ToolOutput::success(format!(
+                "Geolocation set to {latitude}, {longitude} (±{accuracy} m)"
+            )),
+            (Ok(Ok(_)), None) => ToolOutput::success("Geolocation override removed"),
+            (Ok(Err(e)), _) => ToolOutput::error(format!("Geolocation override failed: {e}")),
+            (Err(_), _) => ToolOutput::error(format!("Timeout after {DEFAULT_TIMEOUT:?}")),
+        }
+    }
+}
+
+// ============================================================================
+// browser_set_locale (REQ-BT-030)
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct LocaleInput {
+    /// BCP 47 tag, e.g. "de-DE"
+    #[serde(default)]
+    locale: Option<String>,
+    /// IANA zone, e.g. "Europe/Berlin"
+    #[serde(default)]
+    timezone: Option<String>,
+    /// Defaults to one derived from `locale`
+    #[serde(default)]
+    accept_language: Option<String>,
+    /// Remove all three overrides
+    #[serde(default)]
+    reset: bool,
+}
+
+/// The Accept-Language a browser set to `locale` would send: the locale,
+/// then its bare language as a fallback.
+fn accept_language_for(locale: &str) -> String {
+    match locale.split_once('-') {
+        Some((language, _)) if !language.is_empty() => format!("{locale},{language};q=0.9"),
+        _ => locale.to_string(),
+    }
+}
+
+pub struct BrowserSetLocaleTool;
+
+#[async_trait]
+impl Tool for BrowserSetLocaleTool {
+    fn name(&self) -> &'static str {
+        "browser_set_locale"
+    }
+
+    fn description(&self) -> String {
+        "Override the timezone, locale (Intl formatting, navigator.language) and Accept-Language \
+         header for the browser session. Use to test translations and date, number and currency \
+         formatting. Fields left out keep their current value; reset: true removes all \
+         overrides. Reload the page afterwards so it picks up the new settings."
+            .to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "locale": {
+                    "type": "string",
+                    "description": "BCP 47 locale, e.g. 'de-DE', 'ja-JP', 'pt-BR'"
+                },
+                "timezone": {
+                    "type": "string",
+                    "description": "IANA timezone, e.g. 'Europe/Berlin', 'America/Sao_Paulo'"
+                },
+                "accept_language": {
+                    "type": "string",
+                    "description": "Accept-Language header and navigator.languages \
+                                    (default: derived from locale, e.g. 'de-DE,de;q=0.9')"
+                },
+                "reset": {
+                    "type": "boolean",
+                    "description": "Remove the timezone, locale and Accept-Language overrides"
+                }
+            }
+        })
+    }
+
+    async fn run(&self, input: Value, ctx: ToolContext) -> ToolOutput {
+        use chromiumoxide::cdp::browser_protocol::emulation::{
+            SetLocaleOverrideParams, SetTimezoneOverrideParams, SetUserAgentOverrideParams,
+        };
+
+        let input: LocaleInput = match serde_json::from_value(input) {
+            Ok(i) => i,
+            Err(e) => return ToolOutput::error(format!("Invalid input: {e}")),
+        };
+
+        let changes =
+            input.locale.is_some() || input.timezone.is_some() || input.accept_language.is_some();
+        if input.reset == changes {
+            return ToolOutput::error(
+                "Give locale, timezone or accept_language, or reset: true on its own",
+            );
+        }
+        let accept_language = input
+            .accept_language
+            .clone()
+            .or_else(|| input.locale.as_deref().map(accept_language_for));
+
+        let session: Arc<RwLock<BrowserSession>> = match ctx.browser().await {
+            Ok(s) => s,
+            Err(e) => return ToolOutput::error(format!("Failed to get browser: {e}")),
+        };
+
+        let mut guard = session.write().await;
+        guard.last_activity = std::time::Instant::now();
+        let page = &guard.page;
+
+        let apply = async {
+            let mut applied = Vec::new();
+            if input.reset || input.timezone.is_some() {
+                // An empty timezone removes the override
+                let timezone = input.timezone.clone().unwrap_or_default();
+                page.execute(SetTimezoneOverrideParams::new(timezone.clone()))
+                    .await
+                    .map_err(|e| format!("Timezone override failed: {e}"))?;
+                if !timezone.is_empty() {
+                    applied.push(format!("timezone {timezone}"));
+                }
+            }
+            if input.reset || input.locale.is_some() {
+                let params = SetLocaleOverrideParams {
+                    locale: input.locale.clone(),
+                };
+                page.execute(params)
+                    .await
+                    .map_err(|e| format!("Locale override failed: {e}"))?;
+                if let Some(locale) = &input.locale {
+                    applied.push(format!("locale {locale}"));
+                }
+            }
+            if input.reset || accept_language.is_some() {
+                // Accept-Language rides on the user agent override; keep the
+                // browser's own user agent.
+                let user_agent = page
+                    .user_agent()
+                    .await
+                    .map_err(|e| format!("Failed to read user agent: {e}"))?;
+                let mut params = SetUserAgentOverrideParams::new(user_agent);
+                params.accept_language.clone_from(&accept_language);
+                page.execute(params)
+                    .await
+                    .map_err(|e| format!("Accept-Language override failed: {e}"))?;
+                if let Some(accept_language) = &accept_language {
+                    applied.push(format!("Accept-Language {accept_language}"));
+                }
+            }
+            Ok::<_, String>(applied)
+        };
+
+        match tokio::time::timeout(DEFAULT_TIMEOUT, apply).await {
+            Ok(Ok(_)) if input.reset => {
+                ToolOutput::success("Timezone, locale and Accept-Language overrides removed")
+            }
+            Ok(Ok(applied)) => ToolOutput::success(format!("Set {}", applied.join(", "))),
+            Ok(Err(e)) => ToolOutput::error(e),
+            Err(_) => ToolOutput::error(format!("Timeout after {DEFAULT_TIMEOUT:?}")),
+        }
+    }
+}
+
+// ============================================================================
+// browser_print_to_pdf (REQ-BT-031)
+// ============================================================================
+
+/// Paper sizes in inches, the unit CDP takes, portrait
+fn paper_size(name: &str) -> Option<(f64, f64)> {
+    match name.to_lowercase().as_str() {
+        "letter" => Some((8.5, 11.0)),
+        "legal" => Some((8.5, 14.0)),
+        "tabloid" => Some((11.0, 17.0)),
+        "a3" => Some((11.69, 16.54)),
+        "a4" => Some((8.27, 11.69)),
+        "a5" => Some((5.83, 8.27)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PrintToPdfInput {
+    /// Where to save, relative to the working directory
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default)]
+    paper: Option<String>,
+    #[serde(default)]
+    landscape: bool,
+    /// Same margin on every side, in inches
+    #[serde(default)]
+    margin_inches: Option<f64>,
+    #[serde(default)]
+    print_background: bool,
+    /// Let the page's `@page { size }` rule decide the paper size
+    #[serde(default)]
+    prefer_css_page_size: bool,
+    /// e.g. "1-3, 5"
+    #[serde(default)]
+    page_ranges: Option<String>,
+    #[serde(default)]
+    timeout: Option<String>,
+}
+
+pub struct BrowserPrintToPdfTool;
+
+#[async_trait]
+impl Tool for BrowserPrintToPdfTool {
+    fn name(&self) -> &'static str {
+        "browser_print_to_pdf"
+    }
+
+    fn description(&self) -> String {
+        "Render the current page to a PDF with print media styles and save it in the working \
+         directory. Use to check print stylesheets or produce a report from a page. Choose the \
+         paper size, orientation, margins and whether background colors and images are printed."
+            .to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File to write, relative to the working directory \
+                                    (default: browser-print-<timestamp>.pdf)"
+                },
+                "paper": {
+                    "type": "string",
+                    "enum": ["letter", "legal", "tabloid", "a3", "a4", "a5"],
+                    "description": "Paper size (default: letter)"
+                },
+                "landscape": {
+                    "type": "boolean",
+                    "description": "Landscape orientation (default: false)"
+                },
+                "margin_inches": {
+                    "type": "number",
+                    "description": "Margin on every side in inches (default: Chrome's 0.4)"
+                },
+                "print_background": {
+                    "type": "boolean",
+                    "description": "Print background colors and images (default: false)"
+                },
+                "prefer_css_page_size": {
+                    "type": "boolean",
+                    "description": "Use the page's CSS @page size instead of paper"
+                },
+                "page_ranges": {
+                    "type": "string",
+                    "description": "Pages to include, e.g. '1-3, 5' (default: all)"
+                },
+                "timeout": {
+                    "type": "string",
+                    "description": "Timeout duration (default: 15s). Examples: '5s', '1m', '500ms'"
+                }
+            }
+        })
+    }
+
+    async fn run(&self, input: Value, ctx: ToolContext) -> ToolOutput {
+        use chromiumoxide::cdp::browser_protocol::page::PrintToPdfParams;
+
+        let input: PrintToPdfInput = match serde_json::from_value(input) {
+            Ok(i) => i,
+            Err(e) => return ToolOutput::error(format!("Invalid input: {e}")),
+        };
+
+        let paper = input.paper.as_deref().unwrap_or("letter");
+        let Some((width, height)) = paper_size(paper) else {
+            return ToolOutput::error(format!(
+                "Unknown paper size '{paper}'. Use letter, legal, tabloid, a3, a4, or a5"
+            ));
+        };
+        if let Some(margin) = input.margin_inches {
+            if !margin.is_finite() || margin < 0.0 || 2.0 * margin >= width.min(height) {
+                return ToolOutput::error("Margins must be zero or more and leave room to print");
+            }
+        }
+
+        let relative = input.path.clone().unwrap_or_else(|| {
+            format!(
+                "browser-print-{}.pdf",
+                chrono::Local::now().format("%Y%m%d-%H%M%S")
+            )
+        });
+        let path = ctx.working_dir.join(&relative);
+
+        let timeout = input
+            .timeout
+            .as_deref()
+            .and_then(parse_duration)
+            .unwrap_or(DEFAULT_TIMEOUT);
+
+        let session: Arc<RwLock<BrowserSession>> = match ctx.browser().await {
+            Ok(s) => s,
+            Err(e) => return ToolOutput::error(format!("Failed to get browser: {e}")),
+        };
+
+        let mut guard = session.write().await;
+        guard.last_activity = std::time::Instant::now();
+
+        let params = PrintToPdfParams {
+            landscape: Some(input.landscape),
+            print_background: Some(input.print_background),
+            paper_width: Some(width),
+            paper_height: Some(height),
+            margin_top: input.margin_inches,
+            margin_bottom: input.margin_inches,
+            margin_left: input.margin_inches,
+            margin_right: input.margin_inches,
+            page_ranges: input.page_ranges.clone(),
+            prefer_css_page_size: Some(input.prefer_css_page_size),
+            ..Default::default()
+        };
+
+        let pdf = match tokio::time::timeout(timeout, guard.page.pdf(params)).await {
+            Ok(Ok(pdf)) => pdf,
+            Ok(Err(e)) => return ToolOutput::error(format!("PDF rendering failed: {e}")),
+            Err(_) => return ToolOutput::error(format!("Timeout after {timeout:?}")),
+        };
+        drop(guard);
+
+        if let Some(parent) = path.parent() {
+            if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                return ToolOutput::error(format!("Failed to create {}: {e}", parent.display()));
+            }
+        }
+        if let Err(e) = tokio::fs::write(&path, &pdf).await {
+            return ToolOutput::error(format!("Failed to save PDF to {}: {e}", path.display()));
+        }
+
+        ToolOutput::success(format!(
+            "Saved PDF ({} KB, {paper}{}) to {}",
+            pdf.len().div_ceil(1024),
+            if input.landscape { ", landscape" } else { "" },
+            path.display()
+        ))
+    }
+}
+
+// ============================================================================
+// browser_query (REQ-BT-032)
+// ============================================================================
+
+/// Characters kept of each element's text and attribute values
+const QUERY_TEXT_LEN: usize = 200;
+
+/// Results larger than this go to a temp file, as with `browser_eval`
+const QUERY_INLINE_BYTES: usize = 16 * 1024;
+
+#[derive(Debug, Deserialize)]
+struct QueryInput {
+    selector: String,
+    /// Only report these attributes; all when absent
+    #[serde(default)]
+    attributes: Option<Vec<String>>,
+    #[serde(default = "default_query_limit")]
+    limit: usize,
+    /// Query inside this iframe (REQ-BT-028)
+    #[serde(default)]
+    frame: Option<String>,
+    #[serde(default)]
+    timeout: Option<String>,
+}
+
+fn default_query_limit() -> usize {
+    50
+}
+
+/// Script collecting what `browser_query` reports for each match.
+fn query_script(selector: &str, attributes: Option<&[String]>, limit: usize) -> String {
+    format!(
+        "(() => {{
+            const all = document.querySelectorAll({selector});
+            const names = {attributes};
+            const clip = (s) => s.length > {max} ? s.slice(0, {max}) + '…' : s;
+            return {{
+                count: all.length,
+                elements: Array.from(all).slice(0, {limit}).map((el, index) => {{
+                    const r = el.getBoundingClientRect();
+                    const style = getComputedStyle(el);
+                    const attributes = {{}};
+                    for (const a of el.attributes) {{
+                        if (!names || names.includes(a.name)) attributes[a.name] = clip(a.value);
+                    }}
+                    const info = {{
+                        index,
+                        tag: el.tagName.toLowerCase(),
+                        text: clip((el.innerText ?? el.textContent ?? '').trim()),
+                        attributes,
+                        box: {{
+                            x: Math.round(r.x),
+                            y: Math.round(r.y),
+                            width: Math.round(r.width),
+                            height: Math.round(r.height),
+                        }},
+                        visible: r.width > 0 && r.height > 0
+                            && style.visibility !== 'hidden'
+                            && Number(style.opacity) > 0,
+                    }};
+                    if (typeof el.value === 'string') info.value = clip(el.value);
+                    if (typeof el.checked === 'boolean') info.checked = el.checked;
+                    return info;
+                }}),
+            }};
+        }})()",
+        selector = serde_json::to_string(selector).unwrap(),
+        attributes = serde_json::to_string(&attributes).unwrap(),
+        max = QUERY_TEXT_LEN,
+    )
+}
+
+pub struct BrowserQueryTool;
+
+#[async_trait]
+impl Tool for BrowserQueryTool {
+    fn name(&self) -> &'static str {
+        "browser_query"
+    }
+
+    fn description(&self) -> String {
+        "List the elements matching a CSS selector as JSON: tag, visible text, attributes, \
+         form value and checked state, bounding box in viewport pixels, and whether it is \
+         visible. Use to check lists, tables and forms without writing browser_eval \
+         JavaScript. Returns the total match count and up to `limit` elements."
+            .to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "selector": {
+                    "type": "string",
+                    "description": "CSS selector; every match is reported, in document order"
+                },
+                "attributes": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Only report these attributes (default: all)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum elements to return (default: 50)"
+                },
+                "frame": frames::frame_schema(),
+                "timeout": {
+                    "type": "string",
+                    "description": "Timeout duration (default: 15s). Examples: '5s', '1m', '500ms'"
+                }
+            },
+            "required": ["selector"]
+        })
+    }
+
+    async fn run(&self, input: Value, ctx: ToolContext) -> ToolOutput {
+        let input: QueryInput = match serde_json::from_value(input) {
+            Ok(i) => i,
+            Err(e) => return ToolOutput::error(format!("Invalid input: {e}")),
+        };
+        if input.limit == 0 {
+            return ToolOutput::error("limit must be at least 1");
+        }
+
+        let timeout = input
+            .timeout
+            .as_deref()
+            .and_then(parse_duration)
+            .unwrap_or(DEFAULT_TIMEOUT);
+
+        let session: Arc<RwLock<BrowserSession>> = match ctx.browser().await {
+            Ok(s) => s,
+            Err(e) => return ToolOutput::error(format!("Failed to get browser: {e}")),
+        };
+
+        let mut guard = session.write().await;
+        guard.last_activity = std::time::Instant::now();
+
+        let script = query_script(&input.selector, input.attributes.as_deref(), input.limit);
+        let mut params = EvaluateParams::builder()
+            .expression(script)
+            .return_by_value(true)
+            .build()
+            .unwrap();
+        if let Some(frame) = &input.frame {
+            match frames::resolve(&guard.page, frame).await {
+                Ok(context) => params.context_id = Some(context),
+                Err(e) => return ToolOutput::error(e.to_string()),
+            }
+        }
+
+        let result = match tokio::time::timeout(timeout, guard.page.evaluate(params)).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => return ToolOutput::error(format!("Query failed: {e}")),
+            Err(_) => return ToolOutput::error(format!("Timeout after {timeout:?}")),
+        };
+        let Some(value) = result.value() else {
+            return ToolOutput::error("Query returned no result");
+        };
+        let json_str = serde_json::to_string_pretty(value).unwrap_or_else(|_| "null".to_string());
+
+        if json_str.len() > QUERY_INLINE_BYTES {
+            let path = format!("/tmp/phoenix-query-result-{}.json", uuid::Uuid::new_v4());
+            if let Err(e) = tokio::fs::write(&path, &json_str).await {
+                return ToolOutput::error(format!("Failed to write large output: {e}"));
+            }
+            let count = value.get("count").and_then(Value::as_u64).unwrap_or(0);
+            return ToolOutput::success(format!(
+                "{count} matches; output written to {path} (use `cat` to view)"
+            ));
+        }
+        ToolOutput::success(format!("<query_result>{json_str}</query_result>"))
+    }
+}
@@ -0,0 +1,353 @@
+//! `InspectBinary` tool - look inside files `read_file` refuses
+//!
+//! REQ-PROJ-030: file type from magic bytes, size, printable strings and a
+//! bounded hexdump, for debugging assets and wire formats
+
+use super::{Tool, ToolContext, ToolOutput};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fmt::Write as _;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// Bytes scanned for the file type and strings.
+const SCAN_BYTES: u64 = 1024 * 1024;
+
+const DEFAULT_LENGTH: u64 = 256;
+
+/// Most bytes one call dumps.
+const MAX_LENGTH: u64 = 4096;
+
+/// Shortest run of printable characters reported as a string.
+const MIN_STRING_LEN: usize = 6;
+
+/// Most strings listed, and the longest shown in full.
+const MAX_STRINGS: usize = 20;
+const MAX_STRING_CHARS: usize = 80;
+
+/// Byte patterns that must each appear at their offset.
+type Signature = &'static [(usize, &'static [u8])];
+
+/// Known file types and their signatures.
+const SIGNATURES: &[(&str, Signature)] = &[
+    ("PNG image", &[(0, b"\x89PNG\r\n\x1a\n")]),
+    ("JPEG image", &[(0, b"\xff\xd8\xff")]),
+    ("GIF image", &[(0, b"GIF8")]),
+    ("WebP image", &[(0, b"RIFF"), (8, b"WEBP")]),
+    ("WAV audio", &[(0, b"RIFF"), (8, b"WAVE")]),
+    ("BMP image", &[(0, b"BM")]),
+    ("ICO icon", &[(0, b"\0\0\x01\0")]),
+    ("TIFF image", &[(0, b"II*\0")]),
+    ("TIFF image", &[(0, b"MM\0*")]),
+    ("PDF document", &[(0, b"%PDF-")]),
+    (
+        "ZIP archive (also jar, docx, xlsx, apk)",
+        &[(0, b"PK\x03\x04")],
+    ),
+    ("gzip data", &[(0, b"\x1f\x8b")]),
+    ("bzip2 data", &[(0, b"BZh")]),
+    ("xz data", &[(0, b"\xfd7zXZ\0")]),
+    ("zstd data", &[(0, b"\x28\xb5\x2f\xfd")]),
+    ("7-Zip archive", &[(0, b"7z\xbc\xaf\x27\x1c")]),
+    ("tar archive", &[(257, b"ustar")]),
+    ("ELF executable or library", &[(0, b"\x7fELF")]),
+    ("Mach-O binary", &[(0, b"\xcf\xfa\xed\xfe")]),
+    ("Mach-O binary", &[(0, b"\xce\xfa\xed\xfe")]),
+    (
+        "Mach-O universal binary or Java class",
+        &[(0, b"\xca\xfe\xba\xbe")],
+    ),
+    ("Windows PE executable", &[(0, b"MZ")]),
+    ("WebAssembly module", &[(0, b"\0asm")]),
+    ("SQLite database", &[(0, b"SQLite format 3\0")]),
+    ("Parquet file", &[(0, b"PAR1")]),
+    ("MP4/QuickTime media", &[(4, b"ftyp")]),
+    ("MP3 audio", &[(0, b"ID3")]),
+    ("Ogg media", &[(0, b"OggS")]),
+    ("FLAC audio", &[(0, b"fLaC")]),
+    ("WOFF font", &[(0, b"wOFF")]),
+    ("WOFF2 font", &[(0, b"wOF2")]),
+    ("OpenType font", &[(0, b"OTTO")]),
+];
+
+/// Inspect a file's type, strings and raw bytes.
+pub struct InspectBinaryTool;
+
+#[derive(Debug, Deserialize)]
+struct InspectBinaryInput {
+    path: String,
+    offset: Option<u64>,
+    length: Option<u64>,
+}
+
+/// What `head`, the start of a file, looks like.
+fn identify(head: &[u8]) -> &'static str {
+    let matches =
+        |&(offset, magic): &(usize, &[u8])| head.get(offset..offset + magic.len()) == Some(magic);
+    if let Some(&(name, _)) = SIGNATURES
+        .iter()
+        .find(|(_, parts)| parts.iter().all(matches))
+    {
+        return name;
+    }
+    let sample = &head[..head.len().min(8192)];
+    if !sample.contains(&0) && std::str::from_utf8(sample).is_ok() {
+        "text (read it with read_file)"
+    } else {
+        "unknown"
+    }
+}
+
+/// Runs of printable ASCII at least `MIN_STRING_LEN` long, with their
+/// offsets, like `strings -t x`.
+fn printable_strings(bytes: &[u8]) -> Vec<(usize, &str)> {
+    let printable = |b: &u8| b.is_ascii_graphic() || *b == b' ' || *b == b'\t';
+    let mut found = Vec::new();
+    let mut start = 0;
+    for (i, byte) in bytes.iter().chain(std::iter::once(&0)).enumerate() {
+        if printable(byte) {
+            continue;
+        }
+        if i - start >= MIN_STRING_LEN {
+            // Printable ASCII is valid UTF-8
+            found.push((
+                start,
+                std::str::from_utf8(&bytes[start..i]).unwrap_or_default(),
+            ));
+        }
+        start = i + 1;
+    }
+    found
+}
+
+fn shown_char(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+/// `hexdump -C` style lines for `bytes`, which start at `offset`.
+fn hexdump(bytes: &[u8], offset: u64) -> String {
+    let mut output = String::new();
+    for (row, line) in bytes.chunks(16).enumerate() {
+        let _ = write!(output, "{:08x}  ", offset + row as u64 * 16);
+        for i in 0..16 {
+            match line.get(i) {
+                Some(byte) => {
+                    let _ = write!(output, "{byte:02x} ");
+                }
+                None => output.push_str("   "),
+            }
+            if i == 7 {
+                output.push(' ');
+            }
+        }
+        let ascii: String = line.iter().map(|&b| shown_char(b)).collect();
+        let _ = writeln!(output, " |{ascii}|");
+    }
+    output
+}
+
+#[async_trait]
+impl Tool for InspectBinaryTool {
+    fn name(&self) -> &'static str {
+        "inspect_binary"
+    }
+
+    fn description(&self) -> String {
+        "Inspect a binary file: its type from magic bytes, size, printable strings, and a \
+         hexdump of up to 4096 bytes from offset. Use for images, archives, executables, \
+         databases and protocol captures that read_file cannot show."
+            .to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "required": ["path"],
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "File path (absolute or relative to working directory)"
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Byte offset the hexdump starts at. Default: 0"
+                },
+                "length": {
+                    "type": "integer",
+                    "description": "Bytes to dump, at most 4096. Default: 256"
+                }
+            }
+        })
+    }
+
+    async fn run(&self, input: Value, ctx: ToolContext) -> ToolOutput {
+        let input: InspectBinaryInput = match serde_json::from_value(input) {
+            Ok(i) => i,
+            Err(e) => return ToolOutput::error(format!("Invalid input: {e}")),
+        };
+        let raw = PathBuf::from(&input.path);
+        let path = if raw.is_absolute() {
+            raw
+        } else {
+            ctx.working_dir.join(raw)
+        };
+
+        let mut file = match tokio::fs::File::open(&path).await {
+            Ok(file) => file,
+            Err(e) => return ToolOutput::error(format!("Failed to open '{}': {e}", input.path)),
+        };
+        let metadata = match file.metadata().await {
+            Ok(metadata) if metadata.is_dir() => {
+                return ToolOutput::error(format!("'{}' is a directory", input.path))
+            }
+            Ok(metadata) => metadata,
+            Err(e) => return ToolOutput::error(format!("Failed to stat '{}': {e}", input.path)),
+        };
+        let size = metadata.len();
+        let offset = input.offset.unwrap_or(0);
+        if offset > size {
+            return ToolOutput::error(format!("Offset {offset} is past the end ({size} bytes)"));
+        }
+        let length = input.length.unwrap_or(DEFAULT_LENGTH).min(MAX_LENGTH);
+
+        let mut head = Vec::new();
+        let mut dump = Vec::new();
+        let read = async {
+            (&mut file).take(SCAN_BYTES).read_to_end(&mut head).await?;
+            file.seek(SeekFrom::Start(offset)).await?;
+            (&mut file).take(length).read_to_end(&mut dump).await
+        };
+        if let Err(e) = read.await {
+            return ToolOutput::error(format!("Failed to read '{}': {e}", input.path));
+        }
+        if ctx.cancel.is_cancelled() {
+            return ToolOutput::error("Cancelled");
+        }
+
+        let mut output = format!("{}\nSize: {size} bytes\n", path.display());
+        if let Ok(modified) = metadata.modified() {
+            let modified = chrono::DateTime::<chrono::Utc>::from(modified);
+            let _ = writeln!(
+                output,
+                "Modified: {}",
+                modified.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+        }
+        let _ = writeln!(output, "Type: {}", identify(&head));
+
+        let strings = printable_strings(&head);
+        let scanned = if size > SCAN_BYTES {
+            " in the first 1 MB"
+        } else {
+            ""
+        };
+        let _ = writeln!(
+            output,
+            "\nStrings ({} of {MIN_STRING_LEN}+ printable chars{scanned}):",
+            strings.len()
+        );
+        for (at, text) in strings.iter().take(MAX_STRINGS) {
+            let shown: String = text.chars().take(MAX_STRING_CHARS).collect();
+            let _ = writeln!(output, "  {at:08x}  {shown}");
+        }
+        if strings.len() > MAX_STRINGS {
+            let _ = writeln!(output, "  ... {} more", strings.len() - MAX_STRINGS);
+        }
+
+        let _ = writeln!(output, "\nHex ({} bytes at offset {offset}):", dump.len());
+        output.push_str(&hexdump(&dump, offset));
+        ToolOutput::success(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::BrowserSessionManager;
+    use std::sync::Arc;
+    use tokio_util::sync::CancellationToken;
+
+    fn test_context(working_dir: PathBuf) -> ToolContext {
+        ToolContext::new(
+            CancellationToken::new(),
+            "test-conv".to_string(),
+            working_dir,
+            Arc::new(BrowserSessionManager::default()),
+            Arc::new(crate::tools::BashHandleRegistry::new()),
+            Arc::new(crate::llm::ModelRegistry::new_empty()),
+            crate::terminal::ActiveTerminals::new(),
+            Arc::new(crate::tools::TmuxRegistry::new()),
+            None,
+        )
+    }
+
+    #[test]
+    fn identify_recognises_magic_bytes() {
+        assert_eq!(identify(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), "PNG image");
+        assert_eq!(identify(b"RIFF\0\0\0\0WEBPVP8 "), "WebP image");
+        assert_eq!(
+            identify(b"\x7fELF\x02\x01\x01"),
+            "ELF executable or library"
+        );
+        let mut tar = vec![0; 262];
+        tar[257..].copy_from_slice(b"ustar");
+        assert_eq!(identify(&tar), "tar archive");
+        assert_eq!(identify(b"fn main() {}\n"), "text (read it with read_file)");
+        assert_eq!(identify(b"\x00\x13\x37\xff"), "unknown");
+    }
+
+    #[test]
+    fn strings_are_printable_runs() {
+        let bytes = b"\0\0hello world\x01abc\xffsecond string";
+        let found = printable_strings(bytes);
+        assert_eq!(found, [(2, "hello world"), (18, "second string")]);
+    }
+
+    #[test]
+    fn hexdump_pads_the_last_line() {
+        let dump = hexdump(b"0123456789abcdefXY\n", 0x20);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(
+            lines[0],
+            "00000020  30 31 32 33 34 35 36 37  38 39 61 62 63 64 65 66  |0123456789abcdef|"
+        );
+        assert_eq!(lines[1].len(), lines[0].len() - 13);
+        assert!(lines[1].starts_with("00000030  58 59 0a "));
+        assert!(lines[1].ends_with(" |XY.|"));
+    }
+
+    #[tokio::test]
+    async fn inspects_a_file_with_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut bytes = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        bytes.extend((0..=255u8).cycle().take(1000));
+        std::fs::write(dir.path().join("logo.png"), &bytes).unwrap();
+
+        let result = InspectBinaryTool
+            .run(
+                json!({"path": "logo.png", "offset": 512, "length": 32}),
+                test_context(dir.path().to_path_buf()),
+            )
+            .await;
+        assert!(result.success, "{}", result.output);
+        assert!(result.output.contains("Size: 1016 bytes"));
+        assert!(result.output.contains("Type: PNG image"));
+        assert!(result.output.contains("Hex (32 bytes at offset 512):"));
+        assert!(result.output.contains("\n00000200  "));
+        assert!(!result.output.contains("\n00000220  "));
+
+        let past_end = InspectBinaryTool
+            .run(
+                json!({"path": "logo.png", "offset": 5000}),
+                test_context(dir.path().to_path_buf()),
+            )
+            .await;
+        assert!(!past_end.success);
+    }
+}
@@ -5,10 +5,13 @@
 //! REQ-KWS-003: Result Filtering
 //! REQ-KWS-004: Tool Schema
 //! REQ-KWS-005: LLM Selection
+//! REQ-KWS-006: Result Ranking Signals
+
+mod ranking;
 
 use super::{Tool, ToolContext, ToolOutput};
 use crate::llm::{
-    ContentBlock, LlmMessage, LlmRequest, MessageRole, PromptCacheKey, SystemContent,
+    complete_json, ContentBlock, LlmMessage, LlmRequest, MessageRole, PromptCacheKey, SystemContent,
 };
 use async_trait::async_trait;
 use serde::Deserialize;
@@ -17,6 +20,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
+use std::time::SystemTime;
 use tokio::process::Command;
 
 const MAX_TERM_RESULTS: usize = 64 * 1024; // 64KB per term
@@ -40,18 +44,16 @@ ANALYSIS INSTRUCTIONS:
 3. Exercise strict judgment - only return files that are genuinely relevant
 
 OUTPUT FORMAT:
-Respond with a plain text list of the most relevant files in decreasing order of relevance:
+Respond with a JSON object listing the most relevant files in decreasing order of relevance:
 
-/path/to/most/relevant/file: Concise relevance explanation
-/path/to/second/file: Concise relevance explanation
-...
+{"files": [{"path": "/path/to/most/relevant/file", "reason": "Concise relevance explanation"}]}
 
 IMPORTANT:
 - Only include files with meaningful relevance to the query
-- Keep it short, don't blather
+- Keep reasons short, don't blather
 - Do NOT list all files that had keyword matches
 - Focus on quality over quantity
-- If no files are truly relevant, return "No relevant files found"
+- If no files are truly relevant, return an empty "files" list
 - Use absolute file paths"#;
 
 #[derive(Debug, Deserialize)]
@@ -60,6 +62,38 @@ struct KeywordSearchInput {
     search_terms: Vec<String>,
 }
 
+/// Ranked files returned by the filter model (REQ-LLM-015).
+#[derive(Debug, Deserialize)]
+struct RankedFiles {
+    files: Vec<RankedFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RankedFile {
+    path: String,
+    reason: String,
+}
+
+fn ranked_files_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["files"],
+        "properties": {
+            "files": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["path", "reason"],
+                    "properties": {
+                        "path": {"type": "string", "minLength": 1},
+                        "reason": {"type": "string"}
+                    }
+                }
+            }
+        }
+    })
+}
+
 /// Keyword search tool
 ///
 /// REQ-BASH-010: Stateless - uses `ToolContext` for `working_dir` and `llm_registry`
@@ -92,6 +126,13 @@ impl KeywordSearchTool {
             cmd.args(["-e", term]);
         }
 
+        // Workspace ignore rules (REQ-BED-050). ripgrep has no custom ignore
+        // file name, so the nearest file is passed explicitly; its patterns
+        // match relative to `dir`, the search root.
+        if let Some(ignore_file) = crate::phoenixignore::nearest(dir) {
+            cmd.arg("--ignore-file").arg(ignore_file);
+        }
+
         cmd.current_dir(dir)
             .stdin(Stdio::null())
             .stdout(Stdio::piped())
@@ -134,7 +175,7 @@ impl KeywordSearchTool {
         query: &str,
         search_root: &Path,
         results: &str,
-    ) -> Result<String, String> {
+    ) -> Result<Vec<RankedFile>, String> {
         let llm = Self::select_filter_llm(ctx).ok_or("No LLM available for filtering")?;
 
         let user_content = format!(
@@ -152,16 +193,55 @@ impl KeywordSearchTool {
             }],
             tools: vec![],
             max_tokens: Some(4096),
+            thinking_budget: None,
             // Shared by every keyword-search filter call so FILTER_SYSTEM_PROMPT caches.
             cache_key: PromptCacheKey::stable("keyword-search-filter"),
         };
 
-        let response = llm
-            .complete(&request)
+        let ranked: RankedFiles = complete_json(&*llm, request, &ranked_files_schema())
             .await
             .map_err(|e| format!("LLM filtering failed: {e}"))?;
+        Ok(ranked.files)
+    }
 
-        Ok(response.text())
+    /// Re-order the model's picks by recency, git, and conversation signals
+    /// (REQ-KWS-006), returning the tool output with the score breakdown.
+    async fn rank_files(
+        ctx: &ToolContext,
+        search_root: &Path,
+        files: Vec<RankedFile>,
+    ) -> ToolOutput {
+        if files.is_empty() {
+            return ToolOutput::success("No relevant files found");
+        }
+
+        let resolve = |p: &str| {
+            let path = PathBuf::from(p);
+            if path.is_absolute() {
+                path
+            } else {
+                search_root.join(path)
+            }
+        };
+        let signals = ranking::RankingSignals {
+            recent_commit_files: ranking::recent_commit_files(search_root).await,
+            referenced_files: ctx.referenced_files.iter().map(|p| resolve(p)).collect(),
+        };
+        let paths: Vec<PathBuf> = files.iter().map(|f| resolve(&f.path)).collect();
+        let scores = ranking::rank(&paths, &signals, SystemTime::now());
+
+        // `rank` reorders; look each file's reason back up by position
+        let output = scores
+            .iter()
+            .filter_map(|score| {
+                let i = paths
+                    .iter()
+                    .position(|p| p.display().to_string() == score.path)?;
+                Some(format!("{}: {}", files[i].path, files[i].reason))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        ToolOutput::success(output).with_display(json!({ "ranking": scores }))
     }
 }
 
@@ -262,7 +342,7 @@ IMPORTANT: Do NOT use this tool if you have precise information like log lines,
             .filter_with_llm(&ctx, &input.query, &search_root, &results)
             .await
         {
-            Ok(filtered) => ToolOutput::success(filtered),
+            Ok(files) => Self::rank_files(&ctx, &search_root, files).await,
             Err(e) => {
                 // If LLM fails, return raw results (truncated)
                 tracing::warn!(error = %e, "LLM filtering failed, returning raw results");
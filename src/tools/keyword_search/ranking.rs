@@ -0,0 +1,151 @@
+//! Re-ranking of filtered search results (REQ-KWS-006)
+//!
+//! The filter model orders files by relevance alone. Files the user is
+//! actively working on are more likely to be what the agent wants, so the
+//! model's order is blended with three cheap signals: how recently the file
+//! was modified, whether recent commits touched it, and whether it already
+//! came up in this conversation.
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::SystemTime;
+use tokio::process::Command;
+
+const RELEVANCE_WEIGHT: f64 = 1.0;
+const RECENCY_WEIGHT: f64 = 0.3;
+const GIT_WEIGHT: f64 = 0.2;
+const REFERENCED_WEIGHT: f64 = 0.25;
+
+/// Age at which the recency signal has halved
+const RECENCY_HALF_LIFE_HOURS: f64 = 72.0;
+
+/// How many commits back count as "recent"
+const RECENT_COMMITS: &str = "20";
+
+/// Everything outside the candidate list that feeds the score
+#[derive(Debug, Default)]
+pub struct RankingSignals {
+    /// Files changed in recent commits (absolute)
+    pub recent_commit_files: HashSet<PathBuf>,
+    /// Files touched by earlier tool calls in the conversation (absolute)
+    pub referenced_files: HashSet<PathBuf>,
+}
+
+/// Per-file score with its parts, surfaced in `display_data` for debugging
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreBreakdown {
+    pub path: String,
+    pub relevance: f64,
+    pub recency: f64,
+    pub git: f64,
+    pub referenced: f64,
+    pub total: f64,
+}
+
+/// Files changed in the last few commits under `root`. Empty outside a git
+/// repository or if git fails.
+pub async fn recent_commit_files(root: &Path) -> HashSet<PathBuf> {
+    let output = Command::new("git")
+        .args(["log", "-n", RECENT_COMMITS, "--name-only", "--format="])
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await;
+    match output {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| root.join(line))
+            .collect(),
+        _ => HashSet::new(),
+    }
+}
+
+/// 1.0 for a file modified just now, halving every
+/// `RECENCY_HALF_LIFE_HOURS`; 0.0 when the mtime is unavailable.
+fn recency(path: &Path, now: SystemTime) -> f64 {
+    let Ok(modified) = std::fs::metadata(path).and_then(|m| m.modified()) else {
+        return 0.0;
+    };
+    let age_hours = now
+        .duration_since(modified)
+        .map_or(0.0, |age| age.as_secs_f64() / 3600.0);
+    0.5_f64.powf(age_hours / RECENCY_HALF_LIFE_HOURS)
+}
+
+/// Order `paths` (the filter model's ranking, best first) by blended score.
+/// Ties keep the model's order.
+#[allow(clippy::cast_precision_loss)] // result lists are a few dozen files
+pub fn rank(paths: &[PathBuf], signals: &RankingSignals, now: SystemTime) -> Vec<ScoreBreakdown> {
+    let count = paths.len() as f64;
+    let mut scored: Vec<ScoreBreakdown> = paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            let relevance = 1.0 - i as f64 / count;
+            let recency = recency(path, now);
+            let git = f64::from(u8::from(signals.recent_commit_files.contains(path)));
+            let referenced = f64::from(u8::from(signals.referenced_files.contains(path)));
+            ScoreBreakdown {
+                path: path.display().to_string(),
+                relevance,
+                recency,
+                git,
+                referenced,
+                total: RELEVANCE_WEIGHT * relevance
+                    + RECENCY_WEIGHT * recency
+                    + GIT_WEIGHT * git
+                    + REFERENCED_WEIGHT * referenced,
+            }
+        })
+        .collect();
+    scored.sort_by(|a, b| b.total.total_cmp(&a.total));
+    scored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_model_order_kept_without_signals() {
+        let paths = vec![PathBuf::from("/nope/a"), PathBuf::from("/nope/b")];
+        let ranked = rank(&paths, &RankingSignals::default(), SystemTime::now());
+        assert_eq!(ranked[0].path, "/nope/a");
+        assert_eq!(ranked[1].path, "/nope/b");
+    }
+
+    #[test]
+    fn test_signals_can_reorder_close_results() {
+        let paths: Vec<PathBuf> = (0..5)
+            .map(|i| PathBuf::from(format!("/nope/{i}")))
+            .collect();
+        let signals = RankingSignals {
+            recent_commit_files: HashSet::from([paths[1].clone()]),
+            referenced_files: HashSet::from([paths[1].clone()]),
+        };
+        let ranked = rank(&paths, &signals, SystemTime::now());
+        assert_eq!(ranked[0].path, "/nope/1");
+        assert!((ranked[0].git - 1.0).abs() < f64::EPSILON);
+        assert!((ranked[0].referenced - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_recency_decays() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("f.txt");
+        std::fs::write(&path, "x").unwrap();
+
+        let fresh = recency(&path, SystemTime::now());
+        let later = SystemTime::now() + Duration::from_secs(72 * 3600);
+        let stale = recency(&path, later);
+        assert!(fresh > 0.99, "{fresh}");
+        assert!((stale - 0.5).abs() < 0.01, "{stale}");
+        assert!(recency(&dir.path().join("missing"), SystemTime::now()).abs() < f64::EPSILON);
+    }
+}
@@ -19,6 +19,7 @@ pub mod executor;
 pub mod interpreter;
 pub mod matching;
 pub mod planner;
+pub mod syntax;
 pub mod types;
 
 #[cfg(test)]
@@ -27,10 +28,12 @@ mod proptests;
 pub use planner::PatchPlanner;
 pub use types::*;
 
+use super::preflight::{self, Target};
 use super::{Tool, ToolContext, ToolOutput};
 use async_trait::async_trait;
 use executor::{execute_effects, read_file_content};
 use serde_json::{json, Value};
+use std::fmt::Write;
 use std::path::PathBuf;
 use std::sync::Mutex;
 
@@ -98,8 +101,14 @@ Recipes:
 - paste: replace with fromClipboard
 - in-place indentation change: same as copy, but add indentation adjustment
 
+Multiple files:
+- Instead of path and patches, pass files: [{path, patches}, ...] to edit several files at once
+- All files are planned before any is written; if one fails, none are changed
+- Use this for refactors that must not be left half-applied, e.g. renaming a function and its callers
+
 Usage notes:
 - All inputs are interpreted literally (no automatic newline or whitespace handling)
+- Exception: line breaks are converted to the file's existing line ending (LF or CRLF)
 - For replace operations, oldText must appear EXACTLY ONCE in the file
 
 IMPORTANT: Each patch call must be less than 60k tokens total. For large file
@@ -108,9 +117,49 @@ large overwrite. Prefer incremental replace operations over full file overwrites
     }
 
     fn input_schema(&self) -> Value {
+        let patch_item = json!({
+            "type": "object",
+            "required": ["operation"],
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["replace", "append_eof", "prepend_bof", "overwrite"],
+                    "description": "Type of operation to perform"
+                },
+                "oldText": {
+                    "type": "string",
+                    "description": "Text to locate (must be unique in file, required for replace)"
+                },
+                "newText": {
+                    "type": "string",
+                    "description": "The new text to use (empty for deletions, leave empty if fromClipboard is set)"
+                },
+                "toClipboard": {
+                    "type": "string",
+                    "description": "Save oldText to this named clipboard before the operation"
+                },
+                "fromClipboard": {
+                    "type": "string",
+                    "description": "Use content from this clipboard as newText (overrides newText field)"
+                },
+                "reindent": {
+                    "type": "object",
+                    "description": "Modify indentation of inserted text before insertion",
+                    "properties": {
+                        "strip": {
+                            "type": "string",
+                            "description": "Remove this prefix from each non-empty line"
+                        },
+                        "add": {
+                            "type": "string",
+                            "description": "Add this prefix to each non-empty line after stripping"
+                        }
+                    }
+                }
+            }
+        });
         json!({
             "type": "object",
-            "required": ["path", "patches"],
             "properties": {
                 "path": {
                     "type": "string",
@@ -119,44 +168,23 @@ large overwrite. Prefer incremental replace operations over full file overwrites
                 "patches": {
                     "type": "array",
                     "description": "List of patch requests to apply",
+                    "items": patch_item
+                },
+                "files": {
+                    "type": "array",
+                    "description": "Patch several files atomically instead of using path and patches",
                     "items": {
                         "type": "object",
-                        "required": ["operation"],
+                        "required": ["path", "patches"],
                         "properties": {
-                            "operation": {
-                                "type": "string",
-                                "enum": ["replace", "append_eof", "prepend_bof", "overwrite"],
-                                "description": "Type of operation to perform"
-                            },
-                            "oldText": {
-                                "type": "string",
-                                "description": "Text to locate (must be unique in file, required for replace)"
-                            },
-                            "newText": {
+                            "path": {
                                 "type": "string",
-                                "description": "The new text to use (empty for deletions, leave empty if fromClipboard is set)"
+                                "description": "Path to the file to patch"
                             },
-                            "toClipboard": {
-                                "type": "string",
-                                "description": "Save oldText to this named clipboard before the operation"
-                            },
-                            "fromClipboard": {
-                                "type": "string",
-                                "description": "Use content from this clipboard as newText (overrides newText field)"
-                            },
-                            "reindent": {
-                                "type": "object",
-                                "description": "Modify indentation of inserted text before insertion",
-                                "properties": {
-                                    "strip": {
-                                        "type": "string",
-                                        "description": "Remove this prefix from each non-empty line"
-                                    },
-                                    "add": {
-                                        "type": "string",
-                                        "description": "Add this prefix to each non-empty line after stripping"
-                                    }
-                                }
+                            "patches": {
+                                "type": "array",
+                                "description": "List of patch requests to apply to this file",
+                                "items": patch_item
                             }
                         }
                     }
@@ -165,6 +193,7 @@ large overwrite. Prefer incremental replace operations over full file overwrites
         })
     }
 
+    #[allow(clippy::too_many_lines)]
     async fn run(&self, input: Value, ctx: ToolContext) -> ToolOutput {
         // Check input size
         let input_str = input.to_string();
@@ -182,44 +211,162 @@ large overwrite. Prefer incremental replace operations over full file overwrites
             Err(e) => return ToolOutput::error(format!("Invalid input: {e}")),
         };
 
-        if patch_input.patches.is_empty() {
-            return ToolOutput::error("No patches provided");
+        let files = patch_input.into_files();
+        let multi_file = files.len() > 1;
+
+        // Resolve paths and read current content for every file up front
+        let mut targets = Vec::with_capacity(files.len());
+        for file in &files {
+            if file.path.is_empty() {
+                return ToolOutput::error("No path provided");
+            }
+            if file.patches.is_empty() {
+                return ToolOutput::error(if multi_file {
+                    format!("No patches provided for {}", file.path)
+                } else {
+                    "No patches provided".to_string()
+                });
+            }
+            let path = Self::resolve_path(&ctx, &file.path);
+            if targets.iter().any(|(p, _)| p == &path) {
+                return ToolOutput::error(format!(
+                    "{} appears more than once; combine its patches into one entry",
+                    file.path
+                ));
+            }
+            let current_content = match read_file_content(&path) {
+                Ok(content) => content,
+                Err(e) => return ToolOutput::error(format!("Failed to read {}: {e}", file.path)),
+            };
+            targets.push((path, current_content));
         }
 
-        // Resolve path
-        let path = Self::resolve_path(&ctx, &patch_input.path);
+        // Plan every file before anything is written (REQ-PATCH-012). The
+        // draft planner keeps clipboards unchanged if any file fails.
+        let plans = {
+            let mut planner = self.planner.lock().unwrap();
+            let mut draft = planner.clone();
+            let mut plans = Vec::with_capacity(files.len());
+            for (file, (path, content)) in files.iter().zip(&targets) {
+                let text = content.as_ref().map(|(text, _)| text.as_str());
+                match draft.plan(path, text, &file.patches) {
+                    Ok(plan) => {
+                        // REQ-PATCH-013: write back in the encoding we read
+                        let encoding = content.as_ref().map(|(_, e)| *e).unwrap_or_default();
+                        plans.push(plan.with_encoding(encoding));
+                    }
+                    Err(e) if multi_file => {
+                        return ToolOutput::error(format!(
+                            "{}: {e}. No files were changed.",
+                            file.path
+                        ));
+                    }
+                    Err(e) => return ToolOutput::error(e.to_string()),
+                }
+            }
+            *planner = draft;
+            plans
+        };
 
-        // Read current content
-        let current_content = match read_file_content(&path) {
-            Ok(content) => content,
-            Err(e) => return ToolOutput::error(format!("Failed to read file: {e}")),
+        let staged = StagedPatch {
+            path: targets
+                .iter()
+                .map(|(path, _)| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            diff: plans.iter().map(|plan| plan.diff.as_str()).collect(),
+            autogenerated_warning: plans.iter().any(|plan| plan.autogenerated_warning),
+            converted_line_endings: targets
+                .iter()
+                .zip(&plans)
+                .filter_map(|((path, _), plan)| {
+                    let ending = plan.converted_line_ending?;
+                    Some((path.display().to_string(), ending))
+                })
+                .collect(),
+            effects: plans.into_iter().flat_map(|plan| plan.effects).collect(),
         };
 
-        // Plan patches
-        let plan = {
-            let mut planner = self.planner.lock().unwrap();
-            match planner.plan(&path, current_content.as_deref(), &patch_input.patches) {
-                Ok(plan) => plan,
-                Err(e) => return ToolOutput::error(e.to_string()),
+        // REQ-PATCH-014: the edit is made either way, but the agent hears
+        // when it lands on uncommitted changes it did not make
+        let paths: Vec<PathBuf> = targets.iter().map(|(path, _)| path.clone()).collect();
+        let target = Target::Files(&paths);
+        let dirty = preflight::unrelated_changes(&ctx.working_dir, target, &ctx.edited_files).await;
+        let dirty_warning = (!dirty.is_empty()).then(|| {
+            format!(
+                "\n<warning>{} already had uncommitted changes this conversation did not make, \
+                 likely the user's. They were kept; tell the user their file was edited.</warning>",
+                dirty.join(", ")
+            )
+        });
+
+        // REQ-PATCH-010: hold the edit for the user instead of writing it
+        if ctx.review_patches {
+            let display_data = json!({
+                "path": staged.path,
+                "diff": staged.diff,
+                "staged": true
+            });
+            let mut output = "<patches_staged>awaiting user review</patches_staged>".to_string();
+            if let Some(warning) = &dirty_warning {
+                output.push_str(warning);
             }
-        };
+            return ToolOutput::success(output)
+                .with_display(display_data)
+                .with_staged_patch(staged);
+        }
 
-        // Execute effects
-        if let Err(e) = execute_effects(&plan.effects) {
-            return ToolOutput::error(format!("Failed to write file: {e}"));
+        let mut output = staged.apply().await;
+        if output.success {
+            if let Some(warning) = &dirty_warning {
+                output.output.push_str(warning);
+            }
+        }
+        output
+    }
+}
+
+impl StagedPatch {
+    /// Write the planned effects and report the result as the tool would,
+    /// including any syntax problems in the written files (REQ-PATCH-011).
+    pub async fn apply(&self) -> ToolOutput {
+        if let Err(e) = execute_effects(&self.effects) {
+            let outcome = if e.unrestored.is_empty() {
+                "No files were changed.".to_string()
+            } else {
+                let paths: Vec<_> = e
+                    .unrestored
+                    .iter()
+                    .map(|p| p.display().to_string())
+                    .collect();
+                format!("Could not restore: {}", paths.join(", "))
+            };
+            return ToolOutput::error(format!("Failed to write {e}. {outcome}"));
         }
 
-        // Build output
         let mut output = "<patches_applied>all</patches_applied>".to_string();
-        if plan.autogenerated_warning {
+        if self.autogenerated_warning {
             output.push_str(
                 "\n<warning>This file appears to be auto-generated. Edits may be overwritten.</warning>",
             );
         }
+        for (path, ending) in &self.converted_line_endings {
+            let _ = write!(
+                output,
+                "\n<warning>Line endings in the patch text for {path} were converted to {ending} \
+                 to match the file.</warning>"
+            );
+        }
+        for effect in &self.effects {
+            let PatchEffect::WriteFile { path, .. } = effect;
+            if let Some(report) = syntax::validate(path).await {
+                output.push_str(&report);
+            }
+        }
 
         let display_data = json!({
-            "path": path.display().to_string(),
-            "diff": plan.diff
+            "path": self.path,
+            "diff": self.diff
         });
 
         ToolOutput::success(output).with_display(display_data)
@@ -278,6 +425,260 @@ mod tests {
         assert_eq!(fs::read_to_string(&test_file).unwrap(), "Hello Rust");
     }
 
+    #[tokio::test]
+    async fn test_review_mode_stages_without_writing() {
+        let dir = tempdir().unwrap();
+        let tool = PatchTool::default();
+        let ctx = test_context(dir.path().to_path_buf()).with_patch_review(true);
+
+        let test_file = dir.path().join("test.txt");
+        fs::write(&test_file, "Hello World").unwrap();
+
+        let result = tool
+            .run(
+                json!({
+                    "path": "test.txt",
+                    "patches": [{
+                        "operation": "replace",
+                        "oldText": "World",
+                        "newText": "Rust"
+                    }]
+                }),
+                ctx,
+            )
+            .await;
+
+        assert!(result.success, "Error: {}", result.output);
+        assert_eq!(fs::read_to_string(&test_file).unwrap(), "Hello World");
+
+        let staged = result.staged_patch.expect("patch should be staged");
+        assert!(staged.diff.contains("+Hello Rust"));
+        assert!(staged.apply().await.success);
+        assert_eq!(fs::read_to_string(&test_file).unwrap(), "Hello Rust");
+    }
+
+    #[tokio::test]
+    async fn test_syntax_errors_reported_after_write() {
+        let dir = tempdir().unwrap();
+        let tool = PatchTool::default();
+        let ctx = test_context(dir.path().to_path_buf());
+
+        let test_file = dir.path().join("lib.rs");
+        fs::write(&test_file, "fn main() {\n    let x = 1;\n}\n").unwrap();
+
+        let result = tool
+            .run(
+                json!({
+                    "path": "lib.rs",
+                    "patches": [{
+                        "operation": "replace",
+                        "oldText": "let x = 1;",
+                        "newText": "let x = ;"
+                    }]
+                }),
+                ctx,
+            )
+            .await;
+
+        assert!(result.success, "Error: {}", result.output);
+        assert!(
+            result.output.contains("<syntax_errors path="),
+            "{}",
+            result.output
+        );
+        assert!(result.output.contains("line 2"), "{}", result.output);
+    }
+
+    #[tokio::test]
+    async fn test_multi_file_patch_applies_all() {
+        let dir = tempdir().unwrap();
+        let tool = PatchTool::default();
+        let ctx = test_context(dir.path().to_path_buf());
+
+        fs::write(dir.path().join("a.txt"), "fn old_name").unwrap();
+        fs::write(dir.path().join("b.txt"), "call old_name").unwrap();
+
+        let result = tool
+            .run(
+                json!({
+                    "files": [
+                        {
+                            "path": "a.txt",
+                            "patches": [{
+                                "operation": "replace",
+                                "oldText": "old_name",
+                                "newText": "new_name"
+                            }]
+                        },
+                        {
+                            "path": "b.txt",
+                            "patches": [{
+                                "operation": "replace",
+                                "oldText": "old_name",
+                                "newText": "new_name"
+                            }]
+                        }
+                    ]
+                }),
+                ctx,
+            )
+            .await;
+
+        assert!(result.success, "Error: {}", result.output);
+        let display = result.display_data.unwrap();
+        let diff = display["diff"].as_str().unwrap();
+        assert!(diff.contains("a.txt") && diff.contains("b.txt"), "{diff}");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "fn new_name"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("b.txt")).unwrap(),
+            "call new_name"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_multi_file_patch_plan_failure_changes_nothing() {
+        let dir = tempdir().unwrap();
+        let tool = PatchTool::default();
+        let ctx = test_context(dir.path().to_path_buf());
+
+        fs::write(dir.path().join("a.txt"), "fn old_name").unwrap();
+        fs::write(dir.path().join("b.txt"), "call something_else").unwrap();
+
+        let result = tool
+            .run(
+                json!({
+                    "files": [
+                        {
+                            "path": "a.txt",
+                            "patches": [{
+                                "operation": "replace",
+                                "oldText": "old_name",
+                                "newText": "new_name",
+                                "toClipboard": "clip"
+                            }]
+                        },
+                        {
+                            "path": "b.txt",
+                            "patches": [{
+                                "operation": "replace",
+                                "oldText": "old_name",
+                                "newText": "new_name"
+                            }]
+                        }
+                    ]
+                }),
+                ctx,
+            )
+            .await;
+
+        assert!(!result.success);
+        assert!(result.output.starts_with("b.txt:"), "{}", result.output);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "fn old_name"
+        );
+        assert!(tool.planner.lock().unwrap().clipboards().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_multi_file_patch_rejects_duplicate_paths() {
+        let dir = tempdir().unwrap();
+        let tool = PatchTool::default();
+        let ctx = test_context(dir.path().to_path_buf());
+
+        let overwrite = json!([{ "operation": "overwrite", "newText": "x" }]);
+        let result = tool
+            .run(
+                json!({
+                    "files": [
+                        { "path": "a.txt", "patches": overwrite },
+                        { "path": "a.txt", "patches": overwrite }
+                    ]
+                }),
+                ctx,
+            )
+            .await;
+
+        assert!(!result.success);
+        assert!(
+            result.output.contains("more than once"),
+            "{}",
+            result.output
+        );
+        assert!(!dir.path().join("a.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_crlf_file_keeps_crlf() {
+        let dir = tempdir().unwrap();
+        let tool = PatchTool::default();
+        let ctx = test_context(dir.path().to_path_buf());
+
+        let test_file = dir.path().join("test.txt");
+        fs::write(&test_file, "one\r\ntwo\r\nthree\r\n").unwrap();
+
+        let result = tool
+            .run(
+                json!({
+                    "path": "test.txt",
+                    "patches": [{
+                        "operation": "replace",
+                        "oldText": "one\ntwo\n",
+                        "newText": "one\n2\n"
+                    }]
+                }),
+                ctx,
+            )
+            .await;
+
+        assert!(result.success, "Error: {}", result.output);
+        assert!(
+            result.output.contains("converted to CRLF"),
+            "{}",
+            result.output
+        );
+        assert_eq!(
+            fs::read_to_string(&test_file).unwrap(),
+            "one\r\n2\r\nthree\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bom_and_utf16_preserved() {
+        let dir = tempdir().unwrap();
+        let tool = PatchTool::default();
+
+        let bom_file = dir.path().join("bom.txt");
+        fs::write(&bom_file, b"\xEF\xBB\xBFHello World").unwrap();
+        let utf16_file = dir.path().join("utf16.txt");
+        fs::write(&utf16_file, TextEncoding::Utf16Le.encode("Hello World")).unwrap();
+
+        for name in ["bom.txt", "utf16.txt"] {
+            let result = tool
+                .run(
+                    json!({
+                        "path": name,
+                        "patches": [{
+                            "operation": "prepend_bof",
+                            "newText": ">> "
+                        }]
+                    }),
+                    test_context(dir.path().to_path_buf()),
+                )
+                .await;
+            assert!(result.success, "Error: {}", result.output);
+        }
+
+        assert_eq!(fs::read(&bom_file).unwrap(), b"\xEF\xBB\xBF>> Hello World");
+        assert_eq!(
+            fs::read(&utf16_file).unwrap(),
+            TextEncoding::Utf16Le.encode(">> Hello World")
+        );
+    }
+
     #[tokio::test]
     async fn test_overwrite_creates_file() {
         let dir = tempdir().unwrap();
@@ -345,4 +746,47 @@ mod tests {
 
         assert_eq!(fs::read_to_string(&test_file).unwrap(), "AAA  BBB");
     }
+
+    #[tokio::test]
+    async fn patching_a_file_with_unrelated_changes_warns() {
+        // REQ-PATCH-014
+        let dir = tempdir().unwrap();
+        let setup = "git init -q && echo one > notes.txt && git add . && \
+            git -c user.name=t -c user.email=t@example.com -c commit.gpgsign=false \
+            commit -qm init && echo two > notes.txt";
+        let status = std::process::Command::new("sh")
+            .args(["-c", setup])
+            .current_dir(dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let tool = PatchTool::default();
+        let input = json!({
+            "path": "notes.txt",
+            "patches": [{"operation": "replace", "oldText": "two", "newText": "three"}]
+        });
+        let result = tool
+            .run(input.clone(), test_context(dir.path().to_path_buf()))
+            .await;
+        assert!(result.success, "{}", result.output);
+        assert!(result
+            .output
+            .contains("notes.txt already had uncommitted changes"));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("notes.txt")).unwrap(),
+            "three\n"
+        );
+
+        // Once the conversation has edited the file, its changes are its own
+        let edited = vec![dir.path().join("notes.txt").display().to_string()];
+        let ctx = test_context(dir.path().to_path_buf()).with_edited_files(edited);
+        let input = json!({
+            "path": "notes.txt",
+            "patches": [{"operation": "replace", "oldText": "three", "newText": "four"}]
+        });
+        let result = tool.run(input, ctx).await;
+        assert!(result.success, "{}", result.output);
+        assert!(!result.output.contains("<warning>"), "{}", result.output);
+    }
 }
@@ -1,37 +1,230 @@
 //! Effect execution for real filesystem operations
+//!
+//! The writes of one call form a transaction (REQ-PATCH-012): each new
+//! content is first written to a temp file beside its target, and only once
+//! every temp file exists are they renamed into place. If any step fails,
+//! files already replaced are restored and new directories removed, so either
+//! every file changes or none does.
 
-use super::types::PatchEffect;
+use super::types::{PatchEffect, TextEncoding};
 use std::fs;
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-/// Execute patch effects against the real filesystem
-pub fn execute_effects(effects: &[PatchEffect]) -> Result<(), io::Error> {
+/// A failed transaction, naming the file whose write failed
+#[derive(Debug, thiserror::Error)]
+#[error("{}: {source}", .path.display())]
+pub struct WriteError {
+    pub path: PathBuf,
+    #[source]
+    pub source: io::Error,
+    /// Files that could not be put back after the failure; empty when the
+    /// rollback restored everything.
+    pub unrestored: Vec<PathBuf>,
+}
+
+/// A write whose new content sits in a temp file, not yet renamed into place
+struct PendingWrite {
+    target: PathBuf,
+    temp: PathBuf,
+    /// Content before the write; `None` if the file did not exist
+    original: Option<Vec<u8>>,
+}
+
+impl PendingWrite {
+    fn restore(&self) -> io::Result<()> {
+        match &self.original {
+            Some(bytes) => fs::write(&self.target, bytes),
+            None => fs::remove_file(&self.target),
+        }
+    }
+}
+
+/// Execute patch effects against the real filesystem, all or nothing
+pub fn execute_effects(effects: &[PatchEffect]) -> Result<(), WriteError> {
+    let mut created_dirs = Vec::new();
+    let mut pending = Vec::with_capacity(effects.len());
+
+    // Phase 1: write every new content beside its target
     for effect in effects {
-        match effect {
-            PatchEffect::WriteFile { path, content } => {
-                // Create parent directories if needed
-                if let Some(parent) = path.parent() {
-                    if !parent.as_os_str().is_empty() && !parent.exists() {
-                        fs::create_dir_all(parent)?;
-                    }
-                }
-                fs::write(path, content)?;
+        let PatchEffect::WriteFile {
+            path,
+            content,
+            encoding,
+        } = effect;
+        match prepare_write(path, &encoding.encode(content), &mut created_dirs) {
+            Ok(write) => pending.push(write),
+            Err(source) => {
+                discard_temps(&pending);
+                remove_dirs(&created_dirs);
+                return Err(WriteError {
+                    path: path.clone(),
+                    source,
+                    unrestored: Vec::new(),
+                });
             }
         }
     }
+
+    // Phase 2: move them into place, undoing earlier renames on failure
+    for (i, write) in pending.iter().enumerate() {
+        if let Err(source) = fs::rename(&write.temp, &write.target) {
+            let unrestored: Vec<PathBuf> = pending[..i]
+                .iter()
+                .rev()
+                .filter(|done| done.restore().is_err())
+                .map(|done| done.target.clone())
+                .collect();
+            discard_temps(&pending[i..]);
+            if unrestored.is_empty() {
+                remove_dirs(&created_dirs);
+            }
+            return Err(WriteError {
+                path: write.target.clone(),
+                source,
+                unrestored,
+            });
+        }
+    }
     Ok(())
 }
 
+fn prepare_write(
+    path: &Path,
+    content: &[u8],
+    created_dirs: &mut Vec<PathBuf>,
+) -> io::Result<PendingWrite> {
+    // Create parent directories if needed, remembering which are new
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            let mut missing: Vec<PathBuf> = parent
+                .ancestors()
+                .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+                .map(Path::to_path_buf)
+                .collect();
+            fs::create_dir_all(parent)?;
+            missing.reverse();
+            created_dirs.extend(missing);
+        }
+    }
+
+    // Renaming over a symlink would replace the link; write its target instead
+    let target = if fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink()) {
+        fs::canonicalize(path)?
+    } else {
+        path.to_path_buf()
+    };
+
+    let original = match fs::read(&target) {
+        Ok(bytes) => Some(bytes),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
+    };
+
+    let temp = temp_path(&target);
+    let written = fs::write(&temp, content).and_then(|()| {
+        if original.is_some() {
+            fs::set_permissions(&temp, fs::metadata(&target)?.permissions())?;
+        }
+        Ok(())
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+
+    Ok(PendingWrite {
+        target,
+        temp,
+        original,
+    })
+}
+
+/// Hidden sibling of `target`, so the final rename stays on one filesystem
+fn temp_path(target: &Path) -> PathBuf {
+    let name = target
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    target.with_file_name(format!(".{name}.{}.phoenix-tmp", uuid::Uuid::new_v4()))
+}
+
+fn discard_temps(writes: &[PendingWrite]) {
+    for write in writes {
+        let _ = fs::remove_file(&write.temp);
+    }
+}
+
+/// Remove directories created for the transaction, deepest first. Only
+/// empty directories are removed.
+fn remove_dirs(created: &[PathBuf]) {
+    for dir in created.iter().rev() {
+        let _ = fs::remove_dir(dir);
+    }
+}
+
 /// Read file content, returning None if file doesn't exist
-pub fn read_file_content(path: &Path) -> Result<Option<String>, io::Error> {
-    match fs::read_to_string(path) {
-        Ok(content) => Ok(Some(content)),
+///
+/// The content comes back decoded, without any byte-order mark, together
+/// with the encoding to write it back in (REQ-PATCH-013).
+pub fn read_file_content(path: &Path) -> Result<Option<(String, TextEncoding)>, io::Error> {
+    match fs::read(path) {
+        Ok(bytes) => TextEncoding::decode(bytes).map(Some),
         Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
         Err(e) => Err(e),
     }
 }
 
+impl TextEncoding {
+    const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+    const UTF16_LE_BOM: [u8; 2] = [0xFF, 0xFE];
+    const UTF16_BE_BOM: [u8; 2] = [0xFE, 0xFF];
+
+    /// Decode file bytes, telling the encoding from a byte-order mark.
+    /// Files without one must be UTF-8.
+    pub fn decode(bytes: Vec<u8>) -> Result<(String, Self), io::Error> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+        if let Some(rest) = bytes.strip_prefix(&Self::UTF8_BOM) {
+            let text = String::from_utf8(rest.to_vec()).map_err(|e| invalid(e.to_string()))?;
+            return Ok((text, Self::Utf8Bom));
+        }
+        if let Some(rest) = bytes.strip_prefix(&Self::UTF16_LE_BOM) {
+            return Ok((decode_utf16(rest, u16::from_le_bytes)?, Self::Utf16Le));
+        }
+        if let Some(rest) = bytes.strip_prefix(&Self::UTF16_BE_BOM) {
+            return Ok((decode_utf16(rest, u16::from_be_bytes)?, Self::Utf16Be));
+        }
+        let text = String::from_utf8(bytes).map_err(|e| invalid(e.to_string()))?;
+        Ok((text, Self::Utf8))
+    }
+
+    /// Encode text for writing, with this encoding's byte-order mark.
+    pub fn encode(self, text: &str) -> Vec<u8> {
+        match self {
+            Self::Utf8 => text.as_bytes().to_vec(),
+            Self::Utf8Bom => [&Self::UTF8_BOM[..], text.as_bytes()].concat(),
+            Self::Utf16Le => Self::UTF16_LE_BOM
+                .into_iter()
+                .chain(text.encode_utf16().flat_map(u16::to_le_bytes))
+                .collect(),
+            Self::Utf16Be => Self::UTF16_BE_BOM
+                .into_iter()
+                .chain(text.encode_utf16().flat_map(u16::to_be_bytes))
+                .collect(),
+        }
+    }
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Result<String, io::Error> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let chunks = bytes.chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return Err(invalid("UTF-16 file has an odd number of bytes"));
+    }
+    let units: Vec<u16> = chunks.map(|pair| unit([pair[0], pair[1]])).collect();
+    String::from_utf16(&units).map_err(|_| invalid("invalid UTF-16 in file"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -45,6 +238,7 @@ mod tests {
         execute_effects(&[PatchEffect::WriteFile {
             path: path.clone(),
             content: "hello world".to_string(),
+            encoding: TextEncoding::default(),
         }])
         .unwrap();
 
@@ -59,12 +253,105 @@ mod tests {
         execute_effects(&[PatchEffect::WriteFile {
             path: path.clone(),
             content: "nested".to_string(),
+            encoding: TextEncoding::default(),
         }])
         .unwrap();
 
         assert_eq!(fs::read_to_string(&path).unwrap(), "nested");
     }
 
+    #[test]
+    fn test_writes_all_files() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("sub/b.txt");
+        fs::write(&a, "old a").unwrap();
+
+        execute_effects(&[
+            PatchEffect::WriteFile {
+                path: a.clone(),
+                content: "new a".to_string(),
+                encoding: TextEncoding::default(),
+            },
+            PatchEffect::WriteFile {
+                path: b.clone(),
+                content: "new b".to_string(),
+                encoding: TextEncoding::default(),
+            },
+        ])
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(&a).unwrap(), "new a");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "new b");
+        let leftovers: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".phoenix-tmp"))
+            .collect();
+        assert!(
+            leftovers.is_empty(),
+            "temp files left behind: {leftovers:?}"
+        );
+    }
+
+    #[test]
+    fn test_failed_write_changes_nothing() {
+        let dir = tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        fs::write(&a, "old a").unwrap();
+        // A regular file where a directory is needed makes the second write fail
+        fs::write(dir.path().join("blocker"), "").unwrap();
+        let b = dir.path().join("blocker/b.txt");
+        let c = dir.path().join("new_dir/c.txt");
+
+        let err = execute_effects(&[
+            PatchEffect::WriteFile {
+                path: a.clone(),
+                content: "new a".to_string(),
+                encoding: TextEncoding::default(),
+            },
+            PatchEffect::WriteFile {
+                path: c.clone(),
+                content: "new c".to_string(),
+                encoding: TextEncoding::default(),
+            },
+            PatchEffect::WriteFile {
+                path: b.clone(),
+                content: "new b".to_string(),
+                encoding: TextEncoding::default(),
+            },
+        ])
+        .unwrap_err();
+
+        assert_eq!(err.path, b);
+        assert!(err.unrestored.is_empty());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "old a");
+        assert!(!c.exists());
+        assert!(!dir.path().join("new_dir").exists());
+    }
+
+    #[test]
+    fn test_write_through_symlink_keeps_link() {
+        let dir = tempdir().unwrap();
+        let real = dir.path().join("real.txt");
+        let link = dir.path().join("link.txt");
+        fs::write(&real, "old").unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        execute_effects(&[PatchEffect::WriteFile {
+            path: link.clone(),
+            content: "new".to_string(),
+            encoding: TextEncoding::default(),
+        }])
+        .unwrap();
+
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&real).unwrap(), "new");
+    }
+
     #[test]
     fn test_read_existing_file() {
         let dir = tempdir().unwrap();
@@ -72,7 +359,55 @@ mod tests {
         fs::write(&path, "content").unwrap();
 
         let content = read_file_content(&path).unwrap();
-        assert_eq!(content, Some("content".to_string()));
+        assert_eq!(content, Some(("content".to_string(), TextEncoding::Utf8)));
+    }
+
+    #[test]
+    fn test_encodings_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.txt");
+
+        for encoding in [
+            TextEncoding::Utf8,
+            TextEncoding::Utf8Bom,
+            TextEncoding::Utf16Le,
+            TextEncoding::Utf16Be,
+        ] {
+            execute_effects(&[PatchEffect::WriteFile {
+                path: path.clone(),
+                content: "héllo\r\n".to_string(),
+                encoding,
+            }])
+            .unwrap();
+
+            let content = read_file_content(&path).unwrap();
+            assert_eq!(content, Some(("héllo\r\n".to_string(), encoding)));
+        }
+    }
+
+    #[test]
+    fn test_bom_is_kept_on_disk() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.txt");
+
+        execute_effects(&[PatchEffect::WriteFile {
+            path: path.clone(),
+            content: "x".to_string(),
+            encoding: TextEncoding::Utf8Bom,
+        }])
+        .unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"\xEF\xBB\xBFx");
+    }
+
+    #[test]
+    fn test_read_rejects_odd_utf16() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("test.txt");
+        fs::write(&path, [0xFF, 0xFE, b'a']).unwrap();
+
+        let err = read_file_content(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
     }
 
     #[test]
@@ -57,7 +57,7 @@ impl VirtualFs {
     pub fn interpret(&mut self, effects: &[PatchEffect]) {
         for effect in effects {
             match effect {
-                PatchEffect::WriteFile { path, content } => {
+                PatchEffect::WriteFile { path, content, .. } => {
                     self.files.insert(path.clone(), content.clone());
                 }
             }
@@ -78,6 +78,7 @@ pub fn interpret_effects(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tools::patch::types::TextEncoding;
 
     fn path(s: &str) -> PathBuf {
         PathBuf::from(s)
@@ -89,6 +90,7 @@ mod tests {
         fs.interpret(&[PatchEffect::WriteFile {
             path: path("test.txt"),
             content: "hello".to_string(),
+            encoding: TextEncoding::default(),
         }]);
 
         assert_eq!(fs.get(&path("test.txt")), Some(&"hello".to_string()));
@@ -101,6 +103,7 @@ mod tests {
         fs.interpret(&[PatchEffect::WriteFile {
             path: path("test.txt"),
             content: "new".to_string(),
+            encoding: TextEncoding::default(),
         }]);
 
         assert_eq!(fs.get(&path("test.txt")), Some(&"new".to_string()));
@@ -116,10 +119,12 @@ mod tests {
                 PatchEffect::WriteFile {
                     path: path("a.txt"),
                     content: "AAA".to_string(),
+                    encoding: TextEncoding::default(),
                 },
                 PatchEffect::WriteFile {
                     path: path("b.txt"),
                     content: "BBB".to_string(),
+                    encoding: TextEncoding::default(),
                 },
             ],
         );
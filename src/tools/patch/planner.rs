@@ -5,8 +5,12 @@
 //! for property-based testing.
 
 use super::matching::find_unique_match;
-use super::types::{Edit, Operation, PatchEffect, PatchError, PatchPlan, PatchRequest, Reindent};
+use super::types::{
+    Edit, LineEnding, Operation, PatchEffect, PatchError, PatchPlan, PatchRequest, Reindent,
+    TextEncoding,
+};
 use similar::TextDiff;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -67,8 +71,14 @@ impl PatchPlanner {
             String::new()
         };
 
+        // Patch text follows the file's line endings, not the model's
+        // (REQ-PATCH-013). New files and files without breaks have none.
+        let line_ending = current_content
+            .filter(|content| content.contains('\n'))
+            .map(LineEnding::detect);
+
         // Build edit list
-        let edits = self.build_edits(&original, patches)?;
+        let (edits, converted) = self.build_edits(&original, patches, line_ending)?;
 
         // Apply edits to get result
         let resulting_content = Self::apply_edits(&original, edits)?;
@@ -83,6 +93,7 @@ impl PatchPlanner {
         let effects = vec![PatchEffect::WriteFile {
             path: path.to_path_buf(),
             content: resulting_content.clone(),
+            encoding: TextEncoding::default(),
         }];
 
         Ok(PatchPlan {
@@ -90,16 +101,22 @@ impl PatchPlanner {
             resulting_content,
             diff,
             autogenerated_warning,
+            converted_line_ending: line_ending.filter(|_| converted),
         })
     }
 
     /// Build a list of edits from patch requests
+    ///
+    /// Also reports whether any patch text had to be converted to
+    /// `line_ending`.
     fn build_edits(
         &mut self,
         original: &str,
         patches: &[PatchRequest],
-    ) -> Result<Vec<Edit>, PatchError> {
+        line_ending: Option<LineEnding>,
+    ) -> Result<(Vec<Edit>, bool), PatchError> {
         let mut edits = Vec::new();
+        let mut converted = false;
 
         for patch in patches {
             // Get new text (from clipboard if specified)
@@ -118,6 +135,13 @@ impl PatchPlanner {
                 new_text = apply_reindent(&new_text, reindent)?;
             }
 
+            if let Some(ending) = line_ending {
+                if let Cow::Owned(text) = ending.convert(&new_text) {
+                    new_text = text;
+                    converted = true;
+                }
+            }
+
             // Store to clipboard if requested (ignore empty string names)
             if let Some(name) = &patch.to_clipboard {
                 if !name.is_empty() {
@@ -147,7 +171,15 @@ impl PatchPlanner {
                 Operation::Replace => {
                     let old_text = patch.old_text.as_ref().ok_or(PatchError::MissingOldText)?;
 
-                    let spec = find_unique_match(original, old_text)?;
+                    // Match in the file's line endings, falling back to the
+                    // text as given for files that mix them
+                    let spec = match line_ending.map(|ending| ending.convert(old_text)) {
+                        Some(Cow::Owned(converted_old)) => {
+                            find_unique_match(original, &converted_old)
+                                .or_else(|e| find_unique_match(original, old_text).map_err(|_| e))?
+                        }
+                        _ => find_unique_match(original, old_text)?,
+                    };
 
                     // Update clipboard with actual matched text if it differed
                     if let Some(name) = &patch.to_clipboard {
@@ -171,7 +203,7 @@ impl PatchPlanner {
             edits.push(edit);
         }
 
-        Ok(edits)
+        Ok((edits, converted))
     }
 
     /// Apply edits to content (in reverse order to maintain offsets)
@@ -303,7 +335,7 @@ mod tests {
         assert_eq!(plan.resulting_content, "hello world");
         assert_eq!(plan.effects.len(), 1);
         match &plan.effects[0] {
-            PatchEffect::WriteFile { path, content } => {
+            PatchEffect::WriteFile { path, content, .. } => {
                 assert_eq!(path, &PathBuf::from("test.txt"));
                 assert_eq!(content, "hello world");
             }
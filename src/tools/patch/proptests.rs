@@ -569,7 +569,9 @@ fn test_overwrite_then_replace_with_filesystem() {
     let mut planner = PatchPlanner::new();
 
     // Step 1: Overwrite to create the file with "Hello World"
-    let current_content = read_file_content(&test_file).expect("read should succeed");
+    let current_content = read_file_content(&test_file)
+        .expect("read should succeed")
+        .map(|(text, _)| text);
     assert!(current_content.is_none(), "file should not exist yet");
 
     let plan1 = planner
@@ -596,7 +598,9 @@ fn test_overwrite_then_replace_with_filesystem() {
 
     // Step 2: Replace "Hello World" with "Hello Phoenix IDE"
     // This is where the original bug occurred!
-    let current_content = read_file_content(&test_file).expect("read should succeed");
+    let current_content = read_file_content(&test_file)
+        .expect("read should succeed")
+        .map(|(text, _)| text);
     assert_eq!(current_content.as_deref(), Some("Hello World"));
 
     let plan2 = planner
@@ -0,0 +1,286 @@
+//! Post-write syntax validation (REQ-PATCH-011)
+//!
+//! After a patch is written, the file is re-parsed with tree-sitter when its
+//! extension names a language we know, so the model hears at once that an
+//! edit left the file unparseable. With `PHOENIX_PATCH_CARGO_CHECK=1`, edits
+//! to Rust files also run `cargo check` in the enclosing package.
+
+use super::executor::read_file_content;
+use serde_json::Value;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+use tree_sitter::{Language, Node, Parser};
+
+/// Cap on reported problems per check; the first few are what matter.
+const MAX_REPORTED: usize = 10;
+
+/// Longest source excerpt quoted in a syntax error message, in chars.
+const MAX_SNIPPET_CHARS: usize = 40;
+
+const CARGO_CHECK_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A parse error located in the patched file (1-based line and column).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// The tree-sitter grammar for `path`'s extension. Also picks where large
+/// files are chunked (REQ-FE-012).
+pub(crate) fn language_for(path: &Path) -> Option<Language> {
+    let language = match path.extension()?.to_str()? {
+        "rs" => tree_sitter_rust::LANGUAGE,
+        "py" | "pyi" => tree_sitter_python::LANGUAGE,
+        "js" | "mjs" | "cjs" | "jsx" => tree_sitter_javascript::LANGUAGE,
+        "ts" | "mts" | "cts" => tree_sitter_typescript::LANGUAGE_TYPESCRIPT,
+        "tsx" => tree_sitter_typescript::LANGUAGE_TSX,
+        "go" => tree_sitter_go::LANGUAGE,
+        "json" => tree_sitter_json::LANGUAGE,
+        _ => return None,
+    };
+    Some(language.into())
+}
+
+/// Parse `content` as the language implied by `path`. Returns no errors for
+/// unknown extensions.
+pub fn check_syntax(path: &Path, content: &str) -> Vec<SyntaxError> {
+    let Some(language) = language_for(path) else {
+        return Vec::new();
+    };
+    let mut parser = Parser::new();
+    if parser.set_language(&language).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(content, None) else {
+        return Vec::new();
+    };
+
+    let mut errors = Vec::new();
+    collect_errors(tree.root_node(), content, &mut errors);
+    errors
+}
+
+fn collect_errors(node: Node<'_>, source: &str, out: &mut Vec<SyntaxError>) {
+    if out.len() >= MAX_REPORTED {
+        return;
+    }
+    if node.is_missing() {
+        out.push(located(node, format!("missing `{}`", node.kind())));
+        return;
+    }
+    if node.is_error() {
+        let text = node.utf8_text(source.as_bytes()).unwrap_or_default();
+        let snippet: String = text
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .chars()
+            .take(MAX_SNIPPET_CHARS)
+            .collect();
+        let message = if snippet.is_empty() {
+            "syntax error".to_string()
+        } else {
+            format!("syntax error near `{snippet}`")
+        };
+        out.push(located(node, message));
+        return;
+    }
+    if !node.has_error() {
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_errors(child, source, out);
+    }
+}
+
+fn located(node: Node<'_>, message: String) -> SyntaxError {
+    let start = node.start_position();
+    SyntaxError {
+        line: start.row + 1,
+        column: start.column + 1,
+        message,
+    }
+}
+
+fn cargo_check_enabled() -> bool {
+    std::env::var("PHOENIX_PATCH_CARGO_CHECK").is_ok_and(|v| v == "1")
+}
+
+/// Run `cargo check` for the package containing `path` and collect compiler
+/// errors. `None` when disabled, not a Rust file, outside a package, or when
+/// cargo could not be run to completion.
+pub async fn cargo_check(path: &Path) -> Option<Vec<String>> {
+    if !cargo_check_enabled() || path.extension().is_none_or(|e| e != "rs") {
+        return None;
+    }
+    let package_dir = path
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())?;
+
+    let run = tokio::process::Command::new("cargo")
+        .args(["check", "--message-format=json", "--quiet"])
+        .current_dir(package_dir)
+        .kill_on_drop(true)
+        .output();
+    let output = match tokio::time::timeout(CARGO_CHECK_TIMEOUT, run).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => {
+            tracing::warn!(error = %e, "Failed to run cargo check after patch");
+            return None;
+        }
+        Err(_) => {
+            tracing::warn!(dir = %package_dir.display(), "cargo check after patch timed out");
+            return None;
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(
+        stdout
+            .lines()
+            .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+            .filter_map(|msg| compiler_error(&msg))
+            .take(MAX_REPORTED)
+            .collect(),
+    )
+}
+
+/// Format one `compiler-message` line of `--message-format=json` output, if
+/// it is an error.
+fn compiler_error(msg: &Value) -> Option<String> {
+    if msg["reason"] != "compiler-message" || msg["message"]["level"] != "error" {
+        return None;
+    }
+    let message = msg["message"]["message"].as_str()?;
+    let primary = msg["message"]["spans"]
+        .as_array()
+        .and_then(|spans| spans.iter().find(|s| s["is_primary"] == true));
+    Some(match primary {
+        Some(span) => format!(
+            "{}:{}:{}: {message}",
+            span["file_name"].as_str().unwrap_or("?"),
+            span["line_start"],
+            span["column_start"],
+        ),
+        None => message.to_string(),
+    })
+}
+
+/// Check the file as written and render any problems for the tool result.
+pub async fn validate(path: &Path) -> Option<String> {
+    let (content, _) = read_file_content(path).ok()??;
+    let mut report = String::new();
+
+    let syntax_errors = check_syntax(path, &content);
+    if !syntax_errors.is_empty() {
+        let _ = write!(report, "\n<syntax_errors path=\"{}\">\n", path.display());
+        for e in &syntax_errors {
+            let _ = writeln!(
+                report,
+                "line {}, column {}: {}",
+                e.line, e.column, e.message
+            );
+        }
+        report.push_str("</syntax_errors>");
+    }
+
+    // A file that does not parse will not compile either; skip the slow check
+    if syntax_errors.is_empty() {
+        if let Some(errors) = cargo_check(path).await.filter(|e| !e.is_empty()) {
+            let _ = write!(
+                report,
+                "\n<cargo_check_errors path=\"{}\">\n",
+                path.display()
+            );
+            for e in &errors {
+                report.push_str(e);
+                report.push('\n');
+            }
+            report.push_str("</cargo_check_errors>");
+        }
+    }
+
+    (!report.is_empty()).then_some(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_valid_rust_has_no_errors() {
+        let errors = check_syntax(Path::new("lib.rs"), "fn main() {\n    let x = 1;\n}\n");
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn test_broken_rust_reports_location() {
+        let errors = check_syntax(Path::new("lib.rs"), "fn main() {\n    let x = ;\n}\n");
+        assert!(!errors.is_empty());
+        assert_eq!(errors[0].line, 2);
+    }
+
+    #[test]
+    fn test_unclosed_brace_reports_missing() {
+        let errors = check_syntax(Path::new("main.go"), "package main\n\nfunc main() {\n");
+        assert!(!errors.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_extension_is_skipped() {
+        assert!(check_syntax(Path::new("notes.txt"), "fn main( {").is_empty());
+        assert!(check_syntax(Path::new("Makefile"), "fn main( {").is_empty());
+    }
+
+    #[test]
+    fn test_broken_json() {
+        let errors = check_syntax(Path::new("data.json"), "{\"a\": 1,, }");
+        assert!(!errors.is_empty());
+    }
+
+    #[test]
+    fn test_compiler_error_uses_primary_span() {
+        let msg = json!({
+            "reason": "compiler-message",
+            "message": {
+                "level": "error",
+                "message": "cannot find value `y` in this scope",
+                "spans": [
+                    {
+                        "is_primary": false,
+                        "file_name": "src/a.rs",
+                        "line_start": 1,
+                        "column_start": 1
+                    },
+                    {
+                        "is_primary": true,
+                        "file_name": "src/lib.rs",
+                        "line_start": 7,
+                        "column_start": 13
+                    }
+                ]
+            }
+        });
+        assert_eq!(
+            compiler_error(&msg).as_deref(),
+            Some("src/lib.rs:7:13: cannot find value `y` in this scope")
+        );
+    }
+
+    #[test]
+    fn test_compiler_warnings_are_ignored() {
+        let msg = json!({
+            "reason": "compiler-message",
+            "message": { "level": "warning", "message": "unused variable", "spans": [] }
+        });
+        assert_eq!(compiler_error(&msg), None);
+        assert_eq!(compiler_error(&json!({ "reason": "build-finished" })), None);
+    }
+}
@@ -1,6 +1,8 @@
 //! Core types for patch operations
 
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
 use std::path::PathBuf;
 
 /// A patch operation type
@@ -33,10 +35,38 @@ pub struct PatchRequest {
 }
 
 /// Input for a patch operation
+///
+/// Either `path` + `patches` for one file, or `files` for a multi-file
+/// transaction that is written all-or-nothing.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct PatchInput {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub path: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub patches: Vec<PatchRequest>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FilePatches>,
+}
+
+/// The patches for one file of a multi-file transaction
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FilePatches {
+    pub path: String,
+    pub patches: Vec<PatchRequest>,
+}
+
+impl PatchInput {
+    /// The per-file patch lists, whichever input shape was used.
+    pub fn into_files(self) -> Vec<FilePatches> {
+        if self.files.is_empty() {
+            vec![FilePatches {
+                path: self.path,
+                patches: self.patches,
+            }]
+        } else {
+            self.files
+        }
+    }
 }
 
 /// A located edit in the content
@@ -54,11 +84,87 @@ pub struct Edit {
     pub replacement: String,
 }
 
+/// Line ending convention of a file (REQ-PATCH-013)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    /// The ending used by most line breaks in `content`; LF when it has none.
+    pub fn detect(content: &str) -> Self {
+        let crlf = content.matches("\r\n").count();
+        let lf = content.matches('\n').count() - crlf;
+        if crlf > lf {
+            Self::CrLf
+        } else {
+            Self::Lf
+        }
+    }
+
+    /// Rewrite every line break in `text` to this ending. Borrowed when the
+    /// text already matches.
+    pub fn convert(self, text: &str) -> Cow<'_, str> {
+        match self {
+            Self::Lf if text.contains("\r\n") => Cow::Owned(text.replace("\r\n", "\n")),
+            Self::CrLf if text.matches('\n').count() != text.matches("\r\n").count() => {
+                Cow::Owned(text.replace("\r\n", "\n").replace('\n', "\r\n"))
+            }
+            Self::Lf | Self::CrLf => Cow::Borrowed(text),
+        }
+    }
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Lf => "LF",
+            Self::CrLf => "CRLF",
+        })
+    }
+}
+
+/// On-disk text encoding of a file, kept when it is rewritten (REQ-PATCH-013)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    /// UTF-8 with a leading byte-order mark
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+}
+
 /// Effects produced by patch planning
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum PatchEffect {
     /// Write content to a file (creates parent dirs as needed)
-    WriteFile { path: PathBuf, content: String },
+    WriteFile {
+        path: PathBuf,
+        content: String,
+        #[serde(default)]
+        encoding: TextEncoding,
+    },
+}
+
+/// A planned patch held for user review instead of written (REQ-PATCH-010)
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StagedPatch {
+    /// Resolved path of the patched file; comma-separated for a multi-file patch
+    pub path: String,
+    /// Unified diff shown to the reviewer
+    pub diff: String,
+    /// Writes performed if the patch is applied
+    pub effects: Vec<PatchEffect>,
+    #[serde(default)]
+    pub autogenerated_warning: bool,
+    /// Files whose patch text was converted to their line ending
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub converted_line_endings: Vec<(String, LineEnding)>,
 }
 
 /// Result of planning patches
@@ -73,6 +179,20 @@ pub struct PatchPlan {
     pub diff: String,
     /// Warning if file appears auto-generated
     pub autogenerated_warning: bool,
+    /// Set when patch text was converted to the file's line ending
+    pub converted_line_ending: Option<LineEnding>,
+}
+
+impl PatchPlan {
+    /// Write the result in the file's existing encoding rather than UTF-8.
+    #[must_use]
+    pub fn with_encoding(mut self, file_encoding: TextEncoding) -> Self {
+        for effect in &mut self.effects {
+            let PatchEffect::WriteFile { encoding, .. } = effect;
+            *encoding = file_encoding;
+        }
+        self
+    }
 }
 
 /// Errors that can occur during patch planning
@@ -126,4 +246,22 @@ mod tests {
         assert_eq!(req.old_text, Some("hello".to_string()));
         assert_eq!(req.new_text, Some("world".to_string()));
     }
+
+    #[test]
+    fn test_line_ending_detect() {
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("a\nb\r\nc\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("no breaks"), LineEnding::Lf);
+    }
+
+    #[test]
+    fn test_line_ending_convert() {
+        assert_eq!(LineEnding::CrLf.convert("a\nb\r\n"), "a\r\nb\r\n");
+        assert_eq!(LineEnding::Lf.convert("a\r\nb\n"), "a\nb\n");
+        assert!(matches!(
+            LineEnding::CrLf.convert("a\r\nb"),
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(LineEnding::Lf.convert("a\nb"), Cow::Borrowed(_)));
+    }
 }
@@ -0,0 +1,460 @@
+//! Sandboxed WebAssembly tool plugins (specs/plugins).
+//!
+//! A plugin is a WASI component implementing the `tool` world in
+//! `wit/plugin.wit`: it exports a JSON manifest (name, description, input
+//! schema, capabilities) and a `run` function. Plugins are compiled once at
+//! startup from the plugins directory and registered next to the built-in
+//! tools. Every call gets a fresh instance with only the capabilities its
+//! manifest declares, a fuel budget, and a memory cap.
+
+use super::{Tool, ToolContext, ToolOutput};
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use wasmtime::component::{Component, Linker, ResourceTable};
+use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder};
+use wasmtime_wasi::{DirPerms, FilePerms, WasiCtx, WasiCtxBuilder, WasiView};
+
+mod bindings {
+    wasmtime::component::bindgen!({
+        world: "tool",
+        path: "wit/plugin.wit",
+        async: true,
+    });
+}
+
+/// Directory under `$HOME` scanned when `PHOENIX_PLUGINS_DIR` is unset.
+const LIBRARY_PLUGIN_DIR: &str = ".phoenix-ide/plugins";
+
+/// Fuel granted to a single `run` call. Roughly a few seconds of guest work;
+/// the executor's tool timeout still applies on top (REQ-BED-033).
+const FUEL_PER_CALL: u64 = 5_000_000_000;
+
+/// Fuel granted to the `manifest` export at load time.
+const FUEL_FOR_MANIFEST: u64 = 100_000_000;
+
+/// Guest instructions between cooperative yields, so a busy plugin does not
+/// starve the tokio worker and cancellation is observed promptly.
+const FUEL_YIELD_INTERVAL: u64 = 10_000_000;
+
+/// Linear memory cap per instance.
+const MEMORY_LIMIT_BYTES: usize = 256 * 1024 * 1024;
+
+/// Plugins loaded at startup. Empty until [`install`] runs.
+static INSTALLED: OnceLock<Vec<Arc<PluginTool>>> = OnceLock::new();
+
+/// Filesystem access a plugin may request. Access is always scoped to the
+/// conversation's working directory.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FilesystemAccess {
+    #[default]
+    None,
+    Read,
+    ReadWrite,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Capabilities {
+    pub filesystem: FilesystemAccess,
+    pub network: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginManifest {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+    #[serde(default)]
+    pub capabilities: Capabilities,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PluginError {
+    #[error("failed to compile component: {0:#}")]
+    Compile(wasmtime::Error),
+    #[error("failed to read manifest: {0:#}")]
+    Manifest(wasmtime::Error),
+    #[error("invalid manifest JSON: {0}")]
+    ManifestJson(#[from] serde_json::Error),
+    #[error("invalid tool name '{0}': use lowercase letters, digits, and '_'")]
+    InvalidName(String),
+    #[error("tool name '{0}' is already taken")]
+    NameTaken(String),
+    #[error("input_schema must be a JSON object with \"type\": \"object\"")]
+    InvalidSchema,
+}
+
+impl PluginManifest {
+    /// Parse and validate the JSON returned by a plugin's `manifest` export.
+    pub fn parse(json: &str) -> Result<Self, PluginError> {
+        let manifest: Self = serde_json::from_str(json)?;
+        let valid_name = (1..=64).contains(&manifest.name.len())
+            && manifest.name.starts_with(|c: char| c.is_ascii_lowercase())
+            && manifest
+                .name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid_name {
+            return Err(PluginError::InvalidName(manifest.name));
+        }
+        if manifest.input_schema.get("type").and_then(Value::as_str) != Some("object") {
+            return Err(PluginError::InvalidSchema);
+        }
+        Ok(manifest)
+    }
+}
+
+/// Per-instance store data: WASI context plus resource limits.
+struct PluginState {
+    wasi: WasiCtx,
+    table: ResourceTable,
+    limits: StoreLimits,
+}
+
+impl WasiView for PluginState {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
+
+/// Engine and WASI linker shared by every plugin.
+struct PluginRuntime {
+    engine: Engine,
+    linker: Linker<PluginState>,
+}
+
+impl PluginRuntime {
+    fn new() -> wasmtime::Result<Self> {
+        let mut config = Config::new();
+        config.async_support(true);
+        config.consume_fuel(true);
+        config.wasm_component_model(true);
+        let engine = Engine::new(&config)?;
+        let mut linker = Linker::new(&engine);
+        wasmtime_wasi::add_to_linker_async(&mut linker)?;
+        Ok(Self { engine, linker })
+    }
+
+    fn store(&self, wasi: WasiCtx, fuel: u64) -> wasmtime::Result<Store<PluginState>> {
+        let state = PluginState {
+            wasi,
+            table: ResourceTable::new(),
+            limits: StoreLimitsBuilder::new()
+                .memory_size(MEMORY_LIMIT_BYTES)
+                .build(),
+        };
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|s| &mut s.limits);
+        store.set_fuel(fuel)?;
+        store.fuel_async_yield_interval(Some(FUEL_YIELD_INTERVAL))?;
+        Ok(store)
+    }
+}
+
+/// A tool backed by a WASM component.
+pub struct PluginTool {
+    runtime: Arc<PluginRuntime>,
+    component: Component,
+    manifest: PluginManifest,
+}
+
+impl PluginTool {
+    pub fn capabilities(&self) -> Capabilities {
+        self.manifest.capabilities
+    }
+
+    /// WASI context granting exactly the declared capabilities. The working
+    /// directory and the conversation's extra roots (REQ-BED-049) are
+    /// preopened at their host paths so absolute paths the model already
+    /// uses resolve unchanged inside the guest. A read-only root stays
+    /// read-only even for a read-write plugin.
+    fn wasi_ctx(&self, ctx: &ToolContext) -> wasmtime::Result<WasiCtx> {
+        let caps = self.manifest.capabilities;
+        let mut builder = WasiCtxBuilder::new();
+        let perms = match caps.filesystem {
+            FilesystemAccess::None => None,
+            FilesystemAccess::Read => Some((DirPerms::READ, FilePerms::READ)),
+            FilesystemAccess::ReadWrite => Some((DirPerms::all(), FilePerms::all())),
+        };
+        if let Some((dir_perms, file_perms)) = perms {
+            let guest_path = ctx.working_dir.to_string_lossy();
+            builder.preopened_dir(&ctx.working_dir, guest_path, dir_perms, file_perms)?;
+            for root in &ctx.extra_roots {
+                let (dir_perms, file_perms) = if root.writable {
+                    (dir_perms, file_perms)
+                } else {
+                    (DirPerms::READ, FilePerms::READ)
+                };
+                builder.preopened_dir(&root.path, &root.path, dir_perms, file_perms)?;
+            }
+        }
+        if caps.network {
+            builder.inherit_network();
+            builder.allow_ip_name_lookup(true);
+        }
+        Ok(builder.build())
+    }
+
+    async fn call(
+        &self,
+        input: &str,
+        ctx: &ToolContext,
+    ) -> wasmtime::Result<Result<String, String>> {
+        let wasi = self.wasi_ctx(ctx)?;
+        let mut store = self.runtime.store(wasi, FUEL_PER_CALL)?;
+        let instance =
+            bindings::Tool::instantiate_async(&mut store, &self.component, &self.runtime.linker)
+                .await?;
+        instance.call_run(&mut store, input).await
+    }
+}
+
+#[async_trait]
+impl Tool for PluginTool {
+    fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    fn description(&self) -> String {
+        self.manifest.description.clone()
+    }
+
+    fn input_schema(&self) -> Value {
+        self.manifest.input_schema.clone()
+    }
+
+    async fn run(&self, input: Value, ctx: ToolContext) -> ToolOutput {
+        let input = input.to_string();
+        tokio::select! {
+            () = ctx.cancel.cancelled() => ToolOutput::error("Cancelled"),
+            result = self.call(&input, &ctx) => match result {
+                Ok(Ok(output)) => ToolOutput::success(output),
+                Ok(Err(message)) => ToolOutput::error(message),
+                Err(e) => ToolOutput::error(format!("Plugin '{}' failed: {e:#}", self.name())),
+            },
+        }
+    }
+}
+
+/// `$PHOENIX_PLUGINS_DIR`, else `$HOME/.phoenix-ide/plugins`, or `None` when
+/// neither is set.
+pub fn plugins_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("PHOENIX_PLUGINS_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(LIBRARY_PLUGIN_DIR))
+}
+
+async fn load_one(
+    runtime: &Arc<PluginRuntime>,
+    path: PathBuf,
+    taken: &HashSet<String>,
+) -> Result<PluginTool, PluginError> {
+    // Cranelift compilation is CPU-bound; keep it off the async workers.
+    let engine = runtime.engine.clone();
+    let component = tokio::task::spawn_blocking(move || Component::from_file(&engine, &path))
+        .await
+        .map_err(|e| PluginError::Compile(e.into()))?
+        .map_err(PluginError::Compile)?;
+
+    let mut store = runtime
+        .store(WasiCtxBuilder::new().build(), FUEL_FOR_MANIFEST)
+        .map_err(PluginError::Manifest)?;
+    let instance = bindings::Tool::instantiate_async(&mut store, &component, &runtime.linker)
+        .await
+        .map_err(PluginError::Manifest)?;
+    let json = instance
+        .call_manifest(&mut store)
+        .await
+        .map_err(PluginError::Manifest)?;
+    let manifest = PluginManifest::parse(&json)?;
+    if taken.contains(&manifest.name) {
+        return Err(PluginError::NameTaken(manifest.name));
+    }
+    Ok(PluginTool {
+        runtime: Arc::clone(runtime),
+        component,
+        manifest,
+    })
+}
+
+/// Compile every `*.wasm` file in `dir`. A plugin that fails to load is
+/// logged and skipped; it never prevents startup.
+pub async fn load_dir(dir: &Path) -> Vec<PluginTool> {
+    let mut paths: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "wasm"))
+            .collect(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            tracing::warn!(dir = %dir.display(), error = %e, "Failed to read plugins directory");
+            return Vec::new();
+        }
+    };
+    if paths.is_empty() {
+        return Vec::new();
+    }
+    paths.sort();
+
+    let runtime = match PluginRuntime::new() {
+        Ok(runtime) => Arc::new(runtime),
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to initialize WASM plugin runtime");
+            return Vec::new();
+        }
+    };
+
+    let mut taken: HashSet<String> = super::ToolRegistry::catalog()
+        .definitions()
+        .into_iter()
+        .map(|d| d.name)
+        .collect();
+    let mut plugins = Vec::new();
+    for path in paths {
+        match load_one(&runtime, path.clone(), &taken).await {
+            Ok(plugin) => {
+                tracing::info!(
+                    plugin = %plugin.manifest.name,
+                    path = %path.display(),
+                    capabilities = ?plugin.manifest.capabilities,
+                    "Loaded WASM tool plugin"
+                );
+                taken.insert(plugin.manifest.name.clone());
+                plugins.push(plugin);
+            }
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "Skipping WASM plugin");
+            }
+        }
+    }
+    plugins
+}
+
+/// Make loaded plugins visible to every registry built from now on. Only the
+/// first call has an effect.
+pub fn install(plugins: Vec<PluginTool>) {
+    let _ = INSTALLED.set(plugins.into_iter().map(Arc::new).collect());
+}
+
+/// Installed plugin tools. With `read_only`, plugins that declare write
+/// access to the working directory are left out.
+pub(super) fn installed_tools(read_only: bool) -> Vec<Arc<dyn Tool>> {
+    INSTALLED
+        .get()
+        .into_iter()
+        .flatten()
+        .filter(|p| !read_only || p.capabilities().filesystem != FilesystemAccess::ReadWrite)
+        .map(|p| Arc::clone(p) as Arc<dyn Tool>)
+        .collect()
+}
+
+/// Whether `name` belongs to an installed plugin rather than a built-in tool.
+pub fn is_plugin(name: &str) -> bool {
+    INSTALLED
+        .get()
+        .is_some_and(|plugins| plugins.iter().any(|p| p.manifest.name == name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn manifest(value: &Value) -> Result<PluginManifest, PluginError> {
+        PluginManifest::parse(&value.to_string())
+    }
+
+    #[test]
+    fn manifest_defaults_to_no_capabilities() {
+        let m = manifest(&json!({
+            "name": "word_count",
+            "description": "Count words",
+            "input_schema": {"type": "object", "properties": {}},
+        }))
+        .unwrap();
+        assert_eq!(m.capabilities, Capabilities::default());
+        assert_eq!(m.capabilities.filesystem, FilesystemAccess::None);
+        assert!(!m.capabilities.network);
+    }
+
+    #[test]
+    fn manifest_parses_declared_capabilities() {
+        let m = manifest(&json!({
+            "name": "fetch_docs",
+            "description": "Fetch docs",
+            "input_schema": {"type": "object"},
+            "capabilities": {"filesystem": "read-write", "network": true},
+        }))
+        .unwrap();
+        assert_eq!(m.capabilities.filesystem, FilesystemAccess::ReadWrite);
+        assert!(m.capabilities.network);
+    }
+
+    #[test]
+    fn manifest_rejects_bad_names_and_schemas() {
+        for name in ["", "Upper", "has-dash", "1leading_digit", "../escape"] {
+            let result = manifest(&json!({
+                "name": name,
+                "description": "x",
+                "input_schema": {"type": "object"},
+            }));
+            assert!(
+                matches!(result, Err(PluginError::InvalidName(_))),
+                "{name:?} should be rejected"
+            );
+        }
+        let result = manifest(&json!({
+            "name": "ok",
+            "description": "x",
+            "input_schema": {"type": "string"},
+        }));
+        assert!(matches!(result, Err(PluginError::InvalidSchema)));
+    }
+
+    #[test]
+    fn manifest_rejects_unknown_capabilities() {
+        let result = manifest(&json!({
+            "name": "ok",
+            "description": "x",
+            "input_schema": {"type": "object"},
+            "capabilities": {"filesystem": "everything"},
+        }));
+        assert!(matches!(result, Err(PluginError::ManifestJson(_))));
+        let result = manifest(&json!({
+            "name": "ok",
+            "description": "x",
+            "input_schema": {"type": "object"},
+            "capabilities": {"processes": true},
+        }));
+        assert!(matches!(result, Err(PluginError::ManifestJson(_))));
+    }
+
+    #[tokio::test]
+    async fn missing_or_empty_plugins_dir_loads_nothing() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(load_dir(&tmp.path().join("absent")).await.is_empty());
+        std::fs::write(tmp.path().join("README.md"), "not a plugin").unwrap();
+        assert!(load_dir(tmp.path()).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn invalid_component_is_skipped() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(tmp.path().join("broken.wasm"), b"\0asm garbage").unwrap();
+        assert!(load_dir(tmp.path()).await.is_empty());
+    }
+}
@@ -0,0 +1,222 @@
+//! Working-tree dirty check before destructive operations (REQ-BASH-019,
+//! REQ-PATCH-014).
+//!
+//! Before a tool overwrites or discards files, it asks which uncommitted
+//! changes in the way the conversation did not make itself: the user's
+//! work in progress, or another agent's. A change is the conversation's own
+//! when it edited the file through `patch`, as recorded in the touched-files
+//! index (REQ-BED-042). Edits made through bash are not attributed, so they
+//! count as unrelated.
+//!
+//! The check is advisory: it reads `git` state and never blocks on its own.
+//! Outside a git repository, or in one without commits, it finds nothing.
+
+use std::path::{Path, PathBuf};
+
+use tokio::task::spawn_blocking;
+
+use crate::git_ops::run_git;
+use crate::tools::bash_check::simple_commands;
+
+/// What a destructive git command throws away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Discards {
+    /// Modified, staged and deleted files (`reset --hard`, `checkout -- .`)
+    Tracked,
+    /// Files git does not know about (`clean -f`)
+    Untracked,
+}
+
+/// What an operation is about to affect.
+#[derive(Debug, Clone, Copy)]
+pub enum Target<'a> {
+    /// These files, e.g. the targets of a patch
+    Files(&'a [PathBuf]),
+    /// Every change of one kind, for a command that discards them
+    Tree(Discards),
+}
+
+/// An uncommitted change, by path relative to the repository root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Change {
+    path: String,
+    untracked: bool,
+}
+
+/// Uncommitted changes under `target` that the conversation did not make,
+/// as paths relative to the repository root. `edited` holds the absolute
+/// paths the conversation edited.
+pub async fn unrelated_changes(dir: &Path, target: Target<'_>, edited: &[String]) -> Vec<String> {
+    let owned_dir = dir.to_path_buf();
+    let status = spawn_blocking(move || uncommitted_changes(&owned_dir)).await;
+    let Ok(Some((root, changes))) = status else {
+        return Vec::new();
+    };
+
+    let edited: Vec<PathBuf> = edited.iter().map(|p| canonical(Path::new(p))).collect();
+    let targets: Vec<PathBuf> = match target {
+        Target::Files(files) => files.iter().map(|p| canonical(p.as_path())).collect(),
+        Target::Tree(_) => Vec::new(),
+    };
+    changes
+        .into_iter()
+        .filter(|change| match target {
+            Target::Files(_) => targets.contains(&root.join(&change.path)),
+            Target::Tree(Discards::Tracked) => !change.untracked,
+            Target::Tree(Discards::Untracked) => change.untracked,
+        })
+        .filter(|change| !edited.contains(&root.join(&change.path)))
+        .map(|change| change.path)
+        .collect()
+}
+
+/// The first command in `script` that discards working-tree changes, and
+/// what it discards. Only git's own discarding commands are recognised;
+/// `rm` and friends are left to the safety check (REQ-BASH-011).
+pub fn destructive_git_command(script: &str) -> Option<(String, Discards)> {
+    simple_commands(script)?
+        .into_iter()
+        .find_map(|command| discards(&command).map(|d| (command, d)))
+}
+
+fn discards(command: &str) -> Option<Discards> {
+    let mut words = command.split_whitespace();
+    let program = words.next()?;
+    if program != "git" && !program.ends_with("/git") {
+        return None;
+    }
+    // Skip global options; -C and -c take a value
+    let mut subcommand = None;
+    while let Some(word) = words.next() {
+        match word {
+            "-C" | "-c" => {
+                words.next();
+            }
+            w if w.starts_with('-') => {}
+            w => {
+                subcommand = Some(w);
+                break;
+            }
+        }
+    }
+    let args: Vec<&str> = words.collect();
+    let has = |flags: &[&str]| args.iter().any(|a| flags.contains(a));
+
+    let tracked = match subcommand? {
+        "reset" => has(&["--hard"]),
+        "checkout" => has(&["--", ".", "-f", "--force"]),
+        "restore" => !has(&["--staged", "-S"]) || has(&["--worktree", "-W"]),
+        "switch" => has(&["-f", "--force", "--discard-changes"]),
+        "clean" => {
+            let forced = args.iter().any(|a| {
+                *a == "--force" || (a.starts_with('-') && !a.starts_with("--") && a.contains('f'))
+            });
+            return forced.then_some(Discards::Untracked);
+        }
+        _ => false,
+    };
+    tracked.then_some(Discards::Tracked)
+}
+
+/// The repository root and its uncommitted changes, or `None` when `dir`
+/// is not in a repository with commits.
+fn uncommitted_changes(dir: &Path) -> Option<(PathBuf, Vec<Change>)> {
+    let root = PathBuf::from(run_git(dir, &["rev-parse", "--show-toplevel"]).ok()?);
+    let tracked = run_git(dir, &["diff", "--name-only", "-z", "HEAD"]).ok()?;
+    // Run from the root so untracked paths are root-relative like the diff's
+    let untracked = run_git(&root, &["ls-files", "--others", "--exclude-standard", "-z"]).ok()?;
+
+    let entries = |out: &str, untracked: bool| -> Vec<Change> {
+        out.split('\0')
+            .filter(|path| !path.is_empty())
+            .map(|path| Change {
+                path: path.to_string(),
+                untracked,
+            })
+            .collect()
+    };
+    let mut changes = entries(&tracked, false);
+    changes.extend(entries(&untracked, true));
+    Some((canonical(&root), changes))
+}
+
+/// `path` with symlinks resolved, so `/tmp` and `/private/tmp` compare
+/// equal; unchanged when it does not exist (e.g. a deleted file).
+fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "t")
+            .env("GIT_AUTHOR_EMAIL", "t@example.com")
+            .env("GIT_COMMITTER_NAME", "t")
+            .env("GIT_COMMITTER_EMAIL", "t@example.com")
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?}");
+    }
+
+    #[test]
+    fn recognises_commands_that_discard_changes() {
+        let cases = [
+            ("git reset --hard HEAD~1", Some(Discards::Tracked)),
+            (
+                "cd /repo && git checkout -- src/lib.rs",
+                Some(Discards::Tracked),
+            ),
+            ("git -C /repo checkout .", Some(Discards::Tracked)),
+            ("git restore src/lib.rs", Some(Discards::Tracked)),
+            ("git switch --discard-changes main", Some(Discards::Tracked)),
+            ("git clean -fdx", Some(Discards::Untracked)),
+            ("git reset --soft HEAD~1", None),
+            ("git checkout main", None),
+            ("git restore --staged src/lib.rs", None),
+            ("git clean -n", None),
+            ("git status", None),
+            ("echo git reset --hard", None),
+        ];
+        for (script, expected) in cases {
+            let found = destructive_git_command(script).map(|(_, d)| d);
+            assert_eq!(found, expected, "{script}");
+        }
+    }
+
+    #[tokio::test]
+    async fn only_changes_the_conversation_did_not_make_count() {
+        let repo = tempfile::tempdir().unwrap();
+        let dir = repo.path();
+        git(dir, &["init", "-q"]);
+        std::fs::write(dir.join("mine.rs"), "a").unwrap();
+        std::fs::write(dir.join("theirs.rs"), "a").unwrap();
+        git(dir, &["add", "."]);
+        git(
+            dir,
+            &["-c", "commit.gpgsign=false", "commit", "-q", "-m", "init"],
+        );
+        std::fs::write(dir.join("mine.rs"), "b").unwrap();
+        std::fs::write(dir.join("theirs.rs"), "b").unwrap();
+        std::fs::write(dir.join("new.txt"), "b").unwrap();
+
+        let edited = [dir.join("mine.rs").display().to_string()];
+        let tracked = unrelated_changes(dir, Target::Tree(Discards::Tracked), &edited).await;
+        assert_eq!(tracked, ["theirs.rs"]);
+        let untracked = unrelated_changes(dir, Target::Tree(Discards::Untracked), &edited).await;
+        assert_eq!(untracked, ["new.txt"]);
+        let files = [dir.join("mine.rs"), dir.join("new.txt")];
+        let targeted = unrelated_changes(dir, Target::Files(&files), &edited).await;
+        assert_eq!(targeted, ["new.txt"]);
+
+        let elsewhere = tempfile::tempdir().unwrap();
+        let tree = Target::Tree(Discards::Tracked);
+        let outside = unrelated_changes(elsewhere.path(), tree, &[]).await;
+        assert!(outside.is_empty());
+    }
+}
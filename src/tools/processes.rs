@@ -0,0 +1,515 @@
+//! `list_processes` and `kill_process` tools - the conversation's leftovers
+//!
+//! REQ-BASH-021: a bash command's background jobs, such as a dev server
+//! started with `&`, outlive its handle. They stay in the handle's process
+//! group, so the processes in any group this conversation's handles
+//! started, and their descendants, are the ones the agent may see and
+//! signal. Nothing else on the machine is reachable.
+
+use super::bash::handle::{Handle, KillSignal};
+use super::{Tool, ToolContext, ToolOutput};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// How long `kill_process` waits before reporting what is still running.
+const SETTLE: Duration = Duration::from_secs(1);
+
+/// List processes the conversation's bash commands left running.
+pub struct ListProcessesTool;
+
+/// Signal a process from [`ListProcessesTool`]'s list.
+pub struct KillProcessTool;
+
+/// One line of `ps` output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PsRow {
+    pid: i32,
+    ppid: i32,
+    pgid: i32,
+    elapsed_seconds: u64,
+    args: String,
+}
+
+/// A process the conversation started, as the tools report it.
+#[derive(Debug, Clone, Serialize)]
+struct OwnedProcess {
+    pid: i32,
+    ppid: i32,
+    pgid: i32,
+    elapsed_seconds: u64,
+    command: String,
+    /// The bash handle that started it, and whether that command is
+    /// still running or has exited and left this behind.
+    handle: String,
+    handle_status: &'static str,
+}
+
+/// `ps` elapsed time, `[[dd-]hh:]mm:ss`, in seconds.
+fn parse_etime(etime: &str) -> Option<u64> {
+    let (days, clock) = match etime.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, etime),
+    };
+    let seconds = clock.split(':').try_fold(0u64, |total, part| {
+        Some(total * 60 + part.parse::<u64>().ok()?)
+    })?;
+    Some(days * 86_400 + seconds)
+}
+
+/// Rows of `ps -A -o pid=,ppid=,pgid=,stat=,etime=,args=`, leaving out
+/// zombies: they have exited and only wait to be reaped. The command line
+/// is the rest of the line and may contain spaces.
+fn parse_ps(output: &str) -> Vec<PsRow> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut rest = line.trim_start();
+            let mut fields = [""; 5];
+            for field in &mut fields {
+                let (text, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                *field = text;
+                rest = tail.trim_start();
+            }
+            if fields[3].starts_with('Z') {
+                return None;
+            }
+            Some(PsRow {
+                pid: fields[0].parse().ok()?,
+                ppid: fields[1].parse().ok()?,
+                pgid: fields[2].parse().ok()?,
+                elapsed_seconds: parse_etime(fields[4])?,
+                args: rest.trim_end().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Every process on the machine, from `ps`, which Linux and macOS both
+/// answer in this form.
+async fn ps() -> Result<Vec<PsRow>, String> {
+    let output = tokio::process::Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid=,pgid=,stat=,etime=,args="])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run ps: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("ps failed: {}", stderr.trim()));
+    }
+    Ok(parse_ps(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// The processes `handles` started, each with its handle: those in a
+/// handle's process group that are no older than the handle, and their
+/// descendants. The age check keeps a recycled group id from claiming an
+/// unrelated process.
+fn owned<'a>(
+    rows: &[PsRow],
+    handles: &'a [Arc<Handle>],
+    now: SystemTime,
+) -> Vec<(PsRow, &'a Arc<Handle>)> {
+    let mut newest_first: Vec<&Arc<Handle>> = handles.iter().collect();
+    newest_first.sort_by_key(|h| std::cmp::Reverse(h.started_at));
+    let age = |h: &Handle| {
+        now.duration_since(h.started_at)
+            .unwrap_or_default()
+            .as_secs()
+    };
+
+    let mut owner: HashMap<i32, &Arc<Handle>> = HashMap::new();
+    for row in rows {
+        // `etime` is in whole seconds, so allow one second of rounding
+        let started_by = newest_first
+            .iter()
+            .find(|h| h.pgid == row.pgid && row.elapsed_seconds <= age(h) + 1);
+        if let Some(handle) = started_by {
+            owner.insert(row.pid, *handle);
+        }
+    }
+    // Children that moved to a group of their own are still descendants
+    loop {
+        let adopted: Vec<(i32, &Arc<Handle>)> = rows
+            .iter()
+            .filter(|row| !owner.contains_key(&row.pid))
+            .filter_map(|row| owner.get(&row.ppid).map(|h| (row.pid, *h)))
+            .collect();
+        if adopted.is_empty() {
+            break;
+        }
+        owner.extend(adopted);
+    }
+
+    let own_pid = i32::try_from(std::process::id()).unwrap_or(0);
+    rows.iter()
+        .filter(|row| row.pid != own_pid)
+        .filter_map(|row| owner.get(&row.pid).map(|h| (row.clone(), *h)))
+        .collect()
+}
+
+/// The conversation's processes, ready to report.
+async fn conversation_processes(ctx: &ToolContext) -> Result<Vec<OwnedProcess>, String> {
+    let handles = ctx.spawned_bash_handles().await;
+    if handles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rows = ps().await?;
+    let mut processes = Vec::new();
+    for (row, handle) in owned(&rows, &handles, SystemTime::now()) {
+        let running = handle.state().await.is_live();
+        processes.push(OwnedProcess {
+            pid: row.pid,
+            ppid: row.ppid,
+            pgid: row.pgid,
+            elapsed_seconds: row.elapsed_seconds,
+            command: row.args,
+            handle: handle.handle_id.to_string(),
+            handle_status: if running { "running" } else { "exited" },
+        });
+    }
+    Ok(processes)
+}
+
+/// `pid` and its descendants among `processes`, parents first.
+fn with_descendants(pid: i32, processes: &[OwnedProcess]) -> Vec<i32> {
+    let mut tree = vec![pid];
+    let mut i = 0;
+    while let Some(&parent) = tree.get(i) {
+        tree.extend(
+            processes
+                .iter()
+                .filter(|p| p.ppid == parent && !tree.contains(&p.pid))
+                .map(|p| p.pid)
+                .collect::<Vec<_>>(),
+        );
+        i += 1;
+    }
+    tree
+}
+
+#[cfg(unix)]
+fn send_signal(pid: i32, signal: KillSignal) -> std::io::Result<()> {
+    // SAFETY: kill(2) on a single positive pid has no memory implications
+    if unsafe { libc::kill(pid, signal.as_libc()) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+/// Collect the exit status of a signalled process that was reparented to
+/// Phoenix, the subreaper of REQ-BASH-007, so it does not stay a zombie.
+/// Fails harmlessly for a process that is not Phoenix's child.
+#[cfg(unix)]
+fn reap(pid: i32) {
+    // SAFETY: waitpid on one pid with WNOHANG neither blocks nor touches
+    // memory we own
+    unsafe {
+        libc::waitpid(pid, std::ptr::null_mut(), libc::WNOHANG);
+    }
+}
+
+#[cfg(not(unix))]
+fn reap(_pid: i32) {}
+
+#[cfg(not(unix))]
+fn send_signal(_pid: i32, _signal: KillSignal) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "signals need a unix host",
+    ))
+}
+
+#[async_trait]
+impl Tool for ListProcessesTool {
+    fn name(&self) -> &'static str {
+        "list_processes"
+    }
+
+    fn description(&self) -> String {
+        "List the processes this conversation's bash commands started that are still \
+         running, including background jobs (`cmd &`) left behind by commands that have \
+         exited, such as dev servers. Each names the bash handle that started it. Stop \
+         them with kill_process."
+            .to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {}
+        })
+    }
+
+    async fn run(&self, _input: Value, ctx: ToolContext) -> ToolOutput {
+        match conversation_processes(&ctx).await {
+            Ok(processes) => {
+                let data = json!({ "processes": processes });
+                let output = serde_json::to_string_pretty(&data).unwrap_or_default();
+                ToolOutput::success(output).with_display(data)
+            }
+            Err(e) => ToolOutput::error(e),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct KillProcessInput {
+    pid: i32,
+    #[serde(default)]
+    signal: Option<String>,
+    #[serde(default = "default_children")]
+    children: bool,
+}
+
+fn default_children() -> bool {
+    true
+}
+
+#[async_trait]
+impl Tool for KillProcessTool {
+    fn name(&self) -> &'static str {
+        "kill_process"
+    }
+
+    fn description(&self) -> String {
+        "Send TERM (default) or KILL to a process list_processes reports, and by default \
+         to its descendants. Only processes this conversation's bash commands started \
+         can be signalled. Reports which are still running a second later; send KILL to \
+         those that ignore TERM."
+            .to_string()
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "required": ["pid"],
+            "properties": {
+                "pid": {
+                    "type": "integer",
+                    "description": "Process id from list_processes"
+                },
+                "signal": {
+                    "type": "string",
+                    "enum": ["TERM", "KILL"],
+                    "description": "Signal to send. Default: TERM"
+                },
+                "children": {
+                    "type": "boolean",
+                    "description": "Also signal the process's descendants. Default: true"
+                }
+            }
+        })
+    }
+
+    async fn run(&self, input: Value, ctx: ToolContext) -> ToolOutput {
+        let input: KillProcessInput = match serde_json::from_value(input) {
+            Ok(i) => i,
+            Err(e) => return ToolOutput::error(format!("Invalid input: {e}")),
+        };
+        let signal = match input.signal.as_deref() {
+            None | Some("TERM") => KillSignal::Term,
+            Some("KILL") => KillSignal::Kill,
+            Some(other) => {
+                return ToolOutput::error(format!("Unknown signal '{other}': use TERM or KILL"))
+            }
+        };
+
+        let processes = match conversation_processes(&ctx).await {
+            Ok(processes) => processes,
+            Err(e) => return ToolOutput::error(e),
+        };
+        if !processes.iter().any(|p| p.pid == input.pid) {
+            return ToolOutput::error(format!(
+                "Process {} was not started by this conversation's bash commands, or has \
+                 exited. list_processes shows the ones that can be signalled.",
+                input.pid
+            ));
+        }
+        let targets = if input.children {
+            with_descendants(input.pid, &processes)
+        } else {
+            vec![input.pid]
+        };
+
+        let mut signalled = Vec::new();
+        let mut failed = Vec::new();
+        for pid in targets {
+            match send_signal(pid, signal) {
+                Ok(()) => signalled.push(pid),
+                Err(e) => failed.push(json!({ "pid": pid, "error": e.to_string() })),
+            }
+        }
+        tracing::info!(
+            conv_id = %ctx.conversation_id,
+            pid = input.pid,
+            signal = signal.as_str(),
+            count = signalled.len(),
+            "Signalled conversation processes"
+        );
+
+        tokio::time::sleep(SETTLE).await;
+        // A handle's own shell is a child the bash tool waits on; leave it
+        let leaders: Vec<i32> = ctx
+            .spawned_bash_handles()
+            .await
+            .iter()
+            .map(|h| h.pgid)
+            .collect();
+        for pid in signalled.iter().filter(|pid| !leaders.contains(pid)) {
+            reap(*pid);
+        }
+        let still_running: Vec<i32> = match conversation_processes(&ctx).await {
+            Ok(now) => signalled
+                .iter()
+                .copied()
+                .filter(|pid| now.iter().any(|p| p.pid == *pid))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let data = json!({
+            "signal": signal.as_str(),
+            "signalled": signalled,
+            "still_running": still_running,
+            "failed": failed,
+        });
+        let output = serde_json::to_string_pretty(&data).unwrap_or_default();
+        ToolOutput::success(output).with_display(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::bash::handle::HandleId;
+    use crate::tools::{BashHandleRegistry, BashTool, BrowserSessionManager};
+    use tokio_util::sync::CancellationToken;
+
+    fn row(pid: i32, parent: i32, group: i32, elapsed_seconds: u64) -> PsRow {
+        PsRow {
+            pid,
+            ppid: parent,
+            pgid: group,
+            elapsed_seconds,
+            args: format!("proc {pid}"),
+        }
+    }
+
+    #[test]
+    fn parses_ps_output() {
+        assert_eq!(parse_etime("05"), Some(5));
+        assert_eq!(parse_etime("01:05"), Some(65));
+        assert_eq!(parse_etime("02:01:05"), Some(7265));
+        assert_eq!(parse_etime("3-02:01:05"), Some(3 * 86_400 + 7265));
+        assert_eq!(parse_etime("soon"), None);
+
+        let output = "    1     0     1 Ss   3-02:01:05 /sbin/init\n\
+                      4242  4100  4100 Sl        00:12 node server.js --port 3000\n\
+                      4243  4242  4100 Z         00:03 [node] <defunct>\n\
+                      garbage line\n";
+        let rows = parse_ps(output);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].pid, 4242);
+        assert_eq!(rows[1].pgid, 4100);
+        assert_eq!(rows[1].elapsed_seconds, 12);
+        assert_eq!(rows[1].args, "node server.js --port 3000");
+    }
+
+    #[test]
+    fn owned_follows_groups_and_descendants() {
+        let handle = Handle::new_live(
+            "conv".to_string(),
+            HandleId::new("b-1"),
+            "npm run dev &".to_string(),
+            100,
+            100,
+            1024,
+        );
+        let handles = [handle];
+        let now = SystemTime::now() + Duration::from_secs(60);
+        let rows = [
+            // The orphaned server, its child in a group of its own, and a
+            // grandchild
+            row(101, 1, 100, 50),
+            row(200, 101, 200, 40),
+            row(201, 200, 200, 40),
+            // Unrelated, and a recycled group id older than the handle
+            row(300, 1, 300, 50),
+            row(400, 1, 100, 10_000),
+        ];
+
+        let pids: Vec<i32> = owned(&rows, &handles, now)
+            .iter()
+            .map(|(row, _)| row.pid)
+            .collect();
+        assert_eq!(pids, [101, 200, 201]);
+
+        let processes: Vec<OwnedProcess> = owned(&rows, &handles, now)
+            .into_iter()
+            .map(|(row, _)| OwnedProcess {
+                pid: row.pid,
+                ppid: row.ppid,
+                pgid: row.pgid,
+                elapsed_seconds: row.elapsed_seconds,
+                command: row.args,
+                handle: "b-1".to_string(),
+                handle_status: "exited",
+            })
+            .collect();
+        assert_eq!(with_descendants(200, &processes), [200, 201]);
+        assert_eq!(with_descendants(101, &processes), [101, 200, 201]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn kills_a_background_job_left_behind() {
+        let ctx = ToolContext::new(
+            CancellationToken::new(),
+            "test-conv".to_string(),
+            std::env::temp_dir(),
+            Arc::new(BrowserSessionManager::default()),
+            Arc::new(BashHandleRegistry::new()),
+            Arc::new(crate::llm::ModelRegistry::new_empty()),
+            crate::terminal::ActiveTerminals::new(),
+            Arc::new(crate::tools::TmuxRegistry::new()),
+            None,
+        );
+        let empty = ListProcessesTool.run(json!({}), ctx.clone()).await;
+        assert_eq!(empty.display_data.unwrap()["processes"], json!([]));
+
+        let spawn = BashTool
+            .run(
+                json!({"cmd": "sleep 97 >/dev/null 2>&1 &", "wait_seconds": 5}),
+                ctx.clone(),
+            )
+            .await;
+        assert!(spawn.success, "{}", spawn.output);
+
+        let listed = ListProcessesTool.run(json!({}), ctx.clone()).await;
+        assert!(listed.success, "{}", listed.output);
+        let data = listed.display_data.unwrap();
+        let sleep = data["processes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|p| p["command"].as_str().unwrap().contains("sleep 97"))
+            .unwrap_or_else(|| panic!("sleep not listed: {data}"))
+            .clone();
+        assert_eq!(sleep["handle"], "b-1");
+        assert_eq!(sleep["handle_status"], "exited");
+
+        let not_ours = KillProcessTool.run(json!({"pid": 1}), ctx.clone()).await;
+        assert!(!not_ours.success);
+
+        let killed = KillProcessTool
+            .run(json!({"pid": sleep["pid"], "signal": "KILL"}), ctx.clone())
+            .await;
+        assert!(killed.success, "{}", killed.output);
+        let data = killed.display_data.unwrap();
+        assert_eq!(data["signalled"], json!([sleep["pid"]]));
+        assert_eq!(data["still_running"], json!([]));
+    }
+}
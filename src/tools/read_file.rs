@@ -1,16 +1,27 @@
 //! `ReadFile` tool - read file contents with line numbers
 //!
 //! REQ-PROJ-002, REQ-PROJ-013: Explore mode file reading without bash
+//! REQ-FE-012: large files are outlined and read a chunk at a time
 
 use super::{Tool, ToolContext, ToolOutput};
+use crate::file_chunks;
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{json, Value};
 use std::fmt::Write as _;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 const DEFAULT_LIMIT: usize = 2000;
 
+/// Files bigger than this return an outline unless a range or chunk is asked for.
+const WHOLE_FILE_BYTES: usize = 256 * 1024;
+
+/// Target chunk size, grown for very large files so the outline stays short.
+const CHUNK_BYTES: usize = 32 * 1024;
+
+/// Most chunks in an outline.
+const MAX_CHUNKS: usize = 200;
+
 /// Read a file's contents with line numbers.
 pub struct ReadFileTool;
 
@@ -19,6 +30,34 @@ struct ReadFileInput {
     path: String,
     offset: Option<usize>,
     limit: Option<usize>,
+    chunk: Option<usize>,
+}
+
+/// The file's chunks for `read_file` (REQ-FE-012).
+fn file_chunks(path: &Path, text: &str) -> Vec<file_chunks::FileChunk> {
+    let target = CHUNK_BYTES.max(text.len().div_ceil(MAX_CHUNKS));
+    file_chunks::chunks(path, text, target)
+}
+
+/// What `read_file` returns for a file too big to read whole.
+fn outline(shown: &str, path: &Path, text: &str) -> String {
+    let mut output = format!(
+        "'{shown}' has {} lines ({} KB), too many to read at once. \
+         Read one chunk with `chunk`, or a line range with `offset` and `limit`.\n",
+        text.lines().count(),
+        text.len() / 1024
+    );
+    for chunk in file_chunks(path, text) {
+        let _ = write!(
+            output,
+            "\nchunk {}: lines {}-{}",
+            chunk.index, chunk.start_line, chunk.end_line
+        );
+        if !chunk.symbols.is_empty() {
+            let _ = write!(output, ": {}", chunk.symbols.join(", "));
+        }
+    }
+    output
 }
 
 /// Resolve a path relative to `working_dir`. Absolute paths are used as-is;
@@ -49,7 +88,8 @@ impl Tool for ReadFileTool {
     }
 
     fn description(&self) -> String {
-        "Read a file's contents. Returns numbered lines. Use offset and limit for large files."
+        "Read a file's contents. Returns numbered lines. Files over 256 KB return an outline of \
+         numbered chunks instead; read one with chunk, or a line range with offset and limit."
             .to_string()
     }
 
@@ -69,6 +109,10 @@ impl Tool for ReadFileTool {
                 "limit": {
                     "type": "integer",
                     "description": "Maximum number of lines to return. Default: 2000"
+                },
+                "chunk": {
+                    "type": "integer",
+                    "description": "Chunk from a large file's outline. Overrides offset and limit"
                 }
             }
         })
@@ -106,8 +150,30 @@ impl Tool for ReadFileTool {
             Err(msg) => return ToolOutput::error(msg),
         };
 
-        let offset = input.offset.unwrap_or(1).max(1); // 1-based, minimum 1
-        let limit = input.limit.unwrap_or(DEFAULT_LIMIT);
+        let (offset, limit) = match input.chunk {
+            Some(index) => {
+                let chunks = file_chunks(&resolved, &text);
+                let Some(chunk) = index.checked_sub(1).and_then(|i| chunks.get(i)) else {
+                    return ToolOutput::error(format!(
+                        "'{}' has {} chunks; there is no chunk {index}",
+                        input.path,
+                        chunks.len()
+                    ));
+                };
+                (chunk.start_line, chunk.end_line + 1 - chunk.start_line)
+            }
+            None if text.len() > WHOLE_FILE_BYTES
+                && input.offset.is_none()
+                && input.limit.is_none() =>
+            {
+                return ToolOutput::success(outline(&input.path, &resolved, &text));
+            }
+            // 1-based, minimum 1
+            None => (
+                input.offset.unwrap_or(1).max(1),
+                input.limit.unwrap_or(DEFAULT_LIMIT),
+            ),
+        };
 
         let lines: Vec<&str> = text.lines().collect();
         let total_lines = lines.len();
@@ -260,4 +326,47 @@ mod tests {
         assert!(result.success);
         assert!(result.output.contains("more lines not shown"));
     }
+
+    #[tokio::test]
+    async fn test_read_file_large_file_outline_and_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let content: String = (0..8000).fold(String::new(), |mut s, i| {
+            use std::fmt::Write;
+            let _ = writeln!(s, "fn f{i}() {{\n    println!(\"{i}\");\n}}\n");
+            s
+        });
+        assert!(content.len() > WHOLE_FILE_BYTES);
+        std::fs::write(dir.path().join("big.rs"), &content).unwrap();
+
+        let tool = ReadFileTool;
+        let outline = tool
+            .run(
+                json!({"path": "big.rs"}),
+                test_context(dir.path().to_path_buf()),
+            )
+            .await;
+        assert!(outline.success);
+        assert!(outline.output.contains("chunk 1: lines 1-"));
+        assert!(outline.output.contains("function f0"));
+        assert!(!outline.output.contains("println"));
+
+        let chunk = tool
+            .run(
+                json!({"path": "big.rs", "chunk": 2}),
+                test_context(dir.path().to_path_buf()),
+            )
+            .await;
+        assert!(chunk.success);
+        let first = chunk.output.trim_start().split('\t').nth(1).unwrap();
+        assert!(first.starts_with("fn f"), "{first}");
+        assert!(!chunk.output.contains("\tfn f0()"));
+
+        let missing = tool
+            .run(
+                json!({"path": "big.rs", "chunk": 999}),
+                test_context(dir.path().to_path_buf()),
+            )
+            .await;
+        assert!(!missing.success);
+    }
 }
@@ -255,6 +255,7 @@ fn run_search(args: SearchArgs) -> ToolOutput {
         .git_ignore(true)
         .git_global(true)
         .git_exclude(true)
+        .add_custom_ignore_filename(crate::phoenixignore::FILE_NAME)
         .filter_entry(|entry| {
             if entry.file_type().is_some_and(|ft| ft.is_dir()) {
                 if let Some(name) = entry.file_name().to_str() {
@@ -27,7 +27,8 @@ impl Tool for SkillTool {
     fn description(&self) -> String {
         "Invoke a skill by name. Skills are project-specific or user-level \
          capabilities discovered from .claude/skills/ and .agents/skills/ \
-         directories. Use this when a skill would help accomplish the current task."
+         directories and the user's ~/.phoenix-ide/skills/ library. Use this when \
+         a skill would help accomplish the current task."
             .to_string()
     }
 
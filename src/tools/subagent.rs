@@ -178,6 +178,10 @@ impl Tool for SpawnAgentsTool {
                     "minItems": 1,
                     "maxItems": 10,
                     "description": "List of tasks to execute in parallel (max 10)"
+                },
+                "batch": {
+                    "type": "boolean",
+                    "description": "Send each sub-agent's first request through the provider's batch API at roughly half the cost. Batches can take minutes to return, so use this only when the results are not urgent. Later turns are sent normally."
                 }
             }
         })
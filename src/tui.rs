@@ -0,0 +1,864 @@
+//! Terminal UI mode (REQ-CLI-010): `phoenix-ide --tui`
+//!
+//! A ratatui client for a running Phoenix server, local or remote. The left
+//! pane lists conversations; the right pane follows the selected one over
+//! its SSE stream, showing streamed text, tool calls and results, the
+//! current state, and context-window usage. Messages can be sent and turns
+//! cancelled from the keyboard.
+//!
+//! Talks to the server only over HTTP, so it needs no database and can run
+//! on a different machine: `--api-url` or `PHOENIX_API_URL` picks the
+//! server, `PHOENIX_PASSWORD` supplies the Bearer token.
+
+use std::collections::HashMap;
+use std::io;
+use std::time::Duration;
+
+use crossterm::{
+    event::{self, Event as TermEvent, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
+};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::api_client::{tool_summary, truncate_line, SseFrame, SseParser};
+
+/// How often the conversation list is re-fetched.
+const LIST_REFRESH: Duration = Duration::from_secs(5);
+/// Lines of each tool result shown under its call.
+const TOOL_RESULT_LINES: usize = 4;
+/// Used when the server does not report the model's window.
+const DEFAULT_CONTEXT_WINDOW: u64 = 200_000;
+
+/// Server connection settings.
+#[derive(Debug, Clone)]
+pub struct TuiConfig {
+    pub api_url: String,
+    pub password: Option<String>,
+}
+
+impl TuiConfig {
+    /// `--api-url`, else `PHOENIX_API_URL`, else this machine on
+    /// `PHOENIX_PORT` (default 8000).
+    pub fn new(api_url: Option<String>) -> Self {
+        let api_url = api_url
+            .or_else(|| std::env::var("PHOENIX_API_URL").ok())
+            .unwrap_or_else(|| {
+                let port = std::env::var("PHOENIX_PORT").unwrap_or_else(|_| "8000".to_string());
+                format!("http://localhost:{port}")
+            });
+        Self {
+            api_url: api_url.trim_end_matches('/').to_string(),
+            password: std::env::var("PHOENIX_PASSWORD")
+                .ok()
+                .filter(|p| !p.is_empty()),
+        }
+    }
+}
+
+// ============================================================
+// HTTP
+// ============================================================
+
+#[derive(Clone)]
+struct Api {
+    http: reqwest::Client,
+    config: TuiConfig,
+}
+
+impl Api {
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let req = self
+            .http
+            .request(method, format!("{}{path}", self.config.api_url));
+        match &self.config.password {
+            Some(password) => req.bearer_auth(password),
+            None => req,
+        }
+    }
+
+    async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response, String> {
+        let resp = req.send().await.map_err(|e| e.to_string())?;
+        if resp.status().is_success() {
+            return Ok(resp);
+        }
+        let status = resp.status();
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        let message = body["error"].as_str().unwrap_or("request failed");
+        Err(format!("{message} ({})", status.as_u16()))
+    }
+
+    async fn get(&self, path: &str) -> Result<Value, String> {
+        let resp = self.send(self.request(reqwest::Method::GET, path)).await?;
+        resp.json().await.map_err(|e| e.to_string())
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> Result<Value, String> {
+        let req = self.request(reqwest::Method::POST, path).json(body);
+        self.send(req)
+            .await?
+            .json()
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+// ============================================================
+// Updates
+// ============================================================
+
+/// Everything the UI loop reacts to: keys from the input thread, and
+/// results from the network tasks.
+enum Update {
+    Key(KeyEvent),
+    Resize,
+    Conversations(Vec<Row>),
+    ContextWindows(HashMap<String, u64>),
+    Stream {
+        conv_id: String,
+        frame: SseFrame,
+    },
+    StreamEnded {
+        conv_id: String,
+        error: Option<String>,
+    },
+    Status(String),
+}
+
+type Tx = mpsc::UnboundedSender<Update>;
+
+/// One entry in the conversation list.
+struct Row {
+    id: String,
+    slug: String,
+    display_state: String,
+    model: Option<String>,
+}
+
+fn parse_rows(body: &Value) -> Vec<Row> {
+    body["conversations"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|c| Row {
+            id: c["id"].as_str().unwrap_or_default().to_string(),
+            slug: c["slug"]
+                .as_str()
+                .or_else(|| c["id"].as_str())
+                .unwrap_or_default()
+                .to_string(),
+            display_state: c["display_state"].as_str().unwrap_or_default().to_string(),
+            model: c["model"].as_str().map(str::to_string),
+        })
+        .collect()
+}
+
+fn spawn_list_refresh(api: Api, tx: Tx) {
+    tokio::spawn(async move {
+        if let Ok(models) = api.get("/api/models").await {
+            let windows = models["models"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|m| {
+                    let window = m["context_window"].as_u64()?;
+                    Some((m["id"].as_str()?.to_string(), window))
+                })
+                .collect();
+            let _ = tx.send(Update::ContextWindows(windows));
+        }
+        loop {
+            let update = match api.get("/api/conversations").await {
+                Ok(body) => Update::Conversations(parse_rows(&body)),
+                Err(e) => Update::Status(format!("Cannot list conversations: {e}")),
+            };
+            if tx.send(update).is_err() {
+                return;
+            }
+            tokio::time::sleep(LIST_REFRESH).await;
+        }
+    });
+}
+
+fn spawn_stream(api: Api, conv_id: String, tx: Tx) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let path = format!("/api/conversations/{conv_id}/stream");
+        let error = match api.send(api.request(reqwest::Method::GET, &path)).await {
+            Ok(mut resp) => {
+                let mut parser = SseParser::default();
+                loop {
+                    match resp.chunk().await {
+                        Ok(Some(chunk)) => parser.push(&chunk),
+                        Ok(None) => break None,
+                        Err(e) => break Some(e.to_string()),
+                    }
+                    while let Some(frame) = parser.next_frame() {
+                        let conv_id = conv_id.clone();
+                        if tx.send(Update::Stream { conv_id, frame }).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            Err(e) => Some(e),
+        };
+        let _ = tx.send(Update::StreamEnded { conv_id, error });
+    })
+}
+
+fn spawn_post(api: Api, path: String, body: Value, tx: Tx) {
+    tokio::spawn(async move {
+        if let Err(e) = api.post(&path, &body).await {
+            let _ = tx.send(Update::Status(e));
+        }
+    });
+}
+
+/// Keys are read on a plain thread: crossterm's reader blocks.
+fn spawn_input_thread(tx: Tx) {
+    std::thread::spawn(move || loop {
+        if !event::poll(Duration::from_millis(200)).unwrap_or(false) {
+            if tx.is_closed() {
+                return;
+            }
+            continue;
+        }
+        let update = match event::read() {
+            Ok(TermEvent::Key(key)) if key.kind == KeyEventKind::Press => Update::Key(key),
+            Ok(TermEvent::Resize(..)) => Update::Resize,
+            Ok(_) => continue,
+            Err(_) => return,
+        };
+        if tx.send(update).is_err() {
+            return;
+        }
+    });
+}
+
+// ============================================================
+// State
+// ============================================================
+
+/// A rendered piece of the transcript.
+enum Entry {
+    User(String),
+    Text(String),
+    ToolCall(String),
+    ToolResult {
+        lines: Vec<String>,
+        more: usize,
+        is_error: bool,
+    },
+    Error(String),
+    Note(String),
+}
+
+fn entries_for(message: &Value) -> Vec<Entry> {
+    let content = &message["content"];
+    match message["message_type"].as_str() {
+        Some("user") => vec![Entry::User(
+            content["text"].as_str().unwrap_or_default().to_string(),
+        )],
+        Some("agent") => content
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|block| match block["type"].as_str() {
+                Some("text") => Some(Entry::Text(block["text"].as_str()?.to_string())),
+                Some("tool_use") => Some(Entry::ToolCall(format!(
+                    "⏺ {}({})",
+                    block["name"].as_str().unwrap_or("tool"),
+                    tool_summary(&block["input"])
+                ))),
+                _ => None,
+            })
+            .collect(),
+        Some("tool") => {
+            let output = content["content"].as_str().unwrap_or_default();
+            let all: Vec<&str> = output.lines().collect();
+            vec![Entry::ToolResult {
+                lines: all
+                    .iter()
+                    .take(TOOL_RESULT_LINES)
+                    .map(|l| truncate_line(l))
+                    .collect(),
+                more: all.len().saturating_sub(TOOL_RESULT_LINES),
+                is_error: content["is_error"].as_bool() == Some(true),
+            }]
+        }
+        Some("error") => vec![Entry::Error(
+            content["message"].as_str().unwrap_or("error").to_string(),
+        )],
+        Some("system" | "continuation") => vec![Entry::Note(
+            content["text"].as_str().unwrap_or_default().to_string(),
+        )],
+        _ => Vec::new(),
+    }
+}
+
+/// Tokens used by the turn that produced `message`, if it reports usage.
+fn context_used(message: &Value) -> Option<u64> {
+    let usage = message.get("usage_data").filter(|u| u.is_object())?;
+    Some(
+        [
+            "input_tokens",
+            "output_tokens",
+            "cache_creation_tokens",
+            "cache_read_tokens",
+        ]
+        .iter()
+        .filter_map(|k| usage[*k].as_u64())
+        .sum(),
+    )
+}
+
+/// The conversation shown in the right pane.
+struct Open {
+    id: String,
+    slug: String,
+    model: Option<String>,
+    entries: Vec<Entry>,
+    /// Text streamed for the agent message that has not arrived yet.
+    streaming: String,
+    display_state: String,
+    /// Most recent tool call still waiting for its result.
+    active_tool: Option<String>,
+    context_used: u64,
+    /// Lines scrolled up from the bottom.
+    scroll: u16,
+    stream: JoinHandle<()>,
+}
+
+impl Open {
+    fn apply(&mut self, frame: SseFrame) {
+        let data = frame.data;
+        match frame.event.as_str() {
+            "init" => {
+                self.entries = data["messages"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .flat_map(entries_for)
+                    .collect();
+                self.streaming.clear();
+                self.display_state = data["display_state"].as_str().unwrap_or_default().into();
+                self.context_used = data["context_window_size"].as_u64().unwrap_or(0);
+            }
+            "message" => {
+                let message = &data["message"];
+                match message["message_type"].as_str() {
+                    Some("agent") => {
+                        self.streaming.clear();
+                        self.active_tool = message["content"]
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .filter(|b| b["type"] == "tool_use")
+                            .filter_map(|b| b["name"].as_str())
+                            .next_back()
+                            .map(str::to_string);
+                    }
+                    Some("tool") => self.active_tool = None,
+                    _ => {}
+                }
+                if let Some(used) = context_used(message) {
+                    self.context_used = used;
+                }
+                self.entries.extend(entries_for(message));
+            }
+            "token" => self
+                .streaming
+                .push_str(data["text"].as_str().unwrap_or_default()),
+            "state_change" => {
+                if let Some(state) = data["display_state"].as_str() {
+                    self.display_state = state.to_string();
+                }
+            }
+            "agent_done" => {
+                self.active_tool = None;
+                self.streaming.clear();
+            }
+            "error" => {
+                let text = data["message"].as_str().unwrap_or("error").to_string();
+                self.entries.push(Entry::Error(text));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(PartialEq, Eq)]
+enum Focus {
+    List,
+    Compose,
+}
+
+struct App {
+    api: Api,
+    tx: Tx,
+    rows: Vec<Row>,
+    list: ListState,
+    context_windows: HashMap<String, u64>,
+    open: Option<Open>,
+    focus: Focus,
+    input: String,
+    status: String,
+}
+
+impl App {
+    fn selected(&self) -> Option<&Row> {
+        self.list.selected().and_then(|i| self.rows.get(i))
+    }
+
+    fn set_rows(&mut self, rows: Vec<Row>) {
+        // Keep the selection on the same conversation across refreshes.
+        let selected_id = self.selected().map(|r| r.id.clone());
+        self.rows = rows;
+        let index = selected_id
+            .and_then(|id| self.rows.iter().position(|r| r.id == id))
+            .or((!self.rows.is_empty()).then_some(0));
+        self.list.select(index);
+    }
+
+    fn open_selected(&mut self) {
+        let Some(row) = self.selected() else {
+            return;
+        };
+        let (id, slug, model) = (row.id.clone(), row.slug.clone(), row.model.clone());
+        if let Some(previous) = self.open.take() {
+            previous.stream.abort();
+        }
+        let stream = spawn_stream(self.api.clone(), id.clone(), self.tx.clone());
+        self.open = Some(Open {
+            id,
+            slug,
+            model,
+            entries: Vec::new(),
+            streaming: String::new(),
+            display_state: "connecting".to_string(),
+            active_tool: None,
+            context_used: 0,
+            scroll: 0,
+            stream,
+        });
+    }
+
+    fn send_message(&mut self) {
+        let text = std::mem::take(&mut self.input);
+        let text = text.trim();
+        let Some(open) = &self.open else {
+            return;
+        };
+        if text.is_empty() {
+            return;
+        }
+        let body = json!({ "text": text, "message_id": uuid::Uuid::new_v4().to_string() });
+        let path = format!("/api/conversations/{}/chat", open.id);
+        spawn_post(self.api.clone(), path, body, self.tx.clone());
+    }
+
+    fn cancel(&mut self) {
+        if let Some(open) = &self.open {
+            let path = format!("/api/conversations/{}/cancel", open.id);
+            spawn_post(self.api.clone(), path, json!({}), self.tx.clone());
+            self.status = format!("Cancelling {}", open.slug);
+        }
+    }
+
+    fn scroll(&mut self, up: bool, by: u16) {
+        if let Some(open) = &mut self.open {
+            open.scroll = if up {
+                open.scroll.saturating_add(by)
+            } else {
+                open.scroll.saturating_sub(by)
+            };
+        }
+    }
+
+    /// Returns `true` to quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return true;
+        }
+        if self.focus == Focus::Compose {
+            match key.code {
+                KeyCode::Esc => self.focus = Focus::List,
+                KeyCode::Enter => {
+                    self.send_message();
+                    self.focus = Focus::List;
+                }
+                KeyCode::Backspace => {
+                    self.input.pop();
+                }
+                KeyCode::Char(c) => self.input.push(c),
+                _ => {}
+            }
+            return false;
+        }
+        let len = self.rows.len();
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Down | KeyCode::Char('j') if len > 0 => {
+                let next = self.list.selected().map_or(0, |i| (i + 1).min(len - 1));
+                self.list.select(Some(next));
+            }
+            KeyCode::Up | KeyCode::Char('k') if len > 0 => {
+                let prev = self.list.selected().map_or(0, |i| i.saturating_sub(1));
+                self.list.select(Some(prev));
+            }
+            KeyCode::Enter => self.open_selected(),
+            KeyCode::Char('i') if self.open.is_some() => self.focus = Focus::Compose,
+            KeyCode::Char('x') => self.cancel(),
+            KeyCode::PageUp => self.scroll(true, 10),
+            KeyCode::PageDown => self.scroll(false, 10),
+            KeyCode::End => self.scroll(false, u16::MAX),
+            _ => {}
+        }
+        false
+    }
+
+    fn handle(&mut self, update: Update) -> bool {
+        match update {
+            Update::Key(key) => return self.handle_key(key),
+            Update::Resize => {}
+            Update::Conversations(rows) => self.set_rows(rows),
+            Update::ContextWindows(windows) => self.context_windows = windows,
+            Update::Stream { conv_id, frame } => {
+                if let Some(open) = self.open.as_mut().filter(|o| o.id == conv_id) {
+                    open.apply(frame);
+                }
+            }
+            Update::StreamEnded { conv_id, error } => {
+                if let Some(open) = self.open.as_mut().filter(|o| o.id == conv_id) {
+                    open.display_state = "disconnected".to_string();
+                    self.status = match error {
+                        Some(e) => format!("Stream for {} ended: {e}", open.slug),
+                        None => format!("Stream for {} closed; press Enter to reopen", open.slug),
+                    };
+                }
+            }
+            Update::Status(status) => self.status = status,
+        }
+        false
+    }
+}
+
+// ============================================================
+// Drawing
+// ============================================================
+
+fn state_color(state: &str) -> Color {
+    match state {
+        "working" => Color::Yellow,
+        "error" => Color::Red,
+        "terminal" => Color::DarkGray,
+        "awaiting_approval" => Color::Magenta,
+        _ => Color::Green,
+    }
+}
+
+/// Hard-wrap `text` to `width` columns, one `Line` per row.
+fn wrap(text: &str, width: usize, style: Style, out: &mut Vec<Line<'static>>) {
+    let width = width.max(1);
+    for raw in text.lines() {
+        let chars: Vec<char> = raw.chars().collect();
+        if chars.is_empty() {
+            out.push(Line::default());
+        }
+        for chunk in chars.chunks(width) {
+            out.push(Line::styled(chunk.iter().collect::<String>(), style));
+        }
+    }
+}
+
+fn transcript(open: &Open, width: usize) -> Vec<Line<'static>> {
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines = Vec::new();
+    for entry in &open.entries {
+        match entry {
+            Entry::User(text) => {
+                lines.push(Line::default());
+                let style = Style::default().add_modifier(Modifier::BOLD);
+                wrap(&format!("› {text}"), width, style, &mut lines);
+            }
+            Entry::Text(text) => wrap(text, width, Style::default(), &mut lines),
+            Entry::ToolCall(call) => {
+                wrap(call, width, Style::default().fg(Color::Cyan), &mut lines);
+            }
+            Entry::ToolResult {
+                lines: output,
+                more,
+                is_error,
+            } => {
+                let style = if *is_error {
+                    Style::default().fg(Color::Red)
+                } else {
+                    dim
+                };
+                for (i, line) in output.iter().enumerate() {
+                    let gutter = if i == 0 { "  ⎿ " } else { "    " };
+                    wrap(&format!("{gutter}{line}"), width, style, &mut lines);
+                }
+                if *more > 0 {
+                    wrap(&format!("    … {more} more lines"), width, dim, &mut lines);
+                }
+            }
+            Entry::Error(text) => {
+                wrap(
+                    &format!("✗ {text}"),
+                    width,
+                    Style::default().fg(Color::Red),
+                    &mut lines,
+                );
+            }
+            Entry::Note(text) => wrap(text, width, dim, &mut lines),
+        }
+    }
+    if !open.streaming.is_empty() {
+        wrap(&open.streaming, width, Style::default(), &mut lines);
+    }
+    lines
+}
+
+fn draw_list(f: &mut Frame, app: &mut App, area: Rect) {
+    let items: Vec<ListItem> = app
+        .rows
+        .iter()
+        .map(|row| {
+            let marker = Span::styled("● ", Style::default().fg(state_color(&row.display_state)));
+            ListItem::new(Line::from(vec![marker, Span::raw(row.slug.clone())]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Conversations "),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    f.render_stateful_widget(list, area, &mut app.list);
+}
+
+fn draw_conversation(f: &mut Frame, app: &App, area: Rect) {
+    let Some(open) = &app.open else {
+        let hint = Paragraph::new("Select a conversation and press Enter.")
+            .block(Block::default().borders(Borders::ALL));
+        f.render_widget(hint, area);
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(3),
+        ])
+        .split(area);
+
+    // Header: state, tool activity, and context-window usage.
+    let window = open
+        .model
+        .as_ref()
+        .and_then(|m| app.context_windows.get(m).copied())
+        .unwrap_or(DEFAULT_CONTEXT_WINDOW);
+    #[allow(clippy::cast_precision_loss)] // token counts are far below 2^52
+    let ratio = (open.context_used as f64 / window as f64).clamp(0.0, 1.0);
+    let mut activity = open.display_state.clone();
+    if let Some(tool) = &open.active_tool {
+        activity = format!("{activity} · running {tool}");
+    }
+    let title = format!(" {} · {activity} ", open.slug);
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .gauge_style(Style::default().fg(if ratio > 0.8 { Color::Red } else { Color::Blue }))
+        .ratio(ratio)
+        .label(format!(
+            "context {}k / {}k",
+            open.context_used / 1000,
+            window / 1000
+        ));
+    f.render_widget(gauge, chunks[0]);
+
+    // Transcript, pinned to the bottom unless scrolled.
+    let body = chunks[1];
+    let width = usize::from(body.width.saturating_sub(2));
+    let height = usize::from(body.height.saturating_sub(2));
+    let lines = transcript(open, width);
+    let max_scroll = lines.len().saturating_sub(height);
+    let scroll = usize::from(open.scroll).min(max_scroll);
+    let start = max_scroll - scroll;
+    let visible: Vec<Line> = lines.into_iter().skip(start).take(height).collect();
+    let transcript = Paragraph::new(visible).block(Block::default().borders(Borders::ALL));
+    f.render_widget(transcript, body);
+
+    let (input_title, input_style) = if app.focus == Focus::Compose {
+        (
+            " Message (Enter send, Esc back) ",
+            Style::default().fg(Color::Yellow),
+        )
+    } else {
+        (
+            " i message · x cancel · PgUp/PgDn scroll ",
+            Style::default(),
+        )
+    };
+    let input = Paragraph::new(app.input.as_str())
+        .style(input_style)
+        .block(Block::default().borders(Borders::ALL).title(input_title));
+    f.render_widget(input, chunks[2]);
+}
+
+fn draw(f: &mut Frame, app: &mut App) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(1)])
+        .split(f.area());
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(28), Constraint::Percentage(72)])
+        .split(rows[0]);
+    draw_list(f, app, columns[0]);
+    draw_conversation(f, app, columns[1]);
+
+    let status = if app.status.is_empty() {
+        format!(
+            "{} · ↑↓ select · Enter open · q quit",
+            app.api.config.api_url
+        )
+    } else {
+        app.status.clone()
+    };
+    f.render_widget(
+        Paragraph::new(status).style(Style::default().fg(Color::DarkGray)),
+        rows[1],
+    );
+}
+
+// ============================================================
+// Entry point
+// ============================================================
+
+/// Run the TUI until the user quits.
+pub async fn run(config: TuiConfig) -> io::Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let api = Api {
+        http: reqwest::Client::new(),
+        config,
+    };
+    spawn_list_refresh(api.clone(), tx.clone());
+    spawn_input_thread(tx.clone());
+
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+    let mut app = App {
+        api,
+        tx,
+        rows: Vec::new(),
+        list: ListState::default(),
+        context_windows: HashMap::new(),
+        open: None,
+        focus: Focus::List,
+        input: String::new(),
+        status: String::new(),
+    };
+    let result = async {
+        terminal.draw(|f| draw(f, &mut app))?;
+        while let Some(update) = rx.recv().await {
+            if app.handle(update) {
+                break;
+            }
+            terminal.draw(|f| draw(f, &mut app))?;
+        }
+        io::Result::Ok(())
+    }
+    .await;
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open() -> Open {
+        Open {
+            id: "c-1".to_string(),
+            slug: "blue-river".to_string(),
+            model: None,
+            entries: Vec::new(),
+            streaming: String::new(),
+            display_state: "connecting".to_string(),
+            active_tool: None,
+            context_used: 0,
+            scroll: 0,
+            stream: tokio::spawn(async {}),
+        }
+    }
+
+    fn frame(event: &str, data: Value) -> SseFrame {
+        SseFrame {
+            event: event.to_string(),
+            data,
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_events_track_tool_activity_and_context() {
+        let mut open = open();
+        open.apply(frame(
+            "init",
+            json!({ "messages": [], "display_state": "idle", "context_window_size": 1200 }),
+        ));
+        assert_eq!(open.context_used, 1200);
+
+        open.apply(frame("token", json!({ "text": "Let me look" })));
+        assert_eq!(open.streaming, "Let me look");
+
+        let agent = json!({ "message": {
+            "message_type": "agent",
+            "content": [
+                { "type": "text", "text": "Let me look" },
+                { "type": "tool_use", "name": "bash", "input": { "command": "ls" } },
+            ],
+            "usage_data": { "input_tokens": 2000, "output_tokens": 50 },
+        }});
+        open.apply(frame("message", agent));
+        assert!(open.streaming.is_empty());
+        assert_eq!(open.active_tool.as_deref(), Some("bash"));
+        assert_eq!(open.context_used, 2050);
+        assert!(matches!(&open.entries[1], Entry::ToolCall(call) if call == "⏺ bash(ls)"));
+
+        let result = json!({ "message": {
+            "message_type": "tool",
+            "content": { "content": "a\nb\nc\nd\ne\nf", "is_error": false },
+        }});
+        open.apply(frame("message", result));
+        assert!(open.active_tool.is_none());
+        assert!(matches!(
+            &open.entries[2],
+            Entry::ToolResult { more: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn wrap_splits_long_lines_by_width() {
+        let mut lines = Vec::new();
+        wrap("abcdefg\n\nxy", 3, Style::default(), &mut lines);
+        let text: Vec<String> = lines.iter().map(ToString::to_string).collect();
+        assert_eq!(text, ["abc", "def", "g", "", "xy"]);
+    }
+}
@@ -0,0 +1,48 @@
+// Phoenix service worker: Web Push notifications only (REQ-API-024).
+// No fetch handler and no caches, so it never serves stale app code.
+
+self.addEventListener('install', () => {
+  self.skipWaiting();
+});
+
+self.addEventListener('activate', (event) => {
+  event.waitUntil(self.clients.claim());
+});
+
+self.addEventListener('push', (event) => {
+  if (!event.data) return;
+  let payload;
+  try {
+    payload = event.data.json();
+  } catch {
+    return;
+  }
+  const url = payload.url || '/';
+  event.waitUntil(
+    self.clients.matchAll({ type: 'window', includeUncontrolled: true }).then((windows) => {
+      // The user is already looking at this conversation
+      const watching = windows.some(
+        (w) => w.focused && w.visibilityState === 'visible' && new URL(w.url).pathname === url,
+      );
+      if (watching) return undefined;
+      return self.registration.showNotification(payload.title || 'Phoenix', {
+        body: payload.body || '',
+        tag: payload.tag,
+        icon: '/phoenix.svg',
+        data: { url },
+      });
+    }),
+  );
+});
+
+self.addEventListener('notificationclick', (event) => {
+  event.notification.close();
+  const url = (event.notification.data && event.notification.data.url) || '/';
+  event.waitUntil(
+    self.clients.matchAll({ type: 'window', includeUncontrolled: true }).then((windows) => {
+      const open = windows.find((w) => new URL(w.url).pathname === url);
+      if (open) return open.focus();
+      return self.clients.openWindow(url);
+    }),
+  );
+});
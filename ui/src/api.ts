@@ -37,6 +37,7 @@ import {
   type ChainQaCompletedData,
   type ChainQaFailedData,
 } from './sseSchemas';
+import { getClientId } from './utils/clientId';
 export type { ChainView } from './generated/ChainView';
 export type { ChainMemberSummary } from './generated/ChainMemberSummary';
 export type { ChainPosition } from './generated/ChainPosition';
@@ -89,8 +90,44 @@ export interface Conversation {
    *  this absent or null. The sidebar falls back to the root conversation's
    *  slug when this is null/absent. */
   chain_name?: string | null;
+  /** Extended-thinking budget in tokens (REQ-LLM-014); absent when off. */
+  thinking_budget?: number | null;
+  /** Conversation template this conversation was created from (REQ-API-018). */
+  template?: string | null;
+  /** Tools switched off for this conversation (REQ-BED-039); absent when none. */
+  disabled_tools?: string[];
+  /** How much history each LLM request carries (REQ-BED-046). */
+  history_window?: HistoryWindow;
+  /** List endpoints only (REQ-API-026): start of the latest user or agent
+   *  message, time in the current state, and cost including sub-agents. */
+  last_message_preview?: string | null;
+  state_duration_ms?: number;
+  cost_usd?: number;
+  /** List endpoints only (REQ-BED-056): a question is still unanswered after
+   *  its follow-up reminder went out. */
+  needs_input?: boolean;
 }
 
+/** Sorting and filters for the conversation list (REQ-API-027). Dates are
+ *  RFC 3339; `since` is inclusive and `until` exclusive, on creation time. */
+export interface ConversationListParams {
+  sort?: 'created' | 'updated' | 'cost' | 'message_count';
+  order?: 'asc' | 'desc';
+  /** State type, e.g. `idle` or `error`. */
+  state?: string;
+  cwd_prefix?: string;
+  model?: string;
+  since?: string;
+  until?: string;
+}
+
+/** History window strategy (REQ-BED-046). A turn starts at each user message. */
+export type HistoryWindow =
+  | { strategy: 'full' }
+  | { strategy: 'last_turns'; turns: number }
+  | { strategy: 'summary_recent'; turns: number }
+  | { strategy: 'token_budget'; tokens: number };
+
 export interface Project {
   id: string;
   canonical_path: string;
@@ -99,6 +136,30 @@ export interface Project {
   conversation_count: number;
 }
 
+/** Post-edit verification settings for a project (REQ-BED-037). */
+export interface VerifySettings {
+  command: string;
+  max_attempts: number;
+}
+
+/** A tool that can be switched off per conversation (REQ-BED-039). */
+export interface ToolEntry {
+  name: string;
+  description: string;
+  source: 'builtin' | 'plugin' | 'mcp';
+}
+
+/** Preset applied when creating a conversation (REQ-API-018). */
+export interface ConversationTemplate {
+  name: string;
+  description: string;
+  system_prompt: string;
+  /** Allowed tool names; null keeps the mode's full tool set. */
+  tools: string[] | null;
+  /** Model used when the create request names none. */
+  model: string | null;
+}
+
 export interface PendingSubAgent {
   agent_id: string;
   task: string;
@@ -142,6 +203,15 @@ export type ConversationState =
   | { type: 'cancelling_sub_agents'; pending: PendingSubAgent[] }
   | { type: 'awaiting_task_approval'; title: string; priority: string; plan: string }
   | { type: 'awaiting_user_response'; questions: UserQuestion[] }
+  | { type: 'awaiting_user_guidance'; reason: string }
+  /** The agent asked one question: in prose at the end of its turn
+   *  (REQ-BED-057), otherwise idle, or with `ask_user` (REQ-AUQ-009), when
+   *  `tool_use_id` names the call waiting for the answer */
+  | { type: 'awaiting_user_input'; question: string; options?: string[]; tool_use_id?: string }
+  /** Several models answered one message; the user picks which answer the
+   *  conversation continues from (REQ-BED-058) */
+  | { type: 'awaiting_council_choice'; candidates: CouncilCandidate[] }
+  | { type: 'awaiting_patch_review'; patch: StagedPatch; current_tool: ToolCall }
   | { type: 'context_exhausted'; summary: string }
   | { type: 'error'; message: string }
   | { type: 'awaiting_recovery'; message: string; recovery_kind: string }
@@ -157,10 +227,30 @@ export function getDisplayState(stateType: string | undefined): 'idle' | 'workin
     case 'context_exhausted': return 'awaiting_approval';
     case 'awaiting_task_approval': return 'awaiting_approval';
     case 'awaiting_user_response': return 'awaiting_approval';
+    case 'awaiting_user_guidance': return 'awaiting_approval';
+    case 'awaiting_user_input': return 'awaiting_approval';
+    case 'awaiting_council_choice': return 'awaiting_approval';
+    case 'awaiting_patch_review': return 'awaiting_approval';
     default: return stateType ? 'working' : 'idle';
   }
 }
 
+/** One model's answer to a council message (REQ-BED-058) */
+export interface CouncilCandidate {
+  id: string;
+  model: string;
+  content: ContentBlock[];
+  tool_calls: ToolCall[];
+  /** Set when the model failed; such a candidate cannot be chosen */
+  error?: string;
+}
+
+/** A patch held for the user to apply or reject (REQ-PATCH-010) */
+export interface StagedPatch {
+  path: string;
+  diff: string;
+}
+
 export interface ToolCall {
   id: string;
   input: { _tool?: string; [key: string]: unknown };
@@ -175,7 +265,12 @@ export interface Message {
   content: MessageContent;
   display_data?: ImageData | Record<string, unknown> | null; // For tool results with images (e.g., screenshots)
   usage_data?: UsageData;
+  /** Model that wrote an agent message (REQ-LLM-020) */
+  model?: string;
   created_at: string;
+  /** Set on stream messages whose body went over the server's per-event
+   *  budget (REQ-API-030); fetch the full message with `api.getMessage`. */
+  truncated?: boolean;
 }
 
 export type MessageContent = 
@@ -184,8 +279,10 @@ export type MessageContent =
   | ToolResultContent;  // tool result
 
 export interface ContentBlock {
-  type: 'text' | 'tool_use';
+  type: 'text' | 'tool_use' | 'thinking' | 'redacted_thinking';
   text?: string;
+  /** Extended-thinking text (REQ-LLM-014); empty when redacted at rest */
+  thinking?: string;
   id?: string;
   name?: string;
   input?: Record<string, unknown>;
@@ -199,6 +296,16 @@ export interface ToolResultContent {
   result?: string;
   error?: string;
   is_error?: boolean;
+  /** Set when the user ran the command themselves (REQ-BASH-020) */
+  user_command?: string;
+}
+
+/** Outcome of a command the user ran (REQ-BASH-020) */
+export interface RunCommandResult {
+  message_id: string;
+  success: boolean;
+  /** The bash tool's JSON response */
+  output: string;
 }
 
 export interface ImageData {
@@ -219,6 +326,9 @@ export interface ModelInfo {
   description: string;
   context_window: number;
   recommended: boolean;
+  supports_vision: boolean;
+  supports_tools: boolean;
+  max_output_tokens: number;
 }
 
 export type GatewayStatus = 'not_configured' | 'healthy' | 'unreachable';
@@ -241,6 +351,12 @@ export interface FileSearchEntry {
   is_text_file: boolean;
 }
 
+/** A directory a conversation may use besides its cwd (REQ-BED-049) */
+export interface ConversationRoot {
+  path: string;
+  writable: boolean;
+}
+
 /** A single skill entry returned by the skills API (REQ-IR-005) */
 export interface SkillEntry {
   name: string;
@@ -262,10 +378,36 @@ export interface TaskEntry {
   conversation_slug?: string;
 }
 
+/** Machine-readable kind of every error response (REQ-API-029). */
+export type ErrorCode =
+  | 'bad_request'
+  | 'not_found'
+  | 'conflict'
+  | 'invalid_reference'
+  | 'rate_limited'
+  | 'database'
+  | 'llm_auth'
+  | 'llm_rate_limited'
+  | 'llm_unavailable'
+  | 'llm_rejected'
+  | 'sandbox_violation'
+  | 'conversation_busy'
+  | 'internal';
+
+/** Body of an error response. Typed conflicts and expansion errors add
+ *  their own fields on top. */
+export interface ApiErrorBody {
+  error: string;
+  code: ErrorCode;
+  /** Whether the same request may succeed later, unchanged */
+  retryable: boolean;
+  retry_after_ms?: number;
+}
+
 /** Expansion error returned by the server when an @reference or /skill fails (REQ-IR-007) */
 export interface ExpansionErrorDetail {
   error: string;
-  error_type: 'file_not_found' | 'file_not_text' | 'skill_invocation_failed';
+  error_type: 'file_not_found' | 'file_not_text' | 'file_ignored' | 'skill_invocation_failed';
   reference: string;
 }
 
@@ -292,6 +434,8 @@ export class ExpansionError extends Error {
 export interface ConflictErrorDetail {
   error: string;
   error_type: string;
+  code?: ErrorCode;
+  retryable?: boolean;
   conflict_slug?: string;
   dirty_files?: string[];
   can_auto_stash?: boolean;
@@ -356,6 +500,19 @@ export interface ConversationUsage {
   total: UsageTotals;
 }
 
+/** Estimated input tokens one tool's outputs have cost (REQ-API-031) */
+export interface ToolTokenUsage {
+  tool_name: string;
+  input_tokens: number;
+  requests: number;
+}
+
+export interface ToolUsage {
+  tools: ToolTokenUsage[];
+  /** Prompt tokens billed across the conversation's requests */
+  prompt_tokens: number;
+}
+
 export const api = {
   async authStatus(): Promise<AuthStatus> {
     const resp = await fetch('/api/auth/status');
@@ -381,8 +538,66 @@ export const api = {
     return resp.json();
   },
 
-  async listConversations(): Promise<Conversation[]> {
-    const resp = await fetch('/api/conversations');
+  async getProjectVerify(projectId: string): Promise<VerifySettings | null> {
+    const resp = await fetch(`/api/projects/${projectId}/verify`);
+    if (!resp.ok) throw new Error('Failed to load verify settings');
+    return (await resp.json()).verify;
+  },
+
+  async setProjectVerify(
+    projectId: string,
+    command: string | null,
+    maxAttempts?: number,
+  ): Promise<void> {
+    const resp = await fetch(`/api/projects/${projectId}/verify`, {
+      method: 'PUT',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ command, max_attempts: maxAttempts }),
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to set verify command');
+    }
+  },
+
+  async listTemplates(): Promise<ConversationTemplate[]> {
+    const resp = await fetch('/api/templates');
+    if (!resp.ok) throw new Error('Failed to list templates');
+    return (await resp.json()).templates;
+  },
+
+  async saveTemplate(
+    template: ConversationTemplate,
+    isNew: boolean,
+  ): Promise<ConversationTemplate> {
+    const url = isNew ? '/api/templates' : `/api/templates/${encodeURIComponent(template.name)}`;
+    const resp = await fetch(url, {
+      method: isNew ? 'POST' : 'PUT',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(template),
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to save template');
+    }
+    return resp.json();
+  },
+
+  async deleteTemplate(name: string): Promise<void> {
+    const resp = await fetch(`/api/templates/${encodeURIComponent(name)}`, { method: 'DELETE' });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to delete template');
+    }
+  },
+
+  async listConversations(params: ConversationListParams = {}): Promise<Conversation[]> {
+    const query = new URLSearchParams();
+    for (const [key, value] of Object.entries(params)) {
+      if (value !== undefined && value !== '') query.set(key, String(value));
+    }
+    const qs = query.toString();
+    const resp = await fetch(qs ? `/api/conversations?${qs}` : '/api/conversations');
     if (!resp.ok) throw new Error('Failed to list conversations');
     return (await resp.json()).conversations;
   },
@@ -397,6 +612,8 @@ export const api = {
     baseBranch?: string | null,
     seedParentId?: string | null,
     seedLabel?: string | null,
+    template?: string | null,
+    disabledTools: string[] = [],
   ): Promise<Conversation> {
     const body: Record<string, unknown> = { cwd, model, text, message_id: messageId, images, mode };
     if (baseBranch) {
@@ -408,6 +625,12 @@ export const api = {
     if (seedLabel) {
       body['seed_label'] = seedLabel;
     }
+    if (template) {
+      body['template'] = template;
+    }
+    if (disabledTools.length > 0) {
+      body['disabled_tools'] = disabledTools;
+    }
     const resp = await fetch('/api/conversations/new', {
       method: 'POST',
       headers: { 'Content-Type': 'application/json' },
@@ -462,6 +685,7 @@ export const api = {
         images,
         message_id: localId,
         user_agent: navigator.userAgent,
+        client_id: getClientId(),
       }),
     });
     if (resp.status === 422) {
@@ -469,10 +693,32 @@ export const api = {
       const detail = await resp.json() as ExpansionErrorDetail;
       throw new ExpansionError(detail);
     }
+    if (resp.status === 409) {
+      // Includes multi-tab rejections (REQ-API-013): `composer_locked`,
+      // `duplicate_submission`.
+      throw new ConflictError(await resp.json() as ConflictErrorDetail);
+    }
     if (!resp.ok) throw new Error('Failed to send message');
     return resp.json();
   },
 
+  /** Claim (`typing: true`) or release this tab's composer lease (REQ-API-013). */
+  async updateComposer(
+    convId: string,
+    typing: boolean,
+  ): Promise<{ clients: string[]; composer_holder: string | null }> {
+    const resp = await fetch(`/api/conversations/${convId}/composer`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ client_id: getClientId(), typing }),
+    });
+    if (resp.status === 409) {
+      throw new ConflictError(await resp.json() as ConflictErrorDetail);
+    }
+    if (!resp.ok) throw new Error('Failed to update composer');
+    return resp.json();
+  },
+
   async getSystemPrompt(convId: string): Promise<string> {
     const resp = await fetch(`/api/conversations/${convId}/system-prompt`);
     if (!resp.ok) throw new Error('Failed to fetch system prompt');
@@ -515,6 +761,18 @@ export const api = {
     return resp.json();
   },
 
+  /** Drop one queued tool; the rest of the round continues (REQ-BED-055) */
+  async cancelQueuedTool(convId: string, toolUseId: string): Promise<{ success: boolean }> {
+    const resp = await fetch(`/api/conversations/${convId}/cancel-tool/${toolUseId}`, {
+      method: 'POST',
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to cancel tool');
+    }
+    return resp.json();
+  },
+
   /** Manually trigger context continuation (REQ-BED-023) */
   async triggerContinuation(convId: string): Promise<{ success: boolean }> {
     const resp = await fetch(`/api/conversations/${convId}/trigger-continuation`, {
@@ -524,12 +782,59 @@ export const api = {
     return resp.json();
   },
 
+  async retryConversation(convId: string): Promise<{ success: boolean }> {
+    const resp = await fetch(`/api/conversations/${convId}/retry`, {
+      method: 'POST',
+    });
+    if (!resp.ok) throw new Error('Failed to retry conversation');
+    return resp.json();
+  },
+
+  /** One message in full, for stream messages sent `truncated` (REQ-API-030) */
+  async getMessage(convId: string, messageId: string): Promise<Message> {
+    const resp = await fetch(`/api/conversations/${convId}/messages/${messageId}`);
+    if (!resp.ok) throw new Error('Failed to fetch message');
+    return resp.json();
+  },
+
+  /** Keep a message in the model's context verbatim (REQ-BED-045) */
+  async pinMessage(convId: string, messageId: string): Promise<{ message_ids: string[] }> {
+    const resp = await fetch(`/api/conversations/${convId}/messages/${messageId}/pin`, {
+      method: 'POST',
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to pin message');
+    }
+    return resp.json();
+  },
+
+  /** Rate an agent message (REQ-API-022). Rating again replaces the earlier rating. */
+  async rateMessage(messageId: string, rating: 'up' | 'down', comment?: string): Promise<void> {
+    const resp = await fetch(`/api/messages/${messageId}/feedback`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ rating, comment: comment ?? null }),
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to rate message');
+    }
+  },
+
   async getConversationUsage(convId: string): Promise<ConversationUsage> {
     const resp = await fetch(`/api/conversations/${convId}/usage`);
     if (!resp.ok) throw new Error('Failed to fetch usage');
     return resp.json();
   },
 
+  /** Input tokens spent on each tool's outputs, largest first (REQ-API-031) */
+  async getToolUsage(convId: string): Promise<ToolUsage> {
+    const resp = await fetch(`/api/conversations/${convId}/usage/by-tool`);
+    if (!resp.ok) throw new Error('Failed to fetch tool usage');
+    return resp.json();
+  },
+
   async archiveConversation(convId: string): Promise<{ ok: boolean }> {
     const resp = await fetch(`/api/conversations/${convId}/archive`, {
       method: 'POST',
@@ -567,6 +872,16 @@ export const api = {
     return resp.json();
   },
 
+  /** New conversation with the same cwd, model and settings, no messages (REQ-API-025) */
+  async duplicateConversation(convId: string): Promise<Conversation> {
+    const resp = await fetch(`/api/conversations/${convId}/duplicate`, { method: 'POST' });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to duplicate');
+    }
+    return (await resp.json()).conversation;
+  },
+
   async listArchivedConversations(): Promise<Conversation[]> {
     const resp = await fetch('/api/conversations/archived');
     if (!resp.ok) throw new Error('Failed to list archived conversations');
@@ -617,8 +932,10 @@ export const api = {
     query: string,
     limit = 50,
     signal?: AbortSignal,
+    root?: string,
   ): Promise<{ items: FileSearchEntry[] }> {
     const params = new URLSearchParams({ q: query, limit: String(limit) });
+    if (root) params.set('root', root);
     const resp = await fetch(
       `/api/conversations/${convId}/files/search?${params}`,
       signal ? { signal } : {},
@@ -730,12 +1047,179 @@ export const api = {
     return resp.json();
   },
 
+  /** Answer a pending `ask_user` call and resume the agent (REQ-AUQ-009) */
+  async answerUserInput(convId: string, answer: string): Promise<void> {
+    const resp = await fetch(`/api/conversations/${convId}/answer`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ answer }),
+    });
+    if (!resp.ok) { const err = await resp.json(); throw new Error(err.error || 'Failed to send answer'); }
+  },
+
+  /** Continue the conversation from one council answer (REQ-BED-058) */
+  async chooseCouncilCandidate(convId: string, candidateId: string): Promise<void> {
+    const resp = await fetch(`/api/conversations/${convId}/council/choose`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ candidate_id: candidateId }),
+    });
+    if (!resp.ok) { const err = await resp.json(); throw new Error(err.error || 'Failed to choose answer'); }
+  },
+
+  /** Save a file in the conversation's directories and note it for the agent (REQ-FE-011) */
+  async writeConversationFile(
+    convId: string,
+    path: string,
+    content: string,
+  ): Promise<{ path: string; noted: boolean }> {
+    const resp = await fetch(`/api/conversations/${convId}/files/write`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ path, content }),
+    });
+    if (!resp.ok) { const err = await resp.json(); throw new Error(err.error || 'Failed to write file'); }
+    return resp.json();
+  },
+
+  /** Run a shell command in the conversation's cwd as the user (REQ-BASH-020) */
+  async runCommand(convId: string, command: string): Promise<RunCommandResult> {
+    const resp = await fetch(`/api/conversations/${convId}/run-command`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ command }),
+    });
+    if (!resp.ok) { const err = await resp.json(); throw new Error(err.error || 'Failed to run command'); }
+    return resp.json();
+  },
+
+  /** Write the staged patch and resume the agent (REQ-PATCH-010) */
+  async applyPendingPatch(convId: string, toolUseId: string): Promise<void> {
+    const resp = await fetch(
+      `/api/conversations/${convId}/pending-patches/${encodeURIComponent(toolUseId)}/apply`,
+      { method: 'POST' },
+    );
+    if (!resp.ok) { const err = await resp.json(); throw new Error(err.error || 'Failed to apply patch'); }
+  },
+
+  /** Discard the staged patch; the agent is told it was rejected (REQ-PATCH-010) */
+  async rejectPendingPatch(convId: string, toolUseId: string): Promise<void> {
+    const resp = await fetch(
+      `/api/conversations/${convId}/pending-patches/${encodeURIComponent(toolUseId)}/reject`,
+      { method: 'POST' },
+    );
+    if (!resp.ok) { const err = await resp.json(); throw new Error(err.error || 'Failed to reject patch'); }
+  },
+
   async getMcpStatus(): Promise<McpServerStatus[]> {
     const resp = await fetch('/api/mcp/status');
     if (!resp.ok) throw new Error('Failed to get MCP status');
     return resp.json();
   },
 
+  /** Set or clear the extended-thinking budget (REQ-LLM-014). Conversation must be idle. */
+  async setThinkingBudget(conversationId: string, budgetTokens: number | null): Promise<void> {
+    const resp = await fetch(`/api/conversations/${conversationId}/thinking`, {
+      method: 'PUT',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ budget_tokens: budgetTokens }),
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to set thinking budget');
+    }
+  },
+
+  /** Store the unsent draft server-side (REQ-BED-047). Empty text and no images clears it. */
+  async saveDraft(conversationId: string, text: string, images: ImageData[]): Promise<void> {
+    const resp = await fetch(`/api/conversations/${conversationId}/draft`, {
+      method: 'PUT',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ text, images }),
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to save draft');
+    }
+  },
+
+  /** VAPID public key to subscribe to push notifications with (REQ-API-024) */
+  async getPushKey(): Promise<string> {
+    const resp = await fetch('/api/push/key');
+    if (!resp.ok) throw new Error('Failed to get push key');
+    return (await resp.json()).public_key;
+  },
+
+  /** Store this browser's push subscription (REQ-API-024) */
+  async subscribePush(subscription: PushSubscriptionJSON): Promise<void> {
+    const resp = await fetch('/api/push/subscriptions', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(subscription),
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to subscribe to notifications');
+    }
+  },
+
+  /** Whether a conversation sends push notifications (REQ-API-024) */
+  async getConversationPush(conversationId: string): Promise<boolean> {
+    const resp = await fetch(`/api/conversations/${conversationId}/push`);
+    if (!resp.ok) throw new Error('Failed to get notification setting');
+    return (await resp.json()).enabled;
+  },
+
+  /** Opt a conversation in to or out of push notifications (REQ-API-024) */
+  async setConversationPush(conversationId: string, enabled: boolean): Promise<void> {
+    const resp = await fetch(`/api/conversations/${conversationId}/push`, {
+      method: 'PUT',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ enabled }),
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to set notifications');
+    }
+  },
+
+  /** Directories besides the cwd the conversation may use (REQ-BED-049) */
+  async getConversationRoots(conversationId: string): Promise<ConversationRoot[]> {
+    const resp = await fetch(`/api/conversations/${conversationId}/roots`);
+    if (!resp.ok) throw new Error('Failed to get conversation roots');
+    return (await resp.json()).roots;
+  },
+
+  /** Replace the conversation's extra roots (REQ-BED-049). Conversation must be idle. */
+  async setConversationRoots(
+    conversationId: string,
+    roots: ConversationRoot[],
+  ): Promise<ConversationRoot[]> {
+    const resp = await fetch(`/api/conversations/${conversationId}/roots`, {
+      method: 'PUT',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ roots }),
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to set conversation roots');
+    }
+    return (await resp.json()).roots;
+  },
+
+  /** Set how much history LLM requests carry (REQ-BED-046). Conversation must be idle. */
+  async setHistoryWindow(conversationId: string, window: HistoryWindow): Promise<void> {
+    const resp = await fetch(`/api/conversations/${conversationId}/history-window`, {
+      method: 'PUT',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ window }),
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to set history window');
+    }
+  },
+
   async upgradeModel(conversationId: string, model: string): Promise<void> {
     const resp = await fetch(`/api/conversations/${conversationId}/upgrade-model`, {
       method: 'POST',
@@ -754,6 +1238,39 @@ export const api = {
     return resp.json();
   },
 
+  /** Tools that can be switched off per conversation (REQ-BED-039) */
+  async listTools(): Promise<ToolEntry[]> {
+    const resp = await fetch('/api/tools');
+    if (!resp.ok) throw new Error('Failed to list tools');
+    return (await resp.json()).tools;
+  },
+
+  /** Replace a conversation's disabled tools (REQ-BED-039). Conversation must be idle. */
+  async setDisabledTools(conversationId: string, disabled: string[]): Promise<void> {
+    const resp = await fetch(`/api/conversations/${conversationId}/tools`, {
+      method: 'PUT',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ disabled }),
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to set tools');
+    }
+  },
+
+  /** Stage patches for review instead of writing them (REQ-PATCH-010). Conversation must be idle. */
+  async setPatchReview(conversationId: string, enabled: boolean): Promise<void> {
+    const resp = await fetch(`/api/conversations/${conversationId}/patch-review`, {
+      method: 'PUT',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ enabled }),
+    });
+    if (!resp.ok) {
+      const err = await resp.json();
+      throw new Error(err.error || 'Failed to set patch review');
+    }
+  },
+
   async disableMcpServer(name: string): Promise<void> {
     const resp = await fetch(`/api/mcp/servers/${encodeURIComponent(name)}/disable`, { method: 'POST' });
     if (!resp.ok) throw new Error('Failed to disable MCP server');
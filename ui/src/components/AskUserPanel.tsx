@@ -0,0 +1,88 @@
+/**
+ * AskUserPanel Component
+ *
+ * Renders when the agent is waiting on an `ask_user` call (REQ-AUQ-009).
+ * Shows the question with its options as buttons and a free-text answer;
+ * whichever is sent becomes the call's result and the turn resumes.
+ */
+
+import { useState, useCallback } from 'react';
+import { api } from '../api';
+import './AskUserPanel.css';
+
+export interface AskUserPanelProps {
+  question: string;
+  options: string[];
+  conversationId: string;
+  showToast: (message: string, duration?: number) => void;
+  /** Called after a successful answer POST so the parent can advance the
+   *  local phase without waiting for the SSE state echo. */
+  onSubmitted: () => void;
+}
+
+export function AskUserPanel({
+  question,
+  options,
+  conversationId,
+  showToast,
+  onSubmitted,
+}: AskUserPanelProps) {
+  const [text, setText] = useState('');
+  const [submitting, setSubmitting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const submit = useCallback(async (answer: string) => {
+    if (submitting || !answer.trim()) return;
+    setSubmitting(true);
+    setError(null);
+    try {
+      await api.answerUserInput(conversationId, answer.trim());
+      onSubmitted();
+      showToast('Answer sent', 3000);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Failed to send answer');
+    } finally {
+      setSubmitting(false);
+    }
+  }, [submitting, conversationId, onSubmitted, showToast]);
+
+  return (
+    <div className="ask-user-panel">
+      <div className="ask-user-question">{question}</div>
+      {options.length > 0 && (
+        <div className="ask-user-options">
+          {options.map((option) => (
+            <button
+              key={option}
+              className="ask-user-option"
+              onClick={() => submit(option)}
+              disabled={submitting}
+            >
+              {option}
+            </button>
+          ))}
+        </div>
+      )}
+      <form
+        className="ask-user-form"
+        onSubmit={(e) => {
+          e.preventDefault();
+          void submit(text);
+        }}
+      >
+        <input
+          className="ask-user-input"
+          value={text}
+          onChange={(e) => setText(e.target.value)}
+          placeholder={options.length > 0 ? 'Or type your own answer' : 'Your answer'}
+          disabled={submitting}
+          autoFocus
+        />
+        <button className="ask-user-send" type="submit" disabled={submitting || !text.trim()}>
+          Send
+        </button>
+      </form>
+      {error && <div className="ask-user-error">{error}</div>}
+    </div>
+  );
+}
@@ -8,6 +8,7 @@ import { CommandPaletteInput } from './CommandPaletteInput';
 import { CommandPaletteResults } from './CommandPaletteResults';
 import { createConversationSource } from './sources/ConversationSource';
 import { createFileSource } from './sources/FileSource';
+import { createShellSource } from './sources/ShellSource';
 import { createBuiltInActions } from './actions/builtInActions';
 import { useFileExplorer } from '../../hooks/useFileExplorer';
 import { computeChainRoots } from '../../utils/chains';
@@ -17,9 +18,11 @@ const SEARCH_DEBOUNCE_MS = 120;
 
 interface CommandPaletteProps {
   conversations: readonly Conversation[];
+  /** Reports a `$` command the server refused (REQ-CP-009) */
+  onError?: (message: string) => void;
 }
 
-export function CommandPalette({ conversations }: CommandPaletteProps) {
+export function CommandPalette({ conversations, onError }: CommandPaletteProps) {
   const [state, setState] = useState<PaletteState>(initialState);
   const [isDesktop, setIsDesktop] = useState(() => window.matchMedia('(min-width: 1025px)').matches);
   const navigate = useNavigate();
@@ -72,10 +75,23 @@ export function CommandPalette({ conversations }: CommandPaletteProps) {
     [activeConvId, activeConvCwd, openFile],
   );
 
-  // Stable sources array — changes only when conversationSource or fileSource identity changes.
+  // ShellSource — `$ cmd` runs in the active conversation (REQ-CP-009).
+  const shellSource = useMemo(
+    () =>
+      activeConvId && activeConvCwd
+        ? createShellSource(activeConvId, activeConvCwd, (message) => onError?.(message))
+        : null,
+    [activeConvId, activeConvCwd, onError],
+  );
+
+  // Stable sources array — changes only when a source's identity changes.
+  // The shell source goes first so a `$` query selects its command.
   const sources: PaletteSource[] = useMemo(
-    () => (fileSource ? [fileSource, conversationSource] : [conversationSource]),
-    [conversationSource, fileSource],
+    () =>
+      shellSource && fileSource
+        ? [shellSource, fileSource, conversationSource]
+        : [conversationSource],
+    [conversationSource, fileSource, shellSource],
   );
 
   // Keep a ref so the search effect always sees the latest sources without
@@ -0,0 +1,46 @@
+/**
+ * ShellSource — command palette source that runs a shell command in the
+ * active conversation's working directory (REQ-CP-009, REQ-BASH-020).
+ *
+ * Only answers queries starting with `$`; the rest of the query is the
+ * command. The output is recorded in the conversation as a message from
+ * the user and arrives over its stream, so selection does not wait on it.
+ */
+import { api } from '../../../api';
+import type { PaletteSource, PaletteItem } from '../types';
+
+export const SHELL_PREFIX = '$';
+
+export function createShellSource(
+  convId: string,
+  cwd: string,
+  onError: (message: string) => void,
+): PaletteSource {
+  return {
+    id: 'shell',
+    category: 'Shell',
+
+    search(query: string): Promise<PaletteItem[]> {
+      const command = query.trimStart().startsWith(SHELL_PREFIX)
+        ? query.trimStart().slice(SHELL_PREFIX.length).trim()
+        : '';
+      if (!command) return Promise.resolve([]);
+      return Promise.resolve([
+        {
+          id: `shell:${command}`,
+          title: `Run: ${command}`,
+          subtitle: cwd,
+          category: 'Shell',
+          metadata: command,
+        },
+      ]);
+    },
+
+    onSelect(item: PaletteItem) {
+      const command = item.metadata as string;
+      api.runCommand(convId, command).catch((err: unknown) => {
+        onError(err instanceof Error ? err.message : 'Failed to run command');
+      });
+    },
+  };
+}
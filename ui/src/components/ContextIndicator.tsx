@@ -1,6 +1,6 @@
 import { useState, useRef, useEffect } from 'react';
 import { api } from '../api';
-import type { ConversationUsage, UsageTotals } from '../api';
+import type { ConversationUsage, ToolUsage, UsageTotals } from '../api';
 
 // Thresholds (match backend constants)
 const WARNING_THRESHOLD = 0.80;
@@ -51,9 +51,34 @@ function UsageStatsSection({ label, totals }: { label: string; totals: UsageTota
   );
 }
 
+/** Tools whose outputs cost the most input tokens (REQ-API-031) */
+function ToolUsageSection({ usage }: { usage: ToolUsage }) {
+  if (usage.tools.length === 0) return null;
+  const share = (tokens: number) =>
+    usage.prompt_tokens > 0 ? ` (${Math.round((tokens / usage.prompt_tokens) * 100)}%)` : '';
+  return (
+    <div className="usage-stats-section">
+      <div className="usage-stats-label">Tool output in context</div>
+      <table className="usage-stats-table">
+        <tbody>
+          {usage.tools.slice(0, 5).map((tool) => (
+            <tr key={tool.tool_name} title={`Carried in ${tool.requests} requests`}>
+              <td className="usage-stat-name">{tool.tool_name}</td>
+              <td className="usage-stat-value">
+                ~{formatTokens(tool.input_tokens)}{share(tool.input_tokens)}
+              </td>
+            </tr>
+          ))}
+        </tbody>
+      </table>
+    </div>
+  );
+}
+
 export function ContextIndicator({ used, max, conversationId, onTriggerContinuation }: ContextIndicatorProps) {
   const [panelOpen, setPanelOpen] = useState(false);
   const [usage, setUsage] = useState<ConversationUsage | null>(null);
+  const [toolUsage, setToolUsage] = useState<ToolUsage | null>(null);
   const [usageLoading, setUsageLoading] = useState(false);
   const menuRef = useRef<HTMLDivElement>(null);
 
@@ -74,6 +99,9 @@ export function ContextIndicator({ used, max, conversationId, onTriggerContinuat
     api.getConversationUsage(conversationId)
       .then(data => { setUsage(data); setUsageLoading(false); })
       .catch(() => setUsageLoading(false));
+    api.getToolUsage(conversationId)
+      .then(setToolUsage)
+      .catch(() => setToolUsage(null));
   }, [panelOpen, conversationId]);
 
   const percent = Math.min((used / max) * 100, 100);
@@ -116,6 +144,7 @@ export function ContextIndicator({ used, max, conversationId, onTriggerContinuat
                 {usage.total.turns > usage.own.turns && (
                   <UsageStatsSection label="Total incl. sub-agents" totals={usage.total} />
                 )}
+                {toolUsage && <ToolUsageSection usage={toolUsage} />}
               </>
             ) : (
               <div className="usage-panel-empty">No usage data yet</div>
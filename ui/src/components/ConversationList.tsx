@@ -23,6 +23,9 @@ interface ConversationListProps {
   onUnarchive: (conv: Conversation) => void;
   onDelete: (conv: Conversation) => void;
   onRename: (conv: Conversation) => void;
+  /** Start a fresh conversation with the same setup (REQ-API-025). Offered
+   *  only for conversations without a worktree of their own. */
+  onDuplicate?: (conv: Conversation) => void;
   /** Chain-scope archive/unarchive/delete. Triggered from the chain block
    *  header `⋮` menu. Per-member rows never invoke these — they hide the
    *  affordance entirely so the only path to a chain lifecycle op is the
@@ -47,6 +50,7 @@ export function ConversationList({
   onUnarchive,
   onDelete,
   onRename,
+  onDuplicate,
   onArchiveChain,
   onUnarchiveChain,
   onDeleteChain,
@@ -232,7 +236,17 @@ export function ConversationList({
             <span className="conv-item-messages">
               {conv.message_count} {conv.message_count === 1 ? 'msg' : 'msgs'}
             </span>
+            {conv.cost_usd ? (
+              <span className="conv-item-cost" title="Cost so far, sub-agents included">
+                ${conv.cost_usd < 0.01 ? '<0.01' : conv.cost_usd.toFixed(2)}
+              </span>
+            ) : null}
           </div>
+          {conv.last_message_preview && (
+            <div className="conv-item-preview" title={conv.last_message_preview}>
+              {conv.last_message_preview}
+            </div>
+          )}
           <div className="conv-item-meta secondary">
             {conv.project_id && conv.cwd && (
               <span className="conv-project-label">{conv.cwd.split('/').filter(Boolean).pop()}</span>
@@ -257,6 +271,18 @@ export function ConversationList({
               >
                 Rename
               </button>
+              {onDuplicate && !conv.worktree_path && (
+                <button
+                  className="action-btn"
+                  onClick={(e) => {
+                    e.stopPropagation();
+                    setExpandedId(null);
+                    onDuplicate(conv);
+                  }}
+                >
+                  Duplicate
+                </button>
+              )}
               {!isChainMember && (
                 showArchived ? (
                   <button
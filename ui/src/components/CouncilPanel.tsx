@@ -0,0 +1,85 @@
+/**
+ * CouncilPanel Component
+ *
+ * Renders when a council message has been answered (REQ-BED-058). Shows
+ * each model's answer side by side, or why it failed; the one the user
+ * picks becomes the agent's reply and the turn goes on from there.
+ */
+
+import { useState, useCallback } from 'react';
+import { api } from '../api';
+import type { CouncilCandidate } from '../api';
+import './CouncilPanel.css';
+
+export interface CouncilPanelProps {
+  candidates: CouncilCandidate[];
+  conversationId: string;
+  showToast: (message: string, duration?: number) => void;
+  /** Called after a successful choice POST so the parent can advance the
+   *  local phase without waiting for the SSE state echo. */
+  onSubmitted: () => void;
+}
+
+function answerText(candidate: CouncilCandidate): string {
+  return candidate.content
+    .filter((block) => block.type === 'text' && block.text)
+    .map((block) => block.text)
+    .join('\n\n');
+}
+
+export function CouncilPanel({
+  candidates,
+  conversationId,
+  showToast,
+  onSubmitted,
+}: CouncilPanelProps) {
+  const [submitting, setSubmitting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const choose = useCallback(async (candidateId: string) => {
+    if (submitting) return;
+    setSubmitting(true);
+    setError(null);
+    try {
+      await api.chooseCouncilCandidate(conversationId, candidateId);
+      onSubmitted();
+      showToast('Continuing with the chosen answer', 3000);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Failed to choose answer');
+    } finally {
+      setSubmitting(false);
+    }
+  }, [submitting, conversationId, onSubmitted, showToast]);
+
+  return (
+    <div className="council-panel">
+      <div className="council-candidates">
+        {candidates.map((candidate) => (
+          <div key={candidate.id} className="council-candidate">
+            <div className="council-model">{candidate.model}</div>
+            {candidate.error ? (
+              <div className="council-failed">{candidate.error}</div>
+            ) : (
+              <>
+                <div className="council-answer">{answerText(candidate)}</div>
+                {candidate.tool_calls.length > 0 && (
+                  <div className="council-tools">
+                    then runs {candidate.tool_calls.map((call) => call.input._tool ?? 'tool').join(', ')}
+                  </div>
+                )}
+                <button
+                  className="council-choose"
+                  onClick={() => choose(candidate.id)}
+                  disabled={submitting}
+                >
+                  Continue with this
+                </button>
+              </>
+            )}
+          </div>
+        ))}
+      </div>
+      {error && <div className="council-error">{error}</div>}
+    </div>
+  );
+}
@@ -34,7 +34,7 @@ export function DesktopLayout({ children }: DesktopLayoutProps) {
     collapseThreshold: 120,
   });
   const location = useLocation();
-  const { toasts, dismissToast, showSuccess } = useToast();
+  const { toasts, dismissToast, showSuccess, showError } = useToast();
 
   // Task 08684: ConversationStore is the single source of truth.
   // The store-owned `useConversationsRefresh` (mounted in
@@ -116,7 +116,7 @@ export function DesktopLayout({ children }: DesktopLayoutProps) {
         <div className={isDesktop ? 'desktop-main' : undefined}>
           {children}
         </div>
-        {isDesktop && <CommandPalette conversations={conversations} />}
+        {isDesktop && <CommandPalette conversations={conversations} onError={showError} />}
         <Toast messages={toasts} onDismiss={dismissToast} />
       </div>
     </FileExplorerProvider>
@@ -3,7 +3,7 @@
  * REQ-FE-001, REQ-FE-004, REQ-FE-005
  */
 
-import { useState, useCallback } from 'react';
+import { useState, useCallback, useEffect } from 'react';
 import { FileTree } from './FileTree';
 import { McpStatusPanel } from '../McpStatusPanel';
 import { SkillsPanel } from '../SkillsPanel';
@@ -39,6 +39,18 @@ export function FileExplorerPanel({ collapsed, onToggle, rootPath, conversationI
   const { openFile, activeFile } = useFileExplorer();
   const [refreshKey, setRefreshKey] = useState(0);
   const handleRefresh = useCallback(() => setRefreshKey(k => k + 1), []);
+
+  // REQ-BED-059: reload the tree when files change outside the conversation
+  useEffect(() => {
+    const handler = (e: Event) => {
+      const detail = (e as CustomEvent<{ conversationId?: string }>).detail;
+      if (detail?.conversationId === conversationId) handleRefresh();
+    };
+    window.addEventListener('phoenix:files-changed', handler);
+    return () => {
+      window.removeEventListener('phoenix:files-changed', handler);
+    };
+  }, [conversationId, handleRefresh]);
   const [selectedSkill, setSelectedSkill] = useState<SkillEntry | null>(null);
   const [selectedTask, setSelectedTask] = useState<TaskEntry | null>(null);
   const [skillsPanelExpanded, setSkillsPanelExpanded] = useState(false);
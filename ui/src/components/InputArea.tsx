@@ -12,7 +12,7 @@ import {
 } from 'react';
 // Icon buttons removed from action row -- file browse via sidebar, image attach via paste/drag
 import type { QueuedMessage } from '../hooks';
-import { useDraft } from '../hooks';
+import { useComposerLease, useDraft } from '../hooks';
 import type { ConversationState, ImageData, SkillEntry } from '../api';
 import { api, ExpansionError } from '../api';
 import { isAgentWorking, isCancellingState } from '../utils';
@@ -30,8 +30,12 @@ import { fuzzyMatch } from './CommandPalette/fuzzyMatch';
 export interface InputAreaHandle {
   appendToDraft: (text: string) => void;
   setDraft: (text: string) => void;
+  getDraft: () => string;
 }
 
+/** How long the composer sits still before its draft is saved server-side. */
+const SERVER_DRAFT_DEBOUNCE_MS = 1000;
+
 interface InputAreaProps {
   conversationId: string | undefined;
   convState: ConversationState;
@@ -55,6 +59,9 @@ interface InputAreaProps {
   onCancel: () => void;
   onRetry: (localId: string) => void;
   onDismissError?: (localId: string) => void;
+  /** Another tab holds the composer lease (REQ-API-013). Send is held
+   *  until that tab sends or clears its draft. */
+  composerHeldElsewhere?: boolean;
 }
 
 export const InputArea = forwardRef<InputAreaHandle, InputAreaProps>(function InputArea({
@@ -69,6 +76,7 @@ export const InputArea = forwardRef<InputAreaHandle, InputAreaProps>(function In
   onCancel,
   onRetry,
   onDismissError,
+  composerHeldElsewhere = false,
 }, ref) {
   const agentWorking = isAgentWorking(convState);
   const isCancelling = isCancellingState(convState);
@@ -76,6 +84,7 @@ export const InputArea = forwardRef<InputAreaHandle, InputAreaProps>(function In
   const textareaRef = useRef<HTMLTextAreaElement>(null);
   const fileInputRef = useRef<HTMLInputElement>(null);
   const voiceSupported = isWebSpeechSupported();
+  useComposerLease(conversationId, draft, !isOffline);
 
   useImperativeHandle(ref, () => ({
     appendToDraft: (text: string) => {
@@ -84,8 +93,33 @@ export const InputArea = forwardRef<InputAreaHandle, InputAreaProps>(function In
     setDraft: (text: string) => {
       setDraft(text);
     },
+    getDraft: () => draft,
   }), [draft, setDraft]);
 
+  // REQ-BED-047: mirror the draft server-side so a reload or another device
+  // gets it back. The value present when a conversation opens is taken as
+  // already saved, so opening a conversation never overwrites its draft.
+  const savedDraftRef = useRef<{
+    conversationId: string | undefined;
+    text: string;
+    images: ImageData[];
+  } | null>(null);
+  useEffect(() => {
+    const saved = savedDraftRef.current;
+    if (!conversationId || !saved || saved.conversationId !== conversationId) {
+      savedDraftRef.current = { conversationId, text: draft, images };
+      return;
+    }
+    if (saved.text === draft && saved.images === images) return;
+    const timer = window.setTimeout(() => {
+      savedDraftRef.current = { conversationId, text: draft, images };
+      api.saveDraft(conversationId, draft, images).catch((error) => {
+        console.warn('Error saving draft to server:', error);
+      });
+    }, SERVER_DRAFT_DEBOUNCE_MS);
+    return () => window.clearTimeout(timer);
+  }, [conversationId, draft, images]);
+
   // Listen for external insert-draft events (e.g., from SkillViewer)
   useEffect(() => {
     const handler = (e: Event) => {
@@ -469,6 +503,7 @@ export const InputArea = forwardRef<InputAreaHandle, InputAreaProps>(function In
 
     if (!text && images.length === 0) return;
     if (agentWorking && !isOffline) return;
+    if (composerHeldElsewhere && !isOffline) return;
 
     // Close autocomplete and ghost text on send
     setActiveTrigger(null);
@@ -506,7 +541,17 @@ export const InputArea = forwardRef<InputAreaHandle, InputAreaProps>(function In
       // shows the failure with a retry button.
     }
   // eslint-disable-next-line react-hooks/exhaustive-deps
-  }, [voiceBase, voiceInterim, draft, images, agentWorking, isOffline, onSend, clearDraft]);
+  }, [
+    voiceBase,
+    voiceInterim,
+    draft,
+    images,
+    agentWorking,
+    composerHeldElsewhere,
+    isOffline,
+    onSend,
+    clearDraft,
+  ]);
 
   // =========================================================================
   // Voice input
@@ -571,7 +616,10 @@ export const InputArea = forwardRef<InputAreaHandle, InputAreaProps>(function In
 
   const displayedText = voiceBase !== null ? voiceBase : draft;
   const hasContent = displayedText.trim().length > 0 || voiceInterim.trim().length > 0 || images.length > 0;
-  const sendEnabled = (!agentWorking || isOffline) && hasContent && !expansionError;
+  const sendEnabled = (!agentWorking || isOffline)
+    && (!composerHeldElsewhere || isOffline)
+    && hasContent
+    && !expansionError;
 
   // Cycle placeholder hint each time the input clears (e.g., after send).
   // Advances only when draft goes empty, not on a timer.
@@ -665,6 +713,13 @@ export const InputArea = forwardRef<InputAreaHandle, InputAreaProps>(function In
         </div>
       )}
 
+      {/* Another tab is composing (REQ-API-013) */}
+      {composerHeldElsewhere && !isOffline && (
+        <div className="input-presence-notice" aria-live="polite">
+          Another window is typing in this conversation
+        </div>
+      )}
+
       {/* Expansion error inline indicator (REQ-IR-007) */}
       {expansionError && (
         <div className="input-expansion-error" role="alert">
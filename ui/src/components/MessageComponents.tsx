@@ -151,6 +151,11 @@ function formatToolInput(name: string, input: Record<string, unknown>, displayOv
       return { display: thoughts, isMultiline: thoughts.includes('\n') };
     }
     case 'patch': {
+      const files = input['files'] as Array<{ path?: string }> | undefined;
+      if (files && files.length > 0) {
+        const names = files.map(f => String(f.path || '')).join(', ');
+        return { display: `${files.length} files: ${names}`, isMultiline: false };
+      }
       const path = String(input['path'] || '');
       const patches = input['patches'] as Array<{ operation?: string }> | undefined;
       const op = patches?.[0]?.operation || 'modify';
@@ -168,6 +173,19 @@ function formatToolInput(name: string, input: Record<string, unknown>, displayOv
       const path = String(input['path'] || '');
       return { display: path, isMultiline: false };
     }
+    case 'inspect_binary': {
+      const path = String(input['path'] || '');
+      const offset = input['offset'] as number | undefined;
+      return { display: offset ? `${path}@${offset}` : path, isMultiline: false };
+    }
+    case 'kill_process': {
+      const signal = String(input['signal'] || 'TERM');
+      return { display: `pid ${String(input['pid'] ?? '')} (${signal})`, isMultiline: false };
+    }
+    case 'archive': {
+      const operation = String(input['operation'] || 'list');
+      return { display: `${operation} ${String(input['archive'] || '')}`, isMultiline: false };
+    }
     case 'read_file': {
       const path = String(input['path'] || '');
       const offset = input['offset'] as number | undefined;
@@ -321,6 +339,33 @@ function UserMessageImpl({ message }: { message: Message }) {
   );
 }
 
+export const UserCommandMessage = memo(UserCommandMessageImpl);
+
+// REQ-BASH-020: a command the user ran themselves, with the bash tool's response.
+function UserCommandMessageImpl({ message }: { message: Message }) {
+  const content = message.content as ToolResultContent;
+  const output = content.content ?? '';
+  const response = tryParseJson(output);
+  const timestamp = message.created_at;
+
+  return (
+    <div className="message user user-command" data-sequence-id={message.sequence_id}>
+      <div className="message-header">
+        <span className="message-sender">You ran</span>
+        {timestamp && (
+          <span className="message-time" title={new Date(timestamp).toLocaleString()}>
+            {formatMessageTime(timestamp)}
+          </span>
+        )}
+      </div>
+      <div className="message-content">
+        <pre className="user-command-line">$ {content.user_command}</pre>
+        {response ? <BashResponseView response={response} /> : <pre className="bash-lines">{output}</pre>}
+      </div>
+    </div>
+  );
+}
+
 export const QueuedUserMessage = memo(QueuedUserMessageImpl);
 
 // Pending user message: queued client-side, not yet echoed by the server.
@@ -446,6 +491,23 @@ function AgentMessageImpl({ message, toolResults, onOpenFile, isFirstInTurn = tr
     },
   }), [onOpenFile, syntaxStyle]);
 
+  // Extended thinking (REQ-LLM-014): one collapsed aside per message, placed
+  // where the first thinking block sits. The summary comes from display_data
+  // so it survives redaction of the stored text.
+  const thinkingInfo = (message.display_data as { thinking?: ThinkingDisplay } | null | undefined)
+    ?.thinking;
+  const thinkingText = blocks
+    .filter(block => block.type === 'thinking' && block.thinking)
+    .map(block => block.thinking)
+    .join('\n\n');
+  const firstThinking = blocks.findIndex(
+    block => block.type === 'thinking' || block.type === 'redacted_thinking',
+  );
+
+  // How long the model took to produce this message (REQ-BED-048)
+  const llmDurationMs = (message.display_data as { llm_duration_ms?: unknown } | null | undefined)
+    ?.llm_duration_ms;
+
   // Check if there's any renderable content
   const hasRenderableContent = blocks.some(block => {
     if (block.type === 'text') {
@@ -455,7 +517,7 @@ function AgentMessageImpl({ message, toolResults, onOpenFile, isFirstInTurn = tr
       return true;
     }
     return false;
-  });
+  }) || firstThinking >= 0;
 
   // Don't render empty agent messages
   if (!hasRenderableContent) {
@@ -467,6 +529,11 @@ function AgentMessageImpl({ message, toolResults, onOpenFile, isFirstInTurn = tr
       {isFirstInTurn && (
         <div className="message-header">
           <span className="message-sender">Phoenix</span>
+          {message.model && (
+            <span className="message-model" title="Model that wrote this reply">
+              {message.model}
+            </span>
+          )}
           {timestamp && (
             <span className="message-time" title={new Date(timestamp).toLocaleString()}>
               {formatMessageTime(timestamp)}
@@ -476,6 +543,11 @@ function AgentMessageImpl({ message, toolResults, onOpenFile, isFirstInTurn = tr
       )}
       <div className="message-content">
         {blocks.map((block, i) => {
+          if (block.type === 'thinking' || block.type === 'redacted_thinking') {
+            return i === firstThinking ? (
+              <ThinkingAside key={i} info={thinkingInfo} text={thinkingText} />
+            ) : null;
+          }
           if (block.type === 'text') {
             // Skip empty text blocks - they produce empty bubbles
             if (!block.text || block.text.trim() === '') {
@@ -509,6 +581,59 @@ function AgentMessageImpl({ message, toolResults, onOpenFile, isFirstInTurn = tr
           return null;
         })}
       </div>
+      {typeof llmDurationMs === 'number' && (
+        <div className="message-llm-duration" title="Time the model took to respond">
+          LLM {formatToolDuration(llmDurationMs)}
+        </div>
+      )}
+    </div>
+  );
+}
+
+// ============================================================================
+// Thinking Aside — collapsed extended-thinking summary (REQ-LLM-014)
+// ============================================================================
+
+/** `display_data.thinking` on agent messages, computed by the executor. */
+interface ThinkingDisplay {
+  blocks: number;
+  chars: number;
+  redacted: boolean;
+  summary?: string;
+}
+
+const ThinkingAside = memo(ThinkingAsideImpl);
+
+function ThinkingAsideImpl({ info, text }: { info?: ThinkingDisplay; text: string }) {
+  const [expanded, setExpanded] = useState(false);
+  const canExpand = text !== '';
+  const summary = info?.summary ?? (canExpand ? text.split('\n')[0] : '');
+  const label = info?.redacted || !canExpand ? 'thinking (redacted)' : 'thinking';
+
+  return (
+    <div className={`think-aside ${expanded ? 'expanded' : ''}`}>
+      <div
+        className="think-aside-header"
+        onClick={() => canExpand && setExpanded(!expanded)}
+        role="button"
+        tabIndex={0}
+        onKeyDown={(e) => {
+          if (canExpand && (e.key === 'Enter' || e.key === ' ')) {
+            e.preventDefault();
+            setExpanded(!expanded);
+          }
+        }}
+      >
+        <span className="think-aside-chevron">
+          {expanded ? <ChevronDownIcon /> : <ChevronRightIcon />}
+        </span>
+        <span className="think-aside-label">
+          {label}
+          {!expanded && summary && ` — ${summary}`}
+        </span>
+        {expanded && <CopyButton text={text} title="Copy thinking" />}
+      </div>
+      {expanded && <div className="think-aside-body">{text}</div>}
     </div>
   );
 }
@@ -1,4 +1,5 @@
 import { useState, useEffect, useCallback, useRef } from 'react';
+import { api } from '../api';
 import type { Message, ContentBlock } from '../api';
 import { copyToClipboard } from '../utils/clipboard';
 import './MessageContextMenu.css';
@@ -185,6 +186,29 @@ export function MessageContextMenu({ messages }: MessageContextMenuProps) {
     setMenu(null);
   };
 
+  // REQ-BED-045: pinned messages are never trimmed or summarized away
+  const pinMessage = () => {
+    const { conversation_id, message_id } = menu.message;
+    api.pinMessage(conversation_id, message_id).catch((err) => {
+      console.error('Failed to pin message', err);
+    });
+    setMenu(null);
+  };
+  const pinnable = ['user', 'agent', 'tool', 'skill'].includes(menu.message.message_type);
+
+  // REQ-API-022: ratings feed the exported evaluation dataset
+  const rateMessage = (rating: 'up' | 'down') => {
+    const { message_id } = menu.message;
+    setMenu(null);
+    const comment =
+      rating === 'down' ? window.prompt('What went wrong? (optional)') : undefined;
+    if (comment === null) return; // prompt cancelled
+    api.rateMessage(message_id, rating, comment || undefined).catch((err) => {
+      console.error('Failed to rate message', err);
+    });
+  };
+  const rateable = menu.message.message_type === 'agent';
+
   const copyCommand = () => {
     if (menu.toolContext?.command) {
       void copyToClipboard(menu.toolContext.command);
@@ -232,6 +256,25 @@ export function MessageContextMenu({ messages }: MessageContextMenuProps) {
       <button className="msg-context-item" onClick={selectAll}>
         Select All
       </button>
+      {pinnable && (
+        <>
+          <div className="msg-context-divider" />
+          <button className="msg-context-item" onClick={pinMessage}>
+            Pin to Context
+          </button>
+        </>
+      )}
+      {rateable && (
+        <>
+          <div className="msg-context-divider" />
+          <button className="msg-context-item" onClick={() => rateMessage('up')}>
+            Good Response
+          </button>
+          <button className="msg-context-item" onClick={() => rateMessage('down')}>
+            Bad Response...
+          </button>
+        </>
+      )}
     </div>
   );
 }
@@ -7,6 +7,7 @@ import {
   QueuedUserMessage,
   AgentMessage,
   SubAgentStatus,
+  UserCommandMessage,
   formatMessageTime,
 } from './MessageComponents';
 import { StreamingMessage } from './StreamingMessage';
@@ -142,6 +143,10 @@ const MessageListBody = memo(function MessageListBody({
             );
           }
         }
+        // A command the user ran has no tool_use to render under (REQ-BASH-020)
+        if (type === 'tool' && (msg.content as ToolResultContent)?.user_command) {
+          return <UserCommandMessage key={msg.sequence_id} message={msg} />;
+        }
         // Skip tool messages - they're rendered inline with their tool_use
         return null;
       })}
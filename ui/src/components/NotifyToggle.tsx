@@ -0,0 +1,70 @@
+import { useEffect, useState } from 'react';
+import { api } from '../api';
+import { ensurePushSubscription, pushSupported } from '../pushNotifications';
+
+const BellIcon = ({ off }: { off: boolean }) => (
+  <svg width="14" height="14" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round" aria-hidden="true">
+    <path d="M18 8a6 6 0 0 0-12 0c0 7-3 9-3 9h18s-3-2-3-9" />
+    <path d="M13.73 21a2 2 0 0 1-3.46 0" />
+    {off && <line x1="2" y1="2" x2="22" y2="22" />}
+  </svg>
+);
+
+/**
+ * Bell in the state bar: notify this browser when the conversation finishes
+ * a turn or hits an error, even with the tab in the background (REQ-API-024).
+ */
+export function NotifyToggle({ conversationId }: { conversationId: string }) {
+  const [enabled, setEnabled] = useState<boolean | null>(null);
+  const [busy, setBusy] = useState(false);
+  const [denied, setDenied] = useState(false);
+
+  useEffect(() => {
+    if (!pushSupported()) return;
+    let cancelled = false;
+    setEnabled(null);
+    api.getConversationPush(conversationId)
+      .then(value => { if (!cancelled) setEnabled(value); })
+      .catch(() => { if (!cancelled) setEnabled(false); });
+    return () => { cancelled = true; };
+  }, [conversationId]);
+
+  if (!pushSupported() || enabled === null) return null;
+
+  const toggle = async () => {
+    setBusy(true);
+    try {
+      if (!enabled && !(await ensurePushSubscription())) {
+        setDenied(true);
+        return;
+      }
+      await api.setConversationPush(conversationId, !enabled);
+      setEnabled(!enabled);
+      setDenied(false);
+    } catch (err) {
+      console.error('Failed to change notifications:', err);
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const title = denied
+    ? 'Notifications are blocked for this site'
+    : enabled
+      ? 'Notifying when the agent finishes (click to stop)'
+      : 'Notify me when the agent finishes';
+
+  return (
+    <button
+      type="button"
+      className={`notify-toggle${enabled ? ' notify-toggle--on' : ''}`}
+      onClick={toggle}
+      disabled={busy}
+      title={title}
+      aria-label={title}
+      aria-pressed={enabled}
+    >
+      <BellIcon off={!enabled} />
+    </button>
+  );
+}
@@ -0,0 +1,91 @@
+/**
+ * PatchReviewPanel Component
+ *
+ * Renders when the conversation is in `awaiting_patch_review` state
+ * (REQ-PATCH-010). Shows the staged diff with Apply and Reject actions;
+ * nothing is written to disk until the user applies it.
+ */
+
+import { useState, useCallback } from 'react';
+import { api } from '../api';
+import type { StagedPatch } from '../api';
+import './PatchReviewPanel.css';
+
+export interface PatchReviewPanelProps {
+  patch: StagedPatch;
+  toolUseId: string;
+  conversationId: string;
+  showToast: (message: string, duration?: number) => void;
+  /** Called after a successful apply/reject POST so the parent can advance
+   *  the local phase without waiting for the SSE state echo. */
+  onSubmitted: () => void;
+}
+
+function diffLineClass(line: string): string {
+  if (line.startsWith('+++') || line.startsWith('---')) return 'patch-review-line meta';
+  if (line.startsWith('@@')) return 'patch-review-line hunk';
+  if (line.startsWith('+')) return 'patch-review-line added';
+  if (line.startsWith('-')) return 'patch-review-line removed';
+  return 'patch-review-line';
+}
+
+export function PatchReviewPanel({
+  patch,
+  toolUseId,
+  conversationId,
+  showToast,
+  onSubmitted,
+}: PatchReviewPanelProps) {
+  const [submitting, setSubmitting] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const decide = useCallback(async (approved: boolean) => {
+    if (submitting) return;
+    setSubmitting(true);
+    setError(null);
+    try {
+      if (approved) {
+        await api.applyPendingPatch(conversationId, toolUseId);
+      } else {
+        await api.rejectPendingPatch(conversationId, toolUseId);
+      }
+      onSubmitted();
+      showToast(approved ? 'Patch applied' : 'Patch rejected', 3000);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Failed to submit decision');
+    } finally {
+      setSubmitting(false);
+    }
+  }, [submitting, conversationId, toolUseId, onSubmitted, showToast]);
+
+  return (
+    <div className="patch-review-panel">
+      <div className="patch-review-header">
+        <span className="patch-review-title">Review patch</span>
+        <span className="patch-review-path" title={patch.path}>{patch.path}</span>
+      </div>
+      <pre className="patch-review-diff">
+        {patch.diff.split('\n').map((line, i) => (
+          <div key={i} className={diffLineClass(line)}>{line || ' '}</div>
+        ))}
+      </pre>
+      {error && <div className="patch-review-error">{error}</div>}
+      <div className="patch-review-actions">
+        <button
+          className="patch-review-btn reject"
+          onClick={() => decide(false)}
+          disabled={submitting}
+        >
+          Reject
+        </button>
+        <button
+          className="patch-review-btn apply"
+          onClick={() => decide(true)}
+          disabled={submitting}
+        >
+          Apply
+        </button>
+      </div>
+    </div>
+  );
+}
@@ -51,13 +51,17 @@ export interface ProseReaderProps {
 // imported the type from this module.
 export type { ReviewNote } from '../contexts/ReviewNotesContext';
 
-async function readFile(path: string): Promise<string> {
-  const response = await fetch(`/api/files/read?path=${encodeURIComponent(path)}`);
+async function readFile(path: string, chunk?: number): Promise<string> {
+  const query = chunk ? `&chunk=${chunk}` : '';
+  const response = await fetch(`/api/files/read?path=${encodeURIComponent(path)}${query}`);
   if (!response.ok) {
     const error = await response.json().catch(() => ({ error: 'Unknown error' }));
     throw new Error(error.error || 'Failed to read file');
   }
   const data = await response.json();
+  // REQ-FE-012: a file too large to send whole comes back as chunks. Its
+  // first chunk starts at line 1, so line references stay right.
+  if (data.chunks && !chunk) return readFile(path, 1);
   return data.content;
 }
 
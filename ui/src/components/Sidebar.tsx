@@ -84,6 +84,16 @@ export function Sidebar({
     }
   }, [onConversationCreated]);
 
+  const handleDuplicate = useCallback(async (conv: Conversation) => {
+    try {
+      const copy = await api.duplicateConversation(conv.id);
+      onConversationCreated();
+      navigate(`/c/${copy.slug}`);
+    } catch (err) {
+      console.error('Failed to duplicate:', err);
+    }
+  }, [navigate, onConversationCreated]);
+
   const handleUnarchive = useCallback(async (conv: Conversation) => {
     try {
       await api.unarchiveConversation(conv.id);
@@ -254,6 +264,7 @@ export function Sidebar({
           onUnarchive={handleUnarchive}
           onDelete={handleSetDeleteTarget}
           onRename={handleSetRenameTarget}
+          onDuplicate={handleDuplicate}
           onArchiveChain={handleArchiveChain}
           onUnarchiveChain={handleUnarchiveChain}
           onDeleteChain={requestDeleteChain}
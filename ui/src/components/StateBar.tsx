@@ -4,6 +4,7 @@ import type { Conversation, ConversationState, ModelInfo } from '../api';
 import type { ConnectionState } from '../hooks';
 import { getStateDescription } from '../utils';
 import { ContextIndicator } from './ContextIndicator';
+import { NotifyToggle } from './NotifyToggle';
 
 const CheckIcon = () => (
   <svg width="12" height="12" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="3" strokeLinecap="round" strokeLinejoin="round" aria-hidden="true">
@@ -209,6 +210,22 @@ export function StateBar({
             dotClass += ' approval';
             stateText = 'awaiting response';
             break;
+          case 'awaiting_user_guidance':
+            dotClass += ' approval';
+            stateText = 'paused';
+            break;
+          case 'awaiting_user_input':
+            dotClass += ' approval';
+            stateText = 'waiting for your answer';
+            break;
+          case 'awaiting_council_choice':
+            dotClass += ' approval';
+            stateText = 'choose an answer';
+            break;
+          case 'awaiting_patch_review':
+            dotClass += ' approval';
+            stateText = 'awaiting patch review';
+            break;
           case 'error':
             dotClass += ' error';
             stateText = 'error';
@@ -486,6 +503,9 @@ export function StateBar({
               onTriggerContinuation={indicatorTrigger}
             />
           )}
+          {conversation && (
+            <NotifyToggle conversationId={conversation.id} />
+          )}
         </div>
         {isMobile && (
           <button
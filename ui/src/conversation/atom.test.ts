@@ -424,6 +424,33 @@ describe('conversationReducer', () => {
     });
   });
 
+  describe('message_hydrated', () => {
+    it('inserts a fetched message in sequence order without moving lastSequenceId', () => {
+      const atom: ConversationAtom = {
+        ...createInitialAtom(),
+        messages: [makeMessage(1), makeMessage(3)],
+        lastSequenceId: 9,
+      };
+
+      const next = dispatch(atom, { type: 'message_hydrated', message: makeMessage(2) });
+
+      expect(next.messages.map((m) => m.sequence_id)).toEqual([1, 2, 3]);
+      expect(next.lastSequenceId).toBe(9);
+    });
+
+    it('replaces a message already present', () => {
+      const atom: ConversationAtom = {
+        ...createInitialAtom(),
+        messages: [makeMessage(1)],
+      };
+      const full = { ...makeMessage(1), content: { text: 'full body' } as Message['content'] };
+
+      const next = dispatch(atom, { type: 'message_hydrated', message: full });
+
+      expect(next.messages).toEqual([full]);
+    });
+  });
+
   describe('sse_message_updated', () => {
     // Regression: spawn_agents tool_result gets display_data refreshed AFTER many
     // later SSE events. Now gated by sequenceId: the update must carry an id
@@ -1166,6 +1193,46 @@ describe('conversationReducer', () => {
       expect(next.systemPrompt).toBe('You are helpful.');
     });
   });
+
+  describe('sse_presence', () => {
+    it('replaces the snapshot on join/leave', () => {
+      const atom = createInitialAtom();
+
+      const next = dispatch(atom, {
+        type: 'sse_presence',
+        sequenceId: 1,
+        clients: ['tab-a', 'tab-b'],
+        composerHolder: 'tab-a',
+      });
+
+      expect(next.presence).toEqual({ clients: ['tab-a', 'tab-b'], composerHolder: 'tab-a' });
+      expect(next.lastSequenceId).toBe(1);
+    });
+
+    it('keeps the client list when only the composer changes', () => {
+      const atom: ConversationAtom = {
+        ...createInitialAtom(),
+        presence: { clients: ['tab-a', 'tab-b'], composerHolder: 'tab-a' },
+      };
+
+      const next = dispatch(atom, { type: 'sse_presence', sequenceId: 2, composerHolder: null });
+
+      expect(next.presence).toEqual({ clients: ['tab-a', 'tab-b'], composerHolder: null });
+    });
+
+    it('drops replays', () => {
+      const atom: ConversationAtom = { ...createInitialAtom(), lastSequenceId: 5 };
+
+      const next = dispatch(atom, {
+        type: 'sse_presence',
+        sequenceId: 4,
+        clients: ['tab-a'],
+        composerHolder: null,
+      });
+
+      expect(next).toBe(atom);
+    });
+  });
 });
 
 describe('breadcrumbFromPhase', () => {
@@ -37,6 +37,14 @@ export interface ConversationAtom {
    *  Updated monotonically: a stale `connection_opened` from an older
    *  generation cannot regress the value. */
   connectionEpoch: number | null;
+  /** Which tabs are streaming this conversation and which one is typing
+   *  (REQ-API-013). Replaced wholesale by each presence event. */
+  presence: PresenceState;
+}
+
+export interface PresenceState {
+  clients: string[];
+  composerHolder: string | null;
 }
 
 export interface InitPayload {
@@ -76,10 +84,22 @@ export type SSEAction =
       durationMs?: number;
       epoch?: number;
     }
+  // REQ-API-030: a message the stream sent `truncated`, fetched in full.
+  // Not a wire event, so no sequenceId; it inserts or replaces by id.
+  | { type: 'message_hydrated'; message: Message; epoch?: number }
   | { type: 'sse_state_change'; sequenceId: number; phase: ConversationState; epoch?: number }
   | { type: 'sse_agent_done'; sequenceId: number; epoch?: number }
   | { type: 'sse_token'; sequenceId: number; delta: string; epoch?: number }
   | { type: 'sse_conversation_update'; sequenceId: number; updates: Partial<Conversation>; epoch?: number }
+  // REQ-API-013: client_joined / client_left / composer_changed. `clients`
+  // is absent for composer_changed, which only moves the composer.
+  | {
+      type: 'sse_presence';
+      sequenceId: number;
+      clients?: string[];
+      composerHolder: string | null;
+      epoch?: number;
+    }
   // `sequenceId` is present when the error originated on the wire (server's
   // monotonic counter) and absent when it was synthesized client-side for a
   // schema / parse violation in useConnection.ts. Wire-originated errors are
@@ -129,6 +149,7 @@ export function createInitialAtom(): ConversationAtom {
     uiError: null,
     toolExecutingStartedAt: null,
     connectionEpoch: null,
+    presence: { clients: [], composerHolder: null },
   };
 }
 
@@ -297,11 +318,13 @@ export function conversationReducer(
       const p = action.payload;
 
       // On fresh connect (lastSequenceId=0): replace entirely.
-      // On reconnect (lastSequenceId>0): the server always returns the full
-      // message list so we get a current snapshot of any mutable state. Merge
-      // by replacing existing messages with the incoming version (handles
-      // display_data/content mutations that occurred while disconnected) and
-      // appending genuinely new messages.
+      // On reconnect (lastSequenceId>0): either the server resumed from our
+      // `last_event_id` (no messages here; the missed events follow this
+      // init), or the gap was too old and it sent the full message list as a
+      // current snapshot of any mutable state. Merge by replacing existing
+      // messages with the incoming version (handles display_data/content
+      // mutations that occurred while disconnected) and appending genuinely
+      // new messages — an empty list leaves the atom's messages untouched.
       let mergedMessages: Message[];
       if (atom.lastSequenceId > 0) {
         const incomingById = new Map(p.messages.map((m) => [m.message_id, m]));
@@ -430,6 +453,18 @@ export function conversationReducer(
       });
     }
 
+    case 'message_hydrated': {
+      const idx = atom.messages.findIndex((m) => m.message_id === action.message.message_id);
+      const newMessages = [...atom.messages];
+      if (idx >= 0) {
+        newMessages[idx] = action.message;
+      } else {
+        const at = newMessages.findIndex((m) => m.sequence_id > action.message.sequence_id);
+        newMessages.splice(at < 0 ? newMessages.length : at, 0, action.message);
+      }
+      return { ...atom, messages: newMessages };
+    }
+
     case 'sse_state_change': {
       return applyIfNewer(atom, 'sse_state_change', action.sequenceId, (a) => {
         const newCrumb = breadcrumbFromPhase(action.phase, action.sequenceId);
@@ -500,6 +535,15 @@ export function conversationReducer(
         };
       });
 
+    case 'sse_presence':
+      return applyIfNewer(atom, 'sse_presence', action.sequenceId, (a) => ({
+        ...a,
+        presence: {
+          clients: action.clients ?? a.presence.clients,
+          composerHolder: action.composerHolder,
+        },
+      }));
+
     case 'sse_error':
       // Wire-originated errors carry a sequenceId and route through the
       // standard dedup path, so a replay of the same error after reconnect
@@ -6,4 +6,4 @@ import type { BashLiveHandleSummary } from "./BashLiveHandleSummary";
  * the `error` discriminator + an `error_message`; structured fields
  * vary by error id.
  */
-export type BashErrorResponse = { "error": "handle_not_found", error_message: string, handle_id: string, hint: string, } | { "error": "handle_cap_reached", error_message: string, cap: number, live_handles: Array<BashLiveHandleSummary>, hint: string, } | { "error": "wait_seconds_out_of_range", error_message: string, provided: number, max_wait_seconds: number, } | { "error": "peek_args_mutually_exclusive", error_message: string, } | { "error": "command_safety_rejected", error_message: string, reason: string, } | { "error": "spawn_failed", error_message: string, } | { "error": "mutually_exclusive_modes", error_message: string, conflicting_args: Array<string>, recommended_action: string, };
+export type BashErrorResponse = { "error": "handle_not_found", error_message: string, handle_id: string, hint: string, } | { "error": "handle_cap_reached", error_message: string, cap: number, live_handles: Array<BashLiveHandleSummary>, hint: string, } | { "error": "wait_seconds_out_of_range", error_message: string, provided: number, max_wait_seconds: number, } | { "error": "peek_args_mutually_exclusive", error_message: string, } | { "error": "command_safety_rejected", error_message: string, reason: string, } | { "error": "command_policy_denied", error_message: string, command: string, scope: string | null, rule: string | null, } | { "error": "uncommitted_changes", error_message: string, command: string, files: Array<string>, hint: string, } | { "error": "spawn_failed", error_message: string, } | { "error": "mutually_exclusive_modes", error_message: string, conflicting_args: Array<string>, recommended_action: string, };
@@ -15,4 +15,8 @@ import type { UsageData } from "./UsageData";
  * `content` and `display_data` stay as `serde_json::Value` — see the module
  * docs for the rationale.
  */
-export type EnrichedMessage = { message_id: string, conversation_id: string, sequence_id: number, message_type: MessageType, content: unknown, display_data: unknown | null, usage_data: UsageData | null, created_at: string, };
+export type EnrichedMessage = { message_id: string, conversation_id: string, sequence_id: number, message_type: MessageType, content: unknown, display_data: unknown | null, usage_data: UsageData | null, 
+/**
+ * Model that wrote an agent message (REQ-LLM-020)
+ */
+model?: string, created_at: string, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What went wrong, from the user's point of view.
+ */
+export type ErrorCategory = "auth" | "rate_limit" | "network" | "provider_outage" | "context_too_long" | "content_filter" | "tool_failure" | "invalid_request" | "cancelled";
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrorCategory } from "./ErrorCategory";
+import type { RemediationAction } from "./RemediationAction";
+
+/**
+ * Structured recovery suggestion broadcast when a conversation enters
+ * `Error`. `actions` is ordered by preference.
+ */
+export type Remediation = { category: ErrorCategory, title: string, suggestion: string, actions: Array<RemediationAction>, };
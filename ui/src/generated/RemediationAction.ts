@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A next step the UI can offer as a button.
+ */
+export type RemediationAction = "retry" | "compact" | "check_credentials" | "edit_message" | "new_conversation";
@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Remediation } from "./Remediation";
 import type { SseBreadcrumb } from "./SseBreadcrumb";
 
 /**
@@ -30,7 +31,12 @@ conversation: unknown,
  * valibot schema validates each element against `MessageSchema`
  * and transforms to `Message` at that boundary.
  */
-messages: Array<unknown>, agent_working: boolean, display_state: string, last_sequence_id: number, context_window_size: number, breadcrumbs: Array<SseBreadcrumb>, commits_behind: number, commits_ahead: number, project_name: string | null, } | { "type": "message", sequence_id: number, 
+messages: Array<unknown>, agent_working: boolean, display_state: string, last_sequence_id: number, context_window_size: number, breadcrumbs: Array<SseBreadcrumb>, commits_behind: number, commits_ahead: number, project_name: string | null, 
+/**
+ * Unsent composer text and images (REQ-BED-047); `null` when there
+ * is none. Validated by `DraftSchema` on the UI side.
+ */
+draft: unknown | null, } | { "type": "message", sequence_id: number, 
 /**
  * See the note on `Init.messages` — the message payload is
  * validated against `MessageSchema` and transformed to the UI's
@@ -48,4 +54,4 @@ duration_ms?: number, } | { "type": "state_change", sequence_id: number, state:
  * `message` field. Kind-aware consumers can narrow against
  * `UserFacingError` (also exported by ts-rs for future use).
  */
-error: unknown, } | { "type": "conversation_hard_deleted", sequence_id: number, conversation_id: string, };
+error: unknown, } | { "type": "error_remediation", sequence_id: number, remediation: Remediation, } | { "type": "context_warning", sequence_id: number, used: number, limit: number, percent: number, } | { "type": "conversation_continued", sequence_id: number, conversation_id: string, slug: string | null, } | { "type": "conversation_hard_deleted", sequence_id: number, conversation_id: string, } | { "type": "client_joined", sequence_id: number, client_id: string, clients: Array<string>, composer_holder: string | null, } | { "type": "client_left", sequence_id: number, client_id: string, clients: Array<string>, composer_holder: string | null, } | { "type": "composer_changed", sequence_id: number, composer_holder: string | null, } | { "type": "files_changed", sequence_id: number, paths: Array<string>, };
@@ -3,4 +3,9 @@
 /**
  * Usage statistics
  */
-export type UsageData = { input_tokens: number, output_tokens: number, cache_creation_tokens: number, cache_read_tokens: number, };
+export type UsageData = { input_tokens: number, output_tokens: number, cache_creation_tokens: number, cache_read_tokens: number, 
+/**
+ * Model that produced the response. Differs from the conversation's
+ * model when a fallback answered (REQ-LLM-013).
+ */
+model?: string, };
@@ -17,6 +17,9 @@ export type { SseBreadcrumb } from './SseBreadcrumb';
 export type { UsageData } from './UsageData';
 export type { UserFacingError } from './UserFacingError';
 export type { UserFacingErrorKind } from './UserFacingErrorKind';
+export type { Remediation } from './Remediation';
+export type { ErrorCategory } from './ErrorCategory';
+export type { RemediationAction } from './RemediationAction';
 
 // Bash and tmux tool response wire types (task 02697).
 export type { BashResponse } from './BashResponse';
@@ -64,10 +67,32 @@ export type SseConversationUpdateData = Omit<
   'type'
 >;
 export type SseErrorData = Omit<Extract<SseWireEvent, { type: 'error' }>, 'type'>;
+export type SseErrorRemediationData = Omit<
+  Extract<SseWireEvent, { type: 'error_remediation' }>,
+  'type'
+>;
+export type SseContextWarningData = Omit<
+  Extract<SseWireEvent, { type: 'context_warning' }>,
+  'type'
+>;
+export type SseConversationContinuedData = Omit<
+  Extract<SseWireEvent, { type: 'conversation_continued' }>,
+  'type'
+>;
 export type SseConversationHardDeletedData = Omit<
   Extract<SseWireEvent, { type: 'conversation_hard_deleted' }>,
   'type'
 >;
+export type SseClientJoinedData = Omit<Extract<SseWireEvent, { type: 'client_joined' }>, 'type'>;
+export type SseClientLeftData = Omit<Extract<SseWireEvent, { type: 'client_left' }>, 'type'>;
+export type SseComposerChangedData = Omit<
+  Extract<SseWireEvent, { type: 'composer_changed' }>,
+  'type'
+>;
+export type SseFilesChangedData = Omit<
+  Extract<SseWireEvent, { type: 'files_changed' }>,
+  'type'
+>;
 
 // Chain Q&A wire-event data shapes (Phoenix Chains v1). Same Extract +
 // Omit<…, 'type'> pattern as the conversation-scoped SSE events above.
@@ -0,0 +1,53 @@
+import { useEffect, useRef } from 'react';
+import { api } from '../api';
+
+/** Renewal interval while the draft keeps changing — well inside the
+ *  server's 15s `COMPOSER_LEASE` so an active typist never lapses. */
+const RENEW_MS = 5000;
+
+/**
+ * Hold the conversation's composer lease while this tab has a non-empty
+ * draft (REQ-API-013). Other tabs see `composer_changed` and hold their send
+ * button until the draft is sent or cleared.
+ *
+ * Claims are throttled to one per `RENEW_MS`; the lease is released when the
+ * draft empties, the conversation changes, or the component unmounts.
+ * Request failures are swallowed: the lease is an advisory indicator for
+ * other tabs, and the server enforces it independently on send.
+ */
+export function useComposerLease(
+  conversationId: string | undefined,
+  draft: string,
+  enabled: boolean,
+): void {
+  const lastClaimRef = useRef(0);
+  const holdingRef = useRef(false);
+  const typing = enabled && draft.trim() !== '';
+
+  useEffect(() => {
+    if (!conversationId) return;
+    if (typing) {
+      const now = Date.now();
+      if (now - lastClaimRef.current < RENEW_MS) return;
+      lastClaimRef.current = now;
+      holdingRef.current = true;
+      api.updateComposer(conversationId, true).catch(() => {
+        holdingRef.current = false;
+      });
+    } else if (holdingRef.current) {
+      holdingRef.current = false;
+      lastClaimRef.current = 0;
+      api.updateComposer(conversationId, false).catch(() => {});
+    }
+  }, [conversationId, typing, draft]);
+
+  useEffect(() => {
+    if (!conversationId) return;
+    return () => {
+      if (!holdingRef.current) return;
+      holdingRef.current = false;
+      lastClaimRef.current = 0;
+      api.updateComposer(conversationId, false).catch(() => {});
+    };
+  }, [conversationId]);
+}
@@ -1,6 +1,7 @@
 import { useState, useCallback, useEffect, useRef, type Dispatch } from 'react';
 import * as v from 'valibot';
-import type { SseInitData, SseBreadcrumb } from '../api';
+import { api } from '../api';
+import type { Message, SseInitData, SseBreadcrumb } from '../api';
 import type { SSEAction, InitPayload } from '../conversation/atom';
 import type { Breadcrumb } from '../types';
 import { parseConversationState } from '../utils';
@@ -15,7 +16,14 @@ import {
   SseConversationBecameTerminalDataSchema,
   SseErrorDataSchema,
   SseConversationHardDeletedDataSchema,
+  SseContextWarningDataSchema,
+  SseConversationContinuedDataSchema,
+  SseClientJoinedDataSchema,
+  SseClientLeftDataSchema,
+  SseComposerChangedDataSchema,
+  SseFilesChangedDataSchema,
 } from '../sseSchemas';
+import { getClientId } from '../utils/clientId';
 import {
   ConnectionState,
   ConnectionMachineState,
@@ -137,6 +145,12 @@ interface UseConnectionOptions {
   conversationId: string | undefined;
   /** Dispatch SSE events directly to the conversation atom. */
   dispatch: Dispatch<SSEAction>;
+  /**
+   * Highest sequence id the atom has applied. Sent as `last_event_id` when
+   * reopening the stream so the server can replay only what was missed
+   * (REQ-API-005). Omit, or return 0, to always get a full init.
+   */
+  getLastSequenceId?: () => number;
 }
 
 function transformBreadcrumb(b: SseBreadcrumb): Breadcrumb {
@@ -185,15 +199,17 @@ function transformInitData(raw: SseInitData): InitPayload {
  * Hook for managing SSE connection lifecycle with reconnection handling.
  *
  * Socket lifecycle manager only. Receives `dispatch` from the conversation
- * atom and calls it with SSEActions. The server always returns the full
- * message list on /stream init — update-in-place mutations arrive via the
- * typed `message_updated` SSE event — so this hook carries no sequence-id
- * state of its own. Reducer-side dedup by `lastSequenceId >= event.sequenceId`
- * still applies inside the atom.
+ * atom and calls it with SSEActions. On reconnect it passes the atom's
+ * `lastSequenceId` as `last_event_id`; the server then sends a message-less
+ * init followed by the missed events, or the full message list when the gap
+ * is too old to replay. Either way the hook carries no sequence-id state of
+ * its own. Reducer-side dedup by `lastSequenceId >= event.sequenceId` still
+ * applies inside the atom.
  */
 export function useConnection({
   conversationId,
   dispatch,
+  getLastSequenceId,
 }: UseConnectionOptions): ConnectionInfo {
   const [machineState, setMachineState] = useState<ConnectionMachineState>(initialState);
   const [countdownSeconds, setCountdownSeconds] = useState<number | null>(null);
@@ -204,6 +220,8 @@ export function useConnection({
   const countdownIntervalRef = useRef<number | null>(null);
   const reconnectedTimeoutRef = useRef<number | null>(null);
   const dispatchRef = useRef(dispatch);
+  const getLastSequenceIdRef = useRef(getLastSequenceId);
+  getLastSequenceIdRef.current = getLastSequenceId;
   const conversationIdRef = useRef(conversationId);
   // Task 08683: read-side mirror of machineState so dispatchMachine can
   // compute the next state synchronously without a functional updater.
@@ -274,26 +292,59 @@ export function useConnection({
           // contamination scenario this task closes.
           dispatchRef.current({ type: 'connection_opened', epoch });
 
-          const url = `/api/conversations/${convId}/stream`;
+          // `client_id` registers this tab for presence (REQ-API-013);
+          // `last_event_id` asks for a resume instead of a full init
+          // (REQ-API-005).
+          const lastSeq = getLastSequenceIdRef.current?.() ?? 0;
+          const resume = lastSeq > 0 ? `&last_event_id=${lastSeq}` : '';
+          const url = `/api/conversations/${convId}/stream?client_id=${getClientId()}${resume}`;
           const es = new EventSource(url);
           eventSourceRef.current = es;
 
+          // REQ-API-030: messages over the server's per-event budget arrive
+          // without their bodies. They stay out of the atom until fetched in
+          // full, so nothing renders a message with no content.
+          const hydrate = (messageId: string) => {
+            api
+              .getMessage(convId, messageId)
+              .then((message) => stampedDispatch({ type: 'message_hydrated', message }))
+              .catch((err) => console.warn('[sse] failed to fetch truncated message', err));
+          };
+          const isTruncated = (m: Message) => m.truncated === true;
+
           es.addEventListener('init', (e) => {
             const res = parseEvent(SseInitDataSchema, e, 'init', stampedDispatch);
             if (!res.ok) return;
 
             dispatchMachineRef.current({ type: 'SSE_OPEN' });
+            const payload = transformInitData(res.data);
+            const truncated = payload.messages.filter(isTruncated);
             stampedDispatch({
               type: 'sse_init',
-              payload: transformInitData(res.data),
+              payload: truncated.length > 0
+                ? { ...payload, messages: payload.messages.filter((m) => !isTruncated(m)) }
+                : payload,
             });
+            truncated.forEach((m) => hydrate(m.message_id));
             stampedDispatch({ type: 'connection_state', state: 'live' });
+            // REQ-BED-047: the composer adopts a draft saved on another device
+            if (res.data.draft) {
+              window.dispatchEvent(
+                new CustomEvent('phoenix:server-draft', {
+                  detail: { conversationId: convId, draft: res.data.draft },
+                }),
+              );
+            }
           });
 
           es.addEventListener('message', (e) => {
             const res = parseEvent(SseMessageDataSchema, e, 'message', stampedDispatch);
             if (!res.ok) return;
             const msg = res.data.message;
+            if (isTruncated(msg)) {
+              hydrate(msg.message_id);
+              return;
+            }
             stampedDispatch({
               type: 'sse_message',
               message: msg,
@@ -310,6 +361,7 @@ export function useConnection({
             );
             if (!res.ok) return;
             const data = res.data;
+            if (data.truncated === true) hydrate(data.message_id);
             stampedDispatch({
               type: 'sse_message_updated',
               sequenceId: data.sequence_id,
@@ -410,6 +462,95 @@ export function useConnection({
             );
           });
 
+          // REQ-BED-043: not conversation state, just a nudge for the page
+          // to surface; forwarded like the hard-delete notice.
+          es.addEventListener('context_warning', (e) => {
+            const res = parseEvent(
+              SseContextWarningDataSchema,
+              e,
+              'context_warning',
+              stampedDispatch,
+            );
+            if (!res.ok) return;
+            window.dispatchEvent(
+              new CustomEvent('phoenix:context-warning', {
+                detail: { conversationId: convId, percent: res.data.percent },
+              }),
+            );
+          });
+
+          // REQ-BED-044: the page navigates to the automatic continuation.
+          es.addEventListener('conversation_continued', (e) => {
+            const res = parseEvent(
+              SseConversationContinuedDataSchema,
+              e,
+              'conversation_continued',
+              stampedDispatch,
+            );
+            if (!res.ok) return;
+            window.dispatchEvent(
+              new CustomEvent('phoenix:conversation-continued', {
+                detail: { conversationId: convId, slug: res.data.slug },
+              }),
+            );
+          });
+
+          // REQ-BED-059: files changed outside the conversation. The agent
+          // was told server-side; views showing the tree just reload.
+          es.addEventListener('files_changed', (e) => {
+            const res = parseEvent(
+              SseFilesChangedDataSchema,
+              e,
+              'files_changed',
+              stampedDispatch,
+            );
+            if (!res.ok) return;
+            window.dispatchEvent(
+              new CustomEvent('phoenix:files-changed', {
+                detail: { conversationId: convId, paths: res.data.paths },
+              }),
+            );
+          });
+
+          // REQ-API-013: presence. Each event carries the full snapshot, so
+          // all three collapse into one reducer action.
+          es.addEventListener('client_joined', (e) => {
+            const res = parseEvent(SseClientJoinedDataSchema, e, 'client_joined', stampedDispatch);
+            if (!res.ok) return;
+            stampedDispatch({
+              type: 'sse_presence',
+              sequenceId: res.data.sequence_id,
+              clients: res.data.clients,
+              composerHolder: res.data.composer_holder,
+            });
+          });
+
+          es.addEventListener('client_left', (e) => {
+            const res = parseEvent(SseClientLeftDataSchema, e, 'client_left', stampedDispatch);
+            if (!res.ok) return;
+            stampedDispatch({
+              type: 'sse_presence',
+              sequenceId: res.data.sequence_id,
+              clients: res.data.clients,
+              composerHolder: res.data.composer_holder,
+            });
+          });
+
+          es.addEventListener('composer_changed', (e) => {
+            const res = parseEvent(
+              SseComposerChangedDataSchema,
+              e,
+              'composer_changed',
+              stampedDispatch,
+            );
+            if (!res.ok) return;
+            stampedDispatch({
+              type: 'sse_presence',
+              sequenceId: res.data.sequence_id,
+              composerHolder: res.data.composer_holder,
+            });
+          });
+
           es.addEventListener('error', (e) => {
             // Backend application errors arrive as SSE event type "error" WITH data.
             // Native EventSource connection errors fire with NO data — those are
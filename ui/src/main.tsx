@@ -9,5 +9,5 @@ ReactDOM.createRoot(document.getElementById('root')!).render(
   </React.StrictMode>
 );
 
-// Register service worker for offline functionality
+// Register the service worker that shows push notifications (REQ-API-024)
 serviceWorkerRegistration.register();
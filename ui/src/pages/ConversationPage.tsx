@@ -1,9 +1,10 @@
 import { lazy, Suspense, useState, useEffect, useRef, useCallback, useMemo, type MouseEvent as ReactMouseEvent } from 'react';
 import { useParams, useNavigate } from 'react-router-dom';
-import { api, ExpansionError, type Conversation, type ImageData } from '../api';
+import { api, ConflictError, ExpansionError, type Conversation, type ImageData } from '../api';
 import { refreshModels } from '../modelsPoller';
 import { isAgentWorking, isCancellingState, parseConversationState } from '../utils';
 import { copyToClipboard } from '../utils/clipboard';
+import { getClientId } from '../utils/clientId';
 import { cacheDB } from '../cache';
 import { MessageList } from '../components/MessageList';
 import { InputArea } from '../components/InputArea';
@@ -12,6 +13,9 @@ import { MessageListSkeleton } from '../components/Skeleton';
 import { FileBrowserOverlay, useFileExplorer } from '../components/FileExplorer';
 import { PaneDivider } from '../components/PaneDivider';
 import { QuestionPanel } from '../components/QuestionPanel';
+import { PatchReviewPanel } from '../components/PatchReviewPanel';
+import { AskUserPanel } from '../components/AskUserPanel';
+import { CouncilPanel } from '../components/CouncilPanel';
 import {
   useMessageQueue,
   useConnection,
@@ -28,6 +32,7 @@ import { BreadcrumbBar } from '../components/BreadcrumbBar';
 import { ErrorBanner } from '../components/ErrorBanner';
 import { WorkActions } from '../components/WorkActions';
 import { useConversationAtom } from '../conversation';
+import type { ServerDraft } from '../sseSchemas';
 import { useResizablePane } from '../hooks';
 
 // Conditional overlays / heavy panels — code-split so the default render path
@@ -173,11 +178,66 @@ function ConversationPageContent() {
   const { isOnline, queueOperation } = useAppMachine();
 
   // Toast for question panel feedback
-  const { toasts, dismissToast, showInfo } = useToast();
+  const { toasts, dismissToast, showInfo, showWarning } = useToast();
+
+  // REQ-BED-043: the server warns as context usage crosses each threshold
+  useEffect(() => {
+    const handler = (e: Event) => {
+      const detail = (e as CustomEvent<{ conversationId?: string; percent?: number }>).detail;
+      if (!detail || detail.conversationId !== conversationId) return;
+      showWarning(
+        `Context window ${detail.percent}% full. Consider continuing in a new conversation.`,
+      );
+    };
+    window.addEventListener('phoenix:context-warning', handler);
+    return () => {
+      window.removeEventListener('phoenix:context-warning', handler);
+    };
+  }, [conversationId, showWarning]);
+
+  // REQ-BED-044: follow an automatic continuation once the server creates it
+  useEffect(() => {
+    const handler = (e: Event) => {
+      const detail = (e as CustomEvent<{ conversationId?: string; slug?: string | null }>)
+        .detail;
+      if (!detail || detail.conversationId !== conversationId || !detail.slug) return;
+      showInfo('Context window full. Continued in a new conversation.');
+      navigate(`/c/${detail.slug}`);
+    };
+    window.addEventListener('phoenix:conversation-continued', handler);
+    return () => {
+      window.removeEventListener('phoenix:conversation-continued', handler);
+    };
+  }, [conversationId, navigate, showInfo]);
 
   // Image attachments (not conversation state — cleared on page refresh)
   const [images, setImages] = useState<ImageData[]>([]);
 
+  // REQ-BED-047: adopt the draft the server kept (typed on another device,
+  // or before a reload) unless the composer already holds text. Only this
+  // page's connection dispatches the event, and it can arrive with the very
+  // first init, so the listener does not wait for `conversationId`. Deferred
+  // a tick so InputArea has mounted after the init render.
+  useEffect(() => {
+    let handle: number | undefined;
+    const handler = (e: Event) => {
+      const detail = (e as CustomEvent<{ draft?: ServerDraft }>).detail;
+      const draft = detail?.draft;
+      if (!draft) return;
+      handle = window.setTimeout(() => {
+        const input = inputRef.current;
+        if (!input || input.getDraft().trim()) return;
+        input.setDraft(draft.text);
+        setImages((current) => (current.length > 0 ? current : draft.images));
+      }, 0);
+    };
+    window.addEventListener('phoenix:server-draft', handler);
+    return () => {
+      window.removeEventListener('phoenix:server-draft', handler);
+      window.clearTimeout(handle);
+    };
+  }, []);
+
   // Shared models/credential poller — one request loop app-wide.
   const { models: availableModels, credentialStatus } = useModels();
 
@@ -262,9 +322,14 @@ function ConversationPageContent() {
     [queuedMessages],
   );
 
+  // Ref to read atom state inside effects without adding it to deps
+  const atomRef = useRef(atom);
+  atomRef.current = atom;
+
   const connectionInfo = useConnection({
     conversationId: conversationIdForSSE,
     dispatch,
+    getLastSequenceId: () => atomRef.current.lastSequenceId,
   });
 
   const isOffline =
@@ -272,10 +337,6 @@ function ConversationPageContent() {
   const isConnected =
     connectionInfo.state === 'connected' || connectionInfo.state === 'reconnected';
 
-  // Ref to read atom state inside effects without adding it to deps
-  const atomRef = useRef(atom);
-  atomRef.current = atom;
-
   // Load conversation by slug — skip if atom already has data from a previous visit
   useEffect(() => {
     if (!slug) {
@@ -523,13 +584,20 @@ function ConversationPageContent() {
           // Re-throw so InputArea can display inline error (REQ-IR-007)
           throw err;
         }
+        if (err instanceof ConflictError && err.detail.error_type === 'duplicate_submission') {
+          // Another tab already sent this exact text (REQ-API-013); its
+          // copy arrives over SSE, so drop ours rather than flag a failure.
+          dismissRef.current(localId);
+          showInfo('Already sent from another window');
+          return;
+        }
         console.error('Failed to send message:', err);
         markFailedRef.current(localId);
       } finally {
         sendingMessagesRef.current.delete(localId);
       }
     },
-    [conversationId, isOnline, queueOperation, dispatch]
+    [conversationId, isOnline, queueOperation, dispatch, showInfo]
   );
 
   const sendMessageRef = useRef(sendMessage);
@@ -1075,6 +1143,10 @@ function ConversationPageContent() {
           onCancel={handleCancel}
           onRetry={handleRetry}
           onDismissError={dismiss}
+          composerHeldElsewhere={
+            atom.presence.composerHolder !== null
+            && atom.presence.composerHolder !== getClientId()
+          }
         />
         </>
       ) : convStateForChildren.type === 'error' ? (
@@ -1090,6 +1162,29 @@ function ConversationPageContent() {
           showToast={showInfo}
           onSubmitted={() => dispatch({ type: 'local_phase_change', phase: { type: 'llm_requesting', attempt: 1 } })}
         />
+      ) : convStateForChildren.type === 'awaiting_user_input' && convStateForChildren.tool_use_id ? (
+        <AskUserPanel
+          question={convStateForChildren.question}
+          options={convStateForChildren.options ?? []}
+          conversationId={conversation.id}
+          showToast={showInfo}
+          onSubmitted={() => dispatch({ type: 'local_phase_change', phase: { type: 'llm_requesting', attempt: 1 } })}
+        />
+      ) : convStateForChildren.type === 'awaiting_council_choice' ? (
+        <CouncilPanel
+          candidates={convStateForChildren.candidates}
+          conversationId={conversation.id}
+          showToast={showInfo}
+          onSubmitted={() => dispatch({ type: 'local_phase_change', phase: { type: 'awaiting_llm' } })}
+        />
+      ) : convStateForChildren.type === 'awaiting_patch_review' ? (
+        <PatchReviewPanel
+          patch={convStateForChildren.patch}
+          toolUseId={convStateForChildren.current_tool.id}
+          conversationId={conversation.id}
+          showToast={showInfo}
+          onSubmitted={() => dispatch({ type: 'local_phase_change', phase: { type: 'awaiting_llm' } })}
+        />
       ) : convStateForChildren.type !== 'context_exhausted' && convStateForChildren.type !== 'awaiting_task_approval' && convStateForChildren.type !== 'terminal' ? (
         <>
         {conversationId && (
@@ -0,0 +1,42 @@
+// Web Push subscription for this browser (REQ-API-024). The server pushes to
+// every stored subscription when an opted-in conversation finishes a turn;
+// the service worker shows the notification.
+
+import { api } from './api';
+
+/** Whether this browser can receive push notifications at all. */
+export function pushSupported(): boolean {
+  return 'serviceWorker' in navigator && 'PushManager' in window && 'Notification' in window;
+}
+
+/** base64url (as the server sends the VAPID key) to the bytes `subscribe` wants. */
+function decodeKey(base64url: string): Uint8Array {
+  const base64 = base64url.replace(/-/g, '+').replace(/_/g, '/');
+  const padded = base64 + '='.repeat((4 - (base64.length % 4)) % 4);
+  return Uint8Array.from(atob(padded), c => c.charCodeAt(0));
+}
+
+/**
+ * Ask for notification permission if needed and make sure the server has
+ * this browser's subscription. Resolves to false when the user declines.
+ */
+export async function ensurePushSubscription(): Promise<boolean> {
+  if (!pushSupported()) return false;
+  const permission = Notification.permission === 'default'
+    ? await Notification.requestPermission()
+    : Notification.permission;
+  if (permission !== 'granted') return false;
+
+  const registration = await navigator.serviceWorker.ready;
+  let subscription = await registration.pushManager.getSubscription();
+  if (!subscription) {
+    const key = await api.getPushKey();
+    subscription = await registration.pushManager.subscribe({
+      userVisibleOnly: true,
+      applicationServerKey: decodeKey(key),
+    });
+  }
+  // Re-sent every time: the server may have dropped it as expired.
+  await api.subscribePush(subscription.toJSON());
+  return true;
+}
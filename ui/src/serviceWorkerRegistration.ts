@@ -1,9 +1,26 @@
 // serviceWorkerRegistration.ts
-// Simplified: Only unregisters existing service workers to clean up from previous versions
+// The service worker only delivers Web Push notifications (REQ-API-024); it
+// caches nothing. Caches left by earlier offline-first versions are removed.
+
+const SERVICE_WORKER_URL = '/service-worker.js';
 
 export async function register() {
-  // We no longer use service workers - unregister any existing ones
-  await unregister();
+  if (!('serviceWorker' in navigator)) return;
+  try {
+    // Drop workers registered under another script by earlier versions
+    const registrations = await navigator.serviceWorker.getRegistrations();
+    for (const registration of registrations) {
+      const script = registration.active?.scriptURL ?? registration.installing?.scriptURL;
+      if (script && new URL(script).pathname !== SERVICE_WORKER_URL) {
+        await registration.unregister();
+        console.log('[SW] Unregistered service worker:', registration.scope);
+      }
+    }
+    await clearCaches();
+    await navigator.serviceWorker.register(SERVICE_WORKER_URL);
+  } catch (error) {
+    console.error('[SW] Registration failed:', error);
+  }
 }
 
 export async function unregister() {
@@ -14,18 +31,20 @@ export async function unregister() {
         await registration.unregister();
         console.log('[SW] Unregistered service worker:', registration.scope);
       }
-      // Also clear any SW caches
-      if ('caches' in window) {
-        const cacheNames = await caches.keys();
-        for (const cacheName of cacheNames) {
-          if (cacheName.startsWith('phoenix-')) {
-            await caches.delete(cacheName);
-            console.log('[SW] Deleted cache:', cacheName);
-          }
-        }
-      }
+      await clearCaches();
     } catch (error) {
       console.error('[SW] Unregistration failed:', error);
     }
   }
 }
+
+async function clearCaches() {
+  if (!('caches' in window)) return;
+  const cacheNames = await caches.keys();
+  for (const cacheName of cacheNames) {
+    if (cacheName.startsWith('phoenix-')) {
+      await caches.delete(cacheName);
+      console.log('[SW] Deleted cache:', cacheName);
+    }
+  }
+}
@@ -36,7 +36,14 @@ import type {
   SseConversationBecameTerminalData as WireConversationBecameTerminalData,
   SseConversationUpdateData as WireConversationUpdateData,
   SseErrorData as WireErrorData,
+  SseErrorRemediationData as WireErrorRemediationData,
+  SseContextWarningData as WireContextWarningData,
+  SseConversationContinuedData as WireConversationContinuedData,
   SseConversationHardDeletedData as WireConversationHardDeletedData,
+  SseClientJoinedData as WireClientJoinedData,
+  SseClientLeftData as WireClientLeftData,
+  SseComposerChangedData as WireComposerChangedData,
+  SseFilesChangedData as WireFilesChangedData,
   SseBreadcrumb as GeneratedSseBreadcrumb,
   ChainQaTokenData as WireChainQaTokenData,
   ChainQaCompletedData as WireChainQaCompletedData,
@@ -173,6 +180,14 @@ export type SseBreadcrumb = v.InferOutput<typeof SseBreadcrumbSchema>;
  *  `display_state` is `string` (not optional) in the Rust wire type — task
  *  02677 tightened this field from the previously-optional schema shape
  *  after the generated type surfaced the actual wire contract. */
+/** Unsent composer draft stored server-side (REQ-BED-047). */
+const DraftSchema = v.looseObject({
+  text: v.string(),
+  images: v.array(v.looseObject({ data: v.string(), media_type: v.string() })),
+  updated_at: v.string(),
+});
+export type ServerDraft = v.InferOutput<typeof DraftSchema>;
+
 export const SseInitDataSchema = v.looseObject({
   sequence_id: v.number(),
   conversation: ConversationSchema,
@@ -185,6 +200,7 @@ export const SseInitDataSchema = v.looseObject({
   commits_behind: v.number(),
   commits_ahead: v.number(),
   project_name: v.nullable(v.string()),
+  draft: v.optional(v.nullable(DraftSchema), null),
 }) satisfies v.GenericSchema<unknown, WireInitData>;
 
 /** `message`: a newly-created message joins the conversation.
@@ -269,6 +285,50 @@ export const SseErrorDataSchema = v.looseObject({
   error: v.unknown(),
 }) satisfies v.GenericSchema<unknown, WireErrorData>;
 
+/** `error_remediation`: REQ-BED-035. Sent once when the conversation enters
+ *  the error state, right after the `state_change`. `actions` is ordered by
+ *  preference and drives the recovery buttons. */
+export const SseErrorRemediationDataSchema = v.looseObject({
+  sequence_id: v.number(),
+  remediation: v.looseObject({
+    category: v.picklist([
+      'auth',
+      'rate_limit',
+      'network',
+      'provider_outage',
+      'context_too_long',
+      'content_filter',
+      'tool_failure',
+      'invalid_request',
+      'cancelled',
+    ]),
+    title: v.string(),
+    suggestion: v.string(),
+    actions: v.array(
+      v.picklist(['retry', 'compact', 'check_credentials', 'edit_message', 'new_conversation']),
+    ),
+  }),
+}) satisfies v.GenericSchema<unknown, WireErrorRemediationData>;
+
+/** `context_warning`: REQ-BED-043. The latest turn's context usage reached
+ *  a warning threshold; sent once per threshold so the UI can suggest
+ *  continuing in a new conversation before the window runs out. */
+export const SseContextWarningDataSchema = v.looseObject({
+  sequence_id: v.number(),
+  used: v.number(),
+  limit: v.number(),
+  percent: v.number(),
+}) satisfies v.GenericSchema<unknown, WireContextWarningData>;
+
+/** `conversation_continued`: REQ-BED-044. The conversation ran out of
+ *  context and the server continued it in a new, linked conversation; the
+ *  UI follows it there. */
+export const SseConversationContinuedDataSchema = v.looseObject({
+  sequence_id: v.number(),
+  conversation_id: v.string(),
+  slug: v.nullable(v.string()),
+}) satisfies v.GenericSchema<unknown, WireConversationContinuedData>;
+
 /** `conversation_hard_deleted`: REQ-BED-032 step 6. Conversation row is gone
  *  from SQLite; all per-conversation resources (bash handles, tmux server,
  *  worktree) have been cleaned up. UI subscribers refresh sidebar /
@@ -280,6 +340,37 @@ export const SseConversationHardDeletedDataSchema = v.looseObject({
   conversation_id: v.string(),
 }) satisfies v.GenericSchema<unknown, WireConversationHardDeletedData>;
 
+/** `client_joined` / `client_left`: REQ-API-013. Another tab (or this one)
+ *  opened or closed a stream on the conversation. Each carries the full
+ *  post-change presence snapshot so the reducer can replace, not merge. */
+export const SseClientJoinedDataSchema = v.looseObject({
+  sequence_id: v.number(),
+  client_id: v.string(),
+  clients: v.array(v.string()),
+  composer_holder: v.nullable(v.string()),
+}) satisfies v.GenericSchema<unknown, WireClientJoinedData>;
+
+export const SseClientLeftDataSchema = v.looseObject({
+  sequence_id: v.number(),
+  client_id: v.string(),
+  clients: v.array(v.string()),
+  composer_holder: v.nullable(v.string()),
+}) satisfies v.GenericSchema<unknown, WireClientLeftData>;
+
+/** `composer_changed`: REQ-API-013. `composer_holder` is the tab currently
+ *  typing; `null` means any tab may send. */
+export const SseComposerChangedDataSchema = v.looseObject({
+  sequence_id: v.number(),
+  composer_holder: v.nullable(v.string()),
+}) satisfies v.GenericSchema<unknown, WireComposerChangedData>;
+
+/** `files_changed`: REQ-BED-059. Paths in the cwd that changed outside the
+ *  conversation, relative to the cwd. */
+export const SseFilesChangedDataSchema = v.looseObject({
+  sequence_id: v.number(),
+  paths: v.array(v.string()),
+}) satisfies v.GenericSchema<unknown, WireFilesChangedData>;
+
 // ---------------------------------------------------------------------------
 // Chain Q&A wire-event schemas (Phoenix Chains v1, REQ-CHN-004 / 005).
 //
@@ -335,9 +426,18 @@ export type SseConversationBecameTerminalData = v.InferOutput<
   typeof SseConversationBecameTerminalDataSchema
 >;
 export type SseErrorData = v.InferOutput<typeof SseErrorDataSchema>;
+export type SseErrorRemediationData = v.InferOutput<typeof SseErrorRemediationDataSchema>;
+export type SseContextWarningData = v.InferOutput<typeof SseContextWarningDataSchema>;
+export type SseConversationContinuedData = v.InferOutput<
+  typeof SseConversationContinuedDataSchema
+>;
 export type SseConversationHardDeletedData = v.InferOutput<
   typeof SseConversationHardDeletedDataSchema
 >;
+export type SseClientJoinedData = v.InferOutput<typeof SseClientJoinedDataSchema>;
+export type SseClientLeftData = v.InferOutput<typeof SseClientLeftDataSchema>;
+export type SseComposerChangedData = v.InferOutput<typeof SseComposerChangedDataSchema>;
+export type SseFilesChangedData = v.InferOutput<typeof SseFilesChangedDataSchema>;
 
 // ---------------------------------------------------------------------------
 // Bash and tmux tool response schemas (task 02697).
@@ -491,6 +591,20 @@ export const BashErrorResponseSchema = v.variant('error', [
     error_message: v.string(),
     reason: v.string(),
   }),
+  v.looseObject({
+    error: v.literal('command_policy_denied'),
+    error_message: v.string(),
+    command: v.string(),
+    scope: v.nullable(v.string()),
+    rule: v.nullable(v.string()),
+  }),
+  v.looseObject({
+    error: v.literal('uncommitted_changes'),
+    error_message: v.string(),
+    command: v.string(),
+    files: v.array(v.string()),
+    hint: v.string(),
+  }),
   v.looseObject({
     error: v.literal('spawn_failed'),
     error_message: v.string(),
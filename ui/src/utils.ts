@@ -1,6 +1,9 @@
 // Utility functions
 
-import type { ConversationState, ToolCall, PendingSubAgent, SubAgentResult, UserQuestion } from './api';
+import type {
+  ConversationState, ToolCall, PendingSubAgent, SubAgentResult, UserQuestion, StagedPatch,
+  CouncilCandidate,
+} from './api';
 
 /** Format a keyboard shortcut for the current platform (Cmd on macOS, Ctrl elsewhere) */
 export function formatShortcut(shortcut: string): string {
@@ -53,7 +56,8 @@ export function formatShortDateTime(isoStr: string): string {
 export function isAgentWorking(state: ConversationState): boolean {
   switch (state.type) {
     case 'idle': case 'error': case 'terminal': case 'context_exhausted':
-    case 'awaiting_task_approval': case 'awaiting_user_response':
+    case 'awaiting_task_approval': case 'awaiting_user_response': case 'awaiting_user_guidance':
+    case 'awaiting_user_input': case 'awaiting_council_choice': case 'awaiting_patch_review':
       return false;
     case 'awaiting_llm': case 'llm_requesting': case 'tool_executing':
     case 'awaiting_sub_agents': case 'awaiting_continuation':
@@ -69,7 +73,8 @@ export function isCancellingState(state: ConversationState): boolean {
     case 'cancelling': case 'cancelling_tool': case 'cancelling_sub_agents':
       return true;
     case 'idle': case 'error': case 'terminal': case 'context_exhausted':
-    case 'awaiting_task_approval': case 'awaiting_user_response':
+    case 'awaiting_task_approval': case 'awaiting_user_response': case 'awaiting_user_guidance':
+    case 'awaiting_user_input': case 'awaiting_council_choice': case 'awaiting_patch_review':
     case 'awaiting_llm': case 'llm_requesting': case 'tool_executing':
     case 'awaiting_sub_agents': case 'awaiting_continuation':
     case 'awaiting_recovery':
@@ -109,6 +114,14 @@ export function getStateDescription(state: ConversationState): string {
       return 'awaiting approval';
     case 'awaiting_user_response':
       return 'awaiting response';
+    case 'awaiting_user_guidance':
+      return 'paused';
+    case 'awaiting_user_input':
+      return 'waiting for your answer';
+    case 'awaiting_council_choice':
+      return 'choose an answer';
+    case 'awaiting_patch_review':
+      return 'awaiting patch review';
     case 'error':
       return 'error';
     case 'awaiting_recovery':
@@ -162,6 +175,30 @@ export function parseConversationState(raw: unknown): ConversationState {
         type: 'awaiting_user_response',
         questions: (obj['questions'] as UserQuestion[]) ?? [],
       };
+    case 'awaiting_user_guidance':
+      return { type: 'awaiting_user_guidance', reason: (obj['reason'] as string) ?? '' };
+    case 'awaiting_user_input': {
+      // SSE carries the call id flat; the stored state nests it under `ask`
+      const ask = obj['ask'] as { tool_use_id?: string } | undefined;
+      const toolUseId = (obj['tool_use_id'] as string | undefined) ?? ask?.tool_use_id;
+      return {
+        type: 'awaiting_user_input',
+        question: (obj['question'] as string) ?? '',
+        options: (obj['options'] as string[]) ?? [],
+        ...(toolUseId ? { tool_use_id: toolUseId } : {}),
+      };
+    }
+    case 'awaiting_council_choice':
+      return {
+        type: 'awaiting_council_choice',
+        candidates: (obj['candidates'] as CouncilCandidate[]) ?? [],
+      };
+    case 'awaiting_patch_review':
+      return {
+        type: 'awaiting_patch_review',
+        patch: (obj['patch'] as StagedPatch) ?? { path: '', diff: '' },
+        current_tool: obj['current_tool'] as ToolCall,
+      };
     case 'context_exhausted':
       return { type: 'context_exhausted', summary: (obj['summary'] as string) ?? '' };
     case 'error':
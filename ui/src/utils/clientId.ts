@@ -0,0 +1,15 @@
+import { generateUUID } from './uuid';
+
+/**
+ * Per-tab presence id (REQ-API-013).
+ *
+ * Minted once per page load rather than persisted: browsers copy
+ * `sessionStorage` into a duplicated tab, so a stored id would make two
+ * tabs look like one client and defeat the composer lease. A reload gets a
+ * fresh id, which is fine — the old page's stream closes with it.
+ */
+const CLIENT_ID = generateUUID();
+
+export function getClientId(): string {
+  return CLIENT_ID;
+}